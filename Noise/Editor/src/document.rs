@@ -0,0 +1,75 @@
+use std::path::{Path, PathBuf};
+use noise_engine::graph::Graph;
+
+pub const GRAPH_DIR: &str = "assets/noise_graphs";
+pub const DEFAULT_GRAPH_PATH: &str = "assets/noise_graphs/default.ron";
+const RECENT_FILES_PATH: &str = "assets/recent_graphs.json";
+const MAX_RECENT: usize = 8;
+
+/// File actions that would discard the current graph and therefore go through the unsaved-changes prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileAction {
+    New,
+    Open,
+    OpenPath(PathBuf),
+    Exit,
+}
+
+pub fn load_graph(path: &Path) -> Result<Graph, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str::<Graph>(&text).map_err(|e| e.to_string())
+}
+
+pub fn save_graph(path: &Path, graph: &Graph) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let pretty = ron::ser::PrettyConfig::new();
+    let text = ron::ser::to_string_pretty(graph, pretty).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+fn graph_dialog(filter_name: &str) -> rfd::FileDialog {
+    let _ = std::fs::create_dir_all(GRAPH_DIR);
+    let dir = std::fs::canonicalize(GRAPH_DIR).unwrap_or_else(|_| PathBuf::from(GRAPH_DIR));
+    rfd::FileDialog::new().set_directory(dir).add_filter(filter_name, &["ron"])
+}
+
+/// Native open dialog rooted at the noise_graphs directory.
+pub fn pick_open_path(filter_name: &str) -> Option<PathBuf> {
+    graph_dialog(filter_name).pick_file()
+}
+
+/// Native save dialog rooted at the noise_graphs directory; adds `.ron` if the user left it off.
+pub fn pick_save_path(filter_name: &str, current: Option<&Path>) -> Option<PathBuf> {
+    let mut dialog = graph_dialog(filter_name);
+    if let Some(name) = current.and_then(|p| p.file_name()) {
+        dialog = dialog.set_file_name(name.to_string_lossy());
+    }
+    let mut path = dialog.save_file()?;
+    if path.extension().is_none() {
+        path.set_extension("ron");
+    }
+    Some(path)
+}
+
+pub fn load_recent_files() -> Vec<PathBuf> {
+    std::fs::read_to_string(RECENT_FILES_PATH)
+        .ok()
+        .and_then(|s| serde_json::from_str::<Vec<PathBuf>>(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Moves `path` to the front of the recent list and persists it.
+pub fn push_recent_file(recent: &mut Vec<PathBuf>, path: &Path) {
+    recent.retain(|p| p != path);
+    recent.insert(0, path.to_path_buf());
+    recent.truncate(MAX_RECENT);
+    save_recent_files(recent);
+}
+
+pub fn save_recent_files(recent: &[PathBuf]) {
+    if let Ok(text) = serde_json::to_string_pretty(recent) {
+        let _ = std::fs::write(RECENT_FILES_PATH, text);
+    }
+}
@@ -0,0 +1,234 @@
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use noise_engine::*;
+use noise_engine::graph::Graph;
+use noise_engine::sampling::SimpleEngine;
+use noise_engine::tiles::{copy_tile, tile_rects, TileJob};
+use serde::Serialize;
+use crate::EditorState;
+
+/// Side of the tiles the export is sampled in; also the granularity of the progress bar.
+const TILE_SIZE: u32 = 256;
+
+/// Export window state and the job currently running in the background, if any.
+#[derive(Resource)]
+pub struct ExportPanel {
+    pub open: bool,
+    channel: usize,
+    width: u32,
+    height: u32,
+    origin: [i32; 2],
+    write_raw: bool,
+    job: Option<ExportJob>,
+    /// Message from the last finished export; `Err` for failures.
+    last_result: Option<Result<String, String>>,
+}
+
+impl Default for ExportPanel {
+    fn default() -> Self {
+        Self { open: false, channel: 0, width: 1024, height: 1024, origin: [0, 0], write_raw: true, job: None, last_result: None }
+    }
+}
+
+struct ExportJob {
+    tiles_done: Arc<AtomicU32>,
+    tiles_total: u32,
+    handle: JoinHandle<Result<PathBuf, String>>,
+}
+
+/// Everything the worker thread needs, copied out of the editor so edits during export don't affect it.
+struct ExportRequest {
+    graph: Graph,
+    seed: u64,
+    channel: ChannelDesc,
+    origin: [i32; 2],
+    width: u32,
+    height: u32,
+    png_path: PathBuf,
+    write_raw: bool,
+}
+
+/// Sidecar written next to the raw dump so external tools know how to read it.
+#[derive(Serialize)]
+struct RawSidecar<'a> {
+    channel: &'a str,
+    format: &'a str,
+    origin: [i32; 3],
+    size: [u32; 3],
+    seed: u64,
+    min: f32,
+    max: f32,
+    /// Input range mapped to 0..65535 in the PNG.
+    png_range: [f32; 2],
+}
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExportPanel>().add_systems(Update, (draw_export_window, poll_export_job));
+    }
+}
+
+fn draw_export_window(mut egui_ctx: EguiContexts, state: Res<EditorState>, mut panel: ResMut<ExportPanel>) {
+    if !panel.open {
+        return;
+    }
+    let channels: Vec<&ChannelDesc> = state.selected_channels.iter().filter(|c| is_2d(&c.kind)).collect();
+    let mut open = true;
+    let mut start = false;
+    egui::Window::new(state.ui.get("export.title"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_ctx.ctx_mut(), |ui| {
+            let running = panel.job.is_some();
+            ui.add_enabled_ui(!running, |ui| {
+                egui::Grid::new("export_settings").num_columns(2).show(ui, |ui| {
+                    ui.label(state.ui.get("export.channel"));
+                    let selected = channels.get(panel.channel).map(|c| c.name.clone()).unwrap_or_default();
+                    egui::ComboBox::from_id_source("export_channel")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (i, c) in channels.iter().enumerate() {
+                                ui.selectable_value(&mut panel.channel, i, &c.name);
+                            }
+                        });
+                    ui.end_row();
+                    ui.label(state.ui.get("export.width"));
+                    ui.add(egui::DragValue::new(&mut panel.width).clamp_range(1..=8192));
+                    ui.end_row();
+                    ui.label(state.ui.get("export.height"));
+                    ui.add(egui::DragValue::new(&mut panel.height).clamp_range(1..=8192));
+                    ui.end_row();
+                    ui.label(state.ui.get("export.origin"));
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut panel.origin[0]));
+                        ui.add(egui::DragValue::new(&mut panel.origin[1]));
+                    });
+                    ui.end_row();
+                });
+                ui.checkbox(&mut panel.write_raw, state.ui.get("export.write_raw"));
+                if ui.add_enabled(!channels.is_empty(), egui::Button::new(state.ui.get("export.export_button"))).clicked() {
+                    start = true;
+                }
+            });
+            if let Some(job) = &panel.job {
+                let fraction = job.tiles_done.load(Ordering::Relaxed) as f32 / job.tiles_total.max(1) as f32;
+                ui.add(egui::ProgressBar::new(fraction).show_percentage().text(state.ui.get("export.progress")));
+                ui.ctx().request_repaint();
+            }
+            match &panel.last_result {
+                Some(Ok(msg)) => { ui.label(format!("{} {}", state.ui.get("export.done"), msg)); }
+                Some(Err(msg)) => { ui.colored_label(egui::Color32::LIGHT_RED, format!("{} {}", state.ui.get("export.failed"), msg)); }
+                None => {}
+            }
+        });
+    if !open {
+        panel.open = false;
+    }
+    if start {
+        let Some(channel) = channels.get(panel.channel).map(|c| (*c).clone()) else { return };
+        let _ = std::fs::create_dir_all("assets/exports");
+        let dir = std::fs::canonicalize("assets/exports").unwrap_or_else(|_| PathBuf::from("assets/exports"));
+        let picked = rfd::FileDialog::new()
+            .set_directory(dir)
+            .set_file_name(format!("{}.png", channel.name))
+            .add_filter(state.ui.get("export.filter_name"), &["png"])
+            .save_file();
+        let Some(mut png_path) = picked else { return };
+        png_path.set_extension("png");
+        let seed = state.graph.seed;
+        let request = ExportRequest {
+            graph: state.graph.clone(),
+            seed,
+            channel,
+            origin: panel.origin,
+            width: panel.width,
+            height: panel.height,
+            png_path,
+            write_raw: panel.write_raw,
+        };
+        let tiles_done = Arc::new(AtomicU32::new(0));
+        let progress = tiles_done.clone();
+        let tiles_total = tile_rects([panel.width, panel.height, 1], TILE_SIZE).len() as u32;
+        let handle = std::thread::spawn(move || run_export(request, &progress));
+        panel.job = Some(ExportJob { tiles_done, tiles_total, handle });
+        panel.last_result = None;
+    }
+}
+
+fn poll_export_job(mut panel: ResMut<ExportPanel>) {
+    if !panel.job.as_ref().is_some_and(|j| j.handle.is_finished()) {
+        return;
+    }
+    let Some(job) = panel.job.take() else { return };
+    panel.last_result = Some(match job.handle.join() {
+        Ok(Ok(path)) => Ok(path.display().to_string()),
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err("export thread panicked".into()),
+    });
+    panel.open = true;
+}
+
+fn is_2d(kind: &ChannelKind) -> bool {
+    matches!(kind, ChannelKind::Height2D | ChannelKind::Biome2D | ChannelKind::WaterLevel2D)
+}
+
+/// Samples the requested region in tiles on the engine's worker pool and writes the PNG
+/// (and optionally the raw dump + sidecar).
+fn run_export(req: ExportRequest, tiles_done: &AtomicU32) -> Result<PathBuf, String> {
+    let engine = SimpleEngine::new(req.graph);
+    let size = [req.width, req.height, 1];
+    let region = RegionRequest { origin: [req.origin[0], req.origin[1], 0], size, lod: 0 };
+    let mut job = TileJob::spawn(Arc::new(engine), region, ChannelsSpec(vec![req.channel.clone()]), TILE_SIZE);
+    let mut data = vec![0.0; req.width as usize * req.height as usize];
+    while let Some(tile) = job.recv() {
+        let res = tile.result.map_err(|e| e.to_string())?;
+        match res.channels.into_iter().next() {
+            Some(ChannelData::Scalar2D { data: part, .. }) => copy_tile(&mut data, size, tile.rect, &part),
+            _ => return Err(format!("channel {} produced no 2D data", req.channel.name)),
+        }
+        tiles_done.store(job.received() as u32, Ordering::Relaxed);
+    }
+
+    write_png16(&req.png_path, req.width, req.height, &data)?;
+    if req.write_raw {
+        let raw_path = req.png_path.with_extension("f32");
+        let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+        std::fs::write(&raw_path, bytes).map_err(|e| format!("{}: {}", raw_path.display(), e))?;
+        let (min, max) = data.iter().fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(*v), hi.max(*v)));
+        let sidecar = RawSidecar {
+            channel: &req.channel.name,
+            format: "f32le",
+            origin: [req.origin[0], req.origin[1], 0],
+            size: [req.width, req.height, 1],
+            seed: req.seed,
+            min,
+            max,
+            png_range: [-1.0, 1.0],
+        };
+        let json_path = req.png_path.with_extension("json");
+        let text = serde_json::to_string_pretty(&sidecar).map_err(|e| e.to_string())?;
+        std::fs::write(&json_path, text).map_err(|e| format!("{}: {}", json_path.display(), e))?;
+    }
+    Ok(req.png_path)
+}
+
+/// 16-bit grayscale PNG, mapping -1..1 to the full range like the on-screen preview does.
+fn write_png16(path: &Path, width: u32, height: u32, data: &[f32]) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    let bytes: Vec<u8> = data
+        .iter()
+        .flat_map(|v| (((v * 0.5 + 0.5).clamp(0.0, 1.0) * 65535.0).round() as u16).to_be_bytes())
+        .collect();
+    writer.write_image_data(&bytes).map_err(|e| e.to_string())
+}
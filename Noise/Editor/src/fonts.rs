@@ -0,0 +1,31 @@
+use bevy_egui::egui;
+
+/// Fonts with CJK glyphs, tried in order; egui's built-in fonts have none, so languages such as zh_cn
+/// would render as boxes. `assets/fonts/cjk.ttf` lets a font be shipped next to the editor.
+const CJK_FONT_PATHS: &[&str] = &[
+    "assets/fonts/cjk.ttf",
+    "C:/Windows/Fonts/msyh.ttc",
+    "C:/Windows/Fonts/simhei.ttf",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Medium.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+];
+
+/// Adds the first CJK font found as a fallback after egui's default fonts. Returns false if none is installed.
+pub fn install_cjk_font(ctx: &egui::Context) -> bool {
+    let Some(bytes) = CJK_FONT_PATHS.iter().find_map(|path| std::fs::read(path).ok()) else { return false };
+    let mut fonts = egui::FontDefinitions::default();
+    fonts.font_data.insert("cjk".to_owned(), egui::FontData::from_owned(bytes));
+    for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+        fonts.families.entry(family).or_default().push("cjk".to_owned());
+    }
+    ctx.set_fonts(fonts);
+    true
+}
+
+/// Whether the language with file code `code` (e.g. "zh_cn") needs CJK glyphs.
+pub fn needs_cjk_font(code: &str) -> bool {
+    matches!(code.split('_').next(), Some("zh" | "ja" | "ko"))
+}
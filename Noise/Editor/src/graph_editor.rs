@@ -1,35 +1,491 @@
 use bevy_egui::egui;
-use noise_engine::graph::Graph;
+use bevy_egui::egui::{Color32, Pos2, Rect, Sense, Stroke, Vec2};
+use noise_engine::ChannelDesc;
+use noise_engine::graph::{CellularReturn, Graph, NodeKind};
 use crate::ui_strings::UiStrings;
 
-pub fn graph_editor_ui(ui: &mut egui::Ui, graph: &mut Graph, strings: &UiStrings) {
-    // Toolbar
-    ui.horizontal(|ui| {
-        if ui.button(&strings.graph_panel.add_node).clicked() {
-            let id = (graph.nodes.len() as u64) + 1;
-            let name = format!("{} {}", strings.graph_panel.node_prefix, id);
-            graph.nodes.push(noise_engine::graph::Node { id, name, kind: noise_engine::graph::NodeKind::Constant(0.0) });
+const NODE_WIDTH: f32 = 150.0;
+const HEADER_HEIGHT: f32 = 24.0;
+const ROW_HEIGHT: f32 = 20.0;
+const PORT_RADIUS: f32 = 5.0;
+
+/// Canvas view state: pan/zoom, selection and an in-progress wire drag.
+pub struct GraphView {
+    pub pan: Vec2,
+    pub zoom: f32,
+    pub selected: Option<u64>,
+    /// Source node of a wire currently being dragged from an output port.
+    wire_from: Option<u64>,
+    /// Graph-space position where the add-node context menu was opened.
+    menu_pos: [f32; 2],
+    /// Canvas size from the last frame, used by zoom-to-fit from the toolbar.
+    canvas_size: Vec2,
+}
+
+impl Default for GraphView {
+    fn default() -> Self {
+        Self { pan: Vec2::new(40.0, 40.0), zoom: 1.0, selected: None, wire_from: None, menu_pos: [0.0, 0.0], canvas_size: Vec2::new(800.0, 600.0) }
+    }
+}
+
+impl GraphView {
+    fn to_screen(&self, origin: Pos2, p: [f32; 2]) -> Pos2 {
+        origin + self.pan + Vec2::new(p[0], p[1]) * self.zoom
+    }
+
+    fn to_graph(&self, origin: Pos2, p: Pos2) -> [f32; 2] {
+        let v = (p - origin - self.pan) / self.zoom;
+        [v.x, v.y]
+    }
+
+    /// Centers and scales the view so every node fits inside `size`.
+    pub fn zoom_to_fit(&mut self, graph: &Graph, size: Vec2) {
+        if graph.nodes.is_empty() { return; }
+        let mut min = Pos2::new(f32::MAX, f32::MAX);
+        let mut max = Pos2::new(f32::MIN, f32::MIN);
+        for n in &graph.nodes {
+            min = min.min(Pos2::new(n.pos[0], n.pos[1]));
+            max = max.max(Pos2::new(n.pos[0] + NODE_WIDTH, n.pos[1] + node_height(&n.kind)));
         }
-        if ui.button(&strings.graph_panel.clear).clicked() {
-            graph.nodes.clear();
-            graph.edges.clear();
+        let bounds = Rect::from_min_max(min, max).expand(40.0);
+        self.zoom = (size.x / bounds.width()).min(size.y / bounds.height()).clamp(0.2, 2.0);
+        self.pan = size * 0.5 - bounds.center().to_vec2() * self.zoom;
+    }
+}
+
+/// What a UI pass changed, used for undo labels and for coalescing continuous edits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphEdit {
+    AddNode,
+    RemoveNode,
+    Connect,
+    Disconnect,
+    Clear,
+    Move(u64),
+    Rename(u64),
+    ChangeKind(u64),
+    Parameter(u64),
+    BindOutput,
+    Seed,
+}
+
+impl GraphEdit {
+    /// Whether the edit changes what the engine computes (moves and renames don't).
+    pub fn affects_engine(&self) -> bool {
+        !matches!(self, GraphEdit::Move(_) | GraphEdit::Rename(_))
+    }
+
+    /// Edits that arrive every frame while dragging or typing and should become one undo step.
+    pub fn is_continuous(&self) -> bool {
+        matches!(self, GraphEdit::Move(_) | GraphEdit::Rename(_) | GraphEdit::Parameter(_) | GraphEdit::Seed)
+    }
+
+    /// Stable snake_case key, used to look up undo labels in UI string tables.
+    pub fn key(&self) -> &'static str {
+        match self {
+            GraphEdit::AddNode => "add_node",
+            GraphEdit::RemoveNode => "remove_node",
+            GraphEdit::Connect => "connect",
+            GraphEdit::Disconnect => "disconnect",
+            GraphEdit::Clear => "clear",
+            GraphEdit::Move(_) => "move_node",
+            GraphEdit::Rename(_) => "rename",
+            GraphEdit::ChangeKind(_) => "change_kind",
+            GraphEdit::Parameter(_) => "parameter",
+            GraphEdit::BindOutput => "bind_output",
+            GraphEdit::Seed => "seed",
+        }
+    }
+}
+
+fn node_height(kind: &NodeKind) -> f32 {
+    HEADER_HEIGHT + ROW_HEIGHT * kind.input_count().max(1) as f32 + 6.0
+}
+
+fn input_port_pos(pos: [f32; 2], input: usize) -> [f32; 2] {
+    [pos[0], pos[1] + HEADER_HEIGHT + ROW_HEIGHT * (input as f32 + 0.5)]
+}
+
+fn output_port_pos(pos: [f32; 2]) -> [f32; 2] {
+    [pos[0] + NODE_WIDTH, pos[1] + HEADER_HEIGHT + ROW_HEIGHT * 0.5]
+}
+
+fn wire_shape(from: Pos2, to: Pos2, zoom: f32, color: Color32) -> egui::Shape {
+    let bend = ((to.x - from.x).abs() * 0.5).max(30.0 * zoom);
+    egui::Shape::CubicBezier(egui::epaint::CubicBezierShape::from_points_stroke(
+        [from, from + Vec2::new(bend, 0.0), to - Vec2::new(bend, 0.0), to],
+        false,
+        Color32::TRANSPARENT,
+        Stroke::new(2.0 * zoom.max(0.5), color),
+    ))
+}
+
+/// Toolbar above the canvas: add/clear/fit/delete. Returns the edit made, if any.
+pub fn graph_toolbar_ui(ui: &mut egui::Ui, graph: &mut Graph, view: &mut GraphView, strings: &UiStrings) -> Option<GraphEdit> {
+    let mut edit = None;
+    ui.horizontal_wrapped(|ui| {
+        if ui.button(strings.get("graph_panel.add_node")).clicked() {
+            let id = graph.next_id();
+            let name = format!("{} {}", strings.get("graph_panel.node_prefix"), id);
+            let offset = 20.0 * (id % 8) as f32;
+            let pos = [(-view.pan.x + 40.0) / view.zoom + offset, (-view.pan.y + 40.0) / view.zoom + offset];
+            graph.add_node(name, NodeKind::Constant(0.0), pos);
+            view.selected = Some(id);
+            edit = Some(GraphEdit::AddNode);
+        }
+        if ui.button(strings.get("graph_panel.clear")).clicked() {
+            graph.clear();
+            view.selected = None;
+            edit = Some(GraphEdit::Clear);
+        }
+        if ui.button(strings.get("graph_panel.toolbar.zoom_fit")).clicked() {
+            let size = view.canvas_size;
+            view.zoom_to_fit(graph, size);
+        }
+        if ui.add_enabled(view.selected.is_some(), egui::Button::new(strings.get("graph_panel.toolbar.delete"))).clicked() {
+            if let Some(id) = view.selected.take() {
+                graph.remove_node(id);
+                edit = Some(GraphEdit::RemoveNode);
+            }
+        }
+    });
+    edit
+}
+
+/// Side-panel inspector for the selected node. Returns the edit made, if any.
+pub fn inspector_ui(ui: &mut egui::Ui, graph: &mut Graph, view: &GraphView, strings: &UiStrings) -> Option<GraphEdit> {
+    ui.heading(strings.get("graph_panel.inspector.title"));
+    let Some(id) = view.selected.filter(|id| graph.node(*id).is_some()) else {
+        ui.label(strings.get("graph_panel.inspector.nothing_selected"));
+        return None;
+    };
+    let mut edit = None;
+    let mut new_kind: Option<NodeKind> = None;
+    egui::Grid::new("node_inspector").num_columns(2).show(ui, |ui| {
+        let Some(node) = graph.node_mut(id) else { return };
+        ui.label(strings.get("graph_panel.inspector.name"));
+        if ui.text_edit_singleline(&mut node.name).changed() {
+            edit = Some(GraphEdit::Rename(id));
+        }
+        ui.end_row();
+
+        ui.label(strings.get("graph_panel.inspector.kind"));
+        let current = node.kind.type_key();
+        egui::ComboBox::from_id_source(("node_kind", id))
+            .selected_text(strings.get_in("graph_panel.node_types", current))
+            .show_ui(ui, |ui| {
+                for kind in NodeKind::palette() {
+                    let key = kind.type_key();
+                    if ui.selectable_label(key == current, strings.get_in("graph_panel.node_types", key)).clicked() && key != current {
+                        new_kind = Some(kind);
+                    }
+                }
+            });
+        ui.end_row();
+
+        for (key, value) in node.kind.params_mut() {
+            ui.label(strings.get_in("graph_panel.parameters", key));
+            let speed = match key { "frequency" => 0.0005, "amplitude" => 0.1, _ => 0.01 };
+            if ui.add(egui::DragValue::new(value).speed(speed).max_decimals(4)).changed() {
+                edit = Some(GraphEdit::Parameter(id));
+            }
+            ui.end_row();
+        }
+        if let Some(offset) = node.kind.seed_offset_mut() {
+            ui.label(strings.get("graph_panel.parameters.seed_offset"));
+            if ui.add(egui::DragValue::new(offset)).changed() {
+                edit = Some(GraphEdit::Parameter(id));
+            }
+            ui.end_row();
+        }
+        if let NodeKind::FnlCellular { return_type, .. } = &mut node.kind {
+            ui.label(strings.get("graph_panel.parameters.return_type"));
+            egui::ComboBox::from_id_source(("cellular_return", id))
+                .selected_text(strings.get_in("graph_panel.cellular_returns", return_type.key()))
+                .show_ui(ui, |ui| {
+                    for r in CellularReturn::ALL {
+                        if ui.selectable_value(return_type, r, strings.get_in("graph_panel.cellular_returns", r.key())).changed() {
+                            edit = Some(GraphEdit::Parameter(id));
+                        }
+                    }
+                });
+            ui.end_row();
+        }
+    });
+    if let Some(NodeKind::Curve { points }) = graph.node_mut(id).map(|n| &mut n.kind) {
+        ui.label(strings.get("graph_panel.parameters.curve"));
+        if curve_ui(ui, id, points) {
+            edit = Some(GraphEdit::Parameter(id));
+        }
+        ui.small(strings.get("graph_panel.parameters.curve_hint"));
+    }
+    if let Some(kind) = new_kind {
+        graph.set_kind(id, kind);
+        edit = Some(GraphEdit::ChangeKind(id));
+    }
+    edit
+}
+
+/// Editable plot of a curve node over [-1, 1] x [-1, 1]. Points keep their x order while dragged,
+/// so the curve stays monotonic in x. Returns whether the points changed.
+fn curve_ui(ui: &mut egui::Ui, id: u64, points: &mut Vec<[f32; 2]>) -> bool {
+    const HANDLE_RADIUS: f32 = 5.0;
+    let side = ui.available_width().min(240.0);
+    let (rect, response) = ui.allocate_exact_size(Vec2::splat(side), Sense::click());
+    let to_screen = |p: [f32; 2]| Pos2::new(egui::remap(p[0], -1.0..=1.0, rect.x_range()), egui::remap(p[1], -1.0..=1.0, rect.bottom()..=rect.top()));
+    let to_curve = |p: Pos2| [
+        egui::remap_clamp(p.x, rect.x_range(), -1.0..=1.0),
+        egui::remap_clamp(p.y, rect.bottom()..=rect.top(), -1.0..=1.0),
+    ];
+    let mut changed = false;
+
+    if response.double_clicked() {
+        if let Some(pos) = response.interact_pointer_pos() {
+            let p = to_curve(pos);
+            let at = points.partition_point(|q| q[0] <= p[0]);
+            points.insert(at, p);
+            changed = true;
+        }
+    }
+    let mut remove = None;
+    for i in 0..points.len() {
+        let handle = Rect::from_center_size(to_screen(points[i]), Vec2::splat(HANDLE_RADIUS * 3.0));
+        let r = ui.interact(handle, ui.id().with(("curve_point", id, i)), Sense::click_and_drag());
+        if r.dragged() {
+            if let Some(pos) = r.interact_pointer_pos() {
+                let mut p = to_curve(pos);
+                let lo = if i > 0 { points[i - 1][0] } else { -1.0 };
+                let hi = points.get(i + 1).map_or(1.0, |q| q[0]);
+                p[0] = p[0].clamp(lo, hi.max(lo));
+                points[i] = p;
+                changed = true;
+            }
+        }
+        if r.secondary_clicked() && points.len() > 2 {
+            remove = Some(i);
+        }
+    }
+    if let Some(i) = remove {
+        points.remove(i);
+        changed = true;
+    }
+
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let axis = Stroke::new(1.0, visuals.weak_text_color());
+    painter.line_segment([to_screen([-1.0, 0.0]), to_screen([1.0, 0.0])], axis);
+    painter.line_segment([to_screen([0.0, -1.0]), to_screen([0.0, 1.0])], axis);
+    // Flat beyond the end points, as the engine evaluates it
+    let mut sorted = points.clone();
+    sorted.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    if let (Some(first), Some(last)) = (sorted.first(), sorted.last()) {
+        let line: Vec<Pos2> = std::iter::once([-1.0, first[1]])
+            .chain(sorted.iter().copied())
+            .chain(std::iter::once([1.0, last[1]]))
+            .map(to_screen)
+            .collect();
+        painter.add(egui::Shape::line(line, Stroke::new(2.0, visuals.selection.stroke.color)));
+    }
+    for p in points.iter() {
+        painter.circle(to_screen(*p), HANDLE_RADIUS, visuals.widgets.active.bg_fill, visuals.widgets.active.fg_stroke);
+    }
+    changed
+}
+
+/// Outputs panel: picks the node each requested channel is sampled from. Returns the edit made, if any.
+pub fn outputs_ui(ui: &mut egui::Ui, graph: &mut Graph, channels: &[ChannelDesc], strings: &UiStrings) -> Option<GraphEdit> {
+    ui.heading(strings.get("graph_panel.outputs.title"));
+    ui.label(strings.get("graph_panel.outputs.hint"));
+    let mut binding: Option<(&str, Option<u64>)> = None;
+    egui::Grid::new("channel_outputs").num_columns(2).show(ui, |ui| {
+        for channel in channels {
+            ui.label(&channel.name);
+            let bound = graph.bindings.get(&channel.name).copied();
+            let selected_text = match bound.and_then(|id| graph.node(id)) {
+                Some(node) => node.name.clone(),
+                None if graph.bindings.is_empty() => strings.get("graph_panel.outputs.output_node").to_string(),
+                None => strings.get("graph_panel.outputs.unbound").to_string(),
+            };
+            egui::ComboBox::from_id_source(("channel_output", &channel.name))
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    if ui.selectable_label(bound.is_none(), strings.get("graph_panel.outputs.unbound")).clicked() && bound.is_some() {
+                        binding = Some((&channel.name, None));
+                    }
+                    for node in &graph.nodes {
+                        if ui.selectable_label(bound == Some(node.id), &node.name).clicked() && bound != Some(node.id) {
+                            binding = Some((&channel.name, Some(node.id)));
+                        }
+                    }
+                });
+            ui.end_row();
         }
-        ui.separator();
-        ui.label(&strings.graph_panel.hint);
     });
+    let missing = graph.unbound_channels(channels);
+    if !missing.is_empty() && !graph.nodes.is_empty() {
+        ui.colored_label(Color32::from_rgb(230, 120, 90), format!("{} {}", strings.get("graph_panel.outputs.missing"), missing.join(", ")));
+    }
+    let (channel, node) = binding?;
+    graph.bind(channel, node);
+    Some(GraphEdit::BindOutput)
+}
 
-    ui.separator();
+/// Node canvas: draggable nodes, wires between ports, pan/zoom, Del to delete and a right-click add menu.
+/// Returns the edit made, if any.
+pub fn graph_canvas_ui(ui: &mut egui::Ui, graph: &mut Graph, view: &mut GraphView, strings: &UiStrings) -> Option<GraphEdit> {
+    let mut edit = None;
+    let (rect, _) = ui.allocate_exact_size(ui.available_size(), Sense::hover());
+    let origin = rect.min;
+    view.canvas_size = rect.size();
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, Color32::from_rgb(24, 24, 30));
 
-    // Simple fallback list-based editor (no hardcoded UI strings beyond data)
-    // Nodes list
-    for n in &graph.nodes {
-        ui.label(&n.name);
+    // Background: pan with drag, zoom around the pointer with scroll / pinch.
+    let bg = ui.interact(rect, ui.id().with("graph_canvas_bg"), Sense::click_and_drag());
+    if bg.dragged() {
+        view.pan += bg.drag_delta();
     }
+    if bg.clicked() {
+        view.selected = None;
+    }
+    if let Some(hover) = bg.hover_pos() {
+        let (scroll, zoom_delta) = ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta()));
+        let factor = zoom_delta * (scroll * 0.002).exp();
+        if factor != 1.0 {
+            let anchor = view.to_graph(origin, hover);
+            view.zoom = (view.zoom * factor).clamp(0.2, 3.0);
+            let moved = view.to_screen(origin, anchor);
+            view.pan += hover - moved;
+        }
+    }
+    if bg.secondary_clicked() {
+        if let Some(p) = bg.interact_pointer_pos() {
+            view.menu_pos = view.to_graph(origin, p);
+        }
+    }
+    bg.context_menu(|ui| {
+        ui.label(strings.get("graph_panel.add_node"));
+        ui.separator();
+        for kind in NodeKind::palette() {
+            if ui.button(strings.get_in("graph_panel.node_types", kind.type_key())).clicked() {
+                let id = graph.next_id();
+                let name = format!("{} {}", strings.get("graph_panel.node_prefix"), id);
+                graph.add_node(name, kind, view.menu_pos);
+                view.selected = Some(id);
+                edit = Some(GraphEdit::AddNode);
+                ui.close_menu();
+            }
+        }
+    });
 
-    // Show edges as pairs of ids (data only)
+    // Light grid so panning is visible.
+    let grid = 32.0 * view.zoom;
+    if grid > 6.0 {
+        let grid_color = Color32::from_rgb(34, 34, 42);
+        let mut x = rect.min.x + (view.pan.x % grid + grid) % grid;
+        while x < rect.max.x {
+            painter.line_segment([Pos2::new(x, rect.min.y), Pos2::new(x, rect.max.y)], Stroke::new(1.0, grid_color));
+            x += grid;
+        }
+        let mut y = rect.min.y + (view.pan.y % grid + grid) % grid;
+        while y < rect.max.y {
+            painter.line_segment([Pos2::new(rect.min.x, y), Pos2::new(rect.max.x, y)], Stroke::new(1.0, grid_color));
+            y += grid;
+        }
+    }
+
+    // Existing wires.
     for e in &graph.edges {
-        // Represent as "from -> to" using numeric ids; this is data, not UI text
-        let txt = format!("{} -> {}", e.from, e.to);
-        ui.label(txt);
+        let (Some(from), Some(to)) = (graph.node(e.from), graph.node(e.to)) else { continue };
+        let a = view.to_screen(origin, output_port_pos(from.pos));
+        let b = view.to_screen(origin, input_port_pos(to.pos, e.input));
+        painter.add(wire_shape(a, b, view.zoom, Color32::from_rgb(150, 170, 200)));
+    }
+
+    // Nodes and ports. Interaction order matters: later widgets sit on top.
+    let pointer = ui.input(|i| i.pointer.hover_pos());
+    let released = ui.input(|i| i.pointer.any_released());
+    let mut drop_target: Option<(u64, usize)> = None;
+    let mut picked_up: Option<(u64, usize)> = None;
+    let mut wire_started: Option<u64> = None;
+    let font = egui::FontId::proportional(13.0 * view.zoom);
+    for node in graph.nodes.iter_mut() {
+        let min = view.to_screen(origin, node.pos);
+        let node_rect = Rect::from_min_size(min, Vec2::new(NODE_WIDTH, node_height(&node.kind)) * view.zoom);
+        let resp = ui.interact(node_rect, ui.id().with(("graph_node", node.id)), Sense::click_and_drag());
+        if resp.dragged() {
+            let d = resp.drag_delta() / view.zoom;
+            node.pos[0] += d.x;
+            node.pos[1] += d.y;
+            edit = Some(GraphEdit::Move(node.id));
+        }
+        if resp.clicked() || resp.drag_started() {
+            view.selected = Some(node.id);
+        }
+
+        let selected = view.selected == Some(node.id);
+        let rounding = 4.0 * view.zoom;
+        painter.rect(node_rect, rounding, Color32::from_rgb(48, 50, 60),
+            Stroke::new(if selected { 2.0 } else { 1.0 }, if selected { Color32::from_rgb(240, 200, 80) } else { Color32::from_rgb(80, 84, 96) }));
+        let header = Rect::from_min_size(node_rect.min, Vec2::new(node_rect.width(), HEADER_HEIGHT * view.zoom));
+        painter.rect_filled(header, rounding, Color32::from_rgb(64, 72, 96));
+        painter.text(header.left_center() + Vec2::new(6.0 * view.zoom, 0.0), egui::Align2::LEFT_CENTER, &node.name, font.clone(), Color32::WHITE);
+        painter.text(view.to_screen(origin, [node.pos[0] + NODE_WIDTH * 0.5, node.pos[1] + HEADER_HEIGHT + ROW_HEIGHT * 0.5]),
+            egui::Align2::CENTER_CENTER, strings.get_in("graph_panel.node_types", node.kind.type_key()), font.clone(), Color32::LIGHT_GRAY);
+
+        for input in 0..node.kind.input_count() {
+            let c = view.to_screen(origin, input_port_pos(node.pos, input));
+            let port_rect = Rect::from_center_size(c, Vec2::splat(PORT_RADIUS * 3.0 * view.zoom));
+            let port = ui.interact(port_rect, ui.id().with(("graph_in", node.id, input)), Sense::drag());
+            if port.drag_started() {
+                picked_up = Some((node.id, input));
+            }
+            if view.wire_from.is_some() && released && pointer.is_some_and(|p| port_rect.contains(p)) {
+                drop_target = Some((node.id, input));
+            }
+            painter.circle(c, PORT_RADIUS * view.zoom, Color32::from_rgb(120, 200, 140), Stroke::new(1.0, Color32::BLACK));
+        }
+        let c = view.to_screen(origin, output_port_pos(node.pos));
+        let port_rect = Rect::from_center_size(c, Vec2::splat(PORT_RADIUS * 3.0 * view.zoom));
+        let port = ui.interact(port_rect, ui.id().with(("graph_out", node.id)), Sense::drag());
+        if port.drag_started() {
+            wire_started = Some(node.id);
+        }
+        painter.circle(c, PORT_RADIUS * view.zoom, Color32::from_rgb(220, 150, 90), Stroke::new(1.0, Color32::BLACK));
+    }
+
+    // Dragging from a connected input picks the wire up so it can be moved or dropped to delete it.
+    if let Some((to, input)) = picked_up {
+        if let Some(edge) = graph.disconnect(to, input) {
+            view.wire_from = Some(edge.from);
+            edit = Some(GraphEdit::Disconnect);
+        }
+    }
+    if let Some(from) = wire_started {
+        view.wire_from = Some(from);
+    }
+    if let Some(from) = view.wire_from {
+        if let (Some(node), Some(p)) = (graph.node(from), pointer) {
+            let a = view.to_screen(origin, output_port_pos(node.pos));
+            painter.add(wire_shape(a, p, view.zoom, Color32::from_rgb(240, 200, 80)));
+        }
+        if released {
+            if let Some((to, input)) = drop_target {
+                if graph.connect(from, to, input) {
+                    edit = Some(GraphEdit::Connect);
+                }
+            }
+            view.wire_from = None;
+        }
+    }
+
+    // Del removes the selected node unless a text field has focus.
+    if ui.input(|i| i.key_pressed(egui::Key::Delete)) && !ui.ctx().wants_keyboard_input() {
+        if let Some(id) = view.selected.take() {
+            graph.remove_node(id);
+            edit = Some(GraphEdit::RemoveNode);
+        }
     }
-}
\ No newline at end of file
+    edit
+}
@@ -0,0 +1,98 @@
+use noise_engine::graph::Graph;
+use crate::graph_editor::GraphEdit;
+
+pub const DEFAULT_UNDO_DEPTH: usize = 100;
+/// Continuous edits of the same kind closer together than this (seconds) merge into one undo step.
+const COALESCE_WINDOW: f64 = 0.75;
+
+/// What an undo step reverts; file loads and New replace the whole graph outside the editor widgets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryLabel {
+    Edit(GraphEdit),
+    Load,
+    New,
+}
+
+impl HistoryLabel {
+    /// Stable snake_case key, used to look up undo labels in UI string tables.
+    pub fn key(&self) -> &'static str {
+        match self {
+            HistoryLabel::Edit(edit) => edit.key(),
+            HistoryLabel::Load => "load",
+            HistoryLabel::New => "new",
+        }
+    }
+}
+
+struct Snapshot {
+    label: HistoryLabel,
+    graph: Graph,
+}
+
+/// Undo/redo stacks of whole-graph snapshots.
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    pub max_depth: usize,
+    /// Time of the last recorded edit, for coalescing drags and typing.
+    last_time: f64,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self { undo: vec![], redo: vec![], max_depth: DEFAULT_UNDO_DEPTH, last_time: f64::NEG_INFINITY }
+    }
+}
+
+impl History {
+    /// Records `before` (the graph prior to the edit) as an undo step and clears the redo stack.
+    /// A continuous edit repeating the previous step within the coalesce window extends that step instead.
+    pub fn record(&mut self, label: HistoryLabel, before: Graph, time: f64) {
+        let coalesce = matches!(label, HistoryLabel::Edit(e) if e.is_continuous())
+            && self.undo.last().is_some_and(|s| s.label == label)
+            && time - self.last_time < COALESCE_WINDOW;
+        self.last_time = time;
+        self.redo.clear();
+        if coalesce {
+            return;
+        }
+        self.undo.push(Snapshot { label, graph: before });
+        self.trim();
+    }
+
+    /// Swaps `current` with the most recent undo snapshot. Returns false if there was nothing to undo.
+    pub fn undo(&mut self, current: &mut Graph) -> bool {
+        let Some(snap) = self.undo.pop() else { return false };
+        let redo = Snapshot { label: snap.label, graph: std::mem::replace(current, snap.graph) };
+        self.redo.push(redo);
+        self.last_time = f64::NEG_INFINITY;
+        true
+    }
+
+    /// Reapplies the most recently undone step. Returns false if there was nothing to redo.
+    pub fn redo(&mut self, current: &mut Graph) -> bool {
+        let Some(snap) = self.redo.pop() else { return false };
+        let undo = Snapshot { label: snap.label, graph: std::mem::replace(current, snap.graph) };
+        self.undo.push(undo);
+        self.last_time = f64::NEG_INFINITY;
+        true
+    }
+
+    pub fn undo_label(&self) -> Option<HistoryLabel> {
+        self.undo.last().map(|s| s.label)
+    }
+
+    pub fn redo_label(&self) -> Option<HistoryLabel> {
+        self.redo.last().map(|s| s.label)
+    }
+
+    /// Drops the oldest steps beyond `max_depth`.
+    pub fn trim(&mut self) {
+        let max = self.max_depth.max(1);
+        if self.undo.len() > max {
+            let excess = self.undo.len() - max;
+            self.undo.drain(..excess);
+        }
+        self.redo.truncate(max);
+    }
+}
@@ -1,8 +1,6 @@
-use bevy::{prelude::*, window::WindowResolution};
+use bevy::{prelude::*, window::{PrimaryWindow, WindowCloseRequested, WindowResolution}};
+use bevy::app::AppExit;
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
-use bevy::render::camera::RenderTarget;
-use bevy::window::WindowRef;
-use bevy::math::primitives::Cuboid;
 use noise_engine::*; // API types
 use noise_engine::graph::Graph; // graph types
 use noise_engine::sampling::SimpleEngine; // engine impl
@@ -10,211 +8,499 @@ use noise_engine::sampling::SimpleEngine; // engine impl
 mod ui_strings;
 mod preview;
 mod graph_editor;
+mod document;
+mod preview3d;
+mod export;
+mod history;
+mod settings;
+mod fonts;
+
+use document::FileAction;
+use graph_editor::GraphEdit;
+use history::HistoryLabel;
+use mc_common::localization::LOCALIZATION_DIR;
+use std::path::{Path, PathBuf};
 
 #[derive(Resource)]
 struct EditorState {
     graph: Graph,
+    /// Copy of the graph as of the last recorded edit. The widgets edit `graph` in place, so this becomes the
+    /// undo snapshot when the next edit is reported; the graph is only cloned when something actually changed.
+    baseline: Graph,
     engine: Option<SimpleEngine>,
-    seed: u64,
     selected_channels: Vec<ChannelDesc>,
     ui: ui_strings::UiStrings,
+    settings: settings::EditorSettings,
     preview_w: i32,
     preview_h: i32,
-    preview_channel: i32,
+    /// Index into `selected_channels` of the channel the previews show.
+    preview_channel: usize,
     show_preview_window: bool,
     preview_window_entity: Option<Entity>,
+    graph_view: graph_editor::GraphView,
+    live_preview: preview::LivePreview,
+    comparison: preview::Comparison,
+    history: history::History,
+    /// Set whenever the graph is edited; the engine picks up a fresh copy at the end of the frame.
+    engine_stale: bool,
+    /// Bumped whenever the engine's graph or seed changes, so previews know to resample.
+    engine_version: u64,
+    /// File the graph was last loaded from or saved to; `None` for a new, never-saved graph.
+    current_path: Option<PathBuf>,
+    /// Unsaved changes since the last load/save.
+    dirty: bool,
+    recent_files: Vec<PathBuf>,
+    /// Action waiting on the unsaved-changes prompt.
+    pending_action: Option<FileAction>,
+    /// Last load/save failure, shown until dismissed.
+    file_error: Option<String>,
 }
 
 impl Default for EditorState {
     fn default() -> Self {
         Self {
-            graph: Graph { nodes: vec![], edges: vec![] },
+            graph: Graph::default(),
+            baseline: Graph::default(),
             engine: None,
-            seed: 1337,
             selected_channels: vec![],
             ui: ui_strings::UiStrings::default(),
+            settings: settings::EditorSettings::default(),
             preview_w: 256,
             preview_h: 256,
             preview_channel: 0,
             show_preview_window: false,
             preview_window_entity: None,
+            graph_view: graph_editor::GraphView::default(),
+            live_preview: preview::LivePreview::default(),
+            comparison: preview::Comparison::default(),
+            history: history::History::default(),
+            engine_stale: false,
+            engine_version: 0,
+            current_path: None,
+            dirty: false,
+            recent_files: vec![],
+            pending_action: None,
+            file_error: None,
         }
     }
 }
 
-const DEFAULT_GRAPH_PATH: &str = "assets/noise_graphs/default.ron";
-
 fn main() {
     // Load UI strings early to get window title
-    let ui_strings = ui_strings::load_from_file("assets/ui_strings.json").unwrap_or_default();
+    let settings = settings::load_settings();
+    let ui_strings = ui_strings::UiStrings::load(Path::new(LOCALIZATION_DIR), &settings.language);
 
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.05, 0.05, 0.08)))
         .add_plugins((DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window { title: ui_strings.app.window_title.clone().into(), resolution: WindowResolution::new(1280., 800.), ..default() }),
+            primary_window: Some(Window { title: ui_strings.get("app.window_title").into(), resolution: WindowResolution::new(1280., 800.), ..default() }),
+            // Closing is routed through the unsaved-changes prompt in handle_close_requests
+            close_when_requested: false,
             ..default()
-        }), EguiPlugin))
-        .insert_resource(EditorState { ui: ui_strings, ..Default::default() })
+        }), EguiPlugin, preview3d::TerrainPreviewPlugin, export::ExportPlugin))
+        .insert_resource(EditorState { ui: ui_strings, settings, ..Default::default() })
         .add_systems(Startup, setup)
-        .add_systems(Update, (draw_menu, draw_left_panel, draw_preview, spawn_preview_world_window, monitor_preview_window_closed))
+        .add_systems(Update, ((draw_menu, draw_left_panel, draw_preview, draw_graph_canvas).chain(), draw_file_dialogs, update_window_title, handle_close_requests))
         .run();
 }
 
-fn setup(mut state: ResMut<EditorState>) {
-    state.graph = Graph { nodes: vec![], edges: vec![] };
-    state.seed = 1337;
+fn setup(mut state: ResMut<EditorState>, mut egui_ctx: EguiContexts) {
+    // Languages that need CJK glyphs are only offered when a font for them is installed
+    if !fonts::install_cjk_font(egui_ctx.ctx_mut()) {
+        state.ui.remove_languages(fonts::needs_cjk_font);
+    }
+    state.graph = Graph::default();
     state.selected_channels = vec![
         ChannelDesc { name: "height".into(), kind: ChannelKind::Height2D },
         ChannelDesc { name: "biome".into(), kind: ChannelKind::Biome2D },
     ];
+    state.recent_files = document::load_recent_files();
     // Try load default graph
-    if let Ok(s) = std::fs::read_to_string(DEFAULT_GRAPH_PATH) {
-        if let Ok(g) = ron::from_str::<Graph>(&s) { state.graph = g; }
+    let default_path = PathBuf::from(document::DEFAULT_GRAPH_PATH);
+    if default_path.exists() {
+        open_graph(&mut state, default_path);
     }
     state.engine = Some(SimpleEngine::new(state.graph.clone()));
 }
 
-fn draw_menu(mut egui_ctx: EguiContexts, mut state: ResMut<EditorState>) {
+enum MenuCommand {
+    File(FileAction),
+    Save,
+    SaveAs,
+    ClearRecent,
+    Language(String),
+    Undo,
+    Redo,
+}
+
+fn draw_menu(
+    mut egui_ctx: EguiContexts,
+    mut state: ResMut<EditorState>,
+    mut export_panel: ResMut<export::ExportPanel>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let mut command = None;
     egui::TopBottomPanel::top("menu_bar").show(egui_ctx.ctx_mut(), |ui| {
         egui::menu::bar(ui, |ui| {
             let ui_text = state.ui.clone();
-            let file_text = ui_text.menu.file.clone();
-            ui.menu_button(file_text, move |ui| {
-                if ui.button(&ui_text.menu.save).clicked() {
-                    let _ = std::fs::create_dir_all("assets/noise_graphs");
-                    ui.data_mut(|d| d.insert_temp(egui::Id::new("do_save_graph"), true));
+            ui.menu_button(ui_text.get("menu.file"), |ui| {
+                if ui.button(ui_text.get("menu.new")).clicked() {
+                    command = Some(MenuCommand::File(FileAction::New));
                     ui.close_menu();
                 }
-                if ui.button(&ui_text.menu.load).clicked() {
-                    ui.data_mut(|d| d.insert_temp(egui::Id::new("do_load_graph"), true));
+                if ui.button(ui_text.get("menu.open")).clicked() {
+                    command = Some(MenuCommand::File(FileAction::Open));
                     ui.close_menu();
                 }
+                ui.add_enabled_ui(!state.recent_files.is_empty(), |ui| {
+                    ui.menu_button(ui_text.get("menu.open_recent"), |ui| {
+                        for path in &state.recent_files {
+                            if ui.button(path.display().to_string()).clicked() {
+                                command = Some(MenuCommand::File(FileAction::OpenPath(path.clone())));
+                                ui.close_menu();
+                            }
+                        }
+                        ui.separator();
+                        if ui.button(ui_text.get("menu.clear_recent")).clicked() {
+                            command = Some(MenuCommand::ClearRecent);
+                            ui.close_menu();
+                        }
+                    });
+                });
+                ui.separator();
+                if ui.button(ui_text.get("menu.save")).clicked() {
+                    command = Some(MenuCommand::Save);
+                    ui.close_menu();
+                }
+                if ui.button(ui_text.get("menu.save_as")).clicked() {
+                    command = Some(MenuCommand::SaveAs);
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui.button(ui_text.get("menu.export")).clicked() {
+                    export_panel.open = true;
+                    ui.close_menu();
+                }
+                ui.separator();
+                ui.menu_button(ui_text.get("menu.language"), |ui| {
+                    for language in ui_text.languages() {
+                        if ui.radio(language.code == ui_text.language(), &language.name).clicked() {
+                            command = Some(MenuCommand::Language(language.code.clone()));
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.separator();
+                if ui.button(ui_text.get("menu.exit")).clicked() {
+                    command = Some(MenuCommand::File(FileAction::Exit));
+                    ui.close_menu();
+                }
+            });
+            ui.menu_button(ui_text.get("menu.edit"), |ui| {
+                let undo_label = state.history.undo_label();
+                let undo_text = match undo_label {
+                    Some(label) => format!("{} {}", ui_text.get("menu.undo"), ui_text.get_in("history", label.key())),
+                    None => ui_text.get("menu.undo").to_string(),
+                };
+                if ui.add_enabled(undo_label.is_some(), egui::Button::new(undo_text).shortcut_text("Ctrl+Z")).clicked() {
+                    command = Some(MenuCommand::Undo);
+                    ui.close_menu();
+                }
+                let redo_label = state.history.redo_label();
+                let redo_text = match redo_label {
+                    Some(label) => format!("{} {}", ui_text.get("menu.redo"), ui_text.get_in("history", label.key())),
+                    None => ui_text.get("menu.redo").to_string(),
+                };
+                if ui.add_enabled(redo_label.is_some(), egui::Button::new(redo_text).shortcut_text("Ctrl+Y")).clicked() {
+                    command = Some(MenuCommand::Redo);
+                    ui.close_menu();
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(ui_text.get("menu.history_depth"));
+                    if ui.add(egui::DragValue::new(&mut state.history.max_depth).clamp_range(1..=1000)).changed() {
+                        state.history.trim();
+                    }
+                });
             });
-            let bake_text = ui_text.menu.bake.clone();
-            if ui.button(&bake_text).clicked() {
-                let seed = state.seed; // take value to avoid immutable borrow later
+            if ui.button(ui_text.get("menu.bake")).clicked() {
+                let seed = state.graph.seed; // take value to avoid immutable borrow later
                 if let Some(engine) = &mut state.engine { engine.bake(Seed(seed)); }
+                state.engine_version += 1;
+            }
+            ui.separator();
+            // Reseeding is an undoable graph edit; the engine is rebaked with the new seed at the end of the frame
+            ui.label(ui_text.get("menu.seed"));
+            let mut reseeded = ui.add(egui::DragValue::new(&mut state.graph.seed)).changed();
+            if ui.button(ui_text.get("menu.randomize_seed")).clicked() {
+                state.graph.seed = rand::random::<u32>() as u64;
+                reseeded = true;
+            }
+            if reseeded {
+                let time = ui.input(|i| i.time);
+                apply_edit(&mut state, GraphEdit::Seed, time);
             }
         });
     });
-    // Handle deferred actions that require mutable access to state outside of the UI borrow scope
+    // Undo/redo shortcuts, unless a text field is using the keyboard (it has its own undo)
     let ctx = egui_ctx.ctx_mut();
-    let do_save = ctx.data_mut(|d| d.get_temp::<bool>(egui::Id::new("do_save_graph")).unwrap_or(false));
-    if do_save {
-        let _ = std::fs::create_dir_all("assets/noise_graphs");
-        let pretty = ron::ser::PrettyConfig::new();
-        if let Ok(text) = ron::ser::to_string_pretty(&state.graph, pretty) {
-            let _ = std::fs::write(DEFAULT_GRAPH_PATH, text);
+    if !ctx.wants_keyboard_input() {
+        let redo = ctx.input_mut(|i| {
+            i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z)
+                || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y)
+        });
+        if redo {
+            command = Some(MenuCommand::Redo);
+        } else if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z)) {
+            command = Some(MenuCommand::Undo);
+        }
+    }
+    // File actions run outside the UI closures so they can take the state mutably
+    match command {
+        Some(MenuCommand::File(action)) => request_file_action(&mut state, action, &mut exit),
+        Some(MenuCommand::Save) => { save_graph(&mut state, false); }
+        Some(MenuCommand::SaveAs) => { save_graph(&mut state, true); }
+        Some(MenuCommand::ClearRecent) => {
+            state.recent_files.clear();
+            document::save_recent_files(&state.recent_files);
+        }
+        Some(MenuCommand::Language(code)) => match state.ui.set_language(Path::new(LOCALIZATION_DIR), &code) {
+            Ok(()) => {
+                state.settings.language = code;
+                settings::save_settings(&state.settings);
+            }
+            Err(e) => state.file_error = Some(e),
+        },
+        Some(MenuCommand::Undo) => {
+            let EditorState { history, graph, .. } = &mut *state;
+            if history.undo(graph) { after_history_step(&mut state); }
+        }
+        Some(MenuCommand::Redo) => {
+            let EditorState { history, graph, .. } = &mut *state;
+            if history.redo(graph) { after_history_step(&mut state); }
         }
-        ctx.data_mut(|d| d.remove::<bool>(egui::Id::new("do_save_graph")));
+        None => {}
+    }
+}
+
+/// Records an edit made by the editor widgets: pushes the pre-edit snapshot and marks the document dirty.
+fn apply_edit(state: &mut EditorState, edit: GraphEdit, time: f64) {
+    let before = std::mem::replace(&mut state.baseline, state.graph.clone());
+    state.history.record(HistoryLabel::Edit(edit), before, time);
+    state.dirty = true;
+    state.engine_stale |= edit.affects_engine();
+}
+
+/// Undo/redo swapped the graph wholesale; resync everything that depends on it.
+fn after_history_step(state: &mut EditorState) {
+    state.baseline = state.graph.clone();
+    state.dirty = true;
+    state.engine_stale = true;
+    if state.graph_view.selected.is_some_and(|id| state.graph.node(id).is_none()) {
+        state.graph_view.selected = None;
+    }
+}
+
+/// Replaces the whole graph (New/Load), keeping the old one as an undo step unless it was empty.
+fn replace_graph(state: &mut EditorState, graph: Graph, label: HistoryLabel) {
+    let before = std::mem::replace(&mut state.graph, graph);
+    state.baseline = state.graph.clone();
+    if !before.nodes.is_empty() {
+        state.history.record(label, before, 0.0);
+    }
+    state.graph_view = graph_editor::GraphView::default();
+    state.engine_stale = true;
+}
+
+/// Runs `action` right away, or parks it behind the unsaved-changes prompt if the graph is dirty.
+fn request_file_action(state: &mut EditorState, action: FileAction, exit: &mut EventWriter<AppExit>) {
+    if state.dirty {
+        state.pending_action = Some(action);
+    } else {
+        perform_file_action(state, action, exit);
     }
-    let do_load = ctx.data_mut(|d| d.get_temp::<bool>(egui::Id::new("do_load_graph")).unwrap_or(false));
-    if do_load {
-        if let Ok(s) = std::fs::read_to_string(DEFAULT_GRAPH_PATH) {
-            if let Ok(g) = ron::from_str::<Graph>(&s) {
-                state.graph = g;
-                let graph_clone = state.graph.clone();
-                if let Some(engine) = &mut state.engine { engine.graph = graph_clone; }
+}
+
+fn perform_file_action(state: &mut EditorState, action: FileAction, exit: &mut EventWriter<AppExit>) {
+    match action {
+        FileAction::New => {
+            replace_graph(state, Graph::default(), HistoryLabel::New);
+            state.current_path = None;
+            state.dirty = false;
+        }
+        FileAction::Open => {
+            if let Some(path) = document::pick_open_path(state.ui.get("file.filter_name")) {
+                open_graph(state, path);
             }
         }
-        ctx.data_mut(|d| d.remove::<bool>(egui::Id::new("do_load_graph")));
+        FileAction::OpenPath(path) => open_graph(state, path),
+        FileAction::Exit => { exit.send(AppExit); }
+    }
+}
+
+/// Loads `path`, replacing the current graph; on failure the old graph stays and the error is shown.
+fn open_graph(state: &mut EditorState, path: PathBuf) {
+    match document::load_graph(&path) {
+        Ok(graph) => {
+            replace_graph(state, graph, HistoryLabel::Load);
+            state.dirty = false;
+            document::push_recent_file(&mut state.recent_files, &path);
+            state.current_path = Some(path);
+        }
+        Err(e) => {
+            state.file_error = Some(format!("{} {}: {}", state.ui.get("file.load_failed"), path.display(), e));
+        }
+    }
+}
+
+/// Saves to the current path (asking for one if there is none or `save_as` is set).
+/// Returns false if the user cancelled or the write failed.
+fn save_graph(state: &mut EditorState, save_as: bool) -> bool {
+    let path = match (&state.current_path, save_as) {
+        (Some(path), false) => path.clone(),
+        _ => match document::pick_save_path(state.ui.get("file.filter_name"), state.current_path.as_deref()) {
+            Some(path) => path,
+            None => return false,
+        },
+    };
+    match document::save_graph(&path, &state.graph) {
+        Ok(()) => {
+            state.dirty = false;
+            document::push_recent_file(&mut state.recent_files, &path);
+            state.current_path = Some(path);
+            true
+        }
+        Err(e) => {
+            state.file_error = Some(format!("{} {}: {}", state.ui.get("file.save_failed"), path.display(), e));
+            false
+        }
+    }
+}
+
+enum PromptChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+fn draw_file_dialogs(mut egui_ctx: EguiContexts, mut state: ResMut<EditorState>, mut exit: EventWriter<AppExit>) {
+    let text = state.ui.clone();
+    let ctx = egui_ctx.ctx_mut();
+    if state.pending_action.is_some() {
+        let mut choice = None;
+        egui::Window::new(text.get("file.unsaved_title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(text.get("file.unsaved_message"));
+                ui.horizontal(|ui| {
+                    if ui.button(text.get("file.save")).clicked() { choice = Some(PromptChoice::Save); }
+                    if ui.button(text.get("file.discard")).clicked() { choice = Some(PromptChoice::Discard); }
+                    if ui.button(text.get("file.cancel")).clicked() { choice = Some(PromptChoice::Cancel); }
+                });
+            });
+        match choice {
+            Some(PromptChoice::Save) if save_graph(&mut state, false) => {
+                if let Some(action) = state.pending_action.take() { perform_file_action(&mut state, action, &mut exit); }
+            }
+            Some(PromptChoice::Discard) => {
+                state.dirty = false;
+                if let Some(action) = state.pending_action.take() { perform_file_action(&mut state, action, &mut exit); }
+            }
+            Some(PromptChoice::Cancel) => state.pending_action = None,
+            _ => {}
+        }
+    }
+    if let Some(message) = state.file_error.clone() {
+        let mut dismissed = false;
+        egui::Window::new(text.get("file.error_title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(message);
+                if ui.button(text.get("file.ok")).clicked() { dismissed = true; }
+            });
+        if dismissed { state.file_error = None; }
+    }
+}
+
+fn update_window_title(state: Res<EditorState>, mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    let Ok(mut window) = windows.get_single_mut() else { return };
+    let name = state.current_path.as_ref()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| state.ui.get("file.untitled").to_string());
+    let title = format!("{}{} - {}", name, if state.dirty { "*" } else { "" }, state.ui.get("app.window_title"));
+    if window.title != title { window.title = title; }
+}
+
+fn handle_close_requests(
+    mut commands: Commands,
+    mut events: EventReader<WindowCloseRequested>,
+    primary: Query<(), With<PrimaryWindow>>,
+    mut state: ResMut<EditorState>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for event in events.read() {
+        if primary.get(event.window).is_ok() {
+            request_file_action(&mut state, FileAction::Exit, &mut exit);
+        } else {
+            // Secondary windows (the 3D preview) just close; preview3d cleans up after them
+            commands.entity(event.window).despawn_recursive();
+        }
     }
 }
 
 fn draw_left_panel(mut egui_ctx: EguiContexts, mut state: ResMut<EditorState>) {
     egui::SidePanel::left("graph_panel")
         .resizable(true)
-        .default_width(400.0)
+        .default_width(300.0)
         .min_width(200.0)
         .max_width(600.0)
         .show(egui_ctx.ctx_mut(), |ui| {
             // Render headings using an immutable snapshot of UI strings to avoid borrow conflicts
             let ui_clone = state.ui.clone();
-            ui.heading(&ui_clone.graph_panel.title);
-            ui.label(&ui_clone.graph_panel.hint);
-            // Limit the lifetime of the mutable borrow of graph to this block
-            {
-                let graph_ref = &mut state.graph;
-                graph_editor::graph_editor_ui(ui, graph_ref, &ui_clone);
-            }
-            // Clone graph before mutably borrowing engine to avoid E0502
-            let graph_clone = state.graph.clone();
-            if let Some(engine) = &mut state.engine { engine.graph = graph_clone; }
+            ui.heading(ui_clone.get("graph_panel.title"));
+            ui.label(ui_clone.get("graph_panel.hint"));
+            let time = ui.input(|i| i.time);
+            let EditorState { graph, graph_view, selected_channels, .. } = &mut *state;
+            let mut edit = graph_editor::graph_toolbar_ui(ui, graph, graph_view, &ui_clone);
+            ui.separator();
+            edit = graph_editor::inspector_ui(ui, graph, graph_view, &ui_clone).or(edit);
+            ui.separator();
+            edit = graph_editor::outputs_ui(ui, graph, selected_channels, &ui_clone).or(edit);
+            if let Some(edit) = edit { apply_edit(&mut state, edit, time); }
         });
 }
 
 fn draw_preview(mut egui_ctx: EguiContexts, mut state: ResMut<EditorState>) {
-    egui::CentralPanel::default().show(egui_ctx.ctx_mut(), |ui| {
-        let ui_text = state.ui.clone();
-        preview::preview_ui(ui, &mut state, &ui_text);
-    });
+    egui::SidePanel::right("preview_panel")
+        .resizable(true)
+        .default_width(360.0)
+        .show(egui_ctx.ctx_mut(), |ui| {
+            let ui_text = state.ui.clone();
+            preview::preview_ui(ui, &mut state, &ui_text);
+        });
 }
 
-fn spawn_preview_world_window(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut state: ResMut<EditorState>,
-) {
-    if state.show_preview_window && state.preview_window_entity.is_none() {
-        // Spawn secondary OS window
-        let title = state.ui.preview.window_title.clone();
-        let window_entity = commands
-            .spawn(Window {
-                title: title.into(),
-                resolution: WindowResolution::new(960., 640.),
-                ..default()
-            })
-            .id();
-
-        // Choose color based on selected channel
-        let color = match state.preview_channel {
-            0 => Color::rgb(1.0, 0.2, 0.2), // R
-            1 => Color::rgb(0.2, 1.0, 0.2), // G
-            2 => Color::rgb(0.2, 0.2, 1.0), // B
-            _ => Color::rgb(0.8, 0.8, 0.8),
-        };
-
-        // Camera targeting the new window
-        commands.spawn((
-            Camera3d::default(),
-            Transform::from_xyz(20.0, 20.0, 20.0).looking_at(Vec3::ZERO, Vec3::Y),
-            Camera { target: RenderTarget::Window(WindowRef::Entity(window_entity)), ..default() },
-        ));
-
-        // Light
-        commands.spawn((
-            DirectionalLight::default(),
-            Transform::from_xyz(30.0, 50.0, 30.0).looking_at(Vec3::ZERO, Vec3::Y),
-        ));
-
-        // Ground of monochrome cubes (a small grid)
-        let mesh_handle = meshes.add(Mesh::from(Cuboid::new(1.0, 1.0, 1.0)));
-        let material_handle = materials.add(StandardMaterial { base_color: color, ..default() });
-        let size = 16;
-        for x in 0..size {
-            for z in 0..size {
-                commands.spawn(PbrBundle {
-                    mesh: mesh_handle.clone(),
-                    material: material_handle.clone(),
-                    transform: Transform::from_xyz((x as f32) - size as f32 * 0.5, 0.0, (z as f32) - size as f32 * 0.5),
-                    ..default()
-                });
+fn draw_graph_canvas(mut egui_ctx: EguiContexts, mut state: ResMut<EditorState>) {
+    egui::CentralPanel::default()
+        .frame(egui::Frame::none())
+        .show(egui_ctx.ctx_mut(), |ui| {
+            let ui_clone = state.ui.clone();
+            let time = ui.input(|i| i.time);
+            let EditorState { graph, graph_view, .. } = &mut *state;
+            if let Some(edit) = graph_editor::graph_canvas_ui(ui, graph, graph_view, &ui_clone) {
+                apply_edit(&mut state, edit, time);
             }
-        }
-
-        state.preview_window_entity = Some(window_entity);
+        });
+    if state.engine_stale {
+        // Clone graph before mutably borrowing engine to avoid E0502
+        let graph_clone = state.graph.clone();
+        if let Some(engine) = &mut state.engine { engine.set_graph(graph_clone); }
+        state.engine_version += 1;
+        state.engine_stale = false;
     }
 }
-
-fn monitor_preview_window_closed(
-    windows: Query<(), With<Window>>,
-    mut state: ResMut<EditorState>,
-) {
-    if let Some(entity) = state.preview_window_entity {
-        if windows.get(entity).is_err() {
-            state.preview_window_entity = None;
-            state.show_preview_window = false;
-        }
-    }
-}
\ No newline at end of file
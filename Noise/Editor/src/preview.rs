@@ -1,105 +1,547 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use bevy_egui::egui;
 use noise_engine::*;
+use noise_engine::graph::Graph;
+use noise_engine::sampling::SimpleEngine;
+use noise_engine::tiles::{copy_tile, TileJob, TileRect};
 use crate::EditorState;
 use crate::ui_strings::UiStrings;
 
+/// How long the graph has to stay unchanged before the draft is replaced by a full-resolution pass.
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+/// Draft passes sample the same area at this LOD, i.e. every 2^DRAFT_LOD-th point per axis.
+const DRAFT_LOD: u8 = 2;
+/// Side of the tiles a preview pass is split into; finished tiles are shown as they arrive.
+const TILE_SIZE: u32 = 128;
+
+/// One sampled channel as a `w` x `h` grid, row by row.
+pub struct Samples {
+    w: u32,
+    h: u32,
+    data: Vec<f32>,
+}
+
+impl Samples {
+    /// `self` stretched to `w` x `h` by nearest neighbour.
+    fn resized(&self, w: u32, h: u32) -> Samples {
+        let mut data = Vec::with_capacity((w * h) as usize);
+        for y in 0..h {
+            let sy = (y as u64 * self.h as u64 / h as u64) as u32;
+            for x in 0..w {
+                let sx = (x as u64 * self.w as u64 / w as u64) as u32;
+                data.push(self.data[(sy * self.w + sx) as usize]);
+            }
+        }
+        Samples { w, h, data }
+    }
+
+    fn to_image(&self) -> egui::ColorImage {
+        self.image(TileRect { x: 0, y: 0, width: self.w, height: self.h })
+    }
+
+    /// Grayscale image of the samples inside `rect`.
+    fn image(&self, rect: TileRect) -> egui::ColorImage {
+        let mut img = egui::ColorImage::new([rect.width as usize, rect.height as usize], egui::Color32::BLACK);
+        for (row, pixels) in img.pixels.chunks_exact_mut(rect.width.max(1) as usize).enumerate() {
+            let start = ((rect.y as usize + row) * self.w as usize) + rect.x as usize;
+            for (px, v) in pixels.iter_mut().zip(&self.data[start..]) {
+                *px = egui::Color32::from_gray(((v * 0.5 + 0.5).clamp(0.0, 1.0) * 255.0) as u8);
+            }
+        }
+        img
+    }
+}
+
+/// One tiled sampling pass of a channel, filling a sample buffer as tiles finish.
+struct SamplePass {
+    tiles: TileJob,
+    samples: Samples,
+}
+
+impl SamplePass {
+    /// Bakes `graph` (with its own seed) and starts sampling `channel` on the engine's tile pool.
+    /// Until tiles arrive the buffer holds `background` stretched to the new size (or zeros).
+    fn start(graph: Graph, channel: ChannelDesc, w: u32, h: u32, lod: u8, background: Option<&Samples>) -> Self {
+        let engine = SimpleEngine::new(graph);
+        let req = RegionRequest { origin: [0, 0, 0], size: [w, h, 1], lod };
+        let samples = background.map_or_else(|| Samples { w, h, data: vec![0.0; (w * h) as usize] }, |b| b.resized(w, h));
+        Self { tiles: TileJob::spawn(Arc::new(engine), req, ChannelsSpec(vec![channel]), TILE_SIZE), samples }
+    }
+
+    /// Copies finished tiles into the buffer and returns where they landed; a failed tile fails the pass.
+    fn poll(&mut self) -> Result<Vec<TileRect>, NoiseError> {
+        let mut rects = Vec::new();
+        while let Some(tile) = self.tiles.try_recv() {
+            let data = first_channel(tile.result?)?;
+            copy_tile(&mut self.samples.data, [self.samples.w, self.samples.h, 1], tile.rect, &data);
+            rects.push(tile.rect);
+        }
+        Ok(rects)
+    }
+}
+
+/// Uploads `img` into `texture`, creating it on first use.
+fn set_texture(ctx: &egui::Context, texture: &mut Option<egui::TextureHandle>, name: &str, img: egui::ColorImage, options: egui::TextureOptions) {
+    match texture {
+        Some(tex) => tex.set(img, options),
+        None => *texture = Some(ctx.load_texture(name, img, options)),
+    }
+}
+
+/// Background preview sampling: the pass in flight, streaming into the texture, and the samples
+/// from the last pass that finished.
+pub struct LivePreview {
+    pub auto: bool,
+    /// `EditorState::engine_version`, resolution and channel the last request was made for.
+    requested: Option<(u64, i32, i32, usize)>,
+    /// When the graph last changed; a full pass follows once it has settled.
+    changed_at: Instant,
+    full_pending: bool,
+    job: Option<PreviewJob>,
+    texture: Option<egui::TextureHandle>,
+    /// Samples behind `texture`, and whether they came from a draft pass.
+    samples: Option<(Samples, bool)>,
+    /// Bumped whenever `samples` changes.
+    revision: u64,
+}
+
+impl Default for LivePreview {
+    fn default() -> Self {
+        Self {
+            auto: true,
+            requested: None,
+            changed_at: Instant::now(),
+            full_pending: false,
+            job: None,
+            texture: None,
+            samples: None,
+            revision: 0,
+        }
+    }
+}
+
+struct PreviewJob {
+    draft: bool,
+    pass: SamplePass,
+    /// The texture still has the previous pass's size and filtering; the first poll replaces it whole.
+    uploaded: bool,
+}
+
+impl LivePreview {
+    /// Starts a tiled pass. A pass still in flight is cancelled; what it sampled so far stays on
+    /// screen under the new one.
+    fn start(&mut self, state: &EditorState, draft: bool) {
+        let lod = if draft { DRAFT_LOD } else { 0 };
+        let w = ((state.preview_w as u32) >> lod).max(1);
+        let h = ((state.preview_h as u32) >> lod).max(1);
+        self.requested = Some(request_key(state));
+        let shown = self.job.as_ref().map(|j| &j.pass.samples).or(self.samples.as_ref().map(|(s, _)| s));
+        let pass = SamplePass::start(state.graph.clone(), preview_channel(state), w, h, lod, shown);
+        self.job = Some(PreviewJob { draft, pass, uploaded: false });
+    }
+
+    /// Stops the pass in flight; the tiles it finished stay visible.
+    fn cancel(&mut self) {
+        self.job = None;
+        self.full_pending = false;
+    }
+
+    /// Kicks off draft/full passes as the graph changes and streams finished tiles into the texture.
+    fn update(&mut self, ctx: &egui::Context, state: &EditorState) {
+        if self.auto && self.requested != Some(request_key(state)) {
+            self.start(state, true);
+            self.changed_at = Instant::now();
+            self.full_pending = true;
+        }
+        if self.auto && self.full_pending && self.changed_at.elapsed() >= SETTLE_DELAY {
+            self.full_pending = false;
+            self.start(state, false);
+        }
+        if let Some(job) = &mut self.job {
+            let options = if job.draft { egui::TextureOptions::LINEAR } else { egui::TextureOptions::NEAREST };
+            match job.pass.poll() {
+                Ok(_) if !job.uploaded => {
+                    set_texture(ctx, &mut self.texture, "preview", job.pass.samples.to_image(), options);
+                    job.uploaded = true;
+                }
+                Ok(rects) => {
+                    if let Some(tex) = &mut self.texture {
+                        for rect in rects {
+                            tex.set_partial([rect.x as usize, rect.y as usize], job.pass.samples.image(rect), options);
+                        }
+                    }
+                }
+                Err(_) => self.job = None,
+            }
+        }
+        if self.job.as_ref().is_some_and(|j| j.pass.tiles.is_done()) {
+            let job = self.job.take().expect("checked above");
+            self.samples = Some((job.pass.samples, job.draft));
+            self.revision += 1;
+        }
+        if self.job.is_some() || self.full_pending {
+            ctx.request_repaint();
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        self.job.is_some()
+    }
+
+    fn is_draft(&self) -> bool {
+        self.job.as_ref().is_some_and(|j| j.draft) || self.full_pending
+    }
+
+    /// Fraction of the pass in flight that has been sampled.
+    fn progress(&self) -> f32 {
+        self.job.as_ref().map_or(1.0, |j| j.pass.tiles.progress())
+    }
+
+    /// The last full-resolution samples, if the texture isn't showing a draft.
+    fn full_samples(&self) -> Option<&Samples> {
+        self.samples.as_ref().filter(|(_, draft)| !draft).map(|(samples, _)| samples)
+    }
+}
+
+fn request_key(state: &EditorState) -> (u64, i32, i32, usize) {
+    (state.engine_version, state.preview_w, state.preview_h, state.preview_channel)
+}
+
+/// How the A/B comparison is drawn.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CompareMode {
+    SideBySide,
+    /// One image, A left of the split line and B right of it.
+    Swipe,
+    /// abs(A - B) on a color ramp.
+    Difference,
+}
+
+/// min/max/mean of abs(A - B) over the preview.
+#[derive(Clone, Copy)]
+struct DiffStats {
+    min: f32,
+    max: f32,
+    mean: f32,
+}
+
+/// A/B comparison: "A" is a frozen copy of the graph (with its seed) sampled at the preview's
+/// resolution and channel; "B" is the live preview of the graph being edited.
+pub struct Comparison {
+    snapshot: Option<Graph>,
+    pub mode: CompareMode,
+    /// Split position for `CompareMode::Swipe`, 0..1 from the left edge.
+    pub swipe: f32,
+    /// Resolution and channel A was last sampled for.
+    requested: Option<(i32, i32, usize)>,
+    job: Option<SamplePass>,
+    samples: Option<Samples>,
+    texture: Option<egui::TextureHandle>,
+    revision: u64,
+    /// A and B revisions the difference was computed from.
+    diffed: Option<(u64, u64)>,
+    diff_texture: Option<egui::TextureHandle>,
+    stats: Option<DiffStats>,
+}
+
+impl Default for Comparison {
+    fn default() -> Self {
+        Self {
+            snapshot: None,
+            mode: CompareMode::SideBySide,
+            swipe: 0.5,
+            requested: None,
+            job: None,
+            samples: None,
+            texture: None,
+            revision: 0,
+            diffed: None,
+            diff_texture: None,
+            stats: None,
+        }
+    }
+}
+
+impl Comparison {
+    pub fn is_active(&self) -> bool {
+        self.snapshot.is_some()
+    }
+
+    /// Freezes a deep copy of `graph` as A; it is resampled on the next update.
+    pub fn take_snapshot(&mut self, graph: &Graph) {
+        *self = Self { snapshot: Some(graph.clone()), mode: self.mode, swipe: self.swipe, ..Self::default() };
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self { mode: self.mode, swipe: self.swipe, ..Self::default() };
+    }
+
+    /// Resamples A when the resolution or channel changes, and recomputes the difference
+    /// whenever a new A or full-resolution B arrives.
+    fn update(&mut self, ctx: &egui::Context, state: &EditorState, live: &LivePreview) {
+        let Some(graph) = &self.snapshot else { return };
+        let key = (state.preview_w, state.preview_h, state.preview_channel);
+        if self.requested != Some(key) {
+            self.requested = Some(key);
+            let (w, h) = (state.preview_w as u32, state.preview_h as u32);
+            self.job = Some(SamplePass::start(graph.clone(), preview_channel(state), w, h, 0, None));
+        }
+        if self.job.as_mut().is_some_and(|pass| pass.poll().is_err()) {
+            self.job = None;
+        }
+        if self.job.as_ref().is_some_and(|pass| pass.tiles.is_done()) {
+            let pass = self.job.take().expect("checked above");
+            set_texture(ctx, &mut self.texture, "preview_a", pass.samples.to_image(), egui::TextureOptions::NEAREST);
+            self.samples = Some(pass.samples);
+            self.revision += 1;
+        }
+        if self.job.is_some() {
+            ctx.request_repaint();
+        }
+        if self.diffed != Some((self.revision, live.revision)) {
+            self.diffed = Some((self.revision, live.revision));
+            self.stats = None;
+            let (Some(a), Some(b)) = (&self.samples, live.full_samples()) else { return };
+            if (a.w, a.h) != (b.w, b.h) {
+                return;
+            }
+            let (img, stats) = difference(a, b);
+            set_texture(ctx, &mut self.diff_texture, "preview_diff", img, egui::TextureOptions::NEAREST);
+            self.stats = Some(stats);
+        }
+    }
+}
+
+/// abs(A - B) as an image scaled to the largest difference, plus its statistics.
+fn difference(a: &Samples, b: &Samples) -> (egui::ColorImage, DiffStats) {
+    let diffs: Vec<f32> = a.data.iter().zip(&b.data).map(|(x, y)| (x - y).abs()).collect();
+    let min = diffs.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = diffs.iter().copied().fold(0.0, f32::max);
+    let mean = diffs.iter().sum::<f32>() / diffs.len().max(1) as f32;
+    let mut img = egui::ColorImage::new([a.w as usize, a.h as usize], egui::Color32::BLACK);
+    for (px, d) in img.pixels.iter_mut().zip(&diffs) {
+        *px = diff_color(if max > 0.0 { d / max } else { 0.0 });
+    }
+    (img, DiffStats { min: if diffs.is_empty() { 0.0 } else { min }, max, mean })
+}
+
+/// Black → blue → yellow → red ramp for a difference normalized to 0..1.
+fn diff_color(t: f32) -> egui::Color32 {
+    const STOPS: [(f32, [f32; 3]); 4] = [
+        (0.0, [0.0, 0.0, 0.0]),
+        (0.33, [0.1, 0.3, 0.9]),
+        (0.66, [0.95, 0.85, 0.2]),
+        (1.0, [0.9, 0.15, 0.1]),
+    ];
+    let t = t.clamp(0.0, 1.0);
+    for pair in STOPS.windows(2) {
+        let ((t0, c0), (t1, c1)) = (pair[0], pair[1]);
+        if t <= t1 {
+            let f = (t - t0) / (t1 - t0);
+            let channel = |i: usize| ((c0[i] + (c1[i] - c0[i]) * f) * 255.0) as u8;
+            return egui::Color32::from_rgb(channel(0), channel(1), channel(2));
+        }
+    }
+    egui::Color32::RED
+}
+
+/// The channel picked in the preview selector, falling back to a plain height channel.
+pub fn preview_channel(state: &EditorState) -> ChannelDesc {
+    state.selected_channels.get(state.preview_channel).cloned().unwrap_or_else(|| height_channel(&state.selected_channels))
+}
+
+/// The first height channel, which the 3D preview uses for elevation.
+pub fn height_channel(channels: &[ChannelDesc]) -> ChannelDesc {
+    channels
+        .iter()
+        .find(|c| matches!(c.kind, ChannelKind::Height2D))
+        .cloned()
+        .unwrap_or_else(|| ChannelDesc { name: "height".into(), kind: ChannelKind::Height2D })
+}
+
+/// Samples one channel as a flat slice: 2D channels over the region, 3D channels as a single z layer.
+pub fn sample_slice(engine: &SimpleEngine, req: &RegionRequest, channel: ChannelDesc) -> Result<Vec<f32>, NoiseError> {
+    first_channel(engine.sample_region(req, &ChannelsSpec(vec![channel]))?)
+}
+
+/// Samples of the only channel in a single-channel result.
+fn first_channel(res: RegionResult) -> Result<Vec<f32>, NoiseError> {
+    match res.channels.into_iter().next() {
+        Some(ChannelData::Scalar2D { data, .. } | ChannelData::Scalar3D { data, .. }) => Ok(data),
+        None => Err(NoiseError::Sampling("no channel data in result".into())),
+    }
+}
+
+/// Size that fits `w` x `h` into the available space, keeping the aspect ratio and never upscaling.
+fn fit_size(ui: &egui::Ui, w: f32, h: f32) -> egui::Vec2 {
+    let size = egui::Vec2::new(w, h);
+    let available = ui.available_size_before_wrap();
+    let scale = (available.x / size.x).min(available.y / size.y).min(1.0);
+    size * scale
+}
+
+/// Draws the current preview texture scaled to fit, keeping the configured aspect ratio.
+fn show_texture(ui: &mut egui::Ui, live: &LivePreview, w: i32, h: i32) {
+    let Some(tex) = &live.texture else { return };
+    let size = fit_size(ui, w as f32, h as f32);
+    ui.image(egui::load::SizedTexture::new(tex.id(), size));
+}
+
+/// Draws A and B in the selected comparison mode, with the difference statistics below.
+fn show_comparison(ui: &mut egui::Ui, cmp: &mut Comparison, live: &LivePreview, w: i32, h: i32, ui_text: &UiStrings) {
+    let (tex_a, tex_b) = (cmp.texture.as_ref().map(|t| t.id()), live.texture.as_ref().map(|t| t.id()));
+    match cmp.mode {
+        CompareMode::SideBySide => {
+            let half = ui.available_width() * 0.5 - ui.spacing().item_spacing.x;
+            let size = egui::Vec2::new(w as f32, h as f32) * (half / w as f32).min(1.0);
+            ui.horizontal(|ui| {
+                for (label, tex) in [(ui_text.get("preview.label_a"), tex_a), (ui_text.get("preview.label_b"), tex_b)] {
+                    ui.vertical(|ui| {
+                        ui.label(label);
+                        match tex {
+                            Some(id) => { ui.image(egui::load::SizedTexture::new(id, size)); }
+                            None => { ui.spinner(); }
+                        }
+                    });
+                }
+            });
+        }
+        CompareMode::Swipe => {
+            ui.add(egui::Slider::new(&mut cmp.swipe, 0.0..=1.0).text(ui_text.get("preview.swipe")));
+            ui.horizontal(|ui| {
+                ui.label(ui_text.get("preview.label_a"));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| ui.label(ui_text.get("preview.label_b")));
+            });
+            let size = fit_size(ui, w as f32, h as f32);
+            let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+            let split = rect.left() + rect.width() * cmp.swipe;
+            let painter = ui.painter_at(rect);
+            let uv = |x0: f32, x1: f32| egui::Rect::from_min_max(egui::pos2(x0, 0.0), egui::pos2(x1, 1.0));
+            if let Some(id) = tex_a {
+                let left = egui::Rect::from_min_max(rect.min, egui::pos2(split, rect.bottom()));
+                painter.image(id, left, uv(0.0, cmp.swipe), egui::Color32::WHITE);
+            }
+            if let Some(id) = tex_b {
+                let right = egui::Rect::from_min_max(egui::pos2(split, rect.top()), rect.max);
+                painter.image(id, right, uv(cmp.swipe, 1.0), egui::Color32::WHITE);
+            }
+            painter.line_segment([egui::pos2(split, rect.top()), egui::pos2(split, rect.bottom())], egui::Stroke::new(2.0, egui::Color32::from_rgb(240, 200, 80)));
+        }
+        CompareMode::Difference => match (&cmp.diff_texture, cmp.stats) {
+            (Some(tex), Some(_)) => {
+                let size = fit_size(ui, w as f32, h as f32);
+                ui.image(egui::load::SizedTexture::new(tex.id(), size));
+            }
+            _ => { ui.label(ui_text.get("preview.diff_pending")); }
+        },
+    }
+    match cmp.stats {
+        Some(stats) => { ui.label(format!("{} {:.4} / {:.4} / {:.4}", ui_text.get("preview.diff_stats"), stats.min, stats.max, stats.mean)); }
+        None => { ui.label(ui_text.get("preview.diff_pending")); }
+    }
+}
+
+/// Snapshot/clear buttons and the mode selector for the A/B comparison.
+fn comparison_controls_ui(ui: &mut egui::Ui, state: &mut EditorState, ui_text: &UiStrings) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label(ui_text.get("preview.compare"));
+        if ui.button(ui_text.get("preview.snapshot_a")).clicked() {
+            let EditorState { comparison, graph, .. } = state;
+            comparison.take_snapshot(graph);
+        }
+        if ui.add_enabled(state.comparison.is_active(), egui::Button::new(ui_text.get("preview.clear_a"))).clicked() {
+            state.comparison.clear();
+        }
+    });
+    if state.comparison.is_active() {
+        ui.horizontal(|ui| {
+            let mode = &mut state.comparison.mode;
+            ui.radio_value(mode, CompareMode::SideBySide, ui_text.get("preview.mode_side_by_side"));
+            ui.radio_value(mode, CompareMode::Swipe, ui_text.get("preview.mode_swipe"));
+            ui.radio_value(mode, CompareMode::Difference, ui_text.get("preview.mode_difference"));
+        });
+    }
+}
+
+/// The live preview, or the A/B comparison while a snapshot is held.
+fn show_result(ui: &mut egui::Ui, state: &mut EditorState, live: &LivePreview, ui_text: &UiStrings) {
+    let (w, h) = (state.preview_w, state.preview_h);
+    if state.comparison.is_active() {
+        show_comparison(ui, &mut state.comparison, live, w, h, ui_text);
+    } else {
+        show_texture(ui, live, w, h);
+    }
+}
+
 pub fn preview_ui(ui: &mut egui::Ui, state: &mut EditorState, ui_text: &UiStrings) {
-    ui.heading(&ui_text.preview.title);
+    ui.heading(ui_text.get("preview.title"));
 
     ui.horizontal(|ui| {
-        ui.label(&ui_text.preview.resolution);
-        ui.add(egui::Slider::new(&mut state.preview_w, 32..=1024).text(&ui_text.preview.width_short));
-        ui.add(egui::Slider::new(&mut state.preview_h, 32..=1024).text(&ui_text.preview.height_short));
+        ui.label(ui_text.get("preview.resolution"));
+        ui.add(egui::Slider::new(&mut state.preview_w, 32..=1024).text(ui_text.get("preview.width_short")));
+        ui.add(egui::Slider::new(&mut state.preview_h, 32..=1024).text(ui_text.get("preview.height_short")));
     });
 
     ui.horizontal(|ui| {
-        ui.label(&ui_text.preview.channel);
+        ui.label(ui_text.get("preview.channel"));
         egui::ComboBox::from_label("")
-            .selected_text(match state.preview_channel {
-                0 => ui_text.preview.r.clone(),
-                1 => ui_text.preview.g.clone(),
-                2 => ui_text.preview.b.clone(),
-                _ => ui_text.preview.r.clone(),
-            })
+            .selected_text(preview_channel(state).name)
             .show_ui(ui, |ui| {
-                ui.selectable_value(&mut state.preview_channel, 0, &ui_text.preview.r);
-                ui.selectable_value(&mut state.preview_channel, 1, &ui_text.preview.g);
-                ui.selectable_value(&mut state.preview_channel, 2, &ui_text.preview.b);
+                for (i, channel) in state.selected_channels.iter().enumerate() {
+                    ui.selectable_value(&mut state.preview_channel, i, &channel.name);
+                }
             });
     });
+    comparison_controls_ui(ui, state, ui_text);
 
     ui.separator();
 
     // Open popup window button
-    if ui.button(&ui_text.preview.open_window).clicked() {
+    if ui.button(ui_text.get("preview.open_window")).clicked() {
         state.show_preview_window = true;
     }
 
-    if ui.button(&ui_text.preview.generate).clicked() {
-        if let Some(engine) = &mut state.engine {
-            let w = state.preview_w.max(16) as u32;
-            let h = state.preview_h.max(16) as u32;
-            let req = RegionRequest { origin: [0, 0, 0], size: [w, h, 1], lod: 0 };
-            let spec = ChannelsSpec(vec![ChannelDesc { name: "height".into(), kind: ChannelKind::Height2D }]);
-            if let Ok(res) = engine.sample_region(&req, &spec) {
-                if let Some(ChannelData::Scalar2D { data, .. }) = res.channels.get(0) {
-                    let mut img = egui::ColorImage::new([w as usize, h as usize], egui::Color32::BLACK);
-                    for y in 0..h as usize {
-                        for x in 0..w as usize {
-                            let v = data[y * w as usize + x];
-                            let v = ((v * 0.5 + 0.5).clamp(0.0, 1.0) * 255.0) as u8;
-                            img.pixels[y * w as usize + x] = egui::Color32::from_gray(v);
-                        }
-                    }
-                    let tex = ui.ctx().load_texture("preview", img, egui::TextureOptions::NEAREST);
-                    let tex_size = tex.size_vec2();
-                    let available = ui.available_size_before_wrap();
-                    let scale = (available.x / tex_size.x).min(available.y / tex_size.y).min(1.0);
-                    let draw_size = tex_size * scale;
-                    ui.image(egui::load::SizedTexture::new(tex.id(), draw_size));
-                }
-            }
+    // Take the live preview out so it can read the rest of the state while being updated
+    let mut live = std::mem::take(&mut state.live_preview);
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut live.auto, ui_text.get("preview.auto_preview"));
+        if ui.button(ui_text.get("preview.generate")).clicked() {
+            live.full_pending = false;
+            live.start(state, false);
+        }
+        if live.is_busy() && ui.button(ui_text.get("preview.cancel")).clicked() {
+            live.cancel();
         }
+    });
+    if live.is_busy() {
+        let text = if live.is_draft() { ui_text.get("preview.sampling_draft") } else { ui_text.get("preview.sampling") };
+        ui.add(egui::ProgressBar::new(live.progress()).show_percentage().text(text));
     }
+    live.update(ui.ctx(), state);
+    let mut comparison = std::mem::take(&mut state.comparison);
+    comparison.update(ui.ctx(), state, &live);
+    state.comparison = comparison;
+    show_result(ui, state, &live, ui_text);
 
     // Show popup window with the same preview content if toggled
     if state.show_preview_window {
         let mut open = true;
-        egui::Window::new(&ui_text.preview.window_title)
+        egui::Window::new(ui_text.get("preview.window_title"))
             .open(&mut open)
             .resizable(true)
             .vscroll(true)
             .hscroll(true)
             .show(ui.ctx(), |ui| {
-                if ui.button(&ui_text.preview.generate).clicked() {
-                    if let Some(engine) = &mut state.engine {
-                        let w = state.preview_w.max(16) as u32;
-                        let h = state.preview_h.max(16) as u32;
-                        let req = RegionRequest { origin: [0, 0, 0], size: [w, h, 1], lod: 0 };
-                        let spec = ChannelsSpec(vec![ChannelDesc { name: "height".into(), kind: ChannelKind::Height2D }]);
-                        if let Ok(res) = engine.sample_region(&req, &spec) {
-                            if let Some(ChannelData::Scalar2D { data, .. }) = res.channels.get(0) {
-                                let mut img = egui::ColorImage::new([w as usize, h as usize], egui::Color32::BLACK);
-                                for y in 0..h as usize {
-                                    for x in 0..w as usize {
-                                        let v = data[y * w as usize + x];
-                                        let v = ((v * 0.5 + 0.5).clamp(0.0, 1.0) * 255.0) as u8;
-                                        img.pixels[y * w as usize + x] = egui::Color32::from_gray(v);
-                                    }
-                                }
-                                let tex = ui.ctx().load_texture("preview_window", img, egui::TextureOptions::NEAREST);
-                                let tex_size = tex.size_vec2();
-                                let available = ui.available_size_before_wrap();
-                                let scale = (available.x / tex_size.x).min(available.y / tex_size.y).min(1.0);
-                                let draw_size = tex_size * scale;
-                                ui.image(egui::load::SizedTexture::new(tex.id(), draw_size));
-                            }
-                        }
-                    }
+                if ui.button(ui_text.get("preview.generate")).clicked() {
+                    live.full_pending = false;
+                    live.start(state, false);
                 }
+                show_result(ui, state, &live, ui_text);
             });
         if !open {
             state.show_preview_window = false;
         }
     }
-}
\ No newline at end of file
+    state.live_preview = live;
+}
@@ -0,0 +1,287 @@
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::window::{WindowRef, WindowResolution};
+use bevy_egui::{egui, EguiContexts};
+use noise_engine::*;
+use crate::EditorState;
+use crate::preview;
+
+/// Samples per side of the previewed heightmap.
+const GRID: u32 = 64;
+/// Color for the highest value of the channel the mesh is colored by.
+const CHANNEL_TINT: Vec3 = Vec3::new(1.0, 0.6, 0.2);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    Height,
+    Channel,
+}
+
+/// Settings and bookkeeping for the 3D terrain preview window.
+#[derive(Resource)]
+pub struct TerrainPreview {
+    pub amplitude: f32,
+    pub color_mode: ColorMode,
+    /// Forces a rebuild on the next frame even if the engine hasn't changed.
+    regenerate: bool,
+    /// `EditorState::engine_version` the current mesh was built from.
+    built_version: Option<u64>,
+    /// `EditorState::preview_channel` the current mesh was colored by.
+    built_channel: usize,
+    mesh: Option<Handle<Mesh>>,
+}
+
+impl Default for TerrainPreview {
+    fn default() -> Self {
+        Self { amplitude: 16.0, color_mode: ColorMode::Height, regenerate: true, built_version: None, built_channel: 0, mesh: None }
+    }
+}
+
+/// Everything spawned for the preview window, despawned together when it closes.
+#[derive(Component)]
+struct PreviewWorldEntity;
+
+#[derive(Component)]
+struct OrbitCamera {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+impl OrbitCamera {
+    fn transform(&self) -> Transform {
+        let offset = Vec3::new(
+            self.distance * self.pitch.cos() * self.yaw.sin(),
+            self.distance * self.pitch.sin(),
+            self.distance * self.pitch.cos() * self.yaw.cos(),
+        );
+        Transform::from_translation(offset).looking_at(Vec3::ZERO, Vec3::Y)
+    }
+}
+
+pub struct TerrainPreviewPlugin;
+
+impl Plugin for TerrainPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerrainPreview>().add_systems(
+            Update,
+            (
+                spawn_preview_world_window,
+                preview_window_controls,
+                rebuild_terrain,
+                orbit_camera,
+                monitor_preview_window_closed,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn spawn_preview_world_window(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut state: ResMut<EditorState>,
+    mut preview: ResMut<TerrainPreview>,
+) {
+    if !state.show_preview_window || state.preview_window_entity.is_some() {
+        return;
+    }
+    // Spawn secondary OS window
+    let title = state.ui.get("preview.window_title").to_string();
+    let window_entity = commands
+        .spawn(Window {
+            title,
+            resolution: WindowResolution::new(960., 640.),
+            ..default()
+        })
+        .id();
+
+    // Orbit camera targeting the new window
+    let orbit = OrbitCamera { yaw: 0.8, pitch: 0.6, distance: GRID as f32 * 1.2 };
+    commands.spawn((
+        Camera3dBundle {
+            transform: orbit.transform(),
+            camera: Camera { target: RenderTarget::Window(WindowRef::Entity(window_entity)), ..default() },
+            ..default()
+        },
+        orbit,
+        PreviewWorldEntity,
+    ));
+
+    // Light
+    commands.spawn((
+        DirectionalLightBundle {
+            transform: Transform::from_xyz(30.0, 50.0, 30.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+        PreviewWorldEntity,
+    ));
+
+    // Terrain mesh; filled in by rebuild_terrain. Vertex colors carry the shading, so the base color stays white.
+    let mesh = meshes.add(Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default()));
+    commands.spawn((
+        PbrBundle {
+            mesh: mesh.clone(),
+            material: materials.add(StandardMaterial { base_color: Color::WHITE, perceptual_roughness: 0.9, ..default() }),
+            ..default()
+        },
+        PreviewWorldEntity,
+    ));
+    preview.mesh = Some(mesh);
+    preview.regenerate = true;
+
+    state.preview_window_entity = Some(window_entity);
+}
+
+/// Egui controls drawn inside the preview window itself.
+fn preview_window_controls(mut egui_ctx: EguiContexts, state: Res<EditorState>, mut preview: ResMut<TerrainPreview>) {
+    let Some(window) = state.preview_window_entity else { return };
+    let Some(ctx) = egui_ctx.try_ctx_for_window_mut(window) else { return };
+    egui::Window::new(state.ui.get("preview.terrain_controls"))
+        .anchor(egui::Align2::LEFT_TOP, egui::Vec2::new(8.0, 8.0))
+        .resizable(false)
+        .show(ctx, |ui| {
+            let mut changed = ui.add(egui::Slider::new(&mut preview.amplitude, 0.0..=64.0).text(state.ui.get("preview.amplitude"))).changed();
+            ui.horizontal(|ui| {
+                ui.label(state.ui.get("preview.color_by"));
+                changed |= ui.radio_value(&mut preview.color_mode, ColorMode::Height, state.ui.get("preview.color_height")).changed();
+                changed |= ui.radio_value(&mut preview.color_mode, ColorMode::Channel, state.ui.get("preview.color_channel")).changed();
+            });
+            if ui.button(state.ui.get("preview.regenerate")).clicked() || changed {
+                preview.regenerate = true;
+            }
+            ui.label(state.ui.get("preview.orbit_hint"));
+        });
+}
+
+/// Resamples the engine into the terrain mesh when asked to, or when the graph or seed changed.
+fn rebuild_terrain(state: Res<EditorState>, mut preview: ResMut<TerrainPreview>, mut meshes: ResMut<Assets<Mesh>>) {
+    let Some(handle) = preview.mesh.clone() else { return };
+    let channel_changed = preview.color_mode == ColorMode::Channel && preview.built_channel != state.preview_channel;
+    if !preview.regenerate && !channel_changed && preview.built_version == Some(state.engine_version) {
+        return;
+    }
+    let Some(engine) = &state.engine else { return };
+    let half = GRID as i32 / 2;
+    let req = RegionRequest { origin: [-half, -half, 0], size: [GRID, GRID, 1], lod: 0 };
+    let Ok(heights) = preview::sample_slice(engine, &req, preview::height_channel(&state.selected_channels)) else { return };
+    let shade = match preview.color_mode {
+        ColorMode::Height => None,
+        ColorMode::Channel => match preview::sample_slice(engine, &req, preview::preview_channel(&state)) {
+            Ok(values) => Some(values),
+            Err(_) => return,
+        },
+    };
+    meshes.insert(&handle, terrain_mesh(&heights, shade.as_deref(), preview.amplitude));
+    preview.built_version = Some(state.engine_version);
+    preview.built_channel = state.preview_channel;
+    preview.regenerate = false;
+}
+
+/// Builds the heightmap mesh, colored by height or, when `shade` is given, by those channel values.
+fn terrain_mesh(heights: &[f32], shade: Option<&[f32]>, amplitude: f32) -> Mesh {
+    let n = GRID as usize;
+    let h = |x: usize, z: usize| heights[z.min(n - 1) * n + x.min(n - 1)] * amplitude;
+    let offset = GRID as f32 * 0.5;
+    let mut positions = Vec::with_capacity(n * n);
+    let mut normals = Vec::with_capacity(n * n);
+    let mut colors = Vec::with_capacity(n * n);
+    for z in 0..n {
+        for x in 0..n {
+            positions.push([x as f32 - offset, h(x, z), z as f32 - offset]);
+            let dx = h(x + 1, z) - h(x.saturating_sub(1), z);
+            let dz = h(x, z + 1) - h(x, z.saturating_sub(1));
+            normals.push(Vec3::new(-dx, 2.0, -dz).normalize().to_array());
+            let c = match shade {
+                None => height_color((heights[z * n + x] * 0.5 + 0.5).clamp(0.0, 1.0)),
+                Some(values) => CHANNEL_TINT * (0.25 + 0.75 * (values[z * n + x] * 0.5 + 0.5).clamp(0.0, 1.0)),
+            };
+            colors.push([c.x, c.y, c.z, 1.0]);
+        }
+    }
+    let mut indices = Vec::with_capacity((n - 1) * (n - 1) * 6);
+    for z in 0..n as u32 - 1 {
+        for x in 0..n as u32 - 1 {
+            let i = z * GRID + x;
+            indices.extend_from_slice(&[i, i + GRID, i + 1, i + 1, i + GRID, i + GRID + 1]);
+        }
+    }
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Water → grass → rock → snow ramp for a height normalized to 0..1.
+fn height_color(t: f32) -> Vec3 {
+    const STOPS: [(f32, Vec3); 5] = [
+        (0.0, Vec3::new(0.05, 0.15, 0.45)),
+        (0.45, Vec3::new(0.2, 0.45, 0.75)),
+        (0.5, Vec3::new(0.25, 0.55, 0.2)),
+        (0.75, Vec3::new(0.45, 0.38, 0.3)),
+        (1.0, Vec3::new(0.95, 0.95, 0.97)),
+    ];
+    for pair in STOPS.windows(2) {
+        let ((t0, c0), (t1, c1)) = (pair[0], pair[1]);
+        if t <= t1 {
+            return c0.lerp(c1, ((t - t0) / (t1 - t0)).clamp(0.0, 1.0));
+        }
+    }
+    STOPS[STOPS.len() - 1].1
+}
+
+/// Left-drag orbits, scroll zooms — only while the preview window has focus and egui isn't using the pointer.
+fn orbit_camera(
+    mut egui_ctx: EguiContexts,
+    state: Res<EditorState>,
+    windows: Query<&Window>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+    mut cameras: Query<(&mut OrbitCamera, &mut Transform)>,
+) {
+    let drag: Vec2 = motion.read().map(|m| m.delta).sum();
+    let scroll: f32 = wheel.read().map(|w| match w.unit {
+        MouseScrollUnit::Line => w.y,
+        MouseScrollUnit::Pixel => w.y / 40.0,
+    }).sum();
+    let Some(window) = state.preview_window_entity else { return };
+    if !windows.get(window).is_ok_and(|w| w.focused) {
+        return;
+    }
+    if egui_ctx.try_ctx_for_window_mut(window).is_some_and(|ctx| ctx.wants_pointer_input() || ctx.is_pointer_over_area()) {
+        return;
+    }
+    for (mut orbit, mut transform) in cameras.iter_mut() {
+        if buttons.pressed(MouseButton::Left) {
+            orbit.yaw -= drag.x * 0.005;
+            orbit.pitch = (orbit.pitch + drag.y * 0.005).clamp(0.05, 1.5);
+        }
+        orbit.distance = (orbit.distance * (1.0 - scroll * 0.1)).clamp(10.0, GRID as f32 * 4.0);
+        *transform = orbit.transform();
+    }
+}
+
+fn monitor_preview_window_closed(
+    mut commands: Commands,
+    windows: Query<(), With<Window>>,
+    spawned: Query<Entity, With<PreviewWorldEntity>>,
+    mut state: ResMut<EditorState>,
+    mut preview: ResMut<TerrainPreview>,
+) {
+    if let Some(entity) = state.preview_window_entity {
+        if windows.get(entity).is_err() {
+            state.preview_window_entity = None;
+            state.show_preview_window = false;
+            for e in spawned.iter() {
+                commands.entity(e).despawn_recursive();
+            }
+            preview.mesh = None;
+        }
+    }
+}
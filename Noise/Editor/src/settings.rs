@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use mc_common::localization::DEFAULT_LANGUAGE;
+
+const SETTINGS_PATH: &str = "assets/editor_settings.json";
+
+/// Editor preferences kept between sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorSettings {
+    /// Language file code, e.g. "zh_cn".
+    pub language: String,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self { language: DEFAULT_LANGUAGE.to_string() }
+    }
+}
+
+pub fn load_settings() -> EditorSettings {
+    std::fs::read_to_string(SETTINGS_PATH)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_settings(settings: &EditorSettings) {
+    if let Ok(text) = serde_json::to_string_pretty(settings) {
+        let _ = std::fs::write(SETTINGS_PATH, text);
+    }
+}
@@ -1,93 +1,118 @@
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use mc_common::localization::{scan_languages, LanguageData, LanguageInfo, DEFAULT_LANGUAGE};
 
-#[derive(Debug, Deserialize, Clone)]
-#[serde(default)]
+/// Built-in English, so every string has a value even without a localization folder.
+const ENGLISH: &str = include_str!("../localization/en_us.json");
+
+/// UI text looked up by dotted key ("menu.file"), read from the same language files as the game.
+/// Keys missing from the selected language fall back to English, then to the key itself.
+#[derive(Clone)]
 pub struct UiStrings {
-    pub app: AppStrings,
-    pub menu: MenuStrings,
-    pub graph_panel: GraphPanelStrings,
-    pub preview: PreviewStrings,
+    language: String,
+    languages: Arc<Vec<LanguageInfo>>,
+    texts: Arc<HashMap<String, String>>,
+    english: Arc<HashMap<String, String>>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
-#[serde(default)]
-pub struct AppStrings {
-    pub window_title: String,
+impl Default for UiStrings {
+    fn default() -> Self {
+        let english = LanguageData::from_json(ENGLISH).map(|data| non_empty_texts(&data)).unwrap_or_default();
+        Self {
+            language: DEFAULT_LANGUAGE.to_string(),
+            languages: Arc::new(Vec::new()),
+            texts: Arc::new(HashMap::new()),
+            english: Arc::new(english),
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
-#[serde(default)]
-pub struct MenuStrings {
-    pub file: String,
-    pub bake: String,
-    pub save: String,
-    pub load: String,
-}
+impl UiStrings {
+    /// Lists the languages in `dir` and selects `language`; stays on the built-in English if it can't be read.
+    pub fn load(dir: &Path, language: &str) -> Self {
+        let mut strings = Self { languages: Arc::new(scan_languages(dir)), ..Default::default() };
+        let _ = strings.set_language(dir, language);
+        strings
+    }
+
+    /// Switches to the language file `code` in `dir`; the current language is kept on failure.
+    pub fn set_language(&mut self, dir: &Path, code: &str) -> Result<(), String> {
+        let path = LanguageData::path(dir, code);
+        let texts = if code == DEFAULT_LANGUAGE && !path.exists() { HashMap::new() } else { non_empty_texts(&LanguageData::read(&path)?) };
+        self.texts = Arc::new(texts);
+        self.language = code.to_string();
+        Ok(())
+    }
+
+    /// Drops the languages whose code matches `unsupported` from the list, switching to English if the selected one is dropped.
+    pub fn remove_languages(&mut self, unsupported: impl Fn(&str) -> bool) {
+        self.languages = Arc::new(self.languages.iter().filter(|language| !unsupported(&language.code)).cloned().collect());
+        if unsupported(&self.language) {
+            self.language = DEFAULT_LANGUAGE.to_string();
+            self.texts = Arc::new(HashMap::new());
+        }
+    }
+
+    /// Code of the selected language, e.g. "en_us".
+    pub fn language(&self) -> &str {
+        &self.language
+    }
 
-#[derive(Debug, Deserialize, Clone, Default)]
-#[serde(default)]
-pub struct GraphPanelStrings {
-    pub title: String,
-    pub hint: String,
-    pub add_node: String,
-    pub clear: String,
-    pub node_prefix: String,
+    /// Languages found in the localization folder, sorted by code.
+    pub fn languages(&self) -> &[LanguageInfo] {
+        &self.languages
+    }
+
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.lookup(key).unwrap_or(key)
+    }
+
+    /// Text for `group.key`, for keys that come from data (node types, history steps); falls back to `key`.
+    pub fn get_in<'a>(&'a self, group: &str, key: &'a str) -> &'a str {
+        self.lookup(&format!("{group}.{key}")).unwrap_or(key)
+    }
+
+    fn lookup(&self, key: &str) -> Option<&str> {
+        self.texts.get(key).or_else(|| self.english.get(key)).map(String::as_str)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-#[serde(default)]
-pub struct PreviewStrings {
-    pub title: String,
-    pub resolution: String,
-    pub generate: String,
-    pub channel: String,
-    pub r: String,
-    pub g: String,
-    pub b: String,
-    pub width_short: String,
-    pub height_short: String,
-    pub open_window: String,
-    pub window_title: String,
+/// Empty values are dropped so untranslated keys fall back to English.
+fn non_empty_texts(data: &LanguageData) -> HashMap<String, String> {
+    let mut texts = data.flatten();
+    texts.retain(|_, text| !text.is_empty());
+    texts
 }
 
-impl Default for UiStrings {
-    fn default() -> Self {
-        Self {
-            app: AppStrings {
-                window_title: "Noise Editor".to_string(),
-            },
-            menu: MenuStrings {
-                file: "File".to_string(),
-                save: "Save".to_string(),
-                load: "Load".to_string(),
-                bake: "Bake".to_string(),
-            },
-            graph_panel: GraphPanelStrings {
-                title: "Graph".to_string(),
-                hint: "Edit nodes and connections".to_string(),
-                add_node: "Add Node".to_string(),
-                clear: "Clear".to_string(),
-                node_prefix: "Node".to_string(),
-            },
-            preview: PreviewStrings {
-                title: "Preview".to_string(),
-                resolution: "Resolution".to_string(),
-                generate: "Generate".to_string(),
-                channel: "Channel".to_string(),
-                r: "R".to_string(),
-                g: "G".to_string(),
-                b: "B".to_string(),
-                width_short: "W".to_string(),
-                height_short: "H".to_string(),
-                open_window: "Open Preview Window".to_string(),
-                window_title: "Preview".to_string(),
-            },
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_text_falls_back_to_english_then_to_the_key() {
+        let dir = std::env::temp_dir().join(format!("noise_editor_strings_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            LanguageData::path(&dir, "zh_cn"),
+            r#"{ "language_info": { "name": "简体中文", "code": "zh_cn", "author": "", "version": "1" },
+                 "menu": { "file": "文件", "save": "" } }"#,
+        )
+        .unwrap();
+        let mut strings = UiStrings::load(&dir, "zh_cn");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(strings.language(), "zh_cn");
+        assert_eq!(strings.get("menu.file"), "文件");
+        // Empty and missing translations use English, unknown keys show the key
+        assert_eq!(strings.get("menu.save"), strings.english["menu.save"]);
+        assert!(!strings.get("menu.save").is_empty());
+        assert_eq!(strings.get("menu.no_such_key"), "menu.no_such_key");
+        assert_eq!(strings.get_in("node", "NoSuchNode"), "NoSuchNode");
+
+        strings.remove_languages(|code| code == "zh_cn");
+        assert_eq!(strings.language(), DEFAULT_LANGUAGE);
+        assert!(strings.languages().is_empty());
+        assert_eq!(strings.get("menu.file"), strings.english["menu.file"]);
     }
 }
-
-pub fn load_from_file(path: &str) -> anyhow::Result<UiStrings> {
-    let s = std::fs::read_to_string(path)?;
-    let ui: UiStrings = serde_json::from_str(&s)?;
-    Ok(ui)
-}
\ No newline at end of file
@@ -0,0 +1,46 @@
+//! Compares sampling chunks with a fresh engine per chunk (compile and build every noise
+//! generator each time) against sampling them from one shared bake.
+//!
+//! `cargo run --release --example bake_bench`
+use std::time::Instant;
+use noise_engine::graph::{Graph, NodeKind};
+use noise_engine::sampling::SimpleEngine;
+use noise_engine::*;
+
+const CHUNKS: i32 = 2000;
+
+fn graph() -> Graph {
+    let mut g = Graph::default();
+    let mut prev = g.add_node("base".into(), NodeKind::FnlRidged2D { freq: 0.01, seed_offset: 0 }, [0.0, 0.0]);
+    for i in 0..6 {
+        let noise = g.add_node(format!("layer {i}"), NodeKind::FnlSimplex2D { freq: 0.02 * (i + 1) as f32, seed_offset: i }, [0.0, 0.0]);
+        let add = g.add_node(format!("add {i}"), NodeKind::Add, [0.0, 0.0]);
+        assert!(g.connect(prev, add, 0));
+        assert!(g.connect(noise, add, 1));
+        prev = add;
+    }
+    g
+}
+
+fn main() {
+    let graph = graph();
+    let channels = ChannelsSpec(vec![ChannelDesc { name: "height".into(), kind: ChannelKind::Height2D }]);
+    // Single-sample chunks make the setup cost stand out against the sampling itself
+    let chunk = |i: i32| RegionRequest { origin: [i * 16, 0, 0], size: [1, 1, 1], lod: 0 };
+
+    let start = Instant::now();
+    for i in 0..CHUNKS {
+        SimpleEngine::new(graph.clone()).sample_region(&chunk(i), &channels).unwrap();
+    }
+    let per_chunk = start.elapsed();
+
+    let engine = SimpleEngine::new(graph);
+    let start = Instant::now();
+    for i in 0..CHUNKS {
+        engine.sample_region(&chunk(i), &channels).unwrap();
+    }
+    let shared = start.elapsed();
+
+    println!("engine per chunk: {:?} per chunk", per_chunk / CHUNKS as u32);
+    println!("shared bake:      {:?} per chunk", shared / CHUNKS as u32);
+}
@@ -12,6 +12,13 @@ pub enum NoiseError {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Seed(pub u64);
 
+/// A box of samples to compute.
+///
+/// `size` is always the shape of the returned grid. `lod` sets the spacing between samples:
+/// at lod N, neighbouring samples are 2^N world units apart, so the request covers `size * 2^N`
+/// world units starting at `origin`. Sample `(x, y, z)` of a lod N result equals sample
+/// `(x * 2^N, y * 2^N, z * 2^N)` of the lod 0 result for the same origin, which makes coarse
+/// requests cheap previews of the same terrain (editor draft passes, far chunks).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionRequest {
     pub origin: [i32; 3],
@@ -19,6 +26,23 @@ pub struct RegionRequest {
     pub lod: u8,
 }
 
+impl RegionRequest {
+    /// World units between neighbouring samples (2^lod, capped at 2^16).
+    pub fn stride(&self) -> u32 {
+        1 << self.lod.min(16)
+    }
+
+    /// World position of grid sample `(x, y, z)`.
+    pub fn world_pos(&self, x: u32, y: u32, z: u32) -> [f32; 3] {
+        let s = self.stride() as f32;
+        [
+            self.origin[0] as f32 + x as f32 * s,
+            self.origin[1] as f32 + y as f32 * s,
+            self.origin[2] as f32 + z as f32 * s,
+        ]
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelDesc {
     pub name: String,
@@ -54,5 +78,6 @@ pub enum ChannelData {
 pub trait NoiseEngine: Send + Sync {
     fn validate_graph(&self) -> Result<(), NoiseError>;
     fn bake(&mut self, seed: Seed);
+    /// Samples every requested channel over `req`; see [`RegionRequest`] for how `lod` spaces the samples.
     fn sample_region(&self, req: &RegionRequest, channels: &ChannelsSpec) -> Result<RegionResult, NoiseError>;
 }
\ No newline at end of file
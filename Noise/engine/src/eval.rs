@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use fastnoise_lite::{CellularReturnType, DomainWarpType, FastNoiseLite, FractalType, NoiseType};
+use crate::api::*;
+use crate::graph::*;
+
+/// What `bake` precomputes for one graph and seed: per-node noise generators with their parameters
+/// applied, resolved input indices and channel roots. Immutable once built, so a single bake can be
+/// shared through an `Arc` and sampled from any number of threads at once.
+pub struct BakedGraph {
+    nodes: Vec<EvalNode>,
+    /// Channel name -> node index, from the graph's bindings.
+    bindings: HashMap<String, usize>,
+    /// Node every channel uses when the graph has no bindings.
+    output: Option<usize>,
+    /// Generators for the built-in terrain an empty graph samples.
+    builtin: Option<Builtin>,
+}
+
+/// Fixed Perlin/Simplex terrain used when the graph is empty.
+struct Builtin {
+    height: FastNoiseLite,
+    biome: FastNoiseLite,
+    volume: FastNoiseLite,
+}
+
+impl Builtin {
+    fn new(seed: i32) -> Self {
+        let perlin = || {
+            let mut f = FastNoiseLite::with_seed(seed);
+            f.set_noise_type(Some(NoiseType::Perlin));
+            f.set_frequency(Some(0.01));
+            f
+        };
+        let height = perlin();
+        let mut biome = perlin();
+        biome.set_fractal_type(Some(FractalType::FBm));
+        let mut volume = FastNoiseLite::with_seed(seed);
+        volume.set_noise_type(Some(NoiseType::OpenSimplex2));
+        volume.set_frequency(Some(0.02));
+        Self { height, biome, volume }
+    }
+}
+
+struct EvalNode {
+    kind: NodeKind,
+    inputs: Vec<Option<usize>>,
+    noise: Option<FastNoiseLite>,
+}
+
+impl BakedGraph {
+    /// Validates and bakes `graph`; an empty graph bakes the built-in terrain instead.
+    pub fn new(graph: &Graph, seed: u64) -> Result<Self, NoiseError> {
+        if graph.nodes.is_empty() {
+            return Ok(Self { nodes: vec![], bindings: HashMap::new(), output: None, builtin: Some(Builtin::new(seed as i32)) });
+        }
+        CompiledGraph::compile(graph)?;
+        let index: HashMap<u64, usize> = graph.nodes.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
+        let nodes = graph
+            .nodes
+            .iter()
+            .map(|n| EvalNode {
+                kind: sorted_curve(&n.kind),
+                inputs: (0..n.kind.input_count())
+                    .map(|i| graph.input_edge(n.id, i).map(|e| index[&e.from]))
+                    .collect(),
+                // Offset by id so two otherwise identical sources don't produce the same pattern,
+                // plus the node's own offset so reseeding one layer leaves the others alone
+                noise: make_noise(&n.kind, (seed as i32).wrapping_add(n.id as i32).wrapping_add(n.kind.seed_offset())),
+            })
+            .collect();
+        let bindings = graph.bindings.iter().map(|(channel, id)| (channel.clone(), index[id])).collect();
+        let output = if graph.bindings.is_empty() { graph.output_node().map(|id| index[&id]) } else { None };
+        Ok(Self { nodes, bindings, output, builtin: None })
+    }
+
+    /// Samples every requested channel over `req`, like [`NoiseEngine::sample_region`].
+    pub fn sample_region(&self, req: &RegionRequest, channels: &ChannelsSpec) -> Result<RegionResult, NoiseError> {
+        // Every requested channel needs a node before anything is sampled
+        let roots = match self.builtin {
+            Some(_) => vec![0; channels.0.len()],
+            None => channels.0.iter().map(|ch| self.channel_root(&ch.name)).collect::<Result<Vec<_>, _>>()?,
+        };
+        let mut out_channels = Vec::new();
+        for (ch, root) in channels.0.iter().zip(roots) {
+            let [width, height, depth] = req.size;
+            match ch.kind {
+                ChannelKind::Height2D | ChannelKind::Biome2D | ChannelKind::WaterLevel2D => {
+                    let mut data = Vec::with_capacity((width * height) as usize);
+                    for y in 0..height { for x in 0..width {
+                        let [px, py, _] = req.world_pos(x, y, 0);
+                        data.push(match &self.builtin {
+                            Some(b) if matches!(ch.kind, ChannelKind::Biome2D) => b.biome.get_noise_2d(px, py),
+                            Some(b) => b.height.get_noise_2d(px, py),
+                            None => self.sample(root, [px, py, 0.0], false),
+                        });
+                    }}
+                    out_channels.push(ChannelData::Scalar2D { name: ch.name.clone(), width, height, data });
+                }
+                _ => {
+                    let mut data = Vec::with_capacity((width * height * depth) as usize);
+                    for z in 0..depth { for y in 0..height { for x in 0..width {
+                        let p = req.world_pos(x, y, z);
+                        data.push(match &self.builtin {
+                            Some(b) => b.volume.get_noise_3d(p[0], p[1], p[2]),
+                            None => self.sample(root, p, true),
+                        });
+                    }}}
+                    out_channels.push(ChannelData::Scalar3D { name: ch.name.clone(), width, height, depth, data });
+                }
+            }
+        }
+        Ok(RegionResult { origin: req.origin, size: req.size, channels: out_channels })
+    }
+
+    /// Node index to sample for `channel` (see [`Graph::channel_node`]); fails if the channel has no binding.
+    pub fn channel_root(&self, channel: &str) -> Result<usize, NoiseError> {
+        self.bindings
+            .get(channel)
+            .copied()
+            .or(self.output)
+            .ok_or_else(|| NoiseError::GraphValidation(format!("channel {} is not bound to a node", channel)))
+    }
+
+    /// Value of node `root` (from [`BakedGraph::channel_root`]) at `p`. `is_3d` selects 2D or 3D
+    /// sampling for nodes that follow the channel.
+    pub fn sample(&self, root: usize, p: [f32; 3], is_3d: bool) -> f32 {
+        self.eval(root, p, is_3d)
+    }
+
+    fn eval(&self, idx: usize, p: [f32; 3], is_3d: bool) -> f32 {
+        let node = &self.nodes[idx];
+        // Unconnected inputs read as 0
+        let input = |i: usize, p: [f32; 3]| node.inputs[i].map_or(0.0, |j| self.eval(j, p, is_3d));
+        let noise = || node.noise.as_ref().expect("noise node without generator");
+        match &node.kind {
+            NodeKind::Constant(v) => *v,
+            NodeKind::Add => input(0, p) + input(1, p),
+            NodeKind::Sub => input(0, p) - input(1, p),
+            NodeKind::Mul => input(0, p) * input(1, p),
+            NodeKind::Div => {
+                let d = input(1, p);
+                if d == 0.0 { 0.0 } else { input(0, p) / d }
+            }
+            NodeKind::Min => input(0, p).min(input(1, p)),
+            NodeKind::Max => input(0, p).max(input(1, p)),
+            NodeKind::Abs => input(0, p).abs(),
+            NodeKind::Clamp { min, max } => input(0, p).clamp(*min, max.max(*min)),
+            NodeKind::FnlSimplex2D { .. } | NodeKind::FnlPerlin2D { .. } | NodeKind::FnlRidged2D { .. } => {
+                noise().get_noise_2d(p[0], p[1])
+            }
+            NodeKind::FnlSimplex3D { .. } | NodeKind::FnlPerlin3D { .. } | NodeKind::FnlRidged3D { .. } => {
+                noise().get_noise_3d(p[0], p[1], p[2])
+            }
+            // Billow is ridged fBm flipped upside down: per octave 2|n| - 1 instead of 1 - 2|n|
+            NodeKind::FnlBillow { .. } => -sample_dims(noise(), p, is_3d),
+            NodeKind::FnlCellular { .. } => sample_dims(noise(), p, is_3d),
+            NodeKind::Translate { dx, dy, dz } => input(0, [p[0] + dx, p[1] + dy, p[2] + dz]),
+            NodeKind::Scale { sx, sy, sz } => input(0, [p[0] * sx, p[1] * sy, p[2] * sz]),
+            NodeKind::DomainWarp { .. } => {
+                let warped = if is_3d {
+                    let (x, y, z) = noise().domain_warp_3d(p[0], p[1], p[2]);
+                    [x, y, z]
+                } else {
+                    let (x, y) = noise().domain_warp_2d(p[0], p[1]);
+                    [x, y, p[2]]
+                };
+                input(0, warped)
+            }
+            NodeKind::Curve { points } => remap(points, input(0, p)),
+        }
+    }
+}
+
+/// Copy of `kind` with curve points in ascending x order, as [`remap`] expects.
+fn sorted_curve(kind: &NodeKind) -> NodeKind {
+    let mut kind = kind.clone();
+    if let NodeKind::Curve { points } = &mut kind {
+        points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    }
+    kind
+}
+
+/// Piecewise-linear interpolation through `points` (sorted by x), flat beyond either end.
+/// A curve without points passes its input through.
+fn remap(points: &[[f32; 2]], x: f32) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else { return x };
+    if x <= first[0] || x.is_nan() {
+        return first[1];
+    }
+    if x >= last[0] {
+        return last[1];
+    }
+    // first[0] < x < last[0], so the segment [a, b] with a.x <= x < b.x exists
+    let i = points.partition_point(|p| p[0] <= x);
+    let (a, b) = (points[i - 1], points[i]);
+    a[1] + (b[1] - a[1]) * (x - a[0]) / (b[0] - a[0])
+}
+
+fn sample_dims(noise: &FastNoiseLite, p: [f32; 3], is_3d: bool) -> f32 {
+    if is_3d { noise.get_noise_3d(p[0], p[1], p[2]) } else { noise.get_noise_2d(p[0], p[1]) }
+}
+
+fn make_noise(kind: &NodeKind, seed: i32) -> Option<FastNoiseLite> {
+    let mut f = FastNoiseLite::with_seed(seed);
+    match kind {
+        NodeKind::FnlSimplex2D { freq, .. } | NodeKind::FnlSimplex3D { freq, .. } => {
+            f.set_noise_type(Some(NoiseType::OpenSimplex2));
+            f.set_frequency(Some(*freq));
+        }
+        NodeKind::FnlPerlin2D { freq, .. } | NodeKind::FnlPerlin3D { freq, .. } => {
+            f.set_noise_type(Some(NoiseType::Perlin));
+            f.set_frequency(Some(*freq));
+        }
+        NodeKind::FnlRidged2D { freq, .. } | NodeKind::FnlRidged3D { freq, .. } | NodeKind::FnlBillow { freq, .. } => {
+            f.set_noise_type(Some(NoiseType::OpenSimplex2));
+            f.set_fractal_type(Some(FractalType::Ridged));
+            f.set_frequency(Some(*freq));
+        }
+        NodeKind::FnlCellular { freq, jitter, return_type, .. } => {
+            f.set_noise_type(Some(NoiseType::Cellular));
+            f.set_frequency(Some(*freq));
+            f.set_cellular_jitter(Some(*jitter));
+            f.set_cellular_return_type(Some(cellular_return_type(*return_type)));
+        }
+        NodeKind::DomainWarp { amp, freq } => {
+            f.set_domain_warp_type(Some(DomainWarpType::OpenSimplex2));
+            f.set_domain_warp_amp(Some(*amp));
+            f.set_frequency(Some(*freq));
+        }
+        _ => return None,
+    }
+    Some(f)
+}
+
+fn cellular_return_type(r: CellularReturn) -> CellularReturnType {
+    match r {
+        CellularReturn::CellValue => CellularReturnType::CellValue,
+        CellularReturn::Distance => CellularReturnType::Distance,
+        CellularReturn::Distance2 => CellularReturnType::Distance2,
+        CellularReturn::Distance2Add => CellularReturnType::Distance2Add,
+        CellularReturn::Distance2Sub => CellularReturnType::Distance2Sub,
+        CellularReturn::Distance2Mul => CellularReturnType::Distance2Mul,
+        CellularReturn::Distance2Div => CellularReturnType::Distance2Div,
+    }
+}
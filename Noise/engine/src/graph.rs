@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use crate::api::{ChannelDesc, NoiseError};
 use petgraph::graph::NodeIndex;
 use petgraph::stable_graph::StableDiGraph;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NodeKind {
@@ -13,14 +15,187 @@ pub enum NodeKind {
     Max,
     Abs,
     Clamp { min: f32, max: f32 },
-    // noise sources
-    FnlSimplex2D { freq: f32 },
-    FnlPerlin2D { freq: f32 },
-    FnlSimplex3D { freq: f32 },
-    FnlPerlin3D { freq: f32 },
+    // noise sources; `seed_offset` is added to the graph seed so otherwise identical nodes differ
+    FnlSimplex2D { freq: f32, #[serde(default)] seed_offset: i32 },
+    FnlPerlin2D { freq: f32, #[serde(default)] seed_offset: i32 },
+    FnlSimplex3D { freq: f32, #[serde(default)] seed_offset: i32 },
+    FnlPerlin3D { freq: f32, #[serde(default)] seed_offset: i32 },
     // domain ops
     Translate { dx: f32, dy: f32, dz: f32 },
     Scale { sx: f32, sy: f32, sz: f32 },
+    // fractal / cellular noise sources
+    FnlRidged2D { freq: f32, #[serde(default)] seed_offset: i32 },
+    FnlRidged3D { freq: f32, #[serde(default)] seed_offset: i32 },
+    /// Billow (|n|-based fBm); follows the dimension of the channel being sampled.
+    FnlBillow { freq: f32, #[serde(default)] seed_offset: i32 },
+    /// Cellular (Worley) noise; follows the dimension of the channel being sampled.
+    FnlCellular { freq: f32, jitter: f32, return_type: CellularReturn, #[serde(default)] seed_offset: i32 },
+    /// Offsets the sample position of its input by a noise-driven warp.
+    DomainWarp { amp: f32, freq: f32 },
+    /// Remaps its input through a piecewise-linear curve of `[x, y]` control points; inputs
+    /// outside the points' x range take the value of the nearest end point.
+    Curve { points: Vec<[f32; 2]> },
+}
+
+/// What a cellular node outputs; mirrors fastnoise_lite's `CellularReturnType`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CellularReturn {
+    CellValue,
+    Distance,
+    Distance2,
+    Distance2Add,
+    Distance2Sub,
+    Distance2Mul,
+    Distance2Div,
+}
+
+impl CellularReturn {
+    pub const ALL: [CellularReturn; 7] = [
+        CellularReturn::CellValue,
+        CellularReturn::Distance,
+        CellularReturn::Distance2,
+        CellularReturn::Distance2Add,
+        CellularReturn::Distance2Sub,
+        CellularReturn::Distance2Mul,
+        CellularReturn::Distance2Div,
+    ];
+
+    /// Stable snake_case key, used to look up display names in UI string tables.
+    pub fn key(&self) -> &'static str {
+        match self {
+            CellularReturn::CellValue => "cell_value",
+            CellularReturn::Distance => "distance",
+            CellularReturn::Distance2 => "distance2",
+            CellularReturn::Distance2Add => "distance2_add",
+            CellularReturn::Distance2Sub => "distance2_sub",
+            CellularReturn::Distance2Mul => "distance2_mul",
+            CellularReturn::Distance2Div => "distance2_div",
+        }
+    }
+}
+
+impl NodeKind {
+    /// Number of input ports the node reads from.
+    pub fn input_count(&self) -> usize {
+        match self {
+            NodeKind::Add | NodeKind::Sub | NodeKind::Mul | NodeKind::Div | NodeKind::Min | NodeKind::Max => 2,
+            NodeKind::Abs | NodeKind::Clamp { .. } | NodeKind::Translate { .. } | NodeKind::Scale { .. } | NodeKind::DomainWarp { .. } | NodeKind::Curve { .. } => 1,
+            NodeKind::Constant(_)
+            | NodeKind::FnlSimplex2D { .. }
+            | NodeKind::FnlPerlin2D { .. }
+            | NodeKind::FnlSimplex3D { .. }
+            | NodeKind::FnlPerlin3D { .. }
+            | NodeKind::FnlRidged2D { .. }
+            | NodeKind::FnlRidged3D { .. }
+            | NodeKind::FnlBillow { .. }
+            | NodeKind::FnlCellular { .. } => 0,
+        }
+    }
+
+    /// Stable snake_case key, used to look up display names in UI string tables.
+    pub fn type_key(&self) -> &'static str {
+        match self {
+            NodeKind::Constant(_) => "constant",
+            NodeKind::Add => "add",
+            NodeKind::Sub => "sub",
+            NodeKind::Mul => "mul",
+            NodeKind::Div => "div",
+            NodeKind::Min => "min",
+            NodeKind::Max => "max",
+            NodeKind::Abs => "abs",
+            NodeKind::Clamp { .. } => "clamp",
+            NodeKind::FnlSimplex2D { .. } => "fnl_simplex_2d",
+            NodeKind::FnlPerlin2D { .. } => "fnl_perlin_2d",
+            NodeKind::FnlSimplex3D { .. } => "fnl_simplex_3d",
+            NodeKind::FnlPerlin3D { .. } => "fnl_perlin_3d",
+            NodeKind::Translate { .. } => "translate",
+            NodeKind::Scale { .. } => "scale",
+            NodeKind::FnlRidged2D { .. } => "fnl_ridged_2d",
+            NodeKind::FnlRidged3D { .. } => "fnl_ridged_3d",
+            NodeKind::FnlBillow { .. } => "fnl_billow",
+            NodeKind::FnlCellular { .. } => "fnl_cellular",
+            NodeKind::DomainWarp { .. } => "domain_warp",
+            NodeKind::Curve { .. } => "curve",
+        }
+    }
+
+    /// Mutable access to the node's float parameters, keyed by the same snake_case names the
+    /// editor uses for its parameter labels.
+    pub fn params_mut(&mut self) -> Vec<(&'static str, &mut f32)> {
+        match self {
+            NodeKind::Constant(v) => vec![("value", v)],
+            NodeKind::Clamp { min, max } => vec![("min", min), ("max", max)],
+            NodeKind::FnlSimplex2D { freq, .. }
+            | NodeKind::FnlPerlin2D { freq, .. }
+            | NodeKind::FnlSimplex3D { freq, .. }
+            | NodeKind::FnlPerlin3D { freq, .. }
+            | NodeKind::FnlRidged2D { freq, .. }
+            | NodeKind::FnlRidged3D { freq, .. }
+            | NodeKind::FnlBillow { freq, .. } => vec![("frequency", freq)],
+            NodeKind::FnlCellular { freq, jitter, .. } => vec![("frequency", freq), ("jitter", jitter)],
+            NodeKind::DomainWarp { amp, freq } => vec![("amplitude", amp), ("frequency", freq)],
+            NodeKind::Translate { dx, dy, dz } => vec![("dx", dx), ("dy", dy), ("dz", dz)],
+            NodeKind::Scale { sx, sy, sz } => vec![("sx", sx), ("sy", sy), ("sz", sz)],
+            NodeKind::Add | NodeKind::Sub | NodeKind::Mul | NodeKind::Div | NodeKind::Min | NodeKind::Max | NodeKind::Abs | NodeKind::Curve { .. } => vec![],
+        }
+    }
+
+    /// The per-node seed offset of noise sources; `None` for kinds without one.
+    pub fn seed_offset_mut(&mut self) -> Option<&mut i32> {
+        match self {
+            NodeKind::FnlSimplex2D { seed_offset, .. }
+            | NodeKind::FnlPerlin2D { seed_offset, .. }
+            | NodeKind::FnlSimplex3D { seed_offset, .. }
+            | NodeKind::FnlPerlin3D { seed_offset, .. }
+            | NodeKind::FnlRidged2D { seed_offset, .. }
+            | NodeKind::FnlRidged3D { seed_offset, .. }
+            | NodeKind::FnlBillow { seed_offset, .. }
+            | NodeKind::FnlCellular { seed_offset, .. } => Some(seed_offset),
+            _ => None,
+        }
+    }
+
+    /// Seed offset added to the graph seed for this node; 0 for kinds without one.
+    pub fn seed_offset(&self) -> i32 {
+        match self {
+            NodeKind::FnlSimplex2D { seed_offset, .. }
+            | NodeKind::FnlPerlin2D { seed_offset, .. }
+            | NodeKind::FnlSimplex3D { seed_offset, .. }
+            | NodeKind::FnlPerlin3D { seed_offset, .. }
+            | NodeKind::FnlRidged2D { seed_offset, .. }
+            | NodeKind::FnlRidged3D { seed_offset, .. }
+            | NodeKind::FnlBillow { seed_offset, .. }
+            | NodeKind::FnlCellular { seed_offset, .. } => *seed_offset,
+            _ => 0,
+        }
+    }
+
+    /// One instance of every kind with default parameters, in menu order.
+    pub fn palette() -> Vec<NodeKind> {
+        vec![
+            NodeKind::Constant(0.0),
+            NodeKind::Add,
+            NodeKind::Sub,
+            NodeKind::Mul,
+            NodeKind::Div,
+            NodeKind::Min,
+            NodeKind::Max,
+            NodeKind::Abs,
+            NodeKind::Clamp { min: -1.0, max: 1.0 },
+            NodeKind::FnlSimplex2D { freq: 0.01, seed_offset: 0 },
+            NodeKind::FnlPerlin2D { freq: 0.01, seed_offset: 0 },
+            NodeKind::FnlSimplex3D { freq: 0.02, seed_offset: 0 },
+            NodeKind::FnlPerlin3D { freq: 0.02, seed_offset: 0 },
+            NodeKind::Translate { dx: 0.0, dy: 0.0, dz: 0.0 },
+            NodeKind::Scale { sx: 1.0, sy: 1.0, sz: 1.0 },
+            NodeKind::FnlRidged2D { freq: 0.01, seed_offset: 0 },
+            NodeKind::FnlRidged3D { freq: 0.02, seed_offset: 0 },
+            NodeKind::FnlBillow { freq: 0.01, seed_offset: 0 },
+            NodeKind::FnlCellular { freq: 0.02, jitter: 1.0, return_type: CellularReturn::Distance, seed_offset: 0 },
+            NodeKind::DomainWarp { amp: 30.0, freq: 0.01 },
+            NodeKind::Curve { points: vec![[-1.0, -1.0], [1.0, 1.0]] },
+        ]
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,22 +203,203 @@ pub struct Node {
     pub id: u64,
     pub name: String,
     pub kind: NodeKind,
+    /// Editor canvas position; ignored by the engine.
+    #[serde(default)]
+    pub pos: [f32; 2],
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Edge {
     pub from: u64,
     pub to: u64,
+    /// Input port index on the `to` node.
+    #[serde(default)]
+    pub input: usize,
+}
+
+/// Seed for new graphs and for files saved before graphs carried one.
+pub const DEFAULT_SEED: u64 = 1337;
+
+fn default_seed() -> u64 {
+    DEFAULT_SEED
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Graph {
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    /// Base seed the graph is baked with; each noise node adds its id and seed offset.
+    #[serde(default = "default_seed")]
+    pub seed: u64,
+    /// Output channel name -> id of the node that produces it. A graph with no bindings
+    /// evaluates [`Graph::output_node`] for every channel.
+    #[serde(default)]
+    pub bindings: BTreeMap<String, u64>,
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self { nodes: vec![], edges: vec![], seed: DEFAULT_SEED, bindings: BTreeMap::new() }
+    }
+}
+
+impl Graph {
+    pub fn node(&self, id: u64) -> Option<&Node> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    pub fn node_mut(&mut self, id: u64) -> Option<&mut Node> {
+        self.nodes.iter_mut().find(|n| n.id == id)
+    }
+
+    /// Smallest id greater than every id currently in use.
+    pub fn next_id(&self) -> u64 {
+        self.nodes.iter().map(|n| n.id).max().unwrap_or(0) + 1
+    }
+
+    pub fn add_node(&mut self, name: String, kind: NodeKind, pos: [f32; 2]) -> u64 {
+        let id = self.next_id();
+        self.nodes.push(Node { id, name, kind, pos });
+        id
+    }
+
+    /// Removes a node together with every edge touching it and any channel bound to it.
+    pub fn remove_node(&mut self, id: u64) {
+        self.nodes.retain(|n| n.id != id);
+        self.edges.retain(|e| e.from != id && e.to != id);
+        self.bindings.retain(|_, node| *node != id);
+    }
+
+    /// Removes every node, edge and channel binding.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+        self.bindings.clear();
+    }
+
+    /// The node whose value a sample returns: the last node nothing else reads from.
+    pub fn output_node(&self) -> Option<u64> {
+        self.nodes.iter().rev().find(|n| !self.edges.iter().any(|e| e.from == n.id)).map(|n| n.id)
+    }
+
+    /// Binds `channel` to `node`, or unbinds it when `node` is `None`.
+    pub fn bind(&mut self, channel: &str, node: Option<u64>) {
+        match node {
+            Some(id) => { self.bindings.insert(channel.to_string(), id); }
+            None => { self.bindings.remove(channel); }
+        }
+    }
+
+    /// The node a channel evaluates: its binding, or the output node while the graph has no bindings at all.
+    pub fn channel_node(&self, channel: &str) -> Option<u64> {
+        if self.bindings.is_empty() {
+            self.output_node()
+        } else {
+            self.bindings.get(channel).copied()
+        }
+    }
+
+    /// Names of the requested channels that have no node to evaluate.
+    pub fn unbound_channels<'a>(&self, channels: &'a [ChannelDesc]) -> Vec<&'a str> {
+        channels.iter().filter(|c| self.channel_node(&c.name).is_none()).map(|c| c.name.as_str()).collect()
+    }
+
+    /// Changes a node's kind in place, keeping its id, name and position.
+    /// Parameters with the same name and the seed offset carry over; edges into inputs the new kind no longer has are dropped.
+    pub fn set_kind(&mut self, id: u64, mut kind: NodeKind) {
+        let Some(node) = self.node_mut(id) else { return; };
+        {
+            let old: Vec<(&'static str, f32)> = node.kind.params_mut().into_iter().map(|(k, v)| (k, *v)).collect();
+            for (key, value) in kind.params_mut() {
+                if let Some((_, v)) = old.iter().find(|(k, _)| *k == key) { *value = *v; }
+            }
+        }
+        let offset = node.kind.seed_offset();
+        if let Some(new_offset) = kind.seed_offset_mut() { *new_offset = offset; }
+        let inputs = kind.input_count();
+        node.kind = kind;
+        self.edges.retain(|e| e.to != id || e.input < inputs);
+    }
+
+    /// The edge feeding input `input` of node `to`, if any.
+    pub fn input_edge(&self, to: u64, input: usize) -> Option<&Edge> {
+        self.edges.iter().find(|e| e.to == to && e.input == input)
+    }
+
+    /// Connects `from`'s output to input `input` of `to`, replacing whatever was plugged in there.
+    /// Returns false (and leaves the graph untouched) if the port doesn't exist or the edge would form a cycle.
+    pub fn connect(&mut self, from: u64, to: u64, input: usize) -> bool {
+        let Some(target) = self.node(to) else { return false; };
+        if input >= target.kind.input_count() || self.node(from).is_none() {
+            return false;
+        }
+        if from == to || self.reaches(to, from) {
+            return false;
+        }
+        self.disconnect(to, input);
+        self.edges.push(Edge { from, to, input });
+        true
+    }
+
+    /// Removes the edge feeding input `input` of `to` and returns it.
+    pub fn disconnect(&mut self, to: u64, input: usize) -> Option<Edge> {
+        let idx = self.edges.iter().position(|e| e.to == to && e.input == input)?;
+        Some(self.edges.remove(idx))
+    }
+
+    /// Whether `target` is reachable from `start` by following edges downstream.
+    fn reaches(&self, start: u64, target: u64) -> bool {
+        let mut stack = vec![start];
+        let mut seen = std::collections::HashSet::new();
+        while let Some(id) = stack.pop() {
+            if id == target { return true; }
+            if !seen.insert(id) { continue; }
+            stack.extend(self.edges.iter().filter(|e| e.from == id).map(|e| e.to));
+        }
+        false
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct CompiledGraph {
-    pub graph: StableDiGraph<Node, ()>,
+    /// Edge weights are the input port index on the target node.
+    pub graph: StableDiGraph<Node, usize>,
     pub id_to_index: std::collections::HashMap<u64, NodeIndex>,
-}
\ No newline at end of file
+}
+
+impl CompiledGraph {
+    /// Builds the petgraph view of `graph`, rejecting empty graphs, dangling or duplicate edges,
+    /// edges into ports the target doesn't have, channels bound to missing nodes, and cycles.
+    pub fn compile(graph: &Graph) -> Result<Self, NoiseError> {
+        if graph.nodes.is_empty() {
+            return Err(NoiseError::GraphValidation("empty graph".into()));
+        }
+        let mut g = StableDiGraph::new();
+        let mut id_to_index = std::collections::HashMap::new();
+        for n in &graph.nodes {
+            if id_to_index.insert(n.id, g.add_node(n.clone())).is_some() {
+                return Err(NoiseError::GraphValidation(format!("duplicate node id {}", n.id)));
+            }
+        }
+        let mut used = std::collections::HashSet::new();
+        for e in &graph.edges {
+            let (Some(&from), Some(&to)) = (id_to_index.get(&e.from), id_to_index.get(&e.to)) else {
+                return Err(NoiseError::GraphValidation(format!("edge {} -> {} references a missing node", e.from, e.to)));
+            };
+            if e.input >= g[to].kind.input_count() {
+                return Err(NoiseError::GraphValidation(format!("node {} has no input {}", e.to, e.input)));
+            }
+            if !used.insert((e.to, e.input)) {
+                return Err(NoiseError::GraphValidation(format!("input {} of node {} is connected twice", e.input, e.to)));
+            }
+            g.add_edge(from, to, e.input);
+        }
+        if let Some((channel, id)) = graph.bindings.iter().find(|(_, id)| !id_to_index.contains_key(id)) {
+            return Err(NoiseError::GraphValidation(format!("channel {} is bound to missing node {}", channel, id)));
+        }
+        if petgraph::algo::is_cyclic_directed(&g) {
+            return Err(NoiseError::GraphValidation("graph contains a cycle".into()));
+        }
+        Ok(Self { graph: g, id_to_index })
+    }
+}
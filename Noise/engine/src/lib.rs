@@ -1,5 +1,7 @@
 pub mod graph;
 pub mod sampling;
 pub mod api;
+pub mod eval;
+pub mod tiles;
 
 pub use api::*;
\ No newline at end of file
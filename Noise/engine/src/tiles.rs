@@ -0,0 +1,200 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, OnceLock};
+use parking_lot::Mutex;
+use crate::api::*;
+
+/// Part of a region, in samples from the region's first sample. Tiles always span the full depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A finished tile: where it goes in the region and the sampled channels, in request order.
+pub struct Tile {
+    pub rect: TileRect,
+    pub result: Result<RegionResult, NoiseError>,
+}
+
+/// Splits an x/y grid of `size` into `tile_size` squares, row by row; edge tiles are cut short.
+pub fn tile_rects(size: [u32; 3], tile_size: u32) -> Vec<TileRect> {
+    let step = tile_size.max(1);
+    let mut rects = Vec::new();
+    for y in (0..size[1]).step_by(step as usize) {
+        for x in (0..size[0]).step_by(step as usize) {
+            rects.push(TileRect { x, y, width: step.min(size[0] - x), height: step.min(size[1] - y) });
+        }
+    }
+    rects
+}
+
+/// The part of `req` covered by `rect`; samples land on the same world positions as in `req`.
+pub fn tile_request(req: &RegionRequest, rect: TileRect) -> RegionRequest {
+    let stride = req.stride() as i32;
+    RegionRequest {
+        origin: [req.origin[0] + rect.x as i32 * stride, req.origin[1] + rect.y as i32 * stride, req.origin[2]],
+        size: [rect.width, rect.height, req.size[2]],
+        lod: req.lod,
+    }
+}
+
+/// Copies one tile's samples (`rect.width` x `rect.height` x depth, x fastest) into the full grid `dst` of `size`.
+pub fn copy_tile(dst: &mut [f32], size: [u32; 3], rect: TileRect, src: &[f32]) {
+    let (w, h, tw) = (size[0] as usize, size[1] as usize, rect.width as usize);
+    for (row, line) in src.chunks_exact(tw.max(1)).enumerate() {
+        let z = row / rect.height as usize;
+        let y = rect.y as usize + row % rect.height as usize;
+        let start = (z * h + y) * w + rect.x as usize;
+        dst[start..start + tw].copy_from_slice(line);
+    }
+}
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// Worker threads shared by every tile job: one per available core, started on first use and kept for the
+/// life of the process. Jobs queue one task per tile, so cancelled jobs only leave cheap no-op tasks behind.
+fn tile_pool() -> &'static Sender<Task> {
+    static POOL: OnceLock<Sender<Task>> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = std::thread::available_parallelism().map_or(4, |n| n.get());
+        for i in 0..workers {
+            let receiver = receiver.clone();
+            std::thread::Builder::new()
+                .name(format!("noise-tiles-{i}"))
+                .spawn(move || loop {
+                    // The lock is only held while waiting, not while a tile is sampled
+                    let Ok(task) = receiver.lock().recv() else { break };
+                    // A panicking tile drops its result sender; the job sees it as missing, the worker lives on
+                    let _ = panic::catch_unwind(AssertUnwindSafe(task));
+                })
+                .expect("failed to start a tile worker thread");
+        }
+        sender
+    })
+}
+
+/// Samples a region tile by tile on the shared worker pool. Tiles arrive in completion order;
+/// cancelling (or dropping the job) skips the tiles no worker has started yet.
+pub struct TileJob {
+    req: RegionRequest,
+    channels: ChannelsSpec,
+    /// Behind a lock only so the job can live in `Sync` state (e.g. ECS resources); one owner polls it.
+    receiver: Mutex<Receiver<Tile>>,
+    cancelled: Arc<AtomicBool>,
+    total: usize,
+    received: usize,
+}
+
+impl TileJob {
+    /// Starts sampling `req` in `tile_size` tiles on the shared worker pool.
+    pub fn spawn<E: NoiseEngine + 'static>(engine: Arc<E>, req: RegionRequest, channels: ChannelsSpec, tile_size: u32) -> Self {
+        let rects = tile_rects(req.size, tile_size);
+        let total = rects.len();
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let shared = Arc::new((req.clone(), channels.clone()));
+        for rect in rects {
+            let (engine, shared, cancelled, sender) = (engine.clone(), shared.clone(), cancelled.clone(), sender.clone());
+            let task: Task = Box::new(move || {
+                if cancelled.load(Ordering::Relaxed) {
+                    return;
+                }
+                let (req, channels) = &*shared;
+                let result = engine.sample_region(&tile_request(req, rect), channels);
+                // The job may have been dropped meanwhile; nobody is waiting for the tile then
+                let _ = sender.send(Tile { rect, result });
+            });
+            tile_pool().send(task).expect("tile workers never exit");
+        }
+        Self { req, channels, receiver: Mutex::new(receiver), cancelled, total, received: 0 }
+    }
+
+    /// Number of tiles the region was split into.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Number of tiles handed out by [`TileJob::try_recv`] / [`TileJob::recv`] so far.
+    pub fn received(&self) -> usize {
+        self.received
+    }
+
+    /// Fraction of tiles received, 0..=1.
+    pub fn progress(&self) -> f32 {
+        if self.total == 0 { 1.0 } else { self.received as f32 / self.total as f32 }
+    }
+
+    /// Every tile has been received.
+    pub fn is_done(&self) -> bool {
+        self.received == self.total
+    }
+
+    /// Stops handing out tiles; workers finish the tile they are on and skip the rest.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// The next finished tile, without blocking.
+    pub fn try_recv(&mut self) -> Option<Tile> {
+        let tile = self.receiver.get_mut().try_recv().ok()?;
+        self.received += 1;
+        Some(tile)
+    }
+
+    /// Waits for the next finished tile; `None` once every tile was received or the job was cancelled.
+    pub fn recv(&mut self) -> Option<Tile> {
+        if self.is_done() || self.is_cancelled() {
+            return None;
+        }
+        let tile = self.receiver.get_mut().recv().ok()?;
+        self.received += 1;
+        Some(tile)
+    }
+
+    /// Waits for the remaining tiles and assembles them into one result, as
+    /// [`NoiseEngine::sample_region`] would have returned it. Stops at the first failed tile.
+    pub fn wait(mut self) -> Result<RegionResult, NoiseError> {
+        let [width, height, depth] = self.req.size;
+        // 2D channels are a single layer whatever the request's depth
+        let mut channels: Vec<ChannelData> = self
+            .channels
+            .0
+            .iter()
+            .map(|ch| match ch.kind {
+                ChannelKind::Height2D | ChannelKind::Biome2D | ChannelKind::WaterLevel2D => {
+                    ChannelData::Scalar2D { name: ch.name.clone(), width, height, data: vec![0.0; (width * height) as usize] }
+                }
+                _ => ChannelData::Scalar3D { name: ch.name.clone(), width, height, depth, data: vec![0.0; (width * height * depth) as usize] },
+            })
+            .collect();
+        while let Some(tile) = self.recv() {
+            for (dst, src) in channels.iter_mut().zip(tile.result?.channels) {
+                let (ChannelData::Scalar2D { data: src, .. } | ChannelData::Scalar3D { data: src, .. }) = src;
+                match dst {
+                    ChannelData::Scalar2D { data, .. } => copy_tile(data, [width, height, 1], tile.rect, &src),
+                    ChannelData::Scalar3D { data, .. } => copy_tile(data, [width, height, depth], tile.rect, &src),
+                }
+            }
+        }
+        if !self.is_done() {
+            return Err(NoiseError::Sampling("tiled sampling was cancelled".into()));
+        }
+        Ok(RegionResult { origin: self.req.origin, size: self.req.size, channels })
+    }
+}
+
+impl Drop for TileJob {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
@@ -0,0 +1,49 @@
+use noise_engine::graph::{Graph, NodeKind};
+use noise_engine::sampling::SimpleEngine;
+use noise_engine::*;
+
+fn channel(name: &str, kind: ChannelKind) -> ChannelDesc {
+    ChannelDesc { name: name.into(), kind }
+}
+
+fn first_sample(data: &ChannelData) -> f32 {
+    match data {
+        ChannelData::Scalar2D { data, .. } | ChannelData::Scalar3D { data, .. } => data[0],
+    }
+}
+
+#[test]
+fn each_channel_samples_its_bound_node() {
+    let mut g = Graph::default();
+    let height = g.add_node("height".into(), NodeKind::Constant(0.25), [0.0, 0.0]);
+    let caves = g.add_node("caves".into(), NodeKind::Constant(-0.5), [0.0, 0.0]);
+    g.bind("height", Some(height));
+    g.bind("caves", Some(caves));
+    let g: Graph = ron::from_str(&ron::ser::to_string(&g).unwrap()).unwrap();
+
+    let mut engine = SimpleEngine::new(g.clone());
+    engine.bake(Seed(1));
+    let req = RegionRequest { origin: [0, 0, 0], size: [2, 2, 2], lod: 0 };
+    let spec = ChannelsSpec(vec![channel("height", ChannelKind::Height2D), channel("caves", ChannelKind::Cave3D)]);
+    let res = engine.sample_region(&req, &spec).unwrap();
+    assert_eq!(first_sample(&res.channels[0]), 0.25);
+    assert_eq!(first_sample(&res.channels[1]), -0.5);
+
+    // Once anything is bound, requesting an unbound channel is an error
+    let biome = [channel("biome", ChannelKind::Biome2D)];
+    assert_eq!(g.unbound_channels(&biome), vec!["biome"]);
+    assert!(engine.sample_region(&req, &ChannelsSpec(biome.to_vec())).is_err());
+
+    let mut g = g;
+    g.remove_node(caves);
+    assert_eq!(g.channel_node("caves"), None);
+    assert_eq!(g.channel_node("height"), Some(height));
+}
+
+#[test]
+fn unbound_graphs_use_the_output_node_for_every_channel() {
+    let g: Graph = ron::from_str("(nodes: [(id: 1, name: \"N\", kind: Constant(1.0))], edges: [])").unwrap();
+    assert!(g.bindings.is_empty());
+    assert!(g.unbound_channels(&[channel("height", ChannelKind::Height2D), channel("biome", ChannelKind::Biome2D)]).is_empty());
+    assert_eq!(g.channel_node("anything"), Some(1));
+}
@@ -0,0 +1,21 @@
+//! Helpers shared by the integration tests.
+
+use noise_engine::*;
+
+/// A 2D height channel and a 3D cave channel.
+pub fn channels() -> ChannelsSpec {
+    ChannelsSpec(vec![
+        ChannelDesc { name: "height".into(), kind: ChannelKind::Height2D },
+        ChannelDesc { name: "caves".into(), kind: ChannelKind::Cave3D },
+    ])
+}
+
+/// The sampled values of every channel, in request order.
+pub fn data(res: RegionResult) -> Vec<Vec<f32>> {
+    res.channels
+        .into_iter()
+        .map(|c| match c {
+            ChannelData::Scalar2D { data, .. } | ChannelData::Scalar3D { data, .. } => data,
+        })
+        .collect()
+}
@@ -0,0 +1,58 @@
+use noise_engine::graph::{Graph, NodeKind};
+use noise_engine::sampling::SimpleEngine;
+use noise_engine::*;
+
+/// Feeds the constant `x` through a curve node and samples the result.
+fn remap(points: &[[f32; 2]], x: f32) -> f32 {
+    let mut g = Graph::default();
+    let input = g.add_node("x".into(), NodeKind::Constant(x), [0.0, 0.0]);
+    let curve = g.add_node("curve".into(), NodeKind::Curve { points: points.to_vec() }, [200.0, 0.0]);
+    assert!(g.connect(input, curve, 0));
+    let mut engine = SimpleEngine::new(g);
+    engine.bake(Seed(1));
+    let req = RegionRequest { origin: [0, 0, 0], size: [1, 1, 1], lod: 0 };
+    let channel = ChannelDesc { name: "height".into(), kind: ChannelKind::Height2D };
+    match engine.sample_region(&req, &ChannelsSpec(vec![channel])).unwrap().channels.into_iter().next().unwrap() {
+        ChannelData::Scalar2D { data, .. } | ChannelData::Scalar3D { data, .. } => data[0],
+    }
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-6, "got {actual}, expected {expected}");
+}
+
+const FLATTEN_OCEANS: [[f32; 2]; 4] = [[-1.0, -0.2], [0.0, 0.0], [0.5, 0.1], [1.0, 1.0]];
+
+#[test]
+fn control_points_map_exactly() {
+    for [x, y] in FLATTEN_OCEANS {
+        assert_close(remap(&FLATTEN_OCEANS, x), y);
+    }
+}
+
+#[test]
+fn values_between_points_interpolate_linearly() {
+    assert_close(remap(&FLATTEN_OCEANS, -0.5), -0.1);
+    assert_close(remap(&FLATTEN_OCEANS, 0.25), 0.05);
+    assert_close(remap(&FLATTEN_OCEANS, 0.75), 0.55);
+}
+
+#[test]
+fn out_of_range_inputs_clamp_to_the_end_points() {
+    assert_close(remap(&FLATTEN_OCEANS, -3.0), -0.2);
+    assert_close(remap(&FLATTEN_OCEANS, 1.5), 1.0);
+}
+
+#[test]
+fn unsorted_points_are_evaluated_in_x_order() {
+    let shuffled = [[1.0, 1.0], [-1.0, -0.2], [0.5, 0.1], [0.0, 0.0]];
+    assert_close(remap(&shuffled, 0.75), 0.55);
+    assert_close(remap(&shuffled, -0.5), -0.1);
+}
+
+#[test]
+fn degenerate_curves() {
+    assert_close(remap(&[], 0.3), 0.3);
+    assert_close(remap(&[[0.2, 0.7]], -1.0), 0.7);
+    assert_close(remap(&[[0.2, 0.7]], 1.0), 0.7);
+}
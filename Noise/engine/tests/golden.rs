@@ -0,0 +1,122 @@
+//! Small sampled tiles compared against stored values, so noise output stays deterministic across changes.
+use noise_engine::graph::{CellularReturn, Graph, NodeKind};
+use noise_engine::sampling::SimpleEngine;
+use noise_engine::*;
+
+/// Chains `kinds` so each node feeds input 0 of the next, then samples a 4x4 tile from the last.
+fn tile(kinds: Vec<NodeKind>, kind: ChannelKind) -> Vec<f32> {
+    let mut g = Graph::default();
+    let mut prev = None;
+    for k in kinds {
+        let id = g.add_node("n".into(), k, [0.0, 0.0]);
+        if let Some(p) = prev {
+            assert!(g.connect(p, id, 0));
+        }
+        prev = Some(id);
+    }
+    let mut engine = SimpleEngine::new(g);
+    engine.bake(Seed(1337));
+    let req = RegionRequest { origin: [10, -20, 5], size: [4, 4, 1], lod: 0 };
+    let res = engine.sample_region(&req, &ChannelsSpec(vec![ChannelDesc { name: "c".into(), kind }])).unwrap();
+    match res.channels.into_iter().next().unwrap() {
+        ChannelData::Scalar2D { data, .. } | ChannelData::Scalar3D { data, .. } => data,
+    }
+}
+
+fn assert_tile(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        assert!((a - e).abs() < 1e-5, "sample {i}: got {a}, expected {e}");
+    }
+}
+
+#[test]
+fn ridged_2d() {
+    assert_tile(&tile(vec![NodeKind::FnlRidged2D { freq: 0.05, seed_offset: 0 }], ChannelKind::Height2D), &[
+        0.37101942, 0.35329404, 0.26033127, 0.19788496, 0.5294416, 0.30685905, 0.17856005, 0.25518963,
+        0.5361982, 0.4517489, 0.6299453, 0.37985396, 0.31608498, 0.4280803, 0.28947082, 0.22205605,
+    ]);
+}
+
+#[test]
+fn ridged_3d() {
+    assert_tile(&tile(vec![NodeKind::FnlRidged3D { freq: 0.05, seed_offset: 0 }], ChannelKind::Cave3D), &[
+        -0.18452224, 0.10087906, 0.41805074, 0.33163393, -0.33989668, -0.051620144, 0.49638602, 0.35903764,
+        -0.30130583, -0.01535356, 0.4838316, 0.53055286, -0.30847654, -0.003125839, 0.48157203, 0.6979306,
+    ]);
+}
+
+#[test]
+fn billow_is_inverted_ridged() {
+    let billow = tile(vec![NodeKind::FnlBillow { freq: 0.05, seed_offset: 0 }], ChannelKind::Height2D);
+    let ridged = tile(vec![NodeKind::FnlRidged2D { freq: 0.05, seed_offset: 0 }], ChannelKind::Height2D);
+    let negated: Vec<f32> = ridged.iter().map(|v| -v).collect();
+    assert_tile(&billow, &negated);
+}
+
+#[test]
+fn cellular_distance() {
+    let kind = NodeKind::FnlCellular { freq: 0.1, jitter: 1.0, return_type: CellularReturn::Distance, seed_offset: 0 };
+    assert_tile(&tile(vec![kind], ChannelKind::Height2D), &[
+        -0.80901706, -0.7303659, -0.6317147, -0.72271967, -0.74492145, -0.66627026, -0.686067, -0.77839524,
+        -0.6608259, -0.60941434, -0.7217425, -0.8140708, -0.5567303, -0.6250899, -0.7374182, -0.8297464,
+    ]);
+}
+
+#[test]
+fn domain_warped_simplex() {
+    let kinds = vec![NodeKind::FnlSimplex2D { freq: 0.05, seed_offset: 0 }, NodeKind::DomainWarp { amp: 20.0, freq: 0.02 }];
+    assert_tile(&tile(kinds, ChannelKind::Height2D), &[
+        -0.1869369, -0.11472203, -0.06252822, -0.027066322, -0.26644027, -0.21310784, -0.18095954, -0.1666644,
+        -0.269324, -0.2525928, -0.2579692, -0.2809958, -0.23180713, -0.26290688, -0.31403673, -0.37862822,
+    ]);
+}
+
+#[test]
+fn new_kinds_roundtrip_through_ron() {
+    let mut g = Graph::default();
+    for kind in NodeKind::palette() {
+        g.add_node("n".into(), kind, [0.0, 0.0]);
+    }
+    let text = ron::ser::to_string(&g).unwrap();
+    let back: Graph = ron::from_str(&text).unwrap();
+    let keys: Vec<_> = back.nodes.iter().map(|n| n.kind.type_key()).collect();
+    assert_eq!(keys, NodeKind::palette().iter().map(|k| k.type_key()).collect::<Vec<_>>());
+}
+
+/// One simplex node with the given seed offset, baked with `seed`, sampled as a 4x4 tile.
+fn offset_tile(seed: u64, seed_offset: i32) -> Vec<f32> {
+    let mut g = Graph::default();
+    g.add_node("n".into(), NodeKind::FnlSimplex2D { freq: 0.05, seed_offset }, [0.0, 0.0]);
+    let mut engine = SimpleEngine::new(g);
+    engine.bake(Seed(seed));
+    let req = RegionRequest { origin: [10, -20, 5], size: [4, 4, 1], lod: 0 };
+    let res = engine.sample_region(&req, &ChannelsSpec(vec![ChannelDesc { name: "c".into(), kind: ChannelKind::Height2D }])).unwrap();
+    match res.channels.into_iter().next().unwrap() {
+        ChannelData::Scalar2D { data, .. } | ChannelData::Scalar3D { data, .. } => data,
+    }
+}
+
+#[test]
+fn seed_offsets_shift_the_base_seed() {
+    assert_tile(&offset_tile(1337, 5), &offset_tile(1337, 5));
+    // Offsets add to the base seed, so these pairs are the same noise
+    assert_tile(&offset_tile(1337, 5), &offset_tile(1342, 0));
+    assert_tile(&offset_tile(1337, -3), &offset_tile(1334, 0));
+    assert_ne!(offset_tile(1337, 0), offset_tile(1337, 1));
+    assert_ne!(offset_tile(1337, 1), offset_tile(7, 1));
+}
+
+#[test]
+fn graph_seed_and_offsets_roundtrip_and_default_for_old_files() {
+    let mut g = Graph { seed: 99, ..Graph::default() };
+    let id = g.add_node("n".into(), NodeKind::FnlPerlin3D { freq: 0.02, seed_offset: -4 }, [0.0, 0.0]);
+    let back: Graph = ron::from_str(&ron::ser::to_string(&g).unwrap()).unwrap();
+    assert_eq!(back.seed, 99);
+    assert_eq!(back.node(id).unwrap().kind.seed_offset(), -4);
+    assert_eq!(SimpleEngine::new(back).seed(), 99);
+
+    let old: Graph = ron::from_str("(nodes: [(id: 1, name: \"N\", kind: FnlSimplex2D(freq: 0.01))], edges: [])").unwrap();
+    assert_eq!(old.seed, noise_engine::graph::DEFAULT_SEED);
+    assert_eq!(old.nodes[0].kind.seed_offset(), 0);
+}
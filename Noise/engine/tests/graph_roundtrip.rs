@@ -0,0 +1,57 @@
+use noise_engine::graph::{Graph, NodeKind};
+
+fn edited_graph() -> Graph {
+    let mut g = Graph::default();
+    let a = g.add_node("A".into(), NodeKind::FnlSimplex2D { freq: 0.01, seed_offset: 0 }, [0.0, 0.0]);
+    let b = g.add_node("B".into(), NodeKind::Constant(0.0), [200.0, 40.0]);
+    let c = g.add_node("C".into(), NodeKind::Add, [400.0, 0.0]);
+    assert!(g.connect(a, c, 0));
+    assert!(g.connect(b, c, 1));
+    for (key, v) in g.node_mut(a).unwrap().kind.params_mut() {
+        if key == "frequency" { *v = 0.0375; }
+    }
+    for (_, v) in g.node_mut(b).unwrap().kind.params_mut() { *v = -2.5; }
+    g
+}
+
+#[test]
+fn edited_parameters_survive_ron_roundtrip() {
+    let g = edited_graph();
+    let text = ron::ser::to_string_pretty(&g, ron::ser::PrettyConfig::new()).unwrap();
+    let back: Graph = ron::from_str(&text).unwrap();
+    assert!(matches!(back.node(1).unwrap().kind, NodeKind::FnlSimplex2D { freq, .. } if freq == 0.0375));
+    assert!(matches!(back.node(2).unwrap().kind, NodeKind::Constant(v) if v == -2.5));
+    assert_eq!(back.node(2).unwrap().pos, [200.0, 40.0]);
+    assert_eq!(back.edges.len(), 2);
+    assert!(back.input_edge(3, 1).is_some_and(|e| e.from == 2));
+}
+
+#[test]
+fn curve_points_survive_ron_roundtrip_exactly() {
+    let points = vec![[-1.0, -0.2], [0.1, 1.0 / 3.0], [0.3, -0.0], [1.0, f32::MIN_POSITIVE]];
+    let mut g = Graph::default();
+    g.add_node("curve".into(), NodeKind::Curve { points: points.clone() }, [0.0, 0.0]);
+    let text = ron::ser::to_string_pretty(&g, ron::ser::PrettyConfig::new()).unwrap();
+    let back: Graph = ron::from_str(&text).unwrap();
+    let NodeKind::Curve { points: restored } = &back.node(1).unwrap().kind else { panic!("not a curve") };
+    let bits = |p: &[[f32; 2]]| p.iter().flat_map(|[x, y]| [x.to_bits(), y.to_bits()]).collect::<Vec<_>>();
+    assert_eq!(bits(restored), bits(&points));
+}
+
+#[test]
+fn set_kind_keeps_id_params_and_fitting_edges() {
+    let mut g = edited_graph();
+    g.set_kind(1, NodeKind::FnlPerlin3D { freq: 0.02, seed_offset: 0 });
+    assert!(matches!(g.node(1).unwrap().kind, NodeKind::FnlPerlin3D { freq, .. } if freq == 0.0375));
+    g.set_kind(3, NodeKind::Abs);
+    assert_eq!(g.node(3).unwrap().name, "C");
+    assert!(g.input_edge(3, 0).is_some());
+    assert!(g.input_edge(3, 1).is_none());
+}
+
+#[test]
+fn old_files_without_positions_still_load() {
+    let text = "(nodes: [(id: 1, name: \"N\", kind: Constant(1.0))], edges: [])";
+    let g: Graph = ron::from_str(text).unwrap();
+    assert_eq!(g.nodes[0].pos, [0.0, 0.0]);
+}
@@ -0,0 +1,54 @@
+use noise_engine::graph::{Graph, NodeKind};
+use noise_engine::sampling::SimpleEngine;
+use noise_engine::*;
+
+fn engine(graph: Graph) -> SimpleEngine {
+    let mut engine = SimpleEngine::new(graph);
+    engine.bake(Seed(42));
+    engine
+}
+
+fn sample(engine: &SimpleEngine, kind: ChannelKind, size: [u32; 3], lod: u8) -> Vec<f32> {
+    let req = RegionRequest { origin: [-7, 13, 3], size, lod };
+    let res = engine.sample_region(&req, &ChannelsSpec(vec![ChannelDesc { name: "c".into(), kind }])).unwrap();
+    match res.channels.into_iter().next().unwrap() {
+        ChannelData::Scalar2D { data, .. } | ChannelData::Scalar3D { data, .. } => data,
+    }
+}
+
+fn warped_graph() -> Graph {
+    let mut g = Graph::default();
+    let a = g.add_node("a".into(), NodeKind::FnlSimplex3D { freq: 0.03, seed_offset: 0 }, [0.0, 0.0]);
+    let b = g.add_node("b".into(), NodeKind::DomainWarp { amp: 10.0, freq: 0.02 }, [0.0, 0.0]);
+    assert!(g.connect(a, b, 0));
+    g
+}
+
+#[test]
+fn lod_2_matches_lod_0_at_shared_points_2d() {
+    for graph in [Graph::default(), warped_graph()] {
+        let e = engine(graph);
+        let fine = sample(&e, ChannelKind::Height2D, [16, 16, 1], 0);
+        let coarse = sample(&e, ChannelKind::Height2D, [4, 4, 1], 2);
+        assert_eq!(coarse.len(), 16);
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(coarse[y * 4 + x], fine[(y * 4) * 16 + x * 4], "at ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn lod_2_matches_lod_0_at_shared_points_3d() {
+    let e = engine(warped_graph());
+    let fine = sample(&e, ChannelKind::Cave3D, [8, 8, 8], 0);
+    let coarse = sample(&e, ChannelKind::Cave3D, [2, 2, 2], 2);
+    for z in 0..2 {
+        for y in 0..2 {
+            for x in 0..2 {
+                assert_eq!(coarse[(z * 2 + y) * 2 + x], fine[((z * 4) * 8 + y * 4) * 8 + x * 4]);
+            }
+        }
+    }
+}
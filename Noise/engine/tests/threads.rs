@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use noise_engine::eval::BakedGraph;
+use noise_engine::graph::{CellularReturn, Graph, NodeKind};
+use noise_engine::sampling::SimpleEngine;
+use noise_engine::*;
+use common::{channels, data};
+
+mod common;
+
+fn layered_graph() -> Graph {
+    let mut g = Graph::default();
+    let base = g.add_node("base".into(), NodeKind::FnlRidged2D { freq: 0.01, seed_offset: 0 }, [0.0, 0.0]);
+    let cells = g.add_node(
+        "cells".into(),
+        NodeKind::FnlCellular { freq: 0.05, jitter: 1.0, return_type: CellularReturn::Distance, seed_offset: 3 },
+        [0.0, 0.0],
+    );
+    let add = g.add_node("add".into(), NodeKind::Add, [0.0, 0.0]);
+    let warp = g.add_node("warp".into(), NodeKind::DomainWarp { amp: 12.0, freq: 0.02 }, [0.0, 0.0]);
+    assert!(g.connect(base, add, 0));
+    assert!(g.connect(cells, add, 1));
+    assert!(g.connect(add, warp, 0));
+    g
+}
+
+fn chunk(i: i32) -> RegionRequest {
+    RegionRequest { origin: [i * 16, -i * 16, 0], size: [16, 16, 2], lod: 0 }
+}
+
+#[test]
+fn engines_and_bakes_are_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SimpleEngine>();
+    assert_send_sync::<BakedGraph>();
+}
+
+#[test]
+fn concurrent_sampling_matches_single_threaded() {
+    for graph in [Graph::default(), layered_graph()] {
+        let engine = SimpleEngine::new(graph);
+        let expected: Vec<_> = (0..32).map(|i| data(engine.sample_region(&chunk(i), &channels()).unwrap())).collect();
+        let baked = engine.baked().expect("graph bakes");
+        std::thread::scope(|scope| {
+            for t in 0..8 {
+                let (engine, baked, expected) = (&engine, Arc::clone(&baked), &expected);
+                scope.spawn(move || {
+                    for round in 0..2 {
+                        for i in 0..32 {
+                            // Alternate between the engine and the shared bake, starting at a different chunk per thread
+                            let i = (i + t * 4) % 32;
+                            let res = if round % 2 == 0 {
+                                engine.sample_region(&chunk(i), &channels())
+                            } else {
+                                baked.sample_region(&chunk(i), &channels())
+                            };
+                            assert_eq!(data(res.unwrap()), expected[i as usize], "thread {t}, chunk {i}");
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
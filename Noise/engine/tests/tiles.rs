@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use noise_engine::graph::{Graph, NodeKind};
+use noise_engine::sampling::SimpleEngine;
+use noise_engine::tiles::{tile_rects, TileJob, TileRect};
+use noise_engine::*;
+use common::{channels, data};
+
+mod common;
+
+fn engine() -> Arc<SimpleEngine> {
+    let mut g = Graph::default();
+    let a = g.add_node("a".into(), NodeKind::FnlSimplex3D { freq: 0.03, seed_offset: 0 }, [0.0, 0.0]);
+    let b = g.add_node("b".into(), NodeKind::DomainWarp { amp: 10.0, freq: 0.02 }, [0.0, 0.0]);
+    assert!(g.connect(a, b, 0));
+    let mut engine = SimpleEngine::new(g);
+    engine.bake(Seed(42));
+    Arc::new(engine)
+}
+
+#[test]
+fn tiles_cover_the_grid_with_short_edge_tiles() {
+    let rects = tile_rects([10, 7, 1], 4);
+    assert_eq!(rects.len(), 6);
+    assert_eq!(rects[2], TileRect { x: 8, y: 0, width: 2, height: 4 });
+    assert_eq!(rects[5], TileRect { x: 8, y: 4, width: 2, height: 3 });
+    let covered: u32 = rects.iter().map(|r| r.width * r.height).sum();
+    assert_eq!(covered, 70);
+}
+
+#[test]
+fn tiled_sampling_matches_one_region_call() {
+    let engine = engine();
+    for lod in [0, 1] {
+        let req = RegionRequest { origin: [-13, 7, 2], size: [37, 21, 3], lod };
+        let whole = data(engine.sample_region(&req, &channels()).unwrap());
+        let tiled = data(TileJob::spawn(engine.clone(), req, channels(), 8).wait().unwrap());
+        assert_eq!(tiled, whole, "lod {lod}");
+    }
+}
+
+#[test]
+fn cancelled_jobs_stop_handing_out_tiles() {
+    let req = RegionRequest { origin: [0, 0, 0], size: [256, 256, 1], lod: 0 };
+    let mut job = TileJob::spawn(engine(), req, channels(), 16);
+    assert_eq!(job.total(), 256);
+    job.cancel();
+    assert!(job.recv().is_none());
+    assert!(!job.is_done());
+    assert!(job.wait().is_err());
+}
@@ -0,0 +1,289 @@
+//! 游戏和启动器共用的存档元数据：世界信息（world_info.json）的类型、读写，以及存档目录的布局。
+//! 两边都通过这里读写世界信息，启动器不会因为不认识新版本游戏写入的字段而丢掉它们。
+//! `localization` 是游戏和噪声编辑器共用的语言文件格式，`paths` 是游戏和启动器共用的游戏根目录查找规则
+
+pub mod localization;
+pub mod paths;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 游戏根目录下的存档目录，每个世界一个子目录，目录名就是世界名（游戏的 `--world` 参数）
+pub const SAVES_DIR: &str = "saves";
+/// 世界目录中的世界信息文件
+pub const WORLD_INFO_FILE: &str = "world_info.json";
+/// 世界目录中的统计文件，启动器从中读取游玩时间
+pub const STATS_FILE: &str = "stats.json";
+
+/// 当前的存档格式版本，等于游戏中最后一个迁移步骤（`game_state::migrations`）的版本
+pub const SAVE_FORMAT_VERSION: u32 = 5;
+/// 没有版本号的旧存档的格式版本
+pub const LEGACY_SAVE_FORMAT_VERSION: u32 = 1;
+
+/// 世界信息中时间的格式（UTC），按字符串排序即按时间排序
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// 当前时间，用于世界的创建和最后游玩时间
+pub fn timestamp_now() -> String {
+    chrono::Utc::now().format(TIME_FORMAT).to_string()
+}
+
+/// 新世界的随机种子
+pub fn random_seed() -> u32 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    // RandomState 每次创建时的密钥随机，再混入当前时间
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write(timestamp_now().as_bytes());
+    hasher.finish() as u32
+}
+
+/// 世界存档信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldInfo {
+    pub name: String,
+    pub seed: u32,
+    pub created_time: String,
+    pub last_played: String,
+    pub game_mode: GameMode,
+    pub world_type: WorldType,
+    /// 存档格式版本；没有这个字段的旧存档为版本 1
+    #[serde(default = "legacy_save_format_version")]
+    pub save_format_version: u32,
+    /// 各持久化部分（区块、玩家、统计等）的格式版本
+    #[serde(default)]
+    pub component_versions: BTreeMap<String, u32>,
+    /// 不认识的字段（例如更新版本的游戏写入的），写回时原样保留
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+fn legacy_save_format_version() -> u32 {
+    LEGACY_SAVE_FORMAT_VERSION
+}
+
+/// 新世界的信息。各部分的格式版本由游戏在创建世界时填写
+impl Default for WorldInfo {
+    fn default() -> Self {
+        Self {
+            name: "新世界".to_string(),
+            seed: 12345,
+            created_time: timestamp_now(),
+            last_played: timestamp_now(),
+            game_mode: GameMode::Creative,
+            world_type: WorldType::Default,
+            save_format_version: SAVE_FORMAT_VERSION,
+            component_versions: BTreeMap::new(),
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+impl WorldInfo {
+    /// 存档来自更新版本的游戏，这个版本不能打开
+    pub fn is_newer_version(&self) -> bool {
+        self.save_format_version > SAVE_FORMAT_VERSION
+    }
+
+    pub fn path(world_dir: &Path) -> PathBuf {
+        world_dir.join(WORLD_INFO_FILE)
+    }
+
+    /// 读取世界目录中的世界信息
+    pub fn read(world_dir: &Path) -> Result<Self, String> {
+        let path = Self::path(world_dir);
+        let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        Self::from_json(&content).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    /// 读取世界列表中显示的信息，见 `from_json_for_listing`
+    pub fn read_for_listing(world_dir: &Path) -> Result<Self, String> {
+        let path = Self::path(world_dir);
+        let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        Self::from_json_for_listing(&content).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+    }
+
+    /// 与 `from_json` 相同，但更新版本的游戏写入了这个版本不认识的值（例如新的游戏模式）时，
+    /// 只取名称、格式版本和时间，其余字段为默认值，世界仍然出现在列表中并显示为更新版本的存档
+    pub fn from_json_for_listing(json: &str) -> Result<Self, String> {
+        let error = match Self::from_json(json) {
+            Ok(info) => return Ok(info),
+            Err(e) => e,
+        };
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let version = value.get("save_format_version").and_then(|v| v.as_u64()).and_then(|v| u32::try_from(v).ok());
+        let (Some(name), Some(save_format_version)) = (value.get("name").and_then(|v| v.as_str()), version) else {
+            return Err(error);
+        };
+        if save_format_version <= SAVE_FORMAT_VERSION {
+            return Err(error);
+        }
+        let text = |key: &str| value.get(key).and_then(|v| v.as_str()).map(str::to_string).unwrap_or_default();
+        Ok(Self {
+            name: name.to_string(),
+            created_time: text("created_time"),
+            last_played: text("last_played"),
+            save_format_version,
+            ..Self::default()
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize world info: {}", e))
+    }
+
+    /// 写入世界目录，读取时不认识的字段一起写回
+    pub fn write(&self, world_dir: &Path) -> Result<(), String> {
+        let path = Self::path(world_dir);
+        fs::write(&path, self.to_json()?).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+}
+
+/// 游戏模式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum GameMode {
+    Survival,
+    #[default]
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameMode::Survival => "生存模式",
+            GameMode::Creative => "创造模式",
+            GameMode::Adventure => "冒险模式",
+            GameMode::Spectator => "旁观模式",
+        }
+    }
+
+    /// 本地化键，例如 "game_modes.survival"
+    pub fn localization_key(&self) -> &'static str {
+        match self {
+            GameMode::Survival => "game_modes.survival",
+            GameMode::Creative => "game_modes.creative",
+            GameMode::Adventure => "game_modes.adventure",
+            GameMode::Spectator => "game_modes.spectator",
+        }
+    }
+
+    /// 解析 `/gamemode` 的参数：模式名称、原版的缩写（s/c/a/sp）或编号（0-3），不区分大小写
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "survival" | "s" | "0" => Some(GameMode::Survival),
+            "creative" | "c" | "1" => Some(GameMode::Creative),
+            "adventure" | "a" | "2" => Some(GameMode::Adventure),
+            "spectator" | "sp" | "3" => Some(GameMode::Spectator),
+            _ => None,
+        }
+    }
+
+    /// 创造模式：按下即破坏方块，放置方块和使用工具不消耗物品和耐久
+    pub fn is_creative(&self) -> bool {
+        *self == GameMode::Creative
+    }
+
+    /// 是否可以双击空格切换飞行；旁观模式始终飞行
+    pub fn allows_flight(&self) -> bool {
+        *self == GameMode::Creative
+    }
+
+    /// 是否受到伤害（摔落、生物攻击、爆炸），同时决定生物是否追击玩家、是否显示生命值
+    pub fn takes_damage(&self) -> bool {
+        matches!(self, GameMode::Survival | GameMode::Adventure)
+    }
+
+    /// 是否可以破坏和放置方块；冒险和旁观模式只能观察世界
+    pub fn can_edit_blocks(&self) -> bool {
+        matches!(self, GameMode::Survival | GameMode::Creative)
+    }
+
+    /// 旁观模式：穿过方块飞行，不与世界交互
+    pub fn is_spectator(&self) -> bool {
+        *self == GameMode::Spectator
+    }
+}
+
+/// 世界类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum WorldType {
+    #[default]
+    Default,
+    Flat,
+    LargeBiomes,
+    Amplified,
+    /// 从原版 Anvil 存档导入
+    Imported,
+}
+
+impl WorldType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorldType::Default => "默认",
+            WorldType::Flat => "超平坦",
+            WorldType::LargeBiomes => "巨型生物群系",
+            WorldType::Amplified => "放大化",
+            WorldType::Imported => "导入",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_fields_survive_a_rewrite() {
+        let json = r#"{
+            "name": "World",
+            "seed": 7,
+            "created_time": "2025-01-01 00:00:00",
+            "last_played": "2025-01-02 00:00:00",
+            "game_mode": "Survival",
+            "world_type": "Flat",
+            "difficulty": "hard",
+            "future": { "nested": [1, 2] }
+        }"#;
+        let mut info = WorldInfo::from_json(json).unwrap();
+        assert_eq!(info.save_format_version, LEGACY_SAVE_FORMAT_VERSION);
+        assert_eq!(info.game_mode, GameMode::Survival);
+        assert_eq!(info.extra.len(), 2);
+
+        info.last_played = timestamp_now();
+        let rewritten: serde_json::Value = serde_json::from_str(&info.to_json().unwrap()).unwrap();
+        assert_eq!(rewritten["difficulty"], "hard");
+        assert_eq!(rewritten["future"]["nested"][1], 2);
+        assert_eq!(rewritten["world_type"], "Flat");
+    }
+
+    #[test]
+    fn newer_world_with_unknown_variant_is_still_listed() {
+        let json = format!(r#"{{
+            "name": "Future",
+            "seed": 7,
+            "created_time": "2025-01-01 00:00:00",
+            "last_played": "2025-01-02 00:00:00",
+            "game_mode": "Hardcore",
+            "world_type": "Default",
+            "save_format_version": {}
+        }}"#, SAVE_FORMAT_VERSION + 1);
+        assert!(WorldInfo::from_json(&json).is_err());
+
+        let info = WorldInfo::from_json_for_listing(&json).unwrap();
+        assert_eq!(info.name, "Future");
+        assert_eq!(info.last_played, "2025-01-02 00:00:00");
+        assert!(info.is_newer_version());
+
+        // 当前版本的存档解析失败说明文件损坏，不按更新版本处理
+        let broken = json.replace(&format!("{}", SAVE_FORMAT_VERSION + 1), &SAVE_FORMAT_VERSION.to_string());
+        assert!(WorldInfo::from_json_for_listing(&broken).is_err());
+    }
+}
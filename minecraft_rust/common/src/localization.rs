@@ -0,0 +1,115 @@
+//! 语言文件的格式：`localization/<语言代码>.json`，顶层的 `language_info` 描述语言，其余内容是嵌套的文本，
+//! 按 "ui.main_menu.title" 这样用点连接的键查找。游戏和噪声编辑器都用这种格式
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 语言文件所在的目录
+pub const LOCALIZATION_DIR: &str = "localization";
+/// 默认语言，其他语言缺少的文本用它补上
+pub const DEFAULT_LANGUAGE: &str = "en_us";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageInfo {
+    pub name: String,
+    pub code: String,
+    pub author: String,
+    pub version: String,
+}
+
+impl LanguageInfo {
+    /// 没有 `language_info` 的语言文件，以语言代码作为名称
+    pub fn unnamed(code: &str) -> Self {
+        Self { name: code.to_string(), code: code.to_string(), author: "Unknown".to_string(), version: "1.0.0".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageData {
+    pub language_info: LanguageInfo,
+    #[serde(flatten)]
+    pub texts: serde_json::Value,
+}
+
+impl LanguageData {
+    /// 语言目录中某个语言的文件
+    pub fn path(dir: &Path, code: &str) -> PathBuf {
+        dir.join(format!("{}.json", code))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        Self::from_json(&content).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    /// 把嵌套的文本展开成用点连接的键，非字符串的值忽略
+    pub fn flatten(&self) -> HashMap<String, String> {
+        let mut texts = HashMap::new();
+        flatten_into(&self.texts, String::new(), &mut texts);
+        texts
+    }
+}
+
+fn flatten_into(value: &serde_json::Value, prefix: String, texts: &mut HashMap<String, String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, val) in map {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_into(val, key, texts);
+            }
+        }
+        serde_json::Value::String(s) => {
+            texts.insert(prefix, s.clone());
+        }
+        _ => {}
+    }
+}
+
+/// 扫描语言目录中的所有语言文件，按语言代码排序
+pub fn scan_languages(dir: &Path) -> Vec<LanguageInfo> {
+    let mut languages = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else { return languages };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(code) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+        match LanguageData::read(&path) {
+            Ok(data) => languages.push(data.language_info),
+            Err(_) => languages.push(LanguageInfo::unnamed(code)),
+        }
+    }
+    languages.sort_by(|a, b| a.code.cmp(&b.code));
+    languages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_texts_flatten_to_dotted_keys() {
+        let data = LanguageData::from_json(
+            r#"{
+                "language_info": { "name": "Test", "code": "xx", "author": "", "version": "1" },
+                "menu": { "file": "File", "recent": { "clear": "Clear" } },
+                "title": "Editor",
+                "count": 3
+            }"#,
+        )
+        .unwrap();
+        let texts = data.flatten();
+        assert_eq!(texts["menu.file"], "File");
+        assert_eq!(texts["menu.recent.clear"], "Clear");
+        assert_eq!(texts["title"], "Editor");
+        assert!(!texts.contains_key("count"));
+        assert!(!texts.contains_key("language_info.name"));
+    }
+}
@@ -0,0 +1,69 @@
+//! 游戏根目录（存档、数据目录和游戏可执行文件所在的目录）的查找规则，游戏和启动器共用
+
+use std::path::{Path, PathBuf};
+
+/// 指定游戏根目录的环境变量
+pub const DATA_DIR_ENV: &str = "MINECRAFT_RUST_DATA_DIR";
+/// 游戏根目录中必须存在的数据目录，也用来识别游戏根目录
+pub const REQUIRED_DIRS: [&str; 2] = ["scripts", "localization"];
+/// 游戏根目录下的资源目录（纹理、声音等，从原版客户端解压的 assets/minecraft），游戏和启动器都从这里加载资源
+pub const ASSETS_DIR: &str = "assets";
+
+pub fn is_game_root(dir: &Path) -> bool {
+    REQUIRED_DIRS.iter().all(|name| dir.join(name).is_dir())
+}
+
+/// 按 `explicit`（例如游戏的 `--data-dir` 参数）、环境变量、可执行文件位置和当前目录确定游戏根目录（绝对路径）
+pub fn resolve_from_environment(explicit: Option<PathBuf>) -> PathBuf {
+    let explicit = explicit.or_else(|| std::env::var_os(DATA_DIR_ENV).map(PathBuf::from));
+    let exe_dir = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    let root = resolve_game_root(explicit, exe_dir.as_deref(), &current_dir);
+    std::path::absolute(&root).unwrap_or(root)
+}
+
+/// 明确指定的目录优先。否则依次查找可执行文件所在目录（打包发布时数据和可执行文件放在一起）、
+/// 它的上三级（开发时游戏在 target/<profile>/ 下，启动器在 launcher/target/<profile>/ 下）、
+/// 当前目录及其上一级，都找不到时使用可执行文件所在目录
+pub fn resolve_game_root(explicit: Option<PathBuf>, exe_dir: Option<&Path>, current_dir: &Path) -> PathBuf {
+    if let Some(dir) = explicit {
+        return dir;
+    }
+    exe_dir
+        .into_iter()
+        .flat_map(|dir| dir.ancestors().take(4))
+        .chain(current_dir.ancestors().take(2))
+        .find(|dir| is_game_root(dir))
+        .or(exe_dir)
+        .unwrap_or(current_dir)
+        .to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_root_is_found_next_to_or_above_the_executable() {
+        let root = std::env::temp_dir().join(format!("game_root_{}", std::process::id()));
+        for dir in REQUIRED_DIRS {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let exe_dir = root.join("target").join("release");
+        let launcher_exe_dir = root.join("launcher").join("target").join("debug");
+        std::fs::create_dir_all(&exe_dir).unwrap();
+        std::fs::create_dir_all(&launcher_exe_dir).unwrap();
+        let elsewhere = std::env::temp_dir();
+
+        // 开发布局：游戏在 target/release 下，启动器在 launcher/target/debug 下；打包布局：数据和可执行文件在同一目录
+        assert_eq!(resolve_game_root(None, Some(&exe_dir), &elsewhere), root);
+        assert_eq!(resolve_game_root(None, Some(&launcher_exe_dir), &elsewhere), root);
+        assert_eq!(resolve_game_root(None, Some(&root), &elsewhere), root);
+        // 在启动器目录中运行 cargo run 时从当前目录的上一级找到
+        assert_eq!(resolve_game_root(None, Some(&elsewhere), &root.join("launcher")), root);
+        // 明确指定的目录优先；都找不到时使用可执行文件所在目录
+        assert_eq!(resolve_game_root(Some(elsewhere.clone()), Some(&exe_dir), &root), elsewhere);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(resolve_game_root(None, Some(&elsewhere), &elsewhere), elsewhere);
+    }
+}
@@ -1,5 +1,10 @@
 use bevy::prelude::*;
+use bevy::asset::AssetPlugin;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use mc_common::{WorldInfo, SAVES_DIR, STATS_FILE};
+use mc_common::paths::ASSETS_DIR;
+use std::sync::OnceLock;
 
 
 /// UI字符串配置
@@ -24,6 +29,11 @@ pub struct LauncherStrings {
     pub game_started: String,
     pub launch_failed: String,
     pub create_world_todo: String,
+    pub play_time: String,
+    pub crash_detected: String,
+    pub open_crash_report: String,
+    pub dismiss_crash: String,
+    pub newer_save: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,20 +51,27 @@ pub enum LauncherState {
     Settings,
 }
 
-/// 世界信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorldInfo {
-    pub name: String,
-    pub game_mode: String,
-    pub world_type: String,
-    pub last_played: String,
+/// 世界列表中的一个世界
+#[derive(Debug, Clone)]
+pub struct WorldEntry {
+    pub name: String,  // 存档目录名，即游戏 --world 参数使用的世界名
+    pub info: WorldInfo,
+    pub play_time_secs: f64,  // 来自世界存档的 stats.json
+}
+
+impl WorldEntry {
+    /// 存档来自更新版本的游戏，不能打开
+    pub fn newer_version(&self) -> bool {
+        self.info.is_newer_version()
+    }
 }
 
 /// 启动器资源
 #[derive(Resource, Default)]
 pub struct LauncherData {
-    pub worlds: Vec<WorldInfo>,
+    pub worlds: Vec<WorldEntry>,
     pub selected_world: Option<String>,
+    pub crash_report: Option<PathBuf>,  // 还没有查看过的最新崩溃报告
 }
 
 /// UI字符串资源
@@ -70,6 +87,16 @@ pub struct LauncherUI;
 #[derive(Component)]
 pub struct WorldButton(pub String);
 
+/// 崩溃报告提示条，在所有界面上方显示，不随界面切换清除
+#[derive(Component)]
+pub struct CrashBanner;
+
+/// 与游戏相同的崩溃报告目录；查看或忽略报告后更新标记文件的修改时间，更早的报告不再提示
+const CRASH_REPORT_DIR: &str = "crash-reports";
+const CRASH_SEEN_MARKER: &str = ".seen";
+/// 检查新崩溃报告的间隔（秒），启动器启动的游戏崩溃后也能提示
+const CRASH_CHECK_INTERVAL: f32 = 2.0;
+
 fn main() {
     // 加载UI字符串
     let ui_strings = load_ui_strings();
@@ -83,6 +110,10 @@ fn main() {
                 ..default()
             }),
             ..default()
+        }).set(AssetPlugin {
+            // 界面音效和游戏共用游戏根目录下的资源
+            file_path: game_root().join(ASSETS_DIR).to_string_lossy().into_owned(),
+            ..default()
         }))
         .init_state::<LauncherState>()
         .init_resource::<LauncherData>()
@@ -98,6 +129,9 @@ fn main() {
             main_menu_system.run_if(in_state(LauncherState::MainMenu)),
             world_selection_system.run_if(in_state(LauncherState::WorldSelection)),
             settings_system.run_if(in_state(LauncherState::Settings)),
+            play_button_clicks,
+            check_crash_reports,
+            crash_banner_system,
         ))
         .run();
 }
@@ -195,7 +229,7 @@ fn setup_world_selection(mut commands: Commands, launcher_data: Res<LauncherData
             ..default()
         }).with_children(|parent| {
             for world in &launcher_data.worlds {
-                create_world_button(parent, &world.name);
+                create_world_button(parent, world, &ui_strings.strings.launcher);
             }
         });
 
@@ -281,7 +315,7 @@ fn create_launcher_button(parent: &mut ChildBuilder, text: &str, action: &str) {
     });
 }
 
-fn create_world_button(parent: &mut ChildBuilder, world_name: &str) {
+fn create_world_button(parent: &mut ChildBuilder, world: &WorldEntry, strings: &LauncherStrings) {
     parent.spawn((
         ButtonBundle {
             style: Style {
@@ -296,16 +330,37 @@ fn create_world_button(parent: &mut ChildBuilder, world_name: &str) {
             border_color: Color::srgba(0.4, 0.4, 0.4, 0.8).into(),
             ..default()
         },
-        WorldButton(world_name.to_string()),
+        WorldButton(world.name.clone()),
     )).with_children(|parent| {
-        parent.spawn(TextBundle::from_section(
-            world_name,
-            TextStyle {
-                font: default(),
-                font_size: 18.0,
-                color: Color::WHITE,
+        parent.spawn(TextBundle::from_sections([
+            TextSection::new(
+                format!("{}\n", world.name),
+                TextStyle {
+                    font: default(),
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            ),
+            if world.newer_version() {
+                TextSection::new(
+                    strings.newer_save.clone(),
+                    TextStyle {
+                        font: default(),
+                        font_size: 13.0,
+                        color: Color::srgb(1.0, 0.45, 0.45),
+                    },
+                )
+            } else {
+                TextSection::new(
+                    format!("{}: {}", strings.play_time, format_play_time(world.play_time_secs)),
+                    TextStyle {
+                        font: default(),
+                        font_size: 13.0,
+                        color: Color::srgb(0.7, 0.7, 0.7),
+                    },
+                )
             },
-        ));
+        ]).with_text_justify(JustifyText::Center));
     });
 }
 
@@ -352,6 +407,11 @@ fn world_selection_system(
                     _ => {}
                 }
             } else if let Some(world_button) = world_button {
+                // 更新版本的游戏保存的世界打开后可能损坏，游戏也会拒绝打开
+                if launcher_data.worlds.iter().any(|world| world.name == world_button.0 && world.newer_version()) {
+                    eprintln!("{}: {}", ui_strings.strings.launcher.newer_save, world_button.0);
+                    continue;
+                }
                 // 启动游戏
                 launcher_data.selected_world = Some(world_button.0.clone());
                 launch_game(&world_button.0, &ui_strings.strings.launcher);
@@ -376,15 +436,190 @@ fn settings_system(
     }
 }
 
+/// 任意按钮按下时播放点击音效
+fn play_button_clicks(
+    mut commands: Commands,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    asset_server: Res<AssetServer>,
+) {
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            commands.spawn(AudioBundle {
+                source: asset_server.load("sounds/ui/click.ogg"),
+                settings: PlaybackSettings::DESPAWN,
+            });
+        }
+    }
+}
+
+/// 定期查找比标记文件新的崩溃报告，找到时显示提示条
+fn check_crash_reports(
+    mut commands: Commands,
+    mut launcher_data: ResMut<LauncherData>,
+    ui_strings: Res<UiStringResource>,
+    banners: Query<Entity, With<CrashBanner>>,
+    time: Res<Time>,
+    mut since_check: Local<Option<f32>>,
+) {
+    // 第一帧立即检查
+    let elapsed = since_check.map_or(CRASH_CHECK_INTERVAL, |since| since + time.delta_seconds());
+    if elapsed < CRASH_CHECK_INTERVAL {
+        *since_check = Some(elapsed);
+        return;
+    }
+    *since_check = Some(0.0);
+
+    let Some(report) = newest_unseen_crash_report() else { return };
+    if launcher_data.crash_report.as_ref() == Some(&report) {
+        return;
+    }
+    for entity in &banners {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_crash_banner(&mut commands, &report, &ui_strings.strings.launcher);
+    launcher_data.crash_report = Some(report);
+}
+
+/// 崩溃报告目录中最新的、比标记文件新的报告
+fn newest_unseen_crash_report() -> Option<PathBuf> {
+    let dir = game_root().join(CRASH_REPORT_DIR);
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let seen = modified(&dir.join(CRASH_SEEN_MARKER));
+    std::fs::read_dir(&dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|path| Some((modified(&path)?, path)))
+        .filter(|(time, _)| seen.is_none_or(|seen| *time > seen))
+        .max()
+        .map(|(_, path)| path)
+}
+
+fn spawn_crash_banner(commands: &mut Commands, report: &Path, strings: &LauncherStrings) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            background_color: Color::srgba(0.45, 0.1, 0.1, 0.95).into(),
+            z_index: ZIndex::Global(1),
+            ..default()
+        },
+        CrashBanner,
+    )).with_children(|parent| {
+        let file_name = report.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        parent.spawn(TextBundle::from_section(
+            format!("{} {}", strings.crash_detected, file_name),
+            TextStyle {
+                font: default(),
+                font_size: 16.0,
+                color: Color::WHITE,
+            },
+        ));
+        parent.spawn(NodeBundle {
+            style: Style {
+                column_gap: Val::Px(15.0),
+                ..default()
+            },
+            ..default()
+        }).with_children(|parent| {
+            create_launcher_button(parent, &strings.open_crash_report, "open_crash_report");
+            create_launcher_button(parent, &strings.dismiss_crash, "dismiss_crash");
+        });
+    });
+}
+
+/// 打开崩溃报告或忽略它，两种情况都记为已查看并关闭提示条
+fn crash_banner_system(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &Name), Changed<Interaction>>,
+    mut launcher_data: ResMut<LauncherData>,
+    banners: Query<Entity, With<CrashBanner>>,
+) {
+    for (interaction, name) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let open = match name.as_str() {
+            "open_crash_report" => true,
+            "dismiss_crash" => false,
+            _ => continue,
+        };
+        let Some(report) = launcher_data.crash_report.take() else { continue };
+        if open {
+            open_file(&report);
+        }
+        mark_crash_reports_seen();
+        for entity in &banners {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// 用系统默认程序打开文件
+fn open_file(path: &Path) {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    if let Err(e) = command.arg(path).spawn() {
+        eprintln!("Failed to open {}: {}", path.display(), e);
+    }
+}
+
+/// 重写标记文件，更新它的修改时间
+fn mark_crash_reports_seen() {
+    let path = game_root().join(CRASH_REPORT_DIR).join(CRASH_SEEN_MARKER);
+    if let Err(e) = std::fs::write(&path, "") {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
 fn cleanup_ui(mut commands: Commands, query: Query<Entity, With<LauncherUI>>) {
     for entity in &query {
         commands.entity(entity).despawn_recursive();
     }
 }
 
+/// 游戏根目录（存档、ui_strings.json 和游戏可执行文件所在的目录），查找规则与游戏相同（见 `mc_common::paths`）
+fn game_root() -> &'static Path {
+    static ROOT: OnceLock<PathBuf> = OnceLock::new();
+    ROOT.get_or_init(|| mc_common::paths::resolve_from_environment(None))
+}
+
+/// 游戏可执行文件：打包发布时与启动器在同一目录，开发时优先使用 release 版本
+fn game_executable() -> PathBuf {
+    let name = if cfg!(target_os = "windows") { "minecraft_rust.exe" } else { "minecraft_rust" };
+    let candidates = [
+        std::env::current_exe().ok().and_then(|exe| exe.parent().map(|dir| dir.join(name))),
+        Some(game_root().join(name)),
+        Some(game_root().join("target").join("release").join(name)),
+        Some(game_root().join("target").join("debug").join(name)),
+    ];
+    candidates
+        .iter()
+        .flatten()
+        .find(|path| path.is_file())
+        .cloned()
+        .unwrap_or_else(|| game_root().join(name))
+}
+
 fn load_ui_strings() -> UiStrings {
     // 尝试从配置文件加载UI字符串
-    let config_path = "../ui_strings.json";
+    let config_path = game_root().join("ui_strings.json");
     
     if let Ok(content) = std::fs::read_to_string(config_path) {
         if let Ok(ui_strings) = serde_json::from_str::<UiStrings>(&content) {
@@ -412,58 +647,71 @@ fn load_ui_strings() -> UiStrings {
             game_started: "Game started, PID: ".to_string(),
             launch_failed: "Failed to launch game: ".to_string(),
             create_world_todo: "Create new world feature to be implemented".to_string(),
+            play_time: "Played".to_string(),
+            crash_detected: "The game crashed last time. Crash report:".to_string(),
+            open_crash_report: "Open Report".to_string(),
+            dismiss_crash: "Dismiss".to_string(),
+            newer_save: "Saved by a newer version of the game, cannot be opened".to_string(),
         },
     }
 }
 
-fn load_worlds() -> Vec<WorldInfo> {
-    // 这里应该从文件系统加载世界列表
-    // 现在返回一些示例数据，使用英文名称
-    vec![
-        WorldInfo {
-            name: "My World".to_string(),
-            game_mode: "creative".to_string(),
-            world_type: "default".to_string(),
-            last_played: "2024-01-15".to_string(),
-        },
-        WorldInfo {
-            name: "Survival World".to_string(),
-            game_mode: "survival".to_string(),
-            world_type: "default".to_string(),
-            last_played: "2024-01-14".to_string(),
-        },
-    ]
+/// 扫描游戏的存档目录，读取每个世界的 world_info.json（包括 import 子命令导入的世界），最近游玩的排在前面。
+/// 更新版本的游戏写入的世界信息即使无法完整解析也会列出，显示为不能打开
+fn load_worlds() -> Vec<WorldEntry> {
+    let Ok(entries) = std::fs::read_dir(game_root().join(SAVES_DIR)) else { return Vec::new() };
+    let mut worlds: Vec<WorldEntry> = entries
+        .flatten()
+        .filter(|entry| WorldInfo::path(&entry.path()).exists())
+        .filter_map(|entry| match WorldInfo::read_for_listing(&entry.path()) {
+            Ok(info) => {
+                let name = entry.file_name().to_string_lossy().to_string();
+                Some(WorldEntry { play_time_secs: read_play_time(&name), name, info })
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                None
+            }
+        })
+        .collect();
+    worlds.sort_by(|a, b| b.info.last_played.cmp(&a.info.last_played));
+    worlds
+}
+
+/// 从世界存档的 stats.json 读取累计游玩时间（秒），没有统计文件时为 0
+fn read_play_time(world_name: &str) -> f64 {
+    let path = game_root().join(SAVES_DIR).join(world_name).join(STATS_FILE);
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|stats| stats.get("play_time_secs")?.as_f64())
+        .unwrap_or(0.0)
+}
+
+/// 把秒数格式化为 时:分:秒，与游戏内统计页一致
+fn format_play_time(secs: f64) -> String {
+    let total = secs as u64;
+    format!("{}:{:02}:{:02}", total / 3600, total / 60 % 60, total % 60)
 }
 
 fn launch_game(world_name: &str, strings: &LauncherStrings) {
     println!("{}{}", strings.launch_game, world_name);
     
-    let game_path = if cfg!(target_os = "windows") {
-        // 优先尝试 release 版本
-        if std::path::Path::new("../target/release/minecraft_rust.exe").exists() {
-            "../target/release/minecraft_rust.exe"
-        } else {
-            "../target/debug/minecraft_rust.exe"
-        }
-    } else {
-        // 优先尝试 release 版本
-        if std::path::Path::new("../target/release/minecraft_rust").exists() {
-            "../target/release/minecraft_rust"
-        } else {
-            "../target/debug/minecraft_rust"
-        }
-    };
+    let game_path = game_executable();
     
-    match std::process::Command::new(game_path)
+    // 把启动器找到的游戏根目录传给游戏，两者使用同一份存档
+    match std::process::Command::new(&game_path)
         .arg("--world")
         .arg(world_name)
+        .arg("--data-dir")
+        .arg(game_root())
         .spawn()
     {
         Ok(child) => {
             println!("{}{}", strings.game_started, child.id());
         }
         Err(e) => {
-            eprintln!("{}{}", strings.launch_failed, e);
+            eprintln!("{}{} ({})", strings.launch_failed, e, game_path.display());
         }
     }
 }
\ No newline at end of file
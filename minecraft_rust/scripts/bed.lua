@@ -0,0 +1,11 @@
+-- 床方块定义，右键设置重生点，夜晚使用时跳过黑夜
+return {
+    hardness = 0.2,
+    blast_resistance = 0.2,
+    transparent = false,
+    solid = true,
+    texture = "bed",
+    light_level = 0,
+    sound = "wood",
+    description = "game.block.bed.description",
+}
@@ -1,10 +1,13 @@
 -- 基岩方块定义
 return {
     hardness = 999.0,
+    blast_resistance = 3600000.0,
     transparent = false,
     solid = true,
     texture = "bedrock",
     light_level = 0,
+    sound = "stone",
+    description = "game.block.bedrock.description",
     on_break = function(pos)
         return "Cannot break bedrock!"
     end
@@ -0,0 +1,11 @@
+-- 箱子方块定义，右键打开 27 格容器，内容保存在方块实体中
+return {
+    hardness = 2.5,
+    blast_resistance = 2.5,
+    transparent = false,
+    solid = true,
+    texture = "chest",
+    light_level = 0,
+    sound = "wood",
+    description = "game.block.chest.description",
+}
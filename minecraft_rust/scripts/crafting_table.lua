@@ -0,0 +1,11 @@
+-- 工作台方块定义，右键打开 3×3 合成格
+return {
+    hardness = 2.5,
+    blast_resistance = 2.5,
+    transparent = false,
+    solid = true,
+    texture = "crafting_table",
+    light_level = 0,
+    sound = "wood",
+    description = "game.block.crafting_table.description",
+}
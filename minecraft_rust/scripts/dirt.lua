@@ -1,10 +1,13 @@
 -- 泥土方块定义
 return {
     hardness = 1.0,
+    blast_resistance = 0.5,
     transparent = false,
     solid = true,
     texture = "dirt",
     light_level = 0,
+    sound = "gravel",
+    description = "game.block.dirt.description",
     on_break = function(pos)
         return "Dirt block broken at " .. tostring(pos)
     end
@@ -0,0 +1,11 @@
+-- 沙砾方块定义，下方没有支撑时会下落
+return {
+    hardness = 0.6,
+    blast_resistance = 0.6,
+    transparent = false,
+    solid = true,
+    texture = "gravel",
+    light_level = 0,
+    sound = "gravel",
+    description = "game.block.gravel.description",
+}
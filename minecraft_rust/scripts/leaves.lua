@@ -0,0 +1,11 @@
+-- 橡树叶，树苗长成的树冠，破坏时有机会掉落树苗
+return {
+    hardness = 0.2,
+    blast_resistance = 0.2,
+    transparent = true,
+    solid = true,
+    texture = "oak_leaves",
+    light_level = 0,
+    sound = "grass",
+    description = "game.block.leaves.description",
+}
@@ -0,0 +1,12 @@
+-- 橡木原木，沿放置时点击的面摆放：两端是年轮，四周是树皮
+return {
+    hardness = 2.0,
+    blast_resistance = 2.0,
+    transparent = false,
+    solid = true,
+    texture = "oak_log",
+    light_level = 0,
+    sound = "wood",
+    description = "game.block.log.description",
+    orientable = "axis",
+}
@@ -0,0 +1,11 @@
+-- 黑曜石方块定义
+return {
+    hardness = 50.0,
+    blast_resistance = 1200.0,
+    transparent = false,
+    solid = true,
+    texture = "obsidian",
+    light_level = 0,
+    sound = "stone",
+    description = "game.block.obsidian.description",
+}
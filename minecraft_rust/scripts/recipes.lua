@@ -0,0 +1,51 @@
+-- 合成配方。物品 id 与物品名称的键相同，例如 "grass_block"、"stone_pickaxe"
+-- 有形状的配方用 pattern 按行摆放，key 指定每个符号对应的物品，空格表示空格子；左右镜像的摆法同样可以合成
+-- 无形状的配方用 ingredients 列出材料，放在合成格的任意位置
+
+register_recipe{
+    pattern = { "ss", "ss" },
+    key = { s = "stone" },
+    result = "crafting_table",
+}
+
+register_recipe{
+    pattern = { "sss", "s s", "sss" },
+    key = { s = "stone" },
+    result = "chest",
+}
+
+register_recipe{
+    pattern = { "ggg", "sss" },
+    key = { g = "grass_block", s = "stone" },
+    result = "bed",
+}
+
+register_recipe{
+    pattern = { "l", "s" },
+    key = { l = "lava", s = "stone" },
+    result = "torch",
+    count = 4,
+}
+
+register_recipe{
+    pattern = { "sss", " o ", " o " },
+    key = { s = "stone", o = "obsidian" },
+    result = "stone_pickaxe",
+}
+
+register_recipe{
+    pattern = { "ooo", " s ", " s " },
+    key = { o = "obsidian", s = "stone" },
+    result = "diamond_pickaxe",
+}
+
+register_recipe{
+    ingredients = { "stone" },
+    result = "gravel",
+}
+
+register_recipe{
+    ingredients = { "dirt", "grass_block" },
+    result = "grass_block",
+    count = 2,
+}
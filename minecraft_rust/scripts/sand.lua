@@ -0,0 +1,11 @@
+-- 沙子方块定义，下方没有支撑时会下落
+return {
+    hardness = 0.5,
+    blast_resistance = 0.5,
+    transparent = false,
+    solid = true,
+    texture = "sand",
+    light_level = 0,
+    sound = "sand",
+    description = "game.block.sand.description",
+}
@@ -0,0 +1,11 @@
+-- 橡树苗，只能种在草方块或泥土上，有天空光照时会慢慢长成橡树
+return {
+    hardness = 0.0,
+    blast_resistance = 0.0,
+    transparent = true,
+    solid = false,
+    texture = "oak_sapling",
+    light_level = 0,
+    sound = "grass",
+    description = "game.block.sapling.description",
+}
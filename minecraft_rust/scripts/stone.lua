@@ -1,10 +1,13 @@
 -- 石头方块定义
 return {
     hardness = 2.0,
+    blast_resistance = 6.0,
     transparent = false,
     solid = true,
     texture = "stone",
     light_level = 0,
+    sound = "stone",
+    description = "game.block.stone.description",
     
     -- 破坏时的回调
     on_break = function(pos)
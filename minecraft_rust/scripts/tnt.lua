@@ -0,0 +1,11 @@
+-- TNT 方块定义，右键点燃，几秒后爆炸
+return {
+    hardness = 0.0,
+    blast_resistance = 0.0,
+    transparent = false,
+    solid = true,
+    texture = "tnt",
+    light_level = 0,
+    sound = "grass",
+    description = "game.block.tnt.description",
+}
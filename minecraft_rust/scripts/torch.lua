@@ -0,0 +1,10 @@
+-- 火把方块定义，没有碰撞体积，向周围发出方块光照
+return {
+    hardness = 0.0,
+    blast_resistance = 0.0,
+    transparent = true,
+    solid = false,
+    light_level = 14,
+    sound = "wood",
+    description = "game.block.torch.description",
+}
@@ -0,0 +1,10 @@
+-- 水方块定义（流体，流动由游戏内的流体系统处理）
+return {
+    hardness = 100.0,
+    blast_resistance = 100.0,
+    transparent = true,
+    solid = false,
+    light_level = 0,
+    sound = "stone",
+    description = "game.block.water.description",
+}
@@ -0,0 +1,194 @@
+use bevy::audio::{AudioSinkPlayback, Volume};
+use bevy::prelude::*;
+use crate::camera_environment::CameraEnvironment;
+use crate::controller::FirstPersonController;
+use crate::game_state::GameState;
+use crate::ui::GameSettings;
+use crate::world::access::{split_world_pos, WorldView};
+use crate::world::chunk::Chunk;
+use crate::world::heightmap::Heightmap;
+use crate::world::storage::ChunkStorage;
+
+/// 判断玩家所处环境的间隔（秒），每秒几次就足够，不必每帧查询
+const SAMPLE_INTERVAL: f32 = 0.25;
+/// 环境变化时两段环境音交叉淡入淡出的时间（秒）
+const CROSSFADE_SECONDS: f32 = 2.0;
+/// 摄像机在流体中时环境音降低的音量比例
+const MUFFLED_VOLUME: f32 = 0.6;
+/// 头顶有遮挡且天空光照不超过这一等级时算在地下
+const CAVE_SKY_LIGHT: u8 = 4;
+
+/// 环境音的种类
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmbientKind {
+    /// 露天地表的风声
+    Surface,
+    /// 地下的低沉嗡鸣
+    Cave,
+}
+
+impl AmbientKind {
+    const ALL: [AmbientKind; 2] = [AmbientKind::Surface, AmbientKind::Cave];
+
+    /// 资源目录中循环播放的环境音文件（随游戏提供的 CC0 占位素材）
+    fn file(self) -> &'static str {
+        match self {
+            AmbientKind::Surface => "sounds/ambient/surface.ogg",
+            AmbientKind::Cave => "sounds/ambient/cave.ogg",
+        }
+    }
+}
+
+/// 按玩家所在格子判断环境：高于该列遮挡方块的是露天地表；有遮挡且几乎没有天空光照的是地下；
+/// 其他情况（屋檐下、树下）两段环境音都不播放。`sky_light` 为 None 表示区块未加载
+fn classify(exposed: bool, sky_light: Option<u8>) -> Option<AmbientKind> {
+    if exposed {
+        Some(AmbientKind::Surface)
+    } else if sky_light? <= CAVE_SKY_LIGHT {
+        Some(AmbientKind::Cave)
+    } else {
+        None
+    }
+}
+
+/// 淡入淡出的音量系数向目标移动一帧
+fn step_fade(fade: f32, target: f32, dt: f32) -> f32 {
+    let step = dt / CROSSFADE_SECONDS;
+    if fade < target {
+        (fade + step).min(target)
+    } else {
+        (fade - step).max(target)
+    }
+}
+
+/// 循环播放的一段环境音，`fade` 是淡入淡出的音量系数
+#[derive(Component)]
+struct AmbientLoop {
+    kind: AmbientKind,
+    fade: f32,
+}
+
+/// 最近一次采样得到的环境
+#[derive(Resource, Default)]
+struct AmbientContext {
+    current: Option<AmbientKind>,
+    since_sample: f32,
+}
+
+pub struct AmbiencePlugin;
+
+impl Plugin for AmbiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AmbientContext>()
+           .add_systems(OnEnter(GameState::InGame), spawn_ambient_loops)
+           .add_systems(Update, (sample_ambient_context, fade_ambient_loops).chain().run_if(in_state(GameState::InGame)))
+           // 暂停菜单和拍照模式中环境音停止；从暂停菜单进入拍照模式时先恢复再暂停
+           .add_systems(OnEnter(GameState::Paused), pause_ambient_loops)
+           .add_systems(OnExit(GameState::Paused), resume_ambient_loops)
+           .add_systems(OnEnter(GameState::PhotoMode), pause_ambient_loops)
+           .add_systems(OnExit(GameState::PhotoMode), resume_ambient_loops);
+    }
+}
+
+/// 第一次进入游戏时以零音量开始播放两段环境音，之后只调整音量
+fn spawn_ambient_loops(
+    mut commands: Commands,
+    loops: Query<(), With<AmbientLoop>>,
+    asset_server: Res<AssetServer>,
+) {
+    if !loops.is_empty() {
+        return;
+    }
+    for kind in AmbientKind::ALL {
+        let settings = PlaybackSettings::LOOP.with_volume(Volume::new_absolute(0.0));
+        commands.spawn((AudioBundle { source: asset_server.load(kind.file()), settings }, AmbientLoop { kind, fade: 0.0 }));
+    }
+}
+
+/// 每隔 `SAMPLE_INTERVAL` 用高度图和玩家所在格子的天空光照判断环境
+fn sample_ambient_context(
+    mut context: ResMut<AmbientContext>,
+    player: Query<&Transform, With<FirstPersonController>>,
+    heightmap: Res<Heightmap>,
+    chunk_storage: Res<ChunkStorage>,
+    chunks: Query<&Chunk>,
+    time: Res<Time>,
+) {
+    context.since_sample += time.delta_seconds();
+    if context.since_sample < SAMPLE_INTERVAL {
+        return;
+    }
+    context.since_sample = 0.0;
+    let Ok(transform) = player.get_single() else { return };
+    let pos = transform.translation.floor().as_ivec3();
+    let (coord, local) = split_world_pos(pos);
+    let sky_light = WorldView::new(&chunk_storage, &chunks).chunk(coord).map(|chunk| chunk.get_sky_light(local.x, local.y, local.z));
+    let current = classify(heightmap.is_exposed(pos), sky_light);
+    if current != context.current {
+        debug!("Ambience changed to {:?}", current);
+        context.current = current;
+    }
+}
+
+/// 当前环境的环境音淡入，其他的淡出，音量乘以设置中的主音量和环境音量；在水或岩浆中时声音变小
+fn fade_ambient_loops(
+    context: Res<AmbientContext>,
+    environment: Res<CameraEnvironment>,
+    mut loops: Query<(&mut AmbientLoop, Option<&AudioSink>)>,
+    settings: Res<GameSettings>,
+    time: Res<Time>,
+) {
+    for (mut ambient, sink) in loops.iter_mut() {
+        let target = if context.current == Some(ambient.kind) { 1.0 } else { 0.0 };
+        ambient.fade = step_fade(ambient.fade, target, time.delta_seconds());
+        if let Some(sink) = sink {
+            sink.set_volume(ambient.fade * settings.ambient_volume * settings.master_volume * (1.0 - MUFFLED_VOLUME * environment.muffle()));
+        }
+    }
+}
+
+fn pause_ambient_loops(sinks: Query<&AudioSink, With<AmbientLoop>>) {
+    for sink in sinks.iter() {
+        sink.pause();
+    }
+}
+
+fn resume_ambient_loops(sinks: Query<&AudioSink, With<AmbientLoop>>) {
+    for sink in sinks.iter() {
+        sink.play();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_picks_surface_or_cave_and_crossfades_over_two_seconds() {
+        assert_eq!(classify(true, Some(0)), Some(AmbientKind::Surface));
+        assert_eq!(classify(false, Some(2)), Some(AmbientKind::Cave));
+        assert_eq!(classify(false, Some(12)), None);
+        assert_eq!(classify(false, None), None);
+
+        // 从地表走进洞穴：风声淡出的同时嗡鸣淡入，一秒后各一半，两秒后完成
+        let (mut surface, mut cave) = (1.0, 0.0);
+        for _ in 0..10 {
+            surface = step_fade(surface, 0.0, 0.1);
+            cave = step_fade(cave, 1.0, 0.1);
+        }
+        assert!((surface - 0.5f32).abs() < 1e-5 && (cave - 0.5f32).abs() < 1e-5);
+        for _ in 0..20 {
+            surface = step_fade(surface, 0.0, 0.1);
+            cave = step_fade(cave, 1.0, 0.1);
+        }
+        assert_eq!((surface, cave), (0.0, 1.0));
+    }
+
+    #[test]
+    fn ambient_loops_ship_with_the_game() {
+        let assets = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        for kind in AmbientKind::ALL {
+            assert!(assets.join(kind.file()).is_file(), "missing {}", kind.file());
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use quartz_nbt::io::{read_nbt, Flavor};
+use quartz_nbt::{NbtCompound, NbtList};
+use serde::Deserialize;
+use crate::game_state::{WorldInfo, WorldManager, WorldState, WorldType};
+use crate::world::chunk::{BlockId, Chunk};
+use crate::world::{fluid, persistence};
+use crate::block_registry;
+
+/// 原版方块名到 `BlockId` 的映射表，可用 --blocks 指定其他文件
+const BLOCK_MAP_FILE: &str = "anvil_block_map.json";
+const SECTOR_BYTES: usize = 4096;
+/// 每个区域文件有 32×32 个 16×16 的区块列，共 512×512 方块，正好是整数个 32³ 区块，可以逐个区域转换和写入
+const REGION_COLUMNS: usize = 32 * 32;
+const SECTION_BLOCKS: usize = 16 * 16 * 16;
+/// 1.18 以前的世界高度范围。旧版本存档省略全空的分段，导入时仍写出整列，否则生成器会在空缺处生成地形
+const LEGACY_MIN_Y: i32 = 0;
+const LEGACY_MAX_Y: i32 = 255;
+
+#[derive(Deserialize)]
+pub struct BlockMap {
+    #[serde(default)]
+    air: HashSet<String>,
+    #[serde(default)]
+    blocks: HashMap<String, BlockId>,
+}
+
+impl BlockMap {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read block map {:?}: {}", path, e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse block map {:?}: {}", path, e))
+    }
+
+    /// 未映射的方块返回 None，导入为石头
+    fn get(&self, name: &str) -> Option<BlockId> {
+        if self.air.contains(name) {
+            Some(BlockId::Air)
+        } else {
+            self.blocks.get(name).copied()
+        }
+    }
+}
+
+#[derive(Default)]
+struct ImportSummary {
+    columns: usize,
+    skipped_columns: usize,
+    chunks: usize,
+    /// 未映射的方块名和方块数
+    unmapped: HashMap<String, u64>,
+}
+
+/// `minecraft_rust import <存档路径> <新世界名> [--blocks <映射文件>]`：
+/// 把 Anvil 格式（1.13 及以后）的原版存档转换为新的世界存档。原存档只读取，不会修改
+pub fn run(args: &[String]) -> Result<(), String> {
+    let [source, world_name, rest @ ..] = args else {
+        return Err("Usage: minecraft_rust import <path-to-minecraft-world> <new-world-name> [--blocks <block-map.json>]".to_string());
+    };
+    let block_map_path = match rest {
+        [] => crate::paths::data_path(BLOCK_MAP_FILE),
+        [flag, path] if flag == "--blocks" => PathBuf::from(path),
+        _ => return Err(format!("Unexpected arguments: {}", rest.join(" "))),
+    };
+    let source = Path::new(source);
+    let region_dir = source.join("region");
+    let mut regions: Vec<PathBuf> = fs::read_dir(&region_dir)
+        .map_err(|e| format!("Failed to read {:?}: {}", region_dir, e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mca"))
+        .collect();
+    regions.sort();
+    let block_map = BlockMap::load(&block_map_path)?;
+
+    let mut manager = WorldManager::new();
+    let info = WorldInfo { name: world_name.clone(), world_type: WorldType::Imported, ..default() };
+    manager.create_world(info).map_err(|e| format!("Failed to create world '{}': {}", world_name, e))?;
+    manager.current_world = Some(world_name.clone());
+    let world_dir = manager.current_world_dir();
+
+    let world_state = WorldState { spawn_point: read_spawn(source), ..default() };
+    persistence::write_atomically(&manager.world_state_path(), world_state.to_json()?.as_bytes())?;
+
+    println!("Importing {} region files from {:?} into {:?}", regions.len(), source, world_dir);
+    let mut summary = ImportSummary::default();
+    for (i, region_path) in regions.iter().enumerate() {
+        let columns_before = summary.columns;
+        let chunks = match import_region(region_path, &block_map, &mut summary) {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                eprintln!("Skipping {:?}: {}", region_path, e);
+                continue;
+            }
+        };
+        for chunk in chunks.values() {
+            persistence::save_chunk(&world_dir, chunk)?;
+        }
+        summary.chunks += chunks.len();
+        println!(
+            "[{}/{}] {}: {} columns -> {} chunks",
+            i + 1,
+            regions.len(),
+            region_path.file_name().unwrap_or_default().to_string_lossy(),
+            summary.columns - columns_before,
+            chunks.len()
+        );
+    }
+
+    println!(
+        "Imported {} columns into {} chunks ({} columns skipped)",
+        summary.columns, summary.chunks, summary.skipped_columns
+    );
+    if !summary.unmapped.is_empty() {
+        let mut unmapped: Vec<_> = summary.unmapped.into_iter().collect();
+        unmapped.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        println!("Unmapped block types (imported as stone):");
+        for (name, count) in unmapped {
+            println!("  {:<40} {}", name, count);
+        }
+    }
+    Ok(())
+}
+
+/// level.dat 中的世界出生点；y 转换为出生点下方的地表高度
+fn read_spawn(source: &Path) -> Option<IVec3> {
+    let mut file = File::open(source.join("level.dat")).ok()?;
+    let (root, _) = read_nbt(&mut file, Flavor::GzCompressed).ok()?;
+    let data = root.get::<_, &NbtCompound>("Data").ok()?;
+    let coord = |key: &str| data.get::<_, i32>(key).ok();
+    Some(IVec3::new(coord("SpawnX")?, coord("SpawnY")? - 1, coord("SpawnZ")?))
+}
+
+fn import_region(path: &Path, block_map: &BlockMap, summary: &mut ImportSummary) -> Result<HashMap<IVec3, Chunk>, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let mut chunks = HashMap::new();
+    // 没有任何区块的区域文件可能是空文件
+    if bytes.len() < SECTOR_BYTES * 2 {
+        return Ok(chunks);
+    }
+    for index in 0..REGION_COLUMNS {
+        let entry = &bytes[index * 4..index * 4 + 4];
+        let offset = u32::from_be_bytes([0, entry[0], entry[1], entry[2]]) as usize * SECTOR_BYTES;
+        if offset == 0 {
+            continue;
+        }
+        match read_column(&bytes, offset).and_then(|root| convert_column(&root, block_map, &mut chunks, summary)) {
+            Ok(()) => summary.columns += 1,
+            Err(e) => {
+                eprintln!("  column {} in {:?}: {}", index, path.file_name().unwrap_or_default(), e);
+                summary.skipped_columns += 1;
+            }
+        }
+    }
+    Ok(chunks)
+}
+
+/// 区块数据：4 字节长度（包含压缩类型字节）+ 1 字节压缩类型 + NBT
+fn read_column(bytes: &[u8], offset: usize) -> Result<NbtCompound, String> {
+    let header = bytes.get(offset..offset + 5).ok_or("chunk offset past end of file")?;
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let flavor = match header[4] {
+        1 => Flavor::GzCompressed,
+        2 => Flavor::ZlibCompressed,
+        3 => Flavor::Uncompressed,
+        other => return Err(format!("unsupported compression type {}", other)),
+    };
+    let data = bytes.get(offset + 5..offset + 4 + length).ok_or("chunk data past end of file")?;
+    read_nbt(&mut Cursor::new(data), flavor).map(|(root, _)| root).map_err(|e| e.to_string())
+}
+
+/// 把一个 16 格宽的区块列写入对应的 32³ 区块。支持 1.18 之后的 sections/block_states 和
+/// 1.13 ~ 1.17 的 Level/Sections 两种结构；更早的数字 ID 格式没有调色板，会报错跳过
+fn convert_column(
+    root: &NbtCompound,
+    block_map: &BlockMap,
+    chunks: &mut HashMap<IVec3, Chunk>,
+    summary: &mut ImportSummary,
+) -> Result<(), String> {
+    let level = root.get::<_, &NbtCompound>("Level").unwrap_or(root);
+    let column_x = level.get::<_, i32>("xPos").map_err(|e| e.to_string())?;
+    let column_z = level.get::<_, i32>("zPos").map_err(|e| e.to_string())?;
+    let sections = level
+        .get::<_, &NbtList>("sections")
+        .or_else(|_| level.get::<_, &NbtList>("Sections"))
+        .map_err(|e| e.to_string())?;
+
+    let origin = IVec3::new(column_x * 16, 0, column_z * 16);
+    let (mut min_y, mut max_y) = (LEGACY_MIN_Y, LEGACY_MAX_Y);
+    for section in sections.iter_map::<&NbtCompound>() {
+        let section = section.map_err(|e| e.to_string())?;
+        let section_y = section.get::<_, i8>("Y").map_err(|e| e.to_string())? as i32;
+        let (palette, data) = match section.get::<_, &NbtCompound>("block_states") {
+            Ok(states) => (states.get::<_, &NbtList>("palette"), states.get::<_, &[i64]>("data").ok()),
+            Err(_) => (section.get::<_, &NbtList>("Palette"), section.get::<_, &[i64]>("BlockStates").ok()),
+        };
+        if section.contains_key("Blocks") {
+            return Err("pre-1.13 worlds with numeric block IDs are not supported".to_string());
+        }
+        // 只有光照数据的分段没有调色板
+        let Ok(palette) = palette else { continue };
+        min_y = min_y.min(section_y * 16);
+        max_y = max_y.max(section_y * 16 + 15);
+
+        let mut names = Vec::with_capacity(palette.len());
+        let mut fluid_levels = Vec::with_capacity(palette.len());
+        let mut axes = Vec::with_capacity(palette.len());
+        for entry in palette.iter_map::<&NbtCompound>() {
+            let entry = entry.map_err(|e| e.to_string())?;
+            names.push(entry.get::<_, &str>("Name").map_err(|e| e.to_string())?);
+            fluid_levels.push(fluid_metadata(entry));
+            axes.push(axis_metadata(entry));
+        }
+        let blocks: Vec<Option<BlockId>> = names.iter().map(|name| block_map.get(name)).collect();
+        let indices = match (names.len(), data) {
+            (0, _) => continue,
+            (1, _) => vec![0; SECTION_BLOCKS],
+            (len, Some(data)) => unpack_indices(data, len).ok_or("block state array has an unexpected length")?,
+            (_, None) => return Err("section has a palette but no block states".to_string()),
+        };
+
+        let mut unmapped_counts = vec![0u64; names.len()];
+        for (i, &index) in indices.iter().enumerate() {
+            let Some(&block) = blocks.get(index as usize) else { continue };
+            let block = block.unwrap_or_else(|| {
+                unmapped_counts[index as usize] += 1;
+                BlockId::Stone
+            });
+            if block == BlockId::Air {
+                continue;
+            }
+            let pos = origin + IVec3::new((i & 15) as i32, section_y * 16 + (i >> 8) as i32, ((i >> 4) & 15) as i32);
+            let coord = pos.div_euclid(IVec3::splat(32));
+            let local = pos - coord * 32;
+            let metadata = match block {
+                BlockId::Log => axes[index as usize],
+                block if block.is_fluid() => fluid_levels[index as usize],
+                _ => 0,
+            };
+            chunks
+                .entry(coord)
+                .or_insert_with(|| Chunk::new(coord))
+                .set_block_with_metadata(local.x as u32, local.y as u32, local.z as u32, block, metadata);
+        }
+        for (name, count) in names.iter().zip(unmapped_counts) {
+            if count > 0 {
+                *summary.unmapped.entry(name.to_string()).or_default() += count;
+            }
+        }
+    }
+
+    // 整列都写出区块（包括全空的），读取存档时不会再由生成器补上地形
+    let column_coord = origin.div_euclid(IVec3::splat(32));
+    for y in min_y.div_euclid(32)..=max_y.div_euclid(32) {
+        let coord = IVec3::new(column_coord.x, y, column_coord.z);
+        chunks.entry(coord).or_insert_with(|| Chunk::new(coord));
+    }
+    Ok(())
+}
+
+/// 原版流体的 `level` 属性：0 为源头，1-7 为流动等级，8 及以上为下落的流体
+fn fluid_metadata(entry: &NbtCompound) -> u8 {
+    let level = entry
+        .get::<_, &NbtCompound>("Properties")
+        .and_then(|properties| properties.get::<_, &str>("level"))
+        .ok()
+        .and_then(|level| level.parse::<u8>().ok())
+        .unwrap_or(0);
+    if level >= 8 { fluid::FALLING } else { level }
+}
+
+/// 原木等方块的 `axis` 属性，转换为轴向方块的附加数据
+fn axis_metadata(entry: &NbtCompound) -> u8 {
+    let axis = match entry.get::<_, &NbtCompound>("Properties").and_then(|properties| properties.get::<_, &str>("axis")) {
+        Ok("x") => IVec3::X,
+        Ok("z") => IVec3::Z,
+        _ => IVec3::Y,
+    };
+    block_registry::axis_metadata(axis)
+}
+
+/// 解包分段的调色板索引（x 最快，其次 z，最后 y）。每个索引至少 4 位。
+/// 1.16 起索引不跨越两个 long，之前的版本连续排列，按数组长度区分
+fn unpack_indices(data: &[i64], palette_len: usize) -> Option<Vec<u16>> {
+    let bits = ((usize::BITS - (palette_len - 1).leading_zeros()) as usize).max(4);
+    let per_long = 64 / bits;
+    let mask = (1u64 << bits) - 1;
+    if data.len() == SECTION_BLOCKS.div_ceil(per_long) {
+        Some((0..SECTION_BLOCKS).map(|i| ((data[i / per_long] as u64 >> (i % per_long * bits)) & mask) as u16).collect())
+    } else if data.len() == SECTION_BLOCKS * bits / 64 {
+        Some((0..SECTION_BLOCKS).map(|i| {
+            let (word, shift) = (i * bits / 64, i * bits % 64);
+            let mut value = data[word] as u64 >> shift;
+            if shift + bits > 64 {
+                value |= (data[word + 1] as u64) << (64 - shift);
+            }
+            (value & mask) as u16
+        }).collect())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quartz_nbt::NbtTag;
+
+    /// 按 1.16+ 的方式打包索引
+    fn pack(indices: &[u16], bits: usize) -> Vec<i64> {
+        let per_long = 64 / bits;
+        indices
+            .chunks(per_long)
+            .map(|group| group.iter().enumerate().fold(0u64, |acc, (i, &v)| acc | (v as u64) << (i * bits)) as i64)
+            .collect()
+    }
+
+    #[test]
+    fn converts_a_palette_section_into_chunks() {
+        let block_map: BlockMap = serde_json::from_str(
+            r#"{ "air": ["minecraft:air"], "blocks": { "minecraft:grass_block": "Grass", "minecraft:stone": "Stone" } }"#,
+        ).unwrap();
+        let names = ["minecraft:air", "minecraft:grass_block", "minecraft:stone", "minecraft:stone", "minecraft:oak_log"];
+        let palette: Vec<NbtTag> = names.iter().map(|name| {
+            let mut entry = NbtCompound::new();
+            entry.insert("Name", *name);
+            NbtTag::Compound(entry)
+        }).collect();
+        // 5 项调色板每个索引 4 位：y=0 层为草方块，(3, 1, 2) 为未映射的原木，其余为空气
+        let mut indices = vec![0u16; SECTION_BLOCKS];
+        indices[..256].fill(1);
+        indices[256 + 2 * 16 + 3] = 4;
+
+        let mut states = NbtCompound::new();
+        states.insert("palette", NbtList::from(palette));
+        states.insert("data", pack(&indices, 4));
+        let mut section = NbtCompound::new();
+        section.insert("Y", 4i8);
+        section.insert("block_states", states);
+        let mut root = NbtCompound::new();
+        root.insert("xPos", 1);
+        root.insert("zPos", -1);
+        root.insert("sections", NbtList::from(vec![NbtTag::Compound(section)]));
+
+        let mut chunks = HashMap::new();
+        let mut summary = ImportSummary::default();
+        convert_column(&root, &block_map, &mut chunks, &mut summary).unwrap();
+
+        // 方块 (16, 64, -16) 位于区块 (0, 2, -1) 的本地坐标 (16, 0, 16)
+        let chunk = &chunks[&IVec3::new(0, 2, -1)];
+        assert_eq!(chunk.get_block(16, 0, 16), BlockId::Grass);
+        assert_eq!(chunk.get_block(31, 0, 31), BlockId::Grass);
+        assert_eq!(chunk.get_block(15, 0, 16), BlockId::Air);
+        assert_eq!(chunk.get_block(19, 1, 18), BlockId::Stone);
+        assert_eq!(summary.unmapped["minecraft:oak_log"], 1);
+        // 旧版本高度范围内的整列区块都会写出
+        assert!((0..=7).all(|y| chunks.contains_key(&IVec3::new(0, y, -1))));
+
+        // 1.13 ~ 1.15 的跨 long 排列：5 位索引
+        let values: Vec<u16> = (0..SECTION_BLOCKS).map(|i| (i % 17) as u16).collect();
+        let mut spanning = vec![0u64; SECTION_BLOCKS * 5 / 64];
+        for (i, &v) in values.iter().enumerate() {
+            let (word, shift) = (i * 5 / 64, i * 5 % 64);
+            spanning[word] |= (v as u64) << shift;
+            if shift + 5 > 64 {
+                spanning[word + 1] |= (v as u64) >> (64 - shift);
+            }
+        }
+        let spanning: Vec<i64> = spanning.into_iter().map(|v| v as i64).collect();
+        assert_eq!(unpack_indices(&spanning, 17).unwrap(), values);
+        assert_eq!(unpack_indices(&pack(&values, 5), 17).unwrap(), values);
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use bevy::audio::{AudioSinkPlayback, GlobalVolume, Volume};
+use bevy::prelude::*;
+use crate::block_registry::BlockRegistry;
+use crate::camera_environment::CameraEnvironment;
+use crate::game_state::GameState;
+use crate::ui::GameSettings;
+use crate::world::chunk::BlockId;
+
+/// 没有脚本定义或脚本未指定时使用的声音材质
+const DEFAULT_SOUND: &str = "stone";
+const CLICK_SOUND: &str = "sounds/ui/click.ogg";
+const TOOL_BREAK_SOUND: &str = "sounds/random/break.ogg";
+const FUSE_SOUND: &str = "sounds/random/fuse.ogg";
+const EXPLODE_SOUND: &str = "sounds/random/explode.ogg";
+/// 摄像机在流体中时游戏音效降低的音量和音调比例，近似被水闷住的声音（不是真正的低通滤波）
+const MUFFLED_VOLUME: f32 = 0.5;
+const MUFFLED_PITCH: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundKind {
+    Dig,
+    Place,
+    Step,
+    Click,
+    ToolBreak,
+    Fuse,
+    Explode,
+}
+
+/// 播放一次音效；方块音效按方块脚本定义中的 `sound` 类别选择文件
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SoundEvent {
+    pub kind: SoundKind,
+    pub block: Option<BlockId>,
+}
+
+impl SoundEvent {
+    pub fn block(kind: SoundKind, block: BlockId) -> Self {
+        Self { kind, block: Some(block) }
+    }
+
+    pub fn click() -> Self {
+        Self { kind: SoundKind::Click, block: None }
+    }
+
+    /// 工具耐久耗尽
+    pub fn tool_break() -> Self {
+        Self { kind: SoundKind::ToolBreak, block: None }
+    }
+
+    /// TNT 被点燃
+    pub fn fuse() -> Self {
+        Self { kind: SoundKind::Fuse, block: None }
+    }
+
+    pub fn explosion() -> Self {
+        Self { kind: SoundKind::Explode, block: None }
+    }
+}
+
+/// 游戏内音效，暂停时一起暂停；界面音效没有此标记
+#[derive(Component)]
+struct EffectSound;
+
+/// 已加载的音效句柄，避免每次播放都重新查找路径
+#[derive(Resource, Default)]
+struct SoundHandles(HashMap<String, Handle<AudioSource>>);
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SoundEvent>()
+           .init_resource::<SoundHandles>()
+           .add_systems(Update, (
+               sync_global_volume.run_if(resource_changed::<GameSettings>()),
+               play_sound_events,
+           ))
+           .add_systems(OnEnter(GameState::Paused), pause_effect_sounds)
+           .add_systems(OnExit(GameState::Paused), resume_effect_sounds);
+    }
+}
+
+fn sync_global_volume(settings: Res<GameSettings>, mut global_volume: ResMut<GlobalVolume>) {
+    *global_volume = GlobalVolume::new(settings.master_volume);
+}
+
+fn sound_path(event: &SoundEvent, registry: Option<&BlockRegistry>) -> String {
+    let folder = match event.kind {
+        SoundKind::Dig => "dig",
+        SoundKind::Place => "place",
+        SoundKind::Step => "step",
+        SoundKind::Click => return CLICK_SOUND.to_string(),
+        SoundKind::ToolBreak => return TOOL_BREAK_SOUND.to_string(),
+        SoundKind::Fuse => return FUSE_SOUND.to_string(),
+        SoundKind::Explode => return EXPLODE_SOUND.to_string(),
+    };
+    let category = event
+        .block
+        .and_then(|block| registry?.definition_for(block))
+        .map_or(DEFAULT_SOUND, |definition| definition.sound.as_str());
+    format!("sounds/{}/{}.ogg", folder, category)
+}
+
+fn play_sound_events(
+    mut commands: Commands,
+    mut events: EventReader<SoundEvent>,
+    mut handles: ResMut<SoundHandles>,
+    asset_server: Res<AssetServer>,
+    registry: Option<Res<BlockRegistry>>,
+    settings: Res<GameSettings>,
+    environment: Res<CameraEnvironment>,
+) {
+    let muffle = environment.muffle();
+    for event in events.read() {
+        let path = sound_path(event, registry.as_deref());
+        let source = handles
+            .0
+            .entry(path)
+            .or_insert_with_key(|path| asset_server.load(path.clone()))
+            .clone();
+        let playback = PlaybackSettings::DESPAWN.with_volume(Volume::new_relative(settings.effects_volume));
+        if event.kind == SoundKind::Click {
+            commands.spawn(AudioBundle { source, settings: playback });
+        } else {
+            let playback = playback
+                .with_volume(Volume::new_relative(settings.effects_volume * (1.0 - MUFFLED_VOLUME * muffle)))
+                .with_speed(1.0 - MUFFLED_PITCH * muffle);
+            commands.spawn((AudioBundle { source, settings: playback }, EffectSound));
+        }
+    }
+}
+
+fn pause_effect_sounds(sinks: Query<&AudioSink, With<EffectSound>>) {
+    for sink in sinks.iter() {
+        sink.pause();
+    }
+}
+
+fn resume_effect_sounds(sinks: Query<&AudioSink, With<EffectSound>>) {
+    for sink in sinks.iter() {
+        sink.play();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::scripting::ScriptEngine;
+
+    #[test]
+    fn every_sound_resolves_to_a_shipped_file() {
+        let engine = ScriptEngine::new(concat!(env!("CARGO_MANIFEST_DIR"), "/scripts"));
+        let mut registry = BlockRegistry::new();
+        registry.load_from_scripts(&engine).unwrap();
+        // 没有脚本定义的方块使用默认材质
+        let mut events = vec![
+            SoundEvent::click(),
+            SoundEvent::tool_break(),
+            SoundEvent::fuse(),
+            SoundEvent::explosion(),
+            SoundEvent { kind: SoundKind::Dig, block: None },
+        ];
+        for definition in registry.get_all_registered_blocks() {
+            let Some(block) = registry.get_block_id(&definition.id) else { continue };
+            events.extend([SoundKind::Dig, SoundKind::Place, SoundKind::Step].map(|kind| SoundEvent::block(kind, block)));
+        }
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        for event in events {
+            let path = sound_path(&event, Some(&registry));
+            assert!(assets.join(&path).is_file(), "missing {} for {:?}", path, event);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use bevy::app::AppExit;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+use crate::controller::{ControlMode, FirstPersonController};
+use crate::game_state::{GameState, SaveTask, WorldDataSave, WorldManager, WorldState};
+use crate::hud::hud_visible;
+use crate::inventory::PlayerInventory;
+use crate::localization::LocalizationManager;
+use crate::net::client::connected_to_server;
+use crate::stats::{flush_stats, WorldStats};
+use crate::ui::GameSettings;
+use crate::world::chunk::Chunk;
+use crate::world::entity_persistence::PersistentEntities;
+use crate::world::pending_blocks::{PendingBlockStore, PendingBlocks};
+use crate::world::persistence;
+
+/// 每个保存任务写入的区块数，修改过的区块较多时分成多个任务并行写入
+const CHUNKS_PER_TASK: usize = 16;
+/// 退出时等待最终保存完成的最长时间
+const EXIT_SAVE_TIMEOUT: Duration = Duration::from_secs(5);
+const PLAYER_FILE: &str = "player.json";
+/// 玩家存档格式版本。新增字段时加 `#[serde(default)]` 并提高版本号，不兼容的修改还需要在 `game_state::migrations` 中添加迁移
+pub const PLAYER_SAVE_VERSION: u32 = 2;
+
+/// 存档中的玩家数据
+#[derive(Serialize, Deserialize)]
+pub struct PlayerSave {
+    pub version: u32,
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub mode: ControlMode,
+    pub inventory: PlayerInventory,
+}
+
+impl PlayerSave {
+    /// 读取世界存档中的玩家数据。新世界（文件不存在）返回 None，使用出生点；文件无法识别时返回错误
+    pub fn read(world_dir: &Path) -> Result<Option<Self>, String> {
+        let path = world_dir.join(PLAYER_FILE);
+        let Ok(content) = fs::read_to_string(&path) else { return Ok(None) };
+        match serde_json::from_str::<PlayerSave>(&content) {
+            Ok(save) if save.version <= PLAYER_SAVE_VERSION => Ok(Some(save)),
+            Ok(save) => Err(format!("Player save {:?} has unsupported version {}", path, save.version)),
+            Err(e) => Err(format!("Failed to parse player save {:?}: {}", path, e)),
+        }
+    }
+}
+
+/// 立即保存整个世界（服务器控制台的 save-all 命令）
+#[derive(Event)]
+pub struct SaveWorldEvent;
+
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        // 联机时世界保存在服务器上，客户端不写入本地存档
+        app.add_event::<SaveWorldEvent>()
+           .init_resource::<FailedChunkSaves>()
+           .add_systems(Update, (
+               autosave_timer.run_if(in_state(GameState::InGame)),
+               autosave_now.run_if(on_event::<SaveWorldEvent>()),
+               remark_failed_chunk_saves,
+           ).run_if(not(connected_to_server)))
+           .add_systems(OnExit(GameState::InGame), autosave_now.after(flush_stats).run_if(not(connected_to_server)))
+           // 在主菜单退出时还没有打开任何世界
+           .add_systems(Last, final_save_on_exit.after(flush_stats).run_if(not(connected_to_server)).run_if(not(in_state(GameState::MainMenu))));
+
+        // 保存提示只在有界面的客户端显示（UiPlugin 先于本插件添加 EguiPlugin）
+        if app.is_plugin_added::<EguiPlugin>() {
+            app.add_systems(Update, saving_indicator.run_if(hud_visible));
+        }
+    }
+}
+
+/// 后台写入失败的区块坐标，由 `remark_failed_chunk_saves` 重新标记为修改过，下次保存时再写入
+#[derive(Resource, Default, Clone)]
+struct FailedChunkSaves(Arc<Mutex<Vec<IVec3>>>);
+
+impl FailedChunkSaves {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<IVec3>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 除区块和玩家以外随世界保存的资源，以及写入区块的持久实体
+#[derive(SystemParam)]
+struct WorldSaveSources<'w, 's> {
+    world_manager: Res<'w, WorldManager>,
+    world_state: Res<'w, WorldState>,
+    stats: Res<'w, WorldStats>,
+    pending_blocks: Res<'w, PendingBlockStore>,
+    persistent_entities: PersistentEntities<'w, 's>,
+    failed_chunks: Res<'w, FailedChunkSaves>,
+}
+
+/// 把持久实体写入所在区块后，收集修改过的区块、玩家数据、世界状态、统计和等待写入的结构方块，分批启动后台写入任务，并清除区块的修改标记。
+/// 写入失败的区块记录到 `FailedChunkSaves`，之后重新标记
+fn start_world_save(
+    sources: &WorldSaveSources,
+    chunks: &mut Query<&mut Chunk>,
+    player: &Query<(&Transform, &FirstPersonController, &PlayerInventory)>,
+) -> Vec<Task<Result<(), String>>> {
+    let WorldSaveSources { world_manager, world_state, stats, pending_blocks, persistent_entities, failed_chunks } = sources;
+    persistent_entities.store_all(chunks);
+    let mut modified = Vec::new();
+    for mut chunk in chunks.iter_mut() {
+        if chunk.modified {
+            chunk.modified = false;
+            modified.push(chunk.clone());
+        }
+    }
+
+    let world_dir = world_manager.current_world_dir();
+    let task_pool = AsyncComputeTaskPool::get();
+    let mut tasks = Vec::new();
+
+    let state_path = world_manager.world_state_path();
+    let state_json = world_state.to_json();
+    tasks.push(task_pool.spawn(async move {
+        persistence::write_atomically(&state_path, state_json?.as_bytes())
+    }));
+
+    let stats_path = WorldStats::path(&world_dir);
+    let stats_json = stats.to_json();
+    tasks.push(task_pool.spawn(async move {
+        persistence::write_atomically(&stats_path, stats_json?.as_bytes())
+    }));
+
+    let pending_path = PendingBlocks::path(&world_dir);
+    let pending_bytes = pending_blocks.lock().to_bytes();
+    tasks.push(task_pool.spawn(async move {
+        persistence::write_atomically(&pending_path, &pending_bytes?)
+    }));
+
+    while !modified.is_empty() {
+        let batch: Vec<Chunk> = modified.drain(..modified.len().min(CHUNKS_PER_TASK)).collect();
+        let world_dir = world_dir.clone();
+        let failed_chunks = FailedChunkSaves::clone(failed_chunks);
+        tasks.push(task_pool.spawn(async move {
+            // 一个区块失败时继续写入同一批的其余区块，只重新标记没写进去的
+            let mut result = Ok(());
+            for chunk in &batch {
+                if let Err(e) = persistence::save_chunk(&world_dir, chunk) {
+                    failed_chunks.lock().push(chunk.coord);
+                    result = Err(e);
+                }
+            }
+            result
+        }));
+    }
+
+    if let Ok((transform, controller, inventory)) = player.get_single() {
+        let save = PlayerSave {
+            version: PLAYER_SAVE_VERSION,
+            position: transform.translation,
+            yaw: controller.yaw,
+            pitch: controller.pitch,
+            mode: controller.mode,
+            inventory: inventory.clone(),
+        };
+        tasks.push(task_pool.spawn(async move {
+            let json = serde_json::to_string_pretty(&save).map_err(|e| format!("Failed to serialize player: {}", e))?;
+            persistence::write_atomically(&world_dir.join(PLAYER_FILE), json.as_bytes())
+        }));
+    }
+    tasks
+}
+
+fn autosave_now(
+    mut commands: Commands,
+    mut chunks: Query<&mut Chunk>,
+    player: Query<(&Transform, &FirstPersonController, &PlayerInventory)>,
+    sources: WorldSaveSources,
+) {
+    let tasks = start_world_save(&sources, &mut chunks, &player);
+    debug!("Autosave started with {} tasks", tasks.len());
+    for task in tasks {
+        commands.spawn((SaveTask { task }, WorldDataSave));
+    }
+}
+
+/// 写入失败的区块重新标记为修改过，避免下次保存时漏掉。区块已经卸载时无法恢复
+fn remark_failed_chunk_saves(failed_chunks: Res<FailedChunkSaves>, mut chunks: Query<&mut Chunk>) {
+    let failed: HashSet<IVec3> = failed_chunks.lock().drain(..).collect();
+    if failed.is_empty() {
+        return;
+    }
+    for mut chunk in chunks.iter_mut() {
+        if failed.contains(&chunk.coord) {
+            chunk.modified = true;
+        }
+    }
+}
+
+/// 按设置中的间隔自动保存，只在游戏进行中计时
+fn autosave_timer(
+    commands: Commands,
+    chunks: Query<&mut Chunk>,
+    player: Query<(&Transform, &FirstPersonController, &PlayerInventory)>,
+    sources: WorldSaveSources,
+    settings: Option<Res<GameSettings>>,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+) {
+    *elapsed += time.delta_seconds();
+    let minutes = settings.map_or(GameSettings::default().autosave_minutes, |settings| settings.autosave_minutes);
+    if *elapsed < minutes * 60.0 {
+        return;
+    }
+    *elapsed = 0.0;
+    autosave_now(commands, chunks, player, sources);
+}
+
+/// 退出前同步完成最终保存，超时后放弃等待，避免存档被截断
+fn final_save_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    mut chunks: Query<&mut Chunk>,
+    player: Query<(&Transform, &FirstPersonController, &PlayerInventory)>,
+    sources: WorldSaveSources,
+    mut pending: Query<&mut SaveTask>,
+) {
+    if exit_events.read().count() == 0 {
+        return;
+    }
+    let mut tasks = start_world_save(&sources, &mut chunks, &player);
+    let mut pending: Vec<Mut<SaveTask>> = pending.iter_mut().collect();
+    let mut waiting: Vec<&mut Task<Result<(), String>>> = tasks
+        .iter_mut()
+        .chain(pending.iter_mut().map(|save_task| &mut save_task.task))
+        .collect();
+
+    let deadline = Instant::now() + EXIT_SAVE_TIMEOUT;
+    while !waiting.is_empty() && Instant::now() < deadline {
+        waiting.retain_mut(|task| match future::block_on(future::poll_once(&mut **task)) {
+            Some(Err(e)) => {
+                error!("Final save failed: {}", e);
+                false
+            }
+            Some(Ok(())) => false,
+            None => true,
+        });
+        if !waiting.is_empty() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    let remaining = waiting.len();
+    if remaining > 0 {
+        warn!("Gave up waiting for {} save tasks after {:?}", remaining, EXIT_SAVE_TIMEOUT);
+    } else {
+        info!("World saved before exit");
+    }
+}
+
+fn saving_indicator(
+    mut contexts: EguiContexts,
+    saves: Query<(), With<SaveTask>>,
+    localization: Res<LocalizationManager>,
+) {
+    if saves.is_empty() {
+        return;
+    }
+    egui::Area::new(egui::Id::new("saving_indicator"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(egui::RichText::new(localization.get("game.autosave.saving")).color(egui::Color32::LIGHT_GRAY));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::{ItemStack, ItemType};
+    use crate::world::chunk::BlockId;
+
+    #[test]
+    fn player_save_round_trips_and_rejects_newer_versions() {
+        let dir = std::env::temp_dir().join(format!("player_save_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut inventory = PlayerInventory::default();
+        inventory.main[13] = ItemStack::new(ItemType::Block(BlockId::Dirt), 7);
+        inventory.selected_slot = 6;
+        let mut save = PlayerSave {
+            version: PLAYER_SAVE_VERSION,
+            position: Vec3::new(10.5, 80.0, -3.25),
+            yaw: 1.0,
+            pitch: -0.5,
+            mode: ControlMode::Flying,
+            inventory,
+        };
+        fs::write(dir.join(PLAYER_FILE), serde_json::to_string(&save).unwrap()).unwrap();
+
+        let loaded = PlayerSave::read(&dir).unwrap().unwrap();
+        assert_eq!(loaded.position, save.position);
+        assert_eq!(loaded.mode, ControlMode::Flying);
+        assert_eq!(loaded.inventory.main[13], save.inventory.main[13]);
+        assert_eq!(loaded.inventory.selected_slot, 6);
+
+        save.version = PLAYER_SAVE_VERSION + 1;
+        fs::write(dir.join(PLAYER_FILE), serde_json::to_string(&save).unwrap()).unwrap();
+        assert!(PlayerSave::read(&dir).is_err_and(|e| e.contains("unsupported version")));
+        fs::remove_file(dir.join(PLAYER_FILE)).unwrap();
+        assert!(PlayerSave::read(&dir).unwrap().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
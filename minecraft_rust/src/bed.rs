@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use crate::random::random_unit;
+use crate::game_state::{GameState, Weather, WorldState};
+use crate::hud::ScreenFade;
+use crate::localization::LocalizationManager;
+use crate::notifications::Notifications;
+
+/// 睡觉时屏幕变黑和恢复各用的秒数，以及全黑保持的秒数
+const SLEEP_FADE_SECONDS: f32 = 1.0;
+const SLEEP_HOLD_SECONDS: f32 = 1.0;
+/// 睡醒时的时间（日出）
+const WAKE_UP_TIME: f32 = 0.25;
+
+/// 玩家右键了床（只在单人游戏中发送）
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UseBedEvent {
+    pub pos: IVec3,
+}
+
+/// 正在睡觉：屏幕淡入黑色，全黑时跳到早上，再淡出
+#[derive(Resource, Default)]
+struct Sleeping {
+    elapsed: f32,
+    woke_up: bool,
+}
+
+pub struct BedPlugin;
+
+impl Plugin for BedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UseBedEvent>()
+           .add_systems(Update, (
+               use_bed,
+               advance_sleep.after(use_bed).run_if(resource_exists::<Sleeping>()),
+           ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 使用床时把它设为重生点；夜晚还会开始睡觉
+fn use_bed(
+    mut commands: Commands,
+    mut events: EventReader<UseBedEvent>,
+    mut world_state: ResMut<WorldState>,
+    sleeping: Option<Res<Sleeping>>,
+    localization: Res<LocalizationManager>,
+    mut notifications: ResMut<Notifications>,
+) {
+    for event in events.read() {
+        world_state.player_spawn = Some(event.pos);
+        notifications.info(localization.get("game.bed.spawn_set"));
+        if world_state.is_night() && sleeping.is_none() {
+            commands.insert_resource(Sleeping::default());
+        }
+    }
+}
+
+/// 睡觉过程中遮罩的不透明度
+fn sleep_fade(elapsed: f32) -> f32 {
+    let fade_out_at = SLEEP_FADE_SECONDS + SLEEP_HOLD_SECONDS;
+    if elapsed < SLEEP_FADE_SECONDS {
+        elapsed / SLEEP_FADE_SECONDS
+    } else if elapsed < fade_out_at {
+        1.0
+    } else {
+        (1.0 - (elapsed - fade_out_at) / SLEEP_FADE_SECONDS).max(0.0)
+    }
+}
+
+/// 推进睡觉过程：屏幕全黑时把时间调到早上并让雨停下，淡出结束后醒来
+fn advance_sleep(
+    mut commands: Commands,
+    mut sleeping: ResMut<Sleeping>,
+    mut world_state: ResMut<WorldState>,
+    mut fade: ResMut<ScreenFade>,
+    time: Res<Time>,
+) {
+    sleeping.elapsed += time.delta_seconds();
+    if !sleeping.woke_up && sleeping.elapsed >= SLEEP_FADE_SECONDS {
+        sleeping.woke_up = true;
+        world_state.time_of_day = WAKE_UP_TIME;
+        if world_state.weather == Weather::Rain {
+            let random = random_unit(time.elapsed_seconds_f64().to_bits(), 0);
+            world_state.set_weather(Weather::Clear, None, random);
+        }
+        info!("Slept through the night");
+    }
+    fade.0 = sleep_fade(sleeping.elapsed);
+    if sleeping.elapsed >= SLEEP_FADE_SECONDS * 2.0 + SLEEP_HOLD_SECONDS {
+        commands.remove_resource::<Sleeping>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleep_fades_to_black_and_back() {
+        assert_eq!(sleep_fade(0.0), 0.0);
+        assert_eq!(sleep_fade(SLEEP_FADE_SECONDS * 0.5), 0.5);
+        assert_eq!(sleep_fade(SLEEP_FADE_SECONDS + SLEEP_HOLD_SECONDS * 0.5), 1.0);
+        assert_eq!(sleep_fade(SLEEP_FADE_SECONDS * 2.0 + SLEEP_HOLD_SECONDS), 0.0);
+    }
+}
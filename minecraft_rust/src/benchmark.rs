@@ -0,0 +1,363 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use crate::controller::{ControlMode, FirstPersonController};
+use crate::game_state::{GameState, Weather, WorldInfo, WorldManager, WorldState, SAVES_DIR};
+use crate::ui::GameSettings;
+use crate::world::chunk_loader::{ChunkLoaderSet, ChunkWorkBudget};
+use crate::world::chunk_stats::ChunkStats;
+use crate::world::generator::WorldGeneratorConfig;
+use crate::world::chunk::Chunk;
+
+/// 启动基准测试模式的命令行参数
+pub const BENCHMARK_FLAG: &str = "--benchmark";
+/// 基准测试使用的世界目录，每次运行前删除，保证所有区块都重新生成
+const BENCHMARK_WORLD: &str = "benchmark-run";
+const BENCHMARK_SEED: u32 = 12345;
+/// 报告目录（游戏根目录下）
+const REPORT_DIR: &str = "benchmarks";
+
+/// 飞行路线：先在出生点上方沿 +X 方向直线飞行，然后螺旋下降到地下
+const FLIGHT_HEIGHT: f32 = 24.0;   // 直线飞行时高于出生点的高度
+const FLIGHT_SPEED: f32 = 20.0;    // 直线飞行速度（格/秒）
+const FLIGHT_SECONDS: f32 = 30.0;
+const FLIGHT_PITCH: f32 = -0.2;    // 直线飞行时略微向下看
+const SPIRAL_RADIUS: f32 = 24.0;
+const SPIRAL_TURNS: f32 = 3.0;
+const SPIRAL_DEPTH: f32 = 96.0;    // 螺旋下降的总深度，终点在出生点以下 SPIRAL_DEPTH - FLIGHT_HEIGHT 格
+const SPIRAL_SECONDS: f32 = 20.0;
+
+/// 基准测试运行状态。存在这个资源时玩家输入被忽略，由 `fly_benchmark_path` 控制摄像机
+#[derive(Resource, Default)]
+pub struct Benchmark {
+    start: Option<Vec3>,  // 路线起点（进入游戏时玩家的位置）
+    elapsed: f32,
+    frames: Vec<FrameSample>,
+    finished: bool,
+}
+
+/// 每帧记录的数据，区块和网格数量为累计值，取自 `ChunkStats` 和 `ChunkWorkBudget`
+#[derive(Clone, Copy, Debug, Serialize)]
+struct FrameSample {
+    time: f32,
+    frame_ms: f32,
+    loaded_chunks: usize,
+    chunks_generated: usize,  // 完成的生成任务（包括从存档读取）
+    generation_ms: f32,       // 生成任务的累计耗时
+    chunks_completed: usize,
+    chunks_discarded: usize,
+    chunk_reloads: usize,
+    meshes_built: usize,
+    mesh_rebuilds: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct BenchmarkSummary {
+    seed: u32,
+    horizontal_radius: f32,
+    duration_secs: f32,
+    frames: usize,
+    avg_fps: f32,
+    avg_frame_ms: f32,
+    p50_frame_ms: f32,
+    p95_frame_ms: f32,
+    p99_frame_ms: f32,
+    max_frame_ms: f32,
+    one_percent_low_fps: f32,  // 最慢的 1% 帧的平均帧率
+    chunks_generated: usize,
+    chunks_per_second: f32,
+    avg_generation_ms: f32,  // 单个生成任务的平均耗时
+    chunks_discarded: usize,
+    chunk_reloads: usize,
+    peak_loaded_chunks: usize,
+    meshes_built: usize,
+    mesh_rebuilds: usize,
+}
+
+#[derive(Serialize)]
+struct BenchmarkReport<'a> {
+    summary: &'a BenchmarkSummary,
+    frames: &'a [FrameSample],
+}
+
+pub struct BenchmarkPlugin;
+
+impl Plugin for BenchmarkPlugin {
+    fn build(&self, app: &mut App) {
+        app.world.resource_mut::<WorldManager>().current_world = Some(BENCHMARK_WORLD.to_string());
+        app.insert_resource(WorldGeneratorConfig { seed: BENCHMARK_SEED, ..default() })
+           .init_resource::<Benchmark>()
+           // 世界状态在加载界面的后台任务中读取，进入游戏时再覆盖
+           .add_systems(OnExit(GameState::Loading), fix_world_state)
+           .add_systems(Update, (
+               fly_benchmark_path.before(ChunkLoaderSet),
+               record_benchmark_frame.after(ChunkLoaderSet),
+           ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 删除上一次基准测试的世界存档。同名目录是正常世界（有 world_info.json）时拒绝删除
+pub fn prepare_world() -> Result<(), String> {
+    let dir = crate::paths::data_path(SAVES_DIR).join(BENCHMARK_WORLD);
+    if WorldInfo::path(&dir).exists() {
+        return Err(format!("Refusing to delete {}: it is a regular world save", dir.display()));
+    }
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete benchmark world {}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+/// 固定在正午、晴天，关闭昼夜和天气循环，两次运行的画面和刷怪条件相同
+fn fix_world_state(mut world_state: ResMut<WorldState>) {
+    world_state.time_of_day = 0.5;
+    world_state.weather = Weather::Clear;
+    world_state.set_rule("do_daylight_cycle", "false");
+    world_state.set_rule("do_weather_cycle", "false");
+}
+
+/// 路线总时长（秒）
+fn path_duration() -> f32 {
+    FLIGHT_SECONDS + SPIRAL_SECONDS
+}
+
+/// 路线在 `t` 秒时的位置和朝向 (位置, yaw, pitch)，只取决于起点和时间。
+/// 螺旋从直线的终点开始并沿相同方向切入，路线连续
+fn camera_path(start: Vec3, t: f32) -> (Vec3, f32, f32) {
+    let flight_end = start + Vec3::new(FLIGHT_SPEED * FLIGHT_SECONDS, FLIGHT_HEIGHT, 0.0);
+    if t < FLIGHT_SECONDS {
+        let position = start + Vec3::new(FLIGHT_SPEED * t, FLIGHT_HEIGHT, 0.0);
+        return (position, -std::f32::consts::FRAC_PI_2, FLIGHT_PITCH);
+    }
+
+    // 绕 flight_end - Z * 半径 旋转，角度为 0 时位于 flight_end、朝向 +X
+    let progress = ((t - FLIGHT_SECONDS) / SPIRAL_SECONDS).min(1.0);
+    let angle = progress * SPIRAL_TURNS * std::f32::consts::TAU;
+    let center = flight_end - Vec3::Z * SPIRAL_RADIUS;
+    let position = center + Vec3::new(angle.sin() * SPIRAL_RADIUS, -progress * SPIRAL_DEPTH, angle.cos() * SPIRAL_RADIUS);
+    let pitch = (-SPIRAL_DEPTH).atan2(SPIRAL_RADIUS * SPIRAL_TURNS * std::f32::consts::TAU);
+    (position, angle - std::f32::consts::FRAC_PI_2, pitch)
+}
+
+/// 代替玩家输入沿固定路线移动玩家和摄像机
+fn fly_benchmark_path(
+    mut benchmark: ResMut<Benchmark>,
+    mut player: Query<(&mut Transform, &mut FirstPersonController, &Children)>,
+    mut cameras: Query<&mut Transform, (With<Camera3d>, Without<FirstPersonController>)>,
+    time: Res<Time>,
+) {
+    let Ok((mut transform, mut controller, children)) = player.get_single_mut() else { return };
+    let start = match benchmark.start {
+        Some(start) => start,
+        None => {
+            info!("Benchmark started at {:?}, running for {} seconds", transform.translation, path_duration());
+            controller.mode = ControlMode::Flying;
+            benchmark.start = Some(transform.translation);
+            transform.translation
+        }
+    };
+    benchmark.elapsed += time.delta_seconds();
+    if benchmark.elapsed >= path_duration() {
+        benchmark.finished = true;
+    }
+
+    let (position, yaw, pitch) = camera_path(start, benchmark.elapsed);
+    controller.velocity = Vec3::ZERO;
+    controller.yaw = yaw;
+    controller.pitch = pitch;
+    transform.translation = position;
+    transform.rotation = Quat::from_axis_angle(Vec3::Y, yaw);
+    for &child in children.iter() {
+        if let Ok(mut camera_transform) = cameras.get_mut(child) {
+            camera_transform.rotation = Quat::from_axis_angle(Vec3::X, pitch);
+        }
+    }
+}
+
+/// 记录每帧的耗时和区块流水线计数，路线结束时写出报告并退出游戏
+fn record_benchmark_frame(
+    mut benchmark: ResMut<Benchmark>,
+    chunks: Query<(), With<Chunk>>,
+    stats: Res<ChunkStats>,
+    budget: Res<ChunkWorkBudget>,
+    settings: Res<GameSettings>,
+    time: Res<Time>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if benchmark.start.is_none() {
+        return;
+    }
+    let counts = stats.counters().snapshot();
+    let sample = FrameSample {
+        time: benchmark.elapsed,
+        frame_ms: time.delta_seconds() * 1000.0,
+        loaded_chunks: chunks.iter().count(),
+        chunks_generated: counts.generated as usize,
+        generation_ms: counts.generation_ms as f32,
+        chunks_completed: counts.completed as usize,
+        chunks_discarded: counts.discarded as usize,
+        chunk_reloads: counts.reloaded as usize,
+        meshes_built: budget.meshes_built,
+        mesh_rebuilds: budget.mesh_rebuilds,
+    };
+    benchmark.frames.push(sample);
+    if !benchmark.finished {
+        return;
+    }
+
+    if let Some(summary) = summarize(&benchmark.frames, settings.horizontal_radius) {
+        for line in summary_text(&summary).lines() {
+            info!("{}", line);
+        }
+        match write_report(&summary, &benchmark.frames) {
+            Ok(path) => info!("Benchmark report saved to {}", path.display()),
+            Err(e) => error!("{}", e),
+        }
+    }
+    exit.send(AppExit);
+}
+
+/// 排好序的帧耗时的百分位数
+fn percentile(sorted: &[f32], fraction: f32) -> f32 {
+    let index = ((sorted.len() - 1) as f32 * fraction).round() as usize;
+    sorted[index]
+}
+
+/// 由每帧数据计算汇总。第一帧包含进入游戏的切换耗时，只用作计数的基准
+fn summarize(frames: &[FrameSample], horizontal_radius: f32) -> Option<BenchmarkSummary> {
+    let (first, rest) = frames.split_first()?;
+    let last = rest.last()?;
+    let mut frame_ms: Vec<f32> = rest.iter().map(|frame| frame.frame_ms).collect();
+    frame_ms.sort_by(f32::total_cmp);
+    let total_ms: f32 = frame_ms.iter().sum();
+    let slowest = &frame_ms[frame_ms.len() - frame_ms.len().div_ceil(100)..];
+    let slowest_avg_ms = slowest.iter().sum::<f32>() / slowest.len() as f32;
+    let duration_secs = total_ms / 1000.0;
+    let chunks_generated = last.chunks_completed - first.chunks_completed;
+    let generation_tasks = last.chunks_generated - first.chunks_generated;
+
+    Some(BenchmarkSummary {
+        seed: BENCHMARK_SEED,
+        horizontal_radius,
+        duration_secs,
+        frames: frame_ms.len(),
+        avg_fps: frame_ms.len() as f32 / duration_secs.max(f32::EPSILON),
+        avg_frame_ms: total_ms / frame_ms.len() as f32,
+        p50_frame_ms: percentile(&frame_ms, 0.5),
+        p95_frame_ms: percentile(&frame_ms, 0.95),
+        p99_frame_ms: percentile(&frame_ms, 0.99),
+        max_frame_ms: frame_ms[frame_ms.len() - 1],
+        one_percent_low_fps: 1000.0 / slowest_avg_ms.max(f32::EPSILON),
+        chunks_generated,
+        chunks_per_second: chunks_generated as f32 / duration_secs.max(f32::EPSILON),
+        avg_generation_ms: (last.generation_ms - first.generation_ms) / generation_tasks.max(1) as f32,
+        chunks_discarded: last.chunks_discarded - first.chunks_discarded,
+        chunk_reloads: last.chunk_reloads - first.chunk_reloads,
+        peak_loaded_chunks: frames.iter().map(|frame| frame.loaded_chunks).max().unwrap_or(0),
+        meshes_built: last.meshes_built - first.meshes_built,
+        mesh_rebuilds: last.mesh_rebuilds - first.mesh_rebuilds,
+    })
+}
+
+fn summary_text(summary: &BenchmarkSummary) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "---- Benchmark (seed {}, radius {}) ----", summary.seed, summary.horizontal_radius);
+    let _ = writeln!(text, "{} frames in {:.1} s, avg {:.1} FPS, 1% low {:.1} FPS", summary.frames, summary.duration_secs, summary.avg_fps, summary.one_percent_low_fps);
+    let _ = writeln!(
+        text,
+        "Frame time: avg {:.2} ms, p50 {:.2} ms, p95 {:.2} ms, p99 {:.2} ms, max {:.2} ms",
+        summary.avg_frame_ms, summary.p50_frame_ms, summary.p95_frame_ms, summary.p99_frame_ms, summary.max_frame_ms
+    );
+    let _ = writeln!(text, "Chunks: {} generated ({:.1}/s), peak {} loaded", summary.chunks_generated, summary.chunks_per_second, summary.peak_loaded_chunks);
+    let _ = writeln!(
+        text,
+        "Generation: avg {:.2} ms per chunk, {} discarded, {} reloads",
+        summary.avg_generation_ms, summary.chunks_discarded, summary.chunk_reloads
+    );
+    let _ = write!(text, "Meshes: {} built, {} rebuilds", summary.meshes_built, summary.mesh_rebuilds);
+    text
+}
+
+/// 写出 JSON 报告（汇总 + 每帧数据）和每帧数据的 CSV，返回 JSON 文件路径
+fn write_report(summary: &BenchmarkSummary, frames: &[FrameSample]) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_path(REPORT_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create benchmark directory {}: {}", dir.display(), e))?;
+    let stem = format!("benchmark-{}", chrono::Local::now().format("%Y-%m-%d_%H.%M.%S"));
+
+    let json = serde_json::to_string_pretty(&BenchmarkReport { summary, frames })
+        .map_err(|e| format!("Failed to serialize benchmark report: {}", e))?;
+    let json_path = dir.join(format!("{}.json", stem));
+    write_file(&json_path, &json)?;
+
+    let mut csv = String::from(
+        "time,frame_ms,loaded_chunks,chunks_generated,generation_ms,chunks_completed,chunks_discarded,chunk_reloads,meshes_built,mesh_rebuilds\n",
+    );
+    for frame in frames {
+        let _ = writeln!(
+            csv,
+            "{:.4},{:.3},{},{},{:.3},{},{},{},{},{}",
+            frame.time, frame.frame_ms, frame.loaded_chunks, frame.chunks_generated, frame.generation_ms, frame.chunks_completed,
+            frame.chunks_discarded, frame.chunk_reloads, frame.meshes_built, frame.mesh_rebuilds
+        );
+    }
+    write_file(&dir.join(format!("{}.csv", stem)), &csv)?;
+    Ok(json_path)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_path_is_continuous_and_ends_underground() {
+        let start = Vec3::new(10.0, 70.0, -5.0);
+        let (before, yaw_before, _) = camera_path(start, FLIGHT_SECONDS - 1e-4);
+        let (after, yaw_after, _) = camera_path(start, FLIGHT_SECONDS);
+        assert!(before.distance(after) < 0.01);
+        assert!((yaw_before - yaw_after).abs() < 1e-3);
+        // 直线飞行朝向 +X
+        let forward = FirstPersonController { yaw: yaw_before, ..default() }.look_direction();
+        assert!(forward.x > 0.99);
+
+        let (end, _, _) = camera_path(start, path_duration());
+        assert_eq!(end.y, start.y + FLIGHT_HEIGHT - SPIRAL_DEPTH);
+        // 路线只取决于起点和时间
+        assert_eq!(camera_path(start, 37.5), camera_path(start, 37.5));
+    }
+
+    #[test]
+    fn summary_uses_counter_deltas_and_frame_percentiles() {
+        let frames: Vec<FrameSample> = (0..=100)
+            .map(|i| FrameSample {
+                time: i as f32 * 0.01,
+                frame_ms: if i == 100 { 50.0 } else { 10.0 },
+                loaded_chunks: 100 + i,
+                chunks_generated: 25 + i * 2,
+                generation_ms: i as f32 * 6.0,
+                chunks_completed: 20 + i * 2,
+                chunks_discarded: i / 4,
+                chunk_reloads: 0,
+                meshes_built: 5 + i,
+                mesh_rebuilds: i / 10,
+            })
+            .collect();
+        let summary = summarize(&frames, 12.0).unwrap();
+        assert_eq!(summary.frames, 100);
+        assert_eq!(summary.chunks_generated, 200);
+        assert_eq!(summary.avg_generation_ms, 3.0);
+        assert_eq!(summary.chunks_discarded, 25);
+        assert_eq!(summary.peak_loaded_chunks, 200);
+        assert_eq!(summary.meshes_built, 100);
+        assert_eq!(summary.mesh_rebuilds, 10);
+        assert_eq!(summary.p50_frame_ms, 10.0);
+        assert_eq!(summary.max_frame_ms, 50.0);
+        assert!((summary.one_percent_low_fps - 20.0).abs() < 1e-3);
+        assert!(summarize(&frames[..1], 12.0).is_none());
+    }
+}
@@ -8,10 +8,75 @@ use crate::world::chunk::BlockId;
 pub struct ScriptBlockDefinition {
     pub id: String,
     pub hardness: f32,
+    pub blast_resistance: f32,  // 爆炸抗性，越高越难被炸掉
     pub transparent: bool,
     pub solid: bool,
     pub texture: Option<String>,
     pub light_level: u8,
+    pub sound: String,  // 声音材质类别，对应 sounds/<dig|place|step>/<sound>.ogg
+    pub orientable: Option<Orientable>,  // 放置时按点击的面或玩家朝向决定朝向
+    pub on_neighbor_changed: bool,  // 脚本定义了 on_neighbor_changed，函数保存在 Lua 全局表 neighbor_hooks 中
+    pub description_key: Option<String>,  // 物品提示中的描述，脚本中的 `description` 是本地化键
+}
+
+/// 保存各方块 `on_neighbor_changed` 函数的 Lua 全局表
+const NEIGHBOR_HOOKS_TABLE: &str = "neighbor_hooks";
+
+/// 方块放置时如何决定朝向（脚本中的 `orientable = "axis"` 或 `"facing"`），朝向写在附加数据中，
+/// 只在放置时决定，破坏后掉落的物品不保留朝向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Orientable {
+    /// 沿被点击面的法线摆放（原木），附加数据见 `axis_metadata`
+    Axis,
+    /// 正面朝向玩家，附加数据为正面的水平朝向：0 北（-Z），1 南（+Z），2 西（-X），3 东（+X）
+    Facing,
+}
+
+impl Orientable {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "axis" => Some(Orientable::Axis),
+            "facing" => Some(Orientable::Facing),
+            _ => None,
+        }
+    }
+
+    /// 放置时写入的附加数据：`face_normal` 是被点击面的法线，`look` 是玩家的视线方向
+    pub fn placement_metadata(self, face_normal: IVec3, look: Vec3) -> u8 {
+        match self {
+            Orientable::Axis => axis_metadata(face_normal),
+            Orientable::Facing => {
+                let toward_player = -look;
+                if toward_player.x.abs() > toward_player.z.abs() {
+                    if toward_player.x < 0.0 { 2 } else { 3 }
+                } else if toward_player.z < 0.0 {
+                    0
+                } else {
+                    1
+                }
+            }
+        }
+    }
+}
+
+/// 轴向方块的附加数据：0 为竖直（Y 轴，生成和默认的朝向），1 为 X 轴，2 为 Z 轴
+pub fn axis_metadata(axis: IVec3) -> u8 {
+    if axis.x != 0 {
+        1
+    } else if axis.z != 0 {
+        2
+    } else {
+        0
+    }
+}
+
+/// `axis_metadata` 的逆运算，返回正方向的单位轴
+pub fn metadata_axis(metadata: u8) -> IVec3 {
+    match metadata {
+        1 => IVec3::X,
+        2 => IVec3::Z,
+        _ => IVec3::Y,
+    }
 }
 
 impl Default for ScriptBlockDefinition {
@@ -19,10 +84,15 @@ impl Default for ScriptBlockDefinition {
         Self {
             id: "unknown".to_string(),
             hardness: 1.0,
+            blast_resistance: 1.0,
             transparent: false,
             solid: true,
             texture: None,
             light_level: 0,
+            sound: "stone".to_string(),
+            orientable: None,
+            on_neighbor_changed: false,
+            description_key: None,
         }
     }
 }
@@ -42,7 +112,7 @@ impl BlockRegistry {
         info!("Loading block definitions from separate Lua script files...");
         
         // 需要加载的方块类型
-        let block_names = vec!["stone", "dirt", "grass", "bedrock"];
+        let block_names = vec!["stone", "dirt", "grass", "bedrock", "water", "lava", "obsidian", "sand", "gravel", "torch", "crafting_table", "chest", "tnt", "bed", "log", "leaves", "sapling"];
         
         for block_name in block_names {
             let script_path = format!("{}.lua", block_name);
@@ -69,6 +139,10 @@ impl BlockRegistry {
                             definition.hardness = hardness;
                         }
                         
+                        if let Ok(blast_resistance) = block_def.get::<_, f32>("blast_resistance") {
+                            definition.blast_resistance = blast_resistance;
+                        }
+                        
                         if let Ok(transparent) = block_def.get::<_, bool>("transparent") {
                             definition.transparent = transparent;
                         }
@@ -84,6 +158,34 @@ impl BlockRegistry {
                         if let Ok(light_level) = block_def.get::<_, u8>("light_level") {
                             definition.light_level = light_level;
                         }
+
+                        if let Ok(sound) = block_def.get::<_, String>("sound") {
+                            definition.sound = sound;
+                        }
+
+                        if let Ok(description) = block_def.get::<_, String>("description") {
+                            definition.description_key = Some(description);
+                        }
+
+                        if let Ok(orientable) = block_def.get::<_, String>("orientable") {
+                            definition.orientable = Orientable::from_name(&orientable);
+                            if definition.orientable.is_none() {
+                                warn!("Unknown orientable '{}' for block {}", orientable, block_name);
+                            }
+                        }
+                        
+                        if let Ok(hook) = block_def.get::<_, mlua::Function>("on_neighbor_changed") {
+                            let hooks = match lua.globals().get::<_, mlua::Table>(NEIGHBOR_HOOKS_TABLE) {
+                                Ok(hooks) => hooks,
+                                Err(_) => {
+                                    let hooks = lua.create_table()?;
+                                    lua.globals().set(NEIGHBOR_HOOKS_TABLE, hooks.clone())?;
+                                    hooks
+                                }
+                            };
+                            hooks.set(block_name, hook)?;
+                            definition.on_neighbor_changed = true;
+                        }
                         
                         info!("Registered script block: {} (hardness: {}, texture: {:?})", 
                               definition.id, definition.hardness, definition.texture);
@@ -94,6 +196,19 @@ impl BlockRegistry {
                             "dirt" => BlockId::Dirt,
                             "grass" => BlockId::Grass,
                             "bedrock" => BlockId::Bedrock,
+                            "water" => BlockId::Water,
+                            "lava" => BlockId::Lava,
+                            "obsidian" => BlockId::Obsidian,
+                            "sand" => BlockId::Sand,
+                            "gravel" => BlockId::Gravel,
+                            "torch" => BlockId::Torch,
+                            "crafting_table" => BlockId::CraftingTable,
+                            "chest" => BlockId::Chest,
+                            "tnt" => BlockId::Tnt,
+                            "bed" => BlockId::Bed,
+                            "log" => BlockId::Log,
+                            "leaves" => BlockId::Leaves,
+                            "sapling" => BlockId::Sapling,
                             _ => BlockId::Stone, // 默认映射
                         };
                         
@@ -121,6 +236,67 @@ impl BlockRegistry {
         self.id_to_blockid.get(script_id).copied()
     }
 
+    /// 映射到该 BlockId 的脚本方块定义
+    pub fn definition_for(&self, block: BlockId) -> Option<&ScriptBlockDefinition> {
+        self.id_to_blockid
+            .iter()
+            .find(|(_, id)| **id == block)
+            .and_then(|(script_id, _)| self.definitions.get(script_id))
+    }
+
+    /// 放置时需要决定朝向的方块
+    pub fn orientable(&self, block: BlockId) -> Option<Orientable> {
+        self.definition_for(block).and_then(|definition| definition.orientable)
+    }
+
+    /// 物品提示中方块描述的本地化键
+    pub fn description_key(&self, block: BlockId) -> Option<&str> {
+        self.definition_for(block).and_then(|definition| definition.description_key.as_deref())
+    }
+
+    /// 各方块的发光等级（按 BlockId 编号索引），取自脚本定义的 `light_level`
+    pub fn light_emission(&self) -> [u8; BlockId::COUNT] {
+        let mut emission = [0; BlockId::COUNT];
+        for (script_id, block) in &self.id_to_blockid {
+            if let Some(definition) = self.definitions.get(script_id) {
+                emission[*block as usize] = emission[*block as usize].max(definition.light_level.min(15));
+            }
+        }
+        emission
+    }
+
+    /// 各方块的爆炸抗性（按 BlockId 编号索引），没有脚本定义的方块使用默认值
+    pub fn blast_resistance(&self) -> [f32; BlockId::COUNT] {
+        let mut resistance = [ScriptBlockDefinition::default().blast_resistance; BlockId::COUNT];
+        for (script_id, block) in &self.id_to_blockid {
+            if let Some(definition) = self.definitions.get(script_id) {
+                resistance[*block as usize] = definition.blast_resistance;
+            }
+        }
+        resistance
+    }
+
+    /// 脚本定义了 `on_neighbor_changed` 的方块（按 BlockId 编号索引）
+    pub fn neighbor_hooks(&self) -> [bool; BlockId::COUNT] {
+        let mut hooks = [false; BlockId::COUNT];
+        for (script_id, block) in &self.id_to_blockid {
+            if self.definitions.get(script_id).is_some_and(|definition| definition.on_neighbor_changed) {
+                hooks[*block as usize] = true;
+            }
+        }
+        hooks
+    }
+
+    /// 调用方块脚本的 `on_neighbor_changed(x, y, z)`，返回 true 表示方块应被破坏并掉落
+    pub fn call_neighbor_hook(&self, script_engine: &ScriptEngine, block: BlockId, pos: IVec3) -> Result<bool, mlua::Error> {
+        let Some(definition) = self.definition_for(block) else { return Ok(false) };
+        script_engine.with_lua(|lua| {
+            let hooks = lua.globals().get::<_, mlua::Table>(NEIGHBOR_HOOKS_TABLE)?;
+            let hook = hooks.get::<_, mlua::Function>(definition.id.as_str())?;
+            Ok(hook.call::<_, Option<bool>>((pos.x, pos.y, pos.z))?.unwrap_or(false))
+        })
+    }
+
     pub fn call_block_event(&self, script_engine: &ScriptEngine, block_id: &str, event: &str, args: String) -> Result<String, mlua::Error> {
         script_engine.with_lua(|lua| {
             let globals = lua.globals();
@@ -146,4 +322,21 @@ impl BlockRegistry {
     pub fn get_all_registered_blocks(&self) -> Vec<&ScriptBlockDefinition> {
         self.definitions.values().collect()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_flags_match_block_behaviour() {
+        let engine = ScriptEngine::new(concat!(env!("CARGO_MANIFEST_DIR"), "/scripts"));
+        let mut registry = BlockRegistry::new();
+        registry.load_from_scripts(&engine).unwrap();
+        for definition in registry.get_all_registered_blocks() {
+            let Some(block) = registry.get_block_id(&definition.id) else { continue };
+            assert_eq!(definition.solid, block.is_solid(), "solid flag of {}", definition.id);
+            assert_eq!(definition.transparent, !block.is_opaque(), "transparent flag of {}", definition.id);
+        }
+    }
+}
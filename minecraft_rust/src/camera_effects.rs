@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+use bevy::render::camera::Projection;
+use crate::camera_environment::CameraEnvironment;
+use crate::controller::{is_on_ground, ControlMode, FirstPersonController, EYE_HEIGHT};
+use crate::game_state::GameState;
+use crate::inventory_screen::inventory_screen_open;
+use crate::ui::GameSettings;
+use crate::world::access::WorldView;
+use crate::world::chunk::Chunk;
+use crate::world::storage::ChunkStorage;
+
+/// 视角晃动：每走一格相位增加的弧度，以及左右和上下晃动的幅度（格）
+const BOB_PHASE_PER_BLOCK: f32 = 1.9;
+const BOB_SWAY: f32 = 0.03;
+const BOB_HEIGHT: f32 = 0.05;
+/// 晃动幅度和疾跑视野变化趋向目标值的速度（每秒）
+const BOB_SMOOTHING: f32 = 10.0;
+const FOV_SMOOTHING: f32 = 8.0;
+/// 疾跑时视野增加的比例
+const SPRINT_FOV_KICK: f32 = 0.1;
+/// 落地时摄像机每 1 格/秒冲击速度下沉的距离，最大下沉距离，以及恢复速度（每秒）
+const LANDING_DIP_PER_SPEED: f32 = 0.012;
+const MAX_LANDING_DIP: f32 = 0.25;
+const LANDING_RECOVERY: f32 = 8.0;
+/// 缩放视野过渡的速度（每秒）
+const ZOOM_SMOOTHING: f32 = 12.0;
+
+/// 只影响画面的摄像机效果状态。摄像机的本地变换在这里叠加偏移，
+/// 射线检测等游戏逻辑使用玩家位置加 `EYE_HEIGHT`，不受影响
+#[derive(Resource, Default)]
+struct CameraEffects {
+    bob_phase: f32,
+    bob_amount: f32,       // 当前晃动幅度（0.0 ~ 1.0 左右），随水平速度平滑变化
+    fov_kick: f32,         // 当前疾跑视野变化（0.0 ~ 1.0）
+    landing_dip: f32,      // 当前落地下沉距离（格）
+    was_on_ground: bool,
+    last_fall_speed: f32,  // 上一帧的下落速度，落地时作为冲击速度
+}
+
+/// 缩放状态。缩放时视野缩小到设置中的缩放视野，鼠标灵敏度按视野比例降低
+#[derive(Resource)]
+pub struct Zoom {
+    active: bool,
+    amount: f32,                 // 当前缩放程度（0.0 ~ 1.0），平滑过渡
+    pub sensitivity_scale: f32,  // 鼠标灵敏度的缩放比例：当前视野 / 不缩放时的视野
+}
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Self { active: false, amount: 0.0, sensitivity_scale: 1.0 }
+    }
+}
+
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraEffects>()
+           .init_resource::<Zoom>()
+           .add_systems(Update, (
+               handle_zoom_key.run_if(not(inventory_screen_open)),
+               update_camera_effects.after(handle_zoom_key),
+           ).run_if(in_state(GameState::InGame)))
+           .add_systems(OnExit(GameState::InGame), reset_camera_effects);
+    }
+}
+
+/// 按住缩放键时缩放；设置为切换模式时按一下开始、再按一下结束
+fn handle_zoom_key(keyboard: Res<Input<KeyCode>>, settings: Res<GameSettings>, mut zoom: ResMut<Zoom>) {
+    let key = settings.keys.zoom;
+    let active = if settings.zoom_toggle {
+        zoom.active != keyboard.just_pressed(key)
+    } else {
+        keyboard.pressed(key)
+    };
+    if zoom.active != active {
+        zoom.active = active;
+    }
+}
+
+/// 离开游戏（暂停、回到菜单）时取消缩放和疾跑视野，摄像机恢复设置中的视野，
+/// 暂停界面的设置窗口看到的不是缩放后的值
+fn reset_camera_effects(
+    mut effects: ResMut<CameraEffects>,
+    mut zoom: ResMut<Zoom>,
+    settings: Res<GameSettings>,
+    mut projections: Query<&mut Projection>,
+) {
+    *effects = CameraEffects::default();
+    *zoom = Zoom::default();
+    for mut projection in projections.iter_mut() {
+        if let Projection::Perspective(ref mut perspective) = *projection {
+            perspective.fov = settings.fov.to_radians();
+        }
+    }
+}
+
+/// 以速率 `rate`（每秒）把 `current` 平滑地移向 `target`
+fn approach(current: f32, target: f32, rate: f32, delta_time: f32) -> f32 {
+    current + (target - current) * (rate * delta_time).min(1.0)
+}
+
+/// 晃动相位对应的摄像机偏移（玩家本地空间）：左右摆动一次的时间内上下起伏两次
+fn bob_offset(phase: f32, amount: f32) -> Vec3 {
+    Vec3::new(phase.cos() * BOB_SWAY, -phase.sin().abs() * BOB_HEIGHT, 0.0) * amount
+}
+
+/// 落地冲击速度对应的下沉距离
+fn landing_dip(impact_speed: f32) -> f32 {
+    (impact_speed * LANDING_DIP_PER_SPEED).min(MAX_LANDING_DIP)
+}
+
+/// 设置中的视野加上疾跑视野变化，再向缩放视野过渡（缩放时忽略疾跑视野变化）。返回 (视野, 不缩放时的视野)，单位为度
+fn camera_fov(settings: &GameSettings, fov_kick: f32, zoom: f32) -> (f32, f32) {
+    let unzoomed = settings.fov * (1.0 + SPRINT_FOV_KICK * fov_kick * settings.sprint_fov_kick.clamp(0.0, 1.0));
+    (unzoomed + (settings.zoom_fov - unzoomed) * zoom, unzoomed)
+}
+
+/// 在行走模式下贴地移动时晃动视角，疾跑时平滑增大视野，落地时让摄像机下沉一下，按住缩放键时缩小视野。
+/// 前三种效果的强度分别由设置中的滑块控制，为 0 时关闭。在水或岩浆中时视野再按摄像机环境缩小
+fn update_camera_effects(
+    mut effects: ResMut<CameraEffects>,
+    (mut zoom, environment): (ResMut<Zoom>, Res<CameraEnvironment>),
+    player: Query<(&Transform, &FirstPersonController, &Children)>,
+    mut cameras: Query<(&mut Transform, &mut Projection), Without<FirstPersonController>>,
+    chunks: Query<&Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+    settings: Res<GameSettings>,
+    time: Res<Time>,
+) {
+    let Ok((transform, controller, children)) = player.get_single() else { return };
+    let delta_time = time.delta_seconds();
+    let walking = controller.mode == ControlMode::Walking;
+    let player_height = if controller.is_sneaking { 1.5 } else { 1.8 };
+    let on_ground = walking && is_on_ground(transform.translation, player_height, &WorldView::new(&chunk_storage, &chunks));
+    let horizontal_speed = controller.velocity.xz().length();
+
+    // 视角晃动
+    let bob_target = if on_ground { (horizontal_speed / settings.movement.walk_speed.max(0.1)).min(1.5) } else { 0.0 };
+    effects.bob_amount = approach(effects.bob_amount, bob_target, BOB_SMOOTHING, delta_time);
+    if on_ground {
+        effects.bob_phase = (effects.bob_phase + horizontal_speed * delta_time * BOB_PHASE_PER_BLOCK) % std::f32::consts::TAU;
+    }
+
+    // 落地下沉
+    if on_ground && !effects.was_on_ground {
+        effects.landing_dip = effects.landing_dip.max(landing_dip(effects.last_fall_speed));
+    }
+    effects.landing_dip = approach(effects.landing_dip, 0.0, LANDING_RECOVERY, delta_time);
+    effects.was_on_ground = on_ground;
+    effects.last_fall_speed = if walking { (-controller.velocity.y).max(0.0) } else { 0.0 };
+
+    // 疾跑视野：冲刺状态只在行走时向前移动才成立，撞墙或停下时随之结束
+    effects.fov_kick = approach(effects.fov_kick, if controller.is_sprinting { 1.0 } else { 0.0 }, FOV_SMOOTHING, delta_time);
+
+    let offset = bob_offset(effects.bob_phase, effects.bob_amount) * settings.view_bobbing.clamp(0.0, 1.0)
+        - Vec3::Y * effects.landing_dip * settings.landing_dip.clamp(0.0, 1.0);
+    zoom.amount = approach(zoom.amount, if zoom.active { 1.0 } else { 0.0 }, ZOOM_SMOOTHING, delta_time);
+    let (fov, unzoomed) = camera_fov(&settings, effects.fov_kick, zoom.amount);
+    zoom.sensitivity_scale = fov / unzoomed;
+    let fov = (fov * environment.fov_scale()).to_radians();
+    for &child in children.iter() {
+        let Ok((mut camera_transform, mut projection)) = cameras.get_mut(child) else { continue };
+        let translation = Vec3::Y * EYE_HEIGHT + offset;
+        if camera_transform.translation != translation {
+            camera_transform.translation = translation;
+        }
+        if let Projection::Perspective(ref mut perspective) = *projection {
+            perspective.fov = fov;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bobbing_and_landing_dip_stay_small() {
+        // 晃动在相位 0 时只有左右偏移，四分之一周期时下沉到最低
+        assert_eq!(bob_offset(0.0, 1.0), Vec3::new(BOB_SWAY, 0.0, 0.0));
+        let lowest = bob_offset(std::f32::consts::FRAC_PI_2, 1.0);
+        assert!((lowest.y + BOB_HEIGHT).abs() < 1e-6);
+        assert_eq!(bob_offset(1.0, 0.0), Vec3::ZERO);
+
+        // 下沉距离随冲击速度增大，但有上限
+        assert!(landing_dip(5.0) < landing_dip(10.0));
+        assert_eq!(landing_dip(1000.0), MAX_LANDING_DIP);
+        assert_eq!(landing_dip(0.0), 0.0);
+    }
+
+    #[test]
+    fn zoom_overrides_the_fov_setting_and_sprint_kick() {
+        let settings = GameSettings { fov: 80.0, zoom_fov: 20.0, ..default() };
+        assert_eq!(camera_fov(&settings, 0.0, 0.0), (80.0, 80.0));
+        let (sprinting, _) = camera_fov(&settings, 1.0, 0.0);
+        assert!((sprinting - 88.0).abs() < 1e-4);
+        // 完全缩放时只取决于缩放视野，停止缩放后回到设置中的视野
+        assert_eq!(camera_fov(&settings, 1.0, 1.0).0, 20.0);
+        assert_eq!(camera_fov(&settings, 0.0, 1.0), (20.0, 80.0));
+    }
+}
@@ -0,0 +1,256 @@
+use bevy::pbr::{FogFalloff, FogSettings};
+use bevy::prelude::*;
+use crate::controller::{FirstPersonController, EYE_HEIGHT};
+use crate::game_state::GameState;
+use crate::health::PlayerDamageEvent;
+use crate::world::access::WorldView;
+use crate::world::chunk::{BlockId, Chunk};
+use crate::world::fluid::surface_height;
+use crate::world::storage::ChunkStorage;
+
+/// 进入或离开流体时各效果过渡的时间（秒），在水面上下浮动时不会突然切换
+const TRANSITION_SECONDS: f32 = 0.3;
+/// 全屏色调的颜色，alpha 为完全处于流体中时的不透明度
+const WATER_TINT: Color = Color::rgba(0.1, 0.3, 0.8, 0.3);
+const LAVA_TINT: Color = Color::rgba(1.0, 0.35, 0.0, 0.6);
+/// 流体中的雾：完全遮住远处的距离（格）。空气中没有雾，过渡时从 `AIR_FOG_END` 开始缩短
+const WATER_FOG_END: f32 = 14.0;
+const LAVA_FOG_END: f32 = 2.0;
+const AIR_FOG_END: f32 = 96.0;
+const WATER_FOG_COLOR: Color = Color::rgb(0.05, 0.2, 0.45);
+const LAVA_FOG_COLOR: Color = Color::rgb(0.7, 0.2, 0.0);
+/// 流体中视野缩小的比例
+const FLUID_FOV_REDUCTION: f32 = 0.1;
+/// 身体在岩浆中时每次受到的伤害，受伤后的无敌时间决定伤害频率
+const LAVA_DAMAGE: f32 = 4.0;
+
+/// 摄像机所在的介质
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CameraMedium {
+    #[default]
+    Air,
+    Water,
+    Lava,
+}
+
+impl CameraMedium {
+    fn of(block: BlockId) -> Self {
+        match block {
+            BlockId::Water => CameraMedium::Water,
+            BlockId::Lava => CameraMedium::Lava,
+            _ => CameraMedium::Air,
+        }
+    }
+}
+
+/// 摄像机环境：摄像机（眼睛）位于水或岩浆中时的全屏色调、雾、视野和声音效果。
+/// `water` 和 `lava` 是各自效果的当前强度（0.0 ~ 1.0），在 `TRANSITION_SECONDS` 内线性过渡，
+/// 离开流体后回到 0，所有效果随之完全恢复
+#[derive(Resource, Default)]
+pub struct CameraEnvironment {
+    medium: CameraMedium,
+    water: f32,
+    lava: f32,
+}
+
+impl CameraEnvironment {
+    /// 流体效果的总强度
+    fn strength(&self) -> f32 {
+        (self.water + self.lava).min(1.0)
+    }
+
+    /// 视野的缩放比例，由 `update_camera_effects` 乘到最终视野上
+    pub fn fov_scale(&self) -> f32 {
+        1.0 - FLUID_FOV_REDUCTION * self.strength()
+    }
+
+    /// 声音被闷住的程度（0.0 ~ 1.0）。这不是真正的低通滤波：Bevy 的音频播放不能给声音加滤波器，
+    /// 由音频系统降低音量和音调来近似
+    pub fn muffle(&self) -> f32 {
+        self.strength()
+    }
+
+    /// 按介质向目标强度过渡一步
+    fn step(&mut self, medium: CameraMedium, delta_time: f32) {
+        self.medium = medium;
+        let step = delta_time / TRANSITION_SECONDS;
+        let approach = |current: f32, target: f32| if target > current { (current + step).min(target) } else { (current - step).max(target) };
+        self.water = approach(self.water, if medium == CameraMedium::Water { 1.0 } else { 0.0 });
+        self.lava = approach(self.lava, if medium == CameraMedium::Lava { 1.0 } else { 0.0 });
+    }
+
+    /// 全屏色调：两种流体的颜色按强度加权混合
+    fn tint(&self) -> Color {
+        let (water, lava) = (WATER_TINT.a() * self.water, LAVA_TINT.a() * self.lava);
+        let alpha = water + lava;
+        if alpha <= 0.0 {
+            return Color::NONE;
+        }
+        let mix = |a: f32, b: f32| (a * water + b * lava) / alpha;
+        Color::rgba(mix(WATER_TINT.r(), LAVA_TINT.r()), mix(WATER_TINT.g(), LAVA_TINT.g()), mix(WATER_TINT.b(), LAVA_TINT.b()), alpha.min(1.0))
+    }
+
+    /// 流体中的雾，完全离开流体后为 `None`
+    fn fog(&self) -> Option<FogSettings> {
+        let strength = self.strength();
+        if strength <= 0.0 {
+            return None;
+        }
+        let (color, end) = if self.lava > self.water { (LAVA_FOG_COLOR, LAVA_FOG_END) } else { (WATER_FOG_COLOR, WATER_FOG_END) };
+        Some(FogSettings {
+            color: color.with_a(strength),
+            falloff: FogFalloff::Linear { start: 0.0, end: AIR_FOG_END + (end - AIR_FOG_END) * strength },
+            ..default()
+        })
+    }
+}
+
+/// 覆盖整个屏幕的流体色调，在其他界面下方
+#[derive(Component)]
+struct FluidOverlay;
+
+pub struct CameraEnvironmentPlugin;
+
+impl Plugin for CameraEnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraEnvironment>()
+           .add_systems(OnExit(GameState::Loading), setup_fluid_overlay)
+           .add_systems(OnEnter(GameState::PhotoMode), hide_fluid_overlay)
+           .add_systems(Update, (update_camera_environment, burn_in_lava).run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn setup_fluid_overlay(mut commands: Commands, existing: Query<(), With<FluidOverlay>>) {
+    if !existing.is_empty() {
+        return;
+    }
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                display: Display::None,
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(-10),
+            ..default()
+        },
+        FluidOverlay,
+    ));
+}
+
+/// 摄影模式中摄像机离开玩家，不显示玩家眼睛所在流体的色调；回到游戏后由 `update_camera_environment` 重新显示
+fn hide_fluid_overlay(mut overlay: Query<&mut Style, With<FluidOverlay>>) {
+    for mut style in overlay.iter_mut() {
+        style.display = Display::None;
+    }
+}
+
+/// `pos` 处的介质：流体方块中低于流体表面的部分才算在流体中
+fn medium_at(world: &WorldView, pos: Vec3) -> CameraMedium {
+    let block_pos = pos.floor().as_ivec3();
+    let Some((block, metadata)) = world.get_with_metadata(block_pos) else { return CameraMedium::Air };
+    if !block.is_fluid() {
+        return CameraMedium::Air;
+    }
+    let covered = world.block(block_pos + IVec3::Y) == Some(block);
+    if pos.y - block_pos.y as f32 <= surface_height(metadata, covered) {
+        CameraMedium::of(block)
+    } else {
+        CameraMedium::Air
+    }
+}
+
+/// 按摄像机所在的方块更新流体效果，写入摄像机的雾和全屏色调
+fn update_camera_environment(
+    mut environment: ResMut<CameraEnvironment>,
+    player: Query<(&Transform, &Children), With<FirstPersonController>>,
+    mut cameras: Query<Option<&mut FogSettings>, With<Camera3d>>,
+    mut overlay: Query<(&mut Style, &mut BackgroundColor), With<FluidOverlay>>,
+    mut commands: Commands,
+    // 读取摄像机所在的方块
+    (chunk_storage, chunks): (Res<ChunkStorage>, Query<&Chunk>),
+    time: Res<Time>,
+) {
+    let Ok((transform, children)) = player.get_single() else { return };
+    let medium = medium_at(&WorldView::new(&chunk_storage, &chunks), transform.translation + Vec3::Y * EYE_HEIGHT);
+    let was_active = environment.strength() > 0.0;
+    if medium != environment.medium {
+        debug!("Camera entered {:?}", medium);
+    }
+    environment.step(medium, time.delta_seconds());
+    // 不在流体中且效果已经恢复时不需要再写入
+    if !was_active && environment.strength() <= 0.0 {
+        return;
+    }
+
+    let fog = environment.fog();
+    for &child in children.iter() {
+        let Ok(current) = cameras.get_mut(child) else { continue };
+        match (current, &fog) {
+            (Some(mut current), Some(fog)) => *current = fog.clone(),
+            (None, Some(fog)) => {
+                commands.entity(child).insert(fog.clone());
+            }
+            (Some(_), None) => {
+                commands.entity(child).remove::<FogSettings>();
+            }
+            (None, None) => {}
+        }
+    }
+
+    let tint = environment.tint();
+    for (mut style, mut color) in overlay.iter_mut() {
+        style.display = if tint.a() > 0.0 { Display::Flex } else { Display::None };
+        color.0 = tint;
+    }
+}
+
+/// 身体（脚或眼睛）在岩浆中时持续受到伤害
+fn burn_in_lava(
+    player: Query<&Transform, With<FirstPersonController>>,
+    chunk_storage: Res<ChunkStorage>,
+    chunks: Query<&Chunk>,
+    mut damage: EventWriter<PlayerDamageEvent>,
+) {
+    let Ok(transform) = player.get_single() else { return };
+    let world = WorldView::new(&chunk_storage, &chunks);
+    let in_lava = [0.1, EYE_HEIGHT].into_iter().any(|height| world.block((transform.translation + Vec3::Y * height).floor().as_ivec3()) == Some(BlockId::Lava));
+    if in_lava {
+        damage.send(PlayerDamageEvent { amount: LAVA_DAMAGE, knockback: Vec3::ZERO });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fluid_effects_fade_in_and_revert_completely() {
+        let mut environment = CameraEnvironment::default();
+        assert_eq!(environment.fov_scale(), 1.0);
+        assert!(environment.fog().is_none());
+
+        // 一帧内不会突然切换，约 0.3 秒后完全进入水中
+        environment.step(CameraMedium::Water, 0.1);
+        assert!(environment.muffle() > 0.0 && environment.muffle() < 0.5);
+        for _ in 0..3 {
+            environment.step(CameraMedium::Water, 0.1);
+        }
+        assert_eq!(environment.muffle(), 1.0);
+        assert!((environment.fov_scale() - (1.0 - FLUID_FOV_REDUCTION)).abs() < 1e-6);
+        assert!((environment.tint().a() - WATER_TINT.a()).abs() < 1e-6);
+        let Some(FogSettings { falloff: FogFalloff::Linear { end, .. }, .. }) = environment.fog() else { panic!("expected linear fog") };
+        assert!((end - WATER_FOG_END).abs() < 1e-4);
+
+        // 离开水面后全部恢复
+        for _ in 0..4 {
+            environment.step(CameraMedium::Air, 0.1);
+        }
+        assert_eq!(environment.fov_scale(), 1.0);
+        assert_eq!(environment.tint(), Color::NONE);
+        assert!(environment.fog().is_none());
+    }
+}
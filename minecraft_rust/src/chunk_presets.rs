@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::Path;
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::ui::GameSettings;
+
+/// 预设文件，放在游戏根目录，修改后重启游戏生效
+pub const PRESETS_FILE: &str = "chunk_presets.ron";
+
+/// 一个区块加载预设：区块加载器的所有参数，以及渲染距离（加载半径）、生成线程数和每帧的区块处理预算。
+/// 应用后由加载器随设置更新：`chunk_demand_system` 更新 `ChunkLoaderConfig`，
+/// `thread_pool_management_system` 重建线程池，上限调低时 `unload_on_limit_decrease` 立即检查卸载
+#[derive(Deserialize, Clone, Debug)]
+pub struct ChunkPreset {
+    /// 本地化键 `game.chunk_preset.<name>` 的后缀
+    pub name: String,
+    pub max_loaded_chunks: u32,
+    pub surface_priority_quota: u32,
+    pub horizontal_radius: f32,
+    pub vertical_radius_up: f32,
+    pub vertical_radius_down: f32,
+    pub lod_radius: f32,
+    pub unload_margin: f32,
+    pub unload_grace_period: f32,
+    pub chunk_generation_threads: u32,
+    pub chunk_budget_ms: f32,
+}
+
+impl ChunkPreset {
+    pub fn localization_key(&self) -> String {
+        format!("game.chunk_preset.{}", self.name)
+    }
+
+    pub fn apply(&self, settings: &mut GameSettings) {
+        settings.max_loaded_chunks = self.max_loaded_chunks;
+        settings.surface_priority_quota = self.surface_priority_quota;
+        settings.horizontal_radius = self.horizontal_radius;
+        settings.vertical_radius_up = self.vertical_radius_up;
+        settings.vertical_radius_down = self.vertical_radius_down;
+        settings.lod_radius = self.lod_radius;
+        settings.unload_margin = self.unload_margin;
+        settings.unload_grace_period = self.unload_grace_period;
+        settings.chunk_generation_threads = self.chunk_generation_threads;
+        settings.chunk_budget_ms = self.chunk_budget_ms;
+    }
+
+    /// 当前设置的各项都与预设相同
+    fn matches(&self, settings: &GameSettings) -> bool {
+        self.max_loaded_chunks == settings.max_loaded_chunks
+            && self.surface_priority_quota == settings.surface_priority_quota
+            && self.horizontal_radius == settings.horizontal_radius
+            && self.vertical_radius_up == settings.vertical_radius_up
+            && self.vertical_radius_down == settings.vertical_radius_down
+            && self.lod_radius == settings.lod_radius
+            && self.unload_margin == settings.unload_margin
+            && self.unload_grace_period == settings.unload_grace_period
+            && self.chunk_generation_threads == settings.chunk_generation_threads
+            && self.chunk_budget_ms == settings.chunk_budget_ms
+    }
+}
+
+/// 从 `chunk_presets.ron` 读取的区块加载预设，按文件中的顺序排列
+#[derive(Resource, Clone, Debug)]
+pub struct ChunkPresets(pub Vec<ChunkPreset>);
+
+impl ChunkPresets {
+    /// 读取预设文件，文件缺失或无法解析时使用内置的预设
+    pub fn load(path: &Path) -> Self {
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))
+            .and_then(|content| ron::from_str(&content).map_err(|e| format!("Failed to parse {:?}: {}", path, e)));
+        match parsed {
+            Ok(presets) => Self(presets),
+            Err(e) => {
+                warn!("{}, using the built-in chunk presets", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// 与当前设置一致的预设，没有时为自定义
+    pub fn matching(&self, settings: &GameSettings) -> Option<&ChunkPreset> {
+        self.0.iter().find(|preset| preset.matches(settings))
+    }
+}
+
+impl Default for ChunkPresets {
+    fn default() -> Self {
+        Self(ron::from_str(include_str!("../chunk_presets.ron")).expect("built-in chunk presets are valid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_settings_match_the_balanced_preset_until_edited() {
+        let presets = ChunkPresets::default();
+        let names: Vec<&str> = presets.0.iter().map(|preset| preset.name.as_str()).collect();
+        assert_eq!(names, ["potato", "balanced", "quality", "extreme"]);
+
+        let mut settings = GameSettings::default();
+        assert_eq!(presets.matching(&settings).map(|preset| preset.name.as_str()), Some("balanced"));
+        presets.0[0].apply(&mut settings);
+        assert_eq!(presets.matching(&settings).map(|preset| preset.name.as_str()), Some("potato"));
+        assert_eq!(settings.chunk_generation_threads, 2);
+        // 手动修改任意一项后显示为自定义
+        settings.unload_margin += 0.5;
+        assert!(presets.matching(&settings).is_none());
+    }
+}
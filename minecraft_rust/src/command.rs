@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+use bevy::input::InputSystem;
+use bevy_egui::{egui, EguiContexts, EguiSet};
+use crate::controller::LookTarget;
+use crate::game_state::{game_rule_name, GameMode, GameRuleError, GameState, Weather, WorldManager, WorldState};
+use crate::inventory_screen::inventory_screen_open;
+use crate::localization::LocalizationManager;
+use crate::net::client::NetClient;
+use crate::message_log::MessageLog;
+use crate::world::sapling::GrowSaplingEvent;
+use crate::world_edit::{block_from_id, WorldEditCommand};
+
+const GAMEMODE_USAGE: &str = "/gamemode <survival|creative|adventure|spectator>";
+const GROW_USAGE: &str = "/grow";
+const GAMERULE_USAGE: &str = "/gamerule <rule> [value]";
+const SET_USAGE: &str = "/set <block>";
+const WEATHER_USAGE: &str = "/weather <clear|rain> [seconds]";
+
+/// 游戏内命令行：按 / 打开，回车执行，Esc 取消。打开期间游戏不接收键盘和鼠标按键
+#[derive(Resource, Default)]
+pub struct CommandLine {
+    pub open: bool,
+    text: String,
+}
+
+pub fn command_line_open(command_line: Res<CommandLine>) -> bool {
+    command_line.open
+}
+
+/// 解析后的命令
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    GameMode(GameMode),
+    /// 让准星指向的树苗立即长成树
+    Grow,
+    /// 查看（没有值时）或修改游戏规则
+    GameRule { name: String, value: Option<String> },
+    /// 修改天气，没有给出持续秒数时按天气的随机时长
+    Weather { weather: Weather, seconds: Option<f32> },
+    /// 世界编辑：/pos1 /pos2 /set /copy /paste /undo
+    WorldEdit(WorldEditCommand),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CommandError {
+    Unknown(String),
+    /// 参数不对，附带正确用法
+    Usage(&'static str),
+}
+
+/// 解析一行命令，开头的 / 可以省略
+pub fn parse_command(line: &str) -> Result<Command, CommandError> {
+    let mut args = line.trim().trim_start_matches('/').split_whitespace();
+    let name = args.next().unwrap_or_default();
+    match name {
+        "gamemode" => match (args.next().and_then(GameMode::parse), args.next()) {
+            (Some(game_mode), None) => Ok(Command::GameMode(game_mode)),
+            _ => Err(CommandError::Usage(GAMEMODE_USAGE)),
+        },
+        "grow" => match args.next() {
+            None => Ok(Command::Grow),
+            Some(_) => Err(CommandError::Usage(GROW_USAGE)),
+        },
+        "gamerule" => match (args.next(), args.next(), args.next()) {
+            (Some(name), value, None) => Ok(Command::GameRule { name: name.to_string(), value: value.map(str::to_string) }),
+            _ => Err(CommandError::Usage(GAMERULE_USAGE)),
+        },
+        "weather" => match (args.next().and_then(Weather::parse), args.next().map(|s| s.parse::<f32>().ok().filter(|&s| s > 0.0)), args.next()) {
+            (Some(weather), None, None) => Ok(Command::Weather { weather, seconds: None }),
+            (Some(weather), Some(Some(seconds)), None) => Ok(Command::Weather { weather, seconds: Some(seconds) }),
+            _ => Err(CommandError::Usage(WEATHER_USAGE)),
+        },
+        "set" => match (args.next().and_then(block_from_id), args.next()) {
+            (Some(block), None) => Ok(Command::WorldEdit(WorldEditCommand::Set(block))),
+            _ => Err(CommandError::Usage(SET_USAGE)),
+        },
+        "pos1" | "pos2" | "copy" | "paste" | "undo" => {
+            // 这些命令都没有参数，用法就是命令本身
+            let (command, usage) = match name {
+                "pos1" => (WorldEditCommand::Pos1, "/pos1"),
+                "pos2" => (WorldEditCommand::Pos2, "/pos2"),
+                "copy" => (WorldEditCommand::Copy, "/copy"),
+                "paste" => (WorldEditCommand::Paste, "/paste"),
+                _ => (WorldEditCommand::Undo, "/undo"),
+            };
+            match args.next() {
+                None => Ok(Command::WorldEdit(command)),
+                Some(_) => Err(CommandError::Usage(usage)),
+            }
+        }
+        _ => Err(CommandError::Unknown(name.to_string())),
+    }
+}
+
+pub struct CommandPlugin;
+
+impl Plugin for CommandPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandLine>()
+           // egui 从窗口事件读取输入，清空按键状态不影响输入框
+           .add_systems(PreUpdate, block_game_input.after(InputSystem).after(EguiSet::ProcessInput).run_if(command_line_open))
+           .add_systems(Update, (
+               open_command_line.run_if(not(inventory_screen_open)),
+               command_line_ui.after(open_command_line).run_if(command_line_open),
+           ).run_if(in_state(GameState::InGame)))
+           .add_systems(OnExit(GameState::InGame), close_on_exit);
+    }
+}
+
+fn close_on_exit(mut command_line: ResMut<CommandLine>) {
+    command_line.open = false;
+}
+
+/// 清空游戏的键盘和鼠标按键状态，在 `PreUpdate` 中运行，本帧的游戏系统收不到任何按键。
+/// 其他界面的输入框获得焦点时也使用它
+pub fn block_game_input(mut keyboard: ResMut<Input<KeyCode>>, mut mouse_buttons: ResMut<Input<MouseButton>>) {
+    keyboard.reset_all();
+    mouse_buttons.reset_all();
+}
+
+fn open_command_line(
+    keyboard: Res<Input<KeyCode>>,
+    mut command_line: ResMut<CommandLine>,
+) {
+    if command_line.open || !keyboard.just_pressed(KeyCode::Slash) {
+        return;
+    }
+    command_line.open = true;
+    command_line.text = "/".to_string();
+}
+
+fn command_line_ui(
+    mut contexts: EguiContexts,
+    mut command_line: ResMut<CommandLine>,
+    mut world_manager: ResMut<WorldManager>,
+    localization: Res<LocalizationManager>,
+    mut message_log: ResMut<MessageLog>,
+    // /grow 需要准星目标，/grow、/gamerule 和世界编辑只能在单人游戏中使用
+    (look_target, net, mut world_state): (Res<LookTarget>, Option<Res<NetClient>>, ResMut<WorldState>),
+    (mut grow_events, mut world_edit): (EventWriter<GrowSaplingEvent>, EventWriter<WorldEditCommand>),
+) {
+    let ctx = contexts.ctx_mut();
+    let (submitted, cancelled) = ctx.input(|input| (input.key_pressed(egui::Key::Enter), input.key_pressed(egui::Key::Escape)));
+    egui::Area::new(egui::Id::new("command_line"))
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(8.0, -8.0))
+        .show(ctx, |ui| {
+            egui::Frame::none()
+                .fill(egui::Color32::from_black_alpha(160))
+                .inner_margin(egui::Margin::same(4.0))
+                .show(ui, |ui| {
+                    let text_edit = egui::TextEdit::singleline(&mut command_line.text)
+                        .desired_width(480.0)
+                        .font(egui::TextStyle::Monospace);
+                    ui.add(text_edit).request_focus();
+                });
+        });
+    if !submitted && !cancelled {
+        return;
+    }
+
+    command_line.open = false;
+    let line = std::mem::take(&mut command_line.text);
+    if cancelled || line.trim().trim_start_matches('/').is_empty() {
+        return;
+    }
+    // 输入的命令和它的输出一起留在消息记录中
+    message_log.info(line.trim());
+    match parse_command(&line) {
+        Ok(Command::GameMode(game_mode)) => {
+            if world_manager.set_current_game_mode(game_mode) {
+                info!("Game mode set to {:?}", game_mode);
+                message_log.info(format!("{}: {}", localization.get("game.command.game_mode_changed"), localization.get(game_mode.localization_key())));
+            } else {
+                message_log.error(localization.get("game.command.singleplayer_only"));
+            }
+        }
+        // 联机时方块更新、游戏规则和天气都由服务器处理，客户端不能让树苗生长、修改规则和天气或编辑世界
+        Ok(Command::Grow | Command::GameRule { .. } | Command::Weather { .. } | Command::WorldEdit(_)) if net.is_some_and(|net| net.is_remote()) => message_log.error(localization.get("game.command.singleplayer_only")),
+        Ok(Command::WorldEdit(command)) => world_edit.send(command),
+        Ok(Command::Grow) => match look_target.hit {
+            Some((pos, _)) => grow_events.send(GrowSaplingEvent { pos }),
+            None => message_log.error(localization.get("game.command.grow.not_sapling")),
+        },
+        Ok(Command::GameRule { name, value: None }) => match world_state.game_rules.get(&game_rule_name(&name)) {
+            Some(value) => message_log.info(format!("{} = {}", game_rule_name(&name), value)),
+            None => message_log.error(format!("{}: {}", localization.get("game.command.gamerule.unknown"), name)),
+        },
+        Ok(Command::GameRule { name, value: Some(value) }) => match world_state.set_known_rule(&name, &value) {
+            Ok(name) => {
+                info!("Game rule {} set to {}", name, value);
+                message_log.info(format!("{}: {} = {}", localization.get("game.command.gamerule.set"), name, world_state.game_rules[&name]));
+            }
+            Err(GameRuleError::Unknown) => message_log.error(format!("{}: {}", localization.get("game.command.gamerule.unknown"), name)),
+            Err(GameRuleError::InvalidValue) => message_log.error(format!("{}: {} {}", localization.get("game.command.gamerule.invalid"), name, value)),
+        },
+        Ok(Command::Weather { weather, seconds }) => {
+            world_state.set_weather(weather, seconds, 0.5);
+            info!("Weather set to {:?} for {:.0} seconds", weather, world_state.weather_seconds);
+            message_log.info(format!("{}: {}", localization.get("game.command.weather.set"), localization.get(weather.localization_key())));
+        }
+        Err(CommandError::Unknown(name)) => message_log.error(format!("{}: {}", localization.get("game.command.unknown"), name)),
+        Err(CommandError::Usage(usage)) => message_log.error(format!("{}: {}", localization.get("game.command.usage"), usage)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::chunk::BlockId;
+
+    #[test]
+    fn gamemode_accepts_names_abbreviations_and_numbers() {
+        assert_eq!(parse_command("/gamemode survival"), Ok(Command::GameMode(GameMode::Survival)));
+        assert_eq!(parse_command("gamemode C"), Ok(Command::GameMode(GameMode::Creative)));
+        assert_eq!(parse_command("/gamemode 3"), Ok(Command::GameMode(GameMode::Spectator)));
+        assert_eq!(parse_command("/gamemode"), Err(CommandError::Usage(GAMEMODE_USAGE)));
+        assert_eq!(parse_command("/gamemode flying"), Err(CommandError::Usage(GAMEMODE_USAGE)));
+        assert_eq!(parse_command("/gamemode s extra"), Err(CommandError::Usage(GAMEMODE_USAGE)));
+        assert_eq!(parse_command("/fly"), Err(CommandError::Unknown("fly".to_string())));
+    }
+
+    #[test]
+    fn gamerule_takes_a_name_and_an_optional_value() {
+        assert_eq!(parse_command("/gamerule keepInventory true"), Ok(Command::GameRule { name: "keepInventory".into(), value: Some("true".into()) }));
+        assert_eq!(parse_command("/gamerule fall_damage"), Ok(Command::GameRule { name: "fall_damage".into(), value: None }));
+        assert_eq!(parse_command("/gamerule"), Err(CommandError::Usage(GAMERULE_USAGE)));
+        assert_eq!(parse_command("/gamerule a b c"), Err(CommandError::Usage(GAMERULE_USAGE)));
+    }
+
+    #[test]
+    fn world_edit_commands_parse_block_ids_and_reject_extra_arguments() {
+        assert_eq!(parse_command("/set grass_block"), Ok(Command::WorldEdit(WorldEditCommand::Set(BlockId::Grass))));
+        assert_eq!(parse_command("/set air"), Ok(Command::WorldEdit(WorldEditCommand::Set(BlockId::Air))));
+        assert_eq!(parse_command("/set diamond_pickaxe"), Err(CommandError::Usage(SET_USAGE)));
+        assert_eq!(parse_command("/set"), Err(CommandError::Usage(SET_USAGE)));
+        assert_eq!(parse_command("/pos2"), Ok(Command::WorldEdit(WorldEditCommand::Pos2)));
+        assert_eq!(parse_command("/undo 3"), Err(CommandError::Usage("/undo")));
+    }
+
+    #[test]
+    fn weather_takes_a_kind_and_optional_positive_seconds() {
+        assert_eq!(parse_command("/weather rain"), Ok(Command::Weather { weather: Weather::Rain, seconds: None }));
+        assert_eq!(parse_command("weather clear 120"), Ok(Command::Weather { weather: Weather::Clear, seconds: Some(120.0) }));
+        assert_eq!(parse_command("/weather snow"), Err(CommandError::Usage(WEATHER_USAGE)));
+        assert_eq!(parse_command("/weather rain -5"), Err(CommandError::Usage(WEATHER_USAGE)));
+        assert_eq!(parse_command("/weather rain 60 extra"), Err(CommandError::Usage(WEATHER_USAGE)));
+    }
+
+    #[test]
+    fn grow_takes_no_arguments() {
+        assert_eq!(parse_command("/grow"), Ok(Command::Grow));
+        assert_eq!(parse_command("/grow now"), Err(CommandError::Usage(GROW_USAGE)));
+    }
+}
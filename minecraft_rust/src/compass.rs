@@ -0,0 +1,195 @@
+use std::time::Duration;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use crate::controller::FirstPersonController;
+use crate::game_state::{GameState, WorldState};
+use crate::localization::LocalizationManager;
+use crate::ui::GameSettings;
+use crate::world::SpawnPoint;
+
+/// 坐标和指针的刷新间隔（秒）。文字排版比较耗时，不需要每帧更新
+const UPDATE_INTERVAL: f32 = 0.25;
+/// 表盘大小和指针尺寸（逻辑像素，随界面缩放）
+const DIAL_SIZE: f32 = 48.0;
+const NEEDLE_LENGTH: f32 = 40.0;
+const NEEDLE_WIDTH: f32 = 4.0;
+/// 指针尖端的颜色：指向世界出生点时为红色，指向床时为蓝色
+const SPAWN_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+const BED_COLOR: Color = Color::rgb(0.3, 0.6, 1.0);
+
+#[derive(Component)]
+struct CompassRoot;
+
+/// 指针，绕表盘中心旋转
+#[derive(Component)]
+struct CompassNeedle;
+
+#[derive(Component)]
+struct CompassNeedleTip;
+
+/// 两段文字：玩家的方块坐标，以及目标名称和距离
+#[derive(Component)]
+struct CompassText;
+
+/// 左上角的坐标和罗盘：显示玩家所在的方块坐标，指针指向世界出生点，有床时改为指向床。
+/// 设置中可以关闭
+pub struct CompassPlugin;
+
+impl Plugin for CompassPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(GameState::Loading), setup_compass)
+           .add_systems(Update, update_compass
+               .run_if(in_state(GameState::InGame))
+               .run_if(on_timer(Duration::from_secs_f32(UPDATE_INTERVAL))));
+    }
+}
+
+fn setup_compass(mut commands: Commands, existing: Query<(), With<CompassRoot>>) {
+    if !existing.is_empty() {
+        return;
+    }
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    display: Display::None,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                ..default()
+            },
+            CompassRoot,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(DIAL_SIZE),
+                        height: Val::Px(DIAL_SIZE),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.15).into(),
+                    ..default()
+                })
+                .with_children(|dial| {
+                    dial.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(NEEDLE_WIDTH),
+                                height: Val::Px(NEEDLE_LENGTH),
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        CompassNeedle,
+                    ))
+                    .with_children(|needle| {
+                        let half = Style { width: Val::Percent(100.0), height: Val::Percent(50.0), ..default() };
+                        needle.spawn((NodeBundle { style: half.clone(), background_color: SPAWN_COLOR.into(), ..default() }, CompassNeedleTip));
+                        needle.spawn(NodeBundle { style: half, background_color: Color::rgb(0.85, 0.85, 0.85).into(), ..default() });
+                    });
+                });
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new("", TextStyle { font: default(), font_size: 16.0, color: Color::WHITE }),
+                    TextSection::new("", TextStyle { font: default(), font_size: 14.0, color: SPAWN_COLOR }),
+                ]),
+                CompassText,
+            ));
+        });
+}
+
+/// 罗盘的各个节点
+#[derive(SystemParam)]
+struct CompassNodes<'w, 's> {
+    root: Query<'w, 's, &'static mut Style, With<CompassRoot>>,
+    needle: Query<'w, 's, &'static mut Transform, (With<CompassNeedle>, Without<FirstPersonController>)>,
+    tip: Query<'w, 's, &'static mut BackgroundColor, With<CompassNeedleTip>>,
+    text: Query<'w, 's, &'static mut Text, With<CompassText>>,
+}
+
+/// 从视线方向 `forward` 顺时针转到从 `from` 指向 `to` 的方向的角度（弧度，俯视，-π ~ π），
+/// 只看水平方向；两点在同一列时返回 0
+fn needle_angle(forward: Vec3, from: Vec3, to: Vec3) -> f32 {
+    let forward = forward.xz().normalize_or_zero();
+    let target = (to - from).xz().normalize_or_zero();
+    if forward == Vec2::ZERO || target == Vec2::ZERO {
+        return 0.0;
+    }
+    // 俯视时 +X 向右、+Z 向下，叉积为正表示目标在顺时针一侧
+    forward.perp_dot(target).atan2(forward.dot(target))
+}
+
+fn update_compass(
+    settings: Res<GameSettings>,
+    world_state: Option<Res<WorldState>>,
+    spawn_point: Option<Res<SpawnPoint>>,
+    player: Query<(&Transform, &FirstPersonController)>,
+    localization: Res<LocalizationManager>,
+    mut nodes: CompassNodes,
+) {
+    let Ok(mut root) = nodes.root.get_single_mut() else { return };
+    let display = if settings.show_compass { Display::Flex } else { Display::None };
+    if root.display != display {
+        root.display = display;
+    }
+    let Ok((transform, controller)) = player.get_single() else { return };
+    if !settings.show_compass {
+        return;
+    }
+
+    let position = transform.translation;
+    let block = position.floor().as_ivec3();
+    let bed = world_state.as_ref().and_then(|state| state.player_spawn);
+    let world_spawn = world_state.as_ref().and_then(|state| state.spawn_point).map(|spawn| spawn.as_vec3() + Vec3::splat(0.5));
+    let (target, label, color) = match bed {
+        Some(bed) => (Some(bed.as_vec3() + Vec3::splat(0.5)), "game.compass.bed", BED_COLOR),
+        None => (world_spawn.or(spawn_point.map(|spawn| spawn.0)), "game.compass.spawn", SPAWN_COLOR),
+    };
+
+    let angle = target.map_or(0.0, |target| needle_angle(controller.look_direction(), position, target));
+    for mut needle in nodes.needle.iter_mut() {
+        // UI 坐标的 y 轴向下，绕 z 轴的正角度在屏幕上是顺时针
+        needle.rotation = Quat::from_rotation_z(angle);
+    }
+    for mut tip in nodes.tip.iter_mut() {
+        tip.0 = color;
+    }
+    for mut text in nodes.text.iter_mut() {
+        text.sections[0].value = format!("X: {}  Y: {}  Z: {}\n", block.x, block.y, block.z);
+        text.sections[1].value = match target {
+            Some(target) => format!("{}: {:.0} m", localization.get(label), (target - position).xz().length()),
+            None => String::new(),
+        };
+        text.sections[1].style.color = color;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn needle_turns_clockwise_towards_targets_on_the_right() {
+        let north = Vec3::NEG_Z;
+        let origin = Vec3::new(10.0, 64.0, 10.0);
+        // 面朝北时东方在右边，西方在左边，正南在背后
+        assert!((needle_angle(north, origin, origin + Vec3::new(50.0, 0.0, 0.0)) - FRAC_PI_2).abs() < 1e-5);
+        assert!((needle_angle(north, origin, origin + Vec3::new(-50.0, 20.0, 0.0)) + FRAC_PI_2).abs() < 1e-5);
+        assert!((needle_angle(north, origin, origin + Vec3::new(0.0, 0.0, 5.0)).abs() - std::f32::consts::PI).abs() < 1e-5);
+        // 面朝东时出生点在正前方
+        assert!(needle_angle(Vec3::X, origin, origin + Vec3::new(30.0, -5.0, 0.0)).abs() < 1e-5);
+        assert_eq!(needle_angle(north, origin, origin + Vec3::Y * 10.0), 0.0);
+    }
+}
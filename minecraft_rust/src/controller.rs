@@ -1,11 +1,29 @@
 use bevy::prelude::*;
-use bevy::input::mouse::{MouseMotion, MouseButton};
+use bevy::input::mouse::{MouseMotion, MouseButton, MouseWheel};
 use bevy::input::Input;
-use bevy::window::{CursorGrabMode, PrimaryWindow};
+use serde::{Deserialize, Serialize};
 use crate::world::chunk::{Chunk, BlockId};
 use crate::world::storage::ChunkStorage;
-use crate::inventory::{PlayerInventory, ItemType, ItemStack};
-use crate::game_state::GameState;
+use crate::world::sapling::can_plant_on;
+use crate::world::access::{world_pos_to_chunk_coord, WorldAccess, WorldView};
+use crate::world::block_entity::BlockEntity;
+use crate::explosion::{spawn_primed_tnt, TNT_FUSE_SECONDS};
+use crate::world::events::BlockChangedEvent;
+use crate::inventory::{PlayerInventory, ItemType};
+use crate::game_state::{GameState, WorldManager, WorldState, DEFAULT_GRAVITY_SCALE};
+use crate::block_registry::BlockRegistry;
+use crate::mining::{self, BlockBreaking};
+use crate::audio::{SoundEvent, SoundKind};
+use crate::net::client::NetClient;
+use crate::net::protocol::ClientMessage;
+use crate::inventory_screen::InventoryScreen;
+use crate::health::{DamageEvent, PendingRespawn};
+use crate::benchmark::Benchmark;
+use crate::bed::UseBedEvent;
+use crate::camera_effects::Zoom;
+use crate::cursor::CursorState;
+use crate::localization::LocalizationManager;
+use crate::notifications::{Notifications, Severity};
 
 #[derive(Debug, Clone, Copy)]
 struct AABB {
@@ -51,7 +69,7 @@ fn get_penetration(player_aabb: &AABB, block_aabb: &AABB) -> Vec3 {
     }
 }
 
-fn is_on_ground(position: Vec3, player_height: f32, chunk_storage: &ChunkStorage, chunks: &Query<&Chunk>) -> bool {
+pub fn is_on_ground(position: Vec3, player_height: f32, world: &WorldView) -> bool {
     // 增加检测范围到0.2米，提供更好的容错性
     let feet_pos = position - Vec3::new(0.0, 0.2, 0.0);
     let player_size = Vec3::new(0.6, player_height, 0.6);
@@ -62,7 +80,7 @@ fn is_on_ground(position: Vec3, player_height: f32, chunk_storage: &ChunkStorage
     };
     
     // 只检查附近的区块
-    let nearby_chunks = get_nearby_chunks(position, chunk_storage, chunks);
+    let nearby_chunks = get_nearby_chunks(position, world);
     for chunk in nearby_chunks {
         let solids = chunk.get_solid_blocks();
         for &solid in solids {
@@ -81,117 +99,362 @@ fn is_on_ground(position: Vec3, player_height: f32, chunk_storage: &ChunkStorage
     false
 }
 
-// 优化函数：只检查玩家附近的区块
-fn get_nearby_chunks<'a>(position: Vec3, chunk_storage: &ChunkStorage, chunks: &'a Query<&Chunk>) -> Vec<&'a Chunk> {
-    let mut nearby_chunks = Vec::new();
-    let player_chunk = IVec3::new(
-        (position.x / 32.0).floor() as i32,
-        (position.y / 32.0).floor() as i32,
-        (position.z / 32.0).floor() as i32,
-    );
-    
-    // 只检查玩家周围3x3x3的区块
-    for dx in -1..=1 {
-        for dy in -1..=1 {
-            for dz in -1..=1 {
-                let chunk_coord = player_chunk + IVec3::new(dx, dy, dz);
-                if let Some(chunk_entity) = chunk_storage.get(&chunk_coord) {
-                    if let Ok(chunk) = chunks.get(chunk_entity) {
-                        nearby_chunks.push(chunk);
-                    }
-                }
+/// 玩家脚底中心位于 `position` 时的碰撞箱，尺寸与 `handle_movement` 相同（潜行时更矮）
+fn player_aabb(position: Vec3, is_sneaking: bool) -> AABB {
+    let player_height = if is_sneaking { 1.5 } else { 1.8 };
+    AABB {
+        min: position - Vec3::new(0.3, 0.0, 0.3),
+        max: position + Vec3::new(0.3, player_height, 0.3),
+    }
+}
+
+/// 放置检测时方块包围盒每边缩小的量，碰撞处理后玩家可能还嵌在地面里一点点
+const PLACE_EPSILON: f32 = 1e-3;
+
+/// 在 `pos` 放置方块是否会与玩家或其他实体（包围盒 (min, max)）重叠
+fn placement_overlaps(pos: IVec3, player: &AABB, entities: impl IntoIterator<Item = (Vec3, Vec3)>) -> bool {
+    let block = AABB {
+        min: pos.as_vec3() + Vec3::splat(PLACE_EPSILON),
+        max: pos.as_vec3() + Vec3::splat(1.0 - PLACE_EPSILON),
+    };
+    block.intersects(player) || entities.into_iter().any(|(min, max)| block.intersects(&AABB { min, max }))
+}
+
+/// 世界坐标中的包围盒是否与实心方块相交，使用与玩家碰撞相同的检测（只检查附近的区块）
+pub fn box_collides(min: Vec3, max: Vec3, world: &WorldView) -> bool {
+    let aabb = AABB { min, max };
+    get_nearby_chunks((min + max) / 2.0, world).into_iter().any(|chunk| {
+        chunk.get_solid_blocks().iter().any(|&solid| {
+            let block_world_pos = (chunk.coord * 32 + solid).as_vec3();
+            aabb.intersects(&AABB { min: block_world_pos, max: block_world_pos + Vec3::ONE })
+        })
+    })
+}
+
+/// 玩家移动每帧使用的最大时间步长（秒）
+const MAX_MOVEMENT_STEP: f32 = 0.05;
+
+/// 眼睛（摄像机）离脚底的高度
+pub const EYE_HEIGHT: f32 = 1.62;
+/// 起跳速度（格/秒），适应重力*2，能跳到1.1格高度。生物使用，玩家的起跳速度见 `MovementTuning`
+pub const JUMP_VELOCITY: f32 = 6.6;
+/// 潜行时的速度为行走速度的比例
+const SNEAK_SPEED_FACTOR: f32 = 0.3;
+
+/// 行走模式的移动参数，随设置保存，可在设置的“物理（高级）”中实时调整。速度单位为格/秒，
+/// 加速度为格/秒²，摩擦和空气阻力为每秒损失的速度比例。
+/// 默认值的手感目标（见测试 `movement_defaults_hit_the_feel_targets`）：
+/// 行走 5 格/秒，冲刺 8 格/秒，连续冲刺跳跃比冲刺更快但不超过 `sprint_max_speed`，跳起约 1.1 格高
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MovementTuning {
+    pub walk_speed: f32,
+    pub sprint_speed: f32,
+    /// 冲刺跳跃的冲量可以把水平速度提高到这个上限，行走时的上限为 `walk_speed`
+    pub sprint_max_speed: f32,
+    pub ground_acceleration: f32,
+    pub ground_friction: f32,
+    pub air_acceleration: f32,
+    pub air_acceleration_sprint: f32,
+    pub air_friction: f32,
+    pub air_friction_sprint: f32,
+    pub jump_velocity: f32,
+    /// 冲刺时起跳沿朝向增加的水平速度
+    pub sprint_jump_impulse: f32,
+}
+
+impl Default for MovementTuning {
+    fn default() -> Self {
+        Self {
+            walk_speed: 5.0,
+            sprint_speed: 8.0,
+            sprint_max_speed: 10.0,
+            ground_acceleration: 20.0,
+            ground_friction: 12.0,
+            air_acceleration: 8.0,
+            air_acceleration_sprint: 6.0,
+            air_friction: 0.5,
+            air_friction_sprint: 0.2,
+            jump_velocity: JUMP_VELOCITY,
+            sprint_jump_impulse: 2.0,
+        }
+    }
+}
+
+impl MovementTuning {
+    /// 行走模式下一帧的水平速度。`input` 为归一化的水平输入方向，没有输入时为零。
+    /// 地面上向目标速度加速，松开按键后按摩擦停下；空中只沿输入方向补足到目标速度，
+    /// 超过目标速度的动量（例如冲刺跳跃的冲量）只按空气阻力衰减，转向不会把它吃掉
+    pub fn horizontal_velocity(&self, velocity: Vec2, input: Vec2, on_ground: bool, sprinting: bool, sneaking: bool, delta_time: f32) -> Vec2 {
+        let (target_speed, max_speed) = if sneaking {
+            (self.walk_speed * SNEAK_SPEED_FACTOR, self.walk_speed * SNEAK_SPEED_FACTOR)
+        } else if sprinting {
+            (self.sprint_speed, self.sprint_max_speed.max(self.sprint_speed))
+        } else {
+            (self.walk_speed, self.walk_speed)
+        };
+
+        if on_ground {
+            if input == Vec2::ZERO {
+                return apply_friction(velocity, self.ground_friction, delta_time);
             }
+            let target_velocity = input * target_speed;
+            let velocity_diff = target_velocity - velocity;
+            let max_velocity_change = self.ground_acceleration * delta_time;
+            return if velocity_diff.length() <= max_velocity_change {
+                target_velocity
+            } else {
+                velocity + velocity_diff.normalize() * max_velocity_change
+            };
+        }
+
+        let (acceleration, friction) = if sprinting {
+            (self.air_acceleration_sprint, self.air_friction_sprint)
+        } else {
+            (self.air_acceleration, self.air_friction)
+        };
+        let speed = velocity.length();
+        let along_input = velocity.dot(input);
+        let added = (target_speed - along_input).clamp(0.0, acceleration * delta_time);
+        let steered = apply_friction(velocity + input * added, friction, delta_time);
+        // 空中加速不能超过上限，已有的更快的动量保留
+        let limit = max_speed.max(speed);
+        if steered.length() > limit {
+            steered.normalize() * limit
+        } else {
+            steered
+        }
+    }
+
+    /// 冲刺起跳后的水平速度：沿水平朝向 `forward` 加上冲量，不超过冲刺的速度上限
+    pub fn sprint_jump(&self, velocity: Vec2, forward: Vec2) -> Vec2 {
+        let boosted = velocity + forward.normalize_or_zero() * self.sprint_jump_impulse;
+        let limit = self.sprint_max_speed.max(velocity.length());
+        if boosted.length() > limit {
+            boosted.normalize() * limit
+        } else {
+            boosted
         }
     }
-    nearby_chunks
 }
 
-// 新增函数：检查玩家是否接近地面（用于跳跃检测）
-fn is_near_ground(position: Vec3, player_height: f32, chunk_storage: &ChunkStorage, chunks: &Query<&Chunk>) -> bool {
-    // 检测脚下0.1米范围内是否有地面，用于跳跃
-    let player_size = Vec3::new(0.6, player_height, 0.6);
-    
-    // 创建一个从玩家脚部向下延伸0.1米的检测区域
-    let feet_y = position.y - player_height / 2.0;
-    let check_aabb = AABB { 
-        min: Vec3::new(
-            position.x - player_size.x / 2.0, 
-            feet_y - 0.1,  // 向下检测0.1米
-            position.z - player_size.z / 2.0
-        ), 
-        max: Vec3::new(
-            position.x + player_size.x / 2.0, 
-            feet_y,  // 到脚部位置
-            position.z + player_size.z / 2.0
-        ) 
+/// 行走、飞行和缩放的按键，随设置保存，可在设置的“按键”中修改
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    /// 前进，双击开始冲刺
+    pub forward: KeyCode,
+    pub back: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    /// 跳跃，飞行时上升，双击切换飞行
+    pub jump: KeyCode,
+    /// 潜行，飞行时下降
+    pub sneak: KeyCode,
+    /// 按住冲刺
+    pub sprint: KeyCode,
+    /// 缩放视野（类似望远镜），按住或切换由设置中的 `zoom_toggle` 决定
+    pub zoom: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            forward: KeyCode::W,
+            back: KeyCode::S,
+            left: KeyCode::A,
+            right: KeyCode::D,
+            jump: KeyCode::Space,
+            sneak: KeyCode::ShiftLeft,
+            sprint: KeyCode::ControlLeft,
+            zoom: KeyCode::C,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// 设置窗口中的 (本地化键, 按键)，按显示顺序排列
+    pub fn actions_mut(&mut self) -> [(&'static str, &mut KeyCode); 8] {
+        [
+            ("game.key_bindings.forward", &mut self.forward),
+            ("game.key_bindings.back", &mut self.back),
+            ("game.key_bindings.left", &mut self.left),
+            ("game.key_bindings.right", &mut self.right),
+            ("game.key_bindings.jump", &mut self.jump),
+            ("game.key_bindings.sneak", &mut self.sneak),
+            ("game.key_bindings.sprint", &mut self.sprint),
+            ("game.key_bindings.zoom", &mut self.zoom),
+        ]
+    }
+}
+
+/// 按每秒损失的比例减速，一帧内减到零以下时停止
+fn apply_friction(velocity: Vec2, friction: f32, delta_time: f32) -> Vec2 {
+    let friction_force = velocity * friction * delta_time;
+    if friction_force.length() >= velocity.length() {
+        Vec2::ZERO
+    } else {
+        velocity - friction_force
+    }
+}
+
+/// 行走模式的重力：设置中的重力值乘以 `gravity_scale` 游戏规则（默认 2，增强下落感）。玩家和生物共用
+pub fn apply_gravity(velocity: &mut Vec3, game_settings: &crate::ui::GameSettings, gravity_scale: f32, delta_time: f32) {
+    velocity.y -= game_settings.gravity * gravity_scale * delta_time;
+}
+
+/// 按速度移动一个脚底位于 `position`、大小为 `size` 的碰撞箱，推出与附近实心方块的重叠部分，
+/// 撞到方块的方向上速度清零。返回新的位置；`collided_blocks` 不为 None 时记录碰到的方块碰撞箱。
+/// 玩家和生物共用这套碰撞
+pub fn move_with_collisions(
+    position: Vec3,
+    velocity: &mut Vec3,
+    size: Vec3,
+    walking: bool,
+    delta_time: f32,
+    world: &WorldView,
+    mut collided_blocks: Option<&mut Vec<(Vec3, Vec3)>>,
+) -> Vec3 {
+    let mut proposed_pos = position + *velocity * delta_time;
+    let entity_aabb = AABB {
+        min: proposed_pos - Vec3::new(size.x / 2.0, 0.0, size.z / 2.0),
+        max: proposed_pos + Vec3::new(size.x / 2.0, size.y, size.z / 2.0),
     };
-    
-    // 只检查附近的区块
-    let nearby_chunks = get_nearby_chunks(position, chunk_storage, chunks);
+
+    // 只检查附近的区块，提高性能
+    let nearby_chunks = get_nearby_chunks(proposed_pos, world);
     for chunk in nearby_chunks {
         let solids = chunk.get_solid_blocks();
         for &solid in solids {
             let block_world_pos = Vec3::new(
                 (chunk.coord.x * 32) as f32 + solid.x as f32,
-                (chunk.coord.y * 32) as f32 + solid.y as f32, 
+                (chunk.coord.y * 32) as f32 + solid.y as f32,
                 (chunk.coord.z * 32) as f32 + solid.z as f32,
             );
             let block_aabb = AABB { min: block_world_pos, max: block_world_pos + Vec3::ONE };
 
-            if check_aabb.intersects(&block_aabb) {
-                return true;
+            if entity_aabb.intersects(&block_aabb) {
+                if let Some(collided_blocks) = collided_blocks.as_deref_mut() {
+                    collided_blocks.push((block_aabb.min, block_aabb.max));
+                }
+                let penetration = get_penetration(&entity_aabb, &block_aabb);
+                proposed_pos += penetration;
+
+                if penetration.y.abs() > penetration.x.abs() && penetration.y.abs() > penetration.z.abs() {
+                    // 垂直碰撞
+                    if walking {
+                        // 只有在向下移动时才重置垂直速度（着陆）
+                        // 或者在向上移动时撞到天花板
+                        if (penetration.y > 0.0 && velocity.y <= 0.0) ||
+                           (penetration.y < 0.0 && velocity.y >= 0.0) {
+                            velocity.y = 0.0;
+                        }
+                    } else {
+                        velocity.y = 0.0;
+                    }
+                } else {
+                    // 水平碰撞
+                    if penetration.x.abs() > penetration.z.abs() {
+                        velocity.x = 0.0;
+                    } else {
+                        velocity.z = 0.0;
+                    }
+                }
             }
         }
     }
-    false
+    proposed_pos
 }
 
-fn world_pos_to_chunk_coord(world_pos: IVec3) -> IVec3 {
-    IVec3::new(
-        world_pos.x.div_euclid(32),
-        world_pos.y.div_euclid(32),
-        world_pos.z.div_euclid(32),
-    )
+// 优化函数：只检查玩家附近的区块
+fn get_nearby_chunks<'a>(position: Vec3, world: &'a WorldView) -> Vec<&'a Chunk> {
+    let mut nearby_chunks = Vec::new();
+    let player_chunk = world_pos_to_chunk_coord(position.floor().as_ivec3());
+    
+    // 只检查玩家周围3x3x3的区块，正在卸载的区块不参与碰撞
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for dz in -1..=1 {
+                if let Some(chunk) = world.active_chunk(player_chunk + IVec3::new(dx, dy, dz)) {
+                    nearby_chunks.push(chunk);
+                }
+            }
+        }
+    }
+    nearby_chunks
 }
 
-fn world_pos_to_local_pos(world_pos: IVec3, chunk_coord: IVec3) -> IVec3 {
-    world_pos - chunk_coord * 32
-}
+/// 玩家输入和交互系统。进程内服务器在它之后处理本帧发出的方块操作
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ControllerSet;
 
 pub struct ControllerPlugin;
 
 impl Plugin for ControllerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (
-            handle_mouse_look,
-            handle_movement,
-            handle_cursor_grab,
-            handle_block_interaction,
-        ).run_if(in_state(GameState::InGame)));
+        app.init_resource::<LookTarget>()
+           .init_resource::<CollisionDebug>()
+           .init_resource::<BlockBreaking>()
+           .add_systems(Update, (
+            // 基准测试时由预设路线控制玩家
+            handle_mouse_look.run_if(not(resource_exists::<Benchmark>())),
+            // 游戏模式可能随时被 `/gamemode` 改变，移动前先同步移动能力
+            apply_game_mode.before(handle_movement).run_if(not(resource_exists::<Benchmark>())),
+            // 死亡后等待复活点的区块加载时玩家固定不动
+            handle_movement.run_if(not(resource_exists::<PendingRespawn>()).and_then(not(resource_exists::<Benchmark>()))),
+            update_look_target.after(handle_mouse_look).after(handle_movement),
+            handle_block_breaking.after(update_look_target),
+            handle_block_interaction.after(update_look_target),
+            play_footsteps.after(handle_movement),
+            adjust_fly_speed.before(handle_movement),
+        ).in_set(ControllerSet).run_if(in_state(GameState::InGame)));
     }
 }
 
+/// 碰撞调试数据：开启时 `handle_movement` 记录本帧的玩家碰撞箱和发生碰撞的方块碰撞箱 (min, max)
+#[derive(Resource, Default)]
+pub struct CollisionDebug {
+    pub enabled: bool,
+    pub player: Option<(Vec3, Vec3)>,
+    pub blocks: Vec<(Vec3, Vec3)>,
+}
+
+/// 准星指向的方块：(方块世界坐标, 被击中面的法线)，每帧更新。
+/// 准星先碰到带 `Hittable` 的实体时 `entity` 为该实体，`hit` 为 None
+#[derive(Resource, Default)]
+pub struct LookTarget {
+    pub hit: Option<(IVec3, IVec3)>,
+    pub entity: Option<Entity>,
+}
+
 #[derive(Component)]
 pub struct FirstPersonController {
-    pub speed: f32,
     pub sensitivity: f32,
+    pub pending_look: Vec2,       // 开启视角平滑时还没转完的鼠标位移
     pub yaw: f32,
     pub pitch: f32,
     pub mode: ControlMode,
     pub velocity: Vec3,
-    pub last_space_time: f64,
-    pub is_sneaking: bool,
-    // 新增参数改善手感
-    pub acceleration: f32,        // 地面加速度
-    pub air_acceleration: f32,    // 空中加速度
-    pub friction: f32,            // 地面摩擦力
-    pub air_friction: f32,        // 空中阻力
-    pub max_speed: f32,           // 最大移动速度
-    pub sprint_multiplier: f32,   // 冲刺速度倍数
-    pub is_sprinting: bool,       // 是否在冲刺
-}
-
-#[derive(PartialEq)]
+    pub jump_tap: DoubleTap,      // 双击跳跃键切换飞行
+    pub forward_tap: DoubleTap,   // 双击前进键冲刺
+    pub is_sneaking: bool,        // 只在行走时潜行，飞行时潜行键用于下降
+    pub is_sprinting: bool,       // 是否在冲刺，行走的速度和加速度见 `MovementTuning`
+    pub fly_speed: f32,           // 飞行时的水平速度，可用鼠标滚轮调整
+    pub fly_vertical_speed: f32,  // 飞行时跳跃键上升、潜行键下降的速度
+    pub fly_acceleration: f32,    // 飞行时加速和减速的快慢
+    // 由游戏模式决定，见 `apply_game_mode`
+    pub can_fly: bool,            // 能否双击跳跃键切换飞行
+    pub noclip: bool,             // 始终飞行并穿过方块（旁观模式）
+}
+
+impl FirstPersonController {
+    /// 摄像机视线方向（由 yaw 和 pitch 合成的世界空间单位向量）
+    pub fn look_direction(&self) -> Vec3 {
+        Quat::from_axis_angle(Vec3::Y, self.yaw) * Quat::from_axis_angle(Vec3::X, self.pitch) * Vec3::NEG_Z
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ControlMode {
     Flying,
     Walking,
@@ -200,114 +463,348 @@ pub enum ControlMode {
 impl Default for FirstPersonController {
     fn default() -> Self {
         Self {
-            speed: 5.0,
             sensitivity: 0.002,
+            pending_look: Vec2::ZERO,
             yaw: 0.0,
             pitch: 0.0,
             mode: ControlMode::Walking,
             velocity: Vec3::ZERO,
-            last_space_time: 0.0,
+            jump_tap: DoubleTap::default(),
+            forward_tap: DoubleTap::default(),
             is_sneaking: false,
-            // 新增参数的默认值
-            acceleration: 20.0,        // 地面加速度
-            air_acceleration: 8.0,     // 空中加速度（较低）
-            friction: 12.0,            // 地面摩擦力
-            air_friction: 0.5,         // 空中阻力（很小）
-            max_speed: 8.0,            // 最大移动速度
-            sprint_multiplier: 1.6,    // 冲刺速度倍数
+            can_fly: true,
+            noclip: false,
             is_sprinting: false,       // 默认不冲刺
+            fly_speed: 10.0,
+            fly_vertical_speed: 7.5,
+            fly_acceleration: 40.0,
+        }
+    }
+}
+
+/// 双击的最大间隔（秒），双击跳跃键切换飞行和双击前进键冲刺共用
+const DOUBLE_TAP_SECONDS: f64 = 0.3;
+
+/// 双击检测：两次按下的间隔小于 `DOUBLE_TAP_SECONDS` 算一次双击，之后要重新按两次
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DoubleTap {
+    last_press: Option<f64>,
+}
+
+impl DoubleTap {
+    /// 记录在 `now` 时按下一次，返回是否构成双击
+    pub fn press(&mut self, now: f64) -> bool {
+        match self.last_press.take() {
+            Some(last) if now - last < DOUBLE_TAP_SECONDS => true,
+            _ => {
+                self.last_press = Some(now);
+                false
+            }
         }
     }
 }
 
+/// 鼠标滚轮每格改变的飞行速度和飞行速度的范围（格/秒）
+const FLY_SPEED_STEP: f32 = 1.0;
+const MIN_FLY_SPEED: f32 = 1.0;
+const MAX_FLY_SPEED: f32 = 50.0;
+/// 输入方向与水平朝向夹角的余弦至少为此值才算向前移动，斜向前也可以冲刺
+const SPRINT_MIN_FORWARD_DOT: f32 = 0.5;
+
+/// 水平输入方向（已归一化）是否大致朝向 `forward`
+fn is_moving_forward(input_direction: Vec3, forward: Vec3) -> bool {
+    let forward = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero();
+    input_direction.dot(forward) > SPRINT_MIN_FORWARD_DOT
+}
+
+/// 交互距离（格），比原版稍长，让玩家可以"手再长一点"
+pub const REACH_DISTANCE: f32 = 8.0;
+
+/// 可以被准星射线选中的实体（例如生物）。包围盒中心为实体位置加上 `offset`
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Hittable {
+    pub half_extents: Vec3,
+    pub offset: Vec3,
+}
+
+impl Hittable {
+    /// 实体位于 `translation` 时的包围盒 (min, max)
+    pub fn bounds(&self, translation: Vec3) -> (Vec3, Vec3) {
+        let center = translation + self.offset;
+        (center - self.half_extents, center + self.half_extents)
+    }
+}
+
+/// `raycast` 的结果：射线先碰到的方块或实体，`distance` 为沿射线到交点的距离
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RayHit {
+    Block { pos: IVec3, normal: IVec3, distance: f32 },
+    Entity { entity: Entity, distance: f32 },
+}
+
+impl RayHit {
+    pub fn distance(&self) -> f32 {
+        match *self {
+            RayHit::Block { distance, .. } | RayHit::Entity { distance, .. } => distance,
+        }
+    }
+}
+
+/// 同时检测方块和带 `Hittable` 的实体，返回 `max_distance` 内沿射线最近的一个。
+/// `ignore` 中的实体被跳过（例如发出射线的生物自己）
+pub fn raycast(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    world: &WorldView,
+    hittables: &Query<(Entity, &Transform, &Hittable)>,
+    ignore: Option<Entity>,
+) -> Option<RayHit> {
+    let block = raycast_for_blocks(origin, direction, max_distance, world).map(|(pos, normal)| {
+        let distance = ray_box_distance(origin, direction, pos.as_vec3(), pos.as_vec3() + Vec3::ONE).unwrap_or(0.0);
+        RayHit::Block { pos, normal, distance }
+    });
+    let entity = hittables.iter()
+        .filter(|&(entity, _, _)| Some(entity) != ignore)
+        .filter_map(|(entity, transform, hittable)| {
+            let (min, max) = hittable.bounds(transform.translation);
+            ray_box_distance(origin, direction, min, max).map(|distance| RayHit::Entity { entity, distance })
+        })
+        .filter(|hit| hit.distance() <= max_distance)
+        .min_by(|a, b| a.distance().total_cmp(&b.distance()));
+    match (block, entity) {
+        (Some(block), Some(entity)) => Some(if entity.distance() < block.distance() { entity } else { block }),
+        (block, entity) => block.or(entity),
+    }
+}
+
+/// 从眼睛位置沿视线方向检测准星指向的方块或实体。只使用玩家位置和视角计算，
+/// 不读取摄像机变换，视角晃动等画面效果不会影响选中的目标
+fn update_look_target(
+    controller_query: Query<(&Transform, &FirstPersonController)>,
+    hittables: Query<(Entity, &Transform, &Hittable)>,
+    chunk_query: Query<&Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+    mut look_target: ResMut<LookTarget>,
+) {
+    look_target.hit = None;
+    look_target.entity = None;
+    let Ok((player_transform, controller)) = controller_query.get_single() else { return };
+
+    let hit = raycast(
+        player_transform.translation + Vec3::Y * EYE_HEIGHT,
+        controller.look_direction(),
+        REACH_DISTANCE,
+        &WorldView::new(&chunk_storage, &chunk_query),
+        &hittables,
+        None,
+    );
+    match hit {
+        Some(RayHit::Block { pos, normal, .. }) => look_target.hit = Some((pos, normal)),
+        Some(RayHit::Entity { entity, .. }) => look_target.entity = Some(entity),
+        None => {}
+    }
+}
+
+/// 射线与包围盒相交时返回到交点的距离（`direction` 为单位向量，起点在盒内时为 0），否则返回 None
+pub fn ray_box_distance(origin: Vec3, direction: Vec3, min: Vec3, max: Vec3) -> Option<f32> {
+    let inverse = direction.recip();
+    let t1 = (min - origin) * inverse;
+    let t2 = (max - origin) * inverse;
+    let near = t1.min(t2).max_element().max(0.0);
+    let far = t1.max(t2).min_element();
+    (near <= far).then_some(near)
+}
+
+/// 空手攻击实体的伤害
+const FIST_DAMAGE: f32 = 1.0;
+/// 攻击实体时的击退速度（格/秒）：视线水平方向和向上的部分
+const ATTACK_KNOCKBACK_SPEED: f32 = 5.0;
+const ATTACK_KNOCKBACK_LIFT: f32 = 4.0;
+
+/// 左键破坏准星指向的方块。创造模式按下即破坏；生存模式需要按住左键，
+/// 时间由方块硬度和手持工具决定（见 `mining::break_time_seconds`）。破坏作为消息发给服务器，
+/// 被接受后手持的工具消耗一点耐久，能采集的方块掉落为物品（见 `net::client`）。冒险模式不能破坏方块，旁观模式不与世界交互。
+/// 准星指向实体时左键改为攻击它：发送 `DamageEvent`，伤害取决于手持工具
+fn handle_block_breaking(
+    mouse_buttons: Res<Input<MouseButton>>,
+    time: Res<Time>,
+    mut controller_query: Query<(&FirstPersonController, &mut PlayerInventory)>,
+    look_target: Res<LookTarget>,
+    mut breaking: ResMut<BlockBreaking>,
+    world: WorldAccess,
+    cursor: Res<CursorState>,
+    mut sounds: EventWriter<SoundEvent>,
+    net: Res<NetClient>,
+    registry: Res<BlockRegistry>,
+    world_manager: Res<WorldManager>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let locked = cursor.playing();
+    let Ok((controller, mut inventory)) = controller_query.get_single_mut() else { return };
+    let game_mode = world_manager.current_game_mode();
+    let creative = game_mode.is_creative();
+    if game_mode.is_spectator() {
+        breaking.reset();
+        return;
+    }
+
+    if let Some(entity) = look_target.entity.filter(|_| locked) {
+        breaking.reset();
+        if mouse_buttons.just_pressed(MouseButton::Left) {
+            let amount = match inventory.get_selected_item().item_type {
+                ItemType::Tool(tool) => tool.attack_damage(),
+                _ => FIST_DAMAGE,
+            };
+            let push = controller.look_direction().xz().normalize_or_zero() * ATTACK_KNOCKBACK_SPEED;
+            damage_events.send(DamageEvent {
+                target: entity,
+                amount,
+                knockback: Vec3::new(push.x, ATTACK_KNOCKBACK_LIFT, push.y),
+            });
+            if !creative && inventory.get_selected_item_mut().damage_tool() {
+                sounds.send(SoundEvent::tool_break());
+            }
+        }
+        return;
+    }
+
+    let Some((hit_block_pos, _)) = look_target.hit.filter(|_| locked && game_mode.can_edit_blocks()) else {
+        breaking.reset();
+        return;
+    };
+
+    if creative {
+        if !mouse_buttons.just_pressed(MouseButton::Left) {
+            return;
+        }
+    } else {
+        if !mouse_buttons.pressed(MouseButton::Left) {
+            breaking.reset();
+            return;
+        }
+        let Some(block) = world.block(hit_block_pos) else { return };
+        let hardness = registry.definition_for(block).map_or(1.0, |definition| definition.hardness);
+        let tool = match inventory.get_selected_item().item_type {
+            ItemType::Tool(tool) => Some(tool),
+            _ => None,
+        };
+        let Some(break_time) = mining::break_time_seconds(block, hardness, tool) else {
+            breaking.reset();
+            return;
+        };
+        if breaking.target != Some(hit_block_pos) {
+            breaking.target = Some(hit_block_pos);
+            breaking.progress = 0.0;
+        }
+        breaking.progress += time.delta_seconds() / break_time.max(f32::EPSILON);
+        if breaking.progress < 1.0 {
+            return;
+        }
+        breaking.reset();
+    }
+
+    // 破坏方块交给服务器校验（单人游戏时是进程内的服务器），被接受后回复 BlockBroken
+    debug!("Breaking block at {:?}", hit_block_pos);
+    net.send(ClientMessage::BreakBlock { pos: hit_block_pos });
+}
+
 fn handle_block_interaction(
+    mut commands: Commands,
     mouse_buttons: Res<Input<MouseButton>>,
-    mut controller_query: Query<(&FirstPersonController, &Transform, &Children, &mut PlayerInventory)>,
-    camera_query: Query<&Transform, (With<Camera3d>, Without<FirstPersonController>)>,
-    mut chunk_query: Query<&mut Chunk>,
-    chunk_storage: Res<ChunkStorage>,
-    primary_window: Query<&Window, With<PrimaryWindow>>,
+    controller_query: Query<(&Transform, &FirstPersonController, &PlayerInventory)>,
+    // 系统参数最多 16 个，准星目标和可选中的实体合为一个
+    (look_target, hittables): (Res<LookTarget>, Query<(&Transform, &Hittable)>),
+    mut world: WorldAccess,
+    cursor: Res<CursorState>,
+    mut sounds: EventWriter<SoundEvent>,
+    mut block_changes: EventWriter<BlockChangedEvent>,
+    net: Res<NetClient>,
+    mut inventory_screen: ResMut<InventoryScreen>,
+    mut bed_uses: EventWriter<UseBedEvent>,
+    world_manager: Res<WorldManager>,
+    mut notifications: ResMut<Notifications>,
+    localization: Res<LocalizationManager>,
+    registry: Res<BlockRegistry>,
 ) {
-    let window = primary_window.single();
-    if window.cursor.grab_mode != CursorGrabMode::Locked {
+    let game_mode = world_manager.current_game_mode();
+    if !cursor.playing() || game_mode.is_spectator() {
         return;
     }
 
-    let left_clicked = mouse_buttons.just_pressed(MouseButton::Left);
-    let right_clicked = mouse_buttons.just_pressed(MouseButton::Right);
-    
-    if !left_clicked && !right_clicked {
+    if !mouse_buttons.just_pressed(MouseButton::Right) {
         return;
     }
 
-    if let Ok((_, player_transform, children, mut inventory)) = controller_query.get_single_mut() {
-        // 找到摄像机并获取其全局变换
-        let mut camera_global_transform = None;
-        for &child in children.iter() {
-            if let Ok(camera_transform) = camera_query.get(child) {
-                // 计算摄像机的全局变换（玩家变换 + 摄像机本地变换）
-                let global_camera_transform = player_transform.mul_transform(*camera_transform);
-                camera_global_transform = Some(global_camera_transform);
-                break;
+    let Some((hit_block_pos, face_normal)) = look_target.hit else { return };
+    debug!("Interacting with block {:?}, face normal {:?}", hit_block_pos, face_normal);
+
+    if let Ok((player_transform, controller, inventory)) = controller_query.get_single() {
+        match world.block(hit_block_pos) {
+            // 右键工作台打开 3×3 合成格，而不是在它上面放置方块
+            Some(BlockId::CraftingTable) => inventory_screen.open(3),
+            // 右键箱子打开它的容器界面，第一次打开时创建空的方块实体。
+            // 联机时箱子内容还不同步，客户端不能打开
+            Some(BlockId::Chest) => {
+                if net.is_remote() {
+                    notifications.warn(localization.get("game.inventory.chest_multiplayer"));
+                    return;
+                }
+                if world.get_block_entity(hit_block_pos).is_none() {
+                    world.set_block_entity(hit_block_pos, BlockEntity::new_for(BlockId::Chest));
+                }
+                inventory_screen.open_chest(hit_block_pos);
             }
-        }
+            // 右键 TNT 点燃它，联机时同样只在单人游戏中可用
+            Some(BlockId::Tnt) if !net.is_remote() && game_mode.can_edit_blocks() => {
+                world.set_block(hit_block_pos, BlockId::Air);
+                block_changes.send(BlockChangedEvent { pos: hit_block_pos });
+                spawn_primed_tnt(&mut commands, hit_block_pos, TNT_FUSE_SECONDS);
+                sounds.send(SoundEvent::fuse());
+            }
+            // 右键床设置重生点，夜晚还会睡到早上；重生点保存在本地世界状态中，只在单人游戏中可用
+            Some(BlockId::Bed) if !net.is_remote() => bed_uses.send(UseBedEvent { pos: hit_block_pos }),
+            // 放置方块 - 使用物品栏中选中的物品
+            _ if game_mode.can_edit_blocks() => {
+                let selected_item = inventory.get_selected_item();
+                if let ItemType::Block(block_id) = selected_item.item_type {
+                    if selected_item.count > 0 {
+                        let place_pos = hit_block_pos + face_normal;
+                        // 目标位置可能在这一帧已被其他修改（流体、下落方块、联机同步）占据
+                        if world.is_solid(place_pos) {
+                            return;
+                        }
+                        // 树苗只能种在草方块或泥土上
+                        if block_id == BlockId::Sapling && !world.block(place_pos - IVec3::Y).is_some_and(can_plant_on) {
+                            return;
+                        }
 
-        if let Some(camera_transform) = camera_global_transform {
-            let ray_origin = camera_transform.translation;
-            let ray_direction = camera_transform.forward();
-            
-            println!("射线起点: {:?}, 方向: {:?}", ray_origin, ray_direction);
-            
-            // 增加交互距离到8.0，让玩家可以"手再长一点"
-            if let Some((hit_block_pos, face_normal)) = raycast_for_blocks(
-                ray_origin, 
-                ray_direction, 
-                8.0,  // 从5.0增加到8.0
-                &chunk_query,
-                &chunk_storage
-            ) {
-                if left_clicked {
-                    // 破坏方块
-                    destroy_block(hit_block_pos, &mut chunk_query, &chunk_storage);
-                } else if right_clicked {
-                    // 放置方块 - 使用物品栏中选中的物品
-                    let selected_item = inventory.get_selected_item();
-                    if let ItemType::Block(block_id) = selected_item.item_type {
-                        if selected_item.count > 0 {
-                            let place_pos = hit_block_pos + face_normal;
-                            
-                            // 检查是否与玩家重叠（考虑玩家高度1.8米）
-                            let player_block_pos = IVec3::new(
-                                player_transform.translation.x.floor() as i32,
-                                player_transform.translation.y.floor() as i32,
-                                player_transform.translation.z.floor() as i32,
-                            );
-                            let player_head_pos = player_block_pos + IVec3::Y;
-                            
-                            if place_pos != player_block_pos && place_pos != player_head_pos {
-                                place_block(place_pos, block_id, &mut chunk_query, &chunk_storage);
-                                
-                                // 消耗物品栏中的物品
-                                let selected_item_mut = inventory.get_selected_item_mut();
-                                selected_item_mut.count -= 1;
-                                if selected_item_mut.count == 0 {
-                                    *selected_item_mut = ItemStack::empty();
-                                }
-                            }
+                        // 不能放在与玩家或生物等实体重叠的位置，站在方块边缘时玩家可能跨越四列方块
+                        let player = player_aabb(player_transform.translation, controller.is_sneaking);
+                        let entities = hittables.iter().map(|(transform, hittable)| hittable.bounds(transform.translation));
+                        if !placement_overlaps(place_pos, &player, entities) {
+                            // 可定向的方块按点击的面或玩家朝向决定朝向
+                            let metadata = registry
+                                .orientable(block_id)
+                                .map_or(0, |orientable| orientable.placement_metadata(face_normal, controller.look_direction()));
+                            // 放置交给服务器校验（单人游戏时是进程内的服务器），确认后才消耗物品，被拒绝的放置不损失物品
+                            debug!("Placing {:?} at {:?}", block_id, place_pos);
+                            net.send(ClientMessage::PlaceBlock { pos: place_pos, block: block_id, metadata });
                         }
                     }
                 }
             }
+            _ => {}
         }
     }
 }
 
-fn raycast_for_blocks(
+/// 沿射线用 DDA 逐格遍历，返回 `max_distance` 内第一个可选中的方块和被击中面的法线
+pub fn raycast_for_blocks(
     ray_origin: Vec3,
     ray_direction: Vec3,
     max_distance: f32,
-    chunk_query: &Query<&mut Chunk>,
-    chunk_storage: &ChunkStorage,
+    world: &WorldView,
 ) -> Option<(IVec3, IVec3)> {
     // 使用改进的DDA算法进行精确的体素遍历
     let current_pos = ray_origin;
@@ -351,9 +848,8 @@ fn raycast_for_blocks(
     
     // DDA主循环
     while distance_traveled < max_distance {
-        // 检查当前方块是否为实心
-        if is_solid_block(current_block, chunk_query, chunk_storage) {
-            println!("射线击中方块: 世界坐标 {:?}, 面法线 {:?}", current_block, last_face_normal);
+        // 检查当前方块是否能被选中（实心方块或火把）
+        if world.block(current_block).is_some_and(BlockId::is_selectable) {
             return Some((current_block, last_face_normal));
         }
         
@@ -382,160 +878,61 @@ fn raycast_for_blocks(
     None
 }
 
+/// 视角平滑设置为 1 时的时间常数（秒）
+const MAX_LOOK_SMOOTHING_SECONDS: f32 = 0.1;
 
-
-fn is_solid_block(
-    world_pos: IVec3,
-    chunk_query: &Query<&mut Chunk>,
-    chunk_storage: &ChunkStorage,
-) -> bool {
-    let chunk_coord = world_pos_to_chunk_coord(world_pos);
-    
-    if let Some(chunk_entity) = chunk_storage.get(&chunk_coord) {
-        if let Ok(chunk) = chunk_query.get(chunk_entity) {
-            let local_pos = world_pos_to_local_pos(world_pos, chunk_coord);
-            
-            // 确保坐标在有效范围内
-            if local_pos.x >= 0 && local_pos.x < 32 &&
-               local_pos.y >= 0 && local_pos.y < 32 &&
-               local_pos.z >= 0 && local_pos.z < 32 {
-                let block = chunk.get_block(local_pos.x as u32, local_pos.y as u32, local_pos.z as u32);
-                return block != BlockId::Air;
-            }
-        }
-    }
-    
-    false
-}
-
-fn destroy_block(
-    world_pos: IVec3,
-    chunk_query: &mut Query<&mut Chunk>,
-    chunk_storage: &ChunkStorage,
-) {
-    let chunk_coord = world_pos_to_chunk_coord(world_pos);
-    
-    if let Some(chunk_entity) = chunk_storage.get(&chunk_coord) {
-        if let Ok(mut chunk) = chunk_query.get_mut(chunk_entity) {
-            let local_pos = world_pos_to_local_pos(world_pos, chunk_coord);
-            
-            if local_pos.x >= 0 && local_pos.x < 32 &&
-               local_pos.y >= 0 && local_pos.y < 32 &&
-               local_pos.z >= 0 && local_pos.z < 32 {
-                
-                println!("破坏方块: 世界坐标 {:?}, chunk {:?}, 本地坐标 {:?}", 
-                        world_pos, chunk_coord, local_pos);
-                
-                chunk.set_block(local_pos.x as u32, local_pos.y as u32, local_pos.z as u32, BlockId::Air);
-                chunk.compute_solid_blocks();
-                chunk.dirty = true;
-                
-                // 标记相邻区块为脏，如果方块在区块边界
-                mark_neighbor_chunks_dirty(world_pos, local_pos, chunk_query, chunk_storage);
-            }
-        }
-    }
-}
-
-fn place_block(
-    world_pos: IVec3,
-    block_id: BlockId,
-    chunk_query: &mut Query<&mut Chunk>,
-    chunk_storage: &ChunkStorage,
-) {
-    let chunk_coord = world_pos_to_chunk_coord(world_pos);
-    
-    if let Some(chunk_entity) = chunk_storage.get(&chunk_coord) {
-        if let Ok(mut chunk) = chunk_query.get_mut(chunk_entity) {
-            let local_pos = world_pos_to_local_pos(world_pos, chunk_coord);
-            
-            if local_pos.x >= 0 && local_pos.x < 32 &&
-               local_pos.y >= 0 && local_pos.y < 32 &&
-               local_pos.z >= 0 && local_pos.z < 32 {
-                
-                println!("放置方块: 世界坐标 {:?}, chunk {:?}, 本地坐标 {:?}, 类型 {:?}", 
-                        world_pos, chunk_coord, local_pos, block_id);
-                
-                chunk.set_block(local_pos.x as u32, local_pos.y as u32, local_pos.z as u32, block_id);
-                chunk.compute_solid_blocks();
-                chunk.dirty = true;
-                
-                // 标记相邻区块为脏，如果方块在区块边界
-                mark_neighbor_chunks_dirty(world_pos, local_pos, chunk_query, chunk_storage);
-            }
-        }
-    }
-}
-
-// 新增函数：标记相邻区块为脏
-fn mark_neighbor_chunks_dirty(
-    world_pos: IVec3,
-    local_pos: IVec3,
-    chunk_query: &mut Query<&mut Chunk>,
-    chunk_storage: &ChunkStorage,
-) {
-    // 检查是否在区块边界
-    let neighbors = [
-        (local_pos.x == 0, IVec3::new(-1, 0, 0)),   // 左边界
-        (local_pos.x == 31, IVec3::new(1, 0, 0)),   // 右边界
-        (local_pos.y == 0, IVec3::new(0, -1, 0)),   // 下边界
-        (local_pos.y == 31, IVec3::new(0, 1, 0)),   // 上边界
-        (local_pos.z == 0, IVec3::new(0, 0, -1)),   // 前边界
-        (local_pos.z == 31, IVec3::new(0, 0, 1)),   // 后边界
-    ];
-    
-    let current_chunk_coord = world_pos_to_chunk_coord(world_pos);
-    
-    for (is_boundary, offset) in neighbors {
-        if is_boundary {
-            let neighbor_chunk_coord = current_chunk_coord + offset;
-            if let Some(neighbor_entity) = chunk_storage.get(&neighbor_chunk_coord) {
-                if let Ok(mut neighbor_chunk) = chunk_query.get_mut(neighbor_entity) {
-                    neighbor_chunk.dirty = true;
-                    println!("标记相邻区块为脏: {:?}", neighbor_chunk_coord);
-                }
-            }
-        }
+/// 本帧转过还没转完的视角的多少：剩余部分按时间常数指数衰减，与帧率无关
+fn look_smoothing_fraction(smoothing: f32, delta_time: f32) -> f32 {
+    let time_constant = smoothing.clamp(0.0, 1.0) * MAX_LOOK_SMOOTHING_SECONDS;
+    if time_constant <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-delta_time / time_constant).exp()
     }
 }
 
-
-
+/// 鼠标移动是设备报告的相对位移，每帧合计后一次应用，灵敏度与帧率无关
 fn handle_mouse_look(
     mut mouse_motion: EventReader<MouseMotion>,
     mut controller_query: Query<(&mut FirstPersonController, &mut Transform, &Children)>,
     mut camera_query: Query<&mut Transform, (With<Camera3d>, Without<FirstPersonController>)>,
-    mut primary_window: Query<&mut Window, With<PrimaryWindow>>,
-    keyboard: Res<Input<KeyCode>>,
+    cursor: Res<CursorState>,
     game_settings: Res<crate::ui::GameSettings>,
+    zoom: Res<Zoom>,
+    time: Res<Time>,
 ) {
-    let mut window = primary_window.single_mut();
-    if window.cursor.grab_mode != CursorGrabMode::Locked {
-        return;
-    }
-
-    // 检查是否按住ALT键，如果是则不处理鼠标视角
-    if keyboard.pressed(KeyCode::AltLeft) || keyboard.pressed(KeyCode::AltRight) {
-        return;
-    }
-
-    let center = Vec2::new(window.width() / 2.0, window.height() / 2.0);
+    // 先取出本帧的全部鼠标移动。光标未锁定（包括按住 Alt 和窗口失去焦点，见 `CursorState`）时丢弃，
+    // 不会在重新锁定后把这期间的移动补上
+    let delta: Vec2 = mouse_motion.read().map(|motion| motion.delta).sum();
+    let unlocked = !cursor.playing();
 
+    let invert_y = if game_settings.invert_mouse_y { -1.0 } else { 1.0 };
+    let fraction = look_smoothing_fraction(game_settings.mouse_smoothing, time.delta_seconds());
     for (mut controller, mut player_transform, children) in controller_query.iter_mut() {
-        for motion in mouse_motion.read() {
-            // 使用游戏设置中的鼠标灵敏度
-            let effective_sensitivity = controller.sensitivity * game_settings.mouse_sensitivity;
-            
+        if unlocked {
+            controller.pending_look = Vec2::ZERO;
+            continue;
+        }
+        controller.pending_look += delta * Vec2::new(1.0, invert_y);
+        let applied = controller.pending_look * fraction;
+        controller.pending_look -= applied;
+        if controller.pending_look.length_squared() < 1e-6 {
+            controller.pending_look = Vec2::ZERO;
+        }
+        if applied != Vec2::ZERO {
+            // 使用游戏设置中的鼠标灵敏度，缩放时按视野比例降低
+            let effective_sensitivity = controller.sensitivity * game_settings.mouse_sensitivity * zoom.sensitivity_scale;
+
             // 更新yaw和pitch
-            controller.yaw -= motion.delta.x * effective_sensitivity;
-            controller.pitch -= motion.delta.y * effective_sensitivity;
-            
+            controller.yaw -= applied.x * effective_sensitivity;
+            controller.pitch -= applied.y * effective_sensitivity;
+
             // 限制pitch范围
             controller.pitch = controller.pitch.clamp(-1.54, 1.54); // ~88度
-            
+
             // 只有yaw影响玩家身体旋转（左右转身）
             player_transform.rotation = Quat::from_axis_angle(Vec3::Y, controller.yaw);
-            
+
             // pitch只影响摄像机（上下看）
             for &child in children.iter() {
                 if let Ok(mut camera_transform) = camera_query.get_mut(child) {
@@ -544,9 +941,45 @@ fn handle_mouse_look(
             }
         }
     }
+}
+
+/// 按当前世界的游戏模式设置玩家的移动能力。模式随时可能被 `/gamemode` 改变：
+/// 旁观模式立即进入飞行，不允许飞行的模式立即落地
+fn apply_game_mode(world_manager: Res<WorldManager>, mut query: Query<&mut FirstPersonController>) {
+    let game_mode = world_manager.current_game_mode();
+    for mut controller in query.iter_mut() {
+        controller.can_fly = game_mode.allows_flight();
+        controller.noclip = game_mode.is_spectator();
+        if controller.noclip {
+            controller.mode = ControlMode::Flying;
+        } else if controller.mode == ControlMode::Flying && !controller.can_fly {
+            controller.mode = ControlMode::Walking;
+            controller.velocity = Vec3::ZERO;
+        }
+    }
+}
 
-    // 每帧（有鼠标事件时）将系统光标重置到窗口中心，实现"锁定在中心"的效果
-    window.set_cursor_position(Some(center));
+/// 飞行时用鼠标滚轮调整飞行速度，用通知显示新的速度
+fn adjust_fly_speed(
+    mut wheel_events: EventReader<MouseWheel>,
+    cursor: Res<CursorState>,
+    mut query: Query<&mut FirstPersonController>,
+    mut notifications: ResMut<Notifications>,
+    localization: Res<LocalizationManager>,
+) {
+    // 滚轮的单位可能是行或像素，每个事件只算一格
+    let steps: f32 = wheel_events.read().map(|event| event.y.signum()).sum();
+    if steps == 0.0 || !cursor.playing() {
+        return;
+    }
+    for mut controller in query.iter_mut() {
+        if controller.mode != ControlMode::Flying {
+            continue;
+        }
+        let fly_speed = (controller.fly_speed + steps * FLY_SPEED_STEP).clamp(MIN_FLY_SPEED, MAX_FLY_SPEED);
+        controller.fly_speed = fly_speed;
+        notifications.set("fly_speed", format!("{}: {:.0}", localization.get("game.controls.fly_speed"), fly_speed), Severity::Info);
+    }
 }
 
 fn handle_movement(
@@ -555,8 +988,19 @@ fn handle_movement(
     time: Res<Time>,
     chunks: Query<&Chunk>,
     chunk_storage: Res<ChunkStorage>,
-    game_settings: Res<crate::ui::GameSettings>,
+    // 重力由设置和 `gravity_scale` 游戏规则共同决定
+    (game_settings, world_state): (Res<crate::ui::GameSettings>, Option<Res<WorldState>>),
+    mut collision_debug: ResMut<CollisionDebug>,
 ) {
+    let gravity_scale = world_state.map_or(DEFAULT_GRAVITY_SCALE, |state| state.gravity_scale());
+    if collision_debug.enabled {
+        collision_debug.player = None;
+        collision_debug.blocks.clear();
+    }
+    // 卡顿后的一帧不会一次移动太远（例如重力一步把玩家拉进地面）
+    let delta_time = time.delta_seconds().min(MAX_MOVEMENT_STEP);
+    let world = WorldView::new(&chunk_storage, &chunks);
+    let keys = &game_settings.keys;
     for (mut transform, mut controller) in query.iter_mut() {
         let mut input_direction = Vec3::ZERO;
         
@@ -565,10 +1009,10 @@ fn handle_movement(
         let right = transform.local_x();
         
         // 处理输入
-        if keyboard.pressed(KeyCode::W) { input_direction += forward; }
-        if keyboard.pressed(KeyCode::S) { input_direction -= forward; }
-        if keyboard.pressed(KeyCode::A) { input_direction -= right; }
-        if keyboard.pressed(KeyCode::D) { input_direction += right; }
+        if keyboard.pressed(keys.forward) { input_direction += forward; }
+        if keyboard.pressed(keys.back) { input_direction -= forward; }
+        if keyboard.pressed(keys.left) { input_direction -= right; }
+        if keyboard.pressed(keys.right) { input_direction += right; }
         
         // 归一化水平移动向量（保持Y为0）
         input_direction.y = 0.0;
@@ -576,202 +1020,416 @@ fn handle_movement(
             input_direction = input_direction.normalize();
         }
         
-        // 检查冲刺状态
-        controller.is_sprinting = keyboard.pressed(KeyCode::ControlLeft);
-        
-        // 潜行状态
-        controller.is_sneaking = keyboard.pressed(KeyCode::ShiftLeft);
+        // 潜行状态，飞行时潜行键用于下降，不改变碰撞箱高度
+        controller.is_sneaking = keyboard.pressed(keys.sneak) && controller.mode == ControlMode::Walking;
+
+        // 冲刺：双击前进键或按住冲刺键开始，只在行走时大致向前移动才保持，潜行或停止向前时结束
+        let current_time = time.elapsed_seconds_f64();
+        if keyboard.just_pressed(keys.forward) && controller.forward_tap.press(current_time) {
+            controller.is_sprinting = true;
+        }
+        if keyboard.pressed(keys.sprint) {
+            controller.is_sprinting = true;
+        }
+        if controller.mode != ControlMode::Walking || controller.is_sneaking || !is_moving_forward(input_direction, forward) {
+            controller.is_sprinting = false;
+        }
         
         // 根据潜行状态调整摄像机和玩家高度
         let player_height = if controller.is_sneaking { 1.5 } else { 1.8 };
         
         if controller.mode == ControlMode::Flying {
-            // 飞行模式处理双击空格切换
-            if keyboard.just_pressed(KeyCode::Space) {
-                let current_time = time.elapsed_seconds_f64();
-                if current_time - controller.last_space_time < 0.3 {
-                    controller.mode = ControlMode::Walking;
-                    controller.velocity = Vec3::ZERO;
-                    controller.last_space_time = current_time;
-                    continue;
-                }
-                controller.last_space_time = current_time;
+            // 飞行模式处理双击跳跃键切换，旁观模式不能退出飞行。
+            // 落回行走时保留水平速度，不会在空中骤停
+            if !controller.noclip && keyboard.just_pressed(keys.jump) && controller.jump_tap.press(current_time) {
+                controller.mode = ControlMode::Walking;
+                controller.velocity.y = 0.0;
+                continue;
             }
-            
-            // 飞行移动（保持原有逻辑）
-            if keyboard.pressed(KeyCode::Space) { input_direction.y += 1.0; }
-            if keyboard.pressed(KeyCode::ShiftLeft) { input_direction.y -= 1.0; }
-            
-            if input_direction.length_squared() > 0.0 {
-                controller.velocity = input_direction.normalize() * controller.speed;
+
+            // 水平和垂直速度分开设置，平滑地加速到目标速度，松开按键后平滑地停下
+            let mut target_velocity = input_direction * controller.fly_speed;
+            if keyboard.pressed(keys.jump) { target_velocity.y += controller.fly_vertical_speed; }
+            if keyboard.pressed(keys.sneak) { target_velocity.y -= controller.fly_vertical_speed; }
+
+            let velocity_diff = target_velocity - controller.velocity;
+            let max_velocity_change = controller.fly_acceleration * delta_time;
+            if velocity_diff.length() <= max_velocity_change {
+                controller.velocity = target_velocity;
             } else {
-                controller.velocity = Vec3::ZERO;
+                controller.velocity += velocity_diff.normalize() * max_velocity_change;
             }
         } else { // 行走模式 - 新的移动逻辑
-            // 重力 - 使用设置中的重力值，乘以2增强下落感
-            controller.velocity.y -= game_settings.gravity * 2.0 * time.delta_seconds();
+            apply_gravity(&mut controller.velocity, &game_settings, gravity_scale, delta_time);
 
             // 地面检测 - 使用更宽松的检测减少抖动
-            let on_ground = is_on_ground(transform.translation, player_height, &chunk_storage, &chunks);
+            let on_ground = is_on_ground(transform.translation, player_height, &world);
             
             // 如果在地面上且垂直速度向下，将其设为0以减少抖动
             if on_ground && controller.velocity.y < 0.0 {
                 controller.velocity.y = 0.0;
             }
 
-            // 计算目标速度
-            let mut target_speed = controller.speed;
-            if controller.is_sneaking {
-                target_speed *= 0.3; // 潜行速度为30%
-            } else if controller.is_sprinting {
-                target_speed *= controller.sprint_multiplier; // 冲刺速度
-            }
-            
-            // 限制最大速度
-            target_speed = target_speed.min(controller.max_speed);
-
-            // 水平移动处理
-            let delta_time = time.delta_seconds();
-            let current_horizontal_velocity = Vec2::new(controller.velocity.x, controller.velocity.z);
-            
-            if input_direction.xz().length_squared() > 0.0 {
-                // 有输入时
-                let target_velocity = input_direction.xz().normalize() * target_speed;
-                let acceleration = if on_ground { controller.acceleration } else { controller.air_acceleration };
-                
-                // 使用加速度平滑过渡到目标速度
-                let velocity_diff = target_velocity - current_horizontal_velocity;
-                let max_velocity_change = acceleration * delta_time;
-                
-                let new_horizontal_velocity = if velocity_diff.length() <= max_velocity_change {
-                    target_velocity
-                } else {
-                    current_horizontal_velocity + velocity_diff.normalize() * max_velocity_change
-                };
-                
-                controller.velocity.x = new_horizontal_velocity.x;
-                controller.velocity.z = new_horizontal_velocity.y;
-            } else {
-                // 无输入时应用摩擦力
-                let friction = if on_ground { controller.friction } else { controller.air_friction };
-                let friction_force = current_horizontal_velocity * friction * delta_time;
-                
-                if friction_force.length() >= current_horizontal_velocity.length() {
-                    // 摩擦力足够大，直接停止
-                    controller.velocity.x = 0.0;
-                    controller.velocity.z = 0.0;
-                } else {
-                    // 应用摩擦力
-                    let new_horizontal_velocity = current_horizontal_velocity - friction_force;
-                    controller.velocity.x = new_horizontal_velocity.x;
-                    controller.velocity.z = new_horizontal_velocity.y;
-                }
-            }
+            // 水平移动处理，起跳上升时按空中处理，冲刺跳跃的冲量不会被地面摩擦吃掉
+            let horizontal_velocity = game_settings.movement.horizontal_velocity(
+                controller.velocity.xz(),
+                input_direction.xz(),
+                on_ground && controller.velocity.y <= 0.0,
+                controller.is_sprinting,
+                controller.is_sneaking,
+                delta_time,
+            );
+            controller.velocity.x = horizontal_velocity.x;
+            controller.velocity.z = horizontal_velocity.y;
         }
 
-        // 应用速度
-        let delta_time = time.delta_seconds();
-        let mut proposed_pos = transform.translation + controller.velocity * delta_time;
-
-        // 碰撞检测和处理 - 使用优化的附近区块检测
+        // 应用速度，并与附近的实心方块做碰撞
         let player_size = Vec3::new(0.6, player_height, 0.6);
-        
-        let player_aabb = AABB { 
-            min: proposed_pos - Vec3::new(player_size.x / 2.0, 0.0, player_size.z / 2.0), 
-            max: proposed_pos + Vec3::new(player_size.x / 2.0, player_size.y, player_size.z / 2.0) 
-        };
-        
-        // 只检查玩家附近的区块，提高性能
-        let nearby_chunks = get_nearby_chunks(proposed_pos, &chunk_storage, &chunks);
-        for chunk in nearby_chunks {
-            let solids = chunk.get_solid_blocks();
-            for &solid in solids {
-                let block_world_pos = Vec3::new(
-                    (chunk.coord.x * 32) as f32 + solid.x as f32,
-                    (chunk.coord.y * 32) as f32 + solid.y as f32, 
-                    (chunk.coord.z * 32) as f32 + solid.z as f32,
-                );
-                let block_aabb = AABB { min: block_world_pos, max: block_world_pos + Vec3::ONE };
-
-                if player_aabb.intersects(&block_aabb) {
-                    let penetration = get_penetration(&player_aabb, &block_aabb);
-                    proposed_pos += penetration;
-                    
-                    if penetration.y.abs() > penetration.x.abs() && penetration.y.abs() > penetration.z.abs() {
-                        // 垂直碰撞
-                        if controller.mode == ControlMode::Walking {
-                            // 只有在向下移动时才重置垂直速度（着陆）
-                            // 或者在向上移动时撞到天花板
-                            if (penetration.y > 0.0 && controller.velocity.y <= 0.0) ||
-                               (penetration.y < 0.0 && controller.velocity.y >= 0.0) {
-                                controller.velocity.y = 0.0;
-                            }
-                        } else {
-                            controller.velocity.y = 0.0;
-                        }
-                    } else {
-                        // 水平碰撞
-                        if penetration.x.abs() > penetration.z.abs() {
-                            controller.velocity.x = 0.0;
-                        } else {
-                            controller.velocity.z = 0.0;
-                        }
-                    }
-                }
-            }
+        if collision_debug.enabled {
+            let unresolved = transform.translation + controller.velocity * delta_time;
+            collision_debug.player = Some((
+                unresolved - Vec3::new(player_size.x / 2.0, 0.0, player_size.z / 2.0),
+                unresolved + Vec3::new(player_size.x / 2.0, player_size.y, player_size.z / 2.0),
+            ));
+        }
+        let walking = controller.mode == ControlMode::Walking;
+        // 旁观模式穿过方块
+        if controller.noclip {
+            transform.translation += controller.velocity * delta_time;
+            continue;
+        }
+        let collided_blocks = collision_debug.enabled.then_some(&mut collision_debug.blocks);
+        let forward_speed = controller.velocity.xz().dot(forward.xz());
+        transform.translation = move_with_collisions(
+            transform.translation,
+            &mut controller.velocity,
+            player_size,
+            walking,
+            delta_time,
+            &world,
+            collided_blocks,
+        );
+        // 迎面撞上方块时停止冲刺，沿墙斜着滑动不算
+        if controller.is_sprinting && forward_speed > 0.0 && controller.velocity.xz().dot(forward.xz()) < forward_speed * 0.5 {
+            controller.is_sprinting = false;
         }
-
-        transform.translation = proposed_pos;
 
         // 跳跃和飞行切换
-        if controller.mode == ControlMode::Walking && keyboard.just_pressed(KeyCode::Space) {
-            let current_time = time.elapsed_seconds_f64();
-            if current_time - controller.last_space_time < 0.3 {
-                // 双击空格 - 切换到飞行
+        if controller.mode == ControlMode::Walking && keyboard.just_pressed(keys.jump) {
+            if controller.jump_tap.press(current_time) && controller.can_fly {
+                // 双击跳跃键 - 切换到飞行（只有创造模式可以飞行），保留水平速度
                 controller.mode = ControlMode::Flying;
-                controller.velocity = Vec3::ZERO;
-            } else if is_near_ground(transform.translation, player_height, &chunk_storage, &chunks) {
-                // 单击空格且接近地面 - 跳跃（允许在距离地面0.1米内跳跃）
-                controller.velocity.y = 6.6; // 适应重力*2的跳跃速度，能跳到1.1格高度
+                controller.velocity.y = 0.0;
+            } else if is_on_ground(transform.translation, player_height, &world) {
+                // 单击跳跃键且站在地面上 - 跳跃，冲刺时向前加速。与落地检测使用同一个判断：
+                // 落地时可能停在地面上方 0.2 格内，离开地面更远时不能再次起跳
+                controller.velocity.y = game_settings.movement.jump_velocity;
+                if controller.is_sprinting {
+                    let horizontal_velocity = game_settings.movement.sprint_jump(controller.velocity.xz(), forward.xz());
+                    controller.velocity.x = horizontal_velocity.x;
+                    controller.velocity.z = horizontal_velocity.y;
+                }
             }
-            controller.last_space_time = current_time;
         }
     }
 }
 
 
 
-fn handle_cursor_grab(
-    mouse_buttons: Res<Input<MouseButton>>,
-    keyboard: Res<Input<KeyCode>>,
-    mut primary_window: Query<&mut Window, With<PrimaryWindow>>,
+/// 每走过这么远（格）播放一次脚步声
+const STEP_DISTANCE: f32 = 1.6;
+
+/// 行走模式下贴地移动时，按水平移动距离播放脚下方块的脚步声
+fn play_footsteps(
+    query: Query<(&Transform, &FirstPersonController)>,
+    chunks: Query<&Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+    time: Res<Time>,
+    mut distance: Local<f32>,
+    mut sounds: EventWriter<SoundEvent>,
 ) {
-    let mut window = primary_window.single_mut();
+    let Ok((transform, controller)) = query.get_single() else { return };
+    let world = WorldView::new(&chunk_storage, &chunks);
+    let horizontal_speed = Vec2::new(controller.velocity.x, controller.velocity.z).length();
+    let player_height = if controller.is_sneaking { 1.5 } else { 1.8 };
+    if controller.mode != ControlMode::Walking
+        || horizontal_speed < 0.5
+        || !is_on_ground(transform.translation, player_height, &world)
+    {
+        *distance = 0.0;
+        return;
+    }
+
+    *distance += horizontal_speed * time.delta_seconds();
+    if *distance >= STEP_DISTANCE {
+        *distance -= STEP_DISTANCE;
+        let below = (transform.translation - Vec3::Y * 0.1).floor().as_ivec3();
+        if let Some(block) = world.block(below).filter(|block| block.is_solid()) {
+            sounds.send(SoundEvent::block(SoundKind::Step, block));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::SystemState;
+
+    type RaycastQueries<'w, 's> = (Query<'w, 's, &'static Chunk>, Query<'w, 's, (Entity, &'static Transform, &'static Hittable)>);
+
+    #[test]
+    fn raycast_returns_whichever_of_block_and_entity_is_closer() {
+        let mut world = World::new();
+        let mut chunk = Chunk::new(IVec3::ZERO);
+        chunk.set_block(5, 5, 1, BlockId::Stone);
+        let storage = ChunkStorage::new();
+        storage.insert(IVec3::ZERO, world.spawn(chunk).id());
+        let hittable = Hittable { half_extents: Vec3::splat(0.5), offset: Vec3::ZERO };
+        let entity = world.spawn((Transform::from_xyz(5.5, 5.5, 3.5), hittable)).id();
+
+        let mut state: SystemState<RaycastQueries> = SystemState::new(&mut world);
+        let (chunks, hittables) = state.get(&world);
+        let view = WorldView::new(&storage, &chunks);
+        let origin = Vec3::new(5.5, 5.5, 8.5);
+
+        // 实体挡在方块前面时选中实体，忽略它时选中后面的方块
+        let hit = raycast(origin, Vec3::NEG_Z, REACH_DISTANCE, &view, &hittables, None);
+        assert_eq!(hit, Some(RayHit::Entity { entity, distance: 4.5 }));
+        let hit = raycast(origin, Vec3::NEG_Z, REACH_DISTANCE, &view, &hittables, Some(entity));
+        assert_eq!(hit, Some(RayHit::Block { pos: IVec3::new(5, 5, 1), normal: IVec3::Z, distance: 6.5 }));
+
+        // 超出距离的实体选不中
+        assert_eq!(raycast(origin, Vec3::NEG_Z, 4.0, &view, &hittables, None), None);
+    }
+
+    #[test]
+    fn placement_rejects_every_column_the_player_overlaps() {
+        // 站在 (0, 63, 0) 的边缘，玩家跨越 x = 0 和 x = 1 两列
+        let player = player_aabb(Vec3::new(0.9, 64.0, 0.5), false);
+        assert!(placement_overlaps(IVec3::new(1, 64, 0), &player, []));
+        assert!(placement_overlaps(IVec3::new(1, 65, 0), &player, []));
+        assert!(!placement_overlaps(IVec3::new(2, 64, 0), &player, []));
+        // 脚下和头顶之上的格子不重叠
+        assert!(!placement_overlaps(IVec3::new(1, 63, 0), &player, []));
+        assert!(!placement_overlaps(IVec3::new(1, 66, 0), &player, []));
+
+        // 碰撞处理后略微嵌入地面也不影响在脚下放置
+        let sunk = player_aabb(Vec3::new(0.9, 63.9999, 0.5), false);
+        assert!(!placement_overlaps(IVec3::new(1, 63, 0), &sunk, []));
+
+        // 潜行时变矮，1.5 格高的玩家头顶 y = 65.5
+        let sneaking = player_aabb(Vec3::new(0.5, 64.0, 0.5), true);
+        assert!(placement_overlaps(IVec3::new(0, 65, 0), &sneaking, []));
+        assert!(!placement_overlaps(IVec3::new(0, 66, 0), &sneaking, []));
 
-    // 鼠标左键点击窗口后自动锁定并隐藏光标，但按住ALT时不锁定
-    if mouse_buttons.just_pressed(MouseButton::Left) && 
-       !keyboard.pressed(KeyCode::AltLeft) && 
-       !keyboard.pressed(KeyCode::AltRight) {
-        window.cursor.grab_mode = CursorGrabMode::Locked;
-        window.cursor.visible = false;
-        // 居中系统鼠标位置，避免锁定前存在偏移
-        let center = Vec2::new(window.width() / 2.0, window.height() / 2.0);
-        window.set_cursor_position(Some(center));
+        // 其他实体同样挡住放置
+        let zombie = (Vec3::new(4.7, 64.0, 0.2), Vec3::new(5.3, 66.0, 0.8));
+        assert!(placement_overlaps(IVec3::new(5, 65, 0), &player, [zombie]));
     }
 
-    // 按住 Alt 键时，临时解锁鼠标（释放即可继续锁定）
-    if keyboard.pressed(KeyCode::AltLeft) || keyboard.pressed(KeyCode::AltRight) {
-        if window.cursor.grab_mode == CursorGrabMode::Locked {
-            window.cursor.grab_mode = CursorGrabMode::None;
-            window.cursor.visible = true;
+    #[test]
+    fn pausing_freezes_the_player_and_resuming_does_not_jump() {
+        use crate::game_state::{pause_simulation, resume_simulation};
+        use bevy::time::TimeUpdateStrategy;
+        use std::time::Duration;
+
+        let mut app = movement_app(None);
+        app.add_state::<GameState>()
+           .add_systems(OnEnter(GameState::Paused), pause_simulation)
+           .add_systems(OnExit(GameState::Paused), resume_simulation)
+           .add_systems(Update, handle_movement.run_if(in_state(GameState::InGame)));
+        // 空的世界里玩家一直下落
+        let player = app.world.spawn((Transform::from_xyz(0.5, 100.0, 0.5), FirstPersonController::default())).id();
+        let snapshot = |app: &App| (app.world.get::<Transform>(player).unwrap().translation, app.world.get::<FirstPersonController>(player).unwrap().velocity);
+        let set_state = |app: &mut App, state| app.world.resource_mut::<NextState<GameState>>().set(state);
+
+        set_state(&mut app, GameState::InGame);
+        for _ in 0..5 {
+            app.update();
         }
-    } else {
-        // 松开 Alt 后自动回到锁定，同时再次居中
-        if window.cursor.visible && window.cursor.grab_mode == CursorGrabMode::None {
-            window.cursor.grab_mode = CursorGrabMode::Locked;
-            window.cursor.visible = false;
-            let center = Vec2::new(window.width() / 2.0, window.height() / 2.0);
-            window.set_cursor_position(Some(center));
+        set_state(&mut app, GameState::Paused);
+        app.update();
+        let paused = snapshot(&app);
+        assert!(paused.1.y < 0.0);
+
+        // 暂停 10 秒后恢复，恢复的这一帧不移动
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(10)));
+        app.update();
+        set_state(&mut app, GameState::InGame);
+        app.update();
+        assert_eq!(snapshot(&app), paused);
+
+        // 之后即使一帧过了很久，重力也只作用一个最大步长
+        app.update();
+        let gravity_step = crate::ui::GameSettings::default().gravity * DEFAULT_GRAVITY_SCALE * MAX_MOVEMENT_STEP;
+        assert!(paused.1.y - snapshot(&app).1.y <= gravity_step + 1e-4);
+    }
+
+    #[test]
+    fn gravity_scale_rule_changes_how_fast_the_player_falls() {
+        // 空的世界里下落 5 帧后的竖直速度
+        let fall_speed = |scale: &str| {
+            let mut world_state = WorldState::default();
+            world_state.set_known_rule("gravity_scale", scale).unwrap();
+            let mut app = movement_app(None);
+            app.insert_resource(world_state)
+               .add_systems(Update, handle_movement);
+            let player = app.world.spawn((Transform::from_xyz(0.5, 100.0, 0.5), FirstPersonController::default())).id();
+            for _ in 0..5 {
+                app.update();
+            }
+            app.world.get::<FirstPersonController>(player).unwrap().velocity.y
+        };
+        assert_eq!(fall_speed("0"), 0.0);
+        let normal = fall_speed("2");
+        assert!(normal < 0.0);
+        assert!((fall_speed("4") - normal * 2.0).abs() < 1e-4);
+    }
+
+    /// 移动测试共用的应用：键盘输入、默认设置，每帧 20 毫秒，不添加系统。
+    /// `floor` 为 `Some((y, 方块))` 时原点区块在这一层铺满方块，为 None 时世界是空的
+    fn movement_app(floor: Option<(u32, BlockId)>) -> App {
+        use bevy::time::TimeUpdateStrategy;
+        use crate::world::test_support::flat_chunk_app;
+        use std::time::Duration;
+
+        let mut app = match floor {
+            Some((floor_y, block)) => flat_chunk_app(floor_y, block).0,
+            None => {
+                let mut app = App::new();
+                app.add_plugins(MinimalPlugins).insert_resource(ChunkStorage::new());
+                app
+            }
+        };
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(20)))
+           .init_resource::<Input<KeyCode>>()
+           .init_resource::<crate::ui::GameSettings>()
+           .init_resource::<CollisionDebug>();
+        app
+    }
+
+    /// y = 10 铺满石头的区块上，玩家站在 (16.5, 11, 30.5) 朝 -Z 看
+    fn flat_world_app() -> (App, Entity) {
+        let mut app = movement_app(Some((10, BlockId::Stone)));
+        app.add_systems(Update, handle_movement);
+        let controller = FirstPersonController { can_fly: false, ..default() };
+        let player = app.world.spawn((Transform::from_xyz(16.5, 11.0, 30.5), controller)).id();
+        (app, player)
+    }
+
+    /// 按住 `keys` 运行 `frames` 帧，`jump` 时每帧重新按下空格，返回玩家的位移
+    fn hold_keys(app: &mut App, player: Entity, frames: usize, keys: &[KeyCode], jump: bool) -> Vec3 {
+        let start = app.world.get::<Transform>(player).unwrap().translation;
+        for _ in 0..frames {
+            let mut input = app.world.resource_mut::<Input<KeyCode>>();
+            input.clear();
+            let released: Vec<KeyCode> = input.get_pressed().filter(|key| !keys.contains(key)).copied().collect();
+            for key in released {
+                input.release(key);
+            }
+            for key in keys {
+                input.press(*key);
+            }
+            if jump {
+                input.release(KeyCode::Space);
+                input.press(KeyCode::Space);
+            }
+            app.update();
         }
+        app.world.get::<Transform>(player).unwrap().translation - start
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn movement_defaults_hit_the_feel_targets() {
+        let tuning = MovementTuning::default();
+        let horizontal_speed = |displacement: Vec3, seconds: f32| displacement.xz().length() / seconds;
+
+        // 行走 5 格/秒
+        let (mut app, player) = flat_world_app();
+        hold_keys(&mut app, player, 50, &[KeyCode::W], false);
+        let walking = horizontal_speed(hold_keys(&mut app, player, 50, &[KeyCode::W], false), 1.0);
+        assert!((walking - 5.0).abs() < 0.1, "walking at {} blocks/s", walking);
+
+        // 冲刺 8 格/秒
+        let (mut app, player) = flat_world_app();
+        hold_keys(&mut app, player, 50, &[KeyCode::W, KeyCode::ControlLeft], false);
+        let sprinting = horizontal_speed(hold_keys(&mut app, player, 50, &[KeyCode::W, KeyCode::ControlLeft], false), 1.0);
+        assert!((sprinting - 8.0).abs() < 0.1, "sprinting at {} blocks/s", sprinting);
+
+        // 连续冲刺跳跃比冲刺快，但不超过冲刺的速度上限
+        let (mut app, player) = flat_world_app();
+        hold_keys(&mut app, player, 50, &[KeyCode::W, KeyCode::ControlLeft], true);
+        let sprint_jumping = horizontal_speed(hold_keys(&mut app, player, 100, &[KeyCode::W, KeyCode::ControlLeft], true), 2.0);
+        assert!(sprint_jumping > sprinting + 0.5 && sprint_jumping <= tuning.sprint_max_speed, "sprint-jumping at {} blocks/s", sprint_jumping);
+
+        // 原地跳起约 1.1 格，能跳上一格高的方块
+        let (mut app, player) = flat_world_app();
+        hold_keys(&mut app, player, 5, &[], false);
+        let mut height = hold_keys(&mut app, player, 1, &[], true).y;
+        let mut apex = height;
+        for _ in 0..50 {
+            height += hold_keys(&mut app, player, 1, &[], false).y;
+            apex = apex.max(height);
+        }
+        assert!(apex > 1.0 && apex < 1.3, "jumped {} blocks", apex);
+    }
+
+    #[test]
+    fn sprinting_and_the_flight_toggle_follow_the_key_bindings() {
+        let rebind = |app: &mut App, player: Entity| {
+            let mut settings = app.world.resource_mut::<crate::ui::GameSettings>();
+            settings.keys.forward = KeyCode::Up;
+            settings.keys.sprint = KeyCode::Tab;
+            settings.keys.jump = KeyCode::J;
+            app.world.get_mut::<FirstPersonController>(player).unwrap().can_fly = true;
+        };
+
+        let (mut app, player) = flat_world_app();
+        rebind(&mut app, player);
+        hold_keys(&mut app, player, 50, &[KeyCode::Up, KeyCode::Tab], false);
+        let sprinting = hold_keys(&mut app, player, 50, &[KeyCode::Up, KeyCode::Tab], false).xz().length();
+        assert!((sprinting - 8.0).abs() < 0.1, "sprinting at {} blocks/s", sprinting);
+
+        // 默认的 W 和 Ctrl 不再移动玩家
+        let (mut app, player) = flat_world_app();
+        rebind(&mut app, player);
+        assert!(hold_keys(&mut app, player, 50, &[KeyCode::W, KeyCode::ControlLeft], false).xz().length() < 1e-4);
+
+        // 双击新的跳跃键切换到飞行
+        hold_keys(&mut app, player, 1, &[KeyCode::J], false);
+        hold_keys(&mut app, player, 1, &[], false);
+        hold_keys(&mut app, player, 1, &[KeyCode::J], false);
+        assert_eq!(app.world.get::<FirstPersonController>(player).unwrap().mode, ControlMode::Flying);
+    }
+
+    #[test]
+    fn double_tap_needs_two_quick_presses_and_then_starts_over() {
+        let mut tap = DoubleTap::default();
+        assert!(!tap.press(1.0));
+        assert!(!tap.press(1.5));
+        assert!(tap.press(1.7));
+        // 第三次按下不会再次触发
+        assert!(!tap.press(1.8));
+        assert!(tap.press(1.9));
+    }
+
+    #[test]
+    fn look_smoothing_turns_the_same_amount_at_any_frame_rate() {
+        assert_eq!(look_smoothing_fraction(0.0, 1.0 / 60.0), 1.0);
+        // 0.1 秒后还没转完的部分在 30 和 240 FPS 下相同
+        let remaining = |fps: u32| (0..fps / 10).fold(1.0, |left: f32, _| left * (1.0 - look_smoothing_fraction(0.5, 1.0 / fps as f32)));
+        assert!((remaining(30) - remaining(240)).abs() < 1e-4);
+        assert!(remaining(30) > 0.0 && remaining(30) < 0.5);
+    }
+
+    #[test]
+    fn sprint_needs_forward_ish_input() {
+        // 朝 -Z 看，稍微低头不影响
+        let forward = Vec3::new(0.0, -0.3, -1.0).normalize();
+        assert!(is_moving_forward(Vec3::NEG_Z, forward));
+        assert!(is_moving_forward(Vec3::new(1.0, 0.0, -1.0).normalize(), forward));
+        assert!(!is_moving_forward(Vec3::X, forward));
+        assert!(!is_moving_forward(Vec3::Z, forward));
+        assert!(!is_moving_forward(Vec3::ZERO, forward));
+    }
+}
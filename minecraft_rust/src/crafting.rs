@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+use crate::inventory::{ItemStack, ItemType};
+use crate::scripting::ScriptEngine;
+
+/// 合成格的最大边长（工作台为 3×3，物品栏为 2×2）
+pub const MAX_GRID_SIZE: usize = 3;
+
+/// 脚本中的 `register_recipe{...}` 把配方表追加到全局 `recipes`，所有脚本加载完后由 `RecipeRegistry` 读取。
+/// 必须在加载脚本之前安装
+const RECIPE_API: &str = r#"
+recipes = {}
+function register_recipe(def)
+    table.insert(recipes, def)
+end
+"#;
+
+/// 配方的材料排列
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecipeShape {
+    /// 有形状的配方，按行存储并裁掉四周的空行空列，None 为空格。左右镜像的摆法同样匹配
+    Shaped { width: usize, height: usize, cells: Vec<Option<ItemType>> },
+    /// 无形状的配方：材料可以放在任意格子里，每格一个
+    Shapeless(Vec<ItemType>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Recipe {
+    pub shape: RecipeShape,
+    pub result: ItemStack,
+}
+
+impl Recipe {
+    fn matches(&self, width: usize, height: usize, cells: &[Option<ItemType>]) -> bool {
+        match &self.shape {
+            RecipeShape::Shaped { width: w, height: h, cells: pattern } => {
+                *w == width && *h == height && (pattern == cells || mirrored(*w, *h, pattern) == cells)
+            }
+            RecipeShape::Shapeless(ingredients) => {
+                let items: Vec<ItemType> = cells.iter().flatten().copied().collect();
+                same_items(&items, ingredients)
+            }
+        }
+    }
+
+    /// 两个配方能被同一种摆法匹配时冲突，合成结果将无法确定
+    fn conflicts_with(&self, other: &Recipe) -> bool {
+        match (&self.shape, &other.shape) {
+            (RecipeShape::Shaped { width, height, cells }, _) => other.matches(*width, *height, cells),
+            (_, RecipeShape::Shaped { width, height, cells }) => self.matches(*width, *height, cells),
+            (RecipeShape::Shapeless(a), RecipeShape::Shapeless(b)) => same_items(a, b),
+        }
+    }
+}
+
+/// 左右镜像后的排列
+fn mirrored<T: Clone>(width: usize, height: usize, cells: &[T]) -> Vec<T> {
+    (0..height).flat_map(|row| (0..width).rev().map(move |col| cells[row * width + col].clone())).collect()
+}
+
+/// 两组物品是否相同（不计顺序）
+fn same_items(a: &[ItemType], b: &[ItemType]) -> bool {
+    let mut rest = b.to_vec();
+    a.len() == b.len() && a.iter().all(|item| match rest.iter().position(|other| other == item) {
+        Some(i) => {
+            rest.swap_remove(i);
+            true
+        }
+        None => false,
+    })
+}
+
+/// 裁掉四周的空行空列，返回 (宽, 高, 按行存储的格子)。全空时宽高为 0
+fn trim(width: usize, height: usize, cells: &[Option<ItemType>]) -> (usize, usize, Vec<Option<ItemType>>) {
+    let filled = |row: usize, col: usize| cells[row * width + col].is_some();
+    let rows: Vec<usize> = (0..height).filter(|&row| (0..width).any(|col| filled(row, col))).collect();
+    let cols: Vec<usize> = (0..width).filter(|&col| (0..height).any(|row| filled(row, col))).collect();
+    let (Some(&top), Some(&bottom), Some(&left), Some(&right)) = (rows.first(), rows.last(), cols.first(), cols.last()) else {
+        return (0, 0, Vec::new());
+    };
+    let trimmed = (top..=bottom).flat_map(|row| (left..=right).map(move |col| cells[row * width + col])).collect();
+    (right - left + 1, bottom - top + 1, trimmed)
+}
+
+/// 合成格：物品栏中为 2×2，工作台为 3×3，按行存储
+#[derive(Debug, Clone)]
+pub struct CraftingGrid {
+    pub size: usize,
+    pub slots: Vec<ItemStack>,
+}
+
+impl CraftingGrid {
+    pub fn new(size: usize) -> Self {
+        Self { size, slots: vec![ItemStack::empty(); size * size] }
+    }
+
+    /// 每个非空格子消耗一个物品，合成一次时调用
+    pub fn consume(&mut self) {
+        for slot in &mut self.slots {
+            if !slot.is_empty() {
+                slot.count -= 1;
+                if slot.count == 0 {
+                    *slot = ItemStack::empty();
+                }
+            }
+        }
+    }
+
+    /// 取出所有物品，合成格清空
+    pub fn take_all(&mut self) -> Vec<ItemStack> {
+        self.slots.iter_mut().map(|slot| std::mem::replace(slot, ItemStack::empty())).filter(|stack| !stack.is_empty()).collect()
+    }
+}
+
+/// 脚本注册的所有配方，启动时加载
+#[derive(Resource, Default)]
+pub struct RecipeRegistry {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeRegistry {
+    /// 在 Lua 中定义 `register_recipe`，在 `ScriptEngine::load_all` 之前调用
+    pub fn install_api(script_engine: &ScriptEngine) -> Result<(), mlua::Error> {
+        script_engine.with_lua(|lua| lua.load(RECIPE_API).set_name("recipe_api").exec())
+    }
+
+    /// 读取脚本注册的配方。无效的配方（未知的物品 id、格式错误、与已有配方冲突）记录警告后跳过
+    pub fn load_from_scripts(&mut self, script_engine: &ScriptEngine) -> Result<(), mlua::Error> {
+        let parsed = script_engine.with_lua(|lua| {
+            let definitions: mlua::Table = lua.globals().get("recipes")?;
+            definitions.sequence_values::<mlua::Table>().map(|def| Ok(parse_recipe(&def?))).collect::<Result<Vec<_>, mlua::Error>>()
+        })?;
+        for (i, recipe) in parsed.into_iter().enumerate() {
+            if let Err(e) = recipe.and_then(|recipe| self.register(recipe)) {
+                warn!("Skipping recipe #{}: {}", i + 1, e);
+            }
+        }
+        info!("Loaded {} crafting recipes from scripts", self.recipes.len());
+        Ok(())
+    }
+
+    /// 添加配方；与已有配方冲突时返回错误
+    pub fn register(&mut self, recipe: Recipe) -> Result<(), String> {
+        if let Some(existing) = self.recipes.iter().find(|existing| existing.conflicts_with(&recipe)) {
+            return Err(format!(
+                "Recipe for '{}' conflicts with the recipe for '{}'",
+                recipe.result.item_type.name_key(),
+                existing.result.item_type.name_key()
+            ));
+        }
+        self.recipes.push(recipe);
+        Ok(())
+    }
+
+    /// 合成格当前摆法对应的配方
+    pub fn find(&self, grid: &CraftingGrid) -> Option<&Recipe> {
+        let cells: Vec<Option<ItemType>> = grid.slots.iter().map(|slot| (!slot.is_empty()).then_some(slot.item_type)).collect();
+        let (width, height, cells) = trim(grid.size, grid.size, &cells);
+        if cells.is_empty() {
+            return None;
+        }
+        self.recipes.iter().find(|recipe| recipe.matches(width, height, &cells))
+    }
+
+    pub fn recipe_count(&self) -> usize {
+        self.recipes.len()
+    }
+}
+
+fn item_id(id: &str) -> Result<ItemType, String> {
+    ItemType::from_id(id).ok_or_else(|| format!("Unknown item id '{}'", id))
+}
+
+/// 解析一个配方表：`result`、可选的 `count`，以及 `pattern` + `key`（有形状）或 `ingredients`（无形状）之一
+fn parse_recipe(def: &mlua::Table) -> Result<Recipe, String> {
+    let result_id: String = def.get("result").map_err(|_| "Recipe is missing 'result'".to_string())?;
+    let result = item_id(&result_id)?;
+    let count: u32 = def.get::<_, Option<u32>>("count").map_err(|e| format!("Invalid count for '{}': {}", result_id, e))?.unwrap_or(1);
+    let result = ItemStack::new(result, count);
+    if count == 0 || count > result.max_stack_size() {
+        return Err(format!("Invalid count {} for '{}'", count, result_id));
+    }
+
+    let pattern: Option<Vec<String>> = def.get("pattern").map_err(|e| format!("Invalid pattern for '{}': {}", result_id, e))?;
+    let shape = if let Some(pattern) = pattern {
+        let key: HashMap<String, String> = def.get::<_, Option<_>>("key")
+            .map_err(|e| format!("Invalid key for '{}': {}", result_id, e))?
+            .unwrap_or_default();
+        let height = pattern.len();
+        let width = pattern.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        if height == 0 || width == 0 || height > MAX_GRID_SIZE || width > MAX_GRID_SIZE {
+            return Err(format!("Pattern for '{}' must be between 1×1 and 3×3", result_id));
+        }
+        let mut cells = Vec::with_capacity(width * height);
+        for row in &pattern {
+            for col in 0..width {
+                cells.push(match row.chars().nth(col).unwrap_or(' ') {
+                    ' ' => None,
+                    symbol => {
+                        let id = key.get(&symbol.to_string())
+                            .ok_or_else(|| format!("Pattern symbol '{}' for '{}' is not in the key", symbol, result_id))?;
+                        Some(item_id(id)?)
+                    }
+                });
+            }
+        }
+        let (width, height, cells) = trim(width, height, &cells);
+        RecipeShape::Shaped { width, height, cells }
+    } else {
+        let ingredients: Vec<String> = def.get::<_, Option<_>>("ingredients")
+            .map_err(|e| format!("Invalid ingredients for '{}': {}", result_id, e))?
+            .ok_or_else(|| format!("Recipe for '{}' needs a pattern or ingredients", result_id))?;
+        if ingredients.is_empty() || ingredients.len() > MAX_GRID_SIZE * MAX_GRID_SIZE {
+            return Err(format!("Recipe for '{}' must have 1 to 9 ingredients", result_id));
+        }
+        RecipeShape::Shapeless(ingredients.iter().map(|id| item_id(id)).collect::<Result<_, _>>()?)
+    };
+    Ok(Recipe { shape, result })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::ToolType;
+    use crate::world::chunk::BlockId;
+
+    fn grid(size: usize, items: &[(usize, BlockId)]) -> CraftingGrid {
+        let mut grid = CraftingGrid::new(size);
+        for &(i, block) in items {
+            grid.slots[i] = ItemStack::new(ItemType::Block(block), 2);
+        }
+        grid
+    }
+
+    #[test]
+    fn scripted_recipes_match_mirrored_and_shapeless_grids() {
+        let engine = ScriptEngine::default();
+        RecipeRegistry::install_api(&engine).unwrap();
+        engine.with_lua(|lua| lua.load(r#"
+            register_recipe{ pattern = { "ss", " o" }, key = { s = "stone", o = "obsidian" }, result = "stone_pickaxe" }
+            register_recipe{ ingredients = { "sand", "gravel" }, result = "dirt", count = 2 }
+            register_recipe{ ingredients = { "gravel", "sand" }, result = "stone" }
+            register_recipe{ pattern = { "x" }, key = { x = "emerald" }, result = "stone" }
+        "#).exec()).unwrap();
+        let mut registry = RecipeRegistry::default();
+        registry.load_from_scripts(&engine).unwrap();
+        // 第三个配方与第二个冲突，第四个的物品 id 无效
+        assert_eq!(registry.recipe_count(), 2);
+
+        let pickaxe = ItemType::Tool(ToolType::StonePickaxe);
+        let crafted = |grid: &CraftingGrid| registry.find(grid).map(|recipe| recipe.result);
+        // 工作台右下角的摆法和它的镜像都能合成，上下翻转则不行
+        let placed = grid(3, &[(4, BlockId::Stone), (5, BlockId::Stone), (8, BlockId::Obsidian)]);
+        assert_eq!(crafted(&placed), Some(ItemStack::new(pickaxe, 1)));
+        let mirrored = grid(2, &[(0, BlockId::Stone), (1, BlockId::Stone), (2, BlockId::Obsidian)]);
+        assert_eq!(crafted(&mirrored), Some(ItemStack::new(pickaxe, 1)));
+        assert_eq!(crafted(&grid(2, &[(0, BlockId::Obsidian), (2, BlockId::Stone), (3, BlockId::Stone)])), None);
+
+        let mut shapeless = grid(2, &[(3, BlockId::Sand), (0, BlockId::Gravel)]);
+        assert_eq!(crafted(&shapeless), Some(ItemStack::new(ItemType::Block(BlockId::Dirt), 2)));
+        shapeless.consume();
+        shapeless.consume();
+        assert_eq!(crafted(&shapeless), None);
+    }
+}
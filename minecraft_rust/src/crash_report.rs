@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use crate::game_state::WorldManager;
+use crate::ui::GameSettings;
+use crate::world::chunk::Chunk;
+use crate::world::chunk_loader::ChunkLoadQueue;
+
+/// 崩溃报告目录，启动器启动时检查里面是否有新的报告
+pub const CRASH_REPORT_DIR: &str = "crash-reports";
+/// 游戏状态快照的更新间隔（秒）
+const SNAPSHOT_INTERVAL: f32 = 1.0;
+
+/// 崩溃时写入报告的游戏状态。panic hook 拿不到 ECS 世界，由 `update_crash_context` 定期复制一份
+struct CrashContext {
+    world: String,
+    loaded_chunks: usize,
+    queued_chunks: usize,
+    generating_chunks: usize,
+    settings: String,  // GameSettings 的 JSON，只在设置改变时更新
+}
+
+static CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    world: String::new(),
+    loaded_chunks: 0,
+    queued_chunks: 0,
+    generating_chunks: 0,
+    settings: String::new(),
+});
+
+pub struct CrashReportPlugin;
+
+impl Plugin for CrashReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_crash_context);
+    }
+}
+
+/// 安装 panic hook：先执行默认处理（打印到控制台），再记录到日志并写出崩溃报告
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        error!("{}", info);
+        match write_crash_report(info) {
+            Ok(path) => error!("Crash report saved to {}", path.display()),
+            Err(e) => error!("{}", e),
+        }
+    }));
+}
+
+fn update_crash_context(
+    world_manager: Res<WorldManager>,
+    settings: Option<Res<GameSettings>>,
+    chunks: Query<(), With<Chunk>>,
+    load_queue: Res<ChunkLoadQueue>,
+    time: Res<Time>,
+    mut since_snapshot: Local<f32>,
+) {
+    *since_snapshot += time.delta_seconds();
+    let settings_changed = settings.as_ref().is_some_and(|settings| settings.is_changed());
+    if *since_snapshot < SNAPSHOT_INTERVAL && !settings_changed {
+        return;
+    }
+    *since_snapshot = 0.0;
+    // 崩溃发生在持有锁的线程上时 hook 拿不到锁，所以这里不能长时间持有
+    let settings_json = settings_changed.then(|| {
+        settings.as_ref().map_or_else(String::new, |settings| serde_json::to_string_pretty(settings.as_ref()).unwrap_or_default())
+    });
+    let mut context = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    if context.world != world_manager.current_world_name() {
+        context.world = world_manager.current_world_name().to_string();
+    }
+    context.loaded_chunks = chunks.iter().count();
+    context.queued_chunks = load_queue.len();
+    context.generating_chunks = load_queue.generating.len();
+    if let Some(json) = settings_json {
+        context.settings = json;
+    }
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string())
+}
+
+/// 崩溃报告的内容
+fn build_report(message: &str, location: &str, backtrace: &str, context: Option<&CrashContext>) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "---- Minecraft Rust Crash Report ----");
+    let _ = writeln!(report, "Time: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"));
+    let _ = writeln!(report, "Version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "OS: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(report, "Thread: {}", std::thread::current().name().unwrap_or("<unnamed>"));
+    let _ = writeln!(report, "\nPanic: {}\nLocation: {}", message, location);
+
+    let _ = writeln!(report, "\n-- Game --");
+    match context {
+        Some(context) => {
+            let world = if context.world.is_empty() { "<none>" } else { &context.world };
+            let _ = writeln!(report, "World: {}", world);
+            let _ = writeln!(
+                report,
+                "Chunks: {} loaded, {} queued, {} generating",
+                context.loaded_chunks, context.queued_chunks, context.generating_chunks
+            );
+            let _ = writeln!(report, "\n-- Settings --\n{}", context.settings);
+        }
+        None => {
+            let _ = writeln!(report, "Game state unavailable (the crash happened while it was being updated)");
+        }
+    }
+
+    let _ = writeln!(report, "\n-- Backtrace --\n{}", backtrace);
+    report
+}
+
+fn write_crash_report(info: &PanicHookInfo) -> Result<PathBuf, String> {
+    let location = info.location().map_or_else(|| "<unknown>".to_string(), |location| location.to_string());
+    let backtrace = Backtrace::force_capture().to_string();
+    let context = CONTEXT.try_lock().ok();
+    let report = build_report(&panic_message(info), &location, &backtrace, context.as_deref());
+    drop(context);
+
+    let dir = crate::paths::data_path(CRASH_REPORT_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create crash report directory {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("crash-{}.txt", chrono::Local::now().format("%Y-%m-%d_%H.%M.%S")));
+    std::fs::write(&path, report).map_err(|e| format!("Failed to write crash report {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crash_report_lists_panic_world_chunks_and_settings() {
+        let context = CrashContext {
+            world: "Test World".to_string(),
+            loaded_chunks: 120,
+            queued_chunks: 8,
+            generating_chunks: 3,
+            settings: "{\n  \"fov\": 70.0\n}".to_string(),
+        };
+        let report = build_report("index out of bounds", "src/world/chunk.rs:10:5", "0: main", Some(&context));
+        assert!(report.contains("Panic: index out of bounds\nLocation: src/world/chunk.rs:10:5"));
+        assert!(report.contains("World: Test World"));
+        assert!(report.contains("Chunks: 120 loaded, 8 queued, 3 generating"));
+        assert!(report.contains("\"fov\": 70.0"));
+        assert!(report.contains("-- Backtrace --\n0: main"));
+
+        let without_context = build_report("boom", "<unknown>", "", None);
+        assert!(without_context.contains("Game state unavailable"));
+    }
+}
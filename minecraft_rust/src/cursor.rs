@@ -0,0 +1,151 @@
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow, WindowFocused};
+use crate::command::CommandLine;
+use crate::game_state::GameState;
+use crate::inventory_screen::InventoryScreen;
+use crate::ui::GameSettings;
+
+/// Windows 不支持 `CursorGrabMode::Locked`，光标只被限制在窗口内，仍会移动到窗口边缘，
+/// 只在这种平台上把光标移回窗口中心
+const WARP_CURSOR_TO_CENTER: bool = cfg!(target_os = "windows");
+
+/// 光标应处的状态
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CursorMode {
+    /// 锁定在窗口中心并隐藏，鼠标移动用来转动视角
+    Playing,
+    /// 暂停菜单、物品栏、命令行打开或按住 Alt 时显示光标
+    #[default]
+    Menu,
+    /// 窗口没有焦点，或者失去焦点后还没有点击窗口回到游戏
+    Unfocused,
+}
+
+/// 光标状态。各界面只打开或关闭自己，由 `update_cursor` 根据游戏状态、打开的界面、Alt 和窗口焦点
+/// 决定光标的状态，它是唯一修改窗口光标的系统。游戏系统用 `playing` 判断鼠标输入是否属于游戏
+#[derive(Resource, Default)]
+pub struct CursorState {
+    mode: CursorMode,
+    /// 失去焦点后还没有点击窗口；进入游戏、从暂停菜单或摄影模式回到游戏时清除
+    released: bool,
+}
+
+impl CursorState {
+    pub fn playing(&self) -> bool {
+        self.mode == CursorMode::Playing
+    }
+}
+
+/// 根据当前情况决定光标状态。`in_game` 为游戏中或摄影模式，`menu_open` 包括按住 Alt
+fn resolve_mode(in_game: bool, menu_open: bool, focused: bool, released: bool) -> CursorMode {
+    if !focused {
+        CursorMode::Unfocused
+    } else if !in_game || menu_open {
+        CursorMode::Menu
+    } else if released {
+        CursorMode::Unfocused
+    } else {
+        CursorMode::Playing
+    }
+}
+
+pub struct CursorPlugin;
+
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorState>()
+           .add_systems(OnEnter(GameState::InGame), capture_cursor)
+           .add_systems(OnEnter(GameState::PhotoMode), capture_cursor)
+           // 在游戏系统之后运行：点击窗口回到游戏的那一下不会同时破坏方块
+           .add_systems(PostUpdate, update_cursor);
+    }
+}
+
+/// 进入游戏、从暂停菜单回到游戏或进入摄影模式时重新锁定光标，不需要再点击一次窗口
+fn capture_cursor(mut cursor: ResMut<CursorState>) {
+    cursor.released = false;
+}
+
+/// 决定光标状态用到的输入
+#[derive(SystemParam)]
+struct CursorInputs<'w, 's> {
+    focus_events: EventReader<'w, 's, WindowFocused>,
+    mouse_motion: ResMut<'w, Events<MouseMotion>>,
+    mouse_buttons: Res<'w, Input<MouseButton>>,
+    keyboard: ResMut<'w, Input<KeyCode>>,
+}
+
+fn update_cursor(
+    mut cursor: ResMut<CursorState>,
+    mut primary_window: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut inputs: CursorInputs,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    settings: Res<GameSettings>,
+    // 这些界面打开时显示光标
+    (inventory_screen, command_line): (Res<InventoryScreen>, Res<CommandLine>),
+) {
+    let Ok((window_entity, mut window)) = primary_window.get_single_mut() else { return };
+    let in_game = matches!(state.get(), GameState::InGame | GameState::PhotoMode);
+    let screen_open = inventory_screen.open || command_line.open;
+
+    for event in inputs.focus_events.read().filter(|event| event.window == window_entity) {
+        if event.focused {
+            // 失去焦点期间累积的鼠标移动不属于游戏，丢弃后不会在回到游戏时突然转动视角
+            inputs.mouse_motion.clear();
+        } else {
+            cursor.released = true;
+            // 失去焦点后收不到按键松开的事件，否则切换窗口用的 Alt 回来后仍被当作按住
+            inputs.keyboard.release_all();
+            if settings.pause_on_focus_loss && *state.get() == GameState::InGame && !screen_open {
+                info!("Window lost focus, pausing");
+                next_state.set(GameState::Paused);
+            }
+        }
+    }
+
+    // 按住 Alt 临时显示光标，松开后回到游戏；按住 Alt 点击窗口不回到游戏
+    let alt_held = inputs.keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    if in_game && !screen_open && !alt_held && window.focused && inputs.mouse_buttons.just_pressed(MouseButton::Left) {
+        cursor.released = false;
+    }
+
+    let mode = resolve_mode(in_game, screen_open || alt_held, window.focused, cursor.released);
+    let entered_play = mode == CursorMode::Playing && cursor.mode != CursorMode::Playing;
+    cursor.mode = mode;
+
+    let (grab_mode, visible) = match mode {
+        CursorMode::Playing => (CursorGrabMode::Locked, false),
+        CursorMode::Menu | CursorMode::Unfocused => (CursorGrabMode::None, true),
+    };
+    if window.cursor.grab_mode != grab_mode || window.cursor.visible != visible {
+        window.cursor.grab_mode = grab_mode;
+        window.cursor.visible = visible;
+    }
+    // 锁定时把光标放回中心，避免锁定前存在偏移
+    if entered_play || (WARP_CURSOR_TO_CENTER && mode == CursorMode::Playing && !inputs.mouse_motion.is_empty()) {
+        let center = Vec2::new(window.width() / 2.0, window.height() / 2.0);
+        window.set_cursor_position(Some(center));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_is_captured_only_while_playing_in_a_focused_window() {
+        assert_eq!(resolve_mode(true, false, true, false), CursorMode::Playing);
+        // 菜单、物品栏、命令行或 Alt
+        assert_eq!(resolve_mode(true, true, true, false), CursorMode::Menu);
+        assert_eq!(resolve_mode(false, false, true, false), CursorMode::Menu);
+        // 失去焦点，以及重新获得焦点后还没有点击窗口
+        assert_eq!(resolve_mode(true, false, false, false), CursorMode::Unfocused);
+        assert_eq!(resolve_mode(false, true, false, true), CursorMode::Unfocused);
+        assert_eq!(resolve_mode(true, false, true, true), CursorMode::Unfocused);
+        // 暂停菜单中不需要先点击窗口
+        assert_eq!(resolve_mode(false, false, true, true), CursorMode::Menu);
+    }
+}
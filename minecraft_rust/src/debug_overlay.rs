@@ -0,0 +1,218 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use bevy::prelude::*;
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy_egui::{egui, EguiContexts};
+use crate::controller::{CollisionDebug, FirstPersonController, LookTarget};
+use crate::game_state::{GameState, WorldManager};
+use crate::hud::hud_visible;
+use crate::localization::LocalizationManager;
+use crate::world::access::{world_pos_to_chunk_coord, world_pos_to_local_pos, WorldView};
+use crate::world::chunk::Chunk;
+use crate::world::chunk_loader::{ChunkGenerationTask, ChunkLoadQueue, ChunkUnloadQueue, ChunkUnloadTask};
+use crate::world::events::ChunkLoadedEvent;
+use crate::world::storage::ChunkStorage;
+
+/// 区块边框显示的范围（以玩家所在区块为中心）
+const BORDER_RADIUS_HORIZONTAL: i32 = 2;
+const BORDER_RADIUS_VERTICAL: i32 = 1;
+/// 区块加载后以"新加载"颜色显示的时间（秒）
+const FRESH_CHUNK_SECONDS: f32 = 2.0;
+
+/// F3 调试信息覆盖层
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    pub chunk_borders: bool,  // F3+G：区块边框和碰撞箱
+    combo_used: bool,  // 按住 F3 期间使用了组合键，松开时不切换覆盖层
+    text: String,  // 复用的文本缓冲区，避免每帧重新分配
+}
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+           .add_systems(Update, (
+               toggle_debug_overlay,
+               draw_debug_overlay.run_if(hud_visible),
+               draw_debug_gizmos.run_if(|overlay: Res<DebugOverlay>| overlay.chunk_borders),
+           ).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn toggle_debug_overlay(
+    keyboard: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut collision_debug: ResMut<CollisionDebug>,
+) {
+    if keyboard.pressed(KeyCode::F3) && keyboard.just_pressed(KeyCode::G) {
+        overlay.chunk_borders = !overlay.chunk_borders;
+        overlay.combo_used = true;
+    }
+    if keyboard.just_released(KeyCode::F3) {
+        if !overlay.combo_used {
+            overlay.visible = !overlay.visible;
+        }
+        overlay.combo_used = false;
+    }
+    // 关闭时移动系统不记录碰撞箱
+    if collision_debug.enabled != overlay.chunk_borders {
+        collision_debug.enabled = overlay.chunk_borders;
+    }
+}
+
+/// 视线在水平面上的主方向：(本地化键, 坐标轴)。北为 -Z，与 `CubeFace::North` 一致
+fn facing(direction: Vec3) -> (&'static str, &'static str) {
+    if direction.x.abs() > direction.z.abs() {
+        if direction.x > 0.0 { ("game.debug.east", "+X") } else { ("game.debug.west", "-X") }
+    } else if direction.z > 0.0 {
+        ("game.debug.south", "+Z")
+    } else {
+        ("game.debug.north", "-Z")
+    }
+}
+
+fn draw_debug_overlay(
+    mut contexts: EguiContexts,
+    mut overlay: ResMut<DebugOverlay>,
+    diagnostics: Res<DiagnosticsStore>,
+    player_query: Query<(&Transform, &FirstPersonController)>,
+    look_target: Res<LookTarget>,
+    chunk_storage: Res<ChunkStorage>,
+    chunk_query: Query<&Chunk>,
+    load_queue: Res<ChunkLoadQueue>,
+    unload_queue: Res<ChunkUnloadQueue>,
+    generation_tasks: Query<(), With<ChunkGenerationTask>>,
+    unload_tasks: Query<(), With<ChunkUnloadTask>>,
+    localization: Res<LocalizationManager>,
+    world_manager: Res<WorldManager>,
+) {
+    if !overlay.visible {
+        return;
+    }
+    let Ok((transform, controller)) = player_query.get_single() else { return };
+
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|d| d.smoothed())
+        .unwrap_or(0.0);
+    let frame_ms = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|d| d.smoothed())
+        .unwrap_or(0.0);
+
+    let position = transform.translation;
+    let block = position.floor().as_ivec3();
+    let chunk_coord = world_pos_to_chunk_coord(block);
+    let local = world_pos_to_local_pos(block, chunk_coord);
+    let (facing_key, axis) = facing(controller.look_direction());
+
+    let text = &mut overlay.text;
+    text.clear();
+    // 写入 String 不会失败，忽略 fmt::Result
+    let _ = writeln!(text, "{}: {:.0} ({:.2} ms)", localization.get("fps"), fps, frame_ms);
+    let _ = writeln!(text, "{}: {}", localization.get("game.debug.game_mode"),
+        localization.get(world_manager.current_game_mode().localization_key()));
+    let _ = writeln!(text, "XYZ: {:.3} / {:.5} / {:.3}", position.x, position.y, position.z);
+    let _ = writeln!(text, "{}: {} {} {}", localization.get("game.debug.block"), block.x, block.y, block.z);
+    let _ = writeln!(text, "{}: {} {} {} [{} {} {}]", localization.get("game.debug.chunk"),
+        local.x, local.y, local.z, chunk_coord.x, chunk_coord.y, chunk_coord.z);
+    let _ = writeln!(text, "{}: {} ({}) ({:.1} / {:.1})", localization.get("game.debug.facing"),
+        localization.get(facing_key), axis, controller.yaw.to_degrees(), controller.pitch.to_degrees());
+
+    let _ = write!(text, "{}: ", localization.get("game.debug.targeted_block"));
+    match look_target.hit {
+        Some((target, _)) => {
+            let block_id = WorldView::new(&chunk_storage, &chunk_query).block(target);
+            let _ = write!(text, "{} {} {}", target.x, target.y, target.z);
+            if let Some(block_id) = block_id {
+                let _ = write!(text, " ({:?})", block_id);
+            }
+            let _ = writeln!(text);
+        }
+        None => {
+            let _ = writeln!(text, "-");
+        }
+    }
+
+    let _ = writeln!(text);
+    let _ = writeln!(text, "{}: {}", localization.get("chunks_loaded"), chunk_storage.chunks.len());
+    let _ = writeln!(text, "{}: {} / {}: {} ({} {})", localization.get("game.info.load_queue"), load_queue.len(),
+        localization.get("game.debug.generating"), load_queue.generating.len(),
+        generation_tasks.iter().count(), localization.get("game.debug.tasks"));
+    let _ = writeln!(text, "{}: {} / {}: {} ({} {})", localization.get("game.debug.unload_queue"), unload_queue.pending.len(),
+        localization.get("game.debug.unloading"), unload_queue.unloading.len(),
+        unload_tasks.iter().count(), localization.get("game.debug.tasks"));
+
+    egui::Area::new(egui::Id::new("debug_overlay"))
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::none()
+                .fill(egui::Color32::from_black_alpha(140))
+                .inner_margin(egui::Margin::same(6.0))
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new(text.as_str()).monospace().color(egui::Color32::WHITE));
+                });
+        });
+}
+
+fn aabb_transform(min: Vec3, max: Vec3) -> Transform {
+    Transform::from_translation((min + max) * 0.5).with_scale(max - min)
+}
+
+/// 绘制玩家周围的区块边框（按状态着色）、玩家碰撞箱和本帧发生碰撞的方块
+fn draw_debug_gizmos(
+    mut gizmos: Gizmos,
+    player_query: Query<&Transform, With<FirstPersonController>>,
+    chunk_storage: Res<ChunkStorage>,
+    chunk_query: Query<&Chunk>,
+    unload_queue: Res<ChunkUnloadQueue>,
+    collision_debug: Res<CollisionDebug>,
+    mut loaded_events: EventReader<ChunkLoadedEvent>,
+    mut loaded_at: Local<HashMap<IVec3, f32>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+    for event in loaded_events.read() {
+        loaded_at.insert(event.coord, now);
+    }
+    loaded_at.retain(|_, at| now - *at < FRESH_CHUNK_SECONDS);
+
+    let Ok(player_transform) = player_query.get_single() else { return };
+    let center = world_pos_to_chunk_coord(player_transform.translation.floor().as_ivec3());
+    let unloading: HashSet<IVec3> = unload_queue
+        .pending
+        .iter()
+        .map(|(_, coord)| *coord)
+        .chain(unload_queue.unloading.iter().copied())
+        .collect();
+
+    for x in -BORDER_RADIUS_HORIZONTAL..=BORDER_RADIUS_HORIZONTAL {
+        for y in -BORDER_RADIUS_VERTICAL..=BORDER_RADIUS_VERTICAL {
+            for z in -BORDER_RADIUS_HORIZONTAL..=BORDER_RADIUS_HORIZONTAL {
+                let coord = center + IVec3::new(x, y, z);
+                let Some(chunk) = chunk_storage.get(&coord).and_then(|e| chunk_query.get(e).ok()) else { continue };
+                let color = if unloading.contains(&coord) {
+                    Color::RED
+                } else if chunk.dirty {
+                    Color::YELLOW
+                } else if loaded_at.contains_key(&coord) {
+                    Color::GREEN
+                } else {
+                    Color::rgba(1.0, 1.0, 1.0, 0.4)
+                };
+                let min = coord.as_vec3() * 32.0;
+                gizmos.cuboid(aabb_transform(min, min + Vec3::splat(32.0)), color);
+            }
+        }
+    }
+
+    if let Some((min, max)) = collision_debug.player {
+        gizmos.cuboid(aabb_transform(min, max), Color::CYAN);
+    }
+    for &(min, max) in &collision_debug.blocks {
+        gizmos.cuboid(aabb_transform(min, max), Color::ORANGE_RED);
+    }
+}
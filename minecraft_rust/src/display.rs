@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+use bevy::window::{MonitorSelection, PrimaryWindow, WindowMode, WindowPosition};
+use bevy::winit::WinitWindows;
+use serde::{Deserialize, Serialize};
+use crate::ui::GameSettings;
+
+/// 窗口模式设置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowModeSetting {
+    #[default]
+    Windowed,
+    /// 无边框全屏，使用显示器当前的分辨率
+    Borderless,
+    /// 独占全屏，使用最接近设置分辨率的显示模式
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    pub const ALL: [WindowModeSetting; 3] = [
+        WindowModeSetting::Windowed,
+        WindowModeSetting::Borderless,
+        WindowModeSetting::Fullscreen,
+    ];
+
+    pub fn localization_key(self) -> &'static str {
+        match self {
+            WindowModeSetting::Windowed => "game.display.windowed",
+            WindowModeSetting::Borderless => "game.display.borderless",
+            WindowModeSetting::Fullscreen => "game.display.fullscreen",
+        }
+    }
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::Borderless => WindowMode::BorderlessFullscreen,
+            WindowModeSetting::Fullscreen => WindowMode::SizedFullscreen,
+        }
+    }
+}
+
+/// 一台显示器的名称和支持的分辨率（物理像素，从大到小）
+pub struct MonitorInfo {
+    pub name: String,
+    pub resolutions: Vec<(u32, u32)>,
+}
+
+/// 可用的显示器，启动后和设置窗口打开时从 winit 读取
+#[derive(Resource, Default)]
+pub struct Monitors {
+    pub list: Vec<MonitorInfo>,
+    /// 主显示器在 `list` 中的位置
+    pub primary: Option<usize>,
+}
+
+impl Monitors {
+    /// 名称为 `name` 的显示器在列表中的位置，没有接入时为 None
+    pub fn index_of(&self, name: Option<&str>) -> Option<usize> {
+        self.list.iter().position(|monitor| Some(monitor.name.as_str()) == name)
+    }
+
+    /// 设置中选择的显示器，None 或没有接入时为主显示器
+    pub fn get(&self, name: Option<&str>) -> Option<&MonitorInfo> {
+        self.list.get(self.index_of(name).or(self.primary)?)
+    }
+}
+
+/// 窗口相关的设置，与上次应用的值比较，只在设置变化时修改窗口，
+/// 玩家拖动或缩放窗口后不会被拉回去。`monitor` 是设置中的显示器当前在列表中的位置
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct WindowSettings {
+    mode: WindowModeSetting,
+    monitor: Option<usize>,
+    width: u32,
+    height: u32,
+}
+
+impl WindowSettings {
+    fn of(settings: &GameSettings, monitors: &Monitors) -> Self {
+        Self {
+            mode: settings.window_mode,
+            monitor: monitors.index_of(settings.monitor_name.as_deref()),
+            width: settings.resolution_width as u32,
+            height: settings.resolution_height as u32,
+        }
+    }
+}
+
+/// 上次应用到主窗口的设置
+#[derive(Resource, Default)]
+pub struct AppliedWindowSettings(Option<WindowSettings>);
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Monitors>()
+           .init_resource::<AppliedWindowSettings>()
+           .add_systems(Update, (toggle_borderless, sync_window_settings.after(toggle_borderless), refresh_monitors));
+    }
+}
+
+/// 把窗口模式、显示器和分辨率应用到窗口上，只修改与上次应用时不同的部分。
+/// 全屏时窗口留在原来的显示器上，换显示器时先退出全屏并移动窗口，下一次调用再进入全屏
+pub fn apply_window_settings(window: &mut Window, settings: &GameSettings, monitors: &Monitors, applied: &mut AppliedWindowSettings) {
+    let target = WindowSettings::of(settings, monitors);
+    let previous = applied.0;
+    if previous == Some(target) {
+        return;
+    }
+
+    if previous.is_none_or(|previous| previous.monitor != target.monitor) {
+        let monitor = target.monitor.map_or(MonitorSelection::Primary, MonitorSelection::Index);
+        window.position = WindowPosition::Centered(monitor);
+        if previous.is_some() && window.mode != WindowMode::Windowed {
+            window.mode = WindowMode::Windowed;
+            applied.0 = Some(WindowSettings { mode: WindowModeSetting::Windowed, ..target });
+            return;
+        }
+    }
+    if previous.is_none_or(|previous| (previous.width, previous.height) != (target.width, target.height))
+        && target.width > 0
+        && target.height > 0
+    {
+        window.resolution.set_physical_resolution(target.width, target.height);
+    }
+    window.mode = target.mode.window_mode();
+    applied.0 = Some(target);
+}
+
+fn sync_window_settings(
+    game_settings: Res<GameSettings>,
+    monitors: Res<Monitors>,
+    mut applied: ResMut<AppliedWindowSettings>,
+    mut primary_window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if applied.0 == Some(WindowSettings::of(&game_settings, &monitors)) {
+        return;
+    }
+    if let Ok(mut window) = primary_window.get_single_mut() {
+        apply_window_settings(&mut window, &game_settings, &monitors, &mut applied);
+    }
+}
+
+/// F11 切换无边框全屏，并立即保存设置
+fn toggle_borderless(keyboard: Res<Input<KeyCode>>, mut game_settings: ResMut<GameSettings>) {
+    if !keyboard.just_pressed(KeyCode::F11) {
+        return;
+    }
+    game_settings.window_mode = if game_settings.window_mode == WindowModeSetting::Borderless {
+        WindowModeSetting::Windowed
+    } else {
+        WindowModeSetting::Borderless
+    };
+    game_settings.save();
+}
+
+/// 启动后第一次读取显示器列表，之后在设置窗口打开时重新读取，期间接入的显示器在下次打开时出现。
+/// 读到列表后设置中按名称保存的显示器才能找到，窗口随之移到那台显示器上
+fn refresh_monitors(
+    game_settings: Res<GameSettings>,
+    mut monitors: ResMut<Monitors>,
+    winit_windows: Option<NonSend<WinitWindows>>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    mut was_open: Local<bool>,
+) {
+    let opened = game_settings.show_settings && !*was_open;
+    *was_open = game_settings.show_settings;
+    if !opened && !monitors.list.is_empty() {
+        return;
+    }
+    let Some(winit_window) = primary_window.get_single().ok()
+        .and_then(|entity| winit_windows.as_ref()?.get_window(entity))
+    else {
+        return;
+    };
+
+    let primary = winit_window.primary_monitor();
+    monitors.primary = None;
+    monitors.list.clear();
+    for (index, monitor) in winit_window.available_monitors().enumerate() {
+        if primary.as_ref() == Some(&monitor) {
+            monitors.primary = Some(index);
+        }
+        let mut resolutions: Vec<(u32, u32)> = monitor.video_modes()
+            .map(|mode| (mode.size().width, mode.size().height))
+            .collect();
+        resolutions.sort_unstable_by(|a, b| b.cmp(a));
+        resolutions.dedup();
+        monitors.list.push(MonitorInfo {
+            name: monitor.name().unwrap_or_else(|| format!("#{}", index + 1)),
+            resolutions,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changing_monitor_leaves_fullscreen_before_moving() {
+        let monitor = |name: &str| MonitorInfo { name: name.to_string(), resolutions: Vec::new() };
+        let mut monitors = Monitors { list: vec![monitor("DELL U2720Q"), monitor("LG 27GL850")], primary: Some(0) };
+        let mut settings = GameSettings { window_mode: WindowModeSetting::Borderless, ..default() };
+        let mut applied = AppliedWindowSettings::default();
+        let mut window = Window::default();
+        apply_window_settings(&mut window, &settings, &monitors, &mut applied);
+        assert_eq!(window.mode, WindowMode::BorderlessFullscreen);
+        assert_eq!(window.position, WindowPosition::Centered(MonitorSelection::Primary));
+
+        // 玩家缩放窗口后，设置没变时不会改回去
+        window.resolution.set_physical_resolution(800, 600);
+        apply_window_settings(&mut window, &settings, &monitors, &mut applied);
+        assert_eq!(window.resolution.physical_width(), 800);
+
+        settings.monitor_name = Some("LG 27GL850".to_string());
+        apply_window_settings(&mut window, &settings, &monitors, &mut applied);
+        assert_eq!(window.mode, WindowMode::Windowed);
+        assert_eq!(window.position, WindowPosition::Centered(MonitorSelection::Index(1)));
+        apply_window_settings(&mut window, &settings, &monitors, &mut applied);
+        assert_eq!(window.mode, WindowMode::BorderlessFullscreen);
+
+        // 显示器重新排列后仍按名称找到同一台，拔掉后回到主显示器
+        monitors.list.reverse();
+        assert_eq!(monitors.index_of(settings.monitor_name.as_deref()), Some(0));
+        monitors.list.remove(0);
+        assert_eq!(monitors.index_of(settings.monitor_name.as_deref()), None);
+        assert_eq!(monitors.get(settings.monitor_name.as_deref()).map(|monitor| monitor.name.as_str()), Some("DELL U2720Q"));
+    }
+}
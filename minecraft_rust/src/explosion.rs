@@ -0,0 +1,332 @@
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use crate::audio::SoundEvent;
+use crate::block_registry::BlockRegistry;
+use crate::controller::FirstPersonController;
+use crate::game_state::{GameMode, GameState, WorldManager};
+use crate::health::PlayerDamageEvent;
+use crate::mining::block_drop;
+use crate::random::random_unit;
+use crate::world::access::{world_pos_to_chunk_coord, world_pos_to_local_pos, BlockEditBatch, WorldAccess};
+use crate::world::chunk::{BlockId, Chunk};
+use crate::world::events::BlockChangedEvent;
+use crate::world::falling_block::spawn_dropped_item;
+use crate::world::storage::ChunkStorage;
+
+/// 点燃的 TNT 爆炸前的时间（秒）
+pub const TNT_FUSE_SECONDS: f32 = 4.0;
+/// 被其他爆炸引燃的 TNT 的引线时间范围（秒），连锁爆炸不会同时发生
+const CHAIN_FUSE_SECONDS: (f32, f32) = (0.5, 1.5);
+/// TNT 的爆炸威力
+pub const TNT_POWER: f32 = 4.0;
+/// TNT 闪烁的半周期（秒）
+const FLASH_SECONDS: f32 = 0.2;
+/// 射线每步前进的距离和每步的衰减（与原版相同）
+const RAY_STEP: f32 = 0.3;
+const RAY_DECAY: f32 = 0.225;
+/// 每条边上的射线数，从立方体表面的格点向外发射
+const RAYS_PER_EDGE: i32 = 16;
+/// 爆炸中心处的最大击退速度（格/秒）
+const MAX_KNOCKBACK: f32 = 12.0;
+/// 玩家碰撞箱中心相对脚底的高度
+const PLAYER_CENTER_HEIGHT: f32 = 0.9;
+
+/// 已点燃、正在倒计时的 TNT，位置为方块中心
+#[derive(Component)]
+pub struct PrimedTnt {
+    pub fuse: f32,
+}
+
+/// 在 `center` 处以 `power` 威力爆炸
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ExplosionEvent {
+    pub center: Vec3,
+    pub power: f32,
+}
+
+/// 在后台线程计算的爆炸范围
+#[derive(Component)]
+struct ExplosionTask {
+    center: Vec3,
+    power: f32,
+    seed: u64,
+    task: Task<Vec<(IVec3, BlockId)>>,
+    /// 快照引用的区块，应用结果后释放
+    chunks: Vec<IVec3>,
+}
+
+/// 爆炸范围内区块方块数据的拷贝，交给后台线程计算，不阻塞主线程
+pub struct BlockSnapshot {
+    chunks: HashMap<IVec3, Vec<u8>>,
+}
+
+impl BlockSnapshot {
+    /// 拷贝包含 `min`..=`max` 的所有 Active 区块并引用它们，计算完成前这些区块不会被卸载
+    fn capture(min: IVec3, max: IVec3, chunks: &Query<&Chunk>, chunk_storage: &ChunkStorage) -> Self {
+        let (min, max) = (world_pos_to_chunk_coord(min), world_pos_to_chunk_coord(max));
+        let mut snapshot = HashMap::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    let coord = IVec3::new(x, y, z);
+                    let Some(chunk) = chunk_storage.get_active(&coord).and_then(|entity| chunks.get(entity).ok()) else { continue };
+                    if chunk_storage.retain(&coord) {
+                        snapshot.insert(coord, chunk.blocks.clone());
+                    }
+                }
+            }
+        }
+        Self { chunks: snapshot }
+    }
+
+    /// 世界坐标处的方块，区块未加载时返回 None
+    pub fn block(&self, pos: IVec3) -> Option<BlockId> {
+        let coord = world_pos_to_chunk_coord(pos);
+        let local = world_pos_to_local_pos(pos, coord);
+        let blocks = self.chunks.get(&coord)?;
+        Some(BlockId::from_u8(blocks[((local.y * 32 + local.z) * 32 + local.x) as usize]))
+    }
+}
+
+/// 爆炸最远能到达的距离（格）：最强的射线在空气中衰减到 0 之前走过的距离
+fn max_radius(power: f32) -> f32 {
+    power * 1.3 / RAY_DECAY * RAY_STEP
+}
+
+/// 计算爆炸摧毁的方块：从中心向四周发射射线，每条射线的强度为威力乘以 0.7..1.3 的随机系数，
+/// 每前进一步衰减一次，穿过方块时再按方块的爆炸抗性衰减，强度仍为正的方块被摧毁。
+/// 因此爆炸范围是一个边缘不规则的球体；基岩和流体不会被摧毁，未加载的区块挡住射线
+pub fn explode(center: Vec3, power: f32, snapshot: &BlockSnapshot, resistance: &[f32; BlockId::COUNT], seed: u64) -> Vec<(IVec3, BlockId)> {
+    let mut destroyed = HashSet::new();
+    let mut result = Vec::new();
+    let last = RAYS_PER_EDGE - 1;
+    let mut ray = 0;
+    for i in 0..RAYS_PER_EDGE {
+        for j in 0..RAYS_PER_EDGE {
+            for k in 0..RAYS_PER_EDGE {
+                // 只取立方体表面的格点
+                if ![i, j, k].iter().any(|&v| v == 0 || v == last) {
+                    continue;
+                }
+                ray += 1;
+                let direction = (IVec3::new(i, j, k).as_vec3() / last as f32 * 2.0 - Vec3::ONE).normalize();
+                let mut intensity = power * (0.7 + 0.6 * random_unit(seed, ray));
+                let mut pos = center;
+                while intensity > 0.0 {
+                    let block_pos = pos.floor().as_ivec3();
+                    let Some(block) = snapshot.block(block_pos) else { break };
+                    if block != BlockId::Air {
+                        intensity -= (resistance[block as usize] + RAY_STEP) * RAY_STEP;
+                        let immune = block == BlockId::Bedrock || block.is_fluid();
+                        if intensity > 0.0 && !immune && destroyed.insert(block_pos) {
+                            result.push((block_pos, block));
+                        }
+                    }
+                    pos += direction * RAY_STEP;
+                    intensity -= RAY_DECAY;
+                }
+            }
+        }
+    }
+    result
+}
+
+/// 距离爆炸中心 `distance` 的玩家受到的伤害和击退系数（0..1），超出范围时返回 None。
+/// 影响范围为威力的两倍，伤害公式与原版相同（不计算方块遮挡）
+pub fn explosion_impact(power: f32, distance: f32) -> Option<(f32, f32)> {
+    let range = power * 2.0;
+    if distance >= range {
+        return None;
+    }
+    let impact = 1.0 - distance / range;
+    let damage = ((impact * impact + impact) / 2.0 * 7.0 * range + 1.0).floor();
+    Some((damage, impact))
+}
+
+pub struct ExplosionPlugin;
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExplosionEvent>()
+           .add_systems(Update, (
+               tick_primed_tnt,
+               start_explosions.after(tick_primed_tnt),
+               finish_explosions.after(start_explosions),
+           ).run_if(in_state(GameState::InGame)))
+           .add_systems(OnExit(GameState::InGame), clear_explosions);
+    }
+}
+
+/// 离开世界时销毁点燃的 TNT 和未完成的爆炸，并释放爆炸引用的区块。
+/// 暂停和进入摄影模式也会退出 `InGame`，这时世界只是停止，保留它们；`OnExit` 运行时状态已经是新状态
+fn clear_explosions(
+    mut commands: Commands,
+    tnt: Query<Entity, With<PrimedTnt>>,
+    tasks: Query<(Entity, &ExplosionTask)>,
+    chunk_storage: Res<ChunkStorage>,
+    state: Res<State<GameState>>,
+) {
+    if matches!(state.get(), GameState::Paused | GameState::PhotoMode) {
+        return;
+    }
+    for entity in tnt.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (entity, explosion) in tasks.iter() {
+        for coord in &explosion.chunks {
+            chunk_storage.release(coord);
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 在方块位置生成点燃的 TNT
+pub fn spawn_primed_tnt(commands: &mut Commands, pos: IVec3, fuse: f32) {
+    commands.spawn((
+        PrimedTnt { fuse },
+        SpatialBundle::from_transform(Transform::from_translation(pos.as_vec3() + Vec3::splat(0.5))),
+    ));
+}
+
+/// TNT 引线倒计时，期间闪烁并逐渐膨胀，归零时爆炸
+fn tick_primed_tnt(
+    mut commands: Commands,
+    mut tnt: Query<(Entity, &mut PrimedTnt, &mut Transform, &mut Visibility)>,
+    mut explosions: EventWriter<ExplosionEvent>,
+    time: Res<Time>,
+) {
+    for (entity, mut primed, mut transform, mut visibility) in tnt.iter_mut() {
+        primed.fuse -= time.delta_seconds();
+        if primed.fuse <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            explosions.send(ExplosionEvent { center: transform.translation, power: TNT_POWER });
+            continue;
+        }
+        let flash = ((primed.fuse / FLASH_SECONDS) as u32).is_multiple_of(2);
+        *visibility = if flash { Visibility::Hidden } else { Visibility::Inherited };
+        let swell = 1.0 + 0.15 * (1.0 - (primed.fuse / TNT_FUSE_SECONDS).min(1.0));
+        transform.scale = Vec3::splat(swell);
+    }
+}
+
+/// 拷贝爆炸范围内的区块，在后台线程计算被摧毁的方块
+fn start_explosions(
+    mut commands: Commands,
+    mut explosions: EventReader<ExplosionEvent>,
+    chunks: Query<&Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+    registry: Res<BlockRegistry>,
+    time: Res<Time>,
+) {
+    let task_pool = AsyncComputeTaskPool::get();
+    for (i, explosion) in explosions.read().enumerate() {
+        let reach = Vec3::splat(max_radius(explosion.power));
+        let snapshot = BlockSnapshot::capture(
+            (explosion.center - reach).floor().as_ivec3(),
+            (explosion.center + reach).ceil().as_ivec3(),
+            &chunks,
+            &chunk_storage,
+        );
+        let resistance = registry.blast_resistance();
+        let seed = time.elapsed_seconds_f64().to_bits() ^ i as u64;
+        let ExplosionEvent { center, power } = *explosion;
+        let chunks = snapshot.chunks.keys().copied().collect();
+        let task = task_pool.spawn(async move { explode(center, power, &snapshot, &resistance, seed) });
+        commands.spawn(ExplosionTask { center, power, seed, task, chunks });
+    }
+}
+
+/// 应用计算完成的爆炸：一次性移除所有方块，引燃范围内的 TNT，生存模式下部分方块掉落，
+/// 并对范围内的玩家造成伤害和击退
+fn finish_explosions(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut ExplosionTask)>,
+    mut world: WorldAccess,
+    mut block_changes: EventWriter<BlockChangedEvent>,
+    mut damage: EventWriter<PlayerDamageEvent>,
+    mut sounds: EventWriter<SoundEvent>,
+    player: Query<&Transform, With<FirstPersonController>>,
+    world_manager: Res<WorldManager>,
+) {
+    for (entity, mut explosion) in tasks.iter_mut() {
+        let Some(destroyed) = future::block_on(future::poll_once(&mut explosion.task)) else { continue };
+        commands.entity(entity).despawn();
+        sounds.send(SoundEvent::explosion());
+
+        // 后台计算期间方块可能已经变化，按当前的方块处理
+        let mut batch = BlockEditBatch::new();
+        for &(pos, _) in &destroyed {
+            batch.set(pos, BlockId::Air);
+        }
+        let previous = world.apply(batch);
+        for coord in &explosion.chunks {
+            world.chunk_storage.release(coord);
+        }
+        let survival = world_manager.current_game_mode() == GameMode::Survival;
+        for (i, &(pos, block)) in previous.iter().enumerate() {
+            block_changes.send(BlockChangedEvent { pos });
+            if block == BlockId::Tnt {
+                let (min, max) = CHAIN_FUSE_SECONDS;
+                spawn_primed_tnt(&mut commands, pos, min + (max - min) * random_unit(explosion.seed, i as u64));
+            } else if survival && block != BlockId::Air && random_unit(!explosion.seed, i as u64) < 1.0 / explosion.power {
+                // 与原版相同，威力越大掉落的比例越小
+                if let Some(drop) = block_drop(block, random_unit(explosion.seed.rotate_left(32), i as u64)) {
+                    spawn_dropped_item(&mut commands, drop, pos.as_vec3() + Vec3::splat(0.5));
+                }
+            }
+        }
+
+        if let Ok(transform) = player.get_single() {
+            let offset = transform.translation + Vec3::Y * PLAYER_CENTER_HEIGHT - explosion.center;
+            if let Some((amount, impact)) = explosion_impact(explosion.power, offset.length()) {
+                let knockback = offset.normalize_or_zero() * impact * MAX_KNOCKBACK;
+                damage.send(PlayerDamageEvent { amount, knockback });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explosion_carves_a_bounded_crater_and_spares_bedrock() {
+        // 整个区块都是石头，爆炸中心旁边埋一块黑曜石，下方 y = 10 是一层基岩
+        let mut chunk = Chunk::new(IVec3::ZERO);
+        for x in 0..32 {
+            for y in 0..32 {
+                for z in 0..32 {
+                    let block = if y == 10 { BlockId::Bedrock } else { BlockId::Stone };
+                    chunk.set_block(x, y, z, block);
+                }
+            }
+        }
+        chunk.set_block(18, 16, 16, BlockId::Obsidian);
+        let snapshot = BlockSnapshot { chunks: HashMap::from([(IVec3::ZERO, chunk.blocks.clone())]) };
+        let mut resistance = [1.0; BlockId::COUNT];
+        resistance[BlockId::Stone as usize] = 6.0;
+        resistance[BlockId::Obsidian as usize] = 1200.0;
+        resistance[BlockId::Bedrock as usize] = 3_600_000.0;
+
+        let center = Vec3::new(16.5, 16.5, 16.5);
+        let destroyed = explode(center, TNT_POWER, &snapshot, &resistance, 7);
+        assert!(destroyed.contains(&(IVec3::new(16, 16, 16), BlockId::Stone)));
+        assert!(destroyed.iter().all(|&(pos, block)| {
+            block == BlockId::Stone && (pos.as_vec3() + Vec3::splat(0.5)).distance(center) < max_radius(TNT_POWER) + 1.0
+        }));
+        // 空旷处的 TNT 比埋在石头里炸得更远
+        let mut air = Chunk::new(IVec3::ZERO);
+        air.set_block(16, 16, 22, BlockId::Dirt);
+        let open = BlockSnapshot { chunks: HashMap::from([(IVec3::ZERO, air.blocks.clone())]) };
+        assert_eq!(explode(center, TNT_POWER, &open, &resistance, 7), vec![(IVec3::new(16, 16, 22), BlockId::Dirt)]);
+
+        // 伤害随距离减小，范围外没有影响
+        let (near, _) = explosion_impact(TNT_POWER, 1.0).unwrap();
+        let (far, _) = explosion_impact(TNT_POWER, 6.0).unwrap();
+        assert!(near > far && far >= 1.0);
+        assert!(explosion_impact(TNT_POWER, 8.0).is_none());
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use futures_lite::future;
+use crate::block_registry::BlockRegistry;
+use crate::controller::FirstPersonController;
+use crate::game_state::WorldManager;
+use crate::hud::hud_visible;
+use crate::localization::LocalizationManager;
+use crate::rendering::voxel_mesh::{CubeFace, VoxelMeshBuilder};
+use crate::notifications::Notifications;
+use crate::world::chunk::{BlockId, Chunk, LodLevel};
+use crate::world::generator::{WorldGenerator, WorldGeneratorConfig};
+use crate::world::persistence;
+use crate::world::storage::ChunkStorage;
+
+const EXPORTS_DIR: &str = "exports";
+/// 单次导出的方块数上限，避免误输入坐标时长时间占满后台线程
+const MAX_EXPORT_BLOCKS: u64 = 64 * 1024 * 1024;
+/// 进度以千分比保存
+const PROGRESS_SCALE: u32 = 1000;
+
+/// 要导出的方块区域，两个角都包含在内
+#[derive(Clone, Debug)]
+pub struct ExportRegion {
+    pub min: IVec3,
+    pub max: IVec3,
+    pub name: String,
+}
+
+impl ExportRegion {
+    pub fn new(corner_a: IVec3, corner_b: IVec3, name: impl Into<String>) -> Self {
+        Self { min: corner_a.min(corner_b), max: corner_a.max(corner_b), name: name.into() }
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.max - self.min + IVec3::ONE
+    }
+
+    pub fn block_count(&self) -> u64 {
+        let size = self.size();
+        size.x as u64 * size.y as u64 * size.z as u64
+    }
+
+    fn contains(&self, pos: IVec3) -> bool {
+        pos.cmpge(self.min).all() && pos.cmple(self.max).all()
+    }
+
+    fn chunk_coords(&self) -> Vec<IVec3> {
+        let (min, max) = (self.min.div_euclid(IVec3::splat(32)), self.max.div_euclid(IVec3::splat(32)));
+        let mut coords = Vec::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    coords.push(IVec3::new(x, y, z));
+                }
+            }
+        }
+        coords
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() || !self.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err(format!("Invalid export name '{}': use letters, digits, '_' or '-'", self.name));
+        }
+        if self.block_count() > MAX_EXPORT_BLOCKS {
+            return Err(format!("Region has {} blocks, the limit is {}", self.block_count(), MAX_EXPORT_BLOCKS));
+        }
+        Ok(())
+    }
+}
+
+/// 把区域导出为 exports/<名称>.obj 和 .mtl（服务器控制台的 export 命令或导出窗口发送）
+#[derive(Event)]
+pub struct ExportRegionEvent(pub ExportRegion);
+
+/// 正在后台运行的导出
+#[derive(Component)]
+pub struct ExportTask {
+    pub name: String,
+    pub progress: Arc<AtomicU32>,
+    task: Task<Result<PathBuf, String>>,
+}
+
+impl ExportTask {
+    pub fn fraction(&self) -> f32 {
+        self.progress.load(Ordering::Relaxed) as f32 / PROGRESS_SCALE as f32
+    }
+}
+
+/// 导出窗口的开关和输入内容，由设置窗口中的按钮打开
+#[derive(Resource)]
+pub struct ExportWindow {
+    pub open: bool,
+    from: IVec3,
+    to: IVec3,
+    name: String,
+}
+
+impl Default for ExportWindow {
+    fn default() -> Self {
+        Self { open: false, from: IVec3::ZERO, to: IVec3::splat(15), name: "build".to_string() }
+    }
+}
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportRegionEvent>()
+           .init_resource::<ExportWindow>()
+           .add_systems(Update, (
+               start_exports.run_if(on_event::<ExportRegionEvent>()),
+               finish_exports,
+           ));
+
+        if app.is_plugin_added::<EguiPlugin>() {
+            app.add_systems(Update, export_window_ui.run_if(hud_visible));
+        }
+    }
+}
+
+/// 材质名称和对应的纹理文件；草方块底面使用泥土材质，流体、火把和树苗不导出
+fn face_material(block: BlockId, face: CubeFace) -> Option<(&'static str, &'static str)> {
+    match (block, face) {
+        (BlockId::Air | BlockId::Water | BlockId::Lava | BlockId::Torch | BlockId::Sapling, _) => None,
+        (BlockId::Stone, _) => Some(("stone", "stone.png")),
+        (BlockId::Dirt, _) | (BlockId::Grass, CubeFace::Bottom) => Some(("dirt", "dirt.png")),
+        (BlockId::Bedrock, _) => Some(("bedrock", "bedrock.png")),
+        (BlockId::Obsidian, _) => Some(("obsidian", "obsidian.png")),
+        (BlockId::Sand, _) => Some(("sand", "sand.png")),
+        (BlockId::Gravel, _) => Some(("gravel", "gravel.png")),
+        (BlockId::CraftingTable, _) => Some(("crafting_table", "crafting_table_side.png")),
+        (BlockId::Chest, _) => Some(("chest", "oak_planks.png")),
+        (BlockId::Tnt, _) => Some(("tnt", "tnt_side.png")),
+        (BlockId::Bed, _) => Some(("bed", "red_wool.png")),
+        // 导出时不区分原木的朝向，按竖直放置处理
+        (BlockId::Log, CubeFace::Top | CubeFace::Bottom) => Some(("log_top", "oak_log_top.png")),
+        (BlockId::Log, _) => Some(("log", "oak_log.png")),
+        (BlockId::Leaves, _) => Some(("leaves", "oak_leaves.png")),
+        (BlockId::Grass, CubeFace::Top) => Some(("grass_top", "grass_block_top.png")),
+        (BlockId::Grass, _) => Some(("grass_side", "grass_block_side.png")),
+    }
+}
+
+/// 截取已加载的完整分辨率区块，其余区块在后台任务中从存档读取或重新生成
+fn start_exports(
+    mut commands: Commands,
+    mut events: EventReader<ExportRegionEvent>,
+    chunk_storage: Res<ChunkStorage>,
+    chunks: Query<(&Chunk, Option<&LodLevel>)>,
+    world_manager: Res<WorldManager>,
+    generator_config: Res<WorldGeneratorConfig>,
+    registry: Res<BlockRegistry>,
+    mut notifications: Option<ResMut<Notifications>>,
+) {
+    for ExportRegionEvent(region) in events.read() {
+        if let Err(e) = region.validate() {
+            error!("Export failed: {}", e);
+            if let Some(notifications) = notifications.as_mut() {
+                notifications.error(e);
+            }
+            continue;
+        }
+
+        let mut loaded = HashMap::new();
+        for coord in region.chunk_coords() {
+            let Some((chunk, lod)) = chunk_storage.get(&coord).and_then(|entity| chunks.get(entity).ok()) else { continue };
+            if lod.map_or(true, |lod| lod.0 == 0) {
+                loaded.insert(coord, chunk.clone());
+            }
+        }
+        info!(
+            "Exporting {:?}..={:?} ({} blocks, {} chunks loaded) as '{}'",
+            region.min, region.max, region.block_count(), loaded.len(), region.name
+        );
+
+        let progress = Arc::new(AtomicU32::new(0));
+        let task_progress = progress.clone();
+        let task_region = region.clone();
+        let world_dir = world_manager.current_world_dir();
+        let generator_config = generator_config.clone();
+        let registry = registry.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            let mut generator = None;
+            for coord in task_region.chunk_coords() {
+                if loaded.contains_key(&coord) {
+                    continue;
+                }
+                let chunk = persistence::load_chunk(&world_dir, coord).unwrap_or_else(|| {
+                    let generator = generator.get_or_insert_with(|| WorldGenerator::new(generator_config.clone()));
+                    let mut chunk = Chunk::new(coord);
+                    generator.generate_chunk(&mut chunk, &registry);
+                    chunk
+                });
+                loaded.insert(coord, chunk);
+            }
+            write_export(&task_region, &loaded, &crate::paths::data_path(EXPORTS_DIR), &task_progress)
+        });
+        commands.spawn(ExportTask { name: region.name.clone(), progress, task });
+    }
+}
+
+fn finish_exports(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut ExportTask)>,
+    mut notifications: Option<ResMut<Notifications>>,
+    localization: Option<Res<LocalizationManager>>,
+) {
+    for (entity, mut export) in tasks.iter_mut() {
+        let Some(result) = future::block_on(future::poll_once(&mut export.task)) else { continue };
+        commands.entity(entity).despawn();
+        let label = |key: &str| localization.as_ref().map_or(key.to_string(), |localization| localization.get(key).to_string());
+        match result {
+            Ok(path) => {
+                info!("Exported '{}' to {:?}", export.name, path);
+                if let Some(notifications) = notifications.as_mut() {
+                    notifications.info(format!("{}: {}", label("game.export.done"), path.display()));
+                }
+            }
+            Err(e) => {
+                error!("Export '{}' failed: {}", export.name, e);
+                if let Some(notifications) = notifications.as_mut() {
+                    notifications.error(format!("{}: {}", label("game.export.failed"), e));
+                }
+            }
+        }
+    }
+}
+
+/// 把区域当作一整块体素运行面剔除，逐层写入 OBJ，区域外视为空气。
+/// 每层的面写完后就释放，导出大区域时内存占用只和单层可见面数有关
+fn write_export(
+    region: &ExportRegion,
+    chunks: &HashMap<IVec3, Chunk>,
+    out_dir: &Path,
+    progress: &AtomicU32,
+) -> Result<PathBuf, String> {
+    fs::create_dir_all(out_dir).map_err(|e| format!("Failed to create {:?}: {}", out_dir, e))?;
+    let obj_path = out_dir.join(format!("{}.obj", region.name));
+    let mtl_name = format!("{}.mtl", region.name);
+    let file = File::create(&obj_path).map_err(|e| format!("Failed to create {:?}: {}", obj_path, e))?;
+    let mut obj = ObjWriter { out: BufWriter::new(file), vertex_count: 0 };
+    let io_error = |e: std::io::Error| format!("Failed to write {:?}: {}", obj_path, e);
+
+    let block_at = |pos: IVec3| {
+        if !region.contains(pos) {
+            return BlockId::Air;
+        }
+        let coord = pos.div_euclid(IVec3::splat(32));
+        let local = pos - coord * 32;
+        chunks.get(&coord).map_or(BlockId::Air, |chunk| chunk.get_block(local.x as u32, local.y as u32, local.z as u32))
+    };
+
+    writeln!(obj.out, "# Minecraft Rust export {:?}..={:?}\nmtllib {}", region.min, region.max, mtl_name).map_err(io_error)?;
+    let mut used_materials = BTreeSet::new();
+    let size = region.size();
+    for y in 0..size.y {
+        let mut layer: HashMap<&'static str, VoxelMeshBuilder> = HashMap::new();
+        for z in 0..size.z {
+            for x in 0..size.x {
+                let offset = IVec3::new(x, y, z);
+                let block = block_at(region.min + offset);
+                if !block.is_solid() {
+                    continue;
+                }
+                for face in CubeFace::ALL {
+                    if !block.face_visible_against(block_at(region.min + offset + face.normal().as_ivec3())) {
+                        continue;
+                    }
+                    let Some(material) = face_material(block, face) else { continue };
+                    used_materials.insert(material);
+                    // 与游戏内草方块网格相同的 UV 翻转
+                    let (flip_uv, vertical_flip) = match (block, face) {
+                        (BlockId::Grass, CubeFace::Top) => (true, false),
+                        (BlockId::Grass, CubeFace::Bottom) => (false, false),
+                        (BlockId::Grass, _) => (false, true),
+                        _ => (false, false),
+                    };
+                    layer.entry(material.0).or_default().add_cube_face(offset.as_vec3(), face, 0, flip_uv, vertical_flip);
+                }
+            }
+        }
+        for (material, builder) in layer {
+            obj.write_mesh(material, &builder).map_err(io_error)?;
+        }
+        progress.store((y as u32 + 1) * PROGRESS_SCALE / size.y as u32, Ordering::Relaxed);
+    }
+    obj.out.flush().map_err(io_error)?;
+
+    write_materials(&out_dir.join(&mtl_name), &used_materials)?;
+    Ok(obj_path)
+}
+
+struct ObjWriter {
+    out: BufWriter<File>,
+    vertex_count: u32,
+}
+
+impl ObjWriter {
+    /// OBJ 的索引从 1 开始且在整个文件中累计，每个顶点单独写出位置、UV 和法线
+    fn write_mesh(&mut self, material: &str, builder: &VoxelMeshBuilder) -> std::io::Result<()> {
+        writeln!(self.out, "usemtl {}", material)?;
+        for ((position, uv), normal) in builder.positions.iter().zip(&builder.uvs).zip(&builder.normals) {
+            writeln!(self.out, "v {} {} {}", position.x, position.y, position.z)?;
+            writeln!(self.out, "vt {} {}", uv[0], uv[1])?;
+            writeln!(self.out, "vn {} {} {}", normal.x, normal.y, normal.z)?;
+        }
+        for triangle in builder.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.vertex_count + triangle[i] + 1);
+            writeln!(self.out, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}")?;
+        }
+        self.vertex_count += builder.positions.len() as u32;
+        Ok(())
+    }
+}
+
+/// 写出材质库，并把用到的纹理复制到 exports/textures/；找不到纹理时只记录警告，模型仍可打开
+fn write_materials(path: &Path, materials: &BTreeSet<(&'static str, &'static str)>) -> Result<(), String> {
+    let texture_dir = path.with_file_name("textures");
+    let mut mtl = String::new();
+    for (material, texture) in materials {
+        mtl.push_str(&format!("newmtl {}\nKd 1.000 1.000 1.000\nmap_Kd textures/{}\n\n", material, texture));
+        let target = texture_dir.join(texture);
+        if target.exists() {
+            continue;
+        }
+        let source = crate::paths::assets_dir().join("textures/block").join(texture);
+        let copied = fs::create_dir_all(&texture_dir).and_then(|_| fs::copy(&source, &target));
+        if let Err(e) = copied {
+            warn!("Failed to copy texture {:?} for export: {}", source, e);
+        }
+    }
+    persistence::write_atomically(path, mtl.as_bytes())
+}
+
+fn export_window_ui(
+    mut contexts: EguiContexts,
+    mut window: ResMut<ExportWindow>,
+    tasks: Query<&ExportTask>,
+    player: Query<&Transform, With<FirstPersonController>>,
+    mut export_events: EventWriter<ExportRegionEvent>,
+    localization: Res<LocalizationManager>,
+) {
+    if !window.open {
+        return;
+    }
+    let mut open = true;
+    egui::Window::new(localization.get("game.export.title"))
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            let coordinate_row = |ui: &mut egui::Ui, label: &str, value: &mut IVec3| {
+                ui.label(label);
+                ui.add(egui::DragValue::new(&mut value.x).prefix("x "));
+                ui.add(egui::DragValue::new(&mut value.y).prefix("y "));
+                ui.add(egui::DragValue::new(&mut value.z).prefix("z "));
+                ui.end_row();
+            };
+            let window = &mut *window;
+            egui::Grid::new("export_region").num_columns(4).show(ui, |ui| {
+                coordinate_row(ui, localization.get("game.export.from"), &mut window.from);
+                coordinate_row(ui, localization.get("game.export.to"), &mut window.to);
+            });
+            if ui.button(localization.get("game.export.around_player")).clicked() {
+                if let Ok(transform) = player.get_single() {
+                    let center = transform.translation.floor().as_ivec3();
+                    window.from = center - IVec3::new(8, 4, 8);
+                    window.to = center + IVec3::new(8, 12, 8);
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.label(localization.get("game.export.name"));
+                ui.text_edit_singleline(&mut window.name);
+            });
+
+            let region = ExportRegion::new(window.from, window.to, window.name.trim());
+            let size = region.size();
+            ui.label(format!("{}: {} × {} × {} ({})", localization.get("game.export.size"), size.x, size.y, size.z, region.block_count()));
+            if ui.button(localization.get("game.export.export")).clicked() {
+                export_events.send(ExportRegionEvent(region));
+            }
+
+            for task in tasks.iter() {
+                ui.add(egui::ProgressBar::new(task.fraction()).text(format!("{}: {}", localization.get("game.export.exporting"), task.name)));
+            }
+        });
+    window.open = open;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_culls_faces_inside_the_region() {
+        let mut chunk = Chunk::new(IVec3::ZERO);
+        for x in 0..2 {
+            chunk.set_block(x, 0, 0, BlockId::Stone);
+        }
+        chunk.set_block(0, 1, 0, BlockId::Grass);
+        let chunks = HashMap::from([(IVec3::ZERO, chunk)]);
+        let dir = std::env::temp_dir().join(format!("region_export_{}", std::process::id()));
+        let progress = AtomicU32::new(0);
+
+        let region = ExportRegion::new(IVec3::new(3, 3, 3), IVec3::ZERO, "test");
+        let path = write_export(&region, &chunks, &dir, &progress).unwrap();
+        let obj = fs::read_to_string(&path).unwrap();
+        let mtl = fs::read_to_string(dir.join("test.mtl")).unwrap();
+
+        // 两块石头相接的两个面和石头与草方块之间的两个面被剔除：3 × 6 - 4 = 14 个面，每个面两个三角形
+        assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 14 * 2);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 14 * 4);
+        assert!(mtl.contains("newmtl grass_top") && mtl.contains("newmtl grass_side") && !mtl.contains("newmtl dirt"));
+        assert_eq!(progress.load(Ordering::Relaxed), PROGRESS_SCALE);
+        assert!(ExportRegion::new(IVec3::ZERO, IVec3::ONE, "bad name").validate().is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
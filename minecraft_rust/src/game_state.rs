@@ -1,91 +1,286 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use bevy::tasks::{AsyncComputeTaskPool, Task};
 use futures_lite::future;
+use crate::localization::LocalizationManager;
+use crate::message_log::MessageLog;
+use crate::notifications::Notifications;
+use crate::net::client::connected_to_server;
+
+pub mod migrations;
+
+pub use mc_common::{GameMode, WorldInfo, WorldType, SAVES_DIR};
 
 /// 游戏状态枚举
 #[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum GameState {
+    /// 不经启动器直接运行游戏（没有 `--world`）时显示的主菜单，选择世界后进入 `Loading`
+    MainMenu,
+    /// 预加载出生区域，完成后进入游戏
     #[default]
+    Loading,
     InGame,
+    /// 暂停菜单打开。单人游戏中世界完全停止：只在 `InGame` 中运行的玩法系统不运行，
+    /// 区块加载器不再取出生成和卸载任务（线程池中已有的任务照常完成，结果留到恢复后处理），
+    /// 玩法系统读取的虚拟时间（`Res<Time>`）冻结。界面和存档等需要继续计时的系统使用 `Time<Real>`
     Paused,
+    /// 摄影模式：摄像机脱离玩家自由飞行，世界与暂停时一样停止
+    PhotoMode,
 }
 
-/// 世界存档信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorldInfo {
-    pub name: String,
-    pub seed: u32,
-    pub created_time: String,
-    pub last_played: String,
-    pub game_mode: GameMode,
-    pub world_type: WorldType,
+/// 运行条件：当前世界为创造模式
+pub fn in_creative_mode(world_manager: Res<WorldManager>) -> bool {
+    world_manager.current_game_mode().is_creative()
 }
 
-impl Default for WorldInfo {
-    fn default() -> Self {
-        Self {
-            name: "新世界".to_string(),
-            seed: 12345,
-            created_time: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-            last_played: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-            game_mode: GameMode::Creative,
-            world_type: WorldType::Default,
+/// 运行时世界状态文件，与 world_info.json 放在同一目录
+const WORLD_STATE_FILE: &str = "level.json";
+/// 世界状态文件的格式版本，记录在世界信息中，见 `migrations`
+pub const WORLD_STATE_FORMAT_VERSION: u32 = 1;
+
+/// 游戏规则的值，JSON 中直接存为布尔值、数字或字符串
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl GameRuleValue {
+    /// 解析命令输入的规则值：true/false 为布尔值，能解析为整数或小数时为数字，否则为字符串
+    pub fn parse(input: &str) -> Self {
+        match input {
+            "true" => GameRuleValue::Bool(true),
+            "false" => GameRuleValue::Bool(false),
+            _ => input.parse().map(GameRuleValue::Int)
+                .or_else(|_| input.parse().map(GameRuleValue::Float))
+                .unwrap_or_else(|_| GameRuleValue::Text(input.to_string())),
         }
     }
-}
 
-/// 游戏模式
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
-pub enum GameMode {
-    Survival,
-    #[default]
-    Creative,
-    Adventure,
-    Spectator,
+    /// 把输入转换成与 `self` 相同类型的值；小数规则也接受整数。数字规则不能为负
+    fn parse_as(&self, input: &str) -> Option<GameRuleValue> {
+        match (self, GameRuleValue::parse(input)) {
+            (GameRuleValue::Bool(_), value @ GameRuleValue::Bool(_)) => Some(value),
+            (GameRuleValue::Int(_), GameRuleValue::Int(value)) if value >= 0 => Some(GameRuleValue::Int(value)),
+            (GameRuleValue::Float(_), GameRuleValue::Int(value)) if value >= 0 => Some(GameRuleValue::Float(value as f64)),
+            (GameRuleValue::Float(_), GameRuleValue::Float(value)) if value >= 0.0 && value.is_finite() => Some(GameRuleValue::Float(value)),
+            (GameRuleValue::Text(_), _) => Some(GameRuleValue::Text(input.to_string())),
+            _ => None,
+        }
+    }
 }
 
-impl GameMode {
-    pub fn as_str(&self) -> &'static str {
+impl std::fmt::Display for GameRuleValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            GameMode::Survival => "生存模式",
-            GameMode::Creative => "创造模式",
-            GameMode::Adventure => "冒险模式",
-            GameMode::Spectator => "旁观模式",
+            GameRuleValue::Bool(value) => write!(f, "{}", value),
+            GameRuleValue::Int(value) => write!(f, "{}", value),
+            GameRuleValue::Float(value) => write!(f, "{}", value),
+            GameRuleValue::Text(value) => f.write_str(value),
+        }
+    }
+}
+
+/// 玩家和生物的重力倍数的默认值（乘以设置中的重力），见 `WorldState::gravity_scale`
+pub const DEFAULT_GRAVITY_SCALE: f32 = 2.0;
+
+/// 已知的游戏规则和默认值。存档中的其他规则（例如更新版本的游戏添加的规则）原样保留
+pub fn default_game_rules() -> [(&'static str, GameRuleValue); 7] {
+    [
+        ("do_daylight_cycle", GameRuleValue::Bool(true)),
+        ("do_weather_cycle", GameRuleValue::Bool(true)),
+        ("do_mob_spawning", GameRuleValue::Bool(true)),
+        ("fall_damage", GameRuleValue::Bool(true)),
+        ("keep_inventory", GameRuleValue::Bool(false)),
+        ("random_tick_speed", GameRuleValue::Int(3)),
+        ("gravity_scale", GameRuleValue::Float(DEFAULT_GRAVITY_SCALE as f64)),
+    ]
+}
+
+/// 规则名称：原版的驼峰写法（doDaylightCycle）转换为存档中的下划线写法（do_daylight_cycle）
+pub fn game_rule_name(input: &str) -> String {
+    let mut name = String::with_capacity(input.len() + 4);
+    for c in input.chars() {
+        if c.is_ascii_uppercase() {
+            if !name.is_empty() {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else {
+            name.push(c);
         }
     }
+    name
+}
+
+/// 修改游戏规则失败的原因
+#[derive(Debug, PartialEq)]
+pub enum GameRuleError {
+    Unknown,
+    /// 值的类型不对或超出范围
+    InvalidValue,
 }
 
-/// 世界类型
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
-pub enum WorldType {
+/// 天气
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Weather {
     #[default]
-    Default,
-    Flat,
-    LargeBiomes,
-    Amplified,
+    Clear,
+    Rain,
 }
 
-impl WorldType {
-    pub fn as_str(&self) -> &'static str {
+impl Weather {
+    /// 解析命令输入的天气名称
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "clear" => Some(Weather::Clear),
+            "rain" => Some(Weather::Rain),
+            _ => None,
+        }
+    }
+
+    /// 本地化键，例如 "game.command.weather.rain"
+    pub fn localization_key(self) -> &'static str {
         match self {
-            WorldType::Default => "默认",
-            WorldType::Flat => "超平坦",
-            WorldType::LargeBiomes => "巨型生物群系",
-            WorldType::Amplified => "放大化",
+            Weather::Clear => "game.command.weather.clear",
+            Weather::Rain => "game.command.weather.rain",
+        }
+    }
+
+    /// 这种天气随机持续的时间范围（秒）
+    fn duration_range(self) -> (f32, f32) {
+        match self {
+            Weather::Clear => (600.0, 3600.0),
+            Weather::Rain => (300.0, 900.0),
         }
     }
 }
 
+/// 运行时世界状态（类似 level.dat）：时间、天气、世界出生点和游戏规则。
+/// 进入世界时从存档读取，由自动保存写回
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldState {
+    /// 一天中的时间，0.0 ~ 1.0，0.5 为正午
+    pub time_of_day: f32,
+    pub weather: Weather,
+    /// 当前天气还会持续的秒数，归零时切换天气
+    pub weather_seconds: f32,
+    /// 世界出生点（方块坐标，y 为地表高度），第一次进入世界时计算
+    pub spawn_point: Option<IVec3>,
+    /// 玩家最后使用的床（方块坐标），死亡后在床上复活；床不存在时回到世界出生点
+    pub player_spawn: Option<IVec3>,
+    pub game_rules: BTreeMap<String, GameRuleValue>,
+}
+
+impl Default for WorldState {
+    fn default() -> Self {
+        let game_rules = default_game_rules();
+        Self {
+            time_of_day: 0.5,
+            weather: Weather::Clear,
+            weather_seconds: Weather::Clear.duration_range().0,
+            spawn_point: None,
+            player_spawn: None,
+            game_rules: game_rules.into_iter().map(|(name, value)| (name.to_string(), value)).collect(),
+        }
+    }
+}
+
+impl WorldState {
+    /// 布尔规则的值，未设置或不是布尔值时返回 false
+    pub fn rule_enabled(&self, name: &str) -> bool {
+        self.game_rules.get(name) == Some(&GameRuleValue::Bool(true))
+    }
+
+    /// 整数规则的值，未设置或不是整数时返回 None
+    pub fn rule_int(&self, name: &str) -> Option<i64> {
+        match self.game_rules.get(name) {
+            Some(GameRuleValue::Int(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// 数字规则的值，整数也可以作为小数读取；未设置或不是数字时返回 None
+    pub fn rule_float(&self, name: &str) -> Option<f64> {
+        match self.game_rules.get(name) {
+            Some(GameRuleValue::Float(value)) => Some(*value),
+            Some(GameRuleValue::Int(value)) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    /// 重力倍数，替代原来写死的 ×2
+    pub fn gravity_scale(&self) -> f32 {
+        self.rule_float("gravity_scale").map_or(DEFAULT_GRAVITY_SCALE, |scale| scale as f32)
+    }
+
+    pub fn set_rule(&mut self, name: &str, value: &str) {
+        self.game_rules.insert(name.to_string(), GameRuleValue::parse(value));
+    }
+
+    /// 修改已知的规则（`/gamerule`），值必须与默认值的类型相同。成功时返回规则的名称
+    pub fn set_known_rule(&mut self, name: &str, value: &str) -> Result<String, GameRuleError> {
+        let name = game_rule_name(name);
+        let (_, default) = default_game_rules().into_iter().find(|(known, _)| *known == name).ok_or(GameRuleError::Unknown)?;
+        let value = default.parse_as(value).ok_or(GameRuleError::InvalidValue)?;
+        self.game_rules.insert(name.clone(), value);
+        Ok(name)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize world state: {}", e))
+    }
+
+    /// 切换天气。`seconds` 为 None 时按天气类型随机选择持续时间，`random` 为 [0, 1) 的随机数
+    pub fn set_weather(&mut self, weather: Weather, seconds: Option<f32>, random: f32) {
+        let (min, max) = weather.duration_range();
+        self.weather = weather;
+        self.weather_seconds = seconds.unwrap_or(min + (max - min) * random);
+    }
+
+    /// 旧存档中没有的游戏规则使用默认值
+    fn fill_default_rules(&mut self) {
+        for (name, value) in WorldState::default().game_rules {
+            self.game_rules.entry(name).or_insert(value);
+        }
+    }
+
+    /// 天空亮度：正午为 1.0，午夜为 NIGHT_DAYLIGHT，日出日落时平滑过渡
+    pub fn daylight(&self) -> f32 {
+        let sun = (-(self.time_of_day * std::f32::consts::TAU).cos() * 0.5 + 0.5).clamp(0.0, 1.0);
+        NIGHT_DAYLIGHT + (1.0 - NIGHT_DAYLIGHT) * sun
+    }
+
+    /// 天空亮度低于 NIGHT_MAX_DAYLIGHT 时为夜晚：僵尸生成，可以睡觉
+    pub fn is_night(&self) -> bool {
+        self.daylight() < NIGHT_MAX_DAYLIGHT
+    }
+}
+
+/// 一个完整昼夜的长度（秒），与原版相同
+pub const DAY_LENGTH_SECONDS: f32 = 1200.0;
+/// 午夜时的天空亮度，不至于完全看不见
+const NIGHT_DAYLIGHT: f32 = 0.2;
+/// 天空亮度低于这个值时算作夜晚
+const NIGHT_MAX_DAYLIGHT: f32 = 0.5;
+
 /// 异步保存任务
 #[derive(Component)]
 pub struct SaveTask {
     pub task: Task<Result<(), String>>,
 }
 
+/// 标记写入世界数据（区块、玩家等）的保存任务，全部完成后在消息记录中提示已保存
+#[derive(Component)]
+pub struct WorldDataSave;
+
 /// 保存队列 - 避免重复保存同一个世界
 #[derive(Resource, Default)]
 pub struct SaveQueue {
@@ -96,12 +291,17 @@ pub struct SaveQueue {
 #[derive(Resource)]
 pub struct SaveTaskTimer {
     pub timer: Timer,
+    /// 这一轮保存中是否有世界数据的任务完成、是否有任务失败，所有任务完成后清除
+    world_saved: bool,
+    failed: bool,
 }
 
 impl Default for SaveTaskTimer {
     fn default() -> Self {
         Self {
             timer: Timer::from_seconds(1.0, TimerMode::Repeating), // 每秒检查一次
+            world_saved: false,
+            failed: false,
         }
     }
 }
@@ -116,10 +316,10 @@ pub struct WorldManager {
 
 impl WorldManager {
     pub fn new() -> Self {
-        let saves_dir = PathBuf::from("saves");
+        let saves_dir = crate::paths::data_path(SAVES_DIR);
         if !saves_dir.exists() {
             if let Err(e) = fs::create_dir_all(&saves_dir) {
-                error!("Failed to create saves directory: {}", e);
+                error!("Failed to create saves directory {}: {}", saves_dir.display(), e);
             }
         }
 
@@ -141,22 +341,13 @@ impl WorldManager {
             for entry in entries.flatten() {
                 if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
                     let world_name = entry.file_name().to_string_lossy().to_string();
-                    let world_info_path = entry.path().join("world_info.json");
-                    
-                    if world_info_path.exists() {
-                        match fs::read_to_string(&world_info_path) {
-                            Ok(content) => {
-                                match serde_json::from_str::<WorldInfo>(&content) {
-                                    Ok(world_info) => {
-                                        self.worlds.insert(world_name, world_info);
-                                    }
-                                    Err(e) => {
-                                        warn!("Failed to parse world info for {}: {}", world_name, e);
-                                    }
-                                }
+                    if WorldInfo::path(&entry.path()).exists() {
+                        match WorldInfo::read(&entry.path()) {
+                            Ok(world_info) => {
+                                self.worlds.insert(world_name, world_info);
                             }
                             Err(e) => {
-                                warn!("Failed to read world info for {}: {}", world_name, e);
+                                warn!("Failed to load world info for {}: {}", world_name, e);
                             }
                         }
                     }
@@ -168,7 +359,7 @@ impl WorldManager {
     }
 
     /// 创建新世界
-    pub fn create_world(&mut self, world_info: WorldInfo) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn create_world(&mut self, mut world_info: WorldInfo) -> Result<(), Box<dyn std::error::Error>> {
         let world_dir = self.saves_directory.join(&world_info.name);
         
         // 检查世界是否已存在
@@ -179,10 +370,11 @@ impl WorldManager {
         // 创建世界目录
         fs::create_dir_all(&world_dir)?;
 
-        // 保存世界信息
-        let world_info_path = world_dir.join("world_info.json");
-        let world_info_json = serde_json::to_string_pretty(&world_info)?;
-        fs::write(world_info_path, world_info_json)?;
+        // 保存世界信息，记下各持久化部分的当前格式版本
+        if world_info.component_versions.is_empty() {
+            world_info.component_versions = migrations::component_versions();
+        }
+        world_info.write(&world_dir)?;
 
         // 添加到世界列表
         self.worlds.insert(world_info.name.clone(), world_info);
@@ -215,10 +407,74 @@ impl WorldManager {
         self.current_world.as_ref().and_then(|name| self.worlds.get(name))
     }
 
+    /// 当前世界的游戏模式；未选择世界（例如联机客户端）时按默认的创造模式处理
+    pub fn current_game_mode(&self) -> GameMode {
+        self.get_current_world().map_or_else(GameMode::default, |world| world.game_mode)
+    }
+
+    /// 修改当前世界的游戏模式，随世界信息在退出世界时保存。未选择世界时返回 false
+    pub fn set_current_game_mode(&mut self, game_mode: GameMode) -> bool {
+        let Some(world_info) = self.current_world.as_ref().and_then(|name| self.worlds.get_mut(name)) else { return false };
+        world_info.game_mode = game_mode;
+        true
+    }
+
+    /// 当前世界的目录名，未选择世界时使用 "default"
+    pub fn current_world_name(&self) -> &str {
+        self.current_world.as_deref().unwrap_or("default")
+    }
+
+    /// 当前世界的存档目录
+    pub fn current_world_dir(&self) -> PathBuf {
+        let saves = if self.saves_directory.as_os_str().is_empty() {
+            crate::paths::data_path(SAVES_DIR)
+        } else {
+            self.saves_directory.clone()
+        };
+        saves.join(self.current_world_name())
+    }
+
+    /// 打开当前世界前检查存档版本并执行迁移，存档来自更新版本的游戏时返回错误
+    pub fn prepare_current_world(&self) -> Result<(), String> {
+        let steps = migrations::prepare_world(&self.current_world_dir())?;
+        if steps > 0 {
+            info!("Migrated world '{}' to save format {}", self.current_world_name(), migrations::SAVE_FORMAT_VERSION);
+        }
+        Ok(())
+    }
+
+    /// 当前世界信息中的种子，从存档读取（在 `prepare_current_world` 迁移之后调用）。
+    /// 未选择世界或世界信息读取失败时返回 None
+    pub fn current_world_seed(&self) -> Option<u32> {
+        self.current_world.as_ref()?;
+        match WorldInfo::read(&self.current_world_dir()) {
+            Ok(info) => Some(info.seed),
+            Err(e) => {
+                warn!("{}", e);
+                None
+            }
+        }
+    }
+
+    /// 当前世界的运行时状态文件路径
+    pub fn world_state_path(&self) -> PathBuf {
+        self.current_world_dir().join(WORLD_STATE_FILE)
+    }
+
+    /// 读取当前世界的运行时状态，新世界或文件损坏时使用默认值
+    pub fn load_world_state(&self) -> WorldState {
+        read_world_state(&self.world_state_path())
+            .unwrap_or_else(|e| {
+                warn!("{}", e);
+                None
+            })
+            .unwrap_or_default()
+    }
+
     /// 更新世界最后游玩时间（仅更新内存，不立即保存）
     pub fn update_last_played(&mut self, world_name: &str) {
         if let Some(world_info) = self.worlds.get_mut(world_name) {
-            world_info.last_played = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+            world_info.last_played = mc_common::timestamp_now();
         }
     }
     
@@ -243,18 +499,7 @@ impl WorldManager {
             
             let task_pool = AsyncComputeTaskPool::get();
             let task = task_pool.spawn(async move {
-                let world_dir = saves_directory.join(&world_name_clone);
-                let info_file = world_dir.join("world_info.json");
-                
-                match serde_json::to_string_pretty(&world_info_clone) {
-                    Ok(json) => {
-                        match std::fs::write(&info_file, json) {
-                            Ok(_) => Ok(()),
-                            Err(e) => Err(format!("Failed to write world info file: {}", e)),
-                        }
-                    }
-                    Err(e) => Err(format!("Failed to serialize world info: {}", e)),
-                }
+                world_info_clone.write(&saves_directory.join(&world_name_clone))
             });
             
             commands.spawn(SaveTask { task });
@@ -271,10 +516,61 @@ impl Plugin for GameStatePlugin {
            .init_resource::<WorldManager>()
            .init_resource::<SaveQueue>()
            .init_resource::<SaveTaskTimer>()
+           .init_resource::<WorldState>()
            .add_systems(Startup, setup_world_manager)
            .add_systems(OnEnter(GameState::InGame), update_world_last_played)
-           .add_systems(Update, handle_save_tasks);
+           // 联机时世界由服务器模拟，暂停菜单不停止时间
+           .add_systems(OnEnter(GameState::Paused), pause_simulation.run_if(not(connected_to_server)))
+           .add_systems(OnExit(GameState::Paused), resume_simulation)
+           .add_systems(OnEnter(GameState::PhotoMode), pause_simulation.run_if(not(connected_to_server)))
+           .add_systems(OnExit(GameState::PhotoMode), resume_simulation)
+           .add_systems(Update, handle_save_tasks)
+           .add_systems(Update, advance_time_of_day.run_if(in_state(GameState::InGame)))
+           // 联机时天气由服务器同步
+           .add_systems(Update, advance_weather.run_if(in_state(GameState::InGame)).run_if(not(connected_to_server)));
+    }
+}
+
+/// 游戏规则 do_weather_cycle 开启时天气持续时间倒计时，归零时在晴天和下雨之间切换
+fn advance_weather(time: Res<Time>, mut world_state: ResMut<WorldState>, mut changes: Local<u64>) {
+    if !world_state.rule_enabled("do_weather_cycle") {
+        return;
     }
+    world_state.weather_seconds -= time.delta_seconds();
+    if world_state.weather_seconds <= 0.0 {
+        let next = match world_state.weather {
+            Weather::Clear => Weather::Rain,
+            Weather::Rain => Weather::Clear,
+        };
+        *changes += 1;
+        let random = crate::random::random_unit(time.elapsed_seconds_f64().to_bits(), *changes);
+        world_state.set_weather(next, None, random);
+        info!("Weather changed to {:?} for {:.0} seconds", next, world_state.weather_seconds);
+    }
+}
+
+/// 冻结虚拟时间：暂停期间玩法系统的 delta 为 0，恢复后的第一帧也不包含暂停的时长
+pub fn pause_simulation(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+pub fn resume_simulation(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+/// 游戏规则 do_daylight_cycle 开启时推进一天中的时间
+fn advance_time_of_day(time: Res<Time>, mut world_state: ResMut<WorldState>) {
+    if world_state.rule_enabled("do_daylight_cycle") {
+        world_state.time_of_day = (world_state.time_of_day + time.delta_seconds() / DAY_LENGTH_SECONDS).fract();
+    }
+}
+
+/// 读取世界的运行时状态文件，文件不存在（新世界）时返回 None，无法解析时返回错误
+pub fn read_world_state(path: &Path) -> Result<Option<WorldState>, String> {
+    let Ok(content) = fs::read_to_string(path) else { return Ok(None) };
+    let mut state: WorldState = serde_json::from_str(&content).map_err(|e| format!("Failed to parse world state {:?}: {}", path, e))?;
+    state.fill_default_rules();
+    Ok(Some(state))
 }
 
 /// 设置世界管理器
@@ -282,6 +578,11 @@ fn setup_world_manager(mut world_manager: ResMut<WorldManager>) {
     world_manager.load_worlds();
 }
 
+/// 进入世界时从存档恢复运行时世界状态
+pub fn load_world_state(world_manager: Res<WorldManager>, mut world_state: ResMut<WorldState>) {
+    *world_state = world_manager.load_world_state();
+}
+
 /// 更新当前世界的最后游玩时间
 fn update_world_last_played(
     mut world_manager: ResMut<WorldManager>,
@@ -297,11 +598,14 @@ fn update_world_last_played(
 
 /// 处理异步保存任务
 fn handle_save_tasks(
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     mut commands: Commands,
-    mut save_tasks: Query<(Entity, &mut SaveTask)>,
+    mut save_tasks: Query<(Entity, &mut SaveTask, Has<WorldDataSave>)>,
     mut save_queue: ResMut<SaveQueue>,
     mut save_timer: ResMut<SaveTaskTimer>,
+    // 服务器模式下没有界面，只记录日志
+    (mut notifications, mut message_log): (Option<ResMut<Notifications>>, Option<ResMut<MessageLog>>),
+    localization: Option<Res<LocalizationManager>>,
 ) {
     // 更新定时器
     save_timer.timer.tick(time.delta());
@@ -311,14 +615,22 @@ fn handle_save_tasks(
         return;
     }
     
-    for (entity, mut save_task) in &mut save_tasks {
+    let pending = save_tasks.iter().count();
+    let mut finished = 0;
+    for (entity, mut save_task, world_data) in &mut save_tasks {
         if let Some(result) = future::block_on(future::poll_once(&mut save_task.task)) {
+            finished += 1;
+            save_timer.world_saved |= world_data;
             match result {
                 Ok(_) => {
-                    debug!("World info saved successfully");
+                    debug!("Save task finished successfully");
                 }
                 Err(e) => {
-                    error!("Failed to save world info: {}", e);
+                    save_timer.failed = true;
+                    error!("Failed to save world: {}", e);
+                    if let (Some(notifications), Some(localization)) = (notifications.as_mut(), localization.as_ref()) {
+                        notifications.error(format!("{}: {}", localization.get("game.autosave.failed"), e));
+                    }
                 }
             }
             
@@ -326,6 +638,16 @@ fn handle_save_tasks(
             commands.entity(entity).despawn();
         }
     }
+    // 一次保存分成多个任务，全部完成并且都没有失败才算保存好
+    if finished > 0 && finished == pending {
+        if save_timer.world_saved && !save_timer.failed {
+            if let (Some(message_log), Some(localization)) = (message_log.as_mut(), localization.as_ref()) {
+                message_log.info(localization.get("game.autosave.saved"));
+            }
+        }
+        save_timer.world_saved = false;
+        save_timer.failed = false;
+    }
     
     // 定期清理保存队列中的旧条目（避免内存泄漏）
     if save_queue.pending_saves.len() > 100 {
@@ -333,32 +655,59 @@ fn handle_save_tasks(
     }
 }
 
-/// 处理ESC键切换暂停状态
-fn handle_escape_key(
-    keyboard: Res<Input<KeyCode>>,
-    current_state: Res<State<GameState>>,
-    mut next_state: ResMut<NextState<GameState>>,
-    mut windows: Query<&mut Window>,
-) {
-    if keyboard.just_pressed(KeyCode::Escape) {
-        match current_state.get() {
-            GameState::InGame => {
-                next_state.set(GameState::Paused);
-                // 解锁鼠标
-                if let Ok(mut window) = windows.get_single_mut() {
-                    window.cursor.grab_mode = bevy::window::CursorGrabMode::None;
-                    window.cursor.visible = true;
-                }
-            }
-            GameState::Paused => {
-                next_state.set(GameState::InGame);
-                // 锁定鼠标
-                if let Ok(mut window) = windows.get_single_mut() {
-                    window.cursor.grab_mode = bevy::window::CursorGrabMode::Confined;
-                    window.cursor.visible = false;
-                }
-            }
-            _ => {}
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_state_rules_round_trip_through_json() {
+        let mut state = WorldState::default();
+        state.spawn_point = Some(IVec3::new(4, 70, -2));
+        state.player_spawn = Some(IVec3::new(-10, 64, 7));
+        state.set_rule("keep_inventory", "true");
+        state.set_rule("random_tick_speed", "3");
+        state.set_rule("motd", "hello");
+
+        let loaded: WorldState = serde_json::from_str(&state.to_json().unwrap()).unwrap();
+        assert!(loaded.rule_enabled("keep_inventory"));
+        assert_eq!(loaded.game_rules["random_tick_speed"], GameRuleValue::Int(3));
+        assert_eq!(loaded.game_rules["motd"], GameRuleValue::Text("hello".into()));
+        assert_eq!(loaded.spawn_point, Some(IVec3::new(4, 70, -2)));
+        assert_eq!(loaded.player_spawn, Some(IVec3::new(-10, 64, 7)));
+
+        // 旧存档缺少的字段使用默认值
+        let partial: WorldState = serde_json::from_str(r#"{"time_of_day": 0.75}"#).unwrap();
+        assert_eq!(partial.time_of_day, 0.75);
+        assert_eq!(partial.weather, Weather::Clear);
+        assert_eq!(partial.player_spawn, None);
+        assert!(partial.rule_enabled("do_daylight_cycle"));
+
+        // 已有规则表但缺少新规则时补上默认值
+        let mut old: WorldState = serde_json::from_str(r#"{"game_rules": {"keep_inventory": true}}"#).unwrap();
+        assert!(!old.rule_enabled("do_weather_cycle"));
+        old.fill_default_rules();
+        assert!(old.rule_enabled("do_weather_cycle") && old.rule_enabled("keep_inventory"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn gamerule_command_checks_names_and_types_and_keeps_unknown_rules() {
+        let mut state: WorldState = serde_json::from_str(r#"{"game_rules": {"future_rule": "kept"}}"#).unwrap();
+        state.fill_default_rules();
+        assert_eq!(state.set_known_rule("keepInventory", "true"), Ok("keep_inventory".to_string()));
+        assert!(state.rule_enabled("keep_inventory"));
+        assert_eq!(state.set_known_rule("gravityScale", "1"), Ok("gravity_scale".to_string()));
+        assert_eq!(state.gravity_scale(), 1.0);
+        assert_eq!(state.set_known_rule("gravity_scale", "0.5"), Ok("gravity_scale".to_string()));
+        assert_eq!(state.gravity_scale(), 0.5);
+        assert_eq!(state.set_known_rule("random_tick_speed", "2.5"), Err(GameRuleError::InvalidValue));
+        assert_eq!(state.set_known_rule("random_tick_speed", "-1"), Err(GameRuleError::InvalidValue));
+        assert_eq!(state.set_known_rule("fall_damage", "1"), Err(GameRuleError::InvalidValue));
+        assert_eq!(state.set_known_rule("future_rule", "x"), Err(GameRuleError::Unknown));
+
+        // 不认识的规则随存档原样写回
+        let loaded: WorldState = serde_json::from_str(&state.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.game_rules["future_rule"], GameRuleValue::Text("kept".into()));
+        assert_eq!(loaded.game_rules["gravity_scale"], GameRuleValue::Float(0.5));
+        assert_eq!(WorldState::default().gravity_scale(), DEFAULT_GRAVITY_SCALE);
+    }
+}
@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use crate::world::persistence;
+use super::WorldInfo;
+
+/// 存档格式版本定义在与启动器共用的 `mc_common` 中，等于最后一个迁移步骤的版本
+pub use mc_common::{LEGACY_SAVE_FORMAT_VERSION, SAVE_FORMAT_VERSION};
+/// 迁移前的备份目录，与存档目录放在同一级，不会出现在世界列表中
+const BACKUP_DIR: &str = "save-backups";
+
+/// 各持久化部分当前的格式版本，由各自的模块定义。新建世界时写入世界信息，
+/// 打开世界时任何一部分比这里新都拒绝打开
+pub fn component_versions() -> BTreeMap<String, u32> {
+    [
+        ("chunks", persistence::CHUNK_FORMAT_VERSION),
+        ("world_state", super::WORLD_STATE_FORMAT_VERSION),
+        ("player", crate::autosave::PLAYER_SAVE_VERSION),
+        ("stats", crate::stats::STATS_FORMAT_VERSION),
+    ]
+    .into_iter()
+    .map(|(name, version)| (name.to_string(), version))
+    .collect()
+}
+
+/// 版本 1 的存档中各部分的格式版本。区块文件可能是任意旧格式，按最旧的算
+const LEGACY_COMPONENT_VERSIONS: [(&str, u32); 4] = [("chunks", 1), ("world_state", 1), ("player", 2), ("stats", 1)];
+
+/// 一个迁移步骤：把存档从上一个版本升级到 `version`，同时把 `component` 升级到 `component_version`
+struct Migration {
+    version: u32,
+    component: &'static str,
+    component_version: u32,
+    description: &'static str,
+    run: fn(&Path) -> Result<(), String>,
+}
+
+/// 按版本排列的迁移步骤。修改某个持久化部分的格式时，在这里添加一步并提高 `SAVE_FORMAT_VERSION`
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 2,
+        component: "chunks",
+        component_version: 3,
+        description: "rewrite chunk files saved in older formats",
+        run: upgrade_chunks,
+    },
+    Migration {
+        version: 3,
+        component: "chunks",
+        component_version: 4,
+        description: "allow chunk files to omit all-zero block metadata",
+        run: no_changes,
+    },
+    Migration {
+        version: 4,
+        component: "chunks",
+        component_version: 5,
+        description: "store dropped items and other persistent entities in chunk files",
+        run: no_changes,
+    },
+    Migration {
+        version: 5,
+        component: "chunks",
+        component_version: 6,
+        description: "store the biome of each column in chunk files",
+        run: no_changes,
+    },
+];
+
+/// 新格式能直接读取旧文件，只需要记下版本，旧版本的游戏不会再打开这个存档
+fn no_changes(_world_dir: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn upgrade_chunks(world_dir: &Path) -> Result<(), String> {
+    let upgraded = persistence::upgrade_chunk_files(world_dir)?;
+    info!("Rewrote {} chunk files in the current format", upgraded);
+    Ok(())
+}
+
+/// 存档比当前版本的游戏新时返回错误，消息可以直接显示给玩家
+pub fn check_supported(info: &WorldInfo) -> Result<(), String> {
+    if info.save_format_version > SAVE_FORMAT_VERSION {
+        return Err(format!(
+            "World '{}' was saved by a newer version of the game (save format {}, this version supports up to {})",
+            info.name, info.save_format_version, SAVE_FORMAT_VERSION
+        ));
+    }
+    let supported = component_versions();
+    for (component, &version) in &info.component_versions {
+        if supported.get(component).is_some_and(|&current| version > current) {
+            return Err(format!(
+                "World '{}' was saved by a newer version of the game ({} format {}, this version supports up to {})",
+                info.name, component, version, supported[component]
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 打开世界前检查存档版本，必要时先备份整个存档目录，再按顺序执行迁移，每一步完成后写回世界信息。
+/// 没有世界信息的目录（新世界）不做处理。返回执行的迁移步骤数
+pub fn prepare_world(world_dir: &Path) -> Result<usize, String> {
+    let info_path = WorldInfo::path(world_dir);
+    if !info_path.exists() {
+        return Ok(0);
+    }
+    let mut info = WorldInfo::read(world_dir)?;
+    check_supported(&info)?;
+    if info.save_format_version == SAVE_FORMAT_VERSION {
+        return Ok(0);
+    }
+
+    let backup = backup_world(world_dir, info.save_format_version)?;
+    info!("Backed up world '{}' to {:?} before migrating", info.name, backup);
+    if info.save_format_version == LEGACY_SAVE_FORMAT_VERSION {
+        for (component, version) in LEGACY_COMPONENT_VERSIONS {
+            info.component_versions.entry(component.to_string()).or_insert(version);
+        }
+    }
+    let steps: Vec<&Migration> = MIGRATIONS.iter().filter(|step| step.version > info.save_format_version).collect();
+    for step in &steps {
+        info!("Migrating world '{}' to save format {}: {}", info.name, step.version, step.description);
+        (step.run)(world_dir).map_err(|e| format!("Failed to migrate world '{}' to save format {}: {}", info.name, step.version, e))?;
+        info.save_format_version = step.version;
+        info.component_versions.insert(step.component.to_string(), step.component_version);
+        persistence::write_atomically(&info_path, info.to_json()?.as_bytes())?;
+    }
+    Ok(steps.len())
+}
+
+/// 把存档目录复制到 `save-backups/<世界名>-v<版本>-<时间>`，返回备份目录
+fn backup_world(world_dir: &Path, version: u32) -> Result<std::path::PathBuf, String> {
+    let saves_dir = world_dir.parent().ok_or_else(|| format!("Failed to back up {:?}: no parent directory", world_dir))?;
+    let name = world_dir.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let backup = saves_dir
+        .with_file_name(BACKUP_DIR)
+        .join(format!("{}-v{}-{}", name, version, chrono::Local::now().format("%Y-%m-%d_%H.%M.%S")));
+    copy_dir(world_dir, &backup)?;
+    Ok(backup)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {:?}: {}", to, e))?;
+    let entries = fs::read_dir(from).map_err(|e| format!("Failed to read {:?}: {}", from, e))?;
+    for entry in entries.flatten() {
+        let target = to.join(entry.file_name());
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target).map_err(|e| format!("Failed to copy {:?}: {}", entry.path(), e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::SAVES_DIR;
+    use mc_common::WORLD_INFO_FILE;
+    use crate::world::chunk::{BlockId, Chunk};
+    use serde::Serialize;
+
+    /// 版本 1 的区块文件格式（没有附加数据）
+    #[derive(Serialize)]
+    struct V1Chunk {
+        coord: IVec3,
+        #[serde(with = "serde_bytes")]
+        blocks: Vec<u8>,
+    }
+
+    #[test]
+    fn v1_save_migrates_to_the_current_version_and_loads() {
+        let root = std::env::temp_dir().join(format!("save_migration_{}", std::process::id()));
+        let world_dir = root.join(SAVES_DIR).join("old");
+        let coord = IVec3::new(1, 2, -3);
+        let mut blocks = vec![BlockId::Air as u8; Chunk::COUNT];
+        blocks[0] = BlockId::Stone as u8;
+        let old_chunk = bincode::serialize(&V1Chunk { coord, blocks }).unwrap();
+        persistence::write_atomically(&persistence::chunk_path(&world_dir, coord), &old_chunk).unwrap();
+        let info = r#"{"name": "old", "seed": 7, "created_time": "", "last_played": "", "game_mode": "Survival", "world_type": "Default"}"#;
+        fs::write(world_dir.join(WORLD_INFO_FILE), info).unwrap();
+
+        assert_eq!(prepare_world(&world_dir), Ok(MIGRATIONS.len()));
+        let migrated: WorldInfo = serde_json::from_str(&fs::read_to_string(world_dir.join(WORLD_INFO_FILE)).unwrap()).unwrap();
+        assert_eq!(migrated.save_format_version, SAVE_FORMAT_VERSION);
+        assert_eq!(migrated.component_versions, component_versions());
+        assert_eq!(migrated.seed, 7);
+        let bytes = fs::read(persistence::chunk_path(&world_dir, coord)).unwrap();
+        assert_eq!(bincode::deserialize::<Chunk>(&bytes).unwrap().get_block(0, 0, 0), BlockId::Stone);
+        assert!(persistence::load_chunk(&world_dir, coord).is_some());
+        // 备份保留迁移前的文件，再次打开不再迁移
+        let backups: Vec<_> = fs::read_dir(root.join(BACKUP_DIR)).unwrap().flatten().collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read(persistence::chunk_path(&backups[0].path(), coord)).unwrap(), old_chunk);
+        assert_eq!(prepare_world(&world_dir), Ok(0));
+
+        // 更新的版本保存的存档拒绝打开
+        let newer = WorldInfo { save_format_version: SAVE_FORMAT_VERSION + 1, ..migrated };
+        fs::write(world_dir.join(WORLD_INFO_FILE), serde_json::to_string(&newer).unwrap()).unwrap();
+        assert!(prepare_world(&world_dir).unwrap_err().contains("newer version"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
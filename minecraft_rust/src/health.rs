@@ -0,0 +1,327 @@
+use bevy::prelude::*;
+use crate::controller::{is_on_ground, ControlMode, FirstPersonController};
+use crate::game_state::{GameState, WorldManager, WorldState};
+use crate::hud::ScreenFade;
+use crate::loading::{spawn_area, SpawnPreloadConfig};
+use crate::inventory::{ItemStack, PlayerInventory};
+use crate::localization::LocalizationManager;
+use crate::message_log::MessageLog;
+use crate::net::client::NetClient;
+use crate::rendering::voxel_mesh::ChunkMesh;
+use crate::world::access::WorldView;
+use crate::world::chunk::{BlockId, Chunk};
+use crate::world::chunk_loader::{ChunkLoadQueue, EMERGENCY_PRIORITY};
+use crate::world::falling_block::spawn_dropped_item;
+use crate::world::storage::ChunkStorage;
+use crate::world::SpawnPoint;
+
+/// 玩家满生命值（半颗心为 1 点）
+pub const MAX_HEALTH: f32 = 20.0;
+
+/// 玩家和生物的生命值。玩家归零时在床或世界出生点复活并恢复满生命值，生物归零时消失
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self { current: MAX_HEALTH, max: MAX_HEALTH }
+    }
+}
+
+impl Health {
+    /// 扣除生命值，归零时返回 true
+    pub fn damage(&mut self, amount: f32) -> bool {
+        self.current = (self.current - amount.max(0.0)).max(0.0);
+        self.current <= 0.0
+    }
+}
+
+/// 玩家受到伤害：`knockback` 直接加到玩家速度上（格/秒）。
+/// 创造和旁观模式只有击退，不扣生命值（见 `GameMode::takes_damage`）
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerDamageEvent {
+    pub amount: f32,
+    pub knockback: Vec3,
+}
+
+/// 玩家以外的实体（生物）受到攻击，由实体自己的系统处理：扣除 `Health`，`knockback` 加到它的速度上
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    pub knockback: Vec3,
+}
+
+/// 受伤后的无敌时间（秒），期间的伤害只保留较大的部分（与原版相同）
+const INVULNERABLE_SECONDS: f32 = 0.5;
+/// 下落不超过这个高度（格）时没有摔落伤害，超出部分每格 1 点（与原版相同）
+const SAFE_FALL_DISTANCE: f32 = 3.0;
+
+/// 玩家死亡后正在等待复活点周围的区块加载。区块加载器会丢弃离玩家太远的区块，
+/// 所以玩家先被移到复活点并固定在那里，屏幕保持全黑，区块就绪后再检查床是否还在
+#[derive(Resource)]
+pub struct PendingRespawn {
+    bed: Option<IVec3>,  // 复活用的床；确认床已丢失后为 None，改在世界出生点复活
+    started_at: f32,
+}
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlayerDamageEvent>()
+           .add_event::<DamageEvent>()
+           .add_systems(Update, (
+               track_fall_damage.before(apply_player_damage),
+               apply_player_damage,
+               wait_for_respawn_area.after(apply_player_damage).run_if(resource_exists::<PendingRespawn>()),
+           ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 从 `distance` 格高处落地受到的伤害
+pub fn fall_damage(distance: f32) -> f32 {
+    (distance - SAFE_FALL_DISTANCE).floor().max(0.0)
+}
+
+/// 记录行走模式下离开地面后的最高点，落地时按下落高度发送摔落伤害；落入流体、切换到飞行或复活时不受伤，
+/// `fall_damage` 游戏规则关闭时也不受伤。伤害是否生效由 `apply_player_damage` 按游戏模式决定
+fn track_fall_damage(
+    player: Query<(&Transform, &FirstPersonController)>,
+    chunks: Query<&Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+    world_state: Res<WorldState>,
+    mut damage: EventWriter<PlayerDamageEvent>,
+    respawning: Option<Res<PendingRespawn>>,
+    mut fall_start: Local<Option<f32>>,
+) {
+    let Ok((transform, controller)) = player.get_single() else { return };
+    let position = transform.translation;
+    // 复活时玩家被移到复活点，之前的下落不再计算
+    if controller.mode != ControlMode::Walking || respawning.is_some() || !world_state.rule_enabled("fall_damage") {
+        *fall_start = None;
+        return;
+    }
+    let player_height = if controller.is_sneaking { 1.5 } else { 1.8 };
+    let world = WorldView::new(&chunk_storage, &chunks);
+    if !is_on_ground(position, player_height, &world) {
+        let highest = fall_start.get_or_insert(position.y);
+        *highest = highest.max(position.y);
+        return;
+    }
+    let Some(start) = fall_start.take() else { return };
+    let in_fluid = world.block(position.floor().as_ivec3()).is_some_and(|block| block.is_fluid());
+    let amount = fall_damage(start - position.y);
+    if amount > 0.0 && !in_fluid {
+        damage.send(PlayerDamageEvent { amount, knockback: Vec3::ZERO });
+    }
+}
+
+fn apply_player_damage(
+    mut commands: Commands,
+    mut events: EventReader<PlayerDamageEvent>,
+    mut player: Query<(&Transform, &mut FirstPersonController, &mut Health, &mut PlayerInventory)>,
+    world_manager: Res<WorldManager>,
+    world_state: Res<WorldState>,
+    respawning: Option<Res<PendingRespawn>>,
+    net: Option<Res<NetClient>>,
+    time: Res<Time>,
+    mut last_hurt: Local<Option<(f32, f32)>>,
+) {
+    // 等待复活期间不受伤害
+    if respawning.is_some() {
+        events.clear();
+        return;
+    }
+    let Ok((transform, mut controller, mut health, mut inventory)) = player.get_single_mut() else { return };
+    let takes_damage = world_manager.current_game_mode().takes_damage();
+    let now = time.elapsed_seconds();
+    for event in events.read() {
+        controller.velocity += event.knockback;
+        if !takes_damage {
+            continue;
+        }
+        // 无敌时间内只扣除比上一次伤害多出的部分
+        let amount = match *last_hurt {
+            Some((at, previous)) if now - at < INVULNERABLE_SECONDS => {
+                if event.amount <= previous {
+                    continue;
+                }
+                event.amount - previous
+            }
+            _ => event.amount,
+        };
+        *last_hurt = Some((now, event.amount));
+        if health.damage(amount) {
+            controller.velocity = Vec3::ZERO;
+            health.current = health.max;
+            *last_hurt = None;
+            for stack in death_drops(&mut inventory, &world_state, net.is_some_and(|net| net.is_remote())) {
+                spawn_dropped_item(&mut commands, stack, transform.translation + Vec3::Y);
+            }
+            commands.insert_resource(PendingRespawn { bed: world_state.player_spawn, started_at: now });
+            break;
+        }
+    }
+}
+
+/// 死亡时掉落的物品：`keep_inventory` 游戏规则开启时什么也不掉，否则物品栏清空。
+/// 掉落物只存在于本地，联机时其他玩家看不到也捡不到，所以联机时保留物品栏
+fn death_drops(inventory: &mut PlayerInventory, world_state: &WorldState, multiplayer: bool) -> Vec<ItemStack> {
+    if multiplayer || world_state.rule_enabled("keep_inventory") {
+        Vec::new()
+    } else {
+        inventory.take_all()
+    }
+}
+
+/// 等待区块加载时的位置：床的上方，或世界出生点上方 3 格（没有世界出生点时使用进入世界时的出生位置）。
+/// 在床边复活的最终位置由 `bed_standing_spot` 在区块加载后确定
+fn respawn_target(bed: Option<IVec3>, world_state: &WorldState, spawn_point: Option<&SpawnPoint>) -> Option<Vec3> {
+    match bed {
+        Some(bed) => Some(bed.as_vec3() + Vec3::new(0.5, 1.0, 0.5)),
+        None => world_state.spawn_point.map(|spawn| spawn.as_vec3() + Vec3::Y * 3.0).or(spawn_point.map(|spawn| spawn.0)),
+    }
+}
+
+/// 床边可以站立的位置：脚下是实心方块，脚和头所在的两格都不是。先试床的正上方，再试床周围一圈
+/// 与床同高和高一格的位置，都不行时床视为被阻挡
+fn bed_standing_spot(bed: IVec3, block_at: impl Fn(IVec3) -> BlockId) -> Option<IVec3> {
+    const RING: [(i32, i32); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+    std::iter::once(bed + IVec3::Y)
+        .chain([0, 1].into_iter().flat_map(|dy| RING.map(|(dx, dz)| bed + IVec3::new(dx, dy, dz))))
+        .find(|&pos| {
+            block_at(pos - IVec3::Y).is_solid() && !block_at(pos).is_solid() && !block_at(pos + IVec3::Y).is_solid()
+        })
+}
+
+/// 把玩家固定在复活位置并以最高优先级加载周围的区块，全部就绪（或超时）后检查床是否还在：
+/// 床被破坏或被阻挡时清除重生点，改为等待世界出生点周围的区块
+fn wait_for_respawn_area(
+    mut commands: Commands,
+    mut pending: ResMut<PendingRespawn>,
+    mut player: Query<(&mut Transform, &mut FirstPersonController)>,
+    mut world_state: ResMut<WorldState>,
+    spawn_point: Option<Res<SpawnPoint>>,
+    config: Res<SpawnPreloadConfig>,
+    mut load_queue: ResMut<ChunkLoadQueue>,
+    chunk_storage: Res<ChunkStorage>,
+    chunks: Query<&Chunk>,
+    meshed: Query<(), With<ChunkMesh>>,
+    mut fade: ResMut<ScreenFade>,
+    localization: Res<LocalizationManager>,
+    mut message_log: ResMut<MessageLog>,
+    time: Res<Time>,
+) {
+    let Ok((mut transform, mut controller)) = player.get_single_mut() else { return };
+    let Some(target) = respawn_target(pending.bed, &world_state, spawn_point.as_deref()) else {
+        commands.remove_resource::<PendingRespawn>();
+        return;
+    };
+    transform.translation = target;
+    controller.velocity = Vec3::ZERO;
+    if fade.0 != 1.0 {
+        fade.0 = 1.0;
+    }
+
+    let center = (target / 32.0).floor().as_ivec3();
+    let mut ready = true;
+    for coord in spawn_area(center, &config) {
+        match chunk_storage.get(&coord) {
+            Some(entity) if meshed.contains(entity) => {}
+            Some(_) => ready = false,
+            None => {
+                ready = false;
+                load_queue.push(coord, EMERGENCY_PRIORITY - (coord - center).as_vec3().length());
+            }
+        }
+    }
+    let now = time.elapsed_seconds();
+    if !ready && now - pending.started_at < config.timeout_secs {
+        return;
+    }
+
+    if let Some(bed) = pending.bed {
+        let world = WorldView::new(&chunk_storage, &chunks);
+        let spot = (world.get_block(bed) == BlockId::Bed)
+            .then(|| bed_standing_spot(bed, |pos| world.get_block(pos)))
+            .flatten();
+        let Some(spot) = spot else {
+            if world_state.player_spawn == Some(bed) {
+                world_state.player_spawn = None;
+            }
+            message_log.info(localization.get("game.bed.missing"));
+            pending.bed = None;
+            pending.started_at = now;
+            return;
+        };
+        transform.translation = spot.as_vec3() + Vec3::new(0.5, 0.0, 0.5);
+    }
+    let died = if pending.bed.is_some() { "game.health.died_at_bed" } else { "game.health.died" };
+    message_log.info(localization.get(died));
+    info!("Respawned at {:?}", transform.translation);
+    commands.remove_resource::<PendingRespawn>();
+    fade.0 = 0.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::ItemType;
+
+    #[test]
+    fn falls_up_to_three_blocks_are_safe_and_each_further_block_costs_one_point() {
+        assert_eq!(fall_damage(0.0), 0.0);
+        assert_eq!(fall_damage(SAFE_FALL_DISTANCE), 0.0);
+        // 不足一整格的部分不计
+        assert_eq!(fall_damage(SAFE_FALL_DISTANCE + 0.9), 0.0);
+        assert_eq!(fall_damage(4.0), 1.0);
+        assert_eq!(fall_damage(10.5), 7.0);
+        // 从 23 格高处落下正好失去全部 20 点生命
+        assert_eq!(fall_damage(23.0), MAX_HEALTH);
+    }
+
+    #[test]
+    fn keep_inventory_rule_decides_whether_death_drops_items() {
+        let mut world_state = WorldState::default();
+        let mut inventory = PlayerInventory::default();
+        inventory.hotbar[2] = ItemStack::new(ItemType::Block(BlockId::Dirt), 5);
+        inventory.main[10] = ItemStack::new(ItemType::Block(BlockId::Stone), 64);
+
+        world_state.set_known_rule("keep_inventory", "true").unwrap();
+        assert!(death_drops(&mut inventory, &world_state, false).is_empty());
+        assert_eq!(inventory.hotbar[2].count, 5);
+
+        // 联机时不管游戏规则都保留物品栏
+        world_state.set_known_rule("keep_inventory", "false").unwrap();
+        assert!(death_drops(&mut inventory, &world_state, true).is_empty());
+        assert_eq!(inventory.hotbar[2].count, 5);
+
+        let drops = death_drops(&mut inventory, &world_state, false);
+        assert_eq!(drops.iter().map(|stack| stack.count).sum::<u32>(), 69);
+        assert!(inventory.hotbar.iter().chain(inventory.main.iter()).all(ItemStack::is_empty));
+    }
+
+    #[test]
+    fn bed_respawn_needs_two_blocks_of_headroom() {
+        // 地面在 y = 0，床在 (0, 1, 0)
+        let bed = IVec3::new(0, 1, 0);
+        let open = |pos: IVec3| match pos {
+            pos if pos == bed => BlockId::Bed,
+            pos if pos.y <= 0 => BlockId::Stone,
+            _ => BlockId::Air,
+        };
+        assert_eq!(bed_standing_spot(bed, open), Some(bed + IVec3::Y));
+
+        // 床上方第二格被挡住时站到床边
+        let low_ceiling = |pos: IVec3| if pos == bed + IVec3::new(0, 2, 0) { BlockId::Stone } else { open(pos) };
+        assert_eq!(bed_standing_spot(bed, low_ceiling), Some(bed + IVec3::X));
+
+        // 周围只有一格高的空间时床被阻挡
+        let enclosed = |pos: IVec3| if pos.y >= 3 || (pos.y == 2 && pos != bed + IVec3::Y) { BlockId::Stone } else { open(pos) };
+        assert_eq!(bed_standing_spot(bed, enclosed), None);
+    }
+}
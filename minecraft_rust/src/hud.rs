@@ -1,7 +1,8 @@
 use bevy::prelude::*;
-use crate::inventory::{PlayerInventory, ItemType};
-use crate::world::chunk::BlockId;
-use crate::game_state::GameState;
+use crate::inventory::PlayerInventory;
+use crate::game_state::{GameState, WorldManager};
+use crate::health::Health;
+use crate::controller::FirstPersonController;
 use crate::ui_strings::UiStringManager;
 
 /// HUD根节点标记
@@ -24,13 +25,103 @@ pub struct ItemCountText {
     pub slot_index: usize,
 }
 
+/// 工具耐久条标记，显示在快捷栏槽位底部，只有受损的工具才显示
+#[derive(Component)]
+pub struct DurabilityBar {
+    pub slot_index: usize,
+}
+
+/// 生命值行标记，创造和旁观模式下隐藏
+#[derive(Component)]
+pub struct HealthBar;
+
+/// 生命值行中的一颗心，每颗代表 2 点生命值
+#[derive(Component)]
+pub struct HeartIcon {
+    pub index: usize,
+}
+
+/// 切换快捷栏槽位时显示在快捷栏上方的物品名称，整个 HUD 只有一个，每次切换重新计时
+#[derive(Component)]
+pub struct SelectedItemName {
+    /// 上次看到的选中槽位，`None` 表示还没有看到过（进入世界时不显示名称）
+    slot: Option<usize>,
+    timer: Timer,
+}
+
+/// 物品名称显示的总时长（秒），最后 `ITEM_NAME_FADE_SECONDS` 秒淡出
+const ITEM_NAME_SECONDS: f32 = 2.0;
+const ITEM_NAME_FADE_SECONDS: f32 = 0.5;
+
+/// 是否隐藏整个 HUD（准星、快捷栏、小地图和 egui 窗口），例如截图时
+#[derive(Resource, Default)]
+pub struct HudVisibility {
+    pub hidden: bool,
+}
+
+/// 覆盖整个屏幕的黑色遮罩的不透明度（0.0 ~ 1.0），睡觉和复活时淡入淡出
+#[derive(Resource, Default)]
+pub struct ScreenFade(pub f32);
+
+/// 黑色遮罩节点标记
+#[derive(Component)]
+struct ScreenFadeOverlay;
+
+/// 用于绘制 HUD 元素的系统的运行条件
+pub fn hud_visible(visibility: Res<HudVisibility>) -> bool {
+    !visibility.hidden
+}
+
 /// HUD插件
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::InGame), setup_hud)
-           .add_systems(Update, (update_hotbar_ui, update_item_count_text).run_if(in_state(GameState::InGame)));
+        app.init_resource::<HudVisibility>()
+           .init_resource::<ScreenFade>()
+           .add_systems(Startup, setup_screen_fade)
+           .add_systems(OnExit(GameState::Loading), setup_hud)
+           .add_systems(Update, (update_hotbar_ui, update_item_count_text, update_durability_bars, update_health_bar, update_selected_item_name).run_if(in_state(GameState::InGame)))
+           .add_systems(Update, apply_hud_visibility.run_if(resource_changed::<HudVisibility>()))
+           .add_systems(Update, update_screen_fade.run_if(resource_changed::<ScreenFade>()));
+    }
+}
+
+/// 同步所有顶层 UI 节点的可见性
+fn apply_hud_visibility(
+    visibility: Res<HudVisibility>,
+    mut roots: Query<&mut Visibility, (With<Node>, Without<Parent>)>,
+) {
+    let value = if visibility.hidden { Visibility::Hidden } else { Visibility::Inherited };
+    for mut root in roots.iter_mut() {
+        *root = value;
+    }
+}
+
+/// 遮罩在所有 UI 之上，平时不显示
+fn setup_screen_fade(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+        ScreenFadeOverlay,
+    ));
+}
+
+fn update_screen_fade(fade: Res<ScreenFade>, mut overlay: Query<(&mut Style, &mut BackgroundColor), With<ScreenFadeOverlay>>) {
+    let alpha = fade.0.clamp(0.0, 1.0);
+    for (mut style, mut color) in overlay.iter_mut() {
+        style.display = if alpha > 0.0 { Display::Flex } else { Display::None };
+        color.0 = Color::rgba(0.0, 0.0, 0.0, alpha);
     }
 }
 
@@ -74,6 +165,66 @@ fn setup_hud(
 
     commands.entity(hud_root).push_children(&[hotbar_container]);
 
+    // 快捷栏上方的生命值行，10 颗心
+    let health_bar = commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.0),
+                bottom: Val::Px(44.0),
+                ..default()
+            },
+            ..default()
+        },
+        HealthBar,
+    )).id();
+    commands.entity(hotbar_container).push_children(&[health_bar]);
+    for i in 0..10 {
+        let heart = commands.spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(12.0),
+                    height: Val::Px(12.0),
+                    margin: UiRect::right(Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: Color::RED.into(),
+                ..default()
+            },
+            HeartIcon { index: i },
+        )).id();
+        commands.entity(health_bar).push_children(&[heart]);
+    }
+
+    // 生命值行上方居中的物品名称
+    let item_name_row = commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            bottom: Val::Px(62.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        ..default()
+    }).id();
+    let item_name = commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: default(),
+                font_size: 18.0,
+                color: Color::WHITE,
+            },
+        ),
+        SelectedItemName {
+            slot: None,
+            timer: Timer::from_seconds(ITEM_NAME_SECONDS, TimerMode::Once),
+        },
+    )).id();
+    commands.entity(item_name_row).push_children(&[item_name]);
+    commands.entity(hotbar_container).push_children(&[item_name_row]);
+
     // 创建9个快捷栏槽位
     for i in 0..9 {
         let slot = commands.spawn((
@@ -114,8 +265,26 @@ fn setup_hud(
             ItemCountText { slot_index: i },
         )).id();
 
+        // 工具耐久条
+        let durability_bar = commands.spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(2.0),
+                    bottom: Val::Px(0.0),
+                    width: Val::Px(32.0),
+                    height: Val::Px(3.0),
+                    ..default()
+                },
+                background_color: Color::GREEN.into(),
+                ..default()
+            },
+            DurabilityBar { slot_index: i },
+        )).id();
+
         commands.entity(hotbar_container).push_children(&[slot]);
-        commands.entity(slot).push_children(&[count_text]);
+        commands.entity(slot).push_children(&[count_text, durability_bar]);
     }
 }
 
@@ -147,21 +316,7 @@ fn update_item_count_text(
                 text.sections[0].value = "".to_string();
             } else {
                 // 显示物品类型和数量
-                let item_key = match item.item_type {
-                    ItemType::Block(BlockId::Grass) => "grass_block",
-                    ItemType::Block(BlockId::Dirt) => "dirt",
-                    ItemType::Block(BlockId::Stone) => "stone",
-                    ItemType::Block(BlockId::Bedrock) => "bedrock",
-                    ItemType::Block(BlockId::Air) => "air",
-                    ItemType::Tool(tool_type) => match tool_type {
-                        crate::inventory::ToolType::WoodenPickaxe => "wooden_pickaxe",
-                        crate::inventory::ToolType::StonePickaxe => "stone_pickaxe",
-                        crate::inventory::ToolType::IronPickaxe => "iron_pickaxe",
-                        crate::inventory::ToolType::DiamondPickaxe => "diamond_pickaxe",
-                    },
-                    ItemType::Empty => "",
-                };
-                let item_name = ui_strings.get_item_name(item_key);
+                let item_name = ui_strings.get_item_name(item.item_type.name_key());
                 
                 if item.count > 1 {
                     text.sections[0].value = format!("{}\n{}", item_name, item.count);
@@ -171,4 +326,82 @@ fn update_item_count_text(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// 耐久条长度与剩余耐久成比例，颜色从绿色渐变到红色
+fn update_durability_bars(
+    inventory_query: Query<&PlayerInventory, Changed<PlayerInventory>>,
+    mut bar_query: Query<(&DurabilityBar, &mut Style, &mut BackgroundColor)>,
+) {
+    let Ok(inventory) = inventory_query.get_single() else { return };
+    for (bar, mut style, mut color) in bar_query.iter_mut() {
+        match inventory.hotbar[bar.slot_index].durability() {
+            Some((remaining, max)) if remaining < max => {
+                let fraction = remaining as f32 / max as f32;
+                style.display = Display::Flex;
+                style.width = Val::Px(32.0 * fraction);
+                *color = Color::rgb(1.0 - fraction, fraction, 0.0).into();
+            }
+            _ => style.display = Display::None,
+        }
+    }
+}
+
+/// 满的心为红色，半颗心为暗红色，空的为灰色；创造和旁观模式下不显示生命值
+fn update_health_bar(
+    health_query: Query<&Health, With<FirstPersonController>>,
+    world_manager: Res<WorldManager>,
+    mut bar_query: Query<&mut Style, With<HealthBar>>,
+    mut heart_query: Query<(&HeartIcon, &mut BackgroundColor)>,
+) {
+    let Ok(health) = health_query.get_single() else { return };
+    let shown = world_manager.current_game_mode().takes_damage();
+    for mut style in bar_query.iter_mut() {
+        style.display = if shown { Display::Flex } else { Display::None };
+    }
+    for (heart, mut color) in heart_query.iter_mut() {
+        let points = health.current - heart.index as f32 * 2.0;
+        *color = if points >= 2.0 {
+            Color::RED
+        } else if points >= 1.0 {
+            Color::rgb(0.5, 0.0, 0.0)
+        } else {
+            Color::rgba(0.3, 0.3, 0.3, 0.8)
+        }.into();
+    }
+}
+
+/// 选中槽位变化时显示新物品的名称（空槽位不显示），然后淡出
+fn update_selected_item_name(
+    time: Res<Time>,
+    inventory_query: Query<&PlayerInventory>,
+    ui_strings: Res<UiStringManager>,
+    mut label_query: Query<(&mut SelectedItemName, &mut Text)>,
+) {
+    let Ok(inventory) = inventory_query.get_single() else { return };
+    for (mut label, mut text) in label_query.iter_mut() {
+        match label.slot {
+            Some(slot) if slot == inventory.selected_slot => {
+                label.timer.tick(time.delta());
+            }
+            previous => {
+                label.slot = Some(inventory.selected_slot);
+                if previous.is_some() {
+                    let item = inventory.get_selected_item();
+                    text.sections[0].value = if item.is_empty() {
+                        String::new()
+                    } else {
+                        ui_strings.get_item_name(item.item_type.name_key()).to_string()
+                    };
+                    label.timer.reset();
+                }
+            }
+        }
+
+        let alpha = (label.timer.remaining_secs() / ITEM_NAME_FADE_SECONDS).min(1.0);
+        // 只在透明度变化时修改，避免每帧重新排版文字
+        if text.sections[0].style.color.a() != alpha {
+            text.sections[0].style.color.set_a(alpha);
+        }
+    }
+}
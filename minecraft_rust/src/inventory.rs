@@ -1,16 +1,23 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use crate::world::chunk::BlockId;
-use crate::game_state::GameState;
+use crate::game_state::{GameState, WorldManager};
+use crate::world::falling_block::DroppedItem;
+use crate::localization::LocalizationManager;
+use crate::notifications::Notifications;
 
 /// 物品栏槽位
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ItemStack {
     pub item_type: ItemType,
     pub count: u32,
+    /// 工具的剩余耐久，其他物品为 None。旧存档中的工具没有这一项，按满耐久处理
+    #[serde(default)]
+    pub durability: Option<u32>,
 }
 
 /// 物品类型
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ItemType {
     Block(BlockId),
     Tool(ToolType),
@@ -18,7 +25,7 @@ pub enum ItemType {
 }
 
 /// 工具类型
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ToolType {
     WoodenPickaxe,
     StonePickaxe,
@@ -26,15 +33,106 @@ pub enum ToolType {
     DiamondPickaxe,
 }
 
+impl ToolType {
+    pub const ALL: [ToolType; 4] = [ToolType::WoodenPickaxe, ToolType::StonePickaxe, ToolType::IronPickaxe, ToolType::DiamondPickaxe];
+
+    /// 满耐久时可以破坏的方块数
+    pub fn max_durability(self) -> u32 {
+        match self {
+            ToolType::WoodenPickaxe => 59,
+            ToolType::StonePickaxe => 131,
+            ToolType::IronPickaxe => 250,
+            ToolType::DiamondPickaxe => 1561,
+        }
+    }
+
+    /// 挖掘适用方块时的速度倍数（空手为 1）
+    pub fn speed_multiplier(self) -> f32 {
+        match self {
+            ToolType::WoodenPickaxe => 2.0,
+            ToolType::StonePickaxe => 4.0,
+            ToolType::IronPickaxe => 6.0,
+            ToolType::DiamondPickaxe => 8.0,
+        }
+    }
+
+    /// 用这个工具攻击生物造成的伤害（空手为 1）
+    pub fn attack_damage(self) -> f32 {
+        match self {
+            ToolType::WoodenPickaxe => 2.0,
+            ToolType::StonePickaxe => 3.0,
+            ToolType::IronPickaxe => 4.0,
+            ToolType::DiamondPickaxe => 5.0,
+        }
+    }
+
+    /// 材质等级，决定能否采集要求更高等级的方块（见 `mining::required_tier`）
+    pub fn tier(self) -> u8 {
+        match self {
+            ToolType::WoodenPickaxe => 1,
+            ToolType::StonePickaxe => 2,
+            ToolType::IronPickaxe => 3,
+            ToolType::DiamondPickaxe => 4,
+        }
+    }
+}
+
+impl ItemType {
+    /// 物品名称在 UI 字符串中的键
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            ItemType::Block(BlockId::Grass) => "grass_block",
+            ItemType::Block(BlockId::Dirt) => "dirt",
+            ItemType::Block(BlockId::Stone) => "stone",
+            ItemType::Block(BlockId::Bedrock) => "bedrock",
+            ItemType::Block(BlockId::Water) => "water",
+            ItemType::Block(BlockId::Lava) => "lava",
+            ItemType::Block(BlockId::Obsidian) => "obsidian",
+            ItemType::Block(BlockId::Sand) => "sand",
+            ItemType::Block(BlockId::Gravel) => "gravel",
+            ItemType::Block(BlockId::Torch) => "torch",
+            ItemType::Block(BlockId::CraftingTable) => "crafting_table",
+            ItemType::Block(BlockId::Chest) => "chest",
+            ItemType::Block(BlockId::Tnt) => "tnt",
+            ItemType::Block(BlockId::Bed) => "bed",
+            ItemType::Block(BlockId::Log) => "log",
+            ItemType::Block(BlockId::Leaves) => "leaves",
+            ItemType::Block(BlockId::Sapling) => "sapling",
+            ItemType::Block(BlockId::Air) => "air",
+            ItemType::Tool(tool_type) => match tool_type {
+                ToolType::WoodenPickaxe => "wooden_pickaxe",
+                ToolType::StonePickaxe => "stone_pickaxe",
+                ToolType::IronPickaxe => "iron_pickaxe",
+                ToolType::DiamondPickaxe => "diamond_pickaxe",
+            },
+            ItemType::Empty => "",
+        }
+    }
+
+    /// 按物品 id（与 `name_key` 相同，例如 "grass_block"、"stone_pickaxe"）查找物品，脚本中的配方使用它
+    pub fn from_id(id: &str) -> Option<ItemType> {
+        (1..BlockId::COUNT as u8)
+            .map(|i| ItemType::Block(BlockId::from_u8(i)))
+            .chain(ToolType::ALL.into_iter().map(ItemType::Tool))
+            .find(|item| item.name_key() == id)
+    }
+}
+
 impl ItemStack {
+    /// 新的物品堆，工具为满耐久
     pub fn new(item_type: ItemType, count: u32) -> Self {
-        Self { item_type, count }
+        let durability = match item_type {
+            ItemType::Tool(tool) => Some(tool.max_durability()),
+            _ => None,
+        };
+        Self { item_type, count, durability }
     }
 
     pub fn empty() -> Self {
         Self {
             item_type: ItemType::Empty,
             count: 0,
+            durability: None,
         }
     }
 
@@ -46,6 +144,24 @@ impl ItemStack {
         self.item_type == other.item_type && !self.is_empty() && !other.is_empty()
     }
 
+    /// 工具的 (剩余耐久, 最大耐久)，不是工具时返回 None
+    pub fn durability(&self) -> Option<(u32, u32)> {
+        let ItemType::Tool(tool) = self.item_type else { return None };
+        let max = tool.max_durability();
+        Some((self.durability.unwrap_or(max).min(max), max))
+    }
+
+    /// 工具消耗一点耐久，耐久耗尽时清空槽位并返回 true
+    pub fn damage_tool(&mut self) -> bool {
+        let Some((remaining, _)) = self.durability() else { return false };
+        if remaining <= 1 {
+            *self = ItemStack::empty();
+            return true;
+        }
+        self.durability = Some(remaining - 1);
+        false
+    }
+
     pub fn max_stack_size(&self) -> u32 {
         match self.item_type {
             ItemType::Block(_) => 64,
@@ -56,7 +172,7 @@ impl ItemStack {
 }
 
 /// 玩家物品栏组件
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct PlayerInventory {
     pub hotbar: [ItemStack; 9],     // 快捷栏
     pub main: [ItemStack; 27],      // 主物品栏
@@ -83,6 +199,18 @@ impl PlayerInventory {
         inventory.hotbar[2] = ItemStack::new(ItemType::Block(BlockId::Stone), 64);
         inventory.hotbar[3] = ItemStack::new(ItemType::Block(BlockId::Bedrock), 64);
         inventory.hotbar[4] = ItemStack::new(ItemType::Tool(ToolType::DiamondPickaxe), 1);
+        inventory.hotbar[5] = ItemStack::new(ItemType::Block(BlockId::Water), 64);
+        inventory.hotbar[6] = ItemStack::new(ItemType::Block(BlockId::Lava), 64);
+        inventory.hotbar[7] = ItemStack::new(ItemType::Block(BlockId::Obsidian), 64);
+        inventory.hotbar[8] = ItemStack::new(ItemType::Block(BlockId::Sand), 64);
+        inventory.main[0] = ItemStack::new(ItemType::Block(BlockId::Gravel), 64);
+        inventory.main[1] = ItemStack::new(ItemType::Block(BlockId::Torch), 64);
+        inventory.main[2] = ItemStack::new(ItemType::Block(BlockId::CraftingTable), 1);
+        inventory.main[3] = ItemStack::new(ItemType::Block(BlockId::Chest), 1);
+        inventory.main[4] = ItemStack::new(ItemType::Block(BlockId::Tnt), 16);
+        inventory.main[5] = ItemStack::new(ItemType::Block(BlockId::Bed), 1);
+        inventory.main[6] = ItemStack::new(ItemType::Block(BlockId::Log), 64);
+        inventory.main[7] = ItemStack::new(ItemType::Block(BlockId::Sapling), 16);
         
         inventory
     }
@@ -135,6 +263,27 @@ impl PlayerInventory {
         remaining
     }
 
+    /// 放置方块后消耗一个物品：优先从选中的槽位取，联机时确认到达前玩家可能已换了槽位
+    pub fn consume_placed(&mut self, item_type: ItemType) {
+        let selected = self.get_selected_item_mut();
+        if selected.item_type == item_type && !selected.is_empty() {
+            selected.count -= 1;
+            if selected.count == 0 {
+                *selected = ItemStack::empty();
+            }
+        } else {
+            self.remove_item(item_type, 1);
+        }
+    }
+
+    /// 取出所有物品，物品栏清空（死亡时掉落）
+    pub fn take_all(&mut self) -> Vec<ItemStack> {
+        self.hotbar.iter_mut().chain(self.main.iter_mut())
+            .filter(|slot| !slot.is_empty())
+            .map(|slot| std::mem::replace(slot, ItemStack::empty()))
+            .collect()
+    }
+
     pub fn remove_item(&mut self, item_type: ItemType, count: u32) -> u32 {
         let mut removed = 0;
         let mut remaining = count;
@@ -183,17 +332,54 @@ pub fn inventory_input_system(
 
             if keyboard.just_pressed(key) {
                 inventory.select_slot(i);
-                println!("选择快捷栏槽位: {}", i + 1);
+                debug!("Selected hotbar slot {}", i + 1);
             }
         }
     }
 }
 
+/// 拾取掉落物的距离（从玩家碰撞箱中心算起）
+const PICKUP_DISTANCE: f32 = 1.5;
+
+/// 玩家靠近掉落物时放入物品栏，物品栏装不下的部分留在地上，并提示一次物品栏已满；旁观模式不拾取
+pub fn pickup_dropped_items(
+    mut commands: Commands,
+    mut player_query: Query<(&Transform, &mut PlayerInventory)>,
+    mut items: Query<(Entity, &Transform, &mut DroppedItem)>,
+    world_manager: Res<WorldManager>,
+    mut notifications: ResMut<Notifications>,
+    localization: Res<LocalizationManager>,
+    mut was_full: Local<bool>,
+) {
+    if world_manager.current_game_mode().is_spectator() {
+        return;
+    }
+    let mut full = false;
+    for (player_transform, mut inventory) in player_query.iter_mut() {
+        for (entity, transform, mut item) in items.iter_mut() {
+            if transform.translation.distance(player_transform.translation) > PICKUP_DISTANCE {
+                continue;
+            }
+            item.stack = inventory.add_item(item.stack);
+            if item.stack.is_empty() {
+                commands.entity(entity).despawn_recursive();
+            } else {
+                full = true;
+            }
+        }
+    }
+    // 站在拾取不了的物品旁边时只提示一次
+    if full && !*was_full {
+        notifications.warn(localization.get("game.inventory.full"));
+    }
+    *was_full = full;
+}
+
 /// 物品栏插件
 pub struct InventoryPlugin;
 
 impl Plugin for InventoryPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, inventory_input_system.run_if(in_state(GameState::InGame)));
+        app.add_systems(Update, (inventory_input_system, pickup_dropped_items).run_if(in_state(GameState::InGame)));
     }
 }
\ No newline at end of file
@@ -0,0 +1,578 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+use bevy::input::InputSystem;
+use bevy_egui::{egui, EguiContexts, EguiSet};
+use crate::block_registry::BlockRegistry;
+use crate::command::block_game_input;
+use crate::crafting::{CraftingGrid, RecipeRegistry};
+use crate::debug_overlay::DebugOverlay;
+use crate::game_state::{in_creative_mode, GameState};
+use crate::hud::hud_visible;
+use crate::inventory::{ItemStack, ItemType, PlayerInventory};
+use crate::localization::LocalizationManager;
+use crate::ui_strings::UiStringManager;
+use crate::world::access::WorldAccess;
+use crate::world::block_entity::BlockEntity;
+use crate::world::chunk::BlockId;
+use crate::world::events::BlockEntityRemovedEvent;
+use crate::world::falling_block::spawn_dropped_item;
+
+/// 物品格的边长（像素）
+const SLOT_SIZE: f32 = 52.0;
+/// 创造模式物品选择器：每行的格数、方块图标边长和列表的最大高度（像素），超出时滚动
+const PICKER_COLUMNS: usize = 6;
+const ICON_SIZE: f32 = 36.0;
+const PICKER_HEIGHT: f32 = 360.0;
+
+/// 物品栏界面：按 E 打开（2×2 合成格），右键工作台打开（3×3 合成格），右键箱子打开箱子的 27 格。
+/// 打开时释放鼠标，鼠标上拿着的物品保存在 `held` 中
+#[derive(Resource)]
+pub struct InventoryScreen {
+    pub open: bool,
+    pub crafting: CraftingGrid,
+    pub held: ItemStack,
+    /// 正在查看的箱子（世界坐标），箱子里的物品直接读写它的方块实体
+    pub chest: Option<IVec3>,
+    /// 创造模式物品选择器的搜索文本，关闭界面后保留
+    pub search: String,
+    /// 搜索框有焦点时游戏不接收按键，输入字母不会关闭界面或切换快捷栏
+    search_focused: bool,
+}
+
+impl Default for InventoryScreen {
+    fn default() -> Self {
+        Self {
+            open: false,
+            crafting: CraftingGrid::new(2),
+            held: ItemStack::empty(),
+            chest: None,
+            search: String::new(),
+            search_focused: false,
+        }
+    }
+}
+
+impl InventoryScreen {
+    /// 打开界面，`grid_size` 为合成格边长（物品栏 2，工作台 3）
+    pub fn open(&mut self, grid_size: usize) {
+        self.open = true;
+        self.crafting = CraftingGrid::new(grid_size);
+        self.chest = None;
+    }
+
+    /// 打开箱子界面，不显示合成格
+    pub fn open_chest(&mut self, pos: IVec3) {
+        self.open(2);
+        self.chest = Some(pos);
+    }
+}
+
+/// 物品栏界面打开时，暂停、缩放和命令行等按键系统不运行
+pub fn inventory_screen_open(screen: Res<InventoryScreen>) -> bool {
+    screen.open
+}
+
+pub struct InventoryScreenPlugin;
+
+impl Plugin for InventoryScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InventoryScreen>()
+           .init_resource::<CreativeIcons>()
+           .add_systems(PreUpdate, block_game_input
+               .after(InputSystem)
+               .after(EguiSet::ProcessInput)
+               .run_if(|screen: Res<InventoryScreen>| screen.search_focused))
+           .add_systems(Update, (
+               toggle_inventory_screen,
+               close_removed_chest.after(toggle_inventory_screen).run_if(inventory_screen_open),
+               inventory_screen_ui.after(close_removed_chest).run_if(inventory_screen_open).run_if(hud_visible),
+               load_creative_icons.run_if(inventory_screen_open).run_if(in_creative_mode),
+               creative_picker_ui
+                   .after(inventory_screen_ui)
+                   .after(load_creative_icons)
+                   .run_if(inventory_screen_open)
+                   .run_if(hud_visible)
+                   .run_if(in_creative_mode),
+           ).run_if(in_state(GameState::InGame)))
+           .add_systems(OnExit(GameState::InGame), close_on_exit);
+    }
+}
+
+/// E 切换物品栏界面，Esc 关闭
+fn toggle_inventory_screen(
+    mut commands: Commands,
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut screen: ResMut<InventoryScreen>,
+    mut player_query: Query<(&Transform, &mut PlayerInventory)>,
+) {
+    if screen.open && (keyboard.just_pressed(KeyCode::E) || keyboard.just_pressed(KeyCode::Escape)) {
+        close_screen(&mut commands, &mut screen, &mut player_query);
+        // 关闭界面的 Esc 不再打开暂停菜单
+        keyboard.clear_just_pressed(KeyCode::Escape);
+    } else if !screen.open && keyboard.just_pressed(KeyCode::E) {
+        screen.open(2);
+    }
+}
+
+/// 离开游戏状态时关闭界面，合成格里的物品同样放回物品栏
+fn close_on_exit(
+    mut commands: Commands,
+    mut screen: ResMut<InventoryScreen>,
+    mut player_query: Query<(&Transform, &mut PlayerInventory)>,
+) {
+    if screen.open {
+        close_screen(&mut commands, &mut screen, &mut player_query);
+    }
+}
+
+/// 正在查看的箱子被破坏时关闭界面
+fn close_removed_chest(
+    mut commands: Commands,
+    mut removed: EventReader<BlockEntityRemovedEvent>,
+    mut screen: ResMut<InventoryScreen>,
+    mut player_query: Query<(&Transform, &mut PlayerInventory)>,
+) {
+    if removed.read().any(|event| screen.chest == Some(event.pos)) {
+        close_screen(&mut commands, &mut screen, &mut player_query);
+    }
+}
+
+/// 关闭界面：合成格和鼠标上的物品放回物品栏，放不下的掉在玩家脚下
+fn close_screen(
+    commands: &mut Commands,
+    screen: &mut InventoryScreen,
+    player_query: &mut Query<(&Transform, &mut PlayerInventory)>,
+) {
+    screen.open = false;
+    screen.chest = None;
+    screen.search_focused = false;
+    let Ok((transform, mut inventory)) = player_query.get_single_mut() else { return };
+    let held = std::mem::replace(&mut screen.held, ItemStack::empty());
+    for stack in screen.crafting.take_all().into_iter().chain([held]) {
+        let leftover = inventory.add_item(stack);
+        if !leftover.is_empty() {
+            spawn_dropped_item(commands, leftover, transform.translation);
+        }
+    }
+}
+
+/// 物品名称和物品提示用到的文本和注册表
+struct ItemInfo<'a> {
+    ui_strings: &'a UiStringManager,
+    localization: &'a LocalizationManager,
+    registry: &'a BlockRegistry,
+    /// 调试信息覆盖层打开时在提示中显示物品 id
+    show_id: bool,
+    /// 鼠标上拿着物品时不显示提示，避免挡住拿着的物品
+    tooltips: bool,
+}
+
+impl ItemInfo<'_> {
+    fn name(&self, item_type: ItemType) -> &str {
+        self.ui_strings.get_item_name(item_type.name_key())
+    }
+}
+
+/// 物品提示的内容：名称，以及按需显示的物品 id、数量、工具耐久和方块脚本中的描述
+#[derive(Debug, PartialEq)]
+enum TooltipLine {
+    Name(String),
+    Id(&'static str),
+    Detail(String),
+    Description(String),
+}
+
+fn tooltip_lines(stack: &ItemStack, info: &ItemInfo) -> Vec<TooltipLine> {
+    let mut lines = vec![TooltipLine::Name(info.name(stack.item_type).to_string())];
+    if info.show_id {
+        lines.push(TooltipLine::Id(stack.item_type.name_key()));
+    }
+    if stack.count > 1 {
+        lines.push(TooltipLine::Detail(format!("{}: {}", info.localization.get("game.tooltip.count"), stack.count)));
+    }
+    if let Some((remaining, max)) = stack.durability() {
+        lines.push(TooltipLine::Detail(format!("{}: {} / {}", info.localization.get("game.tooltip.durability"), remaining, max)));
+    }
+    if let ItemType::Block(block) = stack.item_type {
+        if let Some(key) = info.registry.description_key(block) {
+            lines.push(TooltipLine::Description(info.localization.get(key).to_string()));
+        }
+    }
+    lines
+}
+
+/// 在物品格旁边显示物品提示。提示由 egui 放置，靠近屏幕边缘时自动移到能完整显示的一侧
+fn item_tooltip(response: egui::Response, stack: &ItemStack, info: &ItemInfo) -> egui::Response {
+    if !info.tooltips || stack.is_empty() {
+        return response;
+    }
+    response.on_hover_ui_at_pointer(|ui| {
+        for line in tooltip_lines(stack, info) {
+            match line {
+                TooltipLine::Name(name) => ui.label(egui::RichText::new(name).strong()),
+                TooltipLine::Id(id) => ui.label(egui::RichText::new(id).monospace().weak()),
+                TooltipLine::Detail(text) => ui.label(text),
+                TooltipLine::Description(text) => ui.label(egui::RichText::new(text).italics().weak()),
+            };
+        }
+    })
+}
+
+/// 物品格按钮：显示物品名称和数量，鼠标悬停时显示物品提示
+fn item_slot(ui: &mut egui::Ui, stack: &ItemStack, info: &ItemInfo) -> egui::Response {
+    let text = if stack.is_empty() {
+        String::new()
+    } else if stack.count > 1 {
+        format!("{}\n{}", info.name(stack.item_type), stack.count)
+    } else {
+        info.name(stack.item_type).to_string()
+    };
+    let response = ui.add_sized([SLOT_SIZE, SLOT_SIZE], egui::Button::new(egui::RichText::new(text).size(10.0)).wrap(true));
+    item_tooltip(response, stack, info)
+}
+
+/// 点击物品格：左键放下或交换鼠标上的物品（同种物品则尽量叠加），右键拿起一半或放下一个
+pub fn click_slot(slot: &mut ItemStack, held: &mut ItemStack, response: &egui::Response) {
+    if response.clicked() {
+        if held.can_stack_with(slot) {
+            let moved = held.count.min(slot.max_stack_size() - slot.count);
+            slot.count += moved;
+            held.count -= moved;
+            if held.count == 0 {
+                *held = ItemStack::empty();
+            }
+        } else {
+            std::mem::swap(slot, held);
+        }
+    } else if response.secondary_clicked() {
+        if held.is_empty() {
+            let taken = slot.count.div_ceil(2);
+            if taken > 0 {
+                *held = ItemStack { count: taken, ..*slot };
+                slot.count -= taken;
+                if slot.count == 0 {
+                    *slot = ItemStack::empty();
+                }
+            }
+        } else if slot.is_empty() || (held.can_stack_with(slot) && slot.count < slot.max_stack_size()) {
+            if slot.is_empty() {
+                *slot = ItemStack { count: 0, ..*held };
+            }
+            slot.count += 1;
+            held.count -= 1;
+            if held.count == 0 {
+                *held = ItemStack::empty();
+            }
+        }
+    }
+}
+
+/// 合成格（或箱子）、合成结果、主物品栏和快捷栏。点击结果格取出一次合成的产物，每个材料格消耗一个
+fn inventory_screen_ui(
+    mut contexts: EguiContexts,
+    mut screen: ResMut<InventoryScreen>,
+    mut inventory_query: Query<&mut PlayerInventory>,
+    recipes: Res<RecipeRegistry>,
+    localization: Res<LocalizationManager>,
+    mut world: WorldAccess,
+    // 物品名称，以及物品提示中的方块描述和物品 id
+    (ui_strings, registry, debug_overlay): (Res<UiStringManager>, Res<BlockRegistry>, Res<DebugOverlay>),
+) {
+    let Ok(mut inventory) = inventory_query.get_single_mut() else { return };
+    let screen = &mut *screen;
+    let info = ItemInfo {
+        ui_strings: &ui_strings,
+        localization: &localization,
+        registry: &registry,
+        show_id: debug_overlay.visible,
+        tooltips: screen.held.is_empty(),
+    };
+    // 选择器不显示时（箱子界面、切换了游戏模式）搜索框不再有焦点，由之后运行的选择器重新设置
+    screen.search_focused = false;
+    let mut chest = screen.chest.and_then(|pos| match world.get_block_entity(pos) {
+        Some(BlockEntity::Chest(slots)) => Some((pos, slots.clone())),
+        _ => None,
+    });
+    let mut chest_changed = false;
+    let title = match (&chest, screen.crafting.size) {
+        (Some(_), _) => "game.inventory.chest",
+        (None, 3) => "game.inventory.crafting_table",
+        (None, _) => "game.inventory.title",
+    };
+    let ctx = contexts.ctx_mut();
+
+    egui::Window::new(localization.get(title))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            if let Some((_, slots)) = &mut chest {
+                egui::Grid::new("chest").spacing([4.0, 4.0]).show(ui, |ui| {
+                    for (i, slot) in slots.iter_mut().enumerate() {
+                        let before = *slot;
+                        let response = item_slot(ui, slot, &info);
+                        click_slot(slot, &mut screen.held, &response);
+                        chest_changed |= *slot != before;
+                        if i % 9 == 8 {
+                            ui.end_row();
+                        }
+                    }
+                });
+                ui.separator();
+                show_inventory_slots(ui, &mut inventory, &mut screen.held, &info);
+                return;
+            }
+
+            ui.label(localization.get("game.inventory.crafting"));
+            ui.horizontal(|ui| {
+                egui::Grid::new("crafting_grid").spacing([4.0, 4.0]).show(ui, |ui| {
+                    let size = screen.crafting.size;
+                    for (i, slot) in screen.crafting.slots.iter_mut().enumerate() {
+                        let response = item_slot(ui, slot, &info);
+                        click_slot(slot, &mut screen.held, &response);
+                        if i % size == size - 1 {
+                            ui.end_row();
+                        }
+                    }
+                });
+                ui.label(egui::RichText::new("→").size(24.0));
+                let result = recipes.find(&screen.crafting).map_or(ItemStack::empty(), |recipe| recipe.result);
+                let response = item_slot(ui, &result, &info);
+                if response.clicked() && !result.is_empty() {
+                    if screen.held.is_empty() {
+                        screen.held = result;
+                        screen.crafting.consume();
+                    } else if screen.held.can_stack_with(&result) && screen.held.count + result.count <= result.max_stack_size() {
+                        screen.held.count += result.count;
+                        screen.crafting.consume();
+                    }
+                }
+            });
+
+            ui.separator();
+            show_inventory_slots(ui, &mut inventory, &mut screen.held, &info);
+        });
+
+    if let (Some((pos, slots)), true) = (chest, chest_changed) {
+        world.set_block_entity(pos, Some(BlockEntity::Chest(slots)));
+    }
+
+    // 鼠标上拿着的物品跟随指针显示
+    if !screen.held.is_empty() {
+        if let Some(pointer) = ctx.pointer_hover_pos() {
+            let text = format!("{} ×{}", info.name(screen.held.item_type), screen.held.count);
+            ctx.layer_painter(egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("held_item")))
+                .text(pointer + egui::vec2(12.0, 12.0), egui::Align2::LEFT_TOP, text, egui::FontId::proportional(14.0), egui::Color32::WHITE);
+        }
+    }
+}
+
+/// 主物品栏和快捷栏，每行 9 格
+fn show_inventory_slots(ui: &mut egui::Ui, inventory: &mut PlayerInventory, held: &mut ItemStack, info: &ItemInfo) {
+    for (name, row_slots) in [("main", &mut inventory.main[..]), ("hotbar", &mut inventory.hotbar[..])] {
+        egui::Grid::new(name).spacing([4.0, 4.0]).show(ui, |ui| {
+            for (i, slot) in row_slots.iter_mut().enumerate() {
+                let response = item_slot(ui, slot, info);
+                click_slot(slot, held, &response);
+                if i % 9 == 8 {
+                    ui.end_row();
+                }
+            }
+        });
+        ui.add_space(8.0);
+    }
+}
+
+/// 创造模式物品选择器的方块图标：贴图名称 → (图片, egui 纹理)，保留图片的强引用避免被卸载
+#[derive(Resource, Default)]
+struct CreativeIcons(HashMap<String, (Handle<Image>, egui::TextureId)>);
+
+/// 选择器中的一项：一种方块，以及映射到它的脚本方块 id 和第一个脚本定义的贴图
+#[derive(Debug, PartialEq)]
+struct CreativeBlock {
+    block: BlockId,
+    script_ids: Vec<String>,
+    texture: Option<String>,
+}
+
+/// 按方块编号排列所有注册的脚本方块，映射到同一种方块的脚本定义合并为一项
+fn creative_blocks(registry: &BlockRegistry) -> Vec<CreativeBlock> {
+    let mut definitions = registry.get_all_registered_blocks();
+    definitions.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut blocks: Vec<CreativeBlock> = Vec::new();
+    for definition in definitions {
+        let Some(block) = registry.get_block_id(&definition.id) else { continue };
+        match blocks.iter_mut().find(|entry| entry.block == block) {
+            Some(entry) => entry.script_ids.push(definition.id.clone()),
+            None => blocks.push(CreativeBlock {
+                block,
+                script_ids: vec![definition.id.clone()],
+                texture: definition.texture.clone(),
+            }),
+        }
+    }
+    blocks.sort_by_key(|entry| entry.block as u8);
+    blocks
+}
+
+/// 搜索文本（不区分大小写）出现在脚本 id、物品 id 或本地化名称中；空文本匹配所有方块
+fn matches_search(search: &str, entry: &CreativeBlock, name: &str) -> bool {
+    let search = search.trim().to_lowercase();
+    search.is_empty()
+        || entry.script_ids.iter().any(|id| id.contains(&search))
+        || ItemType::Block(entry.block).name_key().contains(&search)
+        || name.to_lowercase().contains(&search)
+}
+
+/// 为脚本方块的贴图（textures/block/<texture>.png）创建 egui 纹理，新注册的方块下一帧就有图标
+fn load_creative_icons(
+    mut contexts: EguiContexts,
+    registry: Res<BlockRegistry>,
+    asset_server: Res<AssetServer>,
+    mut icons: ResMut<CreativeIcons>,
+) {
+    for definition in registry.get_all_registered_blocks() {
+        let Some(texture) = &definition.texture else { continue };
+        if icons.0.contains_key(texture) {
+            continue;
+        }
+        let image: Handle<Image> = asset_server.load(format!("textures/block/{}.png", texture));
+        let texture_id = contexts.add_image(image.clone());
+        icons.0.insert(texture.clone(), (image, texture_id));
+    }
+}
+
+/// 创造模式物品选择器，显示在物品栏界面左侧（箱子界面除外）：列出 `BlockRegistry` 中的所有方块，
+/// 可按 id 或名称搜索。左键把一组方块放到鼠标上，右键放入选中的快捷栏槽位。
+/// 每帧从注册表读取，脚本注册的新方块立即出现
+fn creative_picker_ui(
+    mut contexts: EguiContexts,
+    mut screen: ResMut<InventoryScreen>,
+    mut inventory_query: Query<&mut PlayerInventory>,
+    registry: Res<BlockRegistry>,
+    (icons, debug_overlay): (Res<CreativeIcons>, Res<DebugOverlay>),
+    localization: Res<LocalizationManager>,
+    ui_strings: Res<UiStringManager>,
+) {
+    let Ok(mut inventory) = inventory_query.get_single_mut() else { return };
+    if screen.chest.is_some() {
+        return;
+    }
+    let screen = &mut *screen;
+    let info = ItemInfo {
+        ui_strings: &ui_strings,
+        localization: &localization,
+        registry: &registry,
+        show_id: debug_overlay.visible,
+        tooltips: screen.held.is_empty(),
+    };
+    egui::Window::new(localization.get("game.inventory.creative"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_CENTER, egui::vec2(16.0, 0.0))
+        .show(contexts.ctx_mut(), |ui| {
+            let search = egui::TextEdit::singleline(&mut screen.search)
+                .hint_text(localization.get("game.inventory.search"))
+                .desired_width(PICKER_COLUMNS as f32 * (SLOT_SIZE + 4.0));
+            screen.search_focused = ui.add(search).has_focus();
+
+            let blocks: Vec<(CreativeBlock, &str)> = creative_blocks(&registry)
+                .into_iter()
+                .map(|entry| {
+                    let name = info.name(ItemType::Block(entry.block));
+                    (entry, name)
+                })
+                .filter(|(entry, name)| matches_search(&screen.search, entry, name))
+                .collect();
+            if blocks.is_empty() {
+                ui.label(localization.get("game.inventory.no_results"));
+                return;
+            }
+
+            egui::ScrollArea::vertical().max_height(PICKER_HEIGHT).show(ui, |ui| {
+                egui::Grid::new("creative_blocks").spacing([4.0, 4.0]).show(ui, |ui| {
+                    for (i, (entry, name)) in blocks.iter().enumerate() {
+                        let icon = entry.texture.as_ref().and_then(|texture| icons.0.get(texture));
+                        let button = match icon {
+                            Some((_, texture_id)) => egui::Button::image((*texture_id, egui::vec2(ICON_SIZE, ICON_SIZE))),
+                            None => egui::Button::new(egui::RichText::new(*name).size(10.0)).wrap(true),
+                        };
+                        let mut stack = ItemStack::new(ItemType::Block(entry.block), 1);
+                        stack.count = stack.max_stack_size();
+                        let response = item_tooltip(ui.add_sized([SLOT_SIZE, SLOT_SIZE], button), &stack, &info);
+                        if response.clicked() {
+                            screen.held = stack;
+                        } else if response.secondary_clicked() {
+                            *inventory.get_selected_item_mut() = stack;
+                        }
+                        if i % PICKER_COLUMNS == PICKER_COLUMNS - 1 {
+                            ui.end_row();
+                        }
+                    }
+                });
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_registry::ScriptBlockDefinition;
+    use crate::inventory::ToolType;
+
+    fn register(registry: &mut BlockRegistry, id: &str, block: BlockId) {
+        let definition = ScriptBlockDefinition { id: id.to_string(), texture: Some(id.to_string()), ..default() };
+        registry.definitions.insert(id.to_string(), definition);
+        registry.id_to_blockid.insert(id.to_string(), block);
+    }
+
+    #[test]
+    fn creative_picker_lists_each_block_once_and_filters_by_id_or_name() {
+        let mut registry = BlockRegistry::new();
+        register(&mut registry, "stone", BlockId::Stone);
+        register(&mut registry, "dirt", BlockId::Dirt);
+        register(&mut registry, "grass", BlockId::Grass);
+        // 脚本新注册、映射到石头的方块与石头合并
+        register(&mut registry, "marble", BlockId::Stone);
+
+        let blocks = creative_blocks(&registry);
+        let order: Vec<BlockId> = blocks.iter().map(|entry| entry.block).collect();
+        let mut sorted = order.clone();
+        sorted.sort_by_key(|block| *block as u8);
+        assert_eq!(order, sorted);
+        assert_eq!(blocks.len(), 3);
+        let stone = blocks.iter().find(|entry| entry.block == BlockId::Stone).unwrap();
+        assert_eq!(stone.script_ids, vec!["marble".to_string(), "stone".to_string()]);
+
+        let grass = blocks.iter().find(|entry| entry.block == BlockId::Grass).unwrap();
+        assert!(matches_search("", grass, "草方块"));
+        assert!(matches_search("GRASS", grass, "草方块"));
+        assert!(matches_search("grass_block", grass, "草方块"));
+        assert!(matches_search("草", grass, "草方块"));
+        assert!(!matches_search("dirt", grass, "草方块"));
+        assert!(matches_search("marb", stone, "石头"));
+    }
+
+    #[test]
+    fn tooltip_shows_count_durability_description_and_debug_id() {
+        let mut registry = BlockRegistry::new();
+        register(&mut registry, "stone", BlockId::Stone);
+        registry.definitions.get_mut("stone").unwrap().description_key = Some("game.block.stone.description".to_string());
+        let (ui_strings, localization) = (UiStringManager::default(), LocalizationManager::default());
+        let mut info = ItemInfo { ui_strings: &ui_strings, localization: &localization, registry: &registry, show_id: false, tooltips: true };
+
+        let lines = tooltip_lines(&ItemStack::new(ItemType::Block(BlockId::Stone), 12), &info);
+        assert_eq!(lines.len(), 3);
+        assert!(matches!(&lines[1], TooltipLine::Detail(text) if text.ends_with(": 12")));
+        assert_eq!(lines[2], TooltipLine::Description(localization.get("game.block.stone.description").to_string()));
+
+        // 工具只有一个，显示剩余耐久；调试信息覆盖层打开时显示物品 id
+        info.show_id = true;
+        let mut pickaxe = ItemStack::new(ItemType::Tool(ToolType::WoodenPickaxe), 1);
+        pickaxe.damage_tool();
+        let lines = tooltip_lines(&pickaxe, &info);
+        assert_eq!(lines[1], TooltipLine::Id("wooden_pickaxe"));
+        assert!(matches!(&lines[2], TooltipLine::Detail(text) if text.ends_with(": 58 / 59")));
+        assert_eq!(lines.len(), 3);
+    }
+}
@@ -0,0 +1,360 @@
+use std::path::PathBuf;
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContexts};
+use futures_lite::future;
+use crate::autosave::PlayerSave;
+use crate::game_state::{read_world_state, GameState, WorldManager, WorldState};
+use crate::localization::LocalizationManager;
+use crate::notifications::Notifications;
+use crate::rendering::voxel_mesh::ChunkMesh;
+use crate::world::chunk_loader::{ChunkLoadQueue, EMERGENCY_PRIORITY};
+use crate::world::chunk_stats::ChunkStats;
+use crate::world::generator::WorldGeneratorConfig;
+use crate::world::storage::ChunkStorage;
+use crate::world::{world_spawn_position, SpawnPoint};
+
+/// 出生区域预加载配置
+#[derive(Resource)]
+pub struct SpawnPreloadConfig {
+    pub radius: i32,        // 水平半径（区块），1 表示 3×3 列
+    pub below: i32,         // 出生区块以下预加载的层数
+    pub above: i32,         // 出生区块以上预加载的层数
+    pub timeout_secs: f32,  // 超时后即使未加载完也进入游戏
+}
+
+impl Default for SpawnPreloadConfig {
+    fn default() -> Self {
+        Self {
+            radius: 1,
+            below: 2,
+            above: 1,
+            timeout_secs: 15.0,
+        }
+    }
+}
+
+/// 进入世界的加载阶段，按顺序执行，文件读取都在后台线程进行
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoadingStage {
+    /// 读取世界状态并确定世界出生点
+    #[default]
+    ReadingWorldInfo,
+    /// 读取玩家存档
+    RestoringPlayer,
+    /// 等待出生区域的区块读取或生成并构建网格
+    LoadingSpawnChunks,
+}
+
+impl LoadingStage {
+    pub const ALL: [LoadingStage; 3] = [
+        LoadingStage::ReadingWorldInfo,
+        LoadingStage::RestoringPlayer,
+        LoadingStage::LoadingSpawnChunks,
+    ];
+
+    pub fn localization_key(self) -> &'static str {
+        match self {
+            LoadingStage::ReadingWorldInfo => "game.loading.reading_world_info",
+            LoadingStage::RestoringPlayer => "game.loading.restoring_player",
+            LoadingStage::LoadingSpawnChunks => "game.loading.loading_spawn_chunks",
+        }
+    }
+}
+
+/// 本次加载的当前阶段和需要等待的区块
+#[derive(Resource, Default)]
+pub struct SpawnPreload {
+    pub stage: LoadingStage,
+    pub needed: Vec<IVec3>,
+    pub ready: usize,
+    pub started_at: f32,
+}
+
+impl SpawnPreload {
+    /// 改为等待另一个出生点周围的区块（联机时出生点由服务器决定）
+    pub fn retarget(&mut self, spawn: Vec3, config: &SpawnPreloadConfig) {
+        self.needed = spawn_area((spawn / 32.0).floor().as_ivec3(), config);
+        self.ready = 0;
+    }
+}
+
+/// 世界状态的读取结果（之前是否保存过）、使用的世界状态和世界出生点
+type WorldRead = (Result<bool, String>, WorldState, Vec3);
+
+/// 后台读取任务和读取结果。读取失败时使用默认值继续加载，问题在进入游戏时以通知显示
+#[derive(Resource, Default)]
+pub struct WorldLoad {
+    world_dir: PathBuf,
+    world_task: Option<Task<WorldRead>>,
+    player_task: Option<Task<Result<Option<PlayerSave>, String>>>,
+    /// 世界之前保存过（有世界状态文件），此时缺少玩家存档视为问题
+    saved_before: bool,
+    world_spawn: Vec3,
+    player: Option<PlayerSave>,
+    /// 加载开始时无法读取的区块数，结束时比较得出出生区域中重新生成的区块
+    unreadable_at_start: u64,
+    /// 本地化键和详细信息
+    problems: Vec<(&'static str, String)>,
+}
+
+impl WorldLoad {
+    /// 取出读取到的玩家存档，玩家实体生成时使用
+    pub fn take_player(&mut self) -> Option<PlayerSave> {
+        self.player.take()
+    }
+}
+
+/// 加载界面使用的临时摄像机，进入游戏后由玩家摄像机取代
+#[derive(Component)]
+struct LoadingCamera;
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnPreloadConfig>()
+           .init_resource::<SpawnPreload>()
+           .init_resource::<WorldLoad>()
+           .add_systems(OnEnter(GameState::Loading), start_world_load)
+           .add_systems(Update, (
+               finish_reading_world_info,
+               finish_restoring_player,
+               update_spawn_preload,
+               loading_screen_ui,
+           ).chain().run_if(in_state(GameState::Loading)))
+           .add_systems(OnExit(GameState::Loading), (report_load_problems, cleanup_loading_screen));
+    }
+}
+
+/// 出生区块列及周围的区块坐标，按到出生区块的距离排序
+pub fn spawn_area(center: IVec3, config: &SpawnPreloadConfig) -> Vec<IVec3> {
+    let mut coords = Vec::new();
+    for x in -config.radius..=config.radius {
+        for z in -config.radius..=config.radius {
+            for y in -config.below..=config.above {
+                coords.push(center + IVec3::new(x, y, z));
+            }
+        }
+    }
+    coords.sort_by_key(|c| (*c - center).length_squared());
+    coords
+}
+
+/// 在后台读取世界状态；新世界第一次进入时同时寻找出生点，需要采样地形高度
+fn read_world(state_path: PathBuf, generator_config: WorldGeneratorConfig) -> WorldRead {
+    let (result, mut state) = match read_world_state(&state_path) {
+        Ok(saved) => (Ok(saved.is_some()), saved.unwrap_or_default()),
+        Err(e) => (Err(e), WorldState::default()),
+    };
+    let spawn = world_spawn_position(&mut state, &generator_config);
+    (result, state, spawn)
+}
+
+/// 开始加载当前世界：显示加载界面并在后台读取世界状态
+fn start_world_load(
+    mut commands: Commands,
+    mut preload: ResMut<SpawnPreload>,
+    generator_config: Res<WorldGeneratorConfig>,
+    world_manager: Res<WorldManager>,
+    chunk_stats: Res<ChunkStats>,
+    time: Res<Time>,
+) {
+    // 联机时出生点由服务器在加载期间发来，这里清除上一次加载的值
+    commands.remove_resource::<SpawnPoint>();
+    let state_path = world_manager.world_state_path();
+    let generator_config = generator_config.clone();
+    let world_task = AsyncComputeTaskPool::get().spawn(async move { read_world(state_path, generator_config) });
+    commands.insert_resource(WorldLoad {
+        world_dir: world_manager.current_world_dir(),
+        world_task: Some(world_task),
+        unreadable_at_start: chunk_stats.counters().snapshot().unreadable,
+        ..default()
+    });
+    *preload = SpawnPreload { started_at: time.elapsed_seconds(), ..default() };
+    commands.spawn((Camera2dBundle::default(), LoadingCamera));
+}
+
+/// 世界状态读取完成后应用到资源，并在后台读取玩家存档
+fn finish_reading_world_info(
+    mut load: ResMut<WorldLoad>,
+    mut preload: ResMut<SpawnPreload>,
+    mut world_state: ResMut<WorldState>,
+) {
+    let Some(task) = load.world_task.as_mut() else { return };
+    let Some((result, state, spawn)) = future::block_on(future::poll_once(task)) else { return };
+    load.world_task = None;
+    match result {
+        Ok(saved_before) => load.saved_before = saved_before,
+        Err(e) => {
+            warn!("{}, using the default world state", e);
+            load.problems.push(("game.loading.world_state_error", e));
+        }
+    }
+    *world_state = state;
+    load.world_spawn = spawn;
+
+    let world_dir = load.world_dir.clone();
+    load.player_task = Some(AsyncComputeTaskPool::get().spawn(async move { PlayerSave::read(&world_dir) }));
+    preload.stage = LoadingStage::RestoringPlayer;
+}
+
+/// 玩家存档读取完成后确定出生点，并以最高优先级把出生区域加入加载队列
+fn finish_restoring_player(
+    mut commands: Commands,
+    mut load: ResMut<WorldLoad>,
+    mut preload: ResMut<SpawnPreload>,
+    config: Res<SpawnPreloadConfig>,
+    mut load_queue: ResMut<ChunkLoadQueue>,
+    server_spawn: Option<Res<SpawnPoint>>,
+) {
+    let Some(task) = load.player_task.as_mut() else { return };
+    let Some(result) = future::block_on(future::poll_once(task)) else { return };
+    load.player_task = None;
+    load.player = match result {
+        Ok(Some(save)) => Some(save),
+        Ok(None) => {
+            if load.saved_before {
+                warn!("Player save is missing from {:?}, starting at the world spawn", load.world_dir);
+                let detail = load.world_dir.display().to_string();
+                load.problems.push(("game.loading.player_missing", detail));
+            }
+            None
+        }
+        Err(e) => {
+            warn!("{}, starting at the world spawn", e);
+            load.problems.push(("game.loading.player_error", e));
+            None
+        }
+    };
+
+    // 联机时使用服务器发来的出生点，有玩家存档时预加载上次退出的位置，否则使用世界出生点
+    let spawn = match (&server_spawn, &load.player) {
+        (Some(spawn_point), _) => spawn_point.0,
+        (None, Some(save)) => save.position,
+        (None, None) => load.world_spawn,
+    };
+    if server_spawn.is_none() {
+        commands.insert_resource(SpawnPoint(spawn));
+    }
+
+    let center = (spawn / 32.0).floor().as_ivec3();
+    preload.retarget(spawn, &config);
+    for coord in &preload.needed {
+        let distance = (*coord - center).as_vec3().length();
+        load_queue.push(*coord, EMERGENCY_PRIORITY - distance);
+    }
+    info!("Preloading {} chunks around spawn {:?}", preload.needed.len(), spawn);
+    preload.stage = LoadingStage::LoadingSpawnChunks;
+}
+
+/// 统计已生成并完成网格构建的区块，全部就绪或超时后进入游戏
+fn update_spawn_preload(
+    mut preload: ResMut<SpawnPreload>,
+    config: Res<SpawnPreloadConfig>,
+    chunk_storage: Res<ChunkStorage>,
+    meshed: Query<(), With<ChunkMesh>>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if preload.stage != LoadingStage::LoadingSpawnChunks {
+        return;
+    }
+    preload.ready = preload
+        .needed
+        .iter()
+        .filter(|coord| chunk_storage.get(coord).is_some_and(|entity| meshed.contains(entity)))
+        .count();
+
+    if preload.ready == preload.needed.len() {
+        info!("Spawn area ready ({} chunks)", preload.ready);
+        next_state.set(GameState::InGame);
+    } else if time.elapsed_seconds() - preload.started_at >= config.timeout_secs {
+        warn!(
+            "Spawn preload timed out after {:.0}s with {}/{} chunks ready, entering the world anyway",
+            config.timeout_secs, preload.ready, preload.needed.len()
+        );
+        next_state.set(GameState::InGame);
+    }
+}
+
+fn loading_screen_ui(
+    mut contexts: EguiContexts,
+    preload: Res<SpawnPreload>,
+    localization: Res<LocalizationManager>,
+) {
+    let stage = LoadingStage::ALL.iter().position(|&stage| stage == preload.stage).unwrap_or_default();
+    let total = preload.needed.len().max(1);
+    // 每个阶段占进度条的一段，出生区域阶段按就绪的区块比例推进
+    let (progress, text) = match preload.stage {
+        LoadingStage::LoadingSpawnChunks => (
+            preload.ready as f32 / total as f32,
+            format!("{}: {} / {}", localization.get("game.loading.chunks_ready"), preload.ready, total),
+        ),
+        _ => (0.0, String::new()),
+    };
+    let fraction = (stage as f32 + progress) / LoadingStage::ALL.len() as f32;
+    egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() * 0.4);
+            ui.heading(localization.get("game.loading.title"));
+            ui.add_space(8.0);
+            ui.label(format!("{} ({}/{})", localization.get(preload.stage.localization_key()), stage + 1, LoadingStage::ALL.len()));
+            ui.add_space(12.0);
+            ui.add(egui::ProgressBar::new(fraction).desired_width(320.0).text(text));
+        });
+    });
+}
+
+/// 进入游戏时以通知显示加载中遇到的问题，包括出生区域中无法读取而重新生成的区块
+fn report_load_problems(
+    mut load: ResMut<WorldLoad>,
+    chunk_stats: Res<ChunkStats>,
+    mut notifications: ResMut<Notifications>,
+    localization: Res<LocalizationManager>,
+) {
+    for (key, detail) in load.problems.drain(..) {
+        notifications.error(format!("{}: {}", localization.get(key), detail));
+    }
+    let unreadable = chunk_stats.counters().snapshot().unreadable - load.unreadable_at_start;
+    if unreadable > 0 {
+        notifications.warn(format!("{}: {}", localization.get("game.loading.chunks_regenerated"), unreadable));
+    }
+}
+
+fn cleanup_loading_screen(mut commands: Commands, cameras: Query<Entity, With<LoadingCamera>>) {
+    for entity in cameras.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_area_covers_columns_nearest_first() {
+        let config = SpawnPreloadConfig::default();
+        let center = IVec3::new(0, 2, 0);
+        let area = spawn_area(center, &config);
+        assert_eq!(area.len(), 9 * 4);
+        assert_eq!(area[0], center);
+        assert!(area.contains(&IVec3::new(1, 0, -1)));
+        assert!(area.contains(&IVec3::new(-1, 3, 1)));
+    }
+
+    #[test]
+    fn unreadable_world_state_falls_back_to_defaults_with_a_spawn_point() {
+        let dir = std::env::temp_dir().join(format!("loading_world_state_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("level.json");
+        std::fs::write(&path, "{ not json").unwrap();
+        let (result, state, spawn) = read_world(path.clone(), WorldGeneratorConfig::default());
+        assert!(result.is_err());
+        assert_eq!(state.spawn_point.map(|point| point.as_vec3() + Vec3::Y * 3.0), Some(spawn));
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_world(path, WorldGeneratorConfig::default()).0, Ok(false));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,23 +1,7 @@
 use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
 use bevy::prelude::*;
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LanguageInfo {
-    pub name: String,
-    pub code: String,
-    pub author: String,
-    pub version: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LanguageData {
-    pub language_info: LanguageInfo,
-    #[serde(flatten)]
-    pub texts: serde_json::Value,
-}
+use mc_common::localization::{scan_languages, LanguageData, DEFAULT_LANGUAGE, LOCALIZATION_DIR};
+pub use mc_common::localization::LanguageInfo;
 
 #[derive(Debug, Clone, Resource)]
 pub struct LocalizationManager {
@@ -29,7 +13,7 @@ pub struct LocalizationManager {
 impl Default for LocalizationManager {
     fn default() -> Self {
         let mut manager = Self {
-            current_language: "en_us".to_string(),
+            current_language: DEFAULT_LANGUAGE.to_string(),
             available_languages: Vec::new(),
             texts: HashMap::new(),
         };
@@ -38,7 +22,7 @@ impl Default for LocalizationManager {
         manager.scan_languages();
         
         // Load default language (English)
-        if let Err(e) = manager.load_language("en_us") {
+        if let Err(e) = manager.load_language(DEFAULT_LANGUAGE) {
             warn!("Failed to load default language: {}", e);
             manager.load_fallback_texts();
         }
@@ -53,76 +37,25 @@ impl LocalizationManager {
     }
     
     pub fn scan_languages(&mut self) {
-        self.available_languages.clear();
-        
-        let localization_dir = "localization";
-        if let Ok(entries) = fs::read_dir(localization_dir) {
-            for entry in entries.flatten() {
-                if let Some(file_name) = entry.file_name().to_str() {
-                    if file_name.ends_with(".json") {
-                        let lang_code = file_name.trim_end_matches(".json");
-                        if let Ok(lang_data) = self.load_language_info(&entry.path()) {
-                            self.available_languages.push(lang_data.language_info);
-                        } else {
-                            // Fallback for files without proper language_info
-                            self.available_languages.push(LanguageInfo {
-                                name: lang_code.to_string(),
-                                code: lang_code.to_string(),
-                                author: "Unknown".to_string(),
-                                version: "1.0.0".to_string(),
-                            });
-                        }
-                    }
-                }
-            }
-        }
-        
+        self.available_languages = scan_languages(&crate::paths::data_path(LOCALIZATION_DIR));
         info!("Found {} languages", self.available_languages.len());
     }
     
-    fn load_language_info(&self, path: &Path) -> Result<LanguageData, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string(path)?;
-        let data: LanguageData = serde_json::from_str(&content)?;
-        Ok(data)
-    }
-    
     pub fn load_language(&mut self, language_code: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let file_path = format!("localization/{}.json", language_code);
+        let file_path = LanguageData::path(&crate::paths::data_path(LOCALIZATION_DIR), language_code);
         
-        if !Path::new(&file_path).exists() {
-            return Err(format!("Language file not found: {}", file_path).into());
+        if !file_path.exists() {
+            return Err(format!("Language file not found: {}", file_path.display()).into());
         }
         
-        let content = fs::read_to_string(&file_path)?;
-        let data: LanguageData = serde_json::from_str(&content)?;
-        
-        self.texts.clear();
-        self.flatten_json(&data.texts, String::new());
+        let data = LanguageData::read(&file_path)?;
+        self.texts = data.flatten();
         self.current_language = language_code.to_string();
         
         info!("Loaded language: {} ({})", data.language_info.name, language_code);
         Ok(())
     }
     
-    fn flatten_json(&mut self, value: &serde_json::Value, prefix: String) {
-        match value {
-            serde_json::Value::Object(map) => {
-                for (key, val) in map {
-                    let new_key = if prefix.is_empty() {
-                        key.clone()
-                    } else {
-                        format!("{}.{}", prefix, key)
-                    };
-                    self.flatten_json(val, new_key);
-                }
-            }
-            serde_json::Value::String(s) => {
-                self.texts.insert(prefix, s.clone());
-            }
-            _ => {}
-        }
-    }
-    
     fn load_fallback_texts(&mut self) {
         // Fallback English texts if no language files are available
         self.texts.insert("ui.main_menu.title".to_string(), "Minecraft Rust".to_string());
@@ -0,0 +1,101 @@
+use bevy::log::Level;
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::Mutex;
+use tracing_log::LogTracer;
+use tracing_subscriber::{prelude::*, EnvFilter, Registry};
+
+/// 日志目录：当前运行的日志写入 latest.log，启动时把上一次的日志按它的修改时间重命名
+pub const LOG_DIR: &str = "logs";
+const LATEST_LOG: &str = "latest.log";
+/// 最多保留多少个旧日志文件，更早的被删除
+const MAX_OLD_LOGS: usize = 10;
+/// 指定日志等级的命令行参数
+pub const LOG_LEVEL_FLAG: &str = "--log-level";
+/// 与 Bevy 默认相同的额外过滤，避免渲染后端刷屏
+const DEFAULT_FILTER: &str = "wgpu=error,naga=warn";
+
+/// 解析日志等级（trace、debug、info、warn、error，不区分大小写）
+pub fn parse_level(level: &str) -> Option<Level> {
+    level.trim().parse().ok()
+}
+
+/// 代替 Bevy 的 `LogPlugin` 安装日志：同时输出到控制台和 logs/latest.log（不带颜色）。
+/// 设置了 RUST_LOG 环境变量时用它代替 `level` 过滤日志。日志文件无法创建时只输出到控制台
+pub fn init(level: Level) {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(format!("{},{}", level, DEFAULT_FILTER)))
+        .unwrap_or_else(|_| EnvFilter::new(level.to_string()));
+    let log_dir = crate::paths::data_path(LOG_DIR);
+    let file = match rotate_logs(&log_dir).and_then(|_| {
+        let path = log_dir.join(LATEST_LOG);
+        File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))
+    }) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("{}, logging to the console only", e);
+            None
+        }
+    };
+    let file_layer = file.map(|file| {
+        tracing_subscriber::fmt::layer().with_ansi(false).with_writer(Mutex::new(file))
+    });
+    let subscriber = Registry::default()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer);
+    if LogTracer::init().is_err() || bevy::utils::tracing::subscriber::set_global_default(subscriber).is_err() {
+        eprintln!("A logger was already installed, file logging is disabled");
+    }
+}
+
+/// 把上一次的 latest.log 重命名为它最后修改的时间，并只保留最近的 `MAX_OLD_LOGS` 个旧日志
+fn rotate_logs(log_dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(log_dir).map_err(|e| format!("Failed to create log directory {}: {}", log_dir.display(), e))?;
+    let latest = log_dir.join(LATEST_LOG);
+    if let Ok(modified) = fs::metadata(&latest).and_then(|metadata| metadata.modified()) {
+        let timestamp = chrono::DateTime::<chrono::Local>::from(modified).format("%Y-%m-%d_%H.%M.%S");
+        let rotated = log_dir.join(format!("{}.log", timestamp));
+        fs::rename(&latest, &rotated).map_err(|e| format!("Failed to rotate {}: {}", latest.display(), e))?;
+    }
+
+    // 旧日志的文件名就是时间，按名称排序即按时间排序
+    let mut old_logs: Vec<_> = fs::read_dir(log_dir)
+        .map_err(|e| format!("Failed to read log directory {}: {}", log_dir.display(), e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log") && path.file_name().is_some_and(|name| name != LATEST_LOG))
+        .collect();
+    old_logs.sort();
+    let excess = old_logs.len().saturating_sub(MAX_OLD_LOGS);
+    for path in &old_logs[..excess] {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_renames_latest_and_keeps_recent_logs() {
+        let dir = std::env::temp_dir().join(format!("log_rotation_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for i in 0..MAX_OLD_LOGS {
+            fs::write(dir.join(format!("2000-01-01_00.00.{:02}.log", i)), "old").unwrap();
+        }
+        fs::write(dir.join(LATEST_LOG), "previous run").unwrap();
+
+        rotate_logs(&dir).unwrap();
+        let names: Vec<String> = fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect();
+        fs::remove_dir_all(&dir).unwrap();
+        // latest.log 被重命名，最早的旧日志被删除
+        assert_eq!(names.len(), MAX_OLD_LOGS);
+        assert!(!names.contains(&LATEST_LOG.to_string()));
+        assert!(!names.contains(&"2000-01-01_00.00.00.log".to_string()));
+
+        assert_eq!(parse_level("Debug"), Some(Level::DEBUG));
+        assert_eq!(parse_level("loud"), None);
+    }
+}
@@ -1,125 +1,211 @@
 use bevy::prelude::*;
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::asset::AssetPlugin;
+use bevy::log::{Level, LogPlugin};
 use bevy::render::texture::ImagePlugin;
 use std::env;
+use std::path::PathBuf;
 
 mod systems;
 mod world;
 mod rendering;
 mod ui;
+mod chunk_presets;
+mod display;
 mod localization;
 mod scripting;
+mod paths;
+mod random;
+mod logging;
+mod crash_report;
+mod benchmark;
 mod block_registry;
 mod controller;
+mod command;
+mod cursor;
+mod camera_effects;
+mod camera_environment;
 mod inventory;
+mod inventory_screen;
+mod crafting;
+mod mining;
+mod health;
+mod explosion;
+mod mob;
+mod bed;
 mod hud;
+mod compass;
 mod game_state;
-// 菜单模块已移除，所有菜单功能在启动器中实现
-// mod main_menu;
-// mod pause_menu;
+mod loading;
+mod debug_overlay;
+mod world_map;
+mod world_edit;
+mod notifications;
+mod message_log;
+mod screenshot;
+mod ambience;
+mod audio;
+mod autosave;
+mod stats;
+mod memory;
+mod server;
+mod net;
+mod export;
+mod anvil_import;
+mod pause_menu;
+// 世界选择主要在启动器中进行，直接运行游戏时使用游戏内的简易主菜单
+mod main_menu;
+mod photo_mode;
+// 设置菜单已移除，设置窗口在 ui 中实现
 // mod settings_menu;
 mod ui_strings;
 
 use crate::localization::{LocalizationManager, LanguageChangeEvent, handle_language_change};
 use crate::scripting::ScriptEngine;
 use crate::block_registry::BlockRegistry;
+use crate::crafting::RecipeRegistry;
 use crate::controller::{ControllerPlugin, FirstPersonController};
-use crate::world::generator::{WorldGenerator, WorldGeneratorConfig};
-use crate::game_state::{GameState, GameStatePlugin};
+use crate::world::SpawnPoint;
+use crate::world::chunk_loader::ChunkLoadAnchor;
+use crate::game_state::{GameState, GameStatePlugin, WorldManager};
 use crate::ui_strings::UiStringManager;
+use crate::notifications::Notifications;
 
 // 启动参数资源已移除，游戏直接启动到游戏状态
 
-fn setup_scripting(engine: Res<ScriptEngine>, mut registry: ResMut<BlockRegistry>) {
+/// 加载脚本、方块和配方。出错时记录日志，客户端还会在屏幕上提示（服务器没有通知和本地化资源）
+fn setup_scripting(
+    engine: Res<ScriptEngine>,
+    mut registry: ResMut<BlockRegistry>,
+    mut recipes: ResMut<RecipeRegistry>,
+    mut notifications: Option<ResMut<Notifications>>,
+    localization: Option<Res<LocalizationManager>>,
+) {
+    let mut errors = Vec::new();
+    // 配方脚本调用 register_recipe，需要在加载脚本之前定义
+    if let Err(e) = RecipeRegistry::install_api(&engine) {
+        error!("Failed to install the recipe API: {e}");
+        errors.push(e);
+    }
     // Try load all scripts at startup, ignore errors but log
     if let Err(e) = engine.load_all() {
         error!("Failed to load Lua scripts: {e}");
+        errors.push(e);
     } else {
         info!("Lua scripts loaded successfully");
     }
     if let Err(e) = registry.load_from_scripts(&engine) {
         warn!("Failed to load blocks from scripts: {e}");
+        errors.push(e);
     }
-}
-
-fn find_safe_spawn_point(generator: &WorldGenerator) -> (i32, i32, i32) {
-    // 尝试在原点附近找到一个安全的出生点
-    for radius in 0..10 {
-        for x in -radius..=radius {
-            for z in -radius..=radius {
-                // 只检查圆形边界上的点
-                if x*x + z*z == radius*radius || radius == 0 {
-                    let surface_height = generator.get_surface_height(x, z);
-                    
-                    // 确保出生点不在水下（假设海平面是64）
-                    if surface_height >= 64 {
-                        return (x, surface_height, z);
-                    }
-                }
-            }
+    if let Err(e) = recipes.load_from_scripts(&engine) {
+        warn!("Failed to load recipes from scripts: {e}");
+        errors.push(e);
+    }
+    if let (Some(notifications), Some(localization)) = (notifications.as_mut(), localization) {
+        for e in errors {
+            // Lua 错误带有多行调用栈，通知中只显示第一行，完整内容在日志中
+            let message = e.to_string();
+            notifications.error(format!("{}: {}", localization.get("game.scripts.error"), message.lines().next().unwrap_or_default()));
         }
     }
-    
-    // 如果找不到合适的点，就使用原点
-    (0, generator.get_surface_height(0, 0), 0)
 }
 
-// 主菜单摄像头设置函数已移除
-
 fn setup_game_camera(
     mut commands: Commands,
+    spawn_point: Res<SpawnPoint>,
+    mut world_load: ResMut<loading::WorldLoad>,
 ) {
-    // 创建世界生成器来计算地面高度
-    let config = WorldGeneratorConfig::default();
-    let generator = WorldGenerator::new(config);
-    
-    // 找到安全的出生点
-    let (spawn_x, surface_height, spawn_z) = find_safe_spawn_point(&generator);
-    let spawn_y = surface_height as f32 + 3.0; // 在地面上方3格出生
-    
-    info!("Player spawning at surface height: {} (world pos: {}, {}, {})", surface_height, spawn_x, spawn_y, spawn_z);
+    info!("Player spawning at {:?}", spawn_point.0);
 
+    // 有玩家存档时恢复朝向、移动模式和物品栏，存档在加载界面中读取，位置已写入出生点
+    let mut controller = FirstPersonController::default();
+    let mut inventory = inventory::PlayerInventory::new();
+    if let Some(save) = world_load.take_player() {
+        controller.yaw = save.yaw;
+        controller.pitch = save.pitch;
+        controller.mode = save.mode;
+        inventory = save.inventory;
+        info!("Restored player state from save");
+    }
+
+    let pitch = controller.pitch;
     let player = commands.spawn((
         SpatialBundle {
-            transform: Transform::from_xyz(spawn_x as f32, spawn_y, spawn_z as f32),
+            transform: Transform::from_translation(spawn_point.0)
+                .with_rotation(Quat::from_axis_angle(Vec3::Y, controller.yaw)),
             ..default()
         },
-        FirstPersonController::default(),
-        inventory::PlayerInventory::new(),
+        controller,
+        inventory,
+        health::Health::default(),
+        ChunkLoadAnchor,
     )).id();
 
     commands.entity(player).with_children(|parent| {
         parent.spawn(Camera3dBundle {
-            transform: Transform::from_xyz(0.0, 1.62, 0.0),
+            transform: Transform::from_xyz(0.0, controller::EYE_HEIGHT, 0.0)
+                .with_rotation(Quat::from_axis_angle(Vec3::X, pitch)),
             ..default()
         });
     });
 }
 
-fn setup_localization(mut commands: Commands) {
+/// 加载设置中选择的语言，失败时使用英语
+fn setup_localization(mut commands: Commands, game_settings: Res<ui::GameSettings>) {
     info!("Initializing localization system...");
     let mut localization = LocalizationManager::new();
     localization.scan_languages();
-    if let Err(e) = localization.load_language("en_us") {
-        warn!("Failed to load default language: {}", e);
+    if let Err(e) = localization.load_language(&game_settings.language) {
+        warn!("Failed to load language {}: {}, using en_us", game_settings.language, e);
+        if let Err(e) = localization.load_language("en_us") {
+            warn!("Failed to load default language: {}", e);
+        }
     }
     commands.insert_resource(localization);
 }
 
-// 启动参数解析和初始状态设置函数已移除
-
-fn setup_initial_state(mut next_state: ResMut<NextState<GameState>>) {
-    info!("Starting game directly without menu");
-    next_state.set(GameState::InGame);
-}
+// 启动参数解析和初始状态设置函数已移除：指定了世界（或联机、基准测试）时游戏以 GameState::Loading 启动，否则显示主菜单
 
 fn main() {
-    App::new()
+    // --headless 启动无窗口服务器，--world <名称> 指定要加载的世界，--port <端口> 指定监听端口；
+    // --connect <地址> 作为客户端加入服务器，--name <名称> 指定玩家名；import 子命令导入原版存档。
+    // --data-dir <目录> 指定游戏根目录（脚本、本地化、资源和存档所在的目录），--log-level <等级> 指定日志等级；
+    // --benchmark 在固定种子的新世界中沿预设路线飞行，结束后写出性能报告并退出
+    let args: Vec<String> = env::args().collect();
+    let arg_value = |flag: &str| args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1)).cloned();
+    paths::init(arg_value(paths::DATA_DIR_FLAG).map(PathBuf::from));
+    if args.get(1).is_some_and(|arg| arg == "import") {
+        if let Err(e) = anvil_import::run(&args[2..]) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // 没有 --log-level 时使用设置中的日志等级，无窗口服务器默认 info
+    let headless = args.iter().any(|arg| arg == "--headless");
+    let log_level = arg_value(logging::LOG_LEVEL_FLAG).or_else(|| (!headless).then(|| ui::GameSettings::load().log_level));
+    let log_level = log_level.map_or(Level::INFO, |level| logging::parse_level(&level).unwrap_or_else(|| {
+        eprintln!("Unknown log level {:?}, using info", level);
+        Level::INFO
+    }));
+    logging::init(log_level);
+    crash_report::install_panic_hook();
+
+    if headless {
+        let port = arg_value("--port").and_then(|port| port.parse().ok()).unwrap_or(net::protocol::DEFAULT_PORT);
+        server::run(arg_value("--world"), port);
+        return;
+    }
+
+    let mut app = App::new();
+    app
         .add_event::<LanguageChangeEvent>()
-        .insert_resource(ClearColor(Color::rgb(0.53, 0.81, 0.92)))
+        .insert_resource(ClearColor(rendering::SKY_COLOR))
         .insert_resource(ScriptEngine::default())
         .insert_resource(BlockRegistry::default())
+        .insert_resource(RecipeRegistry::default())
         .insert_resource(UiStringManager::new())
         .add_plugins(DefaultPlugins
             .set(WindowPlugin {
@@ -132,10 +218,12 @@ fn main() {
                 ..default()
             })
             .set(AssetPlugin {
-                file_path: "g:/Document/MinecarftRustver/minecraft".to_string(),
+                file_path: paths::assets_dir().to_string_lossy().into_owned(),
                 ..default()
             })
             .set(ImagePlugin::default_nearest())
+            // 日志由 logging::init 安装，同时写入日志文件
+            .disable::<LogPlugin>()
         )
         .add_plugins(LogDiagnosticsPlugin::default())
         .add_plugins(FrameTimeDiagnosticsPlugin::default())
@@ -143,16 +231,75 @@ fn main() {
         .add_plugins(GameStatePlugin)
         // UI插件（仅保留游戏内UI）
         .add_plugins(ui::UiPlugin)
+        .add_plugins(display::DisplayPlugin)
         // 游戏系统插件
         .add_plugins(world::WorldPlugin)
         .add_plugins(rendering::RenderingPlugin)
         .add_plugins(controller::ControllerPlugin)
+        .add_plugins(command::CommandPlugin)
+        .add_plugins(cursor::CursorPlugin)
+        .add_plugins(camera_effects::CameraEffectsPlugin)
+        .add_plugins(camera_environment::CameraEnvironmentPlugin)
         .add_plugins(inventory::InventoryPlugin)
+        .add_plugins(inventory_screen::InventoryScreenPlugin)
+        .add_plugins(health::HealthPlugin)
+        .add_plugins(explosion::ExplosionPlugin)
+        .add_plugins(mob::MobPlugin)
+        .add_plugins(bed::BedPlugin)
         .add_plugins(hud::HudPlugin)
+        .add_plugins(compass::CompassPlugin)
+        .add_plugins(pause_menu::PauseMenuPlugin)
+        .add_plugins(main_menu::MainMenuPlugin)
+        .add_plugins(photo_mode::PhotoModePlugin)
+        .add_plugins(loading::LoadingPlugin)
+        .add_plugins(debug_overlay::DebugOverlayPlugin)
+        .add_plugins(world_map::WorldMapPlugin)
+        .add_plugins(world_edit::WorldEditPlugin)
+        .add_plugins(notifications::NotificationsPlugin)
+        .add_plugins(message_log::MessageLogPlugin)
+        .add_plugins(screenshot::ScreenshotPlugin)
+        .add_plugins(audio::GameAudioPlugin)
+        .add_plugins(ambience::AmbiencePlugin)
+        .add_plugins(autosave::AutosavePlugin)
+        .add_plugins(stats::StatsPlugin)
+        .add_plugins(memory::MemoryPlugin)
+        .add_plugins(export::ExportPlugin)
+        .add_plugins(crash_report::CrashReportPlugin)
         // 启动系统
-        .add_systems(Startup, (setup_localization, setup_scripting, setup_initial_state).chain())
-        .add_systems(OnEnter(GameState::InGame), setup_game_camera)
+        // 脚本出错时的通知需要本地化，等 setup_localization 插入的资源生效后再加载脚本
+        .add_systems(Startup, (paths::check_game_dirs, setup_localization, apply_deferred, setup_scripting).chain())
+        // 出生区域加载完成后生成玩家
+        .add_systems(OnExit(GameState::Loading), setup_game_camera)
         // 本地化系统
-        .add_systems(Update, handle_language_change)
-        .run();
+        .add_systems(Update, handle_language_change);
+
+    // 启动器通过 --world 指定要进入的世界；直接运行游戏时先显示主菜单，在主菜单中选择世界后再检查存档版本
+    let benchmark = args.iter().any(|arg| arg == benchmark::BENCHMARK_FLAG);
+    let world_arg = arg_value("--world");
+    if world_arg.is_none() && arg_value("--connect").is_none() && !benchmark {
+        app.world.insert_resource(State::new(GameState::MainMenu));
+    } else {
+        if let Some(world_name) = world_arg {
+            app.world.resource_mut::<WorldManager>().current_world = Some(world_name);
+        }
+        // 打开世界前检查存档版本并迁移旧存档，更新版本的游戏保存的世界拒绝打开
+        if let Err(e) = app.world.resource::<WorldManager>().prepare_current_world() {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+        if let Some(seed) = app.world.resource::<WorldManager>().current_world_seed() {
+            app.world.resource_mut::<world::generator::WorldGeneratorConfig>().seed = seed;
+        }
+    }
+    // 单人游戏也通过进程内的服务器处理方块操作，与联机走同一条消息和校验路径
+    let player_name = arg_value("--name").unwrap_or_else(|| "Player".to_string());
+    app.add_plugins(net::client::NetClientPlugin { address: arg_value("--connect"), player_name });
+    if benchmark {
+        if let Err(e) = benchmark::prepare_world() {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+        app.add_plugins(benchmark::BenchmarkPlugin);
+    }
+    app.run();
 }
\ No newline at end of file
@@ -0,0 +1,459 @@
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+use crate::audio::SoundEvent;
+use crate::game_state::{GameMode, GameState, WorldInfo, WorldManager};
+use crate::localization::LocalizationManager;
+use crate::pause_menu::create_pause_button;
+use crate::ui::GameSettings;
+use crate::world::generator::WorldGeneratorConfig;
+
+/// 世界名称的最大长度（字符）
+const MAX_WORLD_NAME_CHARS: usize = 32;
+
+/// 主菜单当前显示的页面
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum MenuPage {
+    #[default]
+    Title,
+    Worlds,
+    CreateWorld,
+}
+
+/// 主菜单的状态，改变时重建界面
+#[derive(Resource, Default)]
+struct MainMenu {
+    page: MenuPage,
+    /// 世界列表中选中的世界
+    selected: Option<String>,
+    /// 已经按过一次删除，再按一次才真正删除
+    confirm_delete: bool,
+    new_world_name: String,
+    new_world_mode: GameMode,
+    /// 显示在按钮上方的错误（已本地化）
+    error: Option<String>,
+}
+
+impl MainMenu {
+    fn show(&mut self, page: MenuPage) {
+        self.page = page;
+        self.confirm_delete = false;
+        self.error = None;
+    }
+}
+
+/// 主菜单UI标记
+#[derive(Component)]
+struct MainMenuUI;
+
+/// 主菜单使用的摄像机，离开主菜单时移除，加载界面有自己的摄像机
+#[derive(Component)]
+struct MainMenuCamera;
+
+/// 世界列表中的一项
+#[derive(Component)]
+struct WorldEntry(String);
+
+/// 本帧交互状态变化的按钮，按钮的动作存在 `Name` 中
+type ButtonPresses<'w, 's> = Query<'w, 's, (&'static Interaction, &'static Name), (Changed<Interaction>, With<Button>)>;
+
+pub struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MainMenu>()
+           .add_systems(OnEnter(GameState::MainMenu), setup_main_menu)
+           .add_systems(OnExit(GameState::MainMenu), cleanup_main_menu)
+           .add_systems(Update, (
+               play_menu_clicks,
+               title_button_system,
+               world_button_system,
+               type_world_name,
+               main_menu_escape,
+               rebuild_main_menu.run_if(resource_changed::<MainMenu>().or_else(resource_changed::<LocalizationManager>())),
+               hide_main_menu_behind_settings,
+           ).chain().run_if(in_state(GameState::MainMenu)));
+    }
+}
+
+fn setup_main_menu(mut commands: Commands, mut menu: ResMut<MainMenu>, mut world_manager: ResMut<WorldManager>) {
+    commands.spawn((Camera2dBundle::default(), MainMenuCamera));
+    world_manager.load_worlds();
+    *menu = MainMenu::default();
+}
+
+fn cleanup_main_menu(mut commands: Commands, ui: Query<Entity, With<MainMenuUI>>, cameras: Query<Entity, With<MainMenuCamera>>) {
+    for entity in ui.iter().chain(cameras.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// 世界列表，最近游玩的排在前面
+fn sorted_worlds(world_manager: &WorldManager) -> Vec<&WorldInfo> {
+    let mut worlds: Vec<(&String, &WorldInfo)> = world_manager.worlds.iter().collect();
+    worlds.sort_by(|a, b| b.1.last_played.cmp(&a.1.last_played).then_with(|| a.0.cmp(b.0)));
+    worlds.into_iter().map(|(_, info)| info).collect()
+}
+
+/// 不与已有世界重名的默认世界名称："新世界"、"新世界 2"……
+fn unique_world_name(base: &str, world_manager: &WorldManager) -> String {
+    let taken = |name: &str| world_manager.worlds.contains_key(name) || world_manager.saves_directory.join(name).exists();
+    (1..)
+        .map(|n| if n == 1 { base.to_string() } else { format!("{} {}", base, n) })
+        .find(|name| !taken(name))
+        .unwrap_or_default()
+}
+
+/// 世界名称同时是存档目录名，不能包含路径分隔符和文件系统不允许的字符
+fn valid_world_name_char(c: char) -> bool {
+    !c.is_control() && !"/\\:*?\"<>|".contains(c)
+}
+
+fn next_game_mode(mode: GameMode) -> GameMode {
+    match mode {
+        GameMode::Survival => GameMode::Creative,
+        GameMode::Creative => GameMode::Adventure,
+        GameMode::Adventure => GameMode::Spectator,
+        GameMode::Spectator => GameMode::Survival,
+    }
+}
+
+fn menu_text(text: impl Into<String>, font_size: f32, color: Color) -> TextBundle {
+    TextBundle::from_section(text, TextStyle { font: default(), font_size, color })
+}
+
+/// 按当前页面重建主菜单界面
+fn rebuild_main_menu(
+    mut commands: Commands,
+    menu: Res<MainMenu>,
+    world_manager: Res<WorldManager>,
+    localization: Res<LocalizationManager>,
+    existing: Query<Entity, With<MainMenuUI>>,
+) {
+    for entity in &existing {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let title = match menu.page {
+        MenuPage::Title => "ui.main_menu.title",
+        MenuPage::Worlds => "ui.world_selection.title",
+        MenuPage::CreateWorld => "ui.create_world.title",
+    };
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(15.0),
+                ..default()
+            },
+            background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+            ..default()
+        },
+        MainMenuUI,
+    )).with_children(|parent| {
+        parent.spawn(menu_text(localization.get(title), 48.0, Color::WHITE).with_style(Style {
+            margin: UiRect::bottom(Val::Px(25.0)),
+            ..default()
+        }));
+
+        match menu.page {
+            MenuPage::Title => {
+                create_pause_button(parent, localization.get("ui.main_menu.singleplayer"), "singleplayer");
+                create_pause_button(parent, localization.get("ui.main_menu.settings"), "settings");
+                create_pause_button(parent, localization.get("ui.main_menu.quit"), "quit");
+            }
+            MenuPage::Worlds => spawn_world_list(parent, &menu, &world_manager, &localization),
+            MenuPage::CreateWorld => {
+                parent.spawn(menu_text(localization.get("ui.create_world.world_name"), 18.0, Color::GRAY));
+                // 名称输入框：直接打字输入，退格删除
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(400.0),
+                        height: Val::Px(40.0),
+                        padding: UiRect::horizontal(Val::Px(10.0)),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                    ..default()
+                }).with_children(|parent| {
+                    parent.spawn(menu_text(format!("{}_", menu.new_world_name), 20.0, Color::WHITE));
+                });
+                let mode = format!(
+                    "{}: {}",
+                    localization.get("ui.create_world.game_mode"),
+                    localization.get(menu.new_world_mode.localization_key())
+                );
+                create_pause_button(parent, &mode, "game_mode");
+                create_pause_button(parent, localization.get("ui.create_world.create"), "create");
+                create_pause_button(parent, localization.get("ui.create_world.cancel"), "cancel");
+            }
+        }
+
+        if let Some(error) = &menu.error {
+            parent.spawn(menu_text(error.clone(), 16.0, Color::rgb(1.0, 0.45, 0.45)));
+        }
+    });
+}
+
+fn spawn_world_list(parent: &mut ChildBuilder, menu: &MainMenu, world_manager: &WorldManager, localization: &LocalizationManager) {
+    let worlds = sorted_worlds(world_manager);
+    parent.spawn(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(8.0),
+            max_height: Val::Percent(50.0),
+            overflow: Overflow::clip_y(),
+            ..default()
+        },
+        ..default()
+    }).with_children(|parent| {
+        if worlds.is_empty() {
+            parent.spawn(menu_text(localization.get("ui.world_selection.no_worlds"), 16.0, Color::GRAY));
+        }
+        for world in worlds {
+            let selected = menu.selected.as_deref() == Some(world.name.as_str());
+            let background = if selected { Color::rgba(0.35, 0.45, 0.35, 0.9) } else { Color::rgba(0.3, 0.3, 0.3, 0.9) };
+            parent.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(400.0),
+                        height: Val::Px(50.0),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: background.into(),
+                    ..default()
+                },
+                WorldEntry(world.name.clone()),
+            )).with_children(|parent| {
+                parent.spawn(menu_text(world.name.clone(), 18.0, Color::WHITE));
+                let details = format!("{}  {}", localization.get(world.game_mode.localization_key()), world.last_played);
+                parent.spawn(menu_text(details, 13.0, Color::rgb(0.7, 0.7, 0.7)));
+            });
+        }
+    });
+
+    parent.spawn(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(15.0),
+            ..default()
+        },
+        ..default()
+    }).with_children(|parent| {
+        if menu.selected.is_some() {
+            create_pause_button(parent, localization.get("ui.world_selection.play"), "play");
+            let delete = if menu.confirm_delete { "ui.world_selection.confirm_delete" } else { "ui.world_selection.delete" };
+            create_pause_button(parent, localization.get(delete), "delete");
+        }
+        create_pause_button(parent, localization.get("ui.world_selection.create_new"), "create_new");
+        create_pause_button(parent, localization.get("ui.world_selection.back"), "back");
+    });
+}
+
+/// 任意按钮按下时播放点击音效
+fn play_menu_clicks(interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>, mut sounds: EventWriter<SoundEvent>) {
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            sounds.send(SoundEvent::click());
+        }
+    }
+}
+
+/// 标题页的按钮
+fn title_button_system(
+    interaction_query: ButtonPresses,
+    mut menu: ResMut<MainMenu>,
+    mut game_settings: ResMut<GameSettings>,
+    mut app_exit_events: EventWriter<bevy::app::AppExit>,
+) {
+    for (interaction, name) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match name.as_str() {
+            "singleplayer" => menu.show(MenuPage::Worlds),
+            "settings" => game_settings.show_settings = true,
+            "quit" => {
+                app_exit_events.send(bevy::app::AppExit);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 世界列表和创建世界页面的按钮
+fn world_button_system(
+    interaction_query: ButtonPresses,
+    entries: Query<(&Interaction, &WorldEntry), Changed<Interaction>>,
+    mut menu: ResMut<MainMenu>,
+    mut world_manager: ResMut<WorldManager>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut generator_config: ResMut<WorldGeneratorConfig>,
+    localization: Res<LocalizationManager>,
+) {
+    for (interaction, entry) in &entries {
+        if *interaction == Interaction::Pressed {
+            menu.selected = Some(entry.0.clone());
+            menu.confirm_delete = false;
+        }
+    }
+    for (interaction, name) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match name.as_str() {
+            "back" | "cancel" => {
+                let page = if menu.page == MenuPage::CreateWorld { MenuPage::Worlds } else { MenuPage::Title };
+                menu.show(page);
+            }
+            "create_new" => {
+                menu.new_world_name = unique_world_name(localization.get("ui.create_world.default_name"), &world_manager);
+                menu.new_world_mode = GameMode::default();
+                menu.show(MenuPage::CreateWorld);
+            }
+            "game_mode" => menu.new_world_mode = next_game_mode(menu.new_world_mode),
+            "play" => {
+                if let Some(world_name) = menu.selected.clone() {
+                    enter_world(world_name, &mut menu, &mut world_manager, &mut next_state, &mut generator_config, &localization);
+                }
+            }
+            "delete" => {
+                let Some(world_name) = menu.selected.clone() else { continue };
+                if !menu.confirm_delete {
+                    menu.confirm_delete = true;
+                    continue;
+                }
+                if let Err(e) = world_manager.delete_world(&world_name) {
+                    error!("Failed to delete world {}: {}", world_name, e);
+                    menu.error = Some(e.to_string());
+                }
+                menu.selected = None;
+                menu.confirm_delete = false;
+            }
+            "create" => create_world(&mut menu, &mut world_manager, &mut next_state, &mut generator_config, &localization),
+            _ => {}
+        }
+    }
+}
+
+/// 创建世界后直接进入
+fn create_world(
+    menu: &mut MainMenu,
+    world_manager: &mut WorldManager,
+    next_state: &mut NextState<GameState>,
+    generator_config: &mut WorldGeneratorConfig,
+    localization: &LocalizationManager,
+) {
+    let name = menu.new_world_name.trim().to_string();
+    if name.is_empty() {
+        return;
+    }
+    if world_manager.worlds.contains_key(&name) || world_manager.saves_directory.join(&name).exists() {
+        menu.error = Some(localization.get("ui.create_world.name_taken").to_string());
+        return;
+    }
+    let info = WorldInfo { name: name.clone(), seed: mc_common::random_seed(), game_mode: menu.new_world_mode, ..default() };
+    if let Err(e) = world_manager.create_world(info) {
+        error!("Failed to create world {}: {}", name, e);
+        menu.error = Some(format!("{}: {}", localization.get("ui.create_world.create_failed"), e));
+        return;
+    }
+    enter_world(name, menu, world_manager, next_state, generator_config, localization);
+}
+
+/// 选择世界，检查存档版本（必要时迁移），用世界的种子配置生成器后进入加载状态
+fn enter_world(
+    world_name: String,
+    menu: &mut MainMenu,
+    world_manager: &mut WorldManager,
+    next_state: &mut NextState<GameState>,
+    generator_config: &mut WorldGeneratorConfig,
+    localization: &LocalizationManager,
+) {
+    world_manager.select_world(world_name.clone());
+    if let Err(e) = world_manager.prepare_current_world() {
+        error!("{}", e);
+        world_manager.current_world = None;
+        menu.error = Some(format!("{}: {}", localization.get("ui.world_selection.open_failed"), e));
+        return;
+    }
+    if let Some(seed) = world_manager.current_world_seed() {
+        generator_config.seed = seed;
+    }
+    info!("Entering world {} from the main menu", world_name);
+    next_state.set(GameState::Loading);
+}
+
+/// 创建世界页面中输入世界名称
+fn type_world_name(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard: Res<Input<KeyCode>>,
+    mut menu: ResMut<MainMenu>,
+    game_settings: Res<GameSettings>,
+) {
+    if menu.page != MenuPage::CreateWorld || game_settings.show_settings {
+        characters.clear();
+        return;
+    }
+    for event in characters.read() {
+        if valid_world_name_char(event.char) && menu.new_world_name.chars().count() < MAX_WORLD_NAME_CHARS {
+            menu.new_world_name.push(event.char);
+        }
+    }
+    if keyboard.just_pressed(KeyCode::Back) {
+        menu.new_world_name.pop();
+    }
+}
+
+/// Esc 先关闭设置窗口，再返回上一页
+fn main_menu_escape(keyboard: Res<Input<KeyCode>>, mut menu: ResMut<MainMenu>, mut game_settings: ResMut<GameSettings>) {
+    if !keyboard.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if game_settings.show_settings {
+        game_settings.show_settings = false;
+        return;
+    }
+    match menu.page {
+        MenuPage::Title => {}
+        MenuPage::Worlds => menu.show(MenuPage::Title),
+        MenuPage::CreateWorld => menu.show(MenuPage::Worlds),
+    }
+}
+
+/// 设置窗口打开时隐藏主菜单，与暂停菜单相同
+fn hide_main_menu_behind_settings(game_settings: Res<GameSettings>, mut menu_query: Query<&mut Style, With<MainMenuUI>>) {
+    let display = if game_settings.show_settings { Display::None } else { Display::Flex };
+    for mut style in menu_query.iter_mut() {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_world_names_skip_existing_worlds() {
+        let saves = std::env::temp_dir().join(format!("main_menu_test_{}", std::process::id()));
+        let mut world_manager = WorldManager { saves_directory: saves.clone(), ..default() };
+        assert_eq!(unique_world_name("New World", &world_manager), "New World");
+        for name in ["New World", "New World 2"] {
+            world_manager.worlds.insert(name.to_string(), WorldInfo { name: name.to_string(), ..default() });
+        }
+        assert_eq!(unique_world_name("New World", &world_manager), "New World 3");
+        assert!("My World 1".chars().all(valid_world_name_char));
+        assert!(!valid_world_name_char('/') && !valid_world_name_char(':'));
+        assert!(!saves.exists());
+    }
+}
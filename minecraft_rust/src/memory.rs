@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::hash::Hash;
+use crate::ui::GameSettings;
+use crate::world::chunk::Chunk;
+use crate::world::chunk_loader::{ChunkLoaderConfig, ChunkLoaderSet};
+
+pub const BYTES_PER_MB: usize = 1024 * 1024;
+/// 超出软上限后，估计占用降到上限的这一比例以下才取消区块数限制，避免在上限附近反复加载和卸载
+const RECOVER_FRACTION: f64 = 0.85;
+/// 按内存限制区块数时至少保留的区块
+const MIN_CHUNK_LIMIT: usize = 64;
+
+/// 一类对象各自的估计大小和总和，对象变化时替换，移除时减去
+struct Tracked<K> {
+    sizes: HashMap<K, usize>,
+    total: usize,
+}
+
+impl<K> Default for Tracked<K> {
+    fn default() -> Self {
+        Self { sizes: HashMap::new(), total: 0 }
+    }
+}
+
+impl<K: Eq + Hash> Tracked<K> {
+    fn set(&mut self, key: K, bytes: usize) {
+        if let Some(old) = self.sizes.insert(key, bytes) {
+            self.total -= old;
+        }
+        self.total += bytes;
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some(old) = self.sizes.remove(key) {
+            self.total -= old;
+        }
+    }
+}
+
+/// 内存占用的估计：区块数据、网格（顶点和索引缓冲区）和纹理。
+/// 大小在对象加入或变化时记录，移除时减去，不需要精确到字节，但区块全部卸载后会回到原来的值
+#[derive(Resource, Default)]
+pub struct MemoryStats {
+    chunks: Tracked<Entity>,
+    meshes: Tracked<AssetId<Mesh>>,
+    textures: Tracked<AssetId<Image>>,
+    /// 估计占用超出软上限时按内存算出的区块数上限
+    pub chunk_limit: Option<usize>,
+}
+
+impl MemoryStats {
+    pub fn chunk_bytes(&self) -> usize {
+        self.chunks.total
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.sizes.len()
+    }
+
+    pub fn mesh_bytes(&self) -> usize {
+        self.meshes.total
+    }
+
+    pub fn mesh_count(&self) -> usize {
+        self.meshes.sizes.len()
+    }
+
+    pub fn texture_bytes(&self) -> usize {
+        self.textures.total
+    }
+
+    pub fn texture_count(&self) -> usize {
+        self.textures.sizes.len()
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.chunks.total + self.meshes.total + self.textures.total
+    }
+
+    /// 软上限内能容纳的区块数：扣除纹理后，按当前每个区块平均的数据和网格占用计算
+    fn chunks_within(&self, soft_cap: usize) -> usize {
+        let per_chunk = (self.chunks.total + self.meshes.total) / self.chunk_count().max(1);
+        let budget = soft_cap.saturating_sub(self.textures.total);
+        (budget / per_chunk.max(1)).max(MIN_CHUNK_LIMIT)
+    }
+
+    /// 根据软上限更新区块数上限：超出时按内存计算，降到 `RECOVER_FRACTION` 以下时取消，之间保持不变
+    fn update_chunk_limit(&mut self, soft_cap: usize) {
+        let total = self.total_bytes();
+        if total > soft_cap {
+            self.chunk_limit = Some(self.chunks_within(soft_cap));
+        } else if (total as f64) < soft_cap as f64 * RECOVER_FRACTION {
+            self.chunk_limit = None;
+        }
+    }
+}
+
+/// 网格上传到显卡的顶点和索引缓冲区大小
+fn mesh_bytes(mesh: &Mesh) -> usize {
+    let vertices: usize = mesh.attributes().map(|(_, values)| values.get_bytes().len()).sum();
+    vertices + mesh.get_index_buffer_bytes().map_or(0, <[u8]>::len)
+}
+
+pub struct MemoryPlugin;
+
+impl Plugin for MemoryPlugin {
+    fn build(&self, app: &mut App) {
+        // 不限制游戏状态：离开世界时卸载的区块和网格也要减去
+        app.init_resource::<MemoryStats>()
+           .add_systems(Update, (
+               (track_chunk_memory, track_mesh_memory, track_texture_memory),
+               update_memory_limit,
+           ).chain().before(ChunkLoaderSet));
+    }
+}
+
+fn track_chunk_memory(
+    mut stats: ResMut<MemoryStats>,
+    chunks: Query<(Entity, &Chunk), Changed<Chunk>>,
+    mut removed: RemovedComponents<Chunk>,
+) {
+    for entity in removed.read() {
+        stats.chunks.remove(&entity);
+    }
+    for (entity, chunk) in chunks.iter() {
+        stats.chunks.set(entity, chunk.memory_bytes());
+    }
+}
+
+/// 按资源事件记录每个资源的大小
+fn track_assets<A: Asset>(tracked: &mut Tracked<AssetId<A>>, events: &mut EventReader<AssetEvent<A>>, assets: &Assets<A>, size: impl Fn(&A) -> usize) {
+    for event in events.read() {
+        match *event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                if let Some(asset) = assets.get(id) {
+                    tracked.set(id, size(asset));
+                }
+            }
+            AssetEvent::Removed { id } => tracked.remove(&id),
+            AssetEvent::LoadedWithDependencies { .. } => {}
+        }
+    }
+}
+
+fn track_mesh_memory(mut stats: ResMut<MemoryStats>, mut events: EventReader<AssetEvent<Mesh>>, meshes: Res<Assets<Mesh>>) {
+    track_assets(&mut stats.meshes, &mut events, &meshes, mesh_bytes);
+}
+
+fn track_texture_memory(mut stats: ResMut<MemoryStats>, mut events: EventReader<AssetEvent<Image>>, images: Res<Assets<Image>>) {
+    track_assets(&mut stats.textures, &mut events, &images, |image| image.data.len());
+}
+
+/// 估计占用超出设置中的软上限时降低区块加载器的最大区块数，加载和卸载系统都按它执行
+fn update_memory_limit(
+    mut stats: ResMut<MemoryStats>,
+    loader_config: Option<ResMut<ChunkLoaderConfig>>,
+    game_settings: Option<Res<GameSettings>>,
+) {
+    let (Some(mut loader_config), Some(settings)) = (loader_config, game_settings) else { return };
+    stats.update_chunk_limit(settings.memory_soft_cap_mb as usize * BYTES_PER_MB);
+    if loader_config.memory_chunk_limit == stats.chunk_limit {
+        return;
+    }
+    match stats.chunk_limit {
+        Some(limit) => info!(
+            "Estimated memory {} MB exceeds soft cap {} MB, limiting loaded chunks to {}",
+            stats.total_bytes() / BYTES_PER_MB, settings.memory_soft_cap_mb, limit
+        ),
+        None => info!("Estimated memory back under the soft cap, chunk limit restored"),
+    }
+    loader_config.memory_chunk_limit = stats.chunk_limit;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::AssetPlugin;
+
+    #[test]
+    fn estimates_return_to_baseline_after_chunks_unload() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+           .init_asset::<Mesh>()
+           .init_asset::<Image>()
+           .configure_sets(Update, ChunkLoaderSet)
+           .add_plugins(MemoryPlugin);
+        app.update();
+        let baseline = app.world.resource::<MemoryStats>().total_bytes();
+
+        let mesh = Mesh::from(shape::Cube { size: 1.0 });
+        let expected_mesh_bytes = mesh_bytes(&mesh) * 3;
+        let mut chunks = Vec::new();
+        for x in 0..3 {
+            let handle = app.world.resource_mut::<Assets<Mesh>>().add(mesh.clone());
+            let chunk = app.world.spawn(Chunk::new(IVec3::new(x, 0, 0))).id();
+            let child = app.world.spawn(handle).id();
+            app.world.entity_mut(chunk).add_child(child);
+            chunks.push(chunk);
+        }
+        app.update();
+        app.update();
+        let stats = app.world.resource::<MemoryStats>();
+        assert_eq!(stats.chunk_count(), 3);
+        assert!(stats.chunk_bytes() >= 3 * Chunk::COUNT * 2);
+        assert_eq!(stats.mesh_bytes(), expected_mesh_bytes);
+
+        // 区块变化后重新估计，不会重复计入
+        app.world.get_mut::<Chunk>(chunks[0]).unwrap().solid_blocks.reserve(100);
+        app.update();
+        assert_eq!(app.world.resource::<MemoryStats>().chunk_count(), 3);
+
+        for chunk in chunks {
+            app.world.entity_mut(chunk).despawn_recursive();
+        }
+        app.update();
+        app.update();
+        let stats = app.world.resource::<MemoryStats>();
+        assert_eq!(stats.chunk_count(), 0);
+        assert_eq!(stats.mesh_count(), 0);
+        assert_eq!(stats.total_bytes(), baseline);
+    }
+
+    #[test]
+    fn chunk_limit_engages_above_the_soft_cap_and_clears_below_the_recover_fraction() {
+        let mut stats = MemoryStats::default();
+        for i in 0..200 {
+            stats.chunks.set(Entity::from_raw(i), BYTES_PER_MB);
+        }
+        stats.update_chunk_limit(300 * BYTES_PER_MB);
+        assert_eq!(stats.chunk_limit, None);
+        stats.update_chunk_limit(100 * BYTES_PER_MB);
+        assert_eq!(stats.chunk_limit, Some(100));
+
+        // 卸载到上限以下但仍高于恢复比例时保持限制
+        for i in 0..110 {
+            stats.chunks.remove(&Entity::from_raw(i));
+        }
+        stats.update_chunk_limit(100 * BYTES_PER_MB);
+        assert_eq!(stats.chunk_limit, Some(100));
+        for i in 110..120 {
+            stats.chunks.remove(&Entity::from_raw(i));
+        }
+        stats.update_chunk_limit(100 * BYTES_PER_MB);
+        assert_eq!(stats.chunk_limit, None);
+    }
+}
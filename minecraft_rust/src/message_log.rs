@@ -0,0 +1,162 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use crate::command::CommandLine;
+use crate::game_state::GameState;
+use crate::hud::hud_visible;
+use crate::notifications::Severity;
+use crate::scripting::ScriptEngine;
+
+/// 保留的消息条数上限，超出后丢弃最早的
+const MAX_ENTRIES: usize = 500;
+/// 命令行关闭时新消息显示的时长，最后 `FADE_SECONDS` 淡出
+const VISIBLE_SECONDS: f32 = 10.0;
+const FADE_SECONDS: f32 = 1.0;
+/// 命令行关闭时最多显示的最近消息条数
+const MAX_RECENT: usize = 10;
+/// 命令行打开时历史记录的最大高度（逻辑像素）
+const HISTORY_HEIGHT: f32 = 240.0;
+
+struct LogEntry {
+    text: String,
+    severity: Severity,
+    added: Instant,
+}
+
+/// 左下角的消息记录：命令输出、脚本 `print` 和游戏事件（死亡、保存世界、玩家加入等）。
+/// 与右下角的通知不同，消息会保留下来，打开命令行时可以滚动查看全部历史。文本由调用方先经过本地化
+#[derive(Resource, Default)]
+pub struct MessageLog {
+    entries: VecDeque<LogEntry>,
+}
+
+impl MessageLog {
+    pub fn push(&mut self, text: impl Into<String>, severity: Severity) {
+        self.push_at(text.into(), severity, Instant::now());
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(text, Severity::Info);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(text, Severity::Error);
+    }
+
+    fn push_at(&mut self, text: String, severity: Severity, added: Instant) {
+        if self.entries.len() >= MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry { text, severity, added });
+    }
+
+    /// 在 `now` 时仍在显示的最近消息，按时间先后排列
+    fn recent(&self, now: Instant) -> impl Iterator<Item = &LogEntry> {
+        let visible = Duration::from_secs_f32(VISIBLE_SECONDS);
+        let count = self.entries.iter().rev().take(MAX_RECENT).take_while(|entry| now.duration_since(entry.added) < visible).count();
+        self.entries.range(self.entries.len() - count..)
+    }
+}
+
+impl LogEntry {
+    /// 命令行关闭时的不透明度：最后 `FADE_SECONDS` 秒从 1 降到 0
+    fn alpha(&self, now: Instant) -> f32 {
+        ((VISIBLE_SECONDS - now.duration_since(self.added).as_secs_f32()) / FADE_SECONDS).clamp(0.0, 1.0)
+    }
+
+    fn color(&self, alpha: f32) -> egui::Color32 {
+        let [r, g, b, _] = self.severity.color().as_rgba_u8();
+        egui::Color32::from_rgba_unmultiplied(r, g, b, (alpha * 255.0) as u8)
+    }
+}
+
+pub struct MessageLogPlugin;
+
+impl Plugin for MessageLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MessageLog>()
+           .add_systems(Update, (
+               collect_script_output,
+               // 没有新消息且命令行关闭时不绘制
+               message_log_ui
+                   .after(collect_script_output)
+                   .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused)))
+                   .run_if(hud_visible)
+                   .run_if(message_log_shown),
+           ));
+    }
+}
+
+fn message_log_shown(log: Res<MessageLog>, command_line: Res<CommandLine>) -> bool {
+    command_line.open || log.recent(Instant::now()).next().is_some()
+}
+
+/// 把脚本 `print` 的输出放进消息记录
+fn collect_script_output(engine: Option<Res<ScriptEngine>>, mut log: ResMut<MessageLog>) {
+    let Some(engine) = engine else { return };
+    for line in engine.take_output() {
+        log.info(line);
+    }
+}
+
+/// 命令行打开时在输入框上方显示可滚动的全部历史，关闭时只显示最近的消息并逐渐淡出
+fn message_log_ui(mut contexts: EguiContexts, log: Res<MessageLog>, command_line: Res<CommandLine>) {
+    let now = Instant::now();
+    let area = egui::Area::new(egui::Id::new("message_log"))
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(8.0, -44.0))
+        .interactable(command_line.open);
+    area.show(contexts.ctx_mut(), |ui| {
+        ui.set_max_width(480.0);
+        if command_line.open {
+            egui::Frame::none()
+                .fill(egui::Color32::from_black_alpha(120))
+                .inner_margin(egui::Margin::same(4.0))
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(HISTORY_HEIGHT)
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for entry in log.entries.iter() {
+                                ui.label(egui::RichText::new(entry.text.as_str()).color(entry.color(1.0)));
+                            }
+                        });
+                });
+        } else {
+            for entry in log.recent(now) {
+                let alpha = entry.alpha(now);
+                ui.label(egui::RichText::new(entry.text.as_str())
+                    .color(entry.color(alpha))
+                    .background_color(egui::Color32::from_black_alpha((100.0 * alpha) as u8)));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_recent_messages_stay_on_screen_and_history_is_capped() {
+        let start = Instant::now();
+        let mut log = MessageLog::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            log.push_at(format!("message {i}"), Severity::Info, start);
+        }
+        assert_eq!(log.entries.len(), MAX_ENTRIES);
+        assert_eq!(log.entries[0].text, "message 5");
+
+        let later = start + Duration::from_secs_f32(VISIBLE_SECONDS - 2.0);
+        log.push_at("you died".to_string(), Severity::Error, later);
+        let recent: Vec<_> = log.recent(later).map(|entry| entry.text.as_str()).collect();
+        assert_eq!(recent.len(), MAX_RECENT);
+        assert_eq!(recent.last(), Some(&"you died"));
+        assert_eq!(log.recent(later).next().unwrap().alpha(later), 1.0);
+
+        // 之前的消息过期，只剩最后一条
+        let expired = start + Duration::from_secs_f32(VISIBLE_SECONDS + 0.5);
+        let recent: Vec<_> = log.recent(expired).map(|entry| entry.text.as_str()).collect();
+        assert_eq!(recent, ["you died"]);
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use crate::inventory::{ItemStack, ItemType, ToolType};
+use crate::world::chunk::BlockId;
+
+/// 树叶掉落树苗的几率，与原版相同
+pub const SAPLING_DROP_CHANCE: f32 = 0.05;
+/// 硬度达到这个值的方块（基岩、流体）无法破坏
+pub const UNBREAKABLE_HARDNESS: f32 = 100.0;
+/// 能采集方块时的挖掘时间系数（秒 / 硬度）
+const HARVEST_FACTOR: f32 = 1.5;
+/// 工具等级不够、无法采集时的挖掘时间系数
+const NO_HARVEST_FACTOR: f32 = 5.0;
+
+/// 生存模式下正在挖掘的方块：按住左键时累积进度，进度达到 1 时方块被破坏，
+/// 松开左键或准星移到别的方块时重新开始
+#[derive(Resource, Default)]
+pub struct BlockBreaking {
+    pub target: Option<IVec3>,
+    /// 0..1
+    pub progress: f32,
+}
+
+impl BlockBreaking {
+    pub fn reset(&mut self) {
+        self.target = None;
+        self.progress = 0.0;
+    }
+}
+
+/// 镐子对这个方块是否有效（加速挖掘）
+pub fn is_effective(tool: ToolType, block: BlockId) -> bool {
+    match tool {
+        ToolType::WoodenPickaxe | ToolType::StonePickaxe | ToolType::IronPickaxe | ToolType::DiamondPickaxe => {
+            matches!(block, BlockId::Stone | BlockId::Obsidian | BlockId::CraftingTable)
+        }
+    }
+}
+
+/// 采集方块所需的最低工具等级，None 表示徒手即可
+pub fn required_tier(block: BlockId) -> Option<u8> {
+    match block {
+        BlockId::Stone => Some(1),
+        BlockId::Obsidian => Some(4),
+        _ => None,
+    }
+}
+
+/// 用 `tool`（None 为空手）破坏方块时能否采集它：生存模式下只有能采集的方块会掉落
+pub fn can_harvest(block: BlockId, tool: Option<ToolType>) -> bool {
+    let effective = tool.filter(|&tool| is_effective(tool, block));
+    required_tier(block).is_none_or(|tier| effective.is_some_and(|tool| tool.tier() >= tier))
+}
+
+/// 掉落表：采集方块后掉落的物品，`roll` 为 [0, 1) 的随机数。树叶只按几率掉落树苗，其余方块掉落自身
+pub fn block_drop(block: BlockId, roll: f32) -> Option<ItemStack> {
+    match block {
+        BlockId::Air => None,
+        BlockId::Leaves => (roll < SAPLING_DROP_CHANCE).then(|| ItemStack::new(ItemType::Block(BlockId::Sapling), 1)),
+        _ => Some(ItemStack::new(ItemType::Block(block), 1)),
+    }
+}
+
+/// 用 `tool`（None 为空手）挖掘硬度为 `hardness` 的方块所需的秒数，方块无法破坏时返回 None。
+/// 与原版相同：能采集时为 硬度 × 1.5 / 速度倍数，工具等级不够时为 硬度 × 5 / 速度倍数
+pub fn break_time_seconds(block: BlockId, hardness: f32, tool: Option<ToolType>) -> Option<f32> {
+    if !(0.0..UNBREAKABLE_HARDNESS).contains(&hardness) {
+        return None;
+    }
+    let effective = tool.filter(|&tool| is_effective(tool, block));
+    let speed = effective.map_or(1.0, ToolType::speed_multiplier);
+    let factor = if can_harvest(block, tool) { HARVEST_FACTOR } else { NO_HARVEST_FACTOR };
+    Some(hardness * factor / speed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_time(block: BlockId, hardness: f32, tool: Option<ToolType>, expected: f32) {
+        let time = break_time_seconds(block, hardness, tool).unwrap();
+        assert!((time - expected).abs() < 1e-4, "{:?} with {:?}: {} != {}", block, tool, time, expected);
+    }
+
+    #[test]
+    fn break_time_depends_on_tool_speed_and_tier() {
+        // 徒手挖石头无法采集，比木镐慢得多；更好的镐子按速度倍数缩短时间
+        assert_time(BlockId::Stone, 2.0, None, 10.0);
+        assert_time(BlockId::Stone, 2.0, Some(ToolType::WoodenPickaxe), 1.5);
+        assert_time(BlockId::Stone, 2.0, Some(ToolType::StonePickaxe), 0.75);
+        assert_time(BlockId::Stone, 2.0, Some(ToolType::DiamondPickaxe), 0.375);
+
+        // 镐子对泥土无效，和空手一样
+        assert_time(BlockId::Dirt, 1.0, None, 1.5);
+        assert_time(BlockId::Dirt, 1.0, Some(ToolType::DiamondPickaxe), 1.5);
+        assert_time(BlockId::Sand, 0.5, Some(ToolType::IronPickaxe), 0.75);
+
+        // 黑曜石只有钻石镐能采集，铁镐虽然加速但按无法采集计算
+        assert_time(BlockId::Obsidian, 50.0, Some(ToolType::DiamondPickaxe), 9.375);
+        assert_time(BlockId::Obsidian, 50.0, Some(ToolType::IronPickaxe), 50.0 * 5.0 / 6.0);
+        assert_time(BlockId::Obsidian, 50.0, None, 250.0);
+
+        // 火把瞬间破坏，基岩和流体无法破坏
+        assert_time(BlockId::Torch, 0.0, None, 0.0);
+        assert_eq!(break_time_seconds(BlockId::Bedrock, 999.0, Some(ToolType::DiamondPickaxe)), None);
+        assert_eq!(break_time_seconds(BlockId::Water, 100.0, None), None);
+    }
+
+    #[test]
+    fn leaves_only_sometimes_drop_a_sapling() {
+        let sapling = Some(ItemStack::new(ItemType::Block(BlockId::Sapling), 1));
+        assert_eq!(block_drop(BlockId::Leaves, 0.01), sapling);
+        assert_eq!(block_drop(BlockId::Leaves, 0.5), None);
+        assert_eq!(block_drop(BlockId::Sapling, 0.5), sapling);
+        assert_eq!(block_drop(BlockId::Log, 0.99), Some(ItemStack::new(ItemType::Block(BlockId::Log), 1)));
+    }
+}
@@ -0,0 +1,293 @@
+use bevy::prelude::*;
+use crate::controller::{self, FirstPersonController, Hittable, JUMP_VELOCITY};
+use crate::random::random_unit;
+use crate::game_state::{GameState, WorldManager, WorldState};
+use crate::health::{DamageEvent, Health, PlayerDamageEvent};
+use crate::net::client::NetClient;
+use crate::ui::GameSettings;
+use crate::world::access::{world_pos_to_chunk_coord, WorldView};
+use crate::world::chunk::{BlockId, Chunk};
+use crate::world::storage::ChunkStorage;
+
+/// 僵尸的碰撞箱（宽、高、宽），与玩家相同
+pub const ZOMBIE_SIZE: Vec3 = Vec3::new(0.6, 1.8, 0.6);
+/// 僵尸的满生命值
+pub const ZOMBIE_MAX_HEALTH: f32 = 20.0;
+/// 僵尸和玩家的眼睛离脚底的高度，视线检测在两者的眼睛之间进行
+const ZOMBIE_EYE_HEIGHT: f32 = 1.6;
+/// 行走速度（格/秒）和水平加速度（格/秒²），被击退后逐渐恢复行走
+const ZOMBIE_SPEED: f32 = 2.3;
+const ZOMBIE_ACCELERATION: f32 = 10.0;
+/// 能发现玩家的距离（格）
+const SIGHT_RANGE: f32 = 16.0;
+/// 近战攻击的水平距离（格）、伤害和冷却时间（秒）
+const ATTACK_RANGE: f32 = 1.2;
+const ATTACK_DAMAGE: f32 = 3.0;
+const ATTACK_COOLDOWN: f32 = 1.0;
+/// 击退速度（格/秒）：水平方向和向上的部分
+const KNOCKBACK_SPEED: f32 = 5.0;
+const KNOCKBACK_LIFT: f32 = 4.0;
+
+/// 每隔多少秒尝试生成一次
+const SPAWN_INTERVAL: f32 = 3.0;
+/// 生成位置离玩家的水平距离范围（格）
+const SPAWN_DISTANCE: (f32, f32) = (16.0, 40.0);
+/// 在玩家高度上下这么多格内寻找地面
+const SPAWN_HEIGHT_RANGE: i32 = 16;
+/// 同时存在的僵尸上限
+const MAX_ZOMBIES: usize = 8;
+/// 离玩家超过这个距离（格）的僵尸直接消失
+const DESPAWN_DISTANCE: f32 = 64.0;
+
+/// 夜晚出现的敌对生物：看见玩家时径直走向玩家，遇到一格高的障碍会跳过去，
+/// 贴近后进行近战攻击。生命值使用 `Health` 组件，不随存档保存
+#[derive(Component, Default)]
+pub struct Zombie {
+    pub velocity: Vec3,
+    /// 最后一次看见玩家时玩家的位置，看不见玩家后走到这里为止
+    target: Option<Vec3>,
+    attack_cooldown: f32,
+}
+
+/// 僵尸的包围盒：实体位置在脚底，包围盒中心在碰撞箱中间
+fn zombie_hittable() -> Hittable {
+    Hittable { half_extents: ZOMBIE_SIZE / 2.0, offset: Vec3::Y * ZOMBIE_SIZE.y / 2.0 }
+}
+
+pub struct MobPlugin;
+
+impl Plugin for MobPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            spawn_zombies,
+            update_zombies.after(spawn_zombies),
+            apply_zombie_damage,
+            despawn_far_zombies,
+        ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 在脚底位置生成一只僵尸
+pub fn spawn_zombie(commands: &mut Commands, position: Vec3) {
+    commands.spawn((
+        Zombie::default(),
+        Health { current: ZOMBIE_MAX_HEALTH, max: ZOMBIE_MAX_HEALTH },
+        zombie_hittable(),
+        SpatialBundle::from_transform(Transform::from_translation(position)),
+    ));
+}
+
+/// 在 `top` 到 `bottom` 之间从上往下寻找能站立的位置：实心方块上方有两格空气。
+/// 返回站立的高度（地面方块上方一格），`block` 返回 None 表示区块未加载
+fn find_spawn_height(top: i32, bottom: i32, block: impl Fn(i32) -> Option<BlockId>) -> Option<i32> {
+    (bottom..=top).rev().find(|&y| {
+        block(y).is_some_and(BlockId::is_solid)
+            && block(y + 1) == Some(BlockId::Air)
+            && block(y + 2) == Some(BlockId::Air)
+    }).map(|y| y + 1)
+}
+
+/// 夜晚在玩家周围的地面上生成僵尸，`do_mob_spawning` 游戏规则关闭时不生成。生物只在单人游戏中存在
+fn spawn_zombies(
+    mut commands: Commands,
+    player: Query<&Transform, With<FirstPersonController>>,
+    zombies: Query<(), With<Zombie>>,
+    chunks: Query<&Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+    world_state: Res<WorldState>,
+    time: Res<Time>,
+    net: Option<Res<NetClient>>,
+    mut elapsed: Local<f32>,
+    mut attempts: Local<u64>,
+) {
+    if net.is_some_and(|net| net.is_remote()) || !world_state.is_night() || !world_state.rule_enabled("do_mob_spawning") {
+        return;
+    }
+    *elapsed += time.delta_seconds();
+    if *elapsed < SPAWN_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+    let Ok(player) = player.get_single() else { return };
+    if zombies.iter().count() >= MAX_ZOMBIES {
+        return;
+    }
+
+    *attempts += 1;
+    let seed = time.elapsed_seconds_f64().to_bits();
+    let angle = random_unit(seed, *attempts * 2) * std::f32::consts::TAU;
+    let distance = SPAWN_DISTANCE.0 + random_unit(seed, *attempts * 2 + 1) * (SPAWN_DISTANCE.1 - SPAWN_DISTANCE.0);
+    let column = (player.translation + Vec3::new(angle.cos(), 0.0, angle.sin()) * distance).floor().as_ivec3();
+    let world = WorldView::new(&chunk_storage, &chunks);
+    let height = find_spawn_height(column.y + SPAWN_HEIGHT_RANGE, column.y - SPAWN_HEIGHT_RANGE, |y| {
+        world.block(IVec3::new(column.x, y, column.z))
+    });
+    if let Some(y) = height {
+        spawn_zombie(&mut commands, Vec3::new(column.x as f32 + 0.5, y as f32, column.z as f32 + 0.5));
+    }
+}
+
+/// 两点之间没有可选中的方块阻挡（使用与准星相同的 DDA 射线）
+fn has_line_of_sight(from: Vec3, to: Vec3, world: &WorldView) -> bool {
+    let offset = to - from;
+    let distance = offset.length();
+    distance < f32::EPSILON
+        || controller::raycast_for_blocks(from, offset / distance, distance, world).is_none()
+}
+
+/// 僵尸的移动和攻击：与玩家共用重力和碰撞（见 `controller::move_with_collisions`）。
+/// 创造和旁观模式的玩家不会被发现
+fn update_zombies(
+    mut zombies: Query<(&mut Transform, &mut Zombie), Without<FirstPersonController>>,
+    player: Query<&Transform, With<FirstPersonController>>,
+    chunks: Query<&Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+    // 重力由设置和 `gravity_scale` 游戏规则共同决定
+    (game_settings, world_state): (Res<GameSettings>, Res<WorldState>),
+    world_manager: Res<WorldManager>,
+    time: Res<Time>,
+    mut damage: EventWriter<PlayerDamageEvent>,
+) {
+    let Ok(player) = player.get_single() else { return };
+    let delta_time = time.delta_seconds();
+    let player_visible = world_manager.current_game_mode().takes_damage();
+    let player_eye = player.translation + Vec3::Y * ZOMBIE_EYE_HEIGHT;
+    let world = WorldView::new(&chunk_storage, &chunks);
+
+    for (mut transform, mut zombie) in zombies.iter_mut() {
+        let position = transform.translation;
+        zombie.attack_cooldown = (zombie.attack_cooldown - delta_time).max(0.0);
+
+        let eye = position + Vec3::Y * ZOMBIE_EYE_HEIGHT;
+        let sees_player = player_visible
+            && eye.distance(player_eye) <= SIGHT_RANGE
+            && has_line_of_sight(eye, player_eye, &world);
+        if sees_player {
+            zombie.target = Some(player.translation);
+        }
+
+        // 走向玩家（或最后看见玩家的位置），到达后停下
+        let to_target = zombie.target.map_or(Vec2::ZERO, |target| (target - position).xz());
+        let direction = if to_target.length() > 0.5 { to_target.normalize() } else {
+            zombie.target = None;
+            Vec2::ZERO
+        };
+
+        controller::apply_gravity(&mut zombie.velocity, &game_settings, world_state.gravity_scale(), delta_time);
+        let on_ground = controller::is_on_ground(position, ZOMBIE_SIZE.y, &world);
+        if on_ground && zombie.velocity.y < 0.0 {
+            zombie.velocity.y = 0.0;
+        }
+
+        let acceleration = if on_ground { ZOMBIE_ACCELERATION } else { ZOMBIE_ACCELERATION * 0.2 };
+        let horizontal = zombie.velocity.xz();
+        let change = (direction * ZOMBIE_SPEED - horizontal).clamp_length_max(acceleration * delta_time);
+        zombie.velocity.x += change.x;
+        zombie.velocity.z += change.y;
+
+        if direction != Vec2::ZERO {
+            transform.rotation = Quat::from_rotation_y((-direction.x).atan2(-direction.y));
+            // 前方有一格高的障碍且上方是空的时跳起来
+            let ahead = position + Vec3::new(direction.x, 0.0, direction.y) * ZOMBIE_SIZE.x;
+            let half = Vec3::new(ZOMBIE_SIZE.x / 2.0, 0.0, ZOMBIE_SIZE.z / 2.0);
+            let blocked = controller::box_collides(ahead - half + Vec3::Y * 0.1, ahead + half + Vec3::Y, &world);
+            let clear_above = !controller::box_collides(
+                ahead - half + Vec3::Y * 1.1,
+                ahead + half + Vec3::Y * (1.0 + ZOMBIE_SIZE.y),
+                &world,
+            );
+            if on_ground && blocked && clear_above {
+                zombie.velocity.y = JUMP_VELOCITY;
+            }
+        }
+
+        transform.translation = controller::move_with_collisions(
+            position,
+            &mut zombie.velocity,
+            ZOMBIE_SIZE,
+            true,
+            delta_time,
+            &world,
+            None,
+        );
+
+        // 近战攻击
+        let offset = player.translation - transform.translation;
+        if sees_player
+            && zombie.attack_cooldown <= 0.0
+            && offset.xz().length() <= ATTACK_RANGE
+            && offset.y.abs() < ZOMBIE_SIZE.y
+        {
+            zombie.attack_cooldown = ATTACK_COOLDOWN;
+            let push = offset.xz().normalize_or_zero() * KNOCKBACK_SPEED;
+            damage.send(PlayerDamageEvent {
+                amount: ATTACK_DAMAGE,
+                knockback: Vec3::new(push.x, KNOCKBACK_LIFT, push.y),
+            });
+        }
+    }
+}
+
+/// 僵尸受到攻击（见 `DamageEvent`）：扣除生命值并被击退，生命值归零时消失
+fn apply_zombie_damage(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    mut zombies: Query<(&mut Zombie, &mut Health)>,
+) {
+    for event in events.read() {
+        let Ok((mut zombie, mut health)) = zombies.get_mut(event.target) else { continue };
+        // 同一帧内已经死亡的僵尸不再处理
+        if health.current <= 0.0 {
+            continue;
+        }
+        zombie.velocity += event.knockback;
+        if health.damage(event.amount) {
+            commands.entity(event.target).despawn_recursive();
+        }
+    }
+}
+
+/// 离玩家太远或所在区块已卸载的僵尸消失
+fn despawn_far_zombies(
+    mut commands: Commands,
+    zombies: Query<(Entity, &Transform), With<Zombie>>,
+    player: Query<&Transform, With<FirstPersonController>>,
+    chunk_storage: Res<ChunkStorage>,
+) {
+    let Ok(player) = player.get_single() else { return };
+    for (entity, transform) in zombies.iter() {
+        let chunk_coord = world_pos_to_chunk_coord(transform.translation.floor().as_ivec3());
+        if transform.translation.distance(player.translation) > DESPAWN_DISTANCE || chunk_storage.get(&chunk_coord).is_none() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zombies_spawn_on_solid_ground_with_headroom() {
+        // y=3 是石头，上面两格空气；y=0 的泥土上方只有一格空气
+        let column = |y: i32| match y {
+            0 => Some(BlockId::Dirt),
+            1 => Some(BlockId::Air),
+            2 | 3 => Some(BlockId::Stone),
+            4..=9 => Some(BlockId::Air),
+            _ => None,
+        };
+        assert_eq!(find_spawn_height(9, 0, column), Some(4));
+        assert_eq!(find_spawn_height(2, 0, column), None);
+
+        // 水面上不生成
+        let water = |y: i32| Some(if y == 0 { BlockId::Water } else { BlockId::Air });
+        assert_eq!(find_spawn_height(5, -5, water), None);
+
+        // 准星射线击中僵尸的包围盒
+        let (min, max) = zombie_hittable().bounds(Vec3::new(0.0, 0.0, -3.0));
+        let hit = controller::ray_box_distance(Vec3::new(0.0, 1.6, 0.0), Vec3::NEG_Z, min, max);
+        assert!((hit.unwrap() - 2.7).abs() < 1e-5);
+        assert_eq!(controller::ray_box_distance(Vec3::new(0.0, 1.6, 0.0), Vec3::Z, min, max), None);
+    }
+}
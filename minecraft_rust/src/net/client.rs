@@ -0,0 +1,344 @@
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use bevy::prelude::*;
+use crossbeam::channel::TryRecvError;
+use crate::audio::{SoundEvent, SoundKind};
+use crate::controller::FirstPersonController;
+use crate::random::random_unit;
+use crate::game_state::{WorldManager, WorldState};
+use crate::inventory::{ItemType, PlayerInventory};
+use crate::loading::{SpawnPreload, SpawnPreloadConfig};
+use crate::localization::LocalizationManager;
+use crate::message_log::MessageLog;
+use crate::mining;
+use crate::net::protocol::{self, ClientMessage, ServerMessage, PROTOCOL_VERSION};
+use crate::net::server::{IntegratedServerPlugin, NetServerSet};
+use crate::net::Connection;
+use crate::notifications::Notifications;
+use crate::stats::WorldStats;
+use crate::world::access::WorldAccess;
+use crate::world::block_update::clear_block_updates;
+use crate::world::chunk::{BlockId, Chunk, LodLevel};
+use crate::world::chunk_loader::ChunkLoaderSet;
+use crate::world::events::{BlockChangedEvent, ChunkLoadedEvent, ChunkUnloadedEvent};
+use crate::world::falling_block::spawn_dropped_item;
+use crate::world::storage::ChunkStorage;
+use crate::world::{BlockUpdateSet, SpawnPoint};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// 位置同步间隔（秒），只在位置或朝向变化时发送
+const POSITION_SEND_INTERVAL: f32 = 0.1;
+/// 超出发送范围这么多区块后丢弃本地区块；比服务器忘记已发送区块的距离更远，回到附近时服务器会重新发送
+const DROP_MARGIN: i32 = 3;
+/// 其他玩家的胶囊体高 1.8 格，实体位置在脚下，胶囊中心要抬高一半
+const CAPSULE_OFFSET: Vec3 = Vec3::new(0.0, 0.9, 0.0);
+
+/// 与服务器的连接，玩家的方块操作都作为消息发给服务器。连接远程服务器时本地不生成区块，
+/// 区块、方块修改和其他玩家都来自服务器；单人游戏时连接进程内的服务器，两者共用同一个世界
+#[derive(Resource)]
+pub struct NetClient {
+    connection: Connection<ServerMessage, ClientMessage>,
+    player_id: Option<u32>,
+    disconnected: bool,
+    remote: bool,
+}
+
+impl NetClient {
+    /// 单人游戏时连接进程内服务器的客户端，不需要握手
+    pub fn local(connection: Connection<ServerMessage, ClientMessage>, player_id: u32) -> Self {
+        Self { connection, player_id: Some(player_id), disconnected: false, remote: false }
+    }
+
+    pub fn send(&self, message: ClientMessage) {
+        self.connection.send(message);
+    }
+
+    /// 是否连接远程服务器；单人游戏时连接的是进程内的服务器
+    pub fn is_remote(&self) -> bool {
+        self.remote
+    }
+}
+
+pub fn connected_to_server(client: Option<Res<NetClient>>) -> bool {
+    client.is_some_and(|client| client.remote)
+}
+
+/// 其他玩家，显示为简单的胶囊体
+#[derive(Component)]
+struct RemotePlayer {
+    player_id: u32,
+    name: String,
+}
+
+#[derive(Resource)]
+struct RemotePlayerAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+/// 以 `--connect <地址>` 启动时连接服务器；没有指定地址或连接失败时在进程内运行服务器，按单人模式运行
+pub struct NetClientPlugin {
+    pub address: Option<String>,
+    pub player_name: String,
+}
+
+impl Plugin for NetClientPlugin {
+    fn build(&self, app: &mut App) {
+        let connection = self.address.as_ref().and_then(|address| match connect(address) {
+            Ok(connection) => {
+                info!("Connected to {} as {}", address, self.player_name);
+                Some(connection)
+            }
+            Err(e) => {
+                error!("Failed to connect to {}: {}. Starting in single-player mode", address, e);
+                None
+            }
+        });
+        match connection {
+            Some(connection) => {
+                connection.send(ClientMessage::Hello { version: PROTOCOL_VERSION, name: self.player_name.clone() });
+                app.insert_resource(NetClient { connection, player_id: None, disconnected: false, remote: true });
+            }
+            None => {
+                app.add_plugins(IntegratedServerPlugin { player_name: self.player_name.clone() });
+            }
+        }
+
+        app.configure_sets(Update, (ChunkLoaderSet, BlockUpdateSet).run_if(not(connected_to_server)))
+           .configure_sets(FixedUpdate, BlockUpdateSet.run_if(not(connected_to_server)))
+           .add_systems(Startup, setup_remote_player_assets)
+           .add_systems(Update, (
+               // 单人游戏时进程内服务器的回复在同一帧内处理
+               receive_server_messages.after(NetServerSet),
+               (
+                   drop_distant_chunks,
+                   send_player_position,
+                   // 方块更新由服务器处理，本地收到的修改不排队
+                   clear_block_updates.after(receive_server_messages),
+               ).run_if(connected_to_server),
+           ));
+    }
+}
+
+fn connect(address: &str) -> io::Result<Connection<ServerMessage, ClientMessage>> {
+    let with_port = if address.contains(':') { address.to_string() } else { format!("{}:{}", address, protocol::DEFAULT_PORT) };
+    let socket_address = with_port
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("Could not resolve {}", with_port)))?;
+    Connection::spawn(TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT)?)
+}
+
+fn setup_remote_player_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(RemotePlayerAssets {
+        mesh: meshes.add(Mesh::from(shape::Capsule { radius: 0.3, depth: 1.2, ..default() })),
+        material: materials.add(Color::rgb(0.85, 0.35, 0.3).into()),
+    });
+}
+
+fn receive_server_messages(
+    mut commands: Commands,
+    mut client: ResMut<NetClient>,
+    mut world: WorldAccess,
+    mut remote_players: Query<(Entity, &RemotePlayer, &mut Transform)>,
+    mut inventory: Query<&mut PlayerInventory>,
+    // 系统参数最多 16 个，世界管理器和世界状态合为一个
+    (world_manager, mut world_state): (Res<WorldManager>, ResMut<WorldState>),
+    assets: Res<RemotePlayerAssets>,
+    mut preload: ResMut<SpawnPreload>,
+    preload_config: Res<SpawnPreloadConfig>,
+    mut loaded_events: EventWriter<ChunkLoadedEvent>,
+    mut block_changes: EventWriter<BlockChangedEvent>,
+    mut sounds: EventWriter<SoundEvent>,
+    mut notifications: ResMut<Notifications>,
+    mut message_log: ResMut<MessageLog>,
+    localization: Res<LocalizationManager>,
+    // 系统参数最多 16 个，统计和时间合为一个
+    (mut stats, time): (ResMut<WorldStats>, Res<Time>),
+) {
+    if client.disconnected {
+        return;
+    }
+    loop {
+        let message = match client.connection.try_recv() {
+            Ok(message) => message,
+            Err(TryRecvError::Empty) => break,
+            Err(TryRecvError::Disconnected) => {
+                warn!("Lost connection to server");
+                notifications.error(localization.get("game.multiplayer.disconnected"));
+                client.disconnected = true;
+                break;
+            }
+        };
+
+        match message {
+            ServerMessage::Welcome { player_id, spawn } => {
+                info!("Joined server as player {}, spawn {:?}", player_id, spawn);
+                client.player_id = Some(player_id);
+                // 出生点由服务器决定，加载界面改为等待服务器出生点周围的区块
+                commands.insert_resource(SpawnPoint(spawn));
+                preload.retarget(spawn, &preload_config);
+            }
+            ServerMessage::Rejected { reason } => {
+                warn!("Server refused the connection: {}", reason);
+                notifications.error(format!("{}: {}", localization.get("game.multiplayer.rejected"), reason));
+            }
+            ServerMessage::ChunkData(compressed) => {
+                let Some(chunk) = compressed.decode() else {
+                    warn!("Received invalid chunk data for {:?}", compressed.coord);
+                    continue;
+                };
+                let coord = chunk.coord;
+                // 已有的区块直接替换数据，网格系统会因 dirty 标志重新构建网格
+                let entity = match world.chunk_storage.get(&coord) {
+                    Some(existing) => {
+                        commands.entity(existing).insert(chunk);
+                        existing
+                    }
+                    None => {
+                        let entity = commands
+                            .spawn((
+                                chunk,
+                                LodLevel(0),
+                                SpatialBundle {
+                                    transform: Transform::from_translation(coord.as_vec3() * 32.0),
+                                    ..default()
+                                },
+                            ))
+                            .id();
+                        world.chunk_storage.insert_loading(coord, entity);
+                        entity
+                    }
+                };
+                loaded_events.send(ChunkLoadedEvent { coord, entity });
+            }
+            ServerMessage::BlockChanged { pos, block, metadata } => {
+                if let Some(previous) = world.set_block_with_metadata(pos, block, metadata) {
+                    // 客户端不运行方块更新系统，事件只用于更新本地光照
+                    block_changes.send(BlockChangedEvent { pos });
+                    // 流体流动不播放声音
+                    let sound = if block == BlockId::Air && previous.is_selectable() {
+                        Some(SoundEvent::block(SoundKind::Dig, previous))
+                    } else if block.is_selectable() {
+                        Some(SoundEvent::block(SoundKind::Place, block))
+                    } else {
+                        None
+                    };
+                    sounds.send_batch(sound);
+                }
+            }
+            // 破坏被服务器接受后才记录统计、掉落物品和消耗工具耐久，创造模式不掉落也不消耗。
+            // 联机时同一修改的 BlockChanged 消息会播放声音
+            ServerMessage::BlockBroken { pos, block } => {
+                stats.record_mined(block);
+                if !client.remote {
+                    sounds.send(SoundEvent::block(SoundKind::Dig, block));
+                }
+                let Ok(mut inventory) = inventory.get_single_mut() else { continue };
+                if world_manager.current_game_mode().is_creative() {
+                    continue;
+                }
+                let tool = match inventory.get_selected_item().item_type {
+                    ItemType::Tool(tool) => Some(tool),
+                    _ => None,
+                };
+                // 掉落物只会存在于本地，联机时不生成（与死亡掉落相同）
+                let roll = random_unit(time.elapsed().as_nanos() as u64, 0);
+                let drop = mining::can_harvest(block, tool).then(|| mining::block_drop(block, roll)).flatten();
+                if let Some(drop) = drop.filter(|_| !client.remote) {
+                    spawn_dropped_item(&mut commands, drop, pos.as_vec3() + Vec3::splat(0.5));
+                }
+                if inventory.get_selected_item_mut().damage_tool() {
+                    sounds.send(SoundEvent::tool_break());
+                }
+            }
+            // 放置被服务器接受后才消耗物品，创造模式不消耗
+            ServerMessage::PlacementAccepted { block } => {
+                stats.record_placed(block);
+                if !client.remote {
+                    sounds.send(SoundEvent::block(SoundKind::Place, block));
+                }
+                if let (Ok(mut inventory), false) = (inventory.get_single_mut(), world_manager.current_game_mode().is_creative()) {
+                    inventory.consume_placed(ItemType::Block(block));
+                }
+            }
+            ServerMessage::PlayerJoined { player_id, name, position } => {
+                message_log.info(format!("{}: {}", localization.get("game.multiplayer.joined"), name));
+                commands.spawn((
+                    PbrBundle {
+                        mesh: assets.mesh.clone(),
+                        material: assets.material.clone(),
+                        transform: Transform::from_translation(position + CAPSULE_OFFSET),
+                        ..default()
+                    },
+                    RemotePlayer { player_id, name },
+                ));
+            }
+            ServerMessage::PlayerMoved { player_id, position, yaw } => {
+                if let Some((_, _, mut transform)) = remote_players.iter_mut().find(|(_, player, _)| player.player_id == player_id) {
+                    transform.translation = position + CAPSULE_OFFSET;
+                    transform.rotation = Quat::from_rotation_y(yaw);
+                }
+            }
+            // 本地不推进天气，雨的显示跟随服务器
+            ServerMessage::Weather(weather) => world_state.weather = weather,
+            ServerMessage::PlayerLeft { player_id } => {
+                if let Some((entity, player, _)) = remote_players.iter().find(|(_, player, _)| player.player_id == player_id) {
+                    message_log.info(format!("{}: {}", localization.get("game.multiplayer.left"), player.name));
+                    commands.entity(entity).despawn();
+                }
+            }
+        }
+    }
+}
+
+/// 丢弃离玩家太远的区块；本地区块加载器在联机时不运行，需要在这里释放
+fn drop_distant_chunks(
+    mut commands: Commands,
+    player: Query<&Transform, With<FirstPersonController>>,
+    chunks: Query<(Entity, &Chunk)>,
+    chunk_storage: Res<ChunkStorage>,
+    mut unloaded_events: EventWriter<ChunkUnloadedEvent>,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+) {
+    *elapsed += time.delta_seconds();
+    if *elapsed < 1.0 {
+        return;
+    }
+    *elapsed = 0.0;
+    let Ok(transform) = player.get_single() else { return };
+    let center = (transform.translation / 32.0).floor().as_ivec3();
+    for (entity, chunk) in chunks.iter() {
+        if !protocol::in_stream_range(chunk.coord - center, DROP_MARGIN) {
+            chunk_storage.remove(&chunk.coord);
+            commands.entity(entity).despawn_recursive();
+            unloaded_events.send(ChunkUnloadedEvent { coord: chunk.coord });
+        }
+    }
+}
+
+fn send_player_position(
+    client: Res<NetClient>,
+    player: Query<(&Transform, &FirstPersonController)>,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut last_sent: Local<Option<(Vec3, f32)>>,
+) {
+    *elapsed += time.delta_seconds();
+    if *elapsed < POSITION_SEND_INTERVAL || client.player_id.is_none() {
+        return;
+    }
+    *elapsed = 0.0;
+    let Ok((transform, controller)) = player.get_single() else { return };
+    let current = (transform.translation, controller.yaw);
+    if *last_sent != Some(current) {
+        client.send(ClientMessage::PlayerMoved { position: current.0, yaw: current.1 });
+        *last_sent = Some(current);
+    }
+}
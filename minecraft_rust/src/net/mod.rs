@@ -0,0 +1,69 @@
+use std::io::{self, BufReader, BufWriter, Write};
+use std::net::{Shutdown, TcpStream};
+use std::thread;
+use crossbeam::channel::{self, Receiver, Sender, TryRecvError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub mod protocol;
+pub mod server;
+pub mod client;
+
+/// 一条 TCP 连接：后台线程负责阻塞读写，主线程通过通道收发消息，不会因网络卡顿
+pub struct Connection<In, Out> {
+    incoming: Receiver<In>,
+    outgoing: Sender<Out>,
+}
+
+impl<In, Out> Connection<In, Out>
+where
+    In: DeserializeOwned + Send + 'static,
+    Out: Serialize + Send + 'static,
+{
+    pub fn spawn(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = BufWriter::new(stream);
+        let (incoming_sender, incoming) = channel::unbounded();
+        let (outgoing, outgoing_receiver) = channel::unbounded::<Out>();
+
+        // 读取出错（对方断开或数据损坏）时线程结束，主线程随后收到 Disconnected
+        thread::spawn(move || {
+            while let Ok(message) = protocol::read_frame::<In>(&mut reader) {
+                if incoming_sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        // 连接被丢弃后先发完已排队的消息再关闭套接字，读取线程也会随之结束
+        thread::spawn(move || {
+            for message in outgoing_receiver.iter() {
+                let written = protocol::write_frame(&mut writer, &message);
+                if written.and_then(|_| if outgoing_receiver.is_empty() { writer.flush() } else { Ok(()) }).is_err() {
+                    break;
+                }
+            }
+            let _ = writer.flush();
+            let _ = writer.get_ref().shutdown(Shutdown::Both);
+        });
+
+        Ok(Self { incoming, outgoing })
+    }
+
+    /// 进程内的一对连接，单人游戏时客户端通过它连接同一进程中的服务器。
+    /// 一端被丢弃后另一端收到 Disconnected，与 TCP 连接断开时相同
+    pub fn local_pair() -> (Self, Connection<Out, In>) {
+        let (in_sender, incoming) = channel::unbounded();
+        let (outgoing, out_receiver) = channel::unbounded();
+        (Self { incoming, outgoing }, Connection { incoming: out_receiver, outgoing: in_sender })
+    }
+
+    pub fn send(&self, message: Out) {
+        // 写线程已经退出说明连接断开，读取端会报告断开
+        let _ = self.outgoing.send(message);
+    }
+
+    pub fn try_recv(&self) -> Result<In, TryRecvError> {
+        self.incoming.try_recv()
+    }
+}
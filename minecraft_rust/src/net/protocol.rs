@@ -0,0 +1,163 @@
+use std::io::{self, Read, Write};
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use crate::game_state::Weather;
+use crate::world::chunk::{BlockId, Chunk};
+
+pub const DEFAULT_PORT: u16 = 25565;
+/// 协议版本，消息格式变化时提高，握手时版本不同的客户端会被拒绝
+pub const PROTOCOL_VERSION: u32 = 5;
+/// 单条消息的最大字节数，超过时视为数据损坏并断开连接
+const MAX_FRAME_BYTES: u32 = 4 * 1024 * 1024;
+
+/// 服务器向每个玩家发送其周围这个范围内的区块（水平、垂直区块半径）
+pub const STREAM_RADIUS: i32 = 4;
+pub const STREAM_RADIUS_VERTICAL: i32 = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ClientMessage {
+    Hello { version: u32, name: String },
+    PlayerMoved { position: Vec3, yaw: f32 },
+    BreakBlock { pos: IVec3 },
+    /// `metadata` 是放置时决定的朝向
+    PlaceBlock { pos: IVec3, block: BlockId, metadata: u8 },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ServerMessage {
+    Welcome { player_id: u32, spawn: Vec3 },
+    Rejected { reason: String },
+    ChunkData(CompressedChunk),
+    BlockChanged { pos: IVec3, block: BlockId, metadata: u8 },
+    /// 只发给破坏方块的玩家：破坏已被接受，`block` 是被破坏的方块，客户端这时才记录统计和消耗工具耐久
+    BlockBroken { pos: IVec3, block: BlockId },
+    /// 只发给放置方块的玩家：放置已被接受，客户端这时才消耗物品
+    PlacementAccepted { block: BlockId },
+    PlayerJoined { player_id: u32, name: String, position: Vec3 },
+    PlayerMoved { player_id: u32, position: Vec3, yaw: f32 },
+    PlayerLeft { player_id: u32 },
+    /// 当前天气，玩家加入和天气变化时发送
+    Weather(Weather),
+}
+
+/// 游程编码的区块数据。地形区块大段都是空气或石头，编码后通常只有几 KB
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompressedChunk {
+    pub coord: IVec3,
+    pub runs: Vec<(u8, u16)>,
+    /// 附加数据（流体等级）的游程编码，绝大多数区块只有一段 0
+    pub metadata_runs: Vec<(u8, u16)>,
+    /// 每列生物群系的游程编码，区块没有记录生物群系时为空
+    pub biome_runs: Vec<(u8, u16)>,
+}
+
+impl CompressedChunk {
+    pub fn encode(chunk: &Chunk) -> Self {
+        Self {
+            coord: chunk.coord,
+            runs: encode_runs(&chunk.blocks),
+            metadata_runs: encode_runs(&chunk.metadata),
+            biome_runs: encode_runs(chunk.biome_ids()),
+        }
+    }
+
+    /// 还原区块；方块、附加数据或生物群系的总数不对时返回 None
+    pub fn decode(&self) -> Option<Chunk> {
+        let mut chunk = Chunk::new(self.coord);
+        chunk.blocks = decode_runs(&self.runs, Chunk::COUNT)?;
+        // 附加数据全为 0 的区块没有附加数据数组
+        if !self.metadata_runs.is_empty() {
+            chunk.metadata = decode_runs(&self.metadata_runs, Chunk::COUNT / 2)?;
+            chunk.compact_metadata();
+        }
+        if !self.biome_runs.is_empty() {
+            let columns = (Chunk::SIZE.x * Chunk::SIZE.z) as usize;
+            chunk.set_biome_ids(decode_runs(&self.biome_runs, columns)?);
+        }
+        chunk.compute_solid_blocks();
+        Some(chunk)
+    }
+}
+
+fn encode_runs(bytes: &[u8]) -> Vec<(u8, u16)> {
+    let mut runs: Vec<(u8, u16)> = Vec::new();
+    for &byte in bytes {
+        match runs.last_mut() {
+            Some((last, count)) if *last == byte && *count < u16::MAX => *count += 1,
+            _ => runs.push((byte, 1)),
+        }
+    }
+    runs
+}
+
+fn decode_runs(runs: &[(u8, u16)], len: usize) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(len);
+    for &(byte, count) in runs {
+        bytes.extend(std::iter::repeat(byte).take(count as usize));
+        if bytes.len() > len {
+            return None;
+        }
+    }
+    (bytes.len() == len).then_some(bytes)
+}
+
+/// 区块坐标偏移是否在发送范围内，`margin` 为额外保留的区块数
+pub fn in_stream_range(offset: IVec3, margin: i32) -> bool {
+    offset.x.abs().max(offset.z.abs()) <= STREAM_RADIUS + margin && offset.y.abs() <= STREAM_RADIUS_VERTICAL + margin
+}
+
+/// 写入一条消息：4 字节小端长度 + bincode 数据
+pub fn write_frame<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let bytes = bincode::serialize(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)
+}
+
+pub fn read_frame<T: DeserializeOwned>(reader: &mut impl Read) -> io::Result<T> {
+    let mut length = [0u8; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::from_le_bytes(length);
+    if length > MAX_FRAME_BYTES {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Frame of {} bytes is too large", length)));
+    }
+    let mut bytes = vec![0u8; length as usize];
+    reader.read_exact(&mut bytes)?;
+    bincode::deserialize(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::generator::BiomeType;
+
+    #[test]
+    fn chunk_survives_compression_and_framing() {
+        let mut chunk = Chunk::new(IVec3::new(3, -1, 7));
+        for x in 0..32 {
+            for z in 0..32 {
+                chunk.set_block(x, 0, z, BlockId::Bedrock);
+                chunk.set_block(x, 1, z, BlockId::Stone);
+            }
+        }
+        chunk.set_block(5, 10, 5, BlockId::Grass);
+        chunk.set_block_with_metadata(6, 2, 6, BlockId::Water, 3);
+        chunk.set_biome(4, 9, BiomeType::Forest);
+        let message = ServerMessage::ChunkData(CompressedChunk::encode(&chunk));
+
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &message).unwrap();
+        assert!(buffer.len() < 100);
+        let ServerMessage::ChunkData(compressed) = read_frame(&mut buffer.as_slice()).unwrap() else {
+            panic!("wrong message type");
+        };
+
+        let decoded = compressed.decode().unwrap();
+        assert_eq!(decoded.coord, chunk.coord);
+        assert_eq!(decoded.blocks, chunk.blocks);
+        assert_eq!(decoded.get_metadata(6, 2, 6), 3);
+        assert_eq!((decoded.biome(4, 9), decoded.biome(9, 4)), (Some(BiomeType::Forest), Some(BiomeType::Plains)));
+        assert_eq!(decoded.get_solid_blocks().len(), 32 * 32 * 2 + 1);
+        assert!(CompressedChunk { coord: IVec3::ZERO, runs: vec![(0, 10)], metadata_runs: Vec::new(), biome_runs: Vec::new() }.decode().is_none());
+    }
+}
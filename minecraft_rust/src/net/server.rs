@@ -0,0 +1,412 @@
+use std::collections::HashSet;
+use std::io;
+use std::net::TcpListener;
+use bevy::prelude::*;
+use crossbeam::channel::TryRecvError;
+use crate::controller::{ControllerSet, FirstPersonController, REACH_DISTANCE};
+use crate::game_state::{GameState, Weather, WorldState};
+use crate::net::protocol::{self, ClientMessage, CompressedChunk, ServerMessage, PROTOCOL_VERSION};
+use crate::net::client::NetClient;
+use crate::net::Connection;
+use crate::world::access::{world_pos_to_chunk_coord, WorldAccess, WorldView};
+use crate::world::chunk::{BlockId, Chunk, LodLevel};
+use crate::world::chunk_loader::ChunkLoadAnchor;
+use crate::world::events::BlockChangedEvent;
+use crate::world::sapling::can_plant_on;
+use crate::world::storage::ChunkStorage;
+use crate::world::SpawnPoint;
+
+/// 局域网联机的玩家数量上限
+const MAX_PLAYERS: usize = 4;
+/// 每个玩家每 tick 最多发送的区块数，避免一次塞满连接
+const CHUNKS_PER_TICK: usize = 8;
+/// 已发送区块超出发送范围这么多区块后不再记录，回到附近时重新发送；客户端的丢弃距离更远，保证不会漏发
+const FORGET_MARGIN: i32 = 2;
+/// 服务器允许的交互距离，比客户端射线略长以容忍位置同步的延迟
+const MAX_REACH: f32 = REACH_DISTANCE + 1.5;
+const EYE_HEIGHT: f32 = 1.62;
+
+#[derive(Resource)]
+struct NetServer {
+    listener: TcpListener,
+    next_player_id: u32,
+}
+
+/// 一个已连接的客户端（单人游戏时是通过进程内连接的本地玩家）。握手完成前 `name` 为空，不接收区块和其他玩家的消息
+#[derive(Component)]
+struct RemoteClient {
+    player_id: u32,
+    name: Option<String>,
+    connection: Connection<ClientMessage, ServerMessage>,
+    sent_chunks: HashSet<IVec3>,
+}
+
+/// 处理玩家消息的服务器系统。单人游戏时客户端在它之后接着处理回复，方块操作在同一帧内完成
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NetServerSet;
+
+/// 在无窗口服务器上监听 TCP 连接：向玩家发送周围的区块，校验并广播方块修改，转发玩家位置
+pub struct NetServerPlugin {
+    pub port: u16,
+}
+
+impl Plugin for NetServerPlugin {
+    fn build(&self, app: &mut App) {
+        let listener = match bind(self.port) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to listen on port {}: {}", self.port, e);
+                return;
+            }
+        };
+        info!("Listening for players on port {}", self.port);
+        app.insert_resource(NetServer { listener, next_player_id: 1 })
+           .add_systems(Update, (
+               accept_clients,
+               handle_client_messages,
+               broadcast_block_changes,
+               broadcast_weather,
+               stream_chunks,
+               follow_players,
+           ).chain().in_set(NetServerSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 单人游戏时在进程内运行的服务器：本地玩家通过 `Connection::local_pair` 连接，方块的放置和破坏与联机时
+/// 经过同一套消息和校验。服务器与客户端共用同一个世界，不需要发送区块和广播方块变化，也没有其他玩家
+pub struct IntegratedServerPlugin {
+    pub player_name: String,
+}
+
+/// 进程内服务器上本地玩家的编号
+pub const LOCAL_PLAYER_ID: u32 = 0;
+
+impl Plugin for IntegratedServerPlugin {
+    fn build(&self, app: &mut App) {
+        let (client, server) = Connection::local_pair();
+        app.world.spawn((
+            RemoteClient { player_id: LOCAL_PLAYER_ID, name: Some(self.player_name.clone()), connection: server, sent_chunks: HashSet::new() },
+            TransformBundle::default(),
+        ));
+        app.insert_resource(NetClient::local(client, LOCAL_PLAYER_ID))
+           .add_systems(Update, (
+               follow_local_player,
+               handle_client_messages,
+           ).chain().in_set(NetServerSet).after(ControllerSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 本地玩家就是客户端控制的玩家，每帧同步位置用于交互距离校验，不需要等待位置消息
+fn follow_local_player(
+    player: Query<&Transform, (With<FirstPersonController>, Without<RemoteClient>)>,
+    mut clients: Query<&mut Transform, With<RemoteClient>>,
+) {
+    let Ok(player) = player.get_single() else { return };
+    for mut transform in clients.iter_mut() {
+        *transform = *player;
+    }
+}
+
+fn bind(port: u16) -> io::Result<TcpListener> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+fn accept_clients(
+    mut commands: Commands,
+    mut server: ResMut<NetServer>,
+    clients: Query<(), With<RemoteClient>>,
+    spawn_point: Res<SpawnPoint>,
+) {
+    let mut player_count = clients.iter().count();
+    loop {
+        let (stream, address) = match server.listener.accept() {
+            Ok(accepted) => accepted,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+            Err(e) => {
+                warn!("Failed to accept connection: {}", e);
+                break;
+            }
+        };
+        // 部分平台上接受的连接会继承监听端口的非阻塞模式，读写线程需要阻塞模式
+        let connection = match stream.set_nonblocking(false).and_then(|_| Connection::spawn(stream)) {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!("Failed to set up connection from {}: {}", address, e);
+                continue;
+            }
+        };
+        if player_count >= MAX_PLAYERS {
+            connection.send(ServerMessage::Rejected { reason: format!("Server is full ({} players)", MAX_PLAYERS) });
+            continue;
+        }
+
+        let player_id = server.next_player_id;
+        server.next_player_id += 1;
+        player_count += 1;
+        info!("Connection from {} (player {})", address, player_id);
+        commands.spawn((
+            RemoteClient { player_id, name: None, connection, sent_chunks: HashSet::new() },
+            TransformBundle::from_transform(Transform::from_translation(spawn_point.0)),
+        ));
+    }
+}
+
+fn handle_client_messages(
+    mut commands: Commands,
+    mut clients: Query<(Entity, &mut RemoteClient, &mut Transform)>,
+    mut world: WorldAccess,
+    spawn_point: Res<SpawnPoint>,
+    world_state: Res<WorldState>,
+    mut block_changes: EventWriter<BlockChangedEvent>,
+) {
+    // (不发送给的玩家, 消息)，遍历完所有客户端后统一广播
+    let mut broadcasts: Vec<(Option<u32>, ServerMessage)> = Vec::new();
+    let mut joined = Vec::new();
+
+    for (entity, mut client, mut transform) in clients.iter_mut() {
+        let player_id = client.player_id;
+        loop {
+            let message = match client.connection.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if let Some(name) = &client.name {
+                        info!("{} left the game", name);
+                        broadcasts.push((Some(player_id), ServerMessage::PlayerLeft { player_id }));
+                    }
+                    commands.entity(entity).despawn();
+                    break;
+                }
+            };
+
+            match message {
+                ClientMessage::Hello { version, name } => {
+                    if version != PROTOCOL_VERSION {
+                        client.connection.send(ServerMessage::Rejected {
+                            reason: format!("Protocol version {} is not supported (server uses {})", version, PROTOCOL_VERSION),
+                        });
+                        // 丢弃连接前会先发完拒绝消息
+                        commands.entity(entity).despawn();
+                        break;
+                    }
+                    if client.name.is_some() {
+                        continue;
+                    }
+                    info!("{} joined the game as player {}", name, player_id);
+                    client.connection.send(ServerMessage::Welcome { player_id, spawn: spawn_point.0 });
+                    client.connection.send(ServerMessage::Weather(world_state.weather));
+                    broadcasts.push((Some(player_id), ServerMessage::PlayerJoined {
+                        player_id,
+                        name: name.clone(),
+                        position: transform.translation,
+                    }));
+                    client.name = Some(name);
+                    joined.push(entity);
+                }
+                _ if client.name.is_none() => {}
+                ClientMessage::PlayerMoved { position, yaw } => {
+                    transform.translation = position;
+                    transform.rotation = Quat::from_rotation_y(yaw);
+                    broadcasts.push((Some(player_id), ServerMessage::PlayerMoved { player_id, position, yaw }));
+                }
+                ClientMessage::BreakBlock { pos } => {
+                    if let Some(broken) = apply_edit(&transform, pos, BlockId::Air, 0, &mut world) {
+                        block_changes.send(BlockChangedEvent { pos });
+                        client.connection.send(ServerMessage::BlockBroken { pos, block: broken });
+                    }
+                }
+                ClientMessage::PlaceBlock { pos, block, metadata } => {
+                    if block != BlockId::Air && apply_edit(&transform, pos, block, metadata, &mut world).is_some() {
+                        block_changes.send(BlockChangedEvent { pos });
+                        client.connection.send(ServerMessage::PlacementAccepted { block });
+                    }
+                }
+            }
+        }
+    }
+
+    // 新加入的玩家需要知道已经在线的其他玩家
+    for entity in joined {
+        let Ok((_, new_client, _)) = clients.get(entity) else { continue };
+        for (_, other, other_transform) in clients.iter() {
+            if let (true, Some(name)) = (other.player_id != new_client.player_id, &other.name) {
+                new_client.connection.send(ServerMessage::PlayerJoined {
+                    player_id: other.player_id,
+                    name: name.clone(),
+                    position: other_transform.translation,
+                });
+            }
+        }
+    }
+
+    for (excluded, message) in broadcasts {
+        for (_, client, _) in clients.iter() {
+            if client.name.is_some() && Some(client.player_id) != excluded {
+                client.connection.send(message.clone());
+            }
+        }
+    }
+}
+
+/// 校验并应用玩家的方块修改：目标在交互距离内、所在区块已加载，破坏的是实心方块，放置的位置不是实心方块，
+/// 树苗下方是泥土或草方块。返回被替换的方块，被拒绝时返回 None
+fn apply_edit(
+    player: &Transform,
+    pos: IVec3,
+    block: BlockId,
+    metadata: u8,
+    world: &mut WorldAccess,
+) -> Option<BlockId> {
+    let eye = player.translation + Vec3::Y * EYE_HEIGHT;
+    if eye.distance(pos.as_vec3() + Vec3::splat(0.5)) > MAX_REACH {
+        debug!("Rejected edit at {:?}: out of reach", pos);
+        return None;
+    }
+    let Some((current, _)) = world.get_with_metadata(pos) else {
+        debug!("Rejected edit at {:?}: chunk not loaded", pos);
+        return None;
+    };
+    if (block == BlockId::Air) != current.is_selectable() {
+        return None;
+    }
+    // 与客户端的检查相同，树苗只能种在草方块或泥土上
+    if block == BlockId::Sapling && !world.block(pos - IVec3::Y).is_some_and(can_plant_on) {
+        debug!("Rejected edit at {:?}: sapling needs soil", pos);
+        return None;
+    }
+    world.set_block_with_metadata(pos, block, metadata)
+}
+
+/// 向已加入的玩家广播方块变化，包括玩家的修改和流体流动
+fn broadcast_block_changes(
+    mut block_changes: EventReader<BlockChangedEvent>,
+    clients: Query<&RemoteClient>,
+    chunks: Query<&Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+) {
+    let world = WorldView::new(&chunk_storage, &chunks);
+    for event in block_changes.read() {
+        let Some((block, metadata)) = world.get_with_metadata(event.pos) else { continue };
+        let chunk_coord = world_pos_to_chunk_coord(event.pos);
+        let message = ServerMessage::BlockChanged { pos: event.pos, block, metadata };
+        for client in clients.iter().filter(|client| client.sent_chunks.contains(&chunk_coord)) {
+            client.connection.send(message.clone());
+        }
+    }
+}
+
+/// 天气变化时通知所有已加入的玩家；刚加入的玩家在握手时收到当前天气
+fn broadcast_weather(world_state: Res<WorldState>, clients: Query<&RemoteClient>, mut last: Local<Option<Weather>>) {
+    if last.replace(world_state.weather) == Some(world_state.weather) {
+        return;
+    }
+    for client in clients.iter().filter(|client| client.name.is_some()) {
+        client.connection.send(ServerMessage::Weather(world_state.weather));
+    }
+}
+
+/// 按距离从近到远向每个玩家发送尚未发送的区块；远景 LOD 区块不发送
+fn stream_chunks(
+    mut clients: Query<(&mut RemoteClient, &Transform)>,
+    chunks: Query<(&Chunk, Option<&LodLevel>)>,
+    chunk_storage: Res<ChunkStorage>,
+    mut offsets: Local<Vec<IVec3>>,
+) {
+    if offsets.is_empty() {
+        let (h, v) = (protocol::STREAM_RADIUS, protocol::STREAM_RADIUS_VERTICAL);
+        for x in -h..=h {
+            for y in -v..=v {
+                for z in -h..=h {
+                    offsets.push(IVec3::new(x, y, z));
+                }
+            }
+        }
+        offsets.sort_by_key(|offset| offset.length_squared());
+    }
+
+    for (mut client, transform) in clients.iter_mut() {
+        if client.name.is_none() {
+            continue;
+        }
+        let center = (transform.translation / 32.0).floor().as_ivec3();
+        client.sent_chunks.retain(|coord| protocol::in_stream_range(*coord - center, FORGET_MARGIN));
+
+        let mut sent = 0;
+        for offset in offsets.iter() {
+            if sent >= CHUNKS_PER_TICK {
+                break;
+            }
+            let coord = center + *offset;
+            if client.sent_chunks.contains(&coord) {
+                continue;
+            }
+            let Some((chunk, lod)) = chunk_storage.get(&coord).and_then(|entity| chunks.get(entity).ok()) else { continue };
+            if lod.is_some_and(|lod| lod.0 > 0) {
+                continue;
+            }
+            client.connection.send(ServerMessage::ChunkData(CompressedChunk::encode(chunk)));
+            client.sent_chunks.insert(coord);
+            sent += 1;
+        }
+    }
+}
+
+/// 区块加载器只跟随一个加载中心，联机时把它放在所有在线玩家的中心位置。
+/// 适用于彼此靠近的少量局域网玩家，走得太远的玩家周围的区块不会加载
+fn follow_players(
+    clients: Query<(&RemoteClient, &Transform), Without<ChunkLoadAnchor>>,
+    mut anchor: Query<&mut Transform, With<ChunkLoadAnchor>>,
+) {
+    let positions: Vec<Vec3> = clients
+        .iter()
+        .filter(|(client, _)| client.name.is_some())
+        .map(|(_, transform)| transform.translation)
+        .collect();
+    if positions.is_empty() {
+        return;
+    }
+    let center = positions.iter().sum::<Vec3>() / positions.len() as f32;
+    for mut transform in anchor.iter_mut() {
+        transform.translation = center;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::test_support::flat_chunk_app;
+
+    #[test]
+    fn local_edits_go_through_server_validation() {
+        let (mut app, chunk) = flat_chunk_app(10, BlockId::Stone);
+        app.insert_resource(SpawnPoint(Vec3::new(16.0, 11.0, 16.0)))
+           .insert_resource(WorldState::default())
+           .add_systems(Update, handle_client_messages);
+        let (client, server) = Connection::<ServerMessage, ClientMessage>::local_pair();
+        app.world.spawn((
+            RemoteClient { player_id: LOCAL_PLAYER_ID, name: Some("Player".to_string()), connection: server, sent_chunks: HashSet::new() },
+            TransformBundle::from_transform(Transform::from_xyz(16.5, 11.0, 16.5)),
+        ));
+
+        client.send(ClientMessage::BreakBlock { pos: IVec3::new(16, 10, 16) });
+        // 超出交互距离
+        client.send(ClientMessage::BreakBlock { pos: IVec3::new(1, 10, 1) });
+        // 树苗下方是空气
+        client.send(ClientMessage::PlaceBlock { pos: IVec3::new(18, 12, 16), block: BlockId::Sapling, metadata: 0 });
+        client.send(ClientMessage::PlaceBlock { pos: IVec3::new(17, 11, 16), block: BlockId::Dirt, metadata: 0 });
+        app.update();
+
+        let replies: Vec<ServerMessage> = std::iter::from_fn(|| client.try_recv().ok()).collect();
+        assert_eq!(replies.len(), 2);
+        assert!(matches!(replies[0], ServerMessage::BlockBroken { pos, block: BlockId::Stone } if pos == IVec3::new(16, 10, 16)));
+        assert!(matches!(replies[1], ServerMessage::PlacementAccepted { block: BlockId::Dirt }));
+
+        let chunk = app.world.get::<Chunk>(chunk).unwrap();
+        assert_eq!(chunk.get_block(16, 10, 16), BlockId::Air);
+        assert_eq!(chunk.get_block(1, 10, 1), BlockId::Stone);
+        assert_eq!(chunk.get_block(18, 12, 16), BlockId::Air);
+        assert_eq!(chunk.get_block(17, 11, 16), BlockId::Dirt);
+    }
+}
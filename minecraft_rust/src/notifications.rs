@@ -0,0 +1,223 @@
+use std::collections::VecDeque;
+use bevy::prelude::*;
+use crate::hud::hud_visible;
+
+/// 通知的默认显示时长（秒），错误显示得更久；最后 `FADE_SECONDS` 秒淡出
+pub const DEFAULT_DURATION: f32 = 3.0;
+pub const ERROR_DURATION: f32 = 5.0;
+const FADE_SECONDS: f32 = 0.5;
+/// 同时显示的通知数量上限，更多的通知排队，等前面的消失后再显示
+const MAX_VISIBLE: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// 通知的文字颜色
+    pub fn color(self) -> Color {
+        match self {
+            Severity::Info => Color::WHITE,
+            Severity::Warning => Color::rgb(1.0, 0.85, 0.3),
+            Severity::Error => Color::rgb(1.0, 0.43, 0.43),
+        }
+    }
+}
+
+struct Notification {
+    /// 由 `Notifications::set` 添加的通知的标识，同一标识只保留最新的一条
+    key: Option<&'static str>,
+    text: String,
+    severity: Severity,
+    duration: f32,
+    shown_at: f32,
+}
+
+/// 屏幕右下角的简短通知，供截图、自动保存、错误提示等功能使用。
+/// 文本由调用方先经过本地化；HUD 隐藏期间通知留在队列中，不开始计时。
+/// 使用真实时间，暂停时通知照常消失
+#[derive(Resource, Default)]
+pub struct Notifications {
+    visible: Vec<Notification>,
+    /// 排队的通知，显示时才设置 `shown_at`
+    queued: VecDeque<Notification>,
+}
+
+impl Notifications {
+    /// 添加一条显示 `duration` 秒的通知
+    pub fn push(&mut self, text: impl Into<String>, severity: Severity, duration: f32) {
+        self.queued.push_back(Notification::new(None, text.into(), severity, duration));
+    }
+
+    /// 显示一条带标识的通知，替换同一标识的旧通知并重新计时，排在队列最前。
+    /// 用于连续变化的数值（例如飞行速度），避免每次变化都堆一条
+    pub fn set(&mut self, key: &'static str, text: impl Into<String>, severity: Severity) {
+        self.visible.retain(|notification| notification.key != Some(key));
+        self.queued.retain(|notification| notification.key != Some(key));
+        self.queued.push_front(Notification::new(Some(key), text.into(), severity, DEFAULT_DURATION));
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(text, Severity::Info, DEFAULT_DURATION);
+    }
+
+    pub fn warn(&mut self, text: impl Into<String>) {
+        self.push(text, Severity::Warning, DEFAULT_DURATION);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(text, Severity::Error, ERROR_DURATION);
+    }
+
+    /// 移除到期的通知，并从队列中补充到上限。显示的通知有变化时返回 true
+    fn update(&mut self, now: f32) -> bool {
+        let before = self.visible.len();
+        self.visible.retain(|notification| now - notification.shown_at < notification.duration);
+        let mut changed = self.visible.len() != before;
+        while self.visible.len() < MAX_VISIBLE {
+            let Some(mut notification) = self.queued.pop_front() else { break };
+            notification.shown_at = now;
+            self.visible.push(notification);
+            changed = true;
+        }
+        changed
+    }
+}
+
+impl Notification {
+    fn new(key: Option<&'static str>, text: String, severity: Severity, duration: f32) -> Self {
+        Self { key, text, severity, duration: duration.max(FADE_SECONDS), shown_at: 0.0 }
+    }
+
+    /// 不透明度：最后 `FADE_SECONDS` 秒从 1 降到 0
+    fn alpha(&self, now: f32) -> f32 {
+        ((self.duration - (now - self.shown_at)) / FADE_SECONDS).clamp(0.0, 1.0)
+    }
+}
+
+/// 通知列表的根节点，显示的通知是它的子节点，新的在下面
+#[derive(Component)]
+struct NotificationStack;
+
+/// 一条通知的节点，`usize` 为它在 `Notifications::visible` 中的位置
+#[derive(Component)]
+struct NotificationToast(usize);
+
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Notifications>()
+           .add_systems(Startup, setup_notification_stack)
+           .add_systems(Update, update_notifications.run_if(hud_visible));
+    }
+}
+
+fn setup_notification_stack(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(12.0),
+                bottom: Val::Px(12.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            // 在其他 UI 之上，屏幕遮罩之下
+            z_index: ZIndex::Global(i32::MAX - 1),
+            ..default()
+        },
+        NotificationStack,
+    ));
+}
+
+/// 显示的通知变化时重建节点，其余时间只更新淡出的透明度
+fn update_notifications(
+    mut commands: Commands,
+    mut notifications: ResMut<Notifications>,
+    time: Res<Time<Real>>,
+    stack: Query<Entity, With<NotificationStack>>,
+    mut toasts: Query<(&NotificationToast, &mut BackgroundColor, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    let now = time.elapsed_seconds();
+    if notifications.update(now) {
+        let Ok(stack) = stack.get_single() else { return };
+        commands.entity(stack).despawn_descendants().with_children(|parent| {
+            for (index, notification) in notifications.visible.iter().enumerate() {
+                let alpha = notification.alpha(now);
+                parent
+                    .spawn((
+                        NodeBundle {
+                            style: Style { padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)), ..default() },
+                            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7 * alpha).into(),
+                            ..default()
+                        },
+                        NotificationToast(index),
+                    ))
+                    .with_children(|toast| {
+                        toast.spawn(TextBundle::from_section(
+                            notification.text.clone(),
+                            TextStyle { font: default(), font_size: 16.0, color: notification.severity.color().with_a(alpha) },
+                        ));
+                    });
+            }
+        });
+        return;
+    }
+
+    for (toast, mut background, children) in toasts.iter_mut() {
+        let Some(notification) = notifications.visible.get(toast.0) else { continue };
+        let alpha = notification.alpha(now);
+        background.0 = background.0.with_a(0.7 * alpha);
+        for &child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                for section in text.sections.iter_mut() {
+                    section.style.color = section.style.color.with_a(alpha);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_notifications_wait_until_earlier_ones_expire() {
+        let mut notifications = Notifications::default();
+        for i in 0..MAX_VISIBLE + 2 {
+            notifications.info(format!("message {i}"));
+        }
+        assert!(notifications.update(0.0));
+        assert_eq!(notifications.visible.len(), MAX_VISIBLE);
+        assert_eq!(notifications.queued.len(), 2);
+        assert!(!notifications.update(1.0));
+
+        notifications.error("failed");
+        assert!(notifications.update(DEFAULT_DURATION));
+        assert_eq!(notifications.visible.len(), 3);
+        assert_eq!(notifications.visible[0].text, "message 5");
+        assert!(notifications.queued.is_empty());
+        assert_eq!(notifications.visible[2].severity, Severity::Error);
+    }
+
+    #[test]
+    fn set_replaces_the_notification_with_the_same_key() {
+        let mut notifications = Notifications::default();
+        notifications.info("hello");
+        notifications.set("speed", "speed 1", Severity::Info);
+        notifications.update(0.0);
+        notifications.set("speed", "speed 2", Severity::Info);
+        assert!(notifications.update(1.0));
+        let texts: Vec<_> = notifications.visible.iter().map(|notification| notification.text.as_str()).collect();
+        assert_eq!(texts, ["hello", "speed 2"]);
+        assert_eq!(notifications.visible[1].shown_at, 1.0);
+    }
+}
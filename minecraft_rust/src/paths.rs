@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use mc_common::paths::{resolve_from_environment, ASSETS_DIR, DATA_DIR_ENV, REQUIRED_DIRS};
+
+/// 指定游戏根目录的命令行参数，优先于环境变量 `DATA_DIR_ENV`
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+
+static GAME_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// 在读取任何数据文件之前确定游戏根目录。`data_dir` 来自 `--data-dir` 参数，优先于环境变量
+pub fn init(data_dir: Option<PathBuf>) {
+    let root = resolve_from_environment(data_dir);
+    if let Err(root) = GAME_ROOT.set(root) {
+        eprintln!("Game root already resolved to {}, ignoring {}", game_root().display(), root.display());
+    }
+}
+
+/// 游戏根目录（绝对路径）。没有调用过 `init` 时按环境变量和可执行文件位置确定
+pub fn game_root() -> &'static Path {
+    GAME_ROOT.get_or_init(|| resolve_from_environment(None))
+}
+
+/// 游戏根目录下的文件或目录
+pub fn data_path(relative: impl AsRef<Path>) -> PathBuf {
+    game_root().join(relative)
+}
+
+/// 资源目录，配置给 `AssetPlugin`，导出模型时也从这里复制纹理
+pub fn assets_dir() -> PathBuf {
+    data_path(ASSETS_DIR)
+}
+
+/// 启动时（日志系统就绪后）记录游戏根目录，检查必需的数据目录和资源目录，缺少时记录错误并列出解析出的绝对路径
+pub fn check_game_dirs() {
+    info!("Game root: {}", game_root().display());
+    for dir in REQUIRED_DIRS.into_iter().chain([ASSETS_DIR]) {
+        let path = data_path(dir);
+        if !path.is_dir() {
+            error!(
+                "Missing game data directory {} (game root resolved to {}; use {} or {} to point at the game folder)",
+                path.display(), game_root().display(), DATA_DIR_FLAG, DATA_DIR_ENV
+            );
+        }
+    }
+}
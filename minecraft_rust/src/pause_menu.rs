@@ -1,5 +1,10 @@
 use bevy::prelude::*;
+use crate::audio::SoundEvent;
+use crate::command::command_line_open;
+use crate::inventory_screen::inventory_screen_open;
 use crate::game_state::{GameState, WorldManager};
+use crate::stats::StatsWindow;
+use crate::ui::GameSettings;
 use crate::ui_strings::UiStringManager;
 
 /// 暂停菜单UI标记
@@ -13,14 +18,42 @@ impl Plugin for PauseMenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Paused), setup_pause_menu)
            .add_systems(OnExit(GameState::Paused), cleanup_pause_menu)
-           .add_systems(Update, pause_menu_button_system.run_if(in_state(GameState::Paused)));
+           .add_systems(Update, (
+               pause_menu_button_system,
+               hide_pause_menu_behind_settings,
+           ).run_if(in_state(GameState::Paused)))
+           // 物品栏和命令行打开时 Esc 用来关闭它们
+           .add_systems(Update, toggle_pause
+               .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused)))
+               .run_if(not(inventory_screen_open).and_then(not(command_line_open))));
+    }
+}
+
+/// Esc 打开或关闭暂停菜单；从暂停菜单打开的设置窗口先被关闭
+fn toggle_pause(
+    keyboard: Res<Input<KeyCode>>,
+    current_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut game_settings: ResMut<GameSettings>,
+) {
+    if !keyboard.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if *current_state.get() == GameState::Paused && game_settings.show_settings {
+        game_settings.show_settings = false;
+        return;
+    }
+    match current_state.get() {
+        // 光标随游戏状态显示或锁定，见 `cursor::CursorState`
+        GameState::InGame => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::InGame),
+        GameState::Loading | GameState::MainMenu | GameState::PhotoMode => {}
     }
 }
 
 /// 设置暂停菜单
 fn setup_pause_menu(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     ui_strings: Res<UiStringManager>,
 ) {
     // 暂停菜单容器
@@ -63,10 +96,19 @@ fn setup_pause_menu(
             ..default()
         }).with_children(|parent| {
             // 继续游戏按钮
-            create_pause_button(parent, &asset_server, &ui_strings.strings.pause_menu.continue_game, "resume");
+            create_pause_button(parent, &ui_strings.strings.pause_menu.continue_game, "resume");
+
+            // 选项按钮，打开设置窗口
+            create_pause_button(parent, &ui_strings.strings.pause_menu.settings, "options");
+
+            // 统计按钮
+            create_pause_button(parent, &ui_strings.strings.pause_menu.statistics, "statistics");
+
+            // 摄影模式按钮
+            create_pause_button(parent, &ui_strings.strings.pause_menu.photo_mode, "photo_mode");
             
             // 退出游戏按钮
-            create_pause_button(parent, &asset_server, &ui_strings.strings.pause_menu.quit, "quit_game");
+            create_pause_button(parent, &ui_strings.strings.pause_menu.quit, "quit_game");
         });
 
         // 提示文本
@@ -84,10 +126,9 @@ fn setup_pause_menu(
     });
 }
 
-/// 创建暂停菜单按钮
-fn create_pause_button(
+/// 创建暂停菜单按钮，主菜单也使用同样的按钮
+pub fn create_pause_button(
     parent: &mut ChildBuilder,
-    asset_server: &AssetServer,
     text: &str,
     action: &str,
 ) {
@@ -121,23 +162,30 @@ fn pause_menu_button_system(
     mut interaction_query: Query<(&Interaction, &Name), (Changed<Interaction>, With<Button>)>,
     mut next_state: ResMut<NextState<GameState>>,
     mut world_manager: ResMut<WorldManager>,
-    mut windows: Query<&mut Window>,
     mut app_exit_events: EventWriter<bevy::app::AppExit>,
     mut commands: Commands,
     mut save_queue: ResMut<crate::game_state::SaveQueue>,
+    mut sounds: EventWriter<SoundEvent>,
+    mut stats_window: ResMut<StatsWindow>,
+    mut game_settings: ResMut<GameSettings>,
 ) {
     for (interaction, name) in &mut interaction_query {
         if *interaction == Interaction::Pressed {
+            sounds.send(SoundEvent::click());
             match name.as_str() {
                 "resume" => {
                     next_state.set(GameState::InGame);
-                    // 重新锁定鼠标
-                    if let Ok(mut window) = windows.get_single_mut() {
-                        window.cursor.grab_mode = bevy::window::CursorGrabMode::Confined;
-                        window.cursor.visible = false;
-                    }
                 }
 
+                "options" => {
+                    game_settings.show_settings = true;
+                }
+                "statistics" => {
+                    stats_window.open = true;
+                }
+                "photo_mode" => {
+                    next_state.set(GameState::PhotoMode);
+                }
                 "quit_game" => {
                     // 保存当前世界（如果有的话）
                     if let Some(current_world) = world_manager.current_world.clone() {
@@ -147,7 +195,7 @@ fn pause_menu_button_system(
                         info!("Saved world before quitting: {}", current_world);
                     }
                     
-                    // 退出游戏
+                    // 退出游戏；autosave 会在退出前阻塞等待最终保存完成（带超时）
                     app_exit_events.send(bevy::app::AppExit);
                 }
                 _ => {}
@@ -156,6 +204,19 @@ fn pause_menu_button_system(
     }
 }
 
+/// 设置窗口打开时隐藏暂停菜单，避免点击设置时按到下面的按钮
+fn hide_pause_menu_behind_settings(
+    game_settings: Res<GameSettings>,
+    mut menu_query: Query<&mut Style, With<PauseMenuUI>>,
+) {
+    let display = if game_settings.show_settings { Display::None } else { Display::Flex };
+    for mut style in menu_query.iter_mut() {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
 /// 清理暂停菜单
 fn cleanup_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenuUI>>) {
     for entity in &query {
@@ -0,0 +1,462 @@
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::pbr::{FogFalloff, FogSettings};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use crate::command::command_line_open;
+use crate::controller::FirstPersonController;
+use crate::cursor::CursorState;
+use crate::game_state::GameState;
+use crate::hud::HudVisibility;
+use crate::inventory_screen::inventory_screen_open;
+use crate::localization::LocalizationManager;
+use crate::ui::GameSettings;
+
+/// 进入和退出摄影模式的按键，Esc 也可以退出
+const PHOTO_MODE_KEY: KeyCode = KeyCode::F4;
+/// 自由摄像机的飞行速度（方块/秒），滚轮每格乘以或除以 `SPEED_STEP`
+const DEFAULT_SPEED: f32 = 8.0;
+const MIN_SPEED: f32 = 0.5;
+const MAX_SPEED: f32 = 100.0;
+const SPEED_STEP: f32 = 1.25;
+/// 倾斜、视野和太阳方向的调节速度（每秒）
+const ROLL_SPEED: f32 = 0.8;
+const FOV_SPEED: f32 = 30.0;
+const SUN_SPEED: f32 = 0.6;
+const MIN_FOV: f32 = 10.0;
+const MAX_FOV: f32 = 120.0;
+/// 雾的能见距离（方块），`-`/`=` 每秒按 `FOG_SPEED` 倍缩放
+const DEFAULT_FOG_VISIBILITY: f32 = 120.0;
+const MIN_FOG_VISIBILITY: f32 = 8.0;
+const MAX_FOG_VISIBILITY: f32 = 1000.0;
+const FOG_SPEED: f32 = 2.0;
+/// 摄像机路径中相邻两个关键帧之间的播放时间（秒）
+const SEGMENT_SECONDS: f32 = 3.0;
+const MAX_KEYFRAMES: usize = 16;
+
+/// 摄像机路径的一个关键帧：位置、朝向和视野（角度）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraKeyframe {
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub fov: f32,
+}
+
+/// 路径从第一个关键帧播放到最后一个所需的时间
+fn path_duration(keyframes: &[CameraKeyframe]) -> f32 {
+    keyframes.len().saturating_sub(1) as f32 * SEGMENT_SECONDS
+}
+
+/// 路径在 `time` 秒时的摄像机。位置按 Catmull-Rom 样条插值，经过每个关键帧且速度连续；
+/// 朝向球面插值，视野线性插值。少于两个关键帧或播放结束时返回 None
+fn sample_path(keyframes: &[CameraKeyframe], time: f32) -> Option<CameraKeyframe> {
+    if keyframes.len() < 2 || !(0.0..path_duration(keyframes)).contains(&time) {
+        return None;
+    }
+    let segment = ((time / SEGMENT_SECONDS) as usize).min(keyframes.len() - 2);
+    let t = time / SEGMENT_SECONDS - segment as f32;
+    let key = |i: isize| keyframes[i.clamp(0, keyframes.len() as isize - 1) as usize];
+    let i = segment as isize;
+    let (p0, p1, p2, p3) = (key(i - 1).position, key(i).position, key(i + 1).position, key(i + 2).position);
+    let (t2, t3) = (t * t, t * t * t);
+    let position = 0.5 * (2.0 * p1 + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2 + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3);
+    let (from, to) = (key(i), key(i + 1));
+    Some(CameraKeyframe {
+        position,
+        rotation: from.rotation.slerp(to.rotation, t),
+        fov: from.fov + (to.fov - from.fov) * t,
+    })
+}
+
+/// 进入摄影模式前的摄像机、HUD、太阳和雾，退出时原样恢复
+struct SavedView {
+    camera: Entity,
+    player: Entity,
+    transform: Transform,
+    fov: f32,
+    fog: Option<FogSettings>,
+    hud_hidden: bool,
+    sun: Option<(Entity, Transform)>,
+}
+
+/// 摄影模式的自由摄像机状态。摄影模式中世界按暂停菜单的约定停止（虚拟时间冻结），
+/// 这里的系统都使用 `Time<Real>`
+#[derive(Resource)]
+pub struct PhotoMode {
+    saved: Option<SavedView>,
+    yaw: f32,
+    pitch: f32,
+    roll: f32,
+    speed: f32,
+    /// 雾的能见距离，None 表示不加雾
+    fog_visibility: Option<f32>,
+    /// 已经应用到摄像机上的能见距离。两者都为 None 时保留摄像机原来的雾设置
+    applied_fog: Option<f32>,
+    keyframes: Vec<CameraKeyframe>,
+    /// 正在播放的路径进度（秒）
+    playback: Option<f32>,
+    show_help: bool,
+}
+
+impl Default for PhotoMode {
+    fn default() -> Self {
+        Self {
+            saved: None,
+            yaw: 0.0,
+            pitch: 0.0,
+            roll: 0.0,
+            speed: DEFAULT_SPEED,
+            fog_visibility: None,
+            applied_fog: None,
+            keyframes: Vec::new(),
+            playback: None,
+            show_help: true,
+        }
+    }
+}
+
+impl PhotoMode {
+    fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, self.roll)
+    }
+}
+
+/// 进入摄影模式时寻找挂在玩家身上的摄像机
+type PlayerCameras<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Parent, &'static Transform, &'static GlobalTransform, &'static Projection, Option<&'static FogSettings>),
+    With<Camera3d>,
+>;
+
+/// 摄影模式中的摄像机
+type PhotoCamera<'w, 's> = Query<'w, 's, (&'static mut Transform, &'static mut Projection), With<Camera3d>>;
+
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhotoMode>()
+           .add_systems(OnEnter(GameState::PhotoMode), enter_photo_mode)
+           .add_systems(OnExit(GameState::PhotoMode), exit_photo_mode)
+           .add_systems(Update, toggle_photo_mode
+               .run_if(in_state(GameState::InGame).or_else(in_state(GameState::PhotoMode)))
+               .run_if(not(inventory_screen_open).and_then(not(command_line_open))))
+           .add_systems(Update, (
+               photo_mode_keys,
+               fly_photo_camera,
+               play_camera_path,
+               adjust_photo_lighting,
+               photo_mode_help_ui,
+           ).chain().run_if(in_state(GameState::PhotoMode)));
+    }
+}
+
+/// F4 进入或退出摄影模式，摄影模式中 Esc 也退出
+fn toggle_photo_mode(
+    keyboard: Res<Input<KeyCode>>,
+    current_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let photo_mode = *current_state.get() == GameState::PhotoMode;
+    let escape = photo_mode && keyboard.just_pressed(KeyCode::Escape);
+    if !(keyboard.just_pressed(PHOTO_MODE_KEY) || escape) {
+        return;
+    }
+    if photo_mode {
+        next_state.set(GameState::InGame);
+    } else {
+        next_state.set(GameState::PhotoMode);
+    }
+}
+
+/// 把摄像机从玩家身上分离出来，保存退出时需要恢复的状态
+fn enter_photo_mode(
+    mut commands: Commands,
+    mut photo: ResMut<PhotoMode>,
+    cameras: PlayerCameras,
+    players: Query<(), With<FirstPersonController>>,
+    sun: Query<(Entity, &Transform), With<DirectionalLight>>,
+    mut hud: ResMut<HudVisibility>,
+) {
+    let Some((camera, parent, transform, global, projection, fog)) = cameras.iter().find(|(_, parent, ..)| players.contains(parent.get())) else {
+        warn!("Photo mode has no player camera to detach");
+        return;
+    };
+    let fov = match projection {
+        Projection::Perspective(perspective) => perspective.fov,
+        Projection::Orthographic(_) => 0.0,
+    };
+    let world = global.compute_transform();
+    let (yaw, pitch, roll) = world.rotation.to_euler(EulerRot::YXZ);
+    *photo = PhotoMode {
+        saved: Some(SavedView {
+            camera,
+            player: parent.get(),
+            transform: *transform,
+            fov,
+            fog: fog.cloned(),
+            hud_hidden: hud.hidden,
+            sun: sun.get_single().ok().map(|(entity, transform)| (entity, *transform)),
+        }),
+        yaw,
+        pitch,
+        roll,
+        keyframes: std::mem::take(&mut photo.keyframes),
+        ..default()
+    };
+    commands.entity(camera).remove_parent().insert(world);
+    hud.hidden = true;
+    info!("Entered photo mode");
+}
+
+/// 把摄像机放回玩家身上，恢复进入前的局部变换、视野、雾、太阳方向和 HUD
+fn exit_photo_mode(
+    mut commands: Commands,
+    mut photo: ResMut<PhotoMode>,
+    mut projections: Query<&mut Projection>,
+    mut transforms: Query<&mut Transform>,
+    mut hud: ResMut<HudVisibility>,
+) {
+    photo.playback = None;
+    let Some(saved) = photo.saved.take() else { return };
+    commands.entity(saved.camera).set_parent(saved.player).insert(saved.transform);
+    match saved.fog {
+        Some(fog) => commands.entity(saved.camera).insert(fog),
+        None => commands.entity(saved.camera).remove::<FogSettings>(),
+    };
+    if let Ok(mut projection) = projections.get_mut(saved.camera) {
+        if let Projection::Perspective(ref mut perspective) = *projection {
+            perspective.fov = saved.fov;
+        }
+    }
+    if let Some((sun, transform)) = saved.sun {
+        if let Ok(mut sun_transform) = transforms.get_mut(sun) {
+            *sun_transform = transform;
+        }
+    }
+    hud.hidden = saved.hud_hidden;
+    info!("Left photo mode");
+}
+
+/// 帮助、关键帧和雾的开关
+fn photo_mode_keys(keyboard: Res<Input<KeyCode>>, mut photo: ResMut<PhotoMode>, cameras: PhotoCamera) {
+    if keyboard.just_pressed(KeyCode::H) {
+        photo.show_help = !photo.show_help;
+    }
+    // 截图前隐藏帮助，这一帧的画面中就没有它
+    if keyboard.just_pressed(KeyCode::F2) {
+        photo.show_help = false;
+    }
+    if keyboard.just_pressed(KeyCode::R) {
+        photo.roll = 0.0;
+    }
+    if keyboard.just_pressed(KeyCode::G) {
+        photo.fog_visibility = match photo.fog_visibility {
+            Some(_) => None,
+            None => Some(DEFAULT_FOG_VISIBILITY),
+        };
+    }
+    let camera = photo.saved.as_ref().map(|saved| saved.camera);
+    if keyboard.just_pressed(KeyCode::K) && photo.keyframes.len() < MAX_KEYFRAMES {
+        if let Some(Ok((transform, Projection::Perspective(perspective)))) = camera.map(|camera| cameras.get(camera)) {
+            let keyframe = CameraKeyframe { position: transform.translation, rotation: transform.rotation, fov: perspective.fov.to_degrees() };
+            photo.keyframes.push(keyframe);
+        }
+    }
+    if keyboard.just_pressed(KeyCode::L) {
+        photo.keyframes.clear();
+        photo.playback = None;
+    }
+    if keyboard.just_pressed(KeyCode::P) {
+        photo.playback = match photo.playback {
+            Some(_) => None,
+            None if photo.keyframes.len() >= 2 => Some(0.0),
+            None => None,
+        };
+    }
+}
+
+/// 自由飞行：鼠标转向，WASD 沿视线方向移动，空格和 Shift 上下，滚轮调节速度，Q/E 倾斜，[ ] 调节视野
+fn fly_photo_camera(
+    keyboard: Res<Input<KeyCode>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut photo: ResMut<PhotoMode>,
+    mut cameras: PhotoCamera,
+    // 光标没有锁定（按住 Alt 或窗口失去焦点）时鼠标移动不转动摄像机
+    (settings, cursor): (Res<GameSettings>, Res<CursorState>),
+    time: Res<Time<Real>>,
+) {
+    let look: Vec2 = mouse_motion.read().map(|motion| motion.delta).sum();
+    let look = if cursor.playing() { look } else { Vec2::ZERO };
+    let steps: f32 = wheel_events.read().map(|event| event.y.signum()).sum();
+    let Some(camera) = photo.saved.as_ref().map(|saved| saved.camera) else { return };
+    if photo.playback.is_some() {
+        return;
+    }
+    let Ok((mut transform, mut projection)) = cameras.get_mut(camera) else { return };
+    let dt = time.delta_seconds();
+
+    let sensitivity = FirstPersonController::default().sensitivity * settings.mouse_sensitivity;
+    let invert_y = if settings.invert_mouse_y { -1.0 } else { 1.0 };
+    photo.yaw -= look.x * sensitivity;
+    photo.pitch = (photo.pitch - look.y * invert_y * sensitivity).clamp(-1.54, 1.54);
+    let roll_input = axis(&keyboard, KeyCode::E, KeyCode::Q);
+    photo.roll += roll_input * ROLL_SPEED * dt;
+    if steps != 0.0 {
+        photo.speed = (photo.speed * SPEED_STEP.powf(steps)).clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    transform.rotation = photo.rotation();
+    let forward = transform.forward();
+    let right = transform.right();
+    let direction = forward * axis(&keyboard, KeyCode::W, KeyCode::S)
+        + right * axis(&keyboard, KeyCode::D, KeyCode::A)
+        + Vec3::Y * axis(&keyboard, KeyCode::Space, KeyCode::ShiftLeft);
+    transform.translation += direction.normalize_or_zero() * photo.speed * dt;
+
+    if let Projection::Perspective(ref mut perspective) = *projection {
+        let fov = axis(&keyboard, KeyCode::BracketRight, KeyCode::BracketLeft);
+        if fov != 0.0 {
+            let degrees = (perspective.fov.to_degrees() + fov * FOV_SPEED * dt).clamp(MIN_FOV, MAX_FOV);
+            perspective.fov = degrees.to_radians();
+        }
+    }
+}
+
+/// 按下 `positive` 为 1，按下 `negative` 为 -1，都按下或都没按为 0
+fn axis(keyboard: &Input<KeyCode>, positive: KeyCode, negative: KeyCode) -> f32 {
+    keyboard.pressed(positive) as i32 as f32 - keyboard.pressed(negative) as i32 as f32
+}
+
+/// 按真实时间沿关键帧路径移动摄像机，播放结束后停在最后一个关键帧，之后可以继续自由飞行
+fn play_camera_path(mut photo: ResMut<PhotoMode>, mut cameras: PhotoCamera, time: Res<Time<Real>>) {
+    let Some(elapsed) = photo.playback else { return };
+    let Some(camera) = photo.saved.as_ref().map(|saved| saved.camera) else { return };
+    let elapsed = elapsed + time.delta_seconds();
+    let keyframe = sample_path(&photo.keyframes, elapsed).or_else(|| photo.keyframes.last().copied());
+    photo.playback = (elapsed < path_duration(&photo.keyframes)).then_some(elapsed);
+    let (Some(keyframe), Ok((mut transform, mut projection))) = (keyframe, cameras.get_mut(camera)) else { return };
+    transform.translation = keyframe.position;
+    transform.rotation = keyframe.rotation;
+    if let Projection::Perspective(ref mut perspective) = *projection {
+        perspective.fov = keyframe.fov.to_radians();
+    }
+    if photo.playback.is_none() {
+        // 从路径的终点继续自由飞行
+        let (yaw, pitch, roll) = keyframe.rotation.to_euler(EulerRot::YXZ);
+        (photo.yaw, photo.pitch, photo.roll) = (yaw, pitch, roll);
+    }
+}
+
+/// 方向键调节太阳方向，- 和 = 调节雾的能见距离；雾的颜色与天空相同
+fn adjust_photo_lighting(
+    keyboard: Res<Input<KeyCode>>,
+    mut photo: ResMut<PhotoMode>,
+    mut commands: Commands,
+    mut sun: Query<&mut Transform, With<DirectionalLight>>,
+    clear_color: Res<ClearColor>,
+    time: Res<Time<Real>>,
+) {
+    let dt = time.delta_seconds();
+    let azimuth = axis(&keyboard, KeyCode::Right, KeyCode::Left);
+    let elevation = axis(&keyboard, KeyCode::Up, KeyCode::Down);
+    if azimuth != 0.0 || elevation != 0.0 {
+        for mut transform in sun.iter_mut() {
+            transform.rotate_y(azimuth * SUN_SPEED * dt);
+            transform.rotate_local_x(elevation * SUN_SPEED * dt);
+        }
+    }
+
+    let Some(camera) = photo.saved.as_ref().map(|saved| saved.camera) else { return };
+    let scale = axis(&keyboard, KeyCode::Equals, KeyCode::Minus);
+    if let Some(visibility) = photo.fog_visibility.as_mut() {
+        if scale != 0.0 {
+            *visibility = (*visibility * FOG_SPEED.powf(scale * dt)).clamp(MIN_FOG_VISIBILITY, MAX_FOG_VISIBILITY);
+        }
+    }
+    if photo.applied_fog == photo.fog_visibility {
+        return;
+    }
+    photo.applied_fog = photo.fog_visibility;
+    match photo.fog_visibility {
+        Some(visibility) => commands.entity(camera).insert(FogSettings {
+            color: clear_color.0,
+            falloff: FogFalloff::from_visibility(visibility),
+            ..default()
+        }),
+        None => commands.entity(camera).remove::<FogSettings>(),
+    };
+}
+
+/// 左上角的操作说明，H 显示或隐藏
+fn photo_mode_help_ui(mut contexts: EguiContexts, photo: Res<PhotoMode>, cameras: PhotoCamera, localization: Res<LocalizationManager>) {
+    if !photo.show_help {
+        return;
+    }
+    let fov = photo
+        .saved
+        .as_ref()
+        .and_then(|saved| match cameras.get(saved.camera) {
+            Ok((_, Projection::Perspective(perspective))) => Some(perspective.fov.to_degrees()),
+            _ => None,
+        })
+        .unwrap_or_default();
+    egui::Window::new(localization.get("game.photo_mode.title"))
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(12.0, 12.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            for key in ["move", "roll", "fov", "sun", "fog", "keyframes", "keys"] {
+                ui.label(localization.get(&format!("game.photo_mode.{}", key)));
+            }
+            ui.separator();
+            ui.label(format!(
+                "{}: {:.1}   {}: {:.0}°   {}: {}",
+                localization.get("game.photo_mode.speed"),
+                photo.speed,
+                localization.get("game.photo_mode.fov_label"),
+                fov,
+                localization.get("game.photo_mode.keyframe_count"),
+                photo.keyframes.len(),
+            ));
+            if photo.playback.is_some() {
+                ui.label(localization.get("game.photo_mode.playing"));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_path_passes_through_keyframes_smoothly() {
+        let keyframes: Vec<CameraKeyframe> = [Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0), Vec3::new(10.0, 5.0, 10.0)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, position)| CameraKeyframe { position, rotation: Quat::from_rotation_y(i as f32), fov: 60.0 + i as f32 * 10.0 })
+            .collect();
+        assert_eq!(path_duration(&keyframes), 2.0 * SEGMENT_SECONDS);
+        assert!(sample_path(&keyframes[..1], 0.0).is_none());
+        assert!(sample_path(&keyframes, path_duration(&keyframes)).is_none());
+
+        // 每段的起点就是关键帧
+        for (i, keyframe) in keyframes.iter().take(2).enumerate() {
+            let sampled = sample_path(&keyframes, i as f32 * SEGMENT_SECONDS).unwrap();
+            assert!(sampled.position.distance(keyframe.position) < 1e-4);
+            assert!(sampled.rotation.angle_between(keyframe.rotation) < 1e-3);
+            assert!((sampled.fov - keyframe.fov).abs() < 1e-4);
+        }
+        // 路径上没有跳变，经过中间关键帧时也连续
+        let step = 0.01;
+        let mut previous = sample_path(&keyframes, 0.0).unwrap();
+        let mut time = step;
+        while let Some(sampled) = sample_path(&keyframes, time) {
+            assert!(sampled.position.distance(previous.position) < 0.2, "jump at {}", time);
+            previous = sampled;
+            time += step;
+        }
+        assert!(previous.position.distance(keyframes[2].position) < 0.2);
+    }
+}
@@ -0,0 +1,10 @@
+//! 不依赖外部随机数库的确定性伪随机数，相同的种子和序号总是得到相同的结果
+
+/// 由种子和序号得到 [0, 1) 的伪随机数（splitmix64）
+pub fn random_unit(seed: u64, index: u64) -> f32 {
+    let mut z = seed.wrapping_add(index.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
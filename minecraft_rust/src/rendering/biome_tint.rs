@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::game_state::{WorldManager, WorldType};
+use crate::net::client::NetClient;
+use crate::world::chunk::Chunk;
+use crate::world::generator::{BiomeType, WorldGenerator, WorldGeneratorConfig};
+
+/// 颜色表文件，放在资源目录中，修改后重新进入世界即可生效
+const TINT_FILE: &str = "biome_tints.json";
+const CHUNK_SIZE: i32 = 32;
+/// 采样生物群系的网格间距。网格按世界坐标对齐，列的颜色在相邻四个网格点之间双线性插值，
+/// 所以生物群系边界上的颜色在一个网格宽度内逐渐过渡，区块边界两侧也完全一致
+const BLEND_SPACING: i32 = 8;
+/// 表中没有的生物群系使用的草地颜色
+const DEFAULT_GRASS: [f32; 3] = [0.57, 0.74, 0.35];
+
+/// 各生物群系的颜色，从 `biome_tints.json` 读取
+#[derive(Deserialize, Clone, Debug)]
+pub struct BiomeTintTable {
+    pub grass: HashMap<BiomeType, [f32; 3]>,
+}
+
+impl BiomeTintTable {
+    /// 读取颜色表，文件缺失或无法解析时使用内置的默认表
+    pub fn load(path: &Path) -> Self {
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| format!("Failed to parse {:?}: {}", path, e)));
+        parsed.unwrap_or_else(|e| {
+            warn!("{}, using the built-in biome tints", e);
+            Self::default()
+        })
+    }
+
+    fn grass(&self, biome: BiomeType) -> Vec3 {
+        Vec3::from(self.grass.get(&biome).copied().unwrap_or(DEFAULT_GRASS))
+    }
+}
+
+impl Default for BiomeTintTable {
+    fn default() -> Self {
+        serde_json::from_str(include_str!("../../assets/biome_tints.json")).expect("built-in biome tints are valid")
+    }
+}
+
+/// 网格构建使用的生物群系颜色：颜色表，以及区块没有记录生物群系时按当前世界种子采样的生成器
+#[derive(Resource, Default)]
+pub struct BiomeTints {
+    table: BiomeTintTable,
+    /// 还没有进入世界、导入的世界和联机时为 None，这时生物群系只来自区块中记录的数据
+    generator: Option<WorldGenerator>,
+}
+
+impl BiomeTints {
+    pub fn new(table: BiomeTintTable, generator: Option<WorldGenerator>) -> Self {
+        Self { table, generator }
+    }
+
+    /// 世界坐标 (x, z) 这一列的生物群系。先用列所在区块中记录的生物群系；区块未加载或没有记录时
+    /// （加入生物群系之前保存的区块）按生成器采样，没有生成器时使用 `chunk` 中最近的一列
+    fn biome_at<'a>(&self, chunk: &Chunk, x: i32, z: i32, get_neighbor: &impl Fn(IVec3) -> Option<&'a Chunk>) -> Option<BiomeType> {
+        let column = IVec2::new(x, z).div_euclid(IVec2::splat(CHUNK_SIZE));
+        let local = IVec2::new(x, z).rem_euclid(IVec2::splat(CHUNK_SIZE)).as_uvec2();
+        let stored = if column == chunk.coord.xz() {
+            chunk.biome(local.x, local.y)
+        } else {
+            get_neighbor(IVec3::new(column.x, chunk.coord.y, column.y)).and_then(|neighbor| neighbor.biome(local.x, local.y))
+        };
+        stored
+            .or_else(|| self.generator.as_ref().map(|generator| generator.biome_at(x, z)))
+            .or_else(|| {
+                let nearest = (IVec2::new(x, z) - chunk.coord.xz() * CHUNK_SIZE).clamp(IVec2::ZERO, IVec2::splat(CHUNK_SIZE - 1)).as_uvec2();
+                chunk.biome(nearest.x, nearest.y)
+            })
+    }
+
+    fn grass_at<'a>(&self, chunk: &Chunk, x: i32, z: i32, get_neighbor: &impl Fn(IVec3) -> Option<&'a Chunk>) -> Vec3 {
+        match self.biome_at(chunk, x, z, get_neighbor) {
+            Some(biome) => self.table.grass(biome),
+            None => Vec3::from(DEFAULT_GRASS),
+        }
+    }
+
+    /// 区块每一列的草地颜色。网格点落在 +X、+Z 方向的相邻区块中时从 `get_neighbor` 读取它们记录的生物群系
+    pub fn column_tints<'a>(&self, chunk: &Chunk, get_neighbor: impl Fn(IVec3) -> Option<&'a Chunk>) -> ColumnTints {
+        const POINTS: i32 = CHUNK_SIZE / BLEND_SPACING + 1;
+        let origin = chunk.coord.xz() * CHUNK_SIZE;
+        let mut grid = Vec::with_capacity((POINTS * POINTS) as usize);
+        for j in 0..POINTS {
+            for i in 0..POINTS {
+                grid.push(self.grass_at(chunk, origin.x + i * BLEND_SPACING, origin.y + j * BLEND_SPACING, &get_neighbor));
+            }
+        }
+        let point = |i: i32, j: i32| grid[(j * POINTS + i) as usize];
+
+        let mut columns = Vec::with_capacity((CHUNK_SIZE * CHUNK_SIZE) as usize);
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let (i, j) = (x / BLEND_SPACING, z / BLEND_SPACING);
+                let tx = (x % BLEND_SPACING) as f32 / BLEND_SPACING as f32;
+                let tz = (z % BLEND_SPACING) as f32 / BLEND_SPACING as f32;
+                let near = point(i, j).lerp(point(i + 1, j), tx);
+                let far = point(i, j + 1).lerp(point(i + 1, j + 1), tx);
+                columns.push(near.lerp(far, tz));
+            }
+        }
+        ColumnTints(columns)
+    }
+}
+
+/// 一个区块 32×32 列的颜色，乘到顶点颜色上
+pub struct ColumnTints(Vec<Vec3>);
+
+impl ColumnTints {
+    pub fn get(&self, x: u32, z: u32) -> Vec3 {
+        self.0[(z * CHUNK_SIZE as u32 + x) as usize]
+    }
+}
+
+/// 进入世界时重新读取颜色表，并按世界种子重建生物群系采样。采样只用于没有记录生物群系的旧区块；
+/// 导入的世界不是生成器生成的，联机时本地也不知道服务器的种子，这两种情况不采样
+pub fn load_biome_tints(
+    mut commands: Commands,
+    generator_config: Res<WorldGeneratorConfig>,
+    world_manager: Res<WorldManager>,
+    net: Option<Res<NetClient>>,
+) {
+    let table = BiomeTintTable::load(&crate::paths::assets_dir().join(TINT_FILE));
+    let imported = world_manager.get_current_world().is_some_and(|info| info.world_type == WorldType::Imported);
+    let generator = (!imported && !net.is_some_and(|net| net.is_remote())).then(|| WorldGenerator::new(generator_config.clone()));
+    commands.insert_resource(BiomeTints::new(table, generator));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 所有列都是同一个生物群系的区块
+    fn chunk_of(coord: IVec3, biome: BiomeType) -> Chunk {
+        let mut chunk = Chunk::new(coord);
+        for x in 0..32 {
+            for z in 0..32 {
+                chunk.set_biome(x, z, biome);
+            }
+        }
+        chunk
+    }
+
+    #[test]
+    fn tints_blend_smoothly_and_match_across_chunk_borders() {
+        let generator = WorldGenerator::new(WorldGeneratorConfig::default());
+        let tints = BiomeTints::new(BiomeTintTable::default(), Some(WorldGenerator::new(WorldGeneratorConfig::default())));
+        // 生成的区块记录了生物群系，没有记录的区块按生成器采样，两者的颜色一致
+        let generated = |coord: IVec3| {
+            let mut chunk = Chunk::new(coord);
+            for x in 0..32 {
+                for z in 0..32 {
+                    chunk.set_biome(x, z, generator.biome_at(coord.x * 32 + x as i32, coord.z * 32 + z as i32));
+                }
+            }
+            chunk
+        };
+        for chunk_x in -20..20 {
+            let (left_chunk, right_chunk) = (generated(IVec3::new(chunk_x, 0, 3)), generated(IVec3::new(chunk_x + 1, 0, 3)));
+            let left = tints.column_tints(&left_chunk, |coord| (coord == right_chunk.coord).then_some(&right_chunk));
+            let right = tints.column_tints(&right_chunk, |_| None);
+            assert_eq!(left.0, tints.column_tints(&Chunk::new(left_chunk.coord), |_| None).0);
+            for z in 0..32 {
+                // 相邻两列的颜色差不超过一个网格间距内的变化
+                let step = left.get(31, z).distance(right.get(0, z));
+                assert!(step <= 1.0 / BLEND_SPACING as f32, "tint jumps by {} at chunk {}", step, chunk_x);
+                for x in 1..32 {
+                    assert!(left.get(x - 1, z).distance(left.get(x, z)) <= 1.0 / BLEND_SPACING as f32);
+                }
+            }
+        }
+        // 网格点上就是该位置生物群系的颜色
+        let table = BiomeTintTable::default();
+        assert_eq!(tints.column_tints(&generated(IVec3::ZERO), |_| None).get(8, 16), table.grass(generator.biome_at(8, 16)));
+    }
+
+    #[test]
+    fn stored_biomes_decide_the_tint_over_the_generator() {
+        let table = BiomeTintTable::default();
+        let (desert, forest) = (table.grass(BiomeType::Desert), table.grass(BiomeType::Forest));
+        let tints = BiomeTints::new(BiomeTintTable::default(), Some(WorldGenerator::new(WorldGeneratorConfig::default())));
+        let left = chunk_of(IVec3::ZERO, BiomeType::Desert);
+        let right = chunk_of(IVec3::X, BiomeType::Forest);
+        let column_tints = tints.column_tints(&left, |coord| (coord == IVec3::X).then_some(&right));
+        assert_eq!(column_tints.get(0, 0), desert);
+        // 最后一个网格间距内向相邻区块的颜色过渡
+        assert_eq!(column_tints.get(24, 0), desert);
+        assert!(column_tints.get(31, 0).distance(forest) < column_tints.get(31, 0).distance(desert));
+
+        // 没有生成器（导入的世界）且相邻区块未加载时沿用本区块边上的生物群系
+        let imported = BiomeTints::new(BiomeTintTable::default(), None);
+        assert!((0..32).all(|x| imported.column_tints(&left, |_| None).get(x, 31) == desert));
+        assert_eq!(imported.column_tints(&Chunk::new(IVec3::ZERO), |_| None).get(5, 5), Vec3::from(DEFAULT_GRASS));
+    }
+}
@@ -0,0 +1,78 @@
+use bevy::asset::load_internal_asset;
+use bevy::pbr::{ExtendedMaterial, MaterialExtension};
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+use crate::game_state::{GameState, WorldState};
+use crate::ui::GameSettings;
+
+/// 区块网格使用的材质：标准材质加上天空亮度和亮度设置
+pub type BlockMaterial = ExtendedMaterial<StandardMaterial, BlockLight>;
+
+const BLOCK_MATERIAL_SHADER: Handle<Shader> = Handle::weak_from_u128(0x6c1f_3a52_9e04_4b7d_8f21_d5a0_c3e9_7b16);
+
+/// 方块材质的全局光照参数。网格顶点只记录天空光照和方块光照等级（见 `packed_light`），
+/// 着色器按这两个参数换算亮度，昼夜变化和调整亮度设置时只更新材质，不重建区块网格
+#[derive(Asset, AsBindGroup, Resource, Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct BlockLight {
+    /// 天空光照的倍数，正午为 1.0，夜晚变暗
+    #[uniform(100)]
+    pub daylight: f32,
+    /// 设置中的亮度（0.0 ~ 1.0），越高暗处的光照曲线越平缓
+    #[uniform(100)]
+    pub gamma: f32,
+}
+
+impl Default for BlockLight {
+    fn default() -> Self {
+        Self { daylight: 1.0, gamma: 0.5 }
+    }
+}
+
+impl MaterialExtension for BlockLight {
+    fn fragment_shader() -> ShaderRef {
+        BLOCK_MATERIAL_SHADER.into()
+    }
+}
+
+/// 把天空光照和方块光照等级（0-15）编码到顶点颜色的 alpha 中，范围 (0, 1]，
+/// 满光照为 1.0，与没有写入光照的顶点一致。着色器解码后再除掉，不影响材质的透明度
+pub fn packed_light(sky: u8, block: u8) -> f32 {
+    (1 + sky.min(15) as u32 * 16 + block.min(15) as u32) as f32 / 256.0
+}
+
+/// 以当前的光照参数创建方块材质
+pub fn block_material(base: StandardMaterial, light: BlockLight) -> BlockMaterial {
+    BlockMaterial { base, extension: light }
+}
+
+pub struct BlockMaterialPlugin;
+
+impl Plugin for BlockMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, BLOCK_MATERIAL_SHADER, "block_material.wgsl", Shader::from_wgsl);
+        app.add_plugins(MaterialPlugin::<BlockMaterial>::default())
+           .init_resource::<BlockLight>()
+           .add_systems(Update, update_block_light
+               .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Loading))));
+    }
+}
+
+/// 天空亮度或亮度设置变化时更新所有方块材质的光照参数
+fn update_block_light(
+    world_state: Res<WorldState>,
+    settings: Option<Res<GameSettings>>,
+    mut light: ResMut<BlockLight>,
+    mut materials: ResMut<Assets<BlockMaterial>>,
+) {
+    let updated = BlockLight {
+        daylight: world_state.daylight(),
+        gamma: settings.map_or(BlockLight::default().gamma, |settings| settings.brightness),
+    };
+    if *light == updated {
+        return;
+    }
+    *light = updated;
+    for (_, material) in materials.iter_mut() {
+        material.extension = updated;
+    }
+}
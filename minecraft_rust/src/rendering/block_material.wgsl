@@ -0,0 +1,60 @@
+// 方块材质的片元着色器：与 bevy 的 pbr.wgsl 相同，只是在光照计算前按顶点中的光照等级调整基础颜色。
+// 顶点颜色的 alpha 编码了天空光照和方块光照等级（见 block_material.rs 的 packed_light），
+// RGB 是生物群系颜色
+
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+    forward_io::{VertexOutput, FragmentOutput},
+}
+
+struct BlockLight {
+    daylight: f32,
+    gamma: f32,
+}
+
+@group(1) @binding(100) var<uniform> block_light: BlockLight;
+
+// 方块光照最亮时的倍数，火把旁边比白天的地面更亮
+const BLOCK_LIGHT_BOOST: f32 = 1.5;
+// 完全黑暗的面仍保留的亮度，避免纯黑
+const MIN_BRIGHTNESS: f32 = 0.02;
+
+// 光照等级（0-15）对应的亮度，亮度设置越高，暗处的曲线越平缓
+fn light_curve(level: f32) -> f32 {
+    let f = level / 15.0;
+    let dark = f / (4.0 - 3.0 * f);
+    let bright = 1.0 - pow(1.0 - f, 4.0);
+    return mix(dark, bright, block_light.gamma);
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+#ifdef VERTEX_COLORS
+    // 天空光照和方块光照各自换算后取较亮的一个，天空光照再乘以天空亮度
+    let code = u32(round(in.color.a * 256.0)) - 1u;
+    let sky = light_curve(f32(code / 16u)) * block_light.daylight;
+    let block = light_curve(f32(code % 16u)) * BLOCK_LIGHT_BOOST;
+    let brightness = max(max(sky, block), MIN_BRIGHTNESS);
+    let base_color = pbr_input.material.base_color;
+    pbr_input.material.base_color = vec4(base_color.rgb * brightness, base_color.a / in.color.a);
+#endif
+
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+    var out: FragmentOutput;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+
+    return out;
+}
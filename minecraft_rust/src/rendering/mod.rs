@@ -1,34 +1,77 @@
 use bevy::prelude::*;
+use bevy::pbr::{CascadeShadowConfig, CascadeShadowConfigBuilder};
 
+pub mod biome_tint;
+pub mod block_material;
 pub mod voxel_mesh;
 pub mod texture_loader;
+pub mod weather;
 
+use biome_tint::{load_biome_tints, BiomeTints};
 use texture_loader::*;
 use voxel_mesh::*;
+use block_material::BlockMaterial;
 use crate::world::storage::ChunkStorage;
+use crate::world::access::WorldView;
+use crate::world::chunk_loader::{ChunkLoaderSet, ChunkWorkBudget};
 use crate::game_state::GameState;
+use crate::ui::GameSettings;
+use crate::world::chunk::LodLevel;
+use crate::world::light::LightSet;
+use crate::world::falling_block::{DroppedItem, FallingBlock};
+use crate::explosion::PrimedTnt;
+use crate::mob::{Zombie, ZOMBIE_SIZE};
+use crate::world::chunk::BlockId;
+use crate::inventory::ItemType;
 
 pub struct RenderingPlugin;
 
 impl Plugin for RenderingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::InGame), (setup_lighting, load_block_textures))
-            .add_systems(Update, update_chunk_meshes.run_if(in_state(GameState::InGame)));
+        app.init_resource::<BiomeTints>()
+            .add_systems(OnEnter(GameState::Loading), (setup_lighting, load_block_textures, load_biome_tints))
+            // 网格构建与区块完成处理共用每帧时间预算，需在其之后运行；网格使用光照，需在光照更新之后
+            .add_systems(Update, update_chunk_meshes.after(ChunkLoaderSet).after(LightSet)
+                .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Loading))))
+            .add_systems(Update, (attach_block_entity_meshes, attach_mob_meshes).run_if(in_state(GameState::InGame)))
+            .add_systems(Update, apply_shadow_cascades)
+            .add_plugins((block_material::BlockMaterialPlugin, weather::WeatherPlugin));
     }
 }
 
+/// 晴天的天空颜色、太阳光和环境光，天气变化时以它们为基准调整（见 `weather`）
+pub const SKY_COLOR: Color = Color::rgb(0.53, 0.81, 0.92);
+pub const SUN_COLOR: Color = Color::rgb(1.0, 0.95, 0.8);
+pub const SUN_ILLUMINANCE: f32 = 10000.0;
+pub const AMBIENT_BRIGHTNESS: f32 = 0.3;
+/// 每个方向光最多的阴影级联数（bevy 的上限）
+pub const MAX_SHADOW_CASCADES: u32 = 4;
+
+/// 按设置生成太阳光的级联阴影配置。每一级每帧都要渲染一次阴影贴图，级联越多开销越大；
+/// 贴图分辨率固定，覆盖距离越远阴影越模糊，第一级的范围越小近处阴影越清晰
+pub fn shadow_cascade_config(settings: &GameSettings) -> CascadeShadowConfig {
+    let first_cascade_far_bound = settings.shadow_first_cascade_bound.max(1.0);
+    CascadeShadowConfigBuilder {
+        num_cascades: settings.shadow_cascades.clamp(1, MAX_SHADOW_CASCADES) as usize,
+        minimum_distance: 0.1,
+        maximum_distance: settings.shadow_distance.max(first_cascade_far_bound + 1.0),
+        first_cascade_far_bound,
+        overlap_proportion: 0.2,
+    }.build()
+}
+
 fn setup_lighting(mut commands: Commands) {
     // 添加环境光
     commands.insert_resource(AmbientLight {
         color: Color::rgb(0.4, 0.4, 0.45),
-        brightness: 0.3,
+        brightness: AMBIENT_BRIGHTNESS,
     });
     
     // 添加方向光（太阳光）
     commands.spawn(DirectionalLightBundle {
         directional_light: DirectionalLight {
-            color: Color::rgb(1.0, 0.95, 0.8),
-            illuminance: 10000.0,
+            color: SUN_COLOR,
+            illuminance: SUN_ILLUMINANCE,
             shadows_enabled: true,
             ..default()
         },
@@ -37,15 +80,93 @@ fn setup_lighting(mut commands: Commands) {
     });
 }
 
+/// 阴影设置变化或太阳光生成时更新级联阴影配置
+fn apply_shadow_cascades(
+    settings: Option<Res<GameSettings>>,
+    mut lights: Query<(Ref<DirectionalLight>, &mut CascadeShadowConfig)>,
+) {
+    let Some(settings) = settings else { return };
+    for (light, mut cascades) in lights.iter_mut() {
+        if !settings.is_changed() && !light.is_added() {
+            continue;
+        }
+        let config = shadow_cascade_config(&settings);
+        if cascades.bounds != config.bounds {
+            *cascades = config;
+        }
+    }
+}
+
+/// 下落方块和点燃的 TNT 显示为整块立方体，掉落物显示为缩小的方块，都使用对应方块的材质
+fn attach_block_entity_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    falling: Query<(Entity, &FallingBlock), Added<FallingBlock>>,
+    dropped: Query<(Entity, &DroppedItem), Added<DroppedItem>>,
+    primed_tnt: Query<Entity, Added<PrimedTnt>>,
+    block_textures: Option<Res<BlockTextures>>,
+) {
+    let Some(block_textures) = block_textures else { return };
+    let falling = falling.iter().map(|(entity, falling)| (entity, falling.block, 0.98));
+    let dropped = dropped.iter().filter_map(|(entity, item)| match item.stack.item_type {
+        ItemType::Block(block) => Some((entity, block, 0.25)),
+        _ => None,
+    });
+    let primed_tnt = primed_tnt.iter().map(|entity| (entity, BlockId::Tnt, 0.98));
+    for (entity, block, size) in falling.chain(dropped).chain(primed_tnt) {
+        let material = block_textures.materials.get(&block)
+            .or(block_textures.grass_materials.side.as_ref());
+        if let Some(material) = material {
+            commands.entity(entity).insert((
+                meshes.add(Mesh::from(shape::Cube { size })),
+                material.clone(),
+            ));
+        }
+    }
+}
+
+/// 僵尸暂时用绿色胶囊体表示，高度与碰撞箱相同，作为子实体向上偏移到碰撞箱中心
+fn attach_mob_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    zombies: Query<Entity, Added<Zombie>>,
+    mut handles: Local<Option<(Handle<Mesh>, Handle<StandardMaterial>)>>,
+) {
+    for entity in zombies.iter() {
+        let (mesh, material) = handles.get_or_insert_with(|| {
+            let radius = ZOMBIE_SIZE.x / 2.0;
+            (
+                meshes.add(Mesh::from(shape::Capsule { radius, depth: ZOMBIE_SIZE.y - radius * 2.0, ..default() })),
+                materials.add(Color::rgb(0.3, 0.55, 0.3).into()),
+            )
+        }).clone();
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh,
+                material,
+                transform: Transform::from_xyz(0.0, ZOMBIE_SIZE.y / 2.0, 0.0),
+                ..default()
+            });
+        });
+    }
+}
+
+const FACE_NEIGHBORS: [IVec3; 6] = [
+    IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z,
+];
+
 fn update_chunk_meshes(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut chunk_queries: ParamSet<(
-        Query<(Entity, &mut crate::world::chunk::Chunk, Option<&ChunkMesh>)>,
+        Query<(Entity, &mut crate::world::chunk::Chunk, Option<&ChunkMesh>, Option<&LodLevel>)>,
         Query<&crate::world::chunk::Chunk>,
     )>,
     block_textures: Option<Res<BlockTextures>>,
+    tints: Res<BiomeTints>,
     chunk_storage: Res<ChunkStorage>,
+    mut budget: Option<ResMut<ChunkWorkBudget>>,
 ) {
     if block_textures.is_none() {
         return; // 纹理还没加载完成
@@ -53,64 +174,80 @@ fn update_chunk_meshes(
     
     let block_textures = block_textures.unwrap();
     
-    // 收集需要更新的chunk信息和数据
+    // 第一遍：只收集需要更新的区块实体，不复制区块数据
     let mut chunks_to_update = Vec::new();
     
-    for (entity, chunk, chunk_mesh) in chunk_queries.p0().iter() {
-        let needs_update = chunk.dirty || chunk_mesh.is_none();
+    for (entity, chunk, chunk_mesh, lod) in chunk_queries.p0().iter() {
+        // 等待卸载或正在卸载的区块不再构建网格
+        let needs_update = (chunk.dirty || chunk_mesh.is_none()) && chunk_storage.get_active(&chunk.coord) == Some(entity);
         if needs_update {
-            chunks_to_update.push((entity, chunk.coord, chunk_mesh.is_some(), chunk.clone()));
+            let lod = lod.map_or(0, |l| l.0);
+            chunks_to_update.push((entity, chunk_mesh.is_some(), lod));
         }
     }
     
-    // 处理需要更新的chunks
-    for (entity, coord, has_mesh, chunk_data) in chunks_to_update {
+    // 第二遍：通过只读查询借用目标区块和邻居区块构建网格
+    // 处理需要更新的chunks，超出时间预算的留到下一帧（每帧至少处理一个）
+    let mut processed = Vec::new();
+    let chunks = chunk_queries.p1();
+    let world = WorldView::new(&chunk_storage, &chunks);
+    let get_chunk = |coord: IVec3| world.active_chunk(coord);
+    for (entity, has_mesh, lod) in chunks_to_update {
+        if !processed.is_empty() && budget.as_ref().is_some_and(|b| b.is_exhausted()) {
+            break;
+        }
+        let started = std::time::Instant::now();
+        let Ok(chunk_data) = chunks.get(entity) else { continue };
+        let coord = chunk_data.coord;
+
         // 如果已有网格，先清除所有子实体（旧的网格）
         if has_mesh {
             commands.entity(entity).despawn_descendants();
         }
         
-        // 计算chunk在世界中的位置
-        let chunk_world_pos = Vec3::new(
-            (coord.x * 32) as f32,
-            (coord.y * 32) as f32,
-            (coord.z * 32) as f32,
-        );
-        
-        // 预先收集所有可能需要的邻居chunk数据
-        let mut neighbor_chunks = std::collections::HashMap::new();
-        for dx in -1..=1 {
-            for dy in -1..=1 {
-                for dz in -1..=1 {
-                    let neighbor_coord = coord + IVec3::new(dx, dy, dz);
-                    if let Some(neighbor_entity) = chunk_storage.get(&neighbor_coord) {
-                        if let Ok(neighbor_chunk) = chunk_queries.p1().get(neighbor_entity) {
-                            neighbor_chunks.insert(neighbor_coord, neighbor_chunk.clone());
-                        }
-                    }
-                }
+        // 远处的半分辨率区块使用合并方块网格，不需要邻居数据
+        if lod > 0 {
+            build_and_spawn_lod_chunk_meshes(&mut commands, entity, chunk_data, &mut meshes, &block_textures, &tints);
+            if let Some(budget) = budget.as_mut() {
+                budget.record_mesh(started.elapsed().as_secs_f32() * 1000.0, has_mesh);
             }
+            processed.push(entity);
+            continue;
         }
         
-        let get_neighbor = |neighbor_coord: IVec3| {
-            neighbor_chunks.get(&neighbor_coord).cloned()
-        };
+        // 全空气区块没有任何面；全实心区块只有在相邻区块的接触面有空气时才会露出边界面，
+        // 相邻区块未加载时先推迟，等它加载后的 ChunkLoadedEvent 会把本区块重新标记为脏
+        let buried = chunk_data.is_fully_solid()
+            && !FACE_NEIGHBORS
+                .iter()
+                .any(|offset| get_chunk(coord + *offset).is_some_and(|n| n.face_has_air(-*offset)));
+        if chunk_data.is_fully_air() || buried {
+            commands.entity(entity).insert((ChunkMesh { coord }, MeshSkipped));
+            processed.push(entity);
+            continue;
+        }
+        commands.entity(entity).remove::<MeshSkipped>();
 
         // 为每种方块类型生成单独的网格
          build_and_spawn_chunk_meshes(
              &mut commands,
              entity,
-             &chunk_data,
+             chunk_data,
              &mut meshes,
              &block_textures,
-             chunk_world_pos,
-             get_neighbor,
+             &tints,
+             get_chunk,
          );
+        
+        if let Some(budget) = budget.as_mut() {
+            budget.record_mesh(started.elapsed().as_secs_f32() * 1000.0, has_mesh);
+        }
+        processed.push(entity);
     }
     
-    // 清除所有dirty标志
-    for (_, mut chunk, _) in chunk_queries.p0().iter_mut() {
-        if chunk.dirty {
+    // 清除已处理区块的dirty标志
+    for entity in processed {
+        if let Ok((_, mut chunk, _, _)) = chunk_queries.p0().get_mut(entity) {
             chunk.dirty = false;
         }
     }
@@ -118,19 +255,19 @@ fn update_chunk_meshes(
 
 
 
-fn build_and_spawn_chunk_meshes(
+fn build_and_spawn_chunk_meshes<'a>(
     commands: &mut Commands,
     chunk_entity: Entity,
     chunk: &crate::world::chunk::Chunk,
     meshes: &mut ResMut<Assets<Mesh>>,
     block_textures: &BlockTextures,
-    _chunk_world_pos: Vec3,
-    get_neighbor: impl Fn(IVec3) -> Option<crate::world::chunk::Chunk>,
+    tints: &BiomeTints,
+    get_neighbor: impl Fn(IVec3) -> Option<&'a crate::world::chunk::Chunk>,
 ) {
     use crate::world::chunk::BlockId;
     
-    // 首先处理石头、泥土、基岩 - 使用原来的网格构建方式
-    let regular_block_types = [BlockId::Stone, BlockId::Dirt, BlockId::Bedrock];
+    // 首先处理草方块以外的实心方块 - 使用原来的网格构建方式
+    let regular_block_types = [BlockId::Stone, BlockId::Dirt, BlockId::Bedrock, BlockId::Obsidian, BlockId::Sand, BlockId::Gravel, BlockId::CraftingTable, BlockId::Chest, BlockId::Tnt, BlockId::Bed, BlockId::Leaves];
     
     for block_type in regular_block_types {
         let mesh = build_chunk_mesh_for_block_type(chunk, block_type, &get_neighbor);
@@ -143,7 +280,7 @@ fn build_and_spawn_chunk_meshes(
             if let Some(material_handle) = block_textures.materials.get(&block_type) {
                 // 创建子entity来渲染区块网格
                 let mesh_entity = commands.spawn((
-                    PbrBundle {
+                    MaterialMeshBundle {
                         mesh: mesh_handle,
                         material: material_handle.clone(),
                         transform: Transform::IDENTITY,
@@ -157,14 +294,27 @@ fn build_and_spawn_chunk_meshes(
         }
     }
     
+    // 原木的端面和侧面使用不同的材质，端面朝向由附加数据决定
+    let (end_mesh, side_mesh) = build_log_meshes(chunk, &get_neighbor);
+    for (mesh, material) in [(end_mesh, Some(&block_textures.log_end_material)), (side_mesh, block_textures.materials.get(&BlockId::Log))] {
+        let Some(material) = material.filter(|_| mesh.count_vertices() > 0) else { continue };
+        let mesh_entity = commands.spawn(MaterialMeshBundle {
+            mesh: meshes.add(mesh),
+            material: material.clone(),
+            transform: Transform::IDENTITY,
+            ..default()
+        }).id();
+        commands.entity(chunk_entity).add_child(mesh_entity);
+    }
+
     // 特别处理草方块 - 使用多纹理构建
-    let (top_mesh, side_mesh, bottom_mesh) = build_chunk_mesh_for_grass_block(chunk, chunk.coord, block_textures, &get_neighbor);
+    let (top_mesh, side_mesh, bottom_mesh) = build_chunk_mesh_for_grass_block(chunk, chunk.coord, &tints.column_tints(chunk, &get_neighbor), &get_neighbor);
     
     // 生成草方块的顶面
     if let Some(mesh) = top_mesh {
         let mesh_handle = meshes.add(mesh);
         if let Some(material) = &block_textures.grass_materials.top {
-            let mesh_entity = commands.spawn(PbrBundle {
+            let mesh_entity = commands.spawn(MaterialMeshBundle {
                 mesh: mesh_handle,
                 material: material.clone(),
                 transform: Transform::IDENTITY,
@@ -178,7 +328,7 @@ fn build_and_spawn_chunk_meshes(
     if let Some(mesh) = side_mesh {
         let mesh_handle = meshes.add(mesh);
         if let Some(material) = &block_textures.grass_materials.side {
-            let mesh_entity = commands.spawn(PbrBundle {
+            let mesh_entity = commands.spawn(MaterialMeshBundle {
                 mesh: mesh_handle,
                 material: material.clone(),
                 transform: Transform::IDENTITY,
@@ -192,7 +342,7 @@ fn build_and_spawn_chunk_meshes(
     if let Some(mesh) = bottom_mesh {
         let mesh_handle = meshes.add(mesh);
         if let Some(material) = &block_textures.grass_materials.bottom {
-            let mesh_entity = commands.spawn(PbrBundle {
+            let mesh_entity = commands.spawn(MaterialMeshBundle {
                 mesh: mesh_handle,
                 material: material.clone(),
                 transform: Transform::IDENTITY,
@@ -202,8 +352,113 @@ fn build_and_spawn_chunk_meshes(
         }
     }
     
+    // 流体使用半透明（水）或自发光（岩浆）材质，表面高度随流动等级变化
+    if chunk.has_fluid() {
+        for fluid in [BlockId::Water, BlockId::Lava] {
+            let mesh = build_fluid_mesh(chunk, fluid, &get_neighbor);
+            if mesh.count_vertices() == 0 {
+                continue;
+            }
+            if let Some(material) = block_textures.materials.get(&fluid) {
+                let mesh_entity = commands.spawn(MaterialMeshBundle {
+                    mesh: meshes.add(mesh),
+                    material: material.clone(),
+                    transform: Transform::IDENTITY,
+                    ..default()
+                }).id();
+                commands.entity(chunk_entity).add_child(mesh_entity);
+            }
+        }
+    }
+
+    // 火把是细柱而不是整块方块，单独构建网格
+    let torch_mesh = build_torch_mesh(chunk);
+    if torch_mesh.count_vertices() > 0 {
+        if let Some(material) = block_textures.materials.get(&BlockId::Torch) {
+            let mesh_entity = commands.spawn(MaterialMeshBundle {
+                mesh: meshes.add(torch_mesh),
+                material: material.clone(),
+                transform: Transform::IDENTITY,
+                ..default()
+            }).id();
+            commands.entity(chunk_entity).add_child(mesh_entity);
+        }
+    }
+
+    // 树苗是两块交叉的面片
+    let sapling_mesh = build_plant_mesh(chunk, BlockId::Sapling);
+    if sapling_mesh.count_vertices() > 0 {
+        if let Some(material) = block_textures.materials.get(&BlockId::Sapling) {
+            let mesh_entity = commands.spawn(MaterialMeshBundle {
+                mesh: meshes.add(sapling_mesh),
+                material: material.clone(),
+                transform: Transform::IDENTITY,
+                ..default()
+            }).id();
+            commands.entity(chunk_entity).add_child(mesh_entity);
+        }
+    }
+
     // 更新chunk mesh状态
     commands.entity(chunk_entity).insert(ChunkMesh {
         coord: chunk.coord,
     });
-}
\ No newline at end of file
+}
+
+/// 半分辨率区块：按方块类型生成合并网格，草方块的顶/侧/底面分别使用各自的材质
+fn build_and_spawn_lod_chunk_meshes(
+    commands: &mut Commands,
+    chunk_entity: Entity,
+    chunk: &crate::world::chunk::Chunk,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    block_textures: &BlockTextures,
+    tints: &BiomeTints,
+) {
+    use crate::world::chunk::BlockId;
+
+    let mut parts = Vec::new();
+    for block_type in [BlockId::Stone, BlockId::Dirt, BlockId::Bedrock, BlockId::Obsidian, BlockId::Sand, BlockId::Gravel, BlockId::CraftingTable, BlockId::Chest, BlockId::Tnt, BlockId::Bed, BlockId::Leaves] {
+        if let Some(material) = block_textures.materials.get(&block_type) {
+            parts.push((build_lod_chunk_mesh(chunk, block_type, None, |_| true), material.clone()));
+        }
+    }
+    // 降采样后没有附加数据，远处的原木都按竖直摆放
+    if let Some(material) = block_textures.materials.get(&BlockId::Log) {
+        parts.push((build_lod_chunk_mesh(chunk, BlockId::Log, None, |face| !matches!(face, CubeFace::Top | CubeFace::Bottom)), material.clone()));
+    }
+    parts.push((
+        build_lod_chunk_mesh(chunk, BlockId::Log, None, |face| matches!(face, CubeFace::Top | CubeFace::Bottom)),
+        block_textures.log_end_material.clone(),
+    ));
+    let grass = &block_textures.grass_materials;
+    let grass_parts: [(&Option<Handle<BlockMaterial>>, fn(CubeFace) -> bool); 3] = [
+        (&grass.top, |face| matches!(face, CubeFace::Top)),
+        (&grass.side, |face| !matches!(face, CubeFace::Top | CubeFace::Bottom)),
+        (&grass.bottom, |face| matches!(face, CubeFace::Bottom)),
+    ];
+    let column_tints = tints.column_tints(chunk, |_| None);
+    for (material, filter) in grass_parts {
+        if let Some(material) = material {
+            // 只有顶面乘以生物群系颜色
+            let tint = filter(CubeFace::Top).then_some(&column_tints);
+            parts.push((build_lod_chunk_mesh(chunk, BlockId::Grass, tint, filter), material.clone()));
+        }
+    }
+
+    for (mesh, material) in parts {
+        if mesh.count_vertices() == 0 {
+            continue;
+        }
+        let mesh_entity = commands.spawn(MaterialMeshBundle {
+            mesh: meshes.add(mesh),
+            material,
+            transform: Transform::IDENTITY,
+            ..default()
+        }).id();
+        commands.entity(chunk_entity).add_child(mesh_entity);
+    }
+
+    commands.entity(chunk_entity).insert(ChunkMesh {
+        coord: chunk.coord,
+    });
+}
@@ -1,14 +1,17 @@
 use bevy::prelude::*;
 use std::collections::HashMap;
 use crate::world::chunk::BlockId;
+use crate::rendering::block_material::{block_material, BlockLight, BlockMaterial};
 
 #[derive(Resource)]
 pub struct BlockTextures {
     pub textures: HashMap<BlockId, Handle<Image>>,
-    pub materials: HashMap<BlockId, Handle<StandardMaterial>>,
+    pub materials: HashMap<BlockId, Handle<BlockMaterial>>,
     // 新增：草方块的多面纹理
     pub grass_textures: GrassTextures,
     pub grass_materials: GrassMaterials,
+    /// 原木两端的年轮材质，`materials` 中原木的材质是树皮
+    pub log_end_material: Handle<BlockMaterial>,
 }
 
 // 草方块纹理集合
@@ -22,9 +25,9 @@ pub struct GrassTextures {
 // 草方块材质集合  
 #[derive(Default)]
 pub struct GrassMaterials {
-    pub top: Option<Handle<StandardMaterial>>,
-    pub side: Option<Handle<StandardMaterial>>,
-    pub bottom: Option<Handle<StandardMaterial>>,
+    pub top: Option<Handle<BlockMaterial>>,
+    pub side: Option<Handle<BlockMaterial>>,
+    pub bottom: Option<Handle<BlockMaterial>>,
 }
 
 #[derive(Resource)]
@@ -35,7 +38,8 @@ pub struct TextureLoadingState {
 pub fn load_block_textures(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut materials: ResMut<Assets<BlockMaterial>>,
+    light: Res<BlockLight>,
 ) {
     info!("Loading block textures...");
     
@@ -46,61 +50,160 @@ pub fn load_block_textures(
     
     // 石头
     let stone_texture = asset_server.load("textures/block/stone.png");
-    let stone_material = materials.add(StandardMaterial {
+    let stone_material = materials.add(block_material(StandardMaterial {
         base_color_texture: Some(stone_texture.clone()),
         unlit: false,
         alpha_mode: AlphaMode::Opaque,
         ..default()
-    });
+    }, *light));
     block_textures.insert(BlockId::Stone, stone_texture);
     block_materials.insert(BlockId::Stone, stone_material);
     
     // 泥土
     let dirt_texture = asset_server.load("textures/block/dirt.png");
-    let dirt_material = materials.add(StandardMaterial {
+    let dirt_material = materials.add(block_material(StandardMaterial {
         base_color_texture: Some(dirt_texture.clone()),
         unlit: false,
         alpha_mode: AlphaMode::Opaque,
         ..default()
-    });
+    }, *light));
     block_textures.insert(BlockId::Dirt, dirt_texture.clone());
     block_materials.insert(BlockId::Dirt, dirt_material);
 
     // 基岩
     let bedrock_texture = asset_server.load("textures/block/bedrock.png");
-    let bedrock_material = materials.add(StandardMaterial {
+    let bedrock_material = materials.add(block_material(StandardMaterial {
         base_color_texture: Some(bedrock_texture.clone()),
         unlit: false,
         alpha_mode: AlphaMode::Opaque,
         ..default()
-    });
+    }, *light));
     block_textures.insert(BlockId::Bedrock, bedrock_texture);
     block_materials.insert(BlockId::Bedrock, bedrock_material);
 
+    // 黑曜石
+    let obsidian_texture = asset_server.load("textures/block/obsidian.png");
+    let obsidian_material = materials.add(block_material(StandardMaterial {
+        base_color_texture: Some(obsidian_texture.clone()),
+        unlit: false,
+        alpha_mode: AlphaMode::Opaque,
+        ..default()
+    }, *light));
+    block_textures.insert(BlockId::Obsidian, obsidian_texture);
+    block_materials.insert(BlockId::Obsidian, obsidian_material);
+
+    // 沙子、沙砾、工作台、箱子、TNT 和床（工作台和 TNT 所有面都使用侧面纹理，原版箱子和床是实体模型，这里用橡木木板和红色羊毛代替）
+    for (block, path) in [
+        (BlockId::Sand, "textures/block/sand.png"),
+        (BlockId::Gravel, "textures/block/gravel.png"),
+        (BlockId::CraftingTable, "textures/block/crafting_table_side.png"),
+        (BlockId::Chest, "textures/block/oak_planks.png"),
+        (BlockId::Tnt, "textures/block/tnt_side.png"),
+        (BlockId::Bed, "textures/block/red_wool.png"),
+    ] {
+        let texture = asset_server.load(path);
+        let material = materials.add(block_material(StandardMaterial {
+            base_color_texture: Some(texture.clone()),
+            unlit: false,
+            alpha_mode: AlphaMode::Opaque,
+            ..default()
+        }, *light));
+        block_textures.insert(block, texture);
+        block_materials.insert(block, material);
+    }
+
+    // 原木：四周是树皮，两端是年轮
+    let log_end_material = materials.add(block_material(StandardMaterial {
+        base_color_texture: Some(asset_server.load("textures/block/oak_log_top.png")),
+        unlit: false,
+        alpha_mode: AlphaMode::Opaque,
+        ..default()
+    }, *light));
+    let log_texture = asset_server.load("textures/block/oak_log.png");
+    let log_material = materials.add(block_material(StandardMaterial {
+        base_color_texture: Some(log_texture.clone()),
+        unlit: false,
+        alpha_mode: AlphaMode::Opaque,
+        ..default()
+    }, *light));
+    block_textures.insert(BlockId::Log, log_texture);
+    block_materials.insert(BlockId::Log, log_material);
+
+    // 树叶：原版纹理是灰色的，乘以绿色；镂空的像素按遮罩裁掉
+    let leaves_texture = asset_server.load("textures/block/oak_leaves.png");
+    let leaves_material = materials.add(block_material(StandardMaterial {
+        base_color: Color::rgb(0.45, 0.7, 0.3),
+        base_color_texture: Some(leaves_texture.clone()),
+        unlit: false,
+        alpha_mode: AlphaMode::Mask(0.5),
+        ..default()
+    }, *light));
+    block_textures.insert(BlockId::Leaves, leaves_texture);
+    block_materials.insert(BlockId::Leaves, leaves_material);
+
+    // 树苗：交叉面片双面显示
+    let sapling_texture = asset_server.load("textures/block/oak_sapling.png");
+    let sapling_material = materials.add(block_material(StandardMaterial {
+        base_color_texture: Some(sapling_texture.clone()),
+        unlit: false,
+        alpha_mode: AlphaMode::Mask(0.5),
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    }, *light));
+    block_textures.insert(BlockId::Sapling, sapling_texture);
+    block_materials.insert(BlockId::Sapling, sapling_material);
+
+    // 水和岩浆：原版纹理是动画帧条，这里只用纯色材质
+    let water_material = materials.add(block_material(StandardMaterial {
+        base_color: Color::rgba(0.25, 0.45, 0.9, 0.7),
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.2,
+        ..default()
+    }, *light));
+    block_materials.insert(BlockId::Water, water_material);
+
+    let lava_material = materials.add(block_material(StandardMaterial {
+        base_color: Color::rgb(0.95, 0.45, 0.1),
+        emissive: Color::rgb(0.9, 0.35, 0.05),
+        alpha_mode: AlphaMode::Opaque,
+        ..default()
+    }, *light));
+    block_materials.insert(BlockId::Lava, lava_material);
+
+    // 火把：木柄颜色加自发光，照亮周围由方块光照负责
+    let torch_material = materials.add(block_material(StandardMaterial {
+        base_color: Color::rgb(0.55, 0.4, 0.2),
+        emissive: Color::rgb(1.0, 0.75, 0.3),
+        alpha_mode: AlphaMode::Opaque,
+        ..default()
+    }, *light));
+    block_materials.insert(BlockId::Torch, torch_material);
+
     // 草方块 - 加载多个纹理
     let grass_top_texture = asset_server.load("textures/block/grass_block_top.png");
     let grass_side_texture = asset_server.load("textures/block/grass_block_side.png");
     
-    let grass_top_material = materials.add(StandardMaterial {
+    let grass_top_material = materials.add(block_material(StandardMaterial {
         base_color_texture: Some(grass_top_texture.clone()),
         unlit: false,
         alpha_mode: AlphaMode::Opaque,
         ..default()
-    });
+    }, *light));
     
-    let grass_side_material = materials.add(StandardMaterial {
+    let grass_side_material = materials.add(block_material(StandardMaterial {
         base_color_texture: Some(grass_side_texture.clone()),
         unlit: false,
         alpha_mode: AlphaMode::Opaque,
         ..default()
-    });
+    }, *light));
     
-    let grass_bottom_material = materials.add(StandardMaterial {
+    let grass_bottom_material = materials.add(block_material(StandardMaterial {
         base_color_texture: Some(dirt_texture.clone()), // 底部使用泥土纹理
         unlit: false,
         alpha_mode: AlphaMode::Opaque,
         ..default()
-    });
+    }, *light));
 
     // 为了兼容性，草方块的默认纹理和材质设为顶部
     block_textures.insert(BlockId::Grass, grass_top_texture.clone());
@@ -126,6 +229,7 @@ pub fn load_block_textures(
         materials: block_materials,
         grass_textures,
         grass_materials,
+        log_end_material,
     });
 
     commands.insert_resource(TextureLoadingState {
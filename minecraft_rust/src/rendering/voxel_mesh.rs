@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
 use crate::world::chunk::{Chunk, BlockId};
+use crate::rendering::biome_tint::ColumnTints;
+use crate::block_registry::metadata_axis;
+use crate::rendering::block_material::packed_light;
 
 const CHUNK_SIZE: u32 = 32;
 
@@ -9,11 +12,17 @@ pub struct ChunkMesh {
     pub coord: IVec3,
 }
 
+/// 区块没有可见几何体（全部为空气，或全部为实心且没有暴露的面），跳过了网格构建。
+/// 区块变脏后会在下一次网格更新时重新判断。
+#[derive(Component)]
+pub struct MeshSkipped;
+
 #[derive(Default)]
 pub struct VoxelMeshBuilder {
     pub positions: Vec<Vec3>,
     pub normals: Vec<Vec3>,
     pub uvs: Vec<[f32; 2]>,
+    pub colors: Vec<[f32; 4]>,
     pub indices: Vec<u32>,
 }
 
@@ -23,6 +32,7 @@ impl VoxelMeshBuilder {
             positions: Vec::new(),
             normals: Vec::new(),
             uvs: Vec::new(),
+            colors: Vec::new(),
             indices: Vec::new(),
         }
     }
@@ -87,6 +97,7 @@ impl VoxelMeshBuilder {
             self.positions.push(*pos);
             self.normals.push(normal);
             self.uvs.push(face_uvs[i]);
+            self.colors.push([1.0; 4]);
         }
     
         let indices = if matches!(face, CubeFace::Top | CubeFace::Bottom) {
@@ -99,6 +110,41 @@ impl VoxelMeshBuilder {
         }
     }
 
+    /// 把光照等级写入最近添加的一个面的顶点颜色 alpha（见 `packed_light`），亮度由方块材质的着色器计算
+    pub fn light_last_face(&mut self, (sky, block): (u8, u8)) {
+        let start = self.colors.len() - 4;
+        for color in &mut self.colors[start..] {
+            color[3] = packed_light(sky, block);
+        }
+    }
+
+    /// 把最近添加的一个面的纹理旋转 90 度，纹理的纵向改为沿面的横向
+    pub fn rotate_last_face_uvs(&mut self) {
+        let start = self.uvs.len() - 4;
+        for uv in &mut self.uvs[start..] {
+            *uv = [uv[1], 1.0 - uv[0]];
+        }
+    }
+
+    /// 把最近添加的一个面的顶点颜色乘以生物群系颜色
+    pub fn tint_last_face(&mut self, tint: Vec3) {
+        let start = self.colors.len() - 4;
+        for color in &mut self.colors[start..] {
+            color[0] *= tint.x;
+            color[1] *= tint.y;
+            color[2] *= tint.z;
+        }
+    }
+
+    /// 添加一个边长为 `size` 的立方体面，用于半分辨率区块的合并方块
+    pub fn add_scaled_cube_face(&mut self, position: Vec3, size: f32, face: CubeFace) {
+        let start = self.positions.len();
+        self.add_cube_face(position, face, 0, false, false);
+        for p in &mut self.positions[start..] {
+            *p = position + (*p - position) * size;
+        }
+    }
+
     pub fn build(self) -> Mesh {
         // 兼容Bevy 0.12 API
         let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
@@ -110,6 +156,7 @@ impl VoxelMeshBuilder {
         mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
         mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
         mesh.set_indices(Some(Indices::U32(self.indices)));
         
         mesh
@@ -127,6 +174,10 @@ pub enum CubeFace {
 }
 
 impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::Top, CubeFace::Bottom, CubeFace::North, CubeFace::South, CubeFace::East, CubeFace::West,
+    ];
+
     pub fn normal(&self) -> Vec3 {
         match self {
             CubeFace::Top => Vec3::Y,
@@ -137,9 +188,17 @@ impl CubeFace {
             CubeFace::West => Vec3::NEG_X,
         }
     }
+
+    /// `add_cube_face` 生成的纹理坐标中，纹理纵向在世界中对应的轴：顶面和底面沿 Z，侧面沿 Y
+    pub fn texture_up_axis(&self) -> IVec3 {
+        match self {
+            CubeFace::Top | CubeFace::Bottom => IVec3::Z,
+            _ => IVec3::Y,
+        }
+    }
 }
 
-pub fn build_chunk_mesh(chunk: &Chunk, get_neighbor: impl Fn(IVec3) -> Option<Chunk>) -> Mesh {
+pub fn build_chunk_mesh<'a>(chunk: &Chunk, get_neighbor: impl Fn(IVec3) -> Option<&'a Chunk>) -> Mesh {
     let mut builder = VoxelMeshBuilder::new();
     
     // 遍历chunk中的每个方块
@@ -154,12 +213,13 @@ pub fn build_chunk_mesh(chunk: &Chunk, get_neighbor: impl Fn(IVec3) -> Option<Ch
                 let position = Vec3::new(x as f32, y as f32, z as f32);
                 
                 // 检查每个面是否需要渲染 (面剔除)
-                let faces_to_render = get_visible_faces(chunk, x, y, z, chunk.coord, &get_neighbor);
+                let faces_to_render = get_visible_faces(chunk, block, x, y, z, chunk.coord, &get_neighbor);
                 
                 let texture_index = get_texture_index_for_block(block);
                 
                 for face in faces_to_render {
                     builder.add_cube_face(position, face, texture_index, false, false);
+                    builder.light_last_face(face_light(chunk, x, y, z, face, &get_neighbor));
                 }
             }
         }
@@ -168,7 +228,7 @@ pub fn build_chunk_mesh(chunk: &Chunk, get_neighbor: impl Fn(IVec3) -> Option<Ch
     builder.build()
 }
 
-pub fn build_chunk_mesh_for_block_type(chunk: &Chunk, block_type: BlockId, get_neighbor: impl Fn(IVec3) -> Option<Chunk>) -> Mesh {
+pub fn build_chunk_mesh_for_block_type<'a>(chunk: &Chunk, block_type: BlockId, get_neighbor: impl Fn(IVec3) -> Option<&'a Chunk>) -> Mesh {
     let mut builder = VoxelMeshBuilder::new();
     
     // 只遍历指定类型的方块
@@ -183,10 +243,11 @@ pub fn build_chunk_mesh_for_block_type(chunk: &Chunk, block_type: BlockId, get_n
                 let position = Vec3::new(x as f32, y as f32, z as f32);
                 
                 // 检查每个面是否需要渲染 (面剔除)
-                let faces_to_render = get_visible_faces(chunk, x, y, z, chunk.coord, &get_neighbor);
+                let faces_to_render = get_visible_faces(chunk, block, x, y, z, chunk.coord, &get_neighbor);
                 
                 for face in faces_to_render {
                     builder.add_cube_face(position, face, 0, false, false); // texture_index 现在不重要了
+                    builder.light_last_face(face_light(chunk, x, y, z, face, &get_neighbor));
                 }
             }
         }
@@ -195,12 +256,119 @@ pub fn build_chunk_mesh_for_block_type(chunk: &Chunk, block_type: BlockId, get_n
     builder.build()
 }
 
-// 为草方块构建特殊的多纹理网格
-pub fn build_chunk_mesh_for_grass_block(
+/// 原木网格：法线沿原木轴向（由附加数据决定）的两个端面使用年轮纹理，其余面使用树皮纹理，
+/// 树皮纹理的纵向顺着原木轴向。返回 (端面网格, 侧面网格)
+pub fn build_log_meshes<'a>(chunk: &Chunk, get_neighbor: impl Fn(IVec3) -> Option<&'a Chunk>) -> (Mesh, Mesh) {
+    let mut ends = VoxelMeshBuilder::new();
+    let mut sides = VoxelMeshBuilder::new();
+    for x in 0..CHUNK_SIZE {
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                if chunk.get_block(x, y, z) != BlockId::Log {
+                    continue;
+                }
+                let axis = metadata_axis(chunk.get_metadata(x, y, z));
+                let position = Vec3::new(x as f32, y as f32, z as f32);
+                for face in get_visible_faces(chunk, BlockId::Log, x, y, z, chunk.coord, &get_neighbor) {
+                    let light = face_light(chunk, x, y, z, face, &get_neighbor);
+                    if face.normal().as_ivec3().abs() == axis {
+                        ends.add_cube_face(position, face, 0, false, false);
+                        ends.light_last_face(light);
+                    } else {
+                        sides.add_cube_face(position, face, 0, false, false);
+                        sides.light_last_face(light);
+                        if axis != face.texture_up_axis() {
+                            sides.rotate_last_face_uvs();
+                        }
+                    }
+                }
+            }
+        }
+    }
+    (ends.build(), sides.build())
+}
+
+/// 流体网格：非下落的流体表面随等级降低，源头为 8/9 格高，流得越远越低；上方有同种流体时填满整格
+pub fn build_fluid_mesh<'a>(chunk: &Chunk, fluid: BlockId, get_neighbor: impl Fn(IVec3) -> Option<&'a Chunk>) -> Mesh {
+    let block_above = |x: u32, y: u32, z: u32| {
+        if y + 1 < CHUNK_SIZE {
+            Some(chunk.get_block(x, y + 1, z))
+        } else {
+            get_neighbor(chunk.coord + IVec3::Y).map(|above| above.get_block(x, 0, z))
+        }
+    };
+
+    let mut builder = VoxelMeshBuilder::new();
+    for x in 0..CHUNK_SIZE {
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                if chunk.get_block(x, y, z) != fluid {
+                    continue;
+                }
+                let metadata = chunk.get_metadata(x, y, z);
+                let height = crate::world::fluid::surface_height(metadata, block_above(x, y, z) == Some(fluid));
+
+                let position = Vec3::new(x as f32, y as f32, z as f32);
+                for face in get_visible_faces(chunk, fluid, x, y, z, chunk.coord, &get_neighbor) {
+                    let start = builder.positions.len();
+                    builder.add_cube_face(position, face, 0, false, false);
+                    builder.light_last_face(face_light(chunk, x, y, z, face, &get_neighbor));
+                    for p in &mut builder.positions[start..] {
+                        p.y = position.y + (p.y - position.y) * height;
+                    }
+                }
+            }
+        }
+    }
+    builder.build()
+}
+
+/// 半分辨率区块的网格：每个 2×2×2 单元输出一个放大的立方体（单元内方块已由降采样统一），
+/// 只在区块内部做面剔除，区块边界上的面始终保留。给出 `tints` 时按单元所在列乘以生物群系颜色
+pub fn build_lod_chunk_mesh(
+    chunk: &Chunk,
+    block_type: BlockId,
+    tints: Option<&ColumnTints>,
+    face_filter: impl Fn(CubeFace) -> bool,
+) -> Mesh {
+    const CELLS: i32 = (CHUNK_SIZE / 2) as i32;
+    let cell_block = |cell: IVec3| {
+        if cell.cmplt(IVec3::ZERO).any() || cell.cmpge(IVec3::splat(CELLS)).any() {
+            BlockId::Air
+        } else {
+            chunk.get_block(cell.x as u32 * 2, cell.y as u32 * 2, cell.z as u32 * 2)
+        }
+    };
+
+    let mut builder = VoxelMeshBuilder::new();
+    for x in 0..CELLS {
+        for y in 0..CELLS {
+            for z in 0..CELLS {
+                let cell = IVec3::new(x, y, z);
+                if cell_block(cell) != block_type {
+                    continue;
+                }
+                for face in CubeFace::ALL {
+                    if face_filter(face) && cell_block(cell + face.normal().as_ivec3()) == BlockId::Air {
+                        builder.add_scaled_cube_face(cell.as_vec3() * 2.0, 2.0, face);
+                        builder.light_last_face((15, 0));
+                        if let Some(tints) = tints {
+                            builder.tint_last_face(tints.get(x as u32 * 2, z as u32 * 2));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    builder.build()
+}
+
+// 为草方块构建特殊的多纹理网格，顶面乘以所在列的生物群系颜色
+pub fn build_chunk_mesh_for_grass_block<'a>(
     chunk: &Chunk,
     chunk_position: IVec3,
-    _block_textures: &crate::rendering::texture_loader::BlockTextures,
-    get_neighbor: impl Fn(IVec3) -> Option<Chunk>
+    tints: &ColumnTints,
+    get_neighbor: impl Fn(IVec3) -> Option<&'a Chunk>
 ) -> (Option<Mesh>, Option<Mesh>, Option<Mesh>) {
     let mut top_builder = VoxelMeshBuilder::new();
     let mut side_builder = VoxelMeshBuilder::new();
@@ -232,29 +400,34 @@ pub fn build_chunk_mesh_for_grass_block(
                     let should_render = if adjacent_x >= 0 && adjacent_x < CHUNK_SIZE as i32 &&
     adjacent_y >= 0 && adjacent_y < CHUNK_SIZE as i32 &&
     adjacent_z >= 0 && adjacent_z < CHUNK_SIZE as i32 {
-    chunk.get_block(adjacent_x as u32, adjacent_y as u32, adjacent_z as u32) == BlockId::Air
+    BlockId::Grass.face_visible_against(chunk.get_block(adjacent_x as u32, adjacent_y as u32, adjacent_z as u32))
 } else {
     let neighbor_coord = chunk_position + IVec3::new(ox, oy, oz);
     let local_x = if adjacent_x < 0 { adjacent_x + CHUNK_SIZE as i32 } else if adjacent_x >= CHUNK_SIZE as i32 { adjacent_x - CHUNK_SIZE as i32 } else { adjacent_x };
     let local_y = if adjacent_y < 0 { adjacent_y + CHUNK_SIZE as i32 } else if adjacent_y >= CHUNK_SIZE as i32 { adjacent_y - CHUNK_SIZE as i32 } else { adjacent_y };
     let local_z = if adjacent_z < 0 { adjacent_z + CHUNK_SIZE as i32 } else if adjacent_z >= CHUNK_SIZE as i32 { adjacent_z - CHUNK_SIZE as i32 } else { adjacent_z };
     if let Some(neighbor_chunk) = get_neighbor(neighbor_coord) {
-        neighbor_chunk.get_block(local_x as u32, local_y as u32, local_z as u32) == BlockId::Air
+        BlockId::Grass.face_visible_against(neighbor_chunk.get_block(local_x as u32, local_y as u32, local_z as u32))
     } else {
         true
     }
 };
 
                     if should_render {
+                        let light = face_light(chunk, x, y, z, face, &get_neighbor);
                         match face {
                             CubeFace::Top => {
                                 top_builder.add_cube_face(render_pos, face, 0, true, false); // 翻转UV
+                                top_builder.light_last_face(light);
+                                top_builder.tint_last_face(tints.get(x, z));
                             },
                             CubeFace::Bottom => {
                                 bottom_builder.add_cube_face(render_pos, face, 0, false, false);
+                                bottom_builder.light_last_face(light);
                             },
                             CubeFace::North | CubeFace::South | CubeFace::East | CubeFace::West => {
                                 side_builder.add_cube_face(render_pos, face, 0, false, true); // 垂直翻转UV
+                                side_builder.light_last_face(light);
                             },
                         }
                     }
@@ -284,45 +457,113 @@ pub fn build_chunk_mesh_for_grass_block(
     (top_mesh, side_mesh, bottom_mesh)
 }
 
-fn get_visible_faces(chunk: &Chunk, x: u32, y: u32, z: u32, chunk_coord: IVec3, get_neighbor: &impl Fn(IVec3) -> Option<Chunk>) -> Vec<CubeFace> {
+/// 面的光照等级 (天空光照, 方块光照)，取面朝向的相邻格子；相邻区块未加载时按露天处理，避免区块边缘出现黑边
+fn face_light<'a>(chunk: &Chunk, x: u32, y: u32, z: u32, face: CubeFace, get_neighbor: &impl Fn(IVec3) -> Option<&'a Chunk>) -> (u8, u8) {
+    let adjacent = IVec3::new(x as i32, y as i32, z as i32) + face.normal().as_ivec3();
+    let offset = adjacent.div_euclid(IVec3::splat(CHUNK_SIZE as i32));
+    let local = adjacent.rem_euclid(IVec3::splat(CHUNK_SIZE as i32)).as_uvec3();
+    let neighbor = if offset == IVec3::ZERO { Some(chunk) } else { get_neighbor(chunk.coord + offset) };
+    match neighbor {
+        Some(neighbor) => (neighbor.get_sky_light(local.x, local.y, local.z), neighbor.get_light(local.x, local.y, local.z)),
+        None => (15, 0),
+    }
+}
+
+/// 火把网格：每个火把是一根立在方块底部中央的细柱，亮度取火把所在格子的光照
+pub fn build_torch_mesh(chunk: &Chunk) -> Mesh {
+    const WIDTH: f32 = 2.0 / 16.0;
+    const HEIGHT: f32 = 10.0 / 16.0;
+    let mut builder = VoxelMeshBuilder::new();
+    for x in 0..CHUNK_SIZE {
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                if chunk.get_block(x, y, z) != BlockId::Torch {
+                    continue;
+                }
+                let position = Vec3::new(x as f32, y as f32, z as f32);
+                let corner = position + Vec3::new((1.0 - WIDTH) / 2.0, 0.0, (1.0 - WIDTH) / 2.0);
+                for face in CubeFace::ALL {
+                    let start = builder.positions.len();
+                    builder.add_cube_face(position, face, 0, false, false);
+                    builder.light_last_face((chunk.get_sky_light(x, y, z), chunk.get_light(x, y, z)));
+                    for p in &mut builder.positions[start..] {
+                        *p = corner + (*p - position) * Vec3::new(WIDTH, HEIGHT, WIDTH);
+                    }
+                }
+            }
+        }
+    }
+    builder.build()
+}
+
+/// 树苗等植物的网格：每株是两块沿对角线交叉的竖直面片，双面显示，亮度取植物所在格子的光照
+pub fn build_plant_mesh(chunk: &Chunk, plant: BlockId) -> Mesh {
+    let mut builder = VoxelMeshBuilder::new();
+    for x in 0..CHUNK_SIZE {
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                if chunk.get_block(x, y, z) != plant {
+                    continue;
+                }
+                let position = Vec3::new(x as f32, y as f32, z as f32);
+                let light = packed_light(chunk.get_sky_light(x, y, z), chunk.get_light(x, y, z));
+                for (from, to) in [(Vec3::ZERO, Vec3::new(1.0, 0.0, 1.0)), (Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 0.0))] {
+                    let base_index = builder.positions.len() as u32;
+                    for (corner, uv) in [(from, [0.0, 1.0]), (to, [1.0, 1.0]), (to + Vec3::Y, [1.0, 0.0]), (from + Vec3::Y, [0.0, 0.0])] {
+                        builder.positions.push(position + corner);
+                        // 法线朝上，两面的光照一致
+                        builder.normals.push(Vec3::Y);
+                        builder.uvs.push(uv);
+                        builder.colors.push([1.0, 1.0, 1.0, light]);
+                    }
+                    builder.indices.extend_from_slice(&[base_index, base_index + 1, base_index + 2, base_index, base_index + 2, base_index + 3]);
+                }
+            }
+        }
+    }
+    builder.build()
+}
+
+fn get_visible_faces<'a>(chunk: &Chunk, block: BlockId, x: u32, y: u32, z: u32, chunk_coord: IVec3, get_neighbor: &impl Fn(IVec3) -> Option<&'a Chunk>) -> Vec<CubeFace> {
     let mut faces = Vec::new();
+    let visible = |neighbor: BlockId| block.face_visible_against(neighbor);
     
-    // 检查每个相邻方块 - 只有当相邻位置是空气时才渲染对应面
+    // 检查每个相邻方块 - 只有当相邻位置不遮挡时才渲染对应面（见 BlockId::face_visible_against）
     let north_visible = if z == 0 {
     if let Some(north_chunk) = get_neighbor(chunk_coord + IVec3::NEG_Z) {
-        north_chunk.get_block(x, y, 31) == BlockId::Air
+        visible(north_chunk.get_block(x, y, 31))
     } else { true }
-} else { chunk.get_block(x, y, z - 1) == BlockId::Air };
+} else { visible(chunk.get_block(x, y, z - 1)) };
 if north_visible { faces.push(CubeFace::North); }
     let south_visible = if z == CHUNK_SIZE - 1 {
     if let Some(south_chunk) = get_neighbor(chunk_coord + IVec3::Z) {
-        south_chunk.get_block(x, y, 0) == BlockId::Air
+        visible(south_chunk.get_block(x, y, 0))
     } else { true }
-} else { chunk.get_block(x, y, z + 1) == BlockId::Air };
+} else { visible(chunk.get_block(x, y, z + 1)) };
 if south_visible { faces.push(CubeFace::South); }
     let west_visible = if x == 0 {
     if let Some(west_chunk) = get_neighbor(chunk_coord + IVec3::NEG_X) {
-        west_chunk.get_block(31, y, z) == BlockId::Air
+        visible(west_chunk.get_block(31, y, z))
     } else { true }
-} else { chunk.get_block(x - 1, y, z) == BlockId::Air };
+} else { visible(chunk.get_block(x - 1, y, z)) };
 if west_visible { faces.push(CubeFace::West); }
     let east_visible = if x == CHUNK_SIZE - 1 {
     if let Some(east_chunk) = get_neighbor(chunk_coord + IVec3::X) {
-        east_chunk.get_block(0, y, z) == BlockId::Air
+        visible(east_chunk.get_block(0, y, z))
     } else { true }
-} else { chunk.get_block(x + 1, y, z) == BlockId::Air };
+} else { visible(chunk.get_block(x + 1, y, z)) };
 if east_visible { faces.push(CubeFace::East); }
     let top_visible = if y == CHUNK_SIZE - 1 {
     if let Some(top_chunk) = get_neighbor(chunk_coord + IVec3::Y) {
-        top_chunk.get_block(x, 0, z) == BlockId::Air
+        visible(top_chunk.get_block(x, 0, z))
     } else { true }
-} else { chunk.get_block(x, y + 1, z) == BlockId::Air };
+} else { visible(chunk.get_block(x, y + 1, z)) };
 if top_visible { faces.push(CubeFace::Top); }
     let bottom_visible = if y == 0 {
     if let Some(bottom_chunk) = get_neighbor(chunk_coord + IVec3::NEG_Y) {
-        bottom_chunk.get_block(x, 31, z) == BlockId::Air
+        visible(bottom_chunk.get_block(x, 31, z))
     } else { true }
-} else { chunk.get_block(x, y - 1, z) == BlockId::Air };
+} else { visible(chunk.get_block(x, y - 1, z)) };
 if bottom_visible { faces.push(CubeFace::Bottom); }
     
     faces
@@ -335,5 +576,95 @@ fn get_texture_index_for_block(block: BlockId) -> usize {
         BlockId::Dirt => 1,
         BlockId::Grass => 2,
         BlockId::Bedrock => 3,
+        BlockId::Water => 4,
+        BlockId::Lava => 5,
+        BlockId::Obsidian => 6,
+        BlockId::Sand => 7,
+        BlockId::Gravel => 8,
+        BlockId::Torch => 9,
+        BlockId::CraftingTable => 10,
+        BlockId::Chest => 11,
+        BlockId::Tnt => 12,
+        BlockId::Bed => 13,
+        BlockId::Log => 14,
+        BlockId::Leaves => 15,
+        BlockId::Sapling => 16,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_registry::axis_metadata;
+    use bevy::render::mesh::VertexAttributeValues;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::time::Instant;
+
+    fn normals(mesh: &Mesh) -> Vec<IVec3> {
+        match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) => normals.iter().map(|n| Vec3::from(*n).as_ivec3()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn log_end_grain_follows_the_placement_axis() {
+        for axis in [IVec3::X, IVec3::Y, IVec3::Z] {
+            let mut chunk = Chunk::new(IVec3::ZERO);
+            chunk.set_block_with_metadata(5, 5, 5, BlockId::Log, axis_metadata(axis));
+            let (ends, sides) = build_log_meshes(&chunk, |_| None);
+
+            let ends = normals(&ends);
+            assert_eq!(ends.len(), 8);
+            assert!(ends.iter().all(|normal| normal.abs() == axis));
+            let sides = normals(&sides);
+            assert_eq!(sides.len(), 16);
+            assert!(sides.iter().all(|normal| normal.abs() != axis));
+        }
     }
-}
\ No newline at end of file
+
+    /// 基准测试，不做断言：实心地形中的区块连同 26 个相邻区块，比较借用相邻区块构建网格和旧做法（先复制全部区块，每次查找相邻区块再复制一次）的耗时。
+    /// 运行：`cargo test --release -- --ignored --nocapture mesh_neighbor_lookup_bench`
+    #[test]
+    #[ignore]
+    fn mesh_neighbor_lookup_bench() {
+        let mut chunks = HashMap::new();
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let coord = IVec3::new(x, y, z);
+                    let mut chunk = Chunk::new(coord);
+                    chunk.blocks.fill(BlockId::Stone as u8);
+                    chunk.compute_solid_blocks();
+                    chunks.insert(coord, chunk);
+                }
+            }
+        }
+        let center = &chunks[&IVec3::ZERO];
+
+        let lookups = Cell::new(0usize);
+        let started = Instant::now();
+        let mesh = build_chunk_mesh_for_block_type(center, BlockId::Stone, |coord| {
+            lookups.set(lookups.get() + 1);
+            chunks.get(&coord)
+        });
+        let borrowed = started.elapsed();
+
+        let started = Instant::now();
+        let copies: HashMap<IVec3, Chunk> = chunks.iter().map(|(coord, chunk)| (*coord, chunk.clone())).collect();
+        for _ in 0..lookups.get() {
+            std::hint::black_box(copies[&IVec3::X].clone());
+        }
+        let copying = started.elapsed();
+
+        println!(
+            "{} vertices, {} neighbor lookups: borrowed meshing {:?}, copying in the old path {:?} ({} MB of block data)",
+            mesh.count_vertices(),
+            lookups.get(),
+            borrowed,
+            copying,
+            (copies.len() + lookups.get()) * Chunk::COUNT / (1024 * 1024),
+        );
+    }
+}
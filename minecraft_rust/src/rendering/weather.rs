@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use bevy::pbr::NotShadowCaster;
+use super::{AMBIENT_BRIGHTNESS, SKY_COLOR, SUN_COLOR, SUN_ILLUMINANCE};
+use crate::controller::{FirstPersonController, EYE_HEIGHT};
+use crate::random::random_unit;
+use crate::game_state::{GameState, Weather, WorldState};
+use crate::ui::GameSettings;
+use crate::world::heightmap::{Heightmap, NO_HEIGHT};
+
+/// 雨强度从 0 变到 1（或反过来）所需的秒数
+const RAIN_FADE_SECONDS: f32 = 5.0;
+/// 粒子质量为 1、雨最大时的雨滴数量
+const MAX_RAIN_DROPS: f32 = 2000.0;
+/// 每帧最多补充的雨滴占总数的比例，开始下雨时逐渐变密
+const SPAWN_FRACTION_PER_FRAME: f32 = 0.05;
+/// 雨滴生成在摄像机周围的圆柱内：半径（格）和相对摄像机的高度范围
+const RAIN_RADIUS: f32 = 16.0;
+const RAIN_SPAWN_HEIGHT: (f32, f32) = (-4.0, 20.0);
+/// 雨滴下落速度（格/秒），以及落到摄像机下方多远时消失
+const RAIN_FALL_SPEED: f32 = 16.0;
+const RAIN_DEPTH: f32 = 12.0;
+/// 下雨时的天空颜色
+const RAIN_SKY_COLOR: Color = Color::rgb(0.45, 0.5, 0.55);
+/// 雨最大时太阳光的饱和度和亮度、环境光亮度的比例
+const RAIN_SUN_SATURATION: f32 = 0.2;
+const RAIN_SUN_ILLUMINANCE: f32 = 0.4;
+const RAIN_AMBIENT: f32 = 0.7;
+
+/// 当前的雨强度（0.0 ~ 1.0），天气变化时平滑过渡
+#[derive(Resource, Default)]
+pub struct RainLevel(pub f32);
+
+/// 下落的雨滴，落到 `stop_y`（所在列最高的遮挡方块顶面）时消失
+#[derive(Component)]
+struct RainDrop {
+    stop_y: f32,
+}
+
+/// 所有雨滴共用的网格和材质
+#[derive(Resource)]
+struct RainAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RainLevel>()
+           .add_systems(Startup, setup_rain_assets)
+           .add_systems(Update, (
+               update_rain_level,
+               update_sky.after(update_rain_level),
+               // 晴天且没有剩余雨滴时两个雨滴系统都不运行
+               spawn_rain_drops.after(update_rain_level).run_if(raining),
+               fall_rain_drops.after(spawn_rain_drops).run_if(any_with_component::<RainDrop>()),
+           ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn raining(level: Res<RainLevel>) -> bool {
+    level.0 > 0.0
+}
+
+fn setup_rain_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(RainAssets {
+        mesh: meshes.add(Mesh::from(shape::Box::new(0.02, 0.6, 0.02))),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.6, 0.7, 0.9, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// 雨强度向当前天气平滑过渡
+fn update_rain_level(world_state: Res<WorldState>, time: Res<Time>, mut level: ResMut<RainLevel>) {
+    let target = if world_state.weather == Weather::Rain { 1.0 } else { 0.0 };
+    if level.0 != target {
+        let step = time.delta_seconds() / RAIN_FADE_SECONDS;
+        level.0 = if target > level.0 { (level.0 + step).min(target) } else { (level.0 - step).max(target) };
+    }
+}
+
+fn mix(from: Color, to: Color, t: f32) -> Color {
+    Color::rgb(
+        from.r() + (to.r() - from.r()) * t,
+        from.g() + (to.g() - from.g()) * t,
+        from.b() + (to.b() - from.b()) * t,
+    )
+}
+
+/// 饱和度降到 `saturation`（0 为灰色）
+fn desaturate(color: Color, saturation: f32) -> Color {
+    let luminance = color.r() * 0.3 + color.g() * 0.59 + color.b() * 0.11;
+    mix(Color::rgb(luminance, luminance, luminance), color, saturation)
+}
+
+/// 天空颜色随昼夜变暗、随雨强度变灰；下雨时太阳光变灰变暗，环境光减弱
+fn update_sky(
+    world_state: Res<WorldState>,
+    level: Res<RainLevel>,
+    mut clear_color: ResMut<ClearColor>,
+    mut ambient: ResMut<AmbientLight>,
+    mut sun: Query<&mut DirectionalLight>,
+) {
+    let rain = level.0;
+    let sky = mix(SKY_COLOR, RAIN_SKY_COLOR, rain) * world_state.daylight();
+    if clear_color.0 != sky {
+        clear_color.0 = sky;
+    }
+    if !level.is_changed() {
+        return;
+    }
+    ambient.brightness = AMBIENT_BRIGHTNESS * (1.0 + (RAIN_AMBIENT - 1.0) * rain);
+    for mut light in sun.iter_mut() {
+        light.color = desaturate(SUN_COLOR, 1.0 + (RAIN_SUN_SATURATION - 1.0) * rain);
+        light.illuminance = SUN_ILLUMINANCE * (1.0 + (RAIN_SUN_ILLUMINANCE - 1.0) * rain);
+    }
+}
+
+/// 在摄像机周围的圆柱内补充雨滴，数量随雨强度和粒子质量设置变化。
+/// 生成位置在所在列的高度图之下（洞穴里、屋檐下）时不生成
+fn spawn_rain_drops(
+    mut commands: Commands,
+    player: Query<&Transform, With<FirstPersonController>>,
+    drops: Query<(), With<RainDrop>>,
+    heightmap: Res<Heightmap>,
+    settings: Res<GameSettings>,
+    level: Res<RainLevel>,
+    assets: Res<RainAssets>,
+    time: Res<Time>,
+    mut spawned: Local<u64>,
+) {
+    let Ok(player) = player.get_single() else { return };
+    let target = (MAX_RAIN_DROPS * settings.particle_quality.clamp(0.0, 1.0) * level.0) as usize;
+    let missing = target.saturating_sub(drops.iter().count());
+    let batch = missing.min((MAX_RAIN_DROPS * SPAWN_FRACTION_PER_FRAME) as usize);
+    if batch == 0 {
+        return;
+    }
+
+    let eye = player.translation + Vec3::Y * EYE_HEIGHT;
+    let seed = time.elapsed_seconds_f64().to_bits();
+    for _ in 0..batch {
+        *spawned += 3;
+        // 圆盘内均匀分布
+        let angle = random_unit(seed, *spawned) * std::f32::consts::TAU;
+        let radius = random_unit(seed, *spawned + 1).sqrt() * RAIN_RADIUS;
+        let height = RAIN_SPAWN_HEIGHT.0 + random_unit(seed, *spawned + 2) * (RAIN_SPAWN_HEIGHT.1 - RAIN_SPAWN_HEIGHT.0);
+        let position = eye + Vec3::new(angle.cos() * radius, height, angle.sin() * radius);
+
+        // 还没加载的列不下雨；没有遮挡方块的列一直落到摄像机下方
+        let Some(column_height) = heightmap.height(position.x.floor() as i32, position.z.floor() as i32) else { continue };
+        let stop_y = if column_height == NO_HEIGHT { f32::MIN } else { column_height as f32 + 1.0 };
+        if position.y <= stop_y {
+            continue;
+        }
+        commands.spawn((
+            RainDrop { stop_y },
+            PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            NotShadowCaster,
+        ));
+    }
+}
+
+/// 雨滴下落，落到地面、掉到摄像机下方太远、离开圆柱范围或雨停时消失
+fn fall_rain_drops(
+    mut commands: Commands,
+    mut drops: Query<(Entity, &mut Transform, &RainDrop), Without<FirstPersonController>>,
+    player: Query<&Transform, With<FirstPersonController>>,
+    level: Res<RainLevel>,
+    time: Res<Time>,
+) {
+    let Ok(player) = player.get_single() else { return };
+    let eye = player.translation + Vec3::Y * EYE_HEIGHT;
+    let fall = RAIN_FALL_SPEED * time.delta_seconds();
+    for (entity, mut transform, drop) in drops.iter_mut() {
+        transform.translation.y -= fall;
+        let position = transform.translation;
+        if level.0 <= 0.0
+            || position.y <= drop.stop_y
+            || position.y < eye.y - RAIN_DEPTH
+            || (position - eye).xz().length() > RAIN_RADIUS * 1.5
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
@@ -0,0 +1,160 @@
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::tasks::IoTaskPool;
+use bevy::window::PrimaryWindow;
+use crossbeam::channel::{unbounded, Receiver, Sender};
+use crate::game_state::{GameState, WorldManager};
+use crate::hud::HudVisibility;
+use crate::localization::LocalizationManager;
+use crate::notifications::Notifications;
+
+const SCREENSHOT_DIR: &str = "screenshots";
+/// 隐藏 HUD 后等待的帧数，保证截取的画面中已经没有 HUD
+const HIDE_HUD_FRAMES: u32 = 2;
+
+/// 截图回调在渲染线程和 IO 线程中执行，通过通道把结果送回主世界
+enum ScreenshotMessage {
+    Captured,
+    Saved(PathBuf),
+    Failed(String),
+}
+
+struct PendingScreenshot {
+    path: PathBuf,
+    frames_left: u32,
+}
+
+#[derive(Resource)]
+struct ScreenshotState {
+    sender: Sender<ScreenshotMessage>,
+    receiver: Receiver<ScreenshotMessage>,
+    pending: Option<PendingScreenshot>,
+    in_flight: bool,    // 已请求截图，等待保存结果
+    restore_hud: bool,  // 截图完成后恢复 HUD
+}
+
+impl Default for ScreenshotState {
+    fn default() -> Self {
+        let (sender, receiver) = unbounded();
+        Self { sender, receiver, pending: None, in_flight: false, restore_hud: false }
+    }
+}
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenshotState>()
+           .add_systems(Update, (request_screenshot, capture_screenshot)
+               .chain()
+               .run_if(in_state(GameState::InGame).or_else(in_state(GameState::PhotoMode))))
+           .add_systems(Update, handle_screenshot_messages);
+    }
+}
+
+/// F2 截图，Shift+F2 截图时隐藏 HUD
+fn request_screenshot(
+    keyboard: Res<Input<KeyCode>>,
+    world_manager: Res<WorldManager>,
+    mut state: ResMut<ScreenshotState>,
+    mut hud: ResMut<HudVisibility>,
+) {
+    if !keyboard.just_pressed(KeyCode::F2) || state.pending.is_some() || state.in_flight {
+        return;
+    }
+    let hide_hud = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if hide_hud && !hud.hidden {
+        hud.hidden = true;
+        state.restore_hud = true;
+    }
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H.%M.%S").to_string();
+    let path = crate::paths::data_path(SCREENSHOT_DIR).join(world_manager.current_world_name()).join(timestamp);
+    state.pending = Some(PendingScreenshot {
+        path,
+        frames_left: if hide_hud { HIDE_HUD_FRAMES } else { 0 },
+    });
+}
+
+fn capture_screenshot(
+    mut state: ResMut<ScreenshotState>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    window: Query<Entity, With<PrimaryWindow>>,
+) {
+    let Some(pending) = state.pending.as_mut() else { return };
+    if pending.frames_left > 0 {
+        pending.frames_left -= 1;
+        return;
+    }
+    let Some(pending) = state.pending.take() else { return };
+    let Ok(window) = window.get_single() else { return };
+
+    let sender = state.sender.clone();
+    let result = screenshot_manager.take_screenshot(window, move |image| {
+        let _ = sender.send(ScreenshotMessage::Captured);
+        // 编码和写盘放到 IO 线程，不阻塞渲染
+        IoTaskPool::get()
+            .spawn(async move {
+                let message = match save_png(image, &pending.path) {
+                    Ok(path) => ScreenshotMessage::Saved(path),
+                    Err(e) => ScreenshotMessage::Failed(e),
+                };
+                let _ = sender.send(message);
+            })
+            .detach();
+    });
+    match result {
+        Ok(()) => state.in_flight = true,
+        Err(e) => {
+            let _ = state.sender.send(ScreenshotMessage::Failed(e.to_string()));
+        }
+    }
+}
+
+/// 保存为 PNG；同一秒内的多张截图在文件名后追加序号
+fn save_png(image: Image, stem: &Path) -> Result<PathBuf, String> {
+    let image = image.try_into_dynamic().map_err(|e| e.to_string())?;
+    if let Some(dir) = stem.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    let mut path = stem.with_extension("png");
+    let mut index = 1;
+    while path.exists() {
+        path = stem.with_file_name(format!("{}_{}.png", stem.file_name().unwrap_or_default().to_string_lossy(), index));
+        index += 1;
+    }
+    // 丢弃 alpha 通道（开启 HDR 时其中存放的是亮度）
+    image
+        .to_rgb8()
+        .save_with_format(&path, image::ImageFormat::Png)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn handle_screenshot_messages(
+    mut state: ResMut<ScreenshotState>,
+    mut hud: ResMut<HudVisibility>,
+    mut notifications: ResMut<Notifications>,
+    localization: Res<LocalizationManager>,
+) {
+    while let Ok(message) = state.receiver.try_recv() {
+        if state.restore_hud {
+            hud.hidden = false;
+            state.restore_hud = false;
+        }
+        match message {
+            ScreenshotMessage::Captured => {}
+            ScreenshotMessage::Saved(path) => {
+                state.in_flight = false;
+                info!("Screenshot saved to {}", path.display());
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                notifications.info(format!("{}: {}", localization.get("game.screenshot.saved"), name));
+            }
+            ScreenshotMessage::Failed(e) => {
+                state.in_flight = false;
+                error!("Failed to save screenshot: {}", e);
+                notifications.error(format!("{}: {}", localization.get("game.screenshot.failed"), e));
+            }
+        }
+    }
+}
@@ -4,21 +4,36 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+/// 等待显示的 `print` 输出的条数上限；没有界面的服务器不取出输出，超出后丢弃最早的
+const MAX_BUFFERED_OUTPUT: usize = 500;
+
 #[derive(Resource, Clone)]
 pub struct ScriptEngine {
     lua: Arc<Mutex<mlua::Lua>>, // guard Lua to satisfy Sync for Bevy resources
     root: PathBuf,
+    /// 脚本 `print` 的输出，由消息记录取出显示
+    output: Arc<Mutex<Vec<String>>>,
 }
 
 impl Default for ScriptEngine {
     fn default() -> Self {
-        Self { lua: Arc::new(Mutex::new(mlua::Lua::new())), root: PathBuf::from("scripts") }
+        Self::new(crate::paths::data_path("scripts"))
     }
 }
 
 impl ScriptEngine {
     pub fn new<P: Into<PathBuf>>(root: P) -> Self {
-        Self { lua: Arc::new(Mutex::new(mlua::Lua::new())), root: root.into() }
+        let lua = mlua::Lua::new();
+        let output = Arc::new(Mutex::new(Vec::new()));
+        if let Err(e) = install_print(&lua, output.clone()) {
+            error!("Failed to install the Lua print binding: {e}");
+        }
+        Self { lua: Arc::new(Mutex::new(lua)), root: root.into(), output }
+    }
+
+    /// 取出脚本 `print` 的输出，没有输出时不分配内存
+    pub fn take_output(&self) -> Vec<String> {
+        std::mem::take(&mut *self.output.lock().expect("Lua output poisoned"))
     }
 
     pub fn root(&self) -> &Path { &self.root }
@@ -65,7 +80,9 @@ impl ScriptEngine {
 
     fn ensure_root_dir(&self) {
         if !self.root.exists() {
-            let _ = fs::create_dir_all(&self.root);
+            if let Err(e) = fs::create_dir_all(&self.root) {
+                error!("Failed to create scripts directory {}: {}", self.root.display(), e);
+            }
         }
     }
 
@@ -85,4 +102,34 @@ impl ScriptEngine {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// 替换 Lua 的 `print`：参数按 `tostring` 转换后用制表符连接，写入日志并留给消息记录显示
+fn install_print(lua: &mlua::Lua, output: Arc<Mutex<Vec<String>>>) -> LuaResult<()> {
+    let print = lua.create_function(move |lua, args: mlua::Variadic<mlua::Value>| {
+        let tostring: Function = lua.globals().get("tostring")?;
+        let parts = args.into_iter().map(|arg| tostring.call::<_, String>(arg)).collect::<LuaResult<Vec<_>>>()?;
+        let line = parts.join("\t");
+        info!("[lua] {}", line);
+        let mut output = output.lock().expect("Lua output poisoned");
+        if output.len() >= MAX_BUFFERED_OUTPUT {
+            output.remove(0);
+        }
+        output.push(line);
+        Ok(())
+    })?;
+    lua.globals().set("print", print)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn print_is_captured_for_the_message_log() {
+        let engine = ScriptEngine::new("missing_scripts");
+        engine.with_lua(|lua| lua.load(r#"print("hello", 42, nil, true)"#).exec()).unwrap();
+        assert_eq!(engine.take_output(), ["hello\t42\tnil\ttrue"]);
+        assert!(engine.take_output().is_empty());
+    }
+}
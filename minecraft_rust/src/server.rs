@@ -0,0 +1,197 @@
+use std::io::BufRead;
+use std::time::Duration;
+use bevy::app::{AppExit, ScheduleRunnerPlugin};
+use bevy::prelude::*;
+use crossbeam::channel::{self, Receiver};
+use crate::autosave::{AutosavePlugin, SaveWorldEvent};
+use crate::block_registry::BlockRegistry;
+use crate::command::{parse_command, Command};
+use crate::crafting::RecipeRegistry;
+use crate::crash_report::CrashReportPlugin;
+use crate::export::{ExportPlugin, ExportRegion, ExportRegionEvent};
+use crate::game_state::{game_rule_name, load_world_state, GameRuleError, GameState, GameStatePlugin, WorldManager, WorldState};
+use crate::net::server::NetServerPlugin;
+use crate::scripting::ScriptEngine;
+use crate::stats::StatsPlugin;
+use crate::world::chunk_loader::ChunkLoadAnchor;
+use crate::world::generator::WorldGeneratorConfig;
+use crate::world::{world_spawn_position, SpawnPoint, WorldPlugin};
+
+/// 服务器模拟频率（每秒 tick 数）
+const TICKS_PER_SECOND: f64 = 20.0;
+
+/// 无窗口服务器：只运行世界、区块加载、脚本和存档系统，不创建窗口、渲染器、界面或玩家控制器
+pub fn run(world_name: Option<String>, port: u16) {
+    let mut app = server_app(world_name, WorldManager::new());
+    if let Err(e) = app.world.resource::<WorldManager>().prepare_current_world() {
+        error!("{}", e);
+        std::process::exit(1);
+    }
+    if let Some(seed) = app.world.resource::<WorldManager>().current_world_seed() {
+        app.world.resource_mut::<WorldGeneratorConfig>().seed = seed;
+    }
+    app.add_plugins(NetServerPlugin { port });
+    app.run();
+}
+
+/// 存档管理器在添加插件前插入，测试中可以把存档目录指向临时目录
+fn server_app(world_name: Option<String>, world_manager: WorldManager) -> App {
+    let mut app = App::new();
+    app
+        .insert_resource(world_manager)
+        .insert_resource(ScriptEngine::default())
+        .insert_resource(BlockRegistry::default())
+        .insert_resource(RecipeRegistry::default())
+        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / TICKS_PER_SECOND))))
+        .add_plugins(GameStatePlugin)
+        .add_plugins(WorldPlugin)
+        .add_plugins(AutosavePlugin)
+        .add_plugins(StatsPlugin)
+        .add_plugins(ExportPlugin)
+        .add_plugins(CrashReportPlugin)
+        .add_plugins(ServerPlugin { world_name })
+        .add_systems(Startup, crate::setup_scripting);
+    app
+}
+
+/// 从标准输入读到的控制台命令
+#[derive(Resource)]
+struct ServerConsole(Receiver<String>);
+
+struct ServerPlugin {
+    world_name: Option<String>,
+}
+
+impl Plugin for ServerPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(world_name) = &self.world_name {
+            app.world.resource_mut::<WorldManager>().current_world = Some(world_name.clone());
+        }
+        app.insert_resource(ServerConsole(spawn_stdin_reader()))
+           .add_systems(OnEnter(GameState::Loading), (load_world_state, start_server_world).chain())
+           .add_systems(Update, handle_console_commands);
+    }
+}
+
+/// 后台线程逐行读取标准输入。输入关闭时线程结束，服务器继续运行
+fn spawn_stdin_reader() -> Receiver<String> {
+    let (sender, receiver) = channel::unbounded();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// 在世界出生点放置区块加载中心，然后直接进入游戏状态；服务器不需要等待出生区域网格
+fn start_server_world(
+    mut commands: Commands,
+    mut world_state: ResMut<WorldState>,
+    generator_config: Res<WorldGeneratorConfig>,
+    world_manager: Res<WorldManager>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let spawn = world_spawn_position(&mut world_state, &generator_config);
+    commands.insert_resource(SpawnPoint(spawn));
+    commands.spawn((TransformBundle::from_transform(Transform::from_translation(spawn)), ChunkLoadAnchor));
+    info!("Server running world '{}' around spawn {:?}", world_manager.current_world_name(), spawn);
+    next_state.set(GameState::InGame);
+}
+
+fn handle_console_commands(
+    console: Res<ServerConsole>,
+    mut save_events: EventWriter<SaveWorldEvent>,
+    mut export_events: EventWriter<ExportRegionEvent>,
+    mut exit_events: EventWriter<AppExit>,
+    mut world_state: ResMut<WorldState>,
+) {
+    for line in console.0.try_iter() {
+        let line = line.trim().trim_start_matches('/');
+        match line {
+            "" => {}
+            "save-all" => {
+                info!("Saving world...");
+                save_events.send(SaveWorldEvent);
+            }
+            // 最终保存由 autosave 在退出前同步完成
+            "stop" => {
+                info!("Stopping server");
+                exit_events.send(AppExit);
+            }
+            _ if line.starts_with("export ") => match parse_export(line) {
+                Some(region) => export_events.send(ExportRegionEvent(region)),
+                None => warn!("Usage: export <x1> <y1> <z1> <x2> <y2> <z2> <name>"),
+            },
+            // 与游戏内的 /weather 命令相同
+            _ if line.starts_with("weather ") => match parse_command(line) {
+                Ok(Command::Weather { weather, seconds }) => {
+                    world_state.set_weather(weather, seconds, 0.5);
+                    info!("Weather set to {:?} for {:.0} seconds", weather, world_state.weather_seconds);
+                }
+                _ => warn!("Usage: weather <clear|rain> [seconds]"),
+            },
+            _ if line.starts_with("gamerule ") => match line.split_whitespace().skip(1).collect::<Vec<_>>().as_slice() {
+                [name] => match world_state.game_rules.get(&game_rule_name(name)) {
+                    Some(value) => info!("{} = {}", game_rule_name(name), value),
+                    None => warn!("Unknown game rule '{}'", name),
+                },
+                [name, value] => match world_state.set_known_rule(name, value) {
+                    Ok(name) => info!("Game rule {} set to {}", name, world_state.game_rules[&name]),
+                    Err(GameRuleError::Unknown) => warn!("Unknown game rule '{}'", name),
+                    Err(GameRuleError::InvalidValue) => warn!("Invalid value '{}' for game rule {}", value, name),
+                },
+                _ => warn!("Usage: gamerule <rule> [value]"),
+            },
+            other => warn!("Unknown command '{}'. Available commands: save-all, export, weather, gamerule, stop", other),
+        }
+    }
+}
+
+/// 解析 `export x1 y1 z1 x2 y2 z2 名称`
+fn parse_export(line: &str) -> Option<ExportRegion> {
+    let args: Vec<&str> = line.split_whitespace().skip(1).collect();
+    let [x1, y1, z1, x2, y2, z2, name] = args.as_slice() else { return None };
+    let coords: Vec<i32> = [x1, y1, z1, x2, y2, z2].iter().map(|v| v.parse().ok()).collect::<Option<_>>()?;
+    Some(ExportRegion::new(
+        IVec3::new(coords[0], coords[1], coords[2]),
+        IVec3::new(coords[3], coords[4], coords[5]),
+        *name,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::WorldStats;
+    use crate::world::chunk::Chunk;
+
+    const TEST_TICKS: usize = 300;
+
+    #[test]
+    fn headless_server_loads_spawn_chunks_and_saves_on_stop() {
+        let saves = std::env::temp_dir().join(format!("headless_server_{}", std::process::id()));
+        let mut app = server_app(Some("test".to_string()), WorldManager { saves_directory: saves.clone(), ..default() });
+        app.finish();
+        app.cleanup();
+        for _ in 0..TEST_TICKS {
+            app.update();
+            // 给后台生成线程留出时间
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(app.world.resource::<State<GameState>>().get(), &GameState::InGame);
+        let world = &mut app.world;
+        assert!(world.query::<&Chunk>().iter(world).next().is_some(), "no chunks loaded around spawn");
+
+        // 与控制台 stop 命令相同：退出前同步完成最终保存
+        app.world.send_event(AppExit);
+        app.update();
+        let world_manager = app.world.resource::<WorldManager>();
+        assert!(world_manager.world_state_path().exists());
+        assert!(WorldStats::path(&world_manager.current_world_dir()).exists());
+        let _ = std::fs::remove_dir_all(&saves);
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use serde::{Deserialize, Serialize};
+use crate::controller::{ControlMode, FirstPersonController};
+use crate::game_state::{GameState, WorldManager};
+use crate::hud::hud_visible;
+use crate::inventory::ItemType;
+use crate::localization::LocalizationManager;
+use crate::ui_strings::UiStringManager;
+use crate::world::chunk::BlockId;
+use mc_common::STATS_FILE;
+
+/// 统计文件的格式版本，记录在世界信息中，见 `game_state::migrations`
+pub const STATS_FORMAT_VERSION: u32 = 1;
+/// 每帧累计的计数写入 `WorldStats` 的间隔（秒）
+const FLUSH_SECONDS: f32 = 5.0;
+/// 单帧位移超过这个距离视为传送，不计入移动距离
+const MAX_FRAME_DISTANCE: f32 = 10.0;
+
+/// 世界统计数据，随世界存档保存，多次游玩的结果累加
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldStats {
+    pub play_time_secs: f64,
+    pub blocks_mined: HashMap<BlockId, u64>,
+    pub blocks_placed: HashMap<BlockId, u64>,
+    pub distance_walked: f64,
+    pub distance_flown: f64,
+    pub deaths: u64,
+}
+
+impl WorldStats {
+    /// 读取世界统计，新世界或文件损坏时从零开始
+    pub fn load(world_dir: &Path) -> Self {
+        let path = world_dir.join(STATS_FILE);
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Failed to parse world stats {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn path(world_dir: &Path) -> PathBuf {
+        world_dir.join(STATS_FILE)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize world stats: {}", e))
+    }
+
+    pub fn record_mined(&mut self, block: BlockId) {
+        *self.blocks_mined.entry(block).or_default() += 1;
+    }
+
+    pub fn record_placed(&mut self, block: BlockId) {
+        *self.blocks_placed.entry(block).or_default() += 1;
+    }
+}
+
+/// 本地累计、尚未写入 `WorldStats` 的每帧计数，避免每帧修改统计资源
+#[derive(Resource, Default)]
+pub struct PendingStats {
+    play_time: f32,
+    walked: f32,
+    flown: f32,
+    since_flush: f32,
+    last_position: Option<Vec3>,
+}
+
+impl PendingStats {
+    fn flush_into(&mut self, stats: &mut WorldStats) {
+        stats.play_time_secs += self.play_time as f64;
+        stats.distance_walked += self.walked as f64;
+        stats.distance_flown += self.flown as f64;
+        self.play_time = 0.0;
+        self.walked = 0.0;
+        self.flown = 0.0;
+        self.since_flush = 0.0;
+    }
+}
+
+/// 统计窗口是否打开，由暂停菜单和设置窗口中的按钮切换
+#[derive(Resource, Default)]
+pub struct StatsWindow {
+    pub open: bool,
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldStats>()
+           .init_resource::<PendingStats>()
+           .init_resource::<StatsWindow>()
+           .add_systems(OnEnter(GameState::Loading), load_world_stats)
+           .add_systems(Update, track_play_stats.run_if(in_state(GameState::InGame)))
+           .add_systems(OnExit(GameState::InGame), flush_stats)
+           .add_systems(Last, flush_stats.run_if(on_event::<AppExit>()));
+
+        if app.is_plugin_added::<EguiPlugin>() {
+            app.add_systems(Update, stats_window_ui.run_if(hud_visible));
+        }
+    }
+}
+
+fn load_world_stats(world_manager: Res<WorldManager>, mut stats: ResMut<WorldStats>, mut pending: ResMut<PendingStats>) {
+    *stats = WorldStats::load(&world_manager.current_world_dir());
+    *pending = PendingStats::default();
+}
+
+fn track_play_stats(
+    player: Query<(&Transform, &FirstPersonController)>,
+    mut pending: ResMut<PendingStats>,
+    mut stats: ResMut<WorldStats>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    pending.play_time += dt;
+    pending.since_flush += dt;
+
+    if let Ok((transform, controller)) = player.get_single() {
+        let position = transform.translation;
+        if let Some(last) = pending.last_position.replace(position) {
+            let moved = position - last;
+            if moved.length() < MAX_FRAME_DISTANCE {
+                match controller.mode {
+                    ControlMode::Walking => pending.walked += Vec2::new(moved.x, moved.z).length(),
+                    ControlMode::Flying => pending.flown += moved.length(),
+                }
+            }
+        }
+    }
+
+    if pending.since_flush >= FLUSH_SECONDS {
+        pending.flush_into(&mut stats);
+    }
+}
+
+/// 把累计的计数写入统计资源；离开游戏状态和退出前运行，保证保存的是最新数据。
+/// 上一帧的位置保留下来，恢复游戏后的移动距离接着累计
+pub fn flush_stats(mut pending: ResMut<PendingStats>, mut stats: ResMut<WorldStats>) {
+    if pending.since_flush > 0.0 {
+        pending.flush_into(&mut stats);
+    }
+}
+
+/// 把秒数格式化为 时:分:秒
+pub fn format_play_time(secs: f64) -> String {
+    let total = secs as u64;
+    format!("{}:{:02}:{:02}", total / 3600, total / 60 % 60, total % 60)
+}
+
+fn stats_window_ui(
+    mut contexts: EguiContexts,
+    mut window: ResMut<StatsWindow>,
+    stats: Res<WorldStats>,
+    localization: Res<LocalizationManager>,
+    ui_strings: Res<UiStringManager>,
+) {
+    if !window.open {
+        return;
+    }
+    egui::Window::new(localization.get("game.stats.title"))
+        .open(&mut window.open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("stats_totals").num_columns(2).show(ui, |ui| {
+                ui.label(localization.get("game.stats.play_time"));
+                ui.label(format_play_time(stats.play_time_secs));
+                ui.end_row();
+                ui.label(localization.get("game.stats.distance_walked"));
+                ui.label(format!("{:.0} m", stats.distance_walked));
+                ui.end_row();
+                ui.label(localization.get("game.stats.distance_flown"));
+                ui.label(format!("{:.0} m", stats.distance_flown));
+                ui.end_row();
+                ui.label(localization.get("game.stats.deaths"));
+                ui.label(stats.deaths.to_string());
+                ui.end_row();
+            });
+
+            ui.separator();
+            let mut blocks: Vec<BlockId> = stats.blocks_mined.keys().chain(stats.blocks_placed.keys()).copied().collect();
+            blocks.sort_by_key(|block| *block as u8);
+            blocks.dedup();
+            egui::Grid::new("stats_blocks").num_columns(3).striped(true).show(ui, |ui| {
+                ui.strong(localization.get("game.stats.block"));
+                ui.strong(localization.get("game.stats.mined"));
+                ui.strong(localization.get("game.stats.placed"));
+                ui.end_row();
+                for block in blocks {
+                    ui.label(ui_strings.get_item_name(ItemType::Block(block).name_key()));
+                    ui.label(stats.blocks_mined.get(&block).copied().unwrap_or(0).to_string());
+                    ui.label(stats.blocks_placed.get(&block).copied().unwrap_or(0).to_string());
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_merge_across_sessions() {
+        let mut stats = WorldStats::default();
+        stats.record_mined(BlockId::Stone);
+        stats.record_mined(BlockId::Stone);
+        stats.record_placed(BlockId::Dirt);
+        let mut pending = PendingStats { play_time: 30.0, walked: 12.5, since_flush: 30.0, ..default() };
+        pending.flush_into(&mut stats);
+
+        let mut reloaded: WorldStats = serde_json::from_str(&stats.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.blocks_mined[&BlockId::Stone], 2);
+        reloaded.record_mined(BlockId::Stone);
+        let mut pending = PendingStats { play_time: 15.0, since_flush: 15.0, ..default() };
+        pending.flush_into(&mut reloaded);
+
+        assert_eq!(reloaded.blocks_mined[&BlockId::Stone], 3);
+        assert_eq!(reloaded.blocks_placed[&BlockId::Dirt], 1);
+        assert_eq!(reloaded.play_time_secs, 45.0);
+        assert_eq!(reloaded.distance_walked, 12.5);
+        assert_eq!(format_play_time(reloaded.play_time_secs + 3600.0), "1:00:45");
+    }
+
+    #[test]
+    fn distance_accumulates_across_frames_and_flushes_on_exit() {
+        use bevy::time::TimeUpdateStrategy;
+        use std::time::Duration;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+           .add_state::<GameState>()
+           .add_event::<AppExit>()
+           .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+           .add_plugins(StatsPlugin);
+        app.world.insert_resource(State::new(GameState::InGame));
+        let player = app.world.spawn((Transform::from_xyz(0.0, 64.0, 0.0), FirstPersonController::default())).id();
+
+        app.update();
+        for _ in 0..4 {
+            app.world.get_mut::<Transform>(player).unwrap().translation.x += 1.5;
+            app.update();
+        }
+        // 还没到写入间隔，距离只在本地累计
+        assert_eq!(app.world.resource::<PendingStats>().walked, 6.0);
+        assert_eq!(app.world.resource::<WorldStats>().distance_walked, 0.0);
+
+        app.world.send_event(AppExit);
+        app.update();
+        assert_eq!(app.world.resource::<WorldStats>().distance_walked, 6.0);
+        assert_eq!(app.world.resource::<PendingStats>().walked, 0.0);
+    }
+}
@@ -1,16 +1,36 @@
 use bevy::prelude::*;
 use bevy::core_pipeline::tonemapping::Tonemapping;
-use bevy::window::{PresentMode, WindowResolution};
+use bevy::window::{PresentMode, PrimaryWindow};
 use bevy::pbr::DirectionalLightShadowMap;
 use bevy::render::camera::Projection;
 use bevy::render::view::Msaa;
-use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::InputSystem;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiSet, EguiSettings};
 use bevy::asset::AssetServer;
 use bevy::diagnostic::DiagnosticsStore;
-use crate::localization::LocalizationManager;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use crate::localization::{LanguageChangeEvent, LocalizationManager};
 use crate::scripting::ScriptEngine;
 use crate::block_registry::BlockRegistry;
-use crate::game_state::GameState;
+use crate::notifications::Notifications;
+use crate::crafting::RecipeRegistry;
+use crate::game_state::{default_game_rules, GameRuleValue, GameState, WorldState};
+use crate::net::client::NetClient;
+use crate::world::chunk_loader::{ChunkLoadQueue, ChunkWorkBudget};
+use crate::world::chunk_stats::{ChunkStats, RATE_HISTORY_SECONDS};
+use crate::memory::{MemoryStats, BYTES_PER_MB};
+use crate::rendering::voxel_mesh::MeshSkipped;
+use crate::debug_overlay::DebugOverlay;
+use crate::stats::StatsWindow;
+use crate::export::ExportWindow;
+use crate::hud::hud_visible;
+use crate::rendering::MAX_SHADOW_CASCADES;
+use crate::command::block_game_input;
+use crate::controller::{KeyBindings, MovementTuning};
+use crate::chunk_presets::{ChunkPreset, ChunkPresets, PRESETS_FILE};
+use crate::display::{apply_window_settings, AppliedWindowSettings, Monitors, WindowModeSetting};
 
 pub struct UiPlugin;
 
@@ -18,11 +38,18 @@ impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(EguiPlugin)
             .insert_resource(DebugState::default())
-            .insert_resource(GameSettings::default())
+            .insert_resource(GameSettings::load())
+            .insert_resource(ChunkPresets::load(&crate::paths::data_path(PRESETS_FILE)))
+            .init_resource::<SettingsWindow>()
             .add_systems(PostStartup, apply_initial_settings)
+            .add_systems(PreUpdate, block_game_input.after(InputSystem).after(EguiSet::ProcessInput).run_if(settings_capture_keys))
+            .add_systems(Update, (save_settings_on_close, apply_ui_scale))
             .add_systems(Update, (
-                debug_ui_system.run_if(in_state(GameState::InGame)),
-                game_settings_ui.run_if(in_state(GameState::InGame)),
+                debug_ui_system.run_if(in_state(GameState::InGame)).run_if(hud_visible),
+                // 暂停菜单的选项按钮和主菜单的设置按钮也会打开设置窗口
+                game_settings_ui
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused)).or_else(in_state(GameState::MainMenu)))
+                    .run_if(hud_visible),
                 crosshair_ui.run_if(in_state(GameState::InGame)),
             ));
     }
@@ -34,26 +61,107 @@ pub struct DebugState {
     pub chunks_loaded: usize,
 }
 
-#[derive(Resource, Clone)]
+/// 设置文件，关闭设置窗口时写入
+const SETTINGS_FILE: &str = "settings.json";
+
+/// 界面缩放的范围；自动缩放以 1080 像素高的窗口为 1 倍，按 0.25 取整，不小于 1 倍
+const MIN_UI_SCALE: f32 = 0.5;
+const MAX_UI_SCALE: f32 = 3.0;
+const AUTO_UI_SCALE_HEIGHT: f64 = 1080.0;
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GameSettings {
+    #[serde(skip)]
     pub show_settings: bool,
     pub msaa_samples: u32,
     pub shadows_enabled: bool,
     pub shadow_resolution: u32,
+    /// 太阳光阴影的级联数、最远距离和第一级的范围（米），见 `rendering::shadow_cascade_config`
+    pub shadow_cascades: u32,
+    pub shadow_distance: f32,
+    pub shadow_first_cascade_bound: f32,
     pub vsync_enabled: bool,
+    pub window_mode: WindowModeSetting,
+    /// 窗口所在显示器的名称，None 为主显示器。按名称保存，显示器重新排列后不变；没有接入时使用主显示器
+    pub monitor_name: Option<String>,
+    /// 窗口化和独占全屏时的分辨率（物理像素）
     pub resolution_width: f32,
     pub resolution_height: f32,
     pub tonemapping_mode: TonemappingMode,
     pub max_loaded_chunks: u32,
     pub surface_priority_quota: u32,
-    pub sphere_loading_radius: f32,
+    pub horizontal_radius: f32,
+    pub vertical_radius_up: f32,
+    pub vertical_radius_down: f32,
+    pub lod_radius: f32,
+    pub unload_margin: f32,
+    pub unload_grace_period: f32,
+    /// 估计内存占用的软上限（MB），超出后减少同时加载的区块，见 `memory::MemoryStats`
+    pub memory_soft_cap_mb: u32,
     pub fov: f32,
+    /// 视角晃动、疾跑视野变化和落地下沉的强度（0.0 ~ 1.0），为 0 时关闭，容易晕动的玩家可以调低
+    pub view_bobbing: f32,
+    pub sprint_fov_kick: f32,
+    pub landing_dip: f32,
+    /// 按住 C 缩放时的视野（度），以及是否改为按一下切换缩放
+    pub zoom_fov: f32,
+    pub zoom_toggle: bool,
     pub mouse_sensitivity: f32,
+    /// HUD 和各窗口的缩放倍数（0.5 ~ 3.0）；`ui_scale_auto` 时按窗口大小自动选择
+    pub ui_scale: f32,
+    pub ui_scale_auto: bool,
+    /// 是否在左上角显示坐标和指向出生点的罗盘
+    pub show_compass: bool,
+    /// 视角平滑程度（0.0 ~ 1.0），为 0 时鼠标移动立即生效
+    pub mouse_smoothing: f32,
+    pub invert_mouse_y: bool,
+    /// 窗口失去焦点（例如切换到其他程序）时是否打开暂停菜单，见 `cursor::CursorState`
+    pub pause_on_focus_loss: bool,
+    /// 暗处的提亮程度（0.0 ~ 1.0），越大洞穴和夜晚越亮
+    pub brightness: f32,
+    /// 粒子效果（雨滴等）的数量比例（0.0 ~ 1.0），为 0 时不生成粒子
+    pub particle_quality: f32,
     pub gravity: f32,
+    /// 行走、冲刺和跳跃的移动参数
+    pub movement: MovementTuning,
+    /// 行走和飞行的按键
+    pub keys: KeyBindings,
     pub chunk_generation_threads: u32,
+    pub chunk_budget_ms: f32,
+    pub master_volume: f32,
+    pub effects_volume: f32,
+    /// 地表风声和地下嗡鸣等环境音的音量
+    pub ambient_volume: f32,
+    pub autosave_minutes: f32,
+    /// 日志等级（trace、debug、info、warn、error），下次启动时生效；命令行参数 --log-level 优先
+    pub log_level: String,
+    /// 界面语言，对应 localization 目录下的文件名
+    pub language: String,
+}
+
+impl GameSettings {
+    /// 读取保存的设置，文件不存在或无法解析时使用默认值
+    pub fn load() -> Self {
+        let path = crate::paths::data_path(SETTINGS_FILE);
+        let Ok(json) = std::fs::read_to_string(&path) else { return Self::default() };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!("Failed to parse {}: {}, using defaults", path.display(), e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(crate::paths::data_path(SETTINGS_FILE), json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            error!("Failed to save {}: {}", SETTINGS_FILE, e);
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TonemappingMode {
     None,
     Reinhard,
@@ -65,6 +173,84 @@ pub enum TonemappingMode {
     BlenderFilmic,
 }
 
+/// 画质预设，一次写入 MSAA、阴影和渲染距离，之后手动修改其中任意一项即显示为自定义。
+/// 阴影的开销主要来自级联数（每级每帧渲染一次阴影贴图）和贴图分辨率，MSAA 按采样数增加填充开销，
+/// 渲染距离决定区块数量，影响内存、网格构建和绘制调用：
+/// - 低：关闭 MSAA，一级 512 阴影只覆盖 48 米，渲染 6 个区块，适合集成显卡
+/// - 中：默认值。4x MSAA，三级 1024 阴影覆盖 128 米，渲染 12 个区块，在一般独立显卡上保持 60 帧
+/// - 高：四级 2048 阴影覆盖 192 米，渲染 16 个区块，远处的山也有阴影
+/// - 极高：8x MSAA，四级 4096 阴影覆盖 320 米，渲染 24 个区块，需要高端显卡和较多内存
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GraphicsPreset {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+
+/// 预设包含的设置项
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PresetValues {
+    msaa_samples: u32,
+    shadow_resolution: u32,
+    shadow_cascades: u32,
+    shadow_distance: f32,
+    shadow_first_cascade_bound: f32,
+    horizontal_radius: f32,
+}
+
+impl PresetValues {
+    fn of(settings: &GameSettings) -> Self {
+        Self {
+            msaa_samples: settings.msaa_samples,
+            shadow_resolution: settings.shadow_resolution,
+            shadow_cascades: settings.shadow_cascades,
+            shadow_distance: settings.shadow_distance,
+            shadow_first_cascade_bound: settings.shadow_first_cascade_bound,
+            horizontal_radius: settings.horizontal_radius,
+        }
+    }
+}
+
+impl GraphicsPreset {
+    const ALL: [GraphicsPreset; 4] = [GraphicsPreset::Low, GraphicsPreset::Medium, GraphicsPreset::High, GraphicsPreset::Ultra];
+
+    fn localization_key(self) -> &'static str {
+        match self {
+            GraphicsPreset::Low => "values.low",
+            GraphicsPreset::Medium => "values.medium",
+            GraphicsPreset::High => "values.high",
+            GraphicsPreset::Ultra => "values.ultra",
+        }
+    }
+
+    fn values(self) -> PresetValues {
+        let (msaa_samples, shadow_resolution, shadow_cascades, shadow_distance, shadow_first_cascade_bound, horizontal_radius) = match self {
+            GraphicsPreset::Low => (1, 512, 1, 48.0, 8.0, 6.0),
+            GraphicsPreset::Medium => (4, 1024, 3, 128.0, 12.0, 12.0),
+            GraphicsPreset::High => (4, 2048, 4, 192.0, 10.0, 16.0),
+            GraphicsPreset::Ultra => (8, 4096, 4, 320.0, 8.0, 24.0),
+        };
+        PresetValues { msaa_samples, shadow_resolution, shadow_cascades, shadow_distance, shadow_first_cascade_bound, horizontal_radius }
+    }
+
+    /// 与当前设置一致的预设，没有时为自定义
+    fn matching(settings: &GameSettings) -> Option<Self> {
+        let current = PresetValues::of(settings);
+        Self::ALL.into_iter().find(|preset| preset.values() == current)
+    }
+
+    fn apply(self, settings: &mut GameSettings) {
+        let values = self.values();
+        settings.msaa_samples = values.msaa_samples;
+        settings.shadow_resolution = values.shadow_resolution;
+        settings.shadow_cascades = values.shadow_cascades;
+        settings.shadow_distance = values.shadow_distance;
+        settings.shadow_first_cascade_bound = values.shadow_first_cascade_bound;
+        settings.horizontal_radius = values.horizontal_radius;
+    }
+}
+
 impl Default for GameSettings {
     fn default() -> Self {
         Self {
@@ -72,17 +258,50 @@ impl Default for GameSettings {
             msaa_samples: 4,
             shadows_enabled: true,
             shadow_resolution: 1024,
+            shadow_cascades: 3,
+            shadow_distance: 128.0,
+            shadow_first_cascade_bound: 12.0,
             vsync_enabled: true,
+            window_mode: WindowModeSetting::Windowed,
+            monitor_name: None,
             resolution_width: 1920.0,
             resolution_height: 1080.0,
             tonemapping_mode: TonemappingMode::Reinhard,
             max_loaded_chunks: 1000,
             surface_priority_quota: 600,
-            sphere_loading_radius: 12.0,
+            horizontal_radius: 12.0,
+            vertical_radius_up: 4.0,
+            vertical_radius_down: 6.0,
+            lod_radius: 0.0,
+            unload_margin: 2.0,
+            unload_grace_period: 10.0,
+            memory_soft_cap_mb: 2048,
             fov: 70.0,
+            view_bobbing: 1.0,
+            sprint_fov_kick: 1.0,
+            landing_dip: 1.0,
+            zoom_fov: 20.0,
+            zoom_toggle: false,
             mouse_sensitivity: 1.0,
+            ui_scale: 1.0,
+            ui_scale_auto: false,
+            show_compass: true,
+            mouse_smoothing: 0.0,
+            invert_mouse_y: false,
+            pause_on_focus_loss: true,
+            brightness: 0.5,
+            particle_quality: 0.5,
             gravity: 9.8,
+            movement: MovementTuning::default(),
+            keys: KeyBindings::default(),
             chunk_generation_threads: 32,
+            chunk_budget_ms: 4.0,
+            master_volume: 1.0,
+            effects_volume: 1.0,
+            ambient_volume: 1.0,
+            autosave_minutes: 5.0,
+            log_level: "info".to_string(),
+            language: "en_us".to_string(),
         }
     }
 }
@@ -92,11 +311,20 @@ fn debug_ui_system(
     mut state: ResMut<DebugState>,
     diagnostics: Res<DiagnosticsStore>,
     mut game_settings: ResMut<GameSettings>,
-    _world_state: Option<Res<crate::world::WorldState>>,
     chunk_storage: Option<Res<crate::world::storage::ChunkStorage>>,
+    load_queue: Option<Res<ChunkLoadQueue>>,
+    // 系统参数最多 16 个，区块流水线统计和内存统计合为一个
+    (chunk_stats, memory_stats): (Option<Res<ChunkStats>>, Option<Res<MemoryStats>>),
+    work_budget: Option<Res<ChunkWorkBudget>>,
+    skipped_chunks: Query<(), With<MeshSkipped>>,
+    mut debug_overlay: ResMut<DebugOverlay>,
+    mut stats_window: ResMut<StatsWindow>,
+    mut export_window: ResMut<ExportWindow>,
     localization: Res<LocalizationManager>,
-    script: Option<Res<ScriptEngine>>,
+    // 系统参数最多 16 个，脚本引擎和通知合为一个
+    (script, mut notifications): (Option<Res<ScriptEngine>>, ResMut<Notifications>),
     registry: Option<Res<BlockRegistry>>,
+    recipes: Option<Res<RecipeRegistry>>,
 ) {
     if let Some(fps_diagnostic) = diagnostics.get(bevy::diagnostic::FrameTimeDiagnosticsPlugin::FPS) {
         if let Some(fps) = fps_diagnostic.smoothed() { state.fps = fps as f32; }
@@ -110,23 +338,105 @@ fn debug_ui_system(
     egui::Window::new(localization.get("game_info")).show(loc, |ui| {
         ui.label(format!("{}: {:.1}", localization.get("fps"), state.fps));
         ui.label(format!("{}: {}", localization.get("chunks_loaded"), state.chunks_loaded));
+        ui.label(format!("{}: {}", localization.get("game.info.mesh_skipped"), skipped_chunks.iter().count()));
+        if let Some(queue) = load_queue {
+            let top = queue.highest_priority().map_or("-".to_string(), |p| format!("{:.1}", p));
+            ui.label(format!("{}: {} ({}: {})", localization.get("game.info.load_queue"), queue.len(),
+                localization.get("game.info.top_priority"), top));
+        }
+        if let Some(budget) = &work_budget {
+            ui.label(format!("{}: {:.2} / {:.1} ms ({:.0}%), {}: {}", localization.get("game.info.chunk_budget"),
+                budget.last_frame_ms, budget.target_ms, budget.usage() * 100.0,
+                localization.get("game.info.task_starts"), budget.start_limit));
+        }
+        if let (Some(stats), Some(budget)) = (chunk_stats, work_budget) {
+            ui.collapsing(localization.get("game.info.chunk_pipeline"), |ui| {
+                chunk_pipeline_ui(ui, &stats, &budget, &localization);
+            });
+        }
+        if let Some(memory) = memory_stats {
+            ui.collapsing(localization.get("game.info.memory"), |ui| {
+                memory_ui(ui, &memory, game_settings.memory_soft_cap_mb, &localization);
+            });
+        }
         if let Some(reg) = registry { ui.label(format!("Script blocks: {}", reg.definitions.len())); }
+        if let Some(recipes) = recipes { ui.label(format!("{}: {}", localization.get("game.info.recipes"), recipes.recipe_count())); }
         ui.separator();
         ui.label(localization.get("controls_hint"));
+        ui.checkbox(&mut debug_overlay.chunk_borders, localization.get("game.debug.show_chunk_borders"));
         ui.separator();
         if ui.button(localization.get("game_settings")).clicked() {
             game_settings.show_settings = !game_settings.show_settings;
         }
+        if ui.button(localization.get("game.stats.title")).clicked() {
+            stats_window.open = !stats_window.open;
+        }
+        if ui.button(localization.get("game.export.title")).clicked() {
+            export_window.open = !export_window.open;
+        }
         if ui.button("Run Lua hello()").clicked() {
             if let Some(engine) = script {
                 if let Err(e) = engine.call0::<()>("hello") {
-                    info!("Lua call error: {e}");
+                    warn!("Lua call error: {e}");
+                    let message = e.to_string();
+                    notifications.error(format!("{}: {}", localization.get("game.scripts.error"), message.lines().next().unwrap_or_default()));
                 }
             }
         }
     });
 }
 
+/// 调试窗口中的区块流水线统计：各阶段的区块数、耗时和每秒生成数的折线图
+fn chunk_pipeline_ui(ui: &mut egui::Ui, stats: &ChunkStats, budget: &ChunkWorkBudget, localization: &LocalizationManager) {
+    let peak = localization.get("game.info.peak");
+    let avg = localization.get("game.info.avg");
+    let current = &stats.current;
+    ui.label(format!("{}: {} ({} {})", localization.get("game.info.queued"), stats.queued, peak, stats.peak_queued));
+    ui.label(format!("{}: {} ({} {})", localization.get("game.info.generating"), stats.generating, peak, stats.peak_generating));
+    ui.label(format!("{}: {} ({} {})", localization.get("game.info.awaiting_mesh"), stats.awaiting_mesh, peak, stats.peak_awaiting_mesh));
+    ui.label(format!("{}: {} ({} {})", localization.get("game.info.generated"), current.generated,
+        localization.get("game.info.from_save"), current.loaded_from_save));
+    ui.label(format!("{}: {} {:.2} ms, {} {:.2} ms", localization.get("game.info.generation_time"),
+        avg, stats.avg_generation_ms, peak, current.peak_generation_ms));
+    ui.label(format!("{}: {} ({} {})", localization.get("game.info.meshes_built"), budget.meshes_built,
+        localization.get("game.info.rebuilds"), budget.mesh_rebuilds));
+    ui.label(format!("{}: {} {:.2} ms, {} {:.2} ms", localization.get("game.info.mesh_time"),
+        avg, budget.avg_mesh_ms, peak, budget.peak_mesh_ms));
+    ui.label(format!("{}: {}", localization.get("game.info.discarded"), current.discarded));
+    ui.label(format!("{}: {}", localization.get("game.info.chunk_reloads"), current.reloaded));
+    ui.label(format!("{}: {} ({} {})", localization.get("game.info.edit_batches"), stats.edit_batches,
+        localization.get("game.info.edited_chunks"), stats.edited_chunks));
+    ui.label(localization.get("game.info.generated_per_second"));
+    rate_plot(ui, &stats.generated_per_second);
+}
+
+/// 调试窗口中的内存估计：总量和各类占用，超出软上限时显示按内存限制的区块数
+fn memory_ui(ui: &mut egui::Ui, memory: &MemoryStats, soft_cap_mb: u32, localization: &LocalizationManager) {
+    let mb = |bytes: usize| bytes as f64 / BYTES_PER_MB as f64;
+    ui.label(format!("{}: {:.1} / {} MB", localization.get("game.info.memory_total"), mb(memory.total_bytes()), soft_cap_mb));
+    ui.label(format!("{}: {:.1} MB ({})", localization.get("game.info.chunk_data"), mb(memory.chunk_bytes()), memory.chunk_count()));
+    ui.label(format!("{}: {:.1} MB ({})", localization.get("game.info.meshes"), mb(memory.mesh_bytes()), memory.mesh_count()));
+    ui.label(format!("{}: {:.1} MB ({})", localization.get("game.info.textures"), mb(memory.texture_bytes()), memory.texture_count()));
+    if let Some(limit) = memory.chunk_limit {
+        ui.colored_label(egui::Color32::YELLOW, format!("{}: {}", localization.get("game.info.memory_chunk_limit"), limit));
+    }
+}
+
+/// 用 egui 的绘图接口画每秒生成数的折线，纵轴从 0 到最大值
+fn rate_plot(ui: &mut egui::Ui, values: &VecDeque<u32>) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width().max(120.0), 48.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(80));
+    let max = values.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let step = rect.width() / (RATE_HISTORY_SECONDS - 1) as f32;
+    let points: Vec<egui::Pos2> = values.iter().enumerate()
+        .map(|(i, &value)| egui::pos2(rect.left() + i as f32 * step, rect.bottom() - value as f32 / max * rect.height()))
+        .collect();
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, egui::Color32::LIGHT_GREEN)));
+    painter.text(rect.left_top() + egui::vec2(4.0, 2.0), egui::Align2::LEFT_TOP, format!("{}", max as u32),
+        egui::FontId::monospace(10.0), egui::Color32::GRAY);
+}
+
 #[derive(Component)]
 struct CrosshairMarker;
 
@@ -138,56 +448,190 @@ fn crosshair_ui(
         return;
     }
 
+    // 铺满屏幕的容器把准星居中，两条线按准星大小的百分比定位，窗口大小变化时不会偏移
     commands
         .spawn((
             NodeBundle {
                 style: Style {
                     position_type: PositionType::Absolute,
-                    left: Val::Percent(50.0),
-                    top: Val::Percent(50.0),
-                    margin: UiRect {
-                        left: Val::Px(-10.0),
-                        top: Val::Px(-1.0),
-                        ..default()
-                    },
-                    width: Val::Px(20.0),
-                    height: Val::Px(2.0),
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
                     ..default()
                 },
-                background_color: Color::WHITE.into(),
                 z_index: ZIndex::Global(1000),
                 ..default()
             },
             CrosshairMarker,
-        ));
-
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    position_type: PositionType::Absolute,
-                    left: Val::Percent(50.0),
-                    top: Val::Percent(50.0),
-                    margin: UiRect {
-                        left: Val::Px(-1.0),
-                        top: Val::Px(-10.0),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(20.0),
+                        height: Val::Px(20.0),
                         ..default()
                     },
-                    width: Val::Px(2.0),
-                    height: Val::Px(20.0),
                     ..default()
-                },
-                background_color: Color::WHITE.into(),
-                z_index: ZIndex::Global(1000),
-                ..default()
-            },
-            CrosshairMarker,
-        ));
+                })
+                .with_children(|crosshair| {
+                    crosshair.spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Percent(0.0),
+                            top: Val::Percent(45.0),
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(10.0),
+                            ..default()
+                        },
+                        background_color: Color::WHITE.into(),
+                        ..default()
+                    });
+                    crosshair.spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Percent(45.0),
+                            top: Val::Percent(0.0),
+                            width: Val::Percent(10.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: Color::WHITE.into(),
+                        ..default()
+                    });
+                });
+        });
+}
+
+/// 设置窗口的分页
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum SettingsTab {
+    #[default]
+    Video,
+    Performance,
+    Controls,
+    Audio,
+    Language,
+    /// 当前世界的游戏规则，只在单人游戏的世界中显示
+    GameRules,
+}
+
+impl SettingsTab {
+    const ALL: [SettingsTab; 6] = [
+        SettingsTab::Video,
+        SettingsTab::Performance,
+        SettingsTab::Controls,
+        SettingsTab::Audio,
+        SettingsTab::Language,
+        SettingsTab::GameRules,
+    ];
+
+    fn localization_key(self) -> &'static str {
+        match self {
+            SettingsTab::Video => "game.settings.video",
+            SettingsTab::Performance => "game.settings.performance",
+            SettingsTab::Controls => "game.settings.controls",
+            SettingsTab::Audio => "game.settings.audio",
+            SettingsTab::Language => "game.settings.language",
+            SettingsTab::GameRules => "game.settings.game_rules",
+        }
+    }
+}
+
+/// 生效时要重建资源的设置：区块生成线程池和阴影贴图。
+/// 在窗口中修改后先记下，点击应用才写入 `GameSettings`，拖动或输入过程中不会反复重建
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ExpensiveSettings {
+    chunk_generation_threads: u32,
+    shadow_resolution: u32,
+}
+
+impl ExpensiveSettings {
+    fn of(settings: &GameSettings) -> Self {
+        Self {
+            chunk_generation_threads: settings.chunk_generation_threads,
+            shadow_resolution: settings.shadow_resolution,
+        }
+    }
+
+    /// 写入设置，线程池由 `thread_pool_management_system` 随设置变化重建
+    fn apply(self, settings: &mut GameSettings, shadow_map: &mut DirectionalLightShadowMap) {
+        settings.chunk_generation_threads = self.chunk_generation_threads;
+        settings.shadow_resolution = self.shadow_resolution;
+        shadow_map.size = self.shadow_resolution as usize;
+    }
+}
+
+/// 设置窗口的界面状态
+#[derive(Resource, Default)]
+pub struct SettingsWindow {
+    tab: SettingsTab,
+    /// 筛选文字，非空时忽略分页，列出所有标签包含它的设置
+    filter: String,
+    /// 筛选框有焦点时游戏不接收按键
+    filter_focused: bool,
+    /// 正在等待新按键的操作（`KeyBindings::actions_mut` 中的本地化键），等待时游戏也不接收按键
+    rebinding: Option<&'static str>,
+    /// 尚未应用的修改，没有修改时为 None；关闭窗口时丢弃
+    pending: Option<ExpensiveSettings>,
+}
+
+fn settings_capture_keys(window: Res<SettingsWindow>) -> bool {
+    window.filter_focused || window.rebinding.is_some()
+}
+
+/// 按筛选文字决定显示哪些设置行（不区分大小写），并记录显示了多少行
+struct SettingsFilter {
+    query: String,
+    shown: usize,
+}
+
+impl SettingsFilter {
+    fn new(filter: &str) -> Self {
+        Self { query: filter.trim().to_lowercase(), shown: 0 }
+    }
+
+    fn is_active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    fn matches(&mut self, label: &str) -> bool {
+        let matched = label.to_lowercase().contains(&self.query);
+        if matched {
+            self.shown += 1;
+        }
+        matched
+    }
+}
+
+fn msaa_from_samples(samples: u32) -> Msaa {
+    match samples {
+        1 => Msaa::Off,
+        2 => Msaa::Sample2,
+        4 => Msaa::Sample4,
+        8 => Msaa::Sample8,
+        _ => Msaa::Sample4,
+    }
+}
+
+fn tonemapping_for(mode: TonemappingMode) -> Tonemapping {
+    match mode {
+        TonemappingMode::None => Tonemapping::None,
+        TonemappingMode::Reinhard => Tonemapping::Reinhard,
+        TonemappingMode::ReinhardLuminance => Tonemapping::ReinhardLuminance,
+        TonemappingMode::AcesFitted => Tonemapping::AcesFitted,
+        TonemappingMode::AgX => Tonemapping::AgX,
+        TonemappingMode::SomewhatBoring => Tonemapping::SomewhatBoringDisplayTransform,
+        TonemappingMode::TonyMcMapface => Tonemapping::TonyMcMapface,
+        TonemappingMode::BlenderFilmic => Tonemapping::BlenderFilmic,
+    }
 }
 
 fn game_settings_ui(
     mut contexts: EguiContexts,
     mut game_settings: ResMut<GameSettings>,
+    mut settings_window: ResMut<SettingsWindow>,
     mut msaa: ResMut<Msaa>,
     mut windows: Query<&mut Window>,
     mut projection_query: Query<&mut Projection>,
@@ -195,236 +639,582 @@ fn game_settings_ui(
     mut light_query: Query<&mut DirectionalLight>,
     mut shadow_map: ResMut<DirectionalLightShadowMap>,
     localization: Res<LocalizationManager>,
+    mut language_events: EventWriter<LanguageChangeEvent>,
+    monitors: Res<Monitors>,
+    // 游戏规则属于当前世界，只在单人游戏中进入世界后可以修改
+    (mut world_state, state, net): (ResMut<WorldState>, Res<State<GameState>>, Option<Res<NetClient>>),
+    chunk_presets: Res<ChunkPresets>,
+    mut key_events: EventReader<KeyboardInput>,
 ) {
+    // 游戏按键在等待新按键时被清空，从键盘事件读取按下的键
+    let pressed_key = key_events.read().filter(|event| event.state.is_pressed()).find_map(|event| event.key_code);
     if !game_settings.show_settings {
+        settings_window.filter_focused = false;
+        settings_window.rebinding = None;
+        settings_window.pending = None;
         return;
     }
 
     let ctx = contexts.ctx_mut();
+    let settings_window = &mut *settings_window;
+    let rules_editable = matches!(state.get(), GameState::InGame | GameState::Paused) && !net.is_some_and(|net| net.is_remote());
+    if !rules_editable && settings_window.tab == SettingsTab::GameRules {
+        settings_window.tab = SettingsTab::Video;
+    }
 
     egui::Window::new(localization.get("game_settings"))
         .collapsible(false)
         .resizable(false)
         .show(ctx, |ui| {
-            // MSAA
-            ui.horizontal(|ui| {
-                ui.label(localization.get("msaa"));
-                let mut msaa_changed = false;
-                egui::ComboBox::from_id_source("msaa")
-                    .selected_text(
-                        if game_settings.msaa_samples == 1 {
-                            localization.get("off")
-                        } else {
-                            "Multi"
+            let filter_edit = egui::TextEdit::singleline(&mut settings_window.filter)
+                .hint_text(localization.get("game.settings.search"));
+            settings_window.filter_focused = ui.add(filter_edit).has_focus();
+            let mut rows = SettingsFilter::new(&settings_window.filter);
+
+            // 筛选时不显示分页，列出所有分页中匹配的设置
+            if !rows.is_active() {
+                ui.horizontal(|ui| {
+                    for tab in SettingsTab::ALL.into_iter().filter(|&tab| rules_editable || tab != SettingsTab::GameRules) {
+                        ui.selectable_value(&mut settings_window.tab, tab, localization.get(tab.localization_key()));
+                    }
+                });
+            }
+            ui.separator();
+
+            let mut pending = settings_window.pending.unwrap_or_else(|| ExpensiveSettings::of(&game_settings));
+            for tab in SettingsTab::ALL {
+                if (!rows.is_active() && tab != settings_window.tab) || (tab == SettingsTab::GameRules && !rules_editable) {
+                    continue;
+                }
+                match tab {
+                    SettingsTab::Video => {
+                        // 画质预设，阴影级联由 rendering::apply_shadow_cascades 随设置更新
+                        if rows.matches(localization.get("graphics.preset")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("graphics.preset"));
+                                let current = GraphicsPreset::matching(&game_settings);
+                                egui::ComboBox::from_id_source("graphics_preset")
+                                    .selected_text(current.map_or(localization.get("graphics.custom"), |preset| localization.get(preset.localization_key())))
+                                    .show_ui(ui, |ui| {
+                                        for preset in GraphicsPreset::ALL {
+                                            if ui.selectable_label(current == Some(preset), localization.get(preset.localization_key())).clicked() {
+                                                preset.apply(&mut game_settings);
+                                                *msaa = msaa_from_samples(game_settings.msaa_samples);
+                                                shadow_map.size = game_settings.shadow_resolution as usize;
+                                                pending = ExpensiveSettings::of(&game_settings);
+                                            }
+                                        }
+                                    });
+                            });
+                        }
+
+                        // MSAA
+                        if rows.matches(localization.get("msaa")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("msaa"));
+                                let mut msaa_changed = false;
+                                egui::ComboBox::from_id_source("msaa")
+                                    .selected_text(
+                                        if game_settings.msaa_samples == 1 {
+                                            localization.get("off")
+                                        } else {
+                                            "Multi"
+                                        }
+                                    )
+                                    .show_ui(ui, |ui| {
+                                        if ui.selectable_value(&mut game_settings.msaa_samples, 1, localization.get("off")).clicked() {
+                                            msaa_changed = true;
+                                        }
+                                        for samples in [2, 4, 8] {
+                                            if ui.selectable_value(&mut game_settings.msaa_samples, samples, format!("{}x", samples)).clicked() {
+                                                msaa_changed = true;
+                                            }
+                                        }
+                                    });
+
+                                if msaa_changed {
+                                    *msaa = msaa_from_samples(game_settings.msaa_samples);
+                                }
+                            });
+                        }
+
+                        // Shadows
+                        if rows.matches(localization.get("enable_shadows"))
+                            && ui.checkbox(&mut game_settings.shadows_enabled, localization.get("enable_shadows")).changed()
+                        {
+                            for mut light in light_query.iter_mut() {
+                                light.shadows_enabled = game_settings.shadows_enabled;
+                            }
                         }
-                    )
-                    .show_ui(ui, |ui| {
-                        if ui.selectable_value(&mut game_settings.msaa_samples, 1, localization.get("off")).clicked() {
-                            msaa_changed = true;
+
+                        // 阴影分辨率改变时重新分配阴影贴图，点击应用后生效
+                        if game_settings.shadows_enabled && rows.matches(localization.get("shadow_resolution")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("shadow_resolution"));
+                                egui::ComboBox::from_id_source("shadow_resolution")
+                                    .selected_text(format!("{}x{}", pending.shadow_resolution, pending.shadow_resolution))
+                                    .show_ui(ui, |ui| {
+                                        for size in [512, 1024, 2048, 4096] {
+                                            ui.selectable_value(&mut pending.shadow_resolution, size, format!("{}x{}", size, size));
+                                        }
+                                    });
+                            });
                         }
-                        if ui.selectable_value(&mut game_settings.msaa_samples, 2, "2x").clicked() {
-                            msaa_changed = true;
+
+                        // 窗口模式：F11 也可以切换无边框全屏
+                        if rows.matches(localization.get("game.display.window_mode")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.display.window_mode"));
+                                egui::ComboBox::from_id_source("window_mode")
+                                    .selected_text(localization.get(game_settings.window_mode.localization_key()))
+                                    .show_ui(ui, |ui| {
+                                        for mode in WindowModeSetting::ALL {
+                                            ui.selectable_value(&mut game_settings.window_mode, mode, localization.get(mode.localization_key()));
+                                        }
+                                    });
+                            });
                         }
-                        if ui.selectable_value(&mut game_settings.msaa_samples, 4, "4x").clicked() {
-                            msaa_changed = true;
+
+                        // 只有一台显示器时不显示
+                        if monitors.list.len() > 1 && rows.matches(localization.get("game.display.monitor")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.display.monitor"));
+                                let selected = game_settings.monitor_name.clone().unwrap_or_else(|| localization.get("game.display.primary_monitor").to_string());
+                                egui::ComboBox::from_id_source("monitor")
+                                    .selected_text(selected)
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut game_settings.monitor_name, None, localization.get("game.display.primary_monitor"));
+                                        for monitor in monitors.list.iter() {
+                                            ui.selectable_value(&mut game_settings.monitor_name, Some(monitor.name.clone()), &monitor.name);
+                                        }
+                                    });
+                            });
                         }
-                        if ui.selectable_value(&mut game_settings.msaa_samples, 8, "8x").clicked() {
-                            msaa_changed = true;
+
+                        // 分辨率列表来自所选显示器支持的显示模式；无边框全屏始终使用显示器的分辨率
+                        if game_settings.window_mode != WindowModeSetting::Borderless && rows.matches(localization.get("game.display.resolution")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.display.resolution"));
+                                let current = (game_settings.resolution_width as u32, game_settings.resolution_height as u32);
+                                egui::ComboBox::from_id_source("resolution")
+                                    .selected_text(format!("{}x{}", current.0, current.1))
+                                    .show_ui(ui, |ui| {
+                                        let resolutions = monitors.get(game_settings.monitor_name.as_deref()).map_or(&[][..], |monitor| &monitor.resolutions[..]);
+                                        for &(width, height) in resolutions {
+                                            if ui.selectable_label(current == (width, height), format!("{}x{}", width, height)).clicked() {
+                                                game_settings.resolution_width = width as f32;
+                                                game_settings.resolution_height = height as f32;
+                                            }
+                                        }
+                                    });
+                            });
                         }
-                    });
-                
-                if msaa_changed {
-                    *msaa = match game_settings.msaa_samples {
-                        1 => Msaa::Off,
-                        2 => Msaa::Sample2,
-                        4 => Msaa::Sample4,
-                        8 => Msaa::Sample8,
-                        _ => Msaa::Sample4,
-                    };
-                }
-            });
 
-            // Shadows
-            if ui.checkbox(&mut game_settings.shadows_enabled, localization.get("enable_shadows")).changed() {
-                for mut light in light_query.iter_mut() {
-                    light.shadows_enabled = game_settings.shadows_enabled;
-                }
-            }
+                        // 界面缩放
+                        if rows.matches(localization.get("game.display.ui_scale")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.display.ui_scale"));
+                                ui.add_enabled(
+                                    !game_settings.ui_scale_auto,
+                                    egui::Slider::new(&mut game_settings.ui_scale, MIN_UI_SCALE..=MAX_UI_SCALE).step_by(0.25).suffix("x"),
+                                );
+                                ui.checkbox(&mut game_settings.ui_scale_auto, localization.get("game.display.ui_scale_auto"));
+                            });
+                        }
+                        if rows.matches(localization.get("game.display.show_compass")) {
+                            ui.checkbox(&mut game_settings.show_compass, localization.get("game.display.show_compass"));
+                        }
 
-            if game_settings.shadows_enabled {
-                ui.horizontal(|ui| {
-                    ui.label(localization.get("shadow_resolution"));
-                    let mut shadow_changed = false;
-                    egui::ComboBox::from_id_source("shadow_resolution")
-                        .selected_text(format!("{}x{}", game_settings.shadow_resolution, game_settings.shadow_resolution))
-                        .show_ui(ui, |ui| {
-                            if ui.selectable_value(&mut game_settings.shadow_resolution, 512, "512x512").clicked() {
-                                shadow_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.shadow_resolution, 1024, "1024x1024").clicked() {
-                                shadow_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.shadow_resolution, 2048, "2048x2048").clicked() {
-                                shadow_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.shadow_resolution, 4096, "4096x4096").clicked() {
-                                shadow_changed = true;
+                        if game_settings.shadows_enabled && rows.matches(localization.get("graphics.shadow_cascades")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("graphics.shadow_cascades"));
+                                ui.add(egui::Slider::new(&mut game_settings.shadow_cascades, 1..=MAX_SHADOW_CASCADES));
+                            });
+                        }
+                        if game_settings.shadows_enabled && rows.matches(localization.get("graphics.shadow_distance")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("graphics.shadow_distance"));
+                                ui.add(egui::Slider::new(&mut game_settings.shadow_distance, 32.0..=512.0).step_by(8.0).suffix(" m"));
+                            });
+                        }
+                        if game_settings.shadows_enabled && game_settings.shadow_cascades > 1 && rows.matches(localization.get("graphics.first_cascade_bound")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("graphics.first_cascade_bound"));
+                                ui.add(egui::Slider::new(&mut game_settings.shadow_first_cascade_bound, 2.0..=64.0).step_by(1.0).suffix(" m"));
+                            });
+                        }
+
+                        // VSync
+                        if rows.matches(localization.get("vsync"))
+                            && ui.checkbox(&mut game_settings.vsync_enabled, localization.get("vsync")).changed()
+                        {
+                            if let Ok(mut window) = windows.get_single_mut() {
+                                window.present_mode = if game_settings.vsync_enabled {
+                                    PresentMode::AutoVsync
+                                } else {
+                                    PresentMode::AutoNoVsync
+                                };
                             }
-                        });
-                    
-                    if shadow_changed {
-                        shadow_map.size = game_settings.shadow_resolution as usize;
-                    }
-                });
-            }
+                        }
 
-            // VSync
-            if ui.checkbox(&mut game_settings.vsync_enabled, localization.get("vsync")).changed() {
-                if let Ok(mut window) = windows.get_single_mut() {
-                    window.present_mode = if game_settings.vsync_enabled {
-                        PresentMode::AutoVsync
-                    } else {
-                        PresentMode::AutoNoVsync
-                    };
-                }
-            }
+                        // FOV
+                        if rows.matches(localization.get("fov")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("fov"));
+                                if ui.add(egui::Slider::new(&mut game_settings.fov, 60.0..=120.0).text("°")).changed() {
+                                    for mut proj in projection_query.iter_mut() {
+                                        if let Projection::Perspective(ref mut persp) = *proj {
+                                            persp.fov = game_settings.fov.to_radians();
+                                        }
+                                    }
+                                }
+                            });
+                        }
 
-            // FOV
-            ui.horizontal(|ui| {
-                ui.label(localization.get("fov"));
-                if ui.add(egui::Slider::new(&mut game_settings.fov, 60.0..=120.0).text("°")).changed() {
-                    for mut proj in projection_query.iter_mut() {
-                        if let Projection::Perspective(ref mut persp) = *proj {
-                            persp.fov = game_settings.fov.to_radians();
+                        // Camera effects
+                        if rows.matches(localization.get("game.view_bobbing")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.view_bobbing"));
+                                ui.add(egui::Slider::new(&mut game_settings.view_bobbing, 0.0..=1.0).step_by(0.05));
+                            });
+                        }
+                        if rows.matches(localization.get("game.sprint_fov_kick")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.sprint_fov_kick"));
+                                ui.add(egui::Slider::new(&mut game_settings.sprint_fov_kick, 0.0..=1.0).step_by(0.05));
+                            });
+                        }
+                        if rows.matches(localization.get("game.landing_dip")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.landing_dip"));
+                                ui.add(egui::Slider::new(&mut game_settings.landing_dip, 0.0..=1.0).step_by(0.05));
+                            });
                         }
-                    }
-                }
-            });
 
-            // Mouse Sensitivity
-            ui.horizontal(|ui| {
-                ui.label(localization.get("mouse_sensitivity"));
-                ui.add(egui::Slider::new(&mut game_settings.mouse_sensitivity, 0.1..=3.0).step_by(0.1));
-            });
+                        // Brightness
+                        if rows.matches(localization.get("game.brightness")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.brightness"));
+                                ui.add(egui::Slider::new(&mut game_settings.brightness, 0.0..=1.0).step_by(0.05));
+                            });
+                        }
 
-            // Gravity
-            ui.horizontal(|ui| {
-                ui.label(localization.get("gravity"));
-                ui.add(egui::Slider::new(&mut game_settings.gravity, 1.0..=20.0).step_by(0.1));
-            });
+                        // Particles
+                        if rows.matches(localization.get("game.particle_quality")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.particle_quality"));
+                                ui.add(egui::Slider::new(&mut game_settings.particle_quality, 0.0..=1.0).step_by(0.05));
+                            });
+                        }
 
-            // Max Loaded Chunks
-            ui.horizontal(|ui| {
-                ui.label(localization.get("max_loaded_chunks"));
-                let mut chunk_text = game_settings.max_loaded_chunks.to_string();
-                let response = ui.text_edit_singleline(&mut chunk_text);
-                if response.changed() {
-                    if let Ok(value) = chunk_text.parse::<u32>() {
-                        if value >= 100 && value <= 10000 {
-                            game_settings.max_loaded_chunks = value;
+                        // Tonemapping
+                        if rows.matches(localization.get("tonemapping_mode")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("tonemapping_mode"));
+                                let mut tone_changed = false;
+                                let modes = [
+                                    (TonemappingMode::None, "none"),
+                                    (TonemappingMode::Reinhard, "reinhard"),
+                                    (TonemappingMode::ReinhardLuminance, "reinhard_luminance"),
+                                    (TonemappingMode::AcesFitted, "aces_fitted"),
+                                    (TonemappingMode::AgX, "agx"),
+                                    (TonemappingMode::SomewhatBoring, "somewhat_boring"),
+                                    (TonemappingMode::TonyMcMapface, "tony_mc_mapface"),
+                                    (TonemappingMode::BlenderFilmic, "blender_filmic"),
+                                ];
+                                let selected = modes.iter()
+                                    .find(|(mode, _)| *mode == game_settings.tonemapping_mode)
+                                    .map_or("", |(_, key)| localization.get(key));
+                                egui::ComboBox::from_id_source("tonemapping_mode")
+                                    .selected_text(selected)
+                                    .show_ui(ui, |ui| {
+                                        for (mode, key) in modes {
+                                            if ui.selectable_value(&mut game_settings.tonemapping_mode, mode, localization.get(key)).clicked() {
+                                                tone_changed = true;
+                                            }
+                                        }
+                                    });
+
+                                if tone_changed {
+                                    let mapped = tonemapping_for(game_settings.tonemapping_mode);
+                                    for mut t in tonemapping_query.iter_mut() {
+                                        *t = mapped;
+                                    }
+                                }
+                            });
                         }
                     }
-                }
-            });
-            ui.colored_label(egui::Color32::GRAY, localization.get("max_chunks_hint"));
+                    SettingsTab::Performance => {
+                        // 区块加载预设，写入下面各项加载设置、渲染距离、生成线程数和帧预算，由区块加载器随设置应用
+                        if rows.matches(localization.get("game.chunk_preset.title")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.chunk_preset.title"));
+                                let current = chunk_presets.matching(&game_settings);
+                                let selected = current.map_or_else(|| localization.get("game.chunk_preset.custom").to_string(), |preset| chunk_preset_label(preset, &localization));
+                                egui::ComboBox::from_id_source("chunk_preset")
+                                    .selected_text(selected)
+                                    .show_ui(ui, |ui| {
+                                        for preset in &chunk_presets.0 {
+                                            let active = current.is_some_and(|current| current.name == preset.name);
+                                            if ui.selectable_label(active, chunk_preset_label(preset, &localization)).clicked() {
+                                                info!("Applying chunk preset {}", preset.name);
+                                                preset.apply(&mut game_settings);
+                                                pending = ExpensiveSettings::of(&game_settings);
+                                            }
+                                        }
+                                    });
+                            });
+                            ui.colored_label(egui::Color32::GRAY, localization.get("game.chunk_preset.hint"));
+                        }
 
-            // Surface Priority Quota
-            ui.horizontal(|ui| {
-                ui.label(localization.get("surface_priority_quota"));
-                let mut quota_text = game_settings.surface_priority_quota.to_string();
-                let response = ui.text_edit_singleline(&mut quota_text);
-                if response.changed() {
-                    if let Ok(value) = quota_text.parse::<u32>() {
-                        if value <= game_settings.max_loaded_chunks {
-                            game_settings.surface_priority_quota = value;
+                        // Max Loaded Chunks
+                        if rows.matches(localization.get("max_loaded_chunks")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("max_loaded_chunks"));
+                                let mut chunk_text = game_settings.max_loaded_chunks.to_string();
+                                let response = ui.text_edit_singleline(&mut chunk_text);
+                                if response.changed() {
+                                    if let Ok(value) = chunk_text.parse::<u32>() {
+                                        if (100..=10000).contains(&value) {
+                                            game_settings.max_loaded_chunks = value;
+                                        }
+                                    }
+                                }
+                            });
+                            ui.colored_label(egui::Color32::GRAY, localization.get("max_chunks_hint"));
+                        }
+
+                        // Surface Priority Quota
+                        if rows.matches(localization.get("surface_priority_quota")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("surface_priority_quota"));
+                                let mut quota_text = game_settings.surface_priority_quota.to_string();
+                                let response = ui.text_edit_singleline(&mut quota_text);
+                                if response.changed() {
+                                    if let Ok(value) = quota_text.parse::<u32>() {
+                                        if value <= game_settings.max_loaded_chunks {
+                                            game_settings.surface_priority_quota = value;
+                                        }
+                                    }
+                                }
+                            });
+                            ui.colored_label(egui::Color32::GRAY, localization.get("surface_quota_hint"));
+                        }
+
+                        // Sphere Loading Radius
+                        if rows.matches(localization.get("sphere_loading_radius")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("sphere_loading_radius"));
+                                ui.add(egui::Slider::new(&mut game_settings.horizontal_radius, 5.0..=25.0).text("chunks"));
+                            });
+                        }
+                        if rows.matches(localization.get("chunk_loading.vertical_radius_up")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("chunk_loading.vertical_radius_up"));
+                                ui.add(egui::Slider::new(&mut game_settings.vertical_radius_up, 1.0..=16.0).step_by(1.0));
+                            });
+                        }
+                        if rows.matches(localization.get("chunk_loading.vertical_radius_down")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("chunk_loading.vertical_radius_down"));
+                                ui.add(egui::Slider::new(&mut game_settings.vertical_radius_down, 1.0..=16.0).step_by(1.0));
+                            });
+                        }
+                        if rows.matches(localization.get("chunk_loading.lod_radius")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("chunk_loading.lod_radius"));
+                                ui.add(egui::Slider::new(&mut game_settings.lod_radius, 0.0..=48.0).step_by(1.0));
+                            });
+                            ui.colored_label(egui::Color32::GRAY, localization.get("chunk_loading.lod_radius_hint"));
+                        }
+
+                        // Unload Hysteresis
+                        if rows.matches(localization.get("chunk_loading.unload_margin")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("chunk_loading.unload_margin"));
+                                ui.add(egui::Slider::new(&mut game_settings.unload_margin, 0.0..=8.0).step_by(0.5));
+                            });
+                        }
+                        if rows.matches(localization.get("chunk_loading.unload_grace_period")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("chunk_loading.unload_grace_period"));
+                                ui.add(egui::Slider::new(&mut game_settings.unload_grace_period, 0.0..=60.0).step_by(1.0));
+                            });
+                        }
+
+                        // 区块生成线程数改变时重建线程池，点击应用后生效
+                        if rows.matches(localization.get("chunk_generation_threads")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("chunk_generation_threads"));
+                                let mut thread_text = pending.chunk_generation_threads.to_string();
+                                let response = ui.text_edit_singleline(&mut thread_text);
+                                if response.changed() {
+                                    if let Ok(value) = thread_text.parse::<u32>() {
+                                        if value > 0 {
+                                            pending.chunk_generation_threads = value;
+                                        }
+                                    }
+                                }
+                            });
+                            ui.colored_label(egui::Color32::YELLOW, localization.get("chunk_threads_warning"));
+                        }
+
+                        if rows.matches(localization.get("game.memory.soft_cap")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.memory.soft_cap"));
+                                ui.add(egui::Slider::new(&mut game_settings.memory_soft_cap_mb, 512..=16384).logarithmic(true).suffix(" MB"));
+                            });
+                            ui.colored_label(egui::Color32::GRAY, localization.get("game.memory.soft_cap_hint"));
+                        }
+
+                        // Chunk Frame Budget
+                        if rows.matches(localization.get("chunk_loading.frame_budget")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("chunk_loading.frame_budget"));
+                                ui.add(egui::Slider::new(&mut game_settings.chunk_budget_ms, 1.0..=16.0).step_by(0.5));
+                            });
+                        }
+
+                        // Autosave
+                        if rows.matches(localization.get("game.autosave.interval")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.autosave.interval"));
+                                ui.add(egui::Slider::new(&mut game_settings.autosave_minutes, 1.0..=30.0).step_by(1.0).suffix(localization.get("game.autosave.minutes")));
+                            });
+                        }
+
+                        // 日志等级（下次启动时生效）
+                        if rows.matches(localization.get("game.log_level")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.log_level"));
+                                egui::ComboBox::from_id_source("log_level")
+                                    .selected_text(game_settings.log_level.clone())
+                                    .show_ui(ui, |ui| {
+                                        for level in ["error", "warn", "info", "debug", "trace"] {
+                                            ui.selectable_value(&mut game_settings.log_level, level.to_string(), level);
+                                        }
+                                    });
+                            });
                         }
                     }
-                }
-            });
-            ui.colored_label(egui::Color32::GRAY, localization.get("surface_quota_hint"));
+                    SettingsTab::Controls => {
+                        // Mouse Sensitivity
+                        if rows.matches(localization.get("mouse_sensitivity")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("mouse_sensitivity"));
+                                ui.add(egui::Slider::new(&mut game_settings.mouse_sensitivity, 0.1..=3.0).step_by(0.1));
+                            });
+                        }
+                        if rows.matches(localization.get("game.mouse_smoothing")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.mouse_smoothing"));
+                                ui.add(egui::Slider::new(&mut game_settings.mouse_smoothing, 0.0..=1.0).step_by(0.05));
+                            });
+                        }
+                        if rows.matches(localization.get("game.invert_mouse_y")) {
+                            ui.checkbox(&mut game_settings.invert_mouse_y, localization.get("game.invert_mouse_y"));
+                        }
+                        if rows.matches(localization.get("game.pause_on_focus_loss")) {
+                            ui.checkbox(&mut game_settings.pause_on_focus_loss, localization.get("game.pause_on_focus_loss"));
+                        }
 
-            // Sphere Loading Radius
-            ui.horizontal(|ui| {
-                ui.label(localization.get("sphere_loading_radius"));
-                ui.add(egui::Slider::new(&mut game_settings.sphere_loading_radius, 5.0..=25.0).text("chunks"));
-            });
+                        // Zoom
+                        if rows.matches(localization.get("game.zoom_fov")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.zoom_fov"));
+                                ui.add(egui::Slider::new(&mut game_settings.zoom_fov, 10.0..=50.0).text("°"));
+                            });
+                        }
+                        if rows.matches(localization.get("game.zoom_toggle")) {
+                            ui.checkbox(&mut game_settings.zoom_toggle, localization.get("game.zoom_toggle"));
+                        }
 
-            // Chunk Generation Threads
-            ui.horizontal(|ui| {
-                ui.label(localization.get("chunk_generation_threads"));
-                let mut thread_text = game_settings.chunk_generation_threads.to_string();
-                let response = ui.text_edit_singleline(&mut thread_text);
-                if response.changed() {
-                    if let Ok(value) = thread_text.parse::<u32>() {
-                        if value > 0 {
-                            game_settings.chunk_generation_threads = value;
+                        // Gravity
+                        if rows.matches(localization.get("gravity")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("gravity"));
+                                ui.add(egui::Slider::new(&mut game_settings.gravity, 1.0..=20.0).step_by(0.1));
+                            });
+                        }
+
+                        // 按键和物理参数都按分组标题筛选
+                        if rows.matches(localization.get("game.key_bindings.title")) {
+                            ui.collapsing(localization.get("game.key_bindings.title"), |ui| {
+                                key_bindings_ui(ui, &mut game_settings.keys, &mut settings_window.rebinding, pressed_key, &localization);
+                            });
+                        }
+
+                        // 行走、冲刺和跳跃的参数
+                        if rows.matches(localization.get("game.physics.title")) {
+                            ui.collapsing(localization.get("game.physics.title"), |ui| {
+                                movement_tuning_ui(ui, &mut game_settings.movement, &localization);
+                            });
                         }
                     }
+                    SettingsTab::Audio => {
+                        if rows.matches(localization.get("audio.master_volume")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("audio.master_volume"));
+                                ui.add(egui::Slider::new(&mut game_settings.master_volume, 0.0..=1.0).step_by(0.05));
+                            });
+                        }
+                        if rows.matches(localization.get("audio.effects_volume")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("audio.effects_volume"));
+                                ui.add(egui::Slider::new(&mut game_settings.effects_volume, 0.0..=1.0).step_by(0.05));
+                            });
+                        }
+                        if rows.matches(localization.get("audio.ambient_volume")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("audio.ambient_volume"));
+                                ui.add(egui::Slider::new(&mut game_settings.ambient_volume, 0.0..=1.0).step_by(0.05));
+                            });
+                        }
+                    }
+                    SettingsTab::Language => {
+                        // 切换后立即生效，并记入设置供下次启动使用
+                        if rows.matches(localization.get("game.settings.language")) {
+                            ui.horizontal(|ui| {
+                                ui.label(localization.get("game.settings.language"));
+                                let languages = localization.get_available_languages();
+                                let current = languages.iter()
+                                    .find(|language| language.code == localization.get_current_language())
+                                    .map_or(localization.get_current_language(), |language| language.name.as_str());
+                                egui::ComboBox::from_id_source("language")
+                                    .selected_text(current)
+                                    .show_ui(ui, |ui| {
+                                        for language in languages {
+                                            if ui.selectable_value(&mut game_settings.language, language.code.clone(), &language.name).clicked() {
+                                                language_events.send(LanguageChangeEvent { new_language: language.code.clone() });
+                                            }
+                                        }
+                                    });
+                            });
+                        }
+                    }
+                    SettingsTab::GameRules => game_rules_ui(ui, &mut rows, &mut world_state, &localization),
                 }
-            });
-            ui.colored_label(egui::Color32::YELLOW, localization.get("chunk_threads_warning"));
+            }
+            if rows.is_active() && rows.shown == 0 {
+                ui.colored_label(egui::Color32::GRAY, localization.get("game.settings.no_matches"));
+            }
+            settings_window.pending = (pending != ExpensiveSettings::of(&game_settings)).then_some(pending);
 
-            // Tonemapping
-            ui.collapsing(localization.get("tonemapping"), |ui| {
+            if settings_window.pending.is_some() {
+                ui.separator();
                 ui.horizontal(|ui| {
-                    ui.label(localization.get("tonemapping_mode"));
-                    let mut tone_changed = false;
-                    egui::ComboBox::from_id_source("tonemapping_mode")
-                        .selected_text(match game_settings.tonemapping_mode {
-                            TonemappingMode::None => localization.get("none"),
-                            TonemappingMode::Reinhard => localization.get("reinhard"),
-                            TonemappingMode::ReinhardLuminance => localization.get("reinhard_luminance"),
-                            TonemappingMode::AcesFitted => localization.get("aces_fitted"),
-                            TonemappingMode::AgX => localization.get("agx"),
-                            TonemappingMode::SomewhatBoring => localization.get("somewhat_boring"),
-                            TonemappingMode::TonyMcMapface => localization.get("tony_mc_mapface"),
-                            TonemappingMode::BlenderFilmic => localization.get("blender_filmic"),
-                        })
-                        .show_ui(ui, |ui| {
-                            if ui.selectable_value(&mut game_settings.tonemapping_mode, TonemappingMode::None, localization.get("none")).clicked() {
-                                tone_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.tonemapping_mode, TonemappingMode::Reinhard, localization.get("reinhard")).clicked() {
-                                tone_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.tonemapping_mode, TonemappingMode::ReinhardLuminance, localization.get("reinhard_luminance")).clicked() {
-                                tone_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.tonemapping_mode, TonemappingMode::AcesFitted, localization.get("aces_fitted")).clicked() {
-                                tone_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.tonemapping_mode, TonemappingMode::AgX, localization.get("agx")).clicked() {
-                                tone_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.tonemapping_mode, TonemappingMode::SomewhatBoring, localization.get("somewhat_boring")).clicked() {
-                                tone_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.tonemapping_mode, TonemappingMode::TonyMcMapface, localization.get("tony_mc_mapface")).clicked() {
-                                tone_changed = true;
-                            }
-                            if ui.selectable_value(&mut game_settings.tonemapping_mode, TonemappingMode::BlenderFilmic, localization.get("blender_filmic")).clicked() {
-                                tone_changed = true;
-                            }
-                        });
-                    
-                    if tone_changed {
-                        let mapped = match game_settings.tonemapping_mode {
-                            TonemappingMode::None => Tonemapping::None,
-                            TonemappingMode::Reinhard => Tonemapping::Reinhard,
-                            TonemappingMode::ReinhardLuminance => Tonemapping::ReinhardLuminance,
-                            TonemappingMode::AcesFitted => Tonemapping::AcesFitted,
-                            TonemappingMode::AgX => Tonemapping::AgX,
-                            TonemappingMode::SomewhatBoring => Tonemapping::SomewhatBoringDisplayTransform,
-                            TonemappingMode::TonyMcMapface => Tonemapping::TonyMcMapface,
-                            TonemappingMode::BlenderFilmic => Tonemapping::BlenderFilmic,
-                        };
-                        for mut t in tonemapping_query.iter_mut() {
-                            *t = mapped;
-                        }
+                    ui.colored_label(egui::Color32::YELLOW, localization.get("game.settings.unapplied"));
+                    if ui.button(localization.get("game.settings.apply")).clicked() {
+                        pending.apply(&mut game_settings, &mut shadow_map);
+                        settings_window.pending = None;
+                    }
+                    if ui.button(localization.get("game.settings.revert")).clicked() {
+                        settings_window.pending = None;
                     }
                 });
-            });
+            }
 
+            ui.separator();
             ui.horizontal(|ui| {
                 if ui.button(localization.get("close")).clicked() {
                     game_settings.show_settings = false;
@@ -432,8 +1222,11 @@ fn game_settings_ui(
 
                 if ui.button(localization.get("restore_defaults")).clicked() {
                     let old_show = game_settings.show_settings;
+                    let language = std::mem::take(&mut game_settings.language);
                     *game_settings = GameSettings::default();
                     game_settings.show_settings = old_show;
+                    game_settings.language = language;
+                    settings_window.pending = None;
                     
                     // Apply default settings
                     *msaa = Msaa::Sample4;
@@ -444,9 +1237,9 @@ fn game_settings_ui(
                     for mut t in tonemapping_query.iter_mut() {
                         *t = Tonemapping::Reinhard;
                     }
+                    // 窗口模式、显示器和分辨率由 display::sync_window_settings 应用
                     if let Ok(mut window) = windows.get_single_mut() {
                         window.present_mode = PresentMode::AutoVsync;
-                        window.resolution = WindowResolution::new(1920.0, 1080.0);
                     }
                     for mut proj in projection_query.iter_mut() {
                         if let Projection::Perspective(ref mut persp) = *proj {
@@ -457,6 +1250,136 @@ fn game_settings_ui(
                 }
             });
         });
+    // 按键设置没有显示（例如切换了分页）时按下的键取消等待，不会一直挡住游戏按键
+    if pressed_key.is_some() {
+        settings_window.rebinding = None;
+    }
+}
+
+/// 区块预设的显示名称，本地化中没有的预设（例如玩家在预设文件中添加的）显示文件中的名称
+fn chunk_preset_label(preset: &ChunkPreset, localization: &LocalizationManager) -> String {
+    let key = preset.localization_key();
+    match localization.get(&key) {
+        label if label == key => preset.name.clone(),
+        label => label.to_string(),
+    }
+}
+
+/// 设置中“物理（高级）”分组的滑块，修改立即作用于玩家移动
+/// 按键设置：点击一个操作后按下新的按键，按 Esc 取消
+fn key_bindings_ui(ui: &mut egui::Ui, keys: &mut KeyBindings, rebinding: &mut Option<&'static str>, pressed: Option<KeyCode>, localization: &LocalizationManager) {
+    egui::Grid::new("key_bindings").num_columns(2).show(ui, |ui| {
+        for (name, key) in keys.actions_mut() {
+            ui.label(localization.get(name));
+            if *rebinding == Some(name) {
+                if let Some(pressed) = pressed {
+                    if pressed != KeyCode::Escape {
+                        info!("Bound {} to {:?}", name, pressed);
+                        *key = pressed;
+                    }
+                    *rebinding = None;
+                }
+            }
+            let text = if *rebinding == Some(name) { localization.get("game.key_bindings.press_key").to_string() } else { format!("{:?}", key) };
+            if ui.button(text).clicked() {
+                *rebinding = Some(name);
+            }
+            ui.end_row();
+        }
+    });
+    if ui.button(localization.get("game.key_bindings.reset")).clicked() {
+        *keys = KeyBindings::default();
+    }
+}
+
+fn movement_tuning_ui(ui: &mut egui::Ui, tuning: &mut MovementTuning, localization: &LocalizationManager) {
+    let rows: [(&str, &mut f32, std::ops::RangeInclusive<f32>); 11] = [
+        ("game.physics.walk_speed", &mut tuning.walk_speed, 1.0..=15.0),
+        ("game.physics.sprint_speed", &mut tuning.sprint_speed, 1.0..=20.0),
+        ("game.physics.sprint_max_speed", &mut tuning.sprint_max_speed, 1.0..=25.0),
+        ("game.physics.ground_acceleration", &mut tuning.ground_acceleration, 1.0..=60.0),
+        ("game.physics.ground_friction", &mut tuning.ground_friction, 0.0..=40.0),
+        ("game.physics.air_acceleration", &mut tuning.air_acceleration, 0.0..=30.0),
+        ("game.physics.air_acceleration_sprint", &mut tuning.air_acceleration_sprint, 0.0..=30.0),
+        ("game.physics.air_friction", &mut tuning.air_friction, 0.0..=5.0),
+        ("game.physics.air_friction_sprint", &mut tuning.air_friction_sprint, 0.0..=5.0),
+        ("game.physics.jump_velocity", &mut tuning.jump_velocity, 1.0..=15.0),
+        ("game.physics.sprint_jump_impulse", &mut tuning.sprint_jump_impulse, 0.0..=6.0),
+    ];
+    egui::Grid::new("movement_tuning").num_columns(2).show(ui, |ui| {
+        for (key, value, range) in rows {
+            ui.label(localization.get(key));
+            ui.add(egui::Slider::new(value, range).step_by(0.1));
+            ui.end_row();
+        }
+    });
+    if ui.button(localization.get("game.physics.reset")).clicked() {
+        *tuning = MovementTuning::default();
+    }
+}
+
+/// 游戏规则分页：每个已知规则一行，布尔规则为复选框，数字规则为滑块。只在值变化时写入世界状态
+fn game_rules_ui(ui: &mut egui::Ui, rows: &mut SettingsFilter, world_state: &mut ResMut<WorldState>, localization: &LocalizationManager) {
+    for (name, default) in default_game_rules() {
+        let key = format!("game.gamerule.{}", name);
+        let label = localization.get(&key);
+        if !rows.matches(label) {
+            continue;
+        }
+        let current = world_state.game_rules.get(name).cloned().unwrap_or(default);
+        let changed = match current {
+            GameRuleValue::Bool(mut value) => ui.checkbox(&mut value, label).changed().then_some(GameRuleValue::Bool(value)),
+            GameRuleValue::Int(mut value) => ui.horizontal(|ui| {
+                ui.label(label);
+                ui.add(egui::Slider::new(&mut value, 0..=100)).changed().then_some(GameRuleValue::Int(value))
+            }).inner,
+            GameRuleValue::Float(mut value) => ui.horizontal(|ui| {
+                ui.label(label);
+                ui.add(egui::Slider::new(&mut value, 0.0..=10.0).step_by(0.1)).changed().then_some(GameRuleValue::Float(value))
+            }).inner,
+            GameRuleValue::Text(_) => None,
+        };
+        if let Some(value) = changed {
+            info!("Game rule {} set to {}", name, value);
+            world_state.game_rules.insert(name.to_string(), value);
+        }
+    }
+}
+
+/// 按窗口的物理高度和系统缩放系数选择界面缩放，让界面在高分辨率屏幕上与 1080p 时一样大
+fn auto_ui_scale(physical_height: u32, scale_factor: f64) -> f32 {
+    let scale = physical_height as f64 / AUTO_UI_SCALE_HEIGHT / scale_factor.max(0.1);
+    ((scale * 4.0).round() / 4.0).clamp(1.0, MAX_UI_SCALE as f64) as f32
+}
+
+/// 把界面缩放应用到 bevy_ui（`UiScale` 同时缩放像素尺寸和文字）和 egui，
+/// 只在数值变化时写入，修改立即生效，不需要重建 HUD
+fn apply_ui_scale(
+    game_settings: Res<GameSettings>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+    mut egui_settings: ResMut<EguiSettings>,
+) {
+    let scale = if game_settings.ui_scale_auto {
+        let Ok(window) = primary_window.get_single() else { return };
+        auto_ui_scale(window.physical_height(), window.scale_factor())
+    } else {
+        game_settings.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+    } as f64;
+    if ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+    if egui_settings.scale_factor != scale {
+        egui_settings.scale_factor = scale;
+    }
+}
+
+/// 设置窗口关闭时保存设置
+fn save_settings_on_close(game_settings: Res<GameSettings>, mut was_open: Local<bool>) {
+    if *was_open && !game_settings.show_settings {
+        game_settings.save();
+    }
+    *was_open = game_settings.show_settings;
 }
 
 fn apply_initial_settings(
@@ -468,15 +1391,11 @@ fn apply_initial_settings(
     mut shadow_map: ResMut<DirectionalLightShadowMap>,
     game_settings: Res<GameSettings>,
     thread_pool: Option<ResMut<crate::world::chunk_loader::ChunkGenerationThreadPool>>,
+    mut applied_window: ResMut<AppliedWindowSettings>,
+    monitors: Res<Monitors>,
 ) {
     // Apply MSAA
-    *msaa = match game_settings.msaa_samples {
-        1 => Msaa::Off,
-        2 => Msaa::Sample2,
-        4 => Msaa::Sample4,
-        8 => Msaa::Sample8,
-        _ => Msaa::Sample4,
-    };
+    *msaa = msaa_from_samples(game_settings.msaa_samples);
 
     // Apply shadows
     for mut light in light_query.iter_mut() {
@@ -484,17 +1403,14 @@ fn apply_initial_settings(
     }
     shadow_map.size = game_settings.shadow_resolution as usize;
 
-    // Apply VSync and resolution
+    // Apply VSync, window mode, monitor and resolution
     if let Ok(mut window) = windows.get_single_mut() {
         window.present_mode = if game_settings.vsync_enabled {
             PresentMode::AutoVsync
         } else {
             PresentMode::AutoNoVsync
         };
-        window.resolution = WindowResolution::new(
-            game_settings.resolution_width,
-            game_settings.resolution_height,
-        );
+        apply_window_settings(&mut window, &game_settings, &monitors, &mut applied_window);
     }
 
     // Apply FOV
@@ -505,16 +1421,7 @@ fn apply_initial_settings(
     }
 
     // Apply tonemapping
-    let mapped = match game_settings.tonemapping_mode {
-        TonemappingMode::None => Tonemapping::None,
-        TonemappingMode::Reinhard => Tonemapping::Reinhard,
-        TonemappingMode::ReinhardLuminance => Tonemapping::ReinhardLuminance,
-        TonemappingMode::AcesFitted => Tonemapping::AcesFitted,
-        TonemappingMode::AgX => Tonemapping::AgX,
-        TonemappingMode::SomewhatBoring => Tonemapping::SomewhatBoringDisplayTransform,
-        TonemappingMode::TonyMcMapface => Tonemapping::TonyMcMapface,
-        TonemappingMode::BlenderFilmic => Tonemapping::BlenderFilmic,
-    };
+    let mapped = tonemapping_for(game_settings.tonemapping_mode);
     for mut t in tonemapping_query.iter_mut() {
         *t = mapped;
     }
@@ -523,4 +1430,40 @@ fn apply_initial_settings(
     if let Some(mut pool) = thread_pool {
         pool.update_thread_count(game_settings.chunk_generation_threads);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_filter_matches_labels_ignoring_case() {
+        let mut rows = SettingsFilter::new("  VOLUME ");
+        assert!(rows.is_active());
+        assert!(rows.matches("Master Volume"));
+        assert!(!rows.matches("Mouse Sensitivity"));
+        assert!(rows.matches("Effects volume"));
+        assert_eq!(rows.shown, 2);
+        assert!(!SettingsFilter::new(" ").is_active());
+    }
+
+    #[test]
+    fn default_settings_match_the_medium_preset() {
+        let mut settings = GameSettings::default();
+        assert_eq!(GraphicsPreset::matching(&settings), Some(GraphicsPreset::Medium));
+        GraphicsPreset::Ultra.apply(&mut settings);
+        assert_eq!(GraphicsPreset::matching(&settings), Some(GraphicsPreset::Ultra));
+        settings.shadow_distance = 100.0;
+        assert_eq!(GraphicsPreset::matching(&settings), None);
+    }
+
+    #[test]
+    fn auto_ui_scale_keeps_the_ui_as_large_as_at_1080p() {
+        assert_eq!(auto_ui_scale(720, 1.0), 1.0);
+        assert_eq!(auto_ui_scale(1440, 1.0), 1.25);
+        assert_eq!(auto_ui_scale(2160, 1.0), 2.0);
+        // 系统已经按 2 倍缩放时不再放大
+        assert_eq!(auto_ui_scale(2160, 2.0), 1.0);
+        assert_eq!(auto_ui_scale(8640, 1.0), MAX_UI_SCALE);
+    }
+}
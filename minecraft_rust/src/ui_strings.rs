@@ -19,6 +19,9 @@ pub struct UiStrings {
 pub struct PauseMenuStrings {
     pub title: String,
     pub continue_game: String,
+    pub settings: String,
+    pub statistics: String,
+    pub photo_mode: String,
     pub quit: String,
     pub hint: String,
 }
@@ -94,7 +97,7 @@ impl UiStringManager {
     }
     
     fn load_strings() -> Result<UiStrings, Box<dyn std::error::Error>> {
-        let content = std::fs::read_to_string("ui_strings.json")?;
+        let content = std::fs::read_to_string(crate::paths::data_path("ui_strings.json"))?;
         let strings: UiStrings = serde_json::from_str(&content)?;
         Ok(strings)
     }
@@ -104,6 +107,9 @@ impl UiStringManager {
             pause_menu: PauseMenuStrings {
                 title: "Game Paused".to_string(),
                 continue_game: "Continue Game".to_string(),
+                settings: "Options".to_string(),
+                statistics: "Statistics".to_string(),
+                photo_mode: "Photo Mode".to_string(),
                 quit: "Quit Game".to_string(),
                 hint: "Press ESC to continue".to_string(),
             },
@@ -114,6 +120,19 @@ impl UiStringManager {
                     items.insert("dirt".to_string(), "Dirt".to_string());
                     items.insert("stone".to_string(), "Stone".to_string());
                     items.insert("bedrock".to_string(), "Bedrock".to_string());
+                    items.insert("water".to_string(), "Water".to_string());
+                    items.insert("lava".to_string(), "Lava".to_string());
+                    items.insert("obsidian".to_string(), "Obsidian".to_string());
+                    items.insert("sand".to_string(), "Sand".to_string());
+                    items.insert("gravel".to_string(), "Gravel".to_string());
+                    items.insert("torch".to_string(), "Torch".to_string());
+                    items.insert("crafting_table".to_string(), "Crafting Table".to_string());
+                    items.insert("chest".to_string(), "Chest".to_string());
+                    items.insert("tnt".to_string(), "TNT".to_string());
+                    items.insert("bed".to_string(), "Bed".to_string());
+                    items.insert("log".to_string(), "Oak Log".to_string());
+                    items.insert("leaves".to_string(), "Oak Leaves".to_string());
+                    items.insert("sapling".to_string(), "Oak Sapling".to_string());
                     items.insert("air".to_string(), "Air".to_string());
                     items.insert("wooden_pickaxe".to_string(), "Wooden Pickaxe".to_string());
                     items.insert("stone_pickaxe".to_string(), "Stone Pickaxe".to_string());
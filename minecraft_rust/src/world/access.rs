@@ -0,0 +1,375 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+use crate::world::block_entity::BlockEntity;
+use crate::world::block_update::BlockUpdateQueue;
+use crate::world::chunk::{BlockId, Chunk};
+use crate::world::events::ChunksModified;
+use crate::world::storage::{ChunkLifecycle, ChunkStorage};
+
+/// 世界坐标所在区块的坐标
+pub fn world_pos_to_chunk_coord(world_pos: IVec3) -> IVec3 {
+    world_pos.div_euclid(IVec3::splat(32))
+}
+
+/// 世界坐标在 `chunk_coord` 区块中的坐标，世界坐标不在这个区块中时会超出 0..32
+pub fn world_pos_to_local_pos(world_pos: IVec3, chunk_coord: IVec3) -> IVec3 {
+    world_pos - chunk_coord * 32
+}
+
+/// 世界坐标 -> (区块坐标, 区块内坐标)
+pub fn split_world_pos(world_pos: IVec3) -> (IVec3, UVec3) {
+    let coord = world_pos_to_chunk_coord(world_pos);
+    (coord, world_pos_to_local_pos(world_pos, coord).as_uvec3())
+}
+
+/// 一个方块修改
+#[derive(Clone, Copy)]
+struct BlockEdit {
+    pos: IVec3,
+    block: BlockId,
+    metadata: u8,
+}
+
+/// 一批方块修改，按所在区块分组，由 `WorldAccess::apply` 一次应用。
+/// 区块按第一次修改的顺序处理，区块内按添加的顺序写入，同一位置修改多次时最后一次生效
+#[derive(Default)]
+pub struct BlockEditBatch {
+    chunks: Vec<(IVec3, Vec<BlockEdit>)>,
+    /// 区块坐标在 `chunks` 中的位置
+    index: HashMap<IVec3, usize>,
+    len: usize,
+}
+
+impl BlockEditBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, pos: IVec3, block: BlockId) {
+        self.set_with_metadata(pos, block, 0);
+    }
+
+    /// 同 `set`，同时写入方块的附加数据（流体等级）
+    pub fn set_with_metadata(&mut self, pos: IVec3, block: BlockId, metadata: u8) {
+        let coord = world_pos_to_chunk_coord(pos);
+        let slot = *self.index.entry(coord).or_insert_with(|| {
+            self.chunks.push((coord, Vec::new()));
+            self.chunks.len() - 1
+        });
+        self.chunks[slot].1.push(BlockEdit { pos, block, metadata });
+        self.len += 1;
+    }
+
+    /// 把 `min` 到 `max`（包含两端）的长方体填成同一种方块
+    #[cfg(test)]
+    pub fn fill(&mut self, min: IVec3, max: IVec3, block: BlockId) {
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                for x in min.x..=max.x {
+                    self.set(IVec3::new(x, y, z), block);
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// 只读的世界视图：只需要读取方块的代码（碰撞、射线检测、网格构建）通过它访问跨区块的方块，
+/// 由 `WorldView::new` 从区块存储和区块查询创建，或者由 `WorldAccess::view` 得到
+pub struct WorldView<'w, 's, 'c> {
+    pub chunk_storage: &'w ChunkStorage,
+    chunks: Query<'w, 's, &'c Chunk>,
+}
+
+impl<'w, 's, 'c> WorldView<'w, 's, 'c> {
+    pub fn new(chunk_storage: &'w ChunkStorage, chunks: &'w Query<'_, 's, &'c Chunk>) -> Self {
+        Self { chunk_storage, chunks: chunks.to_readonly() }
+    }
+}
+
+impl WorldView<'_, '_, '_> {
+    /// 坐标处的区块，不论处于哪个生命周期状态
+    pub fn chunk(&self, coord: IVec3) -> Option<&Chunk> {
+        self.chunks.get(self.chunk_storage.get(&coord)?).ok()
+    }
+
+    /// 坐标处 Active 的区块，网格构建和碰撞只使用这些区块
+    pub fn active_chunk(&self, coord: IVec3) -> Option<&Chunk> {
+        self.chunks.get(self.chunk_storage.get_active(&coord)?).ok()
+    }
+
+    /// 世界坐标处的方块；所在区块未加载时返回 None
+    pub fn block(&self, pos: IVec3) -> Option<BlockId> {
+        let (coord, local) = split_world_pos(pos);
+        Some(self.chunk(coord)?.get_block(local.x, local.y, local.z))
+    }
+
+    /// 同 `block`，未加载的区块按空气处理
+    pub fn get_block(&self, pos: IVec3) -> BlockId {
+        self.block(pos).unwrap_or(BlockId::Air)
+    }
+
+    /// 世界坐标处的方块和附加数据；所在区块未加载时返回 None
+    pub fn get_with_metadata(&self, pos: IVec3) -> Option<(BlockId, u8)> {
+        let (coord, local) = split_world_pos(pos);
+        let chunk = self.chunk(coord)?;
+        Some((chunk.get_block(local.x, local.y, local.z), chunk.get_metadata(local.x, local.y, local.z)))
+    }
+
+    /// 世界坐标处的天空光照（0-15）；所在区块未加载时返回 None
+    pub fn sky_light(&self, pos: IVec3) -> Option<u8> {
+        let (coord, local) = split_world_pos(pos);
+        Some(self.chunk(coord)?.get_sky_light(local.x, local.y, local.z))
+    }
+
+    /// 世界坐标处是否是实心方块，未加载的区块不算
+    pub fn is_solid(&self, pos: IVec3) -> bool {
+        self.block(pos).is_some_and(BlockId::is_solid)
+    }
+}
+
+/// 读写世界方块的系统参数，封装 `ChunkStorage` 和区块查询。所有方块修改（玩家编辑、联机同步、
+/// 流体、下落方块、爆炸）都经过 `apply`：每个区块只重新统计一次实心方块，
+/// 修改的区块和边界上的相邻区块各标记一次需要重建网格，并发送一个 `ChunksModified`；
+/// 修改的位置和相邻位置加入 `BlockUpdateQueue`
+#[derive(SystemParam)]
+pub struct WorldAccess<'w, 's> {
+    pub chunk_storage: Res<'w, ChunkStorage>,
+    pub chunks: Query<'w, 's, &'static mut Chunk>,
+    modified: EventWriter<'w, ChunksModified>,
+    updates: Option<ResMut<'w, BlockUpdateQueue>>,
+}
+
+impl<'s> WorldAccess<'_, 's> {
+    /// 只读视图，在同一个系统中读取方块时使用
+    pub fn view(&self) -> WorldView<'_, 's, 'static> {
+        WorldView { chunk_storage: &self.chunk_storage, chunks: self.chunks.to_readonly() }
+    }
+
+    /// 世界坐标处的方块；所在区块未加载时返回 None
+    pub fn block(&self, pos: IVec3) -> Option<BlockId> {
+        self.view().block(pos)
+    }
+
+    pub fn get_with_metadata(&self, pos: IVec3) -> Option<(BlockId, u8)> {
+        self.view().get_with_metadata(pos)
+    }
+
+    pub fn is_solid(&self, pos: IVec3) -> bool {
+        self.view().is_solid(pos)
+    }
+
+    /// 世界坐标处的方块实体，所在区块未加载或没有方块实体时返回 None
+    pub fn get_block_entity(&self, pos: IVec3) -> Option<&BlockEntity> {
+        let (coord, local) = split_world_pos(pos);
+        self.chunks.get(self.chunk_storage.get(&coord)?).ok()?.block_entity(local)
+    }
+
+    /// 设置或移除（`None`）世界坐标处的方块实体，区块未加载时返回 false
+    pub fn set_block_entity(&mut self, pos: IVec3, entity: Option<BlockEntity>) -> bool {
+        let (coord, local) = split_world_pos(pos);
+        let Some(mut chunk) = self.chunk_storage.get(&coord).and_then(|e| self.chunks.get_mut(e).ok()) else { return false };
+        chunk.set_block_entity(local, entity);
+        true
+    }
+
+    /// 应用一批修改，返回实际修改的 (世界坐标, 原来的方块)。未加载和正在卸载的区块中的修改被跳过，
+    /// 正在卸载的区块已经开始保存，之后的修改不会写入存档
+    pub fn apply(&mut self, batch: BlockEditBatch) -> Vec<(IVec3, BlockId)> {
+        if batch.is_empty() {
+            return Vec::new();
+        }
+        let mut previous = Vec::with_capacity(batch.len());
+        let mut touched = Vec::new();
+        let mut neighbors = HashSet::new();
+        for (coord, edits) in batch.chunks {
+            if self.chunk_storage.state(&coord) == Some(ChunkLifecycle::Unloading) {
+                continue;
+            }
+            let Some(mut chunk) = self.chunk_storage.get(&coord).and_then(|entity| self.chunks.get_mut(entity).ok()) else { continue };
+            let mut solid_changed = false;
+            for edit in edits {
+                let local = world_pos_to_local_pos(edit.pos, coord);
+                let (x, y, z) = (local.x as u32, local.y as u32, local.z as u32);
+                let old = chunk.get_block(x, y, z);
+                chunk.set_block_with_metadata(x, y, z, edit.block, edit.metadata);
+                // 流体之间的变化不影响碰撞用的实心方块列表
+                solid_changed |= old.is_solid() || edit.block.is_solid();
+                previous.push((edit.pos, old));
+                if let Some(updates) = self.updates.as_mut() {
+                    updates.push_with_neighbors(edit.pos);
+                }
+                for axis in [IVec3::X, IVec3::Y, IVec3::Z] {
+                    let along = local.dot(axis);
+                    if along == 0 {
+                        neighbors.insert(coord - axis);
+                    } else if along == 31 {
+                        neighbors.insert(coord + axis);
+                    }
+                }
+            }
+            chunk.modified = true;
+            if solid_changed {
+                chunk.compute_solid_blocks();
+            }
+            chunk.dirty = true;
+            touched.push(coord);
+        }
+
+        for coord in neighbors {
+            if touched.contains(&coord) {
+                continue;
+            }
+            if let Some(mut chunk) = self.chunk_storage.get(&coord).and_then(|entity| self.chunks.get_mut(entity).ok()) {
+                chunk.dirty = true;
+            }
+        }
+        if !touched.is_empty() {
+            self.modified.send(ChunksModified { coords: touched });
+        }
+        previous
+    }
+
+    /// 修改一个方块，返回原来的方块，区块未加载时返回 None
+    pub fn set_block(&mut self, pos: IVec3, block: BlockId) -> Option<BlockId> {
+        self.set_block_with_metadata(pos, block, 0)
+    }
+
+    /// 同 `set_block`，同时写入方块的附加数据（流体等级）
+    pub fn set_block_with_metadata(&mut self, pos: IVec3, block: BlockId, metadata: u8) -> Option<BlockId> {
+        let mut batch = BlockEditBatch::new();
+        batch.set_with_metadata(pos, block, metadata);
+        self.apply(batch).first().map(|&(_, previous)| previous)
+    }
+
+    /// 取出最多 `limit` 个等待处理的方块更新，由方块更新系统调用
+    pub fn take_block_updates(&mut self, limit: usize) -> Vec<IVec3> {
+        self.updates.as_mut().map(|updates| updates.take(limit)).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::SystemState;
+
+    /// 2×2×2 个区块的世界，区块在 `ChunkStorage` 中为活动状态
+    fn test_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Events<ChunksModified>>();
+        let storage = ChunkStorage::new();
+        for x in 0..2 {
+            for y in 0..2 {
+                for z in 0..2 {
+                    let coord = IVec3::new(x, y, z);
+                    storage.insert(coord, world.spawn(Chunk::new(coord)).id());
+                }
+            }
+        }
+        world.insert_resource(storage);
+        world
+    }
+
+    fn clear_dirty(world: &mut World) {
+        for mut chunk in world.query::<&mut Chunk>().iter_mut(world) {
+            chunk.dirty = false;
+        }
+    }
+
+    #[test]
+    fn batch_marks_each_touched_chunk_once_and_reports_previous_blocks() {
+        let mut world = test_world();
+        clear_dirty(&mut world);
+        let mut state = SystemState::<WorldAccess>::new(&mut world);
+        // 跨越 x 方向的区块边界，只修改 (0,0,0) 和 (1,0,0)
+        let mut batch = BlockEditBatch::new();
+        batch.fill(IVec3::new(30, 4, 4), IVec3::new(33, 5, 5), BlockId::Stone);
+        batch.set(IVec3::new(30, 4, 4), BlockId::Dirt);
+        assert_eq!(batch.len(), 17);
+        let previous = state.get_mut(&mut world).apply(batch);
+        assert_eq!(previous.len(), 17);
+        // 同一位置的第二次修改看到第一次写入的方块
+        assert_eq!(previous.iter().filter(|&&(_, block)| block == BlockId::Air).count(), 16);
+        assert!(previous.contains(&(IVec3::new(30, 4, 4), BlockId::Stone)));
+
+        let events: Vec<_> = world.resource_mut::<Events<ChunksModified>>().drain().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].coords, [IVec3::ZERO, IVec3::X]);
+        let mut access = state.get_mut(&mut world);
+        assert_eq!(access.get_with_metadata(IVec3::new(30, 4, 4)), Some((BlockId::Dirt, 0)));
+        assert_eq!(access.get_with_metadata(IVec3::new(33, 5, 5)), Some((BlockId::Stone, 0)));
+        assert!(access.is_solid(IVec3::new(32, 5, 4)) && !access.is_solid(IVec3::new(34, 5, 4)));
+        assert_eq!(access.view().get_block(IVec3::new(-1, 0, 0)), BlockId::Air);
+        assert_eq!(access.set_block(IVec3::new(0, 0, -1), BlockId::Stone), None);
+        let dirty: Vec<_> = world.query::<&Chunk>().iter(&world).filter(|chunk| chunk.dirty).map(|chunk| chunk.coord).collect();
+        assert_eq!(dirty.len(), 2);
+        let solid = world.query::<&Chunk>().iter(&world).map(|chunk| chunk.solid_blocks.len()).sum::<usize>();
+        assert_eq!(solid, 16);
+    }
+
+    /// 16×16×16 的填充跨越 8 个区块：先逐个方块写入，再整体用一批覆盖
+    #[test]
+    fn filling_a_cube_in_one_batch_replaces_blocks_set_one_by_one() {
+        let min = IVec3::new(24, 24, 24);
+        let max = min + IVec3::splat(15);
+        let mut world = test_world();
+        let mut state = SystemState::<WorldAccess>::new(&mut world);
+
+        let mut access = state.get_mut(&mut world);
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                for x in min.x..=max.x {
+                    access.set_block(IVec3::new(x, y, z), BlockId::Stone);
+                }
+            }
+        }
+        clear_dirty(&mut world);
+
+        let mut batch = BlockEditBatch::new();
+        batch.fill(min, max, BlockId::Dirt);
+        let previous = state.get_mut(&mut world).apply(batch);
+
+        assert_eq!(previous.len(), 16 * 16 * 16);
+        assert!(previous.iter().all(|&(_, block)| block == BlockId::Stone));
+        assert_eq!(world.query::<&Chunk>().iter(&world).filter(|chunk| chunk.dirty).count(), 8);
+        assert_eq!(state.get_mut(&mut world).get_with_metadata(max), Some((BlockId::Dirt, 0)));
+    }
+
+    /// 基准测试，不做断言：同样的 16×16×16 填充，逐个方块写入（每次都重新统计区块）和整体一批写入的耗时。
+    /// 运行：`cargo test --release -- --ignored --nocapture cube_fill_bench`
+    #[test]
+    #[ignore]
+    fn cube_fill_bench() {
+        let min = IVec3::new(24, 24, 24);
+        let max = min + IVec3::splat(15);
+        let mut world = test_world();
+        let mut state = SystemState::<WorldAccess>::new(&mut world);
+
+        let started = std::time::Instant::now();
+        let mut access = state.get_mut(&mut world);
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                for x in min.x..=max.x {
+                    access.set_block(IVec3::new(x, y, z), BlockId::Stone);
+                }
+            }
+        }
+        let one_by_one = started.elapsed();
+
+        let started = std::time::Instant::now();
+        let mut batch = BlockEditBatch::new();
+        batch.fill(min, max, BlockId::Dirt);
+        state.get_mut(&mut world).apply(batch);
+        let batched = started.elapsed();
+
+        println!("16x16x16 fill: one block at a time {:?}, one batch {:?}", one_by_one, batched);
+    }
+}
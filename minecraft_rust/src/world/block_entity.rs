@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game_state::GameState;
+use crate::inventory::ItemStack;
+use crate::world::chunk::{BlockId, Chunk};
+use crate::world::events::BlockEntityRemovedEvent;
+use crate::world::falling_block::spawn_dropped_item;
+
+/// 箱子的格子数（3 行 × 9 列）
+pub const CHEST_SLOTS: usize = 27;
+
+/// 方块 id 之外需要保存的方块数据，存放在所在区块中（见 `Chunk::block_entity`），随区块一起读写存档。
+/// 方块被替换时移除，并发送 `BlockEntityRemovedEvent`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BlockEntity {
+    /// 箱子里的物品
+    Chest(Vec<ItemStack>),
+}
+
+impl BlockEntity {
+    /// 方块第一次被使用时创建的空方块实体；不需要方块实体的方块返回 None
+    pub fn new_for(block: BlockId) -> Option<Self> {
+        match block {
+            BlockId::Chest => Some(BlockEntity::Chest(vec![ItemStack::empty(); CHEST_SLOTS])),
+            _ => None,
+        }
+    }
+
+    /// 所属方块被破坏时掉落的物品
+    pub fn drops(&self) -> Vec<ItemStack> {
+        match self {
+            BlockEntity::Chest(slots) => slots.iter().filter(|stack| !stack.is_empty()).copied().collect(),
+        }
+    }
+}
+
+pub struct BlockEntityPlugin;
+
+impl Plugin for BlockEntityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BlockEntityRemovedEvent>()
+           .add_systems(Update, (
+               collect_removed_block_entities,
+               drop_block_entity_contents.after(collect_removed_block_entities),
+           ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 取出各区块中所属方块已被替换的方块实体，逐个发送移除事件
+fn collect_removed_block_entities(
+    mut chunks: Query<&mut Chunk>,
+    mut removed: EventWriter<BlockEntityRemovedEvent>,
+) {
+    for mut chunk in chunks.iter_mut() {
+        // 先只读检查，没有移除方块实体的区块不触发修改检测
+        if chunk.has_removed_block_entities() {
+            for (pos, entity) in chunk.take_removed_block_entities() {
+                removed.send(BlockEntityRemovedEvent { pos, entity });
+            }
+        }
+    }
+}
+
+/// 被破坏的方块的内容（箱子里的物品）掉落在原来的位置
+fn drop_block_entity_contents(mut commands: Commands, mut removed: EventReader<BlockEntityRemovedEvent>) {
+    for event in removed.read() {
+        let position = event.pos.as_vec3() + Vec3::splat(0.5);
+        for stack in event.entity.drops() {
+            spawn_dropped_item(&mut commands, stack, position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::ItemType;
+    use crate::world::persistence;
+
+    #[test]
+    fn chest_contents_persist_and_drop_when_the_chest_is_replaced() {
+        let mut chunk = Chunk::new(IVec3::new(1, 0, -1));
+        chunk.set_block(4, 5, 6, BlockId::Chest);
+        let mut slots = vec![ItemStack::empty(); CHEST_SLOTS];
+        slots[3] = ItemStack::new(ItemType::Block(BlockId::Torch), 12);
+        chunk.set_block_entity(UVec3::new(4, 5, 6), Some(BlockEntity::Chest(slots.clone())));
+        assert!(chunk.modified);
+
+        // 写入存档再读回，方块实体保持不变
+        let dir = std::env::temp_dir().join(format!("block_entity_{}", std::process::id()));
+        persistence::save_chunk(&dir, &chunk).unwrap();
+        let mut loaded = persistence::load_chunk(&dir, chunk.coord).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.block_entity(UVec3::new(4, 5, 6)), Some(&BlockEntity::Chest(slots)));
+
+        // 改变元数据不会移除方块实体，破坏方块时移除并记录世界坐标
+        loaded.set_block_with_metadata(4, 5, 6, BlockId::Chest, 1);
+        assert!(!loaded.has_removed_block_entities());
+        loaded.set_block(4, 5, 6, BlockId::Air);
+        let removed = loaded.take_removed_block_entities();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].0, IVec3::new(36, 5, -26));
+        assert_eq!(removed[0].1.drops(), vec![ItemStack::new(ItemType::Block(BlockId::Torch), 12)]);
+        assert!(loaded.block_entity(UVec3::new(4, 5, 6)).is_none());
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use crate::block_registry::BlockRegistry;
+use crate::random::random_unit;
+use crate::game_state::{GameState, WorldState};
+use crate::mining::block_drop;
+use crate::scripting::ScriptEngine;
+use crate::world::access::{BlockEditBatch, WorldAccess, WorldView};
+use crate::world::chunk::{BlockId, Chunk};
+use crate::world::storage::ChunkLifecycle;
+use crate::world::events::BlockChangedEvent;
+use crate::world::falling_block::spawn_dropped_item;
+use crate::world::BlockUpdateSet;
+
+/// 每帧最多处理的方块更新，超出的留到之后的帧
+const MAX_UPDATES_PER_FRAME: usize = 1024;
+
+/// 随机刻的频率（每秒），与原版的游戏刻相同
+const RANDOM_TICKS_PER_SECOND: f32 = 20.0;
+/// 卡顿之后一帧最多补上的随机刻
+const MAX_RANDOM_TICKS_PER_FRAME: u32 = 4;
+/// 原版按 16×16×16 的子区块抽取随机方块，一个 32×32×32 的区块相当于 8 个子区块
+const SECTIONS_PER_CHUNK: i64 = 8;
+/// `random_tick_speed` 游戏规则不存在时每个子区块每刻抽取的方块数
+const DEFAULT_RANDOM_TICK_SPEED: i64 = 3;
+
+const FACE_NEIGHBORS: [IVec3; 6] = [
+    IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z,
+];
+
+/// 等待处理的方块更新（"相邻方块变化了"），由 `WorldAccess::apply` 在每次修改后加入修改的位置和六个相邻位置。
+/// 同一位置在处理之前只排队一次
+#[derive(Resource, Default)]
+pub struct BlockUpdateQueue {
+    pending: VecDeque<IVec3>,
+    queued: HashSet<IVec3>,
+}
+
+impl BlockUpdateQueue {
+    /// 加入修改的位置和它的六个相邻位置
+    pub fn push_with_neighbors(&mut self, pos: IVec3) {
+        for pos in std::iter::once(pos).chain(FACE_NEIGHBORS.iter().map(|offset| pos + *offset)) {
+            if self.queued.insert(pos) {
+                self.pending.push_back(pos);
+            }
+        }
+    }
+
+    /// 按加入顺序取出最多 `limit` 个位置
+    pub fn take(&mut self, limit: usize) -> Vec<IVec3> {
+        let count = limit.min(self.pending.len());
+        let taken: Vec<IVec3> = self.pending.drain(..count).collect();
+        for pos in &taken {
+            self.queued.remove(pos);
+        }
+        taken
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.queued.clear();
+    }
+}
+
+/// 方块更新和随机刻处理函数可以做的修改，所有处理函数的修改在本帧结束时合成一批应用
+pub struct BlockUpdateContext<'v, 'w, 's> {
+    /// 本帧修改之前的世界
+    pub world: &'v WorldView<'w, 's, 'static>,
+    edits: BlockEditBatch,
+    broken: Vec<(IVec3, BlockId)>,
+    seed: u64,
+    rolls: u64,
+}
+
+impl BlockUpdateContext<'_, '_, '_> {
+    /// 破坏方块并按掉落表掉落物品
+    pub fn break_block(&mut self, pos: IVec3, block: BlockId) {
+        self.edits.set(pos, BlockId::Air);
+        self.broken.push((pos, block));
+    }
+
+    pub fn set_block(&mut self, pos: IVec3, block: BlockId) {
+        self.edits.set(pos, block);
+    }
+
+    /// [0, 1) 的伪随机数，每次调用都不同
+    pub fn random(&mut self) -> f32 {
+        self.rolls += 1;
+        random_unit(self.seed, self.rolls)
+    }
+}
+
+type BlockUpdateHandler = Box<dyn Fn(&mut BlockUpdateContext, IVec3) + Send + Sync>;
+
+/// 按方块类型注册的方块更新和随机刻处理函数，由各功能模块通过 `AddBlockUpdateHandler` 注册
+#[derive(Resource, Default)]
+pub struct BlockUpdateHandlers {
+    handlers: HashMap<BlockId, Vec<BlockUpdateHandler>>,
+    random_ticks: HashMap<BlockId, Vec<BlockUpdateHandler>>,
+}
+
+pub trait AddBlockUpdateHandler {
+    /// 相邻方块变化时对 `block` 类型的方块调用 `handler`，参数为方块所在的位置
+    fn add_block_update_handler(
+        &mut self,
+        block: BlockId,
+        handler: impl Fn(&mut BlockUpdateContext, IVec3) + Send + Sync + 'static,
+    ) -> &mut Self;
+
+    /// 随机刻抽到 `block` 类型的方块时调用 `handler`，参数为方块所在的位置
+    fn add_random_tick_handler(
+        &mut self,
+        block: BlockId,
+        handler: impl Fn(&mut BlockUpdateContext, IVec3) + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl AddBlockUpdateHandler for App {
+    fn add_block_update_handler(
+        &mut self,
+        block: BlockId,
+        handler: impl Fn(&mut BlockUpdateContext, IVec3) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.init_resource::<BlockUpdateHandlers>();
+        self.world.resource_mut::<BlockUpdateHandlers>().handlers.entry(block).or_default().push(Box::new(handler));
+        self
+    }
+
+    fn add_random_tick_handler(
+        &mut self,
+        block: BlockId,
+        handler: impl Fn(&mut BlockUpdateContext, IVec3) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.init_resource::<BlockUpdateHandlers>();
+        self.world.resource_mut::<BlockUpdateHandlers>().random_ticks.entry(block).or_default().push(Box::new(handler));
+        self
+    }
+}
+
+/// 修改世界的方块更新系统共用的参数：`run` 提供一个 `BlockUpdateContext`，结束后把修改合成一批应用，
+/// 发送方块变化事件，并按掉落表掉落被破坏的方块
+#[derive(SystemParam)]
+pub struct BlockUpdates<'w, 's> {
+    commands: Commands<'w, 's>,
+    pub world: WorldAccess<'w, 's>,
+    block_changes: EventWriter<'w, BlockChangedEvent>,
+    runs: Local<'s, u64>,
+}
+
+impl BlockUpdates<'_, '_> {
+    pub fn run<R>(&mut self, f: impl FnOnce(&mut BlockUpdateContext) -> R) -> R {
+        *self.runs += 1;
+        let view = self.world.view();
+        let mut context = BlockUpdateContext {
+            world: &view,
+            edits: BlockEditBatch::new(),
+            broken: Vec::new(),
+            seed: *self.runs,
+            rolls: 0,
+        };
+        let result = f(&mut context);
+        let broken: Vec<_> = std::mem::take(&mut context.broken)
+            .into_iter()
+            .map(|(pos, block)| (pos, block, block_drop(block, context.random())))
+            .collect();
+        let edits = context.edits;
+
+        let changed = self.world.apply(edits);
+        for (pos, _) in &changed {
+            self.block_changes.send(BlockChangedEvent { pos: *pos });
+        }
+        for (pos, block, drop) in broken {
+            let Some(drop) = drop else { continue };
+            if changed.iter().any(|(changed_pos, previous)| *changed_pos == pos && *previous == block) {
+                spawn_dropped_item(&mut self.commands, drop, pos.as_vec3() + Vec3::splat(0.5));
+            }
+        }
+        result
+    }
+}
+
+pub struct BlockUpdatePlugin;
+
+impl Plugin for BlockUpdatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlockUpdateQueue>()
+           .init_resource::<BlockUpdateHandlers>()
+           .add_block_update_handler(BlockId::Torch, break_unsupported_torch)
+           .add_systems(OnEnter(GameState::Loading), clear_block_updates)
+           .add_systems(Update, (dispatch_block_updates, dispatch_random_ticks).in_set(BlockUpdateSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 火把立在下方的方块上，下方不再是实心方块时掉落
+fn break_unsupported_torch(context: &mut BlockUpdateContext, pos: IVec3) {
+    if context.world.block(pos - IVec3::Y).is_some_and(|below| !below.is_solid()) {
+        context.break_block(pos, BlockId::Torch);
+    }
+}
+
+pub fn clear_block_updates(mut queue: ResMut<BlockUpdateQueue>) {
+    queue.clear();
+}
+
+/// 取出本帧的方块更新，按位置上的方块类型调用注册的处理函数和脚本中的 `on_neighbor_changed`，
+/// 然后把所有修改合成一批应用
+fn dispatch_block_updates(
+    mut updates: BlockUpdates,
+    handlers: Res<BlockUpdateHandlers>,
+    registry: Option<Res<BlockRegistry>>,
+    engine: Option<Res<ScriptEngine>>,
+) {
+    let positions = updates.world.take_block_updates(MAX_UPDATES_PER_FRAME);
+    if positions.is_empty() {
+        return;
+    }
+    let scripts = registry.as_deref().zip(engine.as_deref());
+    let script_hooks = scripts.map(|(registry, _)| registry.neighbor_hooks());
+    updates.run(|context| {
+        for pos in positions {
+            let Some(block) = context.world.block(pos) else { continue };
+            for handler in handlers.handlers.get(&block).into_iter().flatten() {
+                handler(context, pos);
+            }
+            if let (Some((registry, engine)), Some(hooks)) = (scripts, &script_hooks) {
+                if hooks[block as usize] {
+                    match registry.call_neighbor_hook(engine, block, pos) {
+                        Ok(true) => context.break_block(pos, block),
+                        Ok(false) => {}
+                        Err(e) => warn!("on_neighbor_changed for {:?} at {:?} failed: {}", block, pos, e),
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// 随机刻：每刻在每个已加载的区块中随机抽取 `random_tick_speed` × 8 个位置，
+/// 抽到注册了随机刻处理函数的方块时调用它们。作物生长这类缓慢的变化由它驱动
+fn dispatch_random_ticks(
+    mut updates: BlockUpdates,
+    handlers: Res<BlockUpdateHandlers>,
+    world_state: Option<Res<WorldState>>,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+) {
+    *elapsed += time.delta_seconds() * RANDOM_TICKS_PER_SECOND;
+    let ticks = (*elapsed as u32).min(MAX_RANDOM_TICKS_PER_FRAME);
+    *elapsed = elapsed.fract();
+    let speed = world_state.and_then(|state| state.rule_int("random_tick_speed")).unwrap_or(DEFAULT_RANDOM_TICK_SPEED);
+    let samples = (speed.max(0) * SECTIONS_PER_CHUNK) as u32 * ticks;
+    if samples == 0 || handlers.random_ticks.is_empty() {
+        return;
+    }
+    let coords: Vec<IVec3> = updates.world.chunk_storage.chunks
+        .iter()
+        .filter(|slot| slot.state == ChunkLifecycle::Active)
+        .map(|slot| *slot.key())
+        .collect();
+    updates.run(|context| {
+        let world = context.world;
+        for coord in coords {
+            let Some(chunk) = world.active_chunk(coord).filter(|chunk| !chunk.is_fully_air()) else { continue };
+            for _ in 0..samples {
+                let local = (Vec3::new(context.random(), context.random(), context.random()) * Chunk::SIZE.as_vec3()).as_uvec3();
+                let block = chunk.get_block(local.x, local.y, local.z);
+                for handler in handlers.random_ticks.get(&block).into_iter().flatten() {
+                    handler(context, coord * Chunk::SIZE.as_ivec3() + local.as_ivec3());
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::inventory::ItemType;
+    use crate::world::falling_block::DroppedItem;
+    use crate::world::test_support::flat_chunk_app;
+
+    #[test]
+    fn updates_are_deduplicated_and_carried_over() {
+        let mut queue = BlockUpdateQueue::default();
+        queue.push_with_neighbors(IVec3::ZERO);
+        queue.push_with_neighbors(IVec3::X);
+        // 两次共有的 (0,0,0) 和 (1,0,0) 只排队一次，共 12 个位置
+        assert_eq!(queue.take(10).len(), 10);
+        // 已取出的位置可以再次排队，剩下的 2 个留到下一次
+        queue.push_with_neighbors(IVec3::ZERO);
+        assert_eq!(queue.take(100).len(), 2 + 7);
+        assert!(queue.take(100).is_empty());
+    }
+
+    #[test]
+    fn torch_drops_when_its_support_is_removed() {
+        // 两支火把立在 y = 4 的石头地面上，挖掉其中一支下面的石头
+        let (mut app, entity) = flat_chunk_app(4, BlockId::Stone);
+        app.add_state::<GameState>()
+           .add_plugins(BlockUpdatePlugin);
+        app.world.insert_resource(State::new(GameState::InGame));
+        let mut chunk = app.world.get_mut::<Chunk>(entity).unwrap();
+        chunk.set_block(4, 5, 4, BlockId::Torch);
+        chunk.set_block(6, 5, 4, BlockId::Torch);
+
+        app.world.run_system_once(|mut world: WorldAccess| {
+            world.set_block(IVec3::new(4, 4, 4), BlockId::Air);
+        });
+        app.update();
+        let chunk = app.world.get::<Chunk>(entity).unwrap();
+        assert_eq!(chunk.get_block(4, 5, 4), BlockId::Air);
+        assert_eq!(chunk.get_block(6, 5, 4), BlockId::Torch);
+        let drops: Vec<ItemType> = app.world.query::<&DroppedItem>().iter(&app.world).map(|item| item.stack.item_type).collect();
+        assert_eq!(drops, vec![ItemType::Block(BlockId::Torch)]);
+    }
+}
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
 use bevy::prelude::*;
 use serde::{Serialize, Deserialize};
+use crate::world::block_entity::BlockEntity;
+use crate::world::entity_persistence::EntityRecord;
+use crate::world::generator::BiomeType;
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum BlockId {
@@ -8,10 +12,88 @@ pub enum BlockId {
     Dirt,
     Grass,
     Bedrock,
+    Water,
+    Lava,
+    Obsidian,
+    Sand,
+    Gravel,
+    Torch,
+    CraftingTable,
+    Chest,
+    Tnt,
+    Bed,
+    Log,
+    Leaves,
+    Sapling,
 }
 
 impl Default for BlockId { fn default() -> Self { BlockId::Air } }
 
+impl BlockId {
+    pub const COUNT: usize = 18;
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => BlockId::Stone,
+            2 => BlockId::Dirt,
+            3 => BlockId::Grass,
+            4 => BlockId::Bedrock,
+            5 => BlockId::Water,
+            6 => BlockId::Lava,
+            7 => BlockId::Obsidian,
+            8 => BlockId::Sand,
+            9 => BlockId::Gravel,
+            10 => BlockId::Torch,
+            11 => BlockId::CraftingTable,
+            12 => BlockId::Chest,
+            13 => BlockId::Tnt,
+            14 => BlockId::Bed,
+            15 => BlockId::Log,
+            16 => BlockId::Leaves,
+            17 => BlockId::Sapling,
+            _ => BlockId::Air,
+        }
+    }
+
+    pub fn is_fluid(self) -> bool {
+        matches!(self, BlockId::Water | BlockId::Lava)
+    }
+
+    /// 下方是空气或流体时会下落的方块
+    pub fn has_gravity(self) -> bool {
+        matches!(self, BlockId::Sand | BlockId::Gravel)
+    }
+
+    /// 有碰撞体积的完整方块；空气、流体、火把和树苗都不是实心方块
+    pub fn is_solid(self) -> bool {
+        !matches!(self, BlockId::Air | BlockId::Torch | BlockId::Sapling) && !self.is_fluid()
+    }
+
+    /// 遮挡相邻面和光照的方块，对应方块脚本中的 `transparent = false`。树叶是实心方块但透光
+    pub fn is_opaque(self) -> bool {
+        self.is_solid() && self != BlockId::Leaves
+    }
+
+    /// 挡住天空直射光的方块：不透光的方块和流体。天空光照在它下面只能从旁边扩散进来
+    pub fn blocks_sky(self) -> bool {
+        self.is_opaque() || self.is_fluid()
+    }
+
+    /// 能被射线选中、破坏的方块：实心方块、火把和树苗
+    pub fn is_selectable(self) -> bool {
+        self != BlockId::Air && !self.is_fluid()
+    }
+
+    /// 与 `neighbor` 相邻的面是否可见：被不透光的方块遮挡，流体和树叶等透光方块还会被同种方块遮挡
+    pub fn face_visible_against(self, neighbor: BlockId) -> bool {
+        !neighbor.is_opaque() && neighbor != self
+    }
+}
+
+/// 区块细节等级：0 为完整分辨率，1 为 2×2×2 合并后的半分辨率（远处区块使用）
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LodLevel(pub u8);
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Chunk {
     pub coord: IVec3,
@@ -20,6 +102,116 @@ pub struct Chunk {
     pub solid_blocks: Vec<IVec3>,
     #[serde(skip)]
     pub dirty: bool,
+    /// 玩家修改过、尚未写入存档
+    #[serde(skip)]
+    pub modified: bool,
+    #[serde(skip)]
+    fluid_count: usize,
+    /// 每个方块 4 位的附加数据，两个方块共用一个字节（低 4 位为偶数下标）。流体用它记录流动等级。
+    /// 全部为 0 时为空数组，不占内存，第一次写入非零值时才分配
+    #[serde(with = "serde_bytes")]
+    pub metadata: Vec<u8>,
+    /// 每个方块一个字节的光照：低 4 位为方块光照，高 4 位为天空光照。光照可以由方块重新算出，不写入存档
+    #[serde(skip, default = "Chunk::dark")]
+    light: Vec<u8>,
+    /// 光照已经计算过；新生成、读档或从服务器收到的区块为 false，由光照系统补算
+    #[serde(skip)]
+    pub lit: bool,
+    /// 方块实体（例如箱子里的物品），按局部坐标存储，随区块写入存档
+    block_entities: HashMap<UVec3, BlockEntity>,
+    /// 所属方块被替换后移除的方块实体（世界坐标），由 `block_entity::collect_removed_block_entities` 取出
+    #[serde(skip)]
+    removed_block_entities: Vec<(IVec3, BlockEntity)>,
+    /// 上次卸载或保存时位于区块内的持久实体（例如掉落物），区块加载时重新生成
+    entities: Vec<EntityRecord>,
+    /// 每一列（x, z）的生物群系编号，生成时写入并随区块保存。为空表示不知道，
+    /// 例如加入生物群系之前保存的区块和导入的区块
+    #[serde(with = "serde_bytes")]
+    biomes: Vec<u8>,
+}
+
+/// 加入附加数据之前的区块存档格式，读取旧存档时使用
+#[derive(Deserialize)]
+pub struct LegacyChunk {
+    pub coord: IVec3,
+    #[serde(with = "serde_bytes")]
+    pub blocks: Vec<u8>,
+}
+
+impl From<LegacyChunk> for Chunk {
+    fn from(legacy: LegacyChunk) -> Self {
+        let mut chunk = Chunk::new(legacy.coord);
+        chunk.blocks = legacy.blocks;
+        chunk
+    }
+}
+
+/// 加入方块实体之前的区块存档格式，读取旧存档时使用
+#[derive(Deserialize)]
+pub struct MetadataChunk {
+    pub coord: IVec3,
+    #[serde(with = "serde_bytes")]
+    pub blocks: Vec<u8>,
+    /// 读档时重新统计，这里只是按顺序跳过存档中的这一段
+    _solid_blocks: Vec<IVec3>,
+    #[serde(with = "serde_bytes")]
+    pub metadata: Vec<u8>,
+}
+
+impl From<MetadataChunk> for Chunk {
+    fn from(old: MetadataChunk) -> Self {
+        let mut chunk = Chunk::new(old.coord);
+        chunk.blocks = old.blocks;
+        chunk.metadata = old.metadata;
+        chunk.compact_metadata();
+        chunk
+    }
+}
+
+/// 加入持久实体之前的区块存档格式，读取旧存档时使用
+#[derive(Deserialize)]
+pub struct BlockEntityChunk {
+    pub coord: IVec3,
+    #[serde(with = "serde_bytes")]
+    pub blocks: Vec<u8>,
+    _solid_blocks: Vec<IVec3>,
+    #[serde(with = "serde_bytes")]
+    pub metadata: Vec<u8>,
+    block_entities: HashMap<UVec3, BlockEntity>,
+}
+
+impl From<BlockEntityChunk> for Chunk {
+    fn from(old: BlockEntityChunk) -> Self {
+        let mut chunk = Chunk::new(old.coord);
+        chunk.blocks = old.blocks;
+        chunk.metadata = old.metadata;
+        chunk.block_entities = old.block_entities;
+        chunk
+    }
+}
+
+/// 加入生物群系之前的区块存档格式，读取旧存档时使用
+#[derive(Deserialize)]
+pub struct EntityChunk {
+    pub coord: IVec3,
+    #[serde(with = "serde_bytes")]
+    pub blocks: Vec<u8>,
+    _solid_blocks: Vec<IVec3>,
+    #[serde(with = "serde_bytes")]
+    pub metadata: Vec<u8>,
+    block_entities: HashMap<UVec3, BlockEntity>,
+    entities: Vec<EntityRecord>,
+}
+
+impl From<EntityChunk> for Chunk {
+    fn from(old: EntityChunk) -> Self {
+        let mut chunk = Chunk::new(old.coord);
+        chunk.blocks = old.blocks;
+        chunk.metadata = old.metadata;
+        chunk.block_entities = old.block_entities;
+        chunk.entities = old.entities;
+        chunk
+    }
 }
 
 impl Chunk {
@@ -28,16 +220,52 @@ impl Chunk {
     pub const COUNT: usize = (32*32*32) as usize;
 
     pub fn new(coord: IVec3) -> Self {
-        Self { coord, blocks: vec![BlockId::Air as u8; Self::COUNT], solid_blocks: Vec::new(), dirty: true }
+        Self {
+            coord,
+            blocks: vec![BlockId::Air as u8; Self::COUNT],
+            solid_blocks: Vec::new(),
+            dirty: true,
+            modified: false,
+            fluid_count: 0,
+            metadata: Vec::new(),
+            light: Self::dark(),
+            lit: false,
+            block_entities: HashMap::new(),
+            removed_block_entities: Vec::new(),
+            entities: Vec::new(),
+            biomes: Vec::new(),
+        }
+    }
+
+    fn dark() -> Vec<u8> {
+        vec![0; Self::COUNT]
+    }
+
+    /// 区块数据占用内存的估计（字节）：各数组按容量计算，方块实体按表项大小估算，不含实体内的物品列表
+    pub fn memory_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.blocks.capacity()
+            + self.metadata.capacity()
+            + self.light.capacity()
+            + self.solid_blocks.capacity() * std::mem::size_of::<IVec3>()
+            + self.block_entities.capacity() * std::mem::size_of::<(UVec3, BlockEntity)>()
+            + self.removed_block_entities.capacity() * std::mem::size_of::<(IVec3, BlockEntity)>()
+            + self.entities.capacity() * std::mem::size_of::<EntityRecord>()
+            + self.biomes.capacity()
     }
 
+    /// 统计实心方块（碰撞检测使用）和流体方块的数量
     pub fn compute_solid_blocks(&mut self) {
         self.solid_blocks.clear();
+        self.fluid_count = 0;
         for x in 0..Self::SIZE.x {
             for y in 0..Self::SIZE.y {
                 for z in 0..Self::SIZE.z {
-                    if self.get_block(x, y, z) != BlockId::Air {
+                    let block = self.get_block(x, y, z);
+                    if block.is_solid() {
                         self.solid_blocks.push(IVec3::new(x as i32, y as i32, z as i32));
+                    } else if block.is_fluid() {
+                        self.fluid_count += 1;
                     }
                 }
             }
@@ -48,25 +276,316 @@ impl Chunk {
         &self.solid_blocks
     }
 
+    /// 全部为空气（依据 `compute_solid_blocks` 的统计结果）
+    pub fn is_fully_air(&self) -> bool {
+        self.solid_blocks.is_empty() && self.fluid_count == 0
+    }
+
+    pub fn has_fluid(&self) -> bool {
+        self.fluid_count > 0
+    }
+
+    /// 全部为实心方块（依据 `compute_solid_blocks` 的统计结果）
+    pub fn is_fully_solid(&self) -> bool {
+        self.solid_blocks.len() == Self::COUNT
+    }
+
+    /// `side` 方向（单位轴向量）那一层边界上是否存在不遮挡相邻面的方块（空气、流体或透光方块）
+    pub fn face_has_air(&self, side: IVec3) -> bool {
+        if self.is_fully_air() {
+            return true;
+        }
+        let edge = |v: i32| if v > 0 { Some(Self::SIZE.x - 1) } else if v < 0 { Some(0) } else { None };
+        (0..Self::SIZE.x).any(|a| {
+            (0..Self::SIZE.x).any(|b| {
+                let (x, y, z) = match (edge(side.x), edge(side.y), edge(side.z)) {
+                    (Some(x), _, _) => (x, a, b),
+                    (_, Some(y), _) => (a, y, b),
+                    (_, _, Some(z)) => (a, b, z),
+                    _ => return false,
+                };
+                !self.get_block(x, y, z).is_opaque()
+            })
+        })
+    }
+
     #[inline]
     fn index(x: u32, y: u32, z: u32) -> usize {
         // x fastest, then z, then y: (y*32 + z)*32 + x
         ((y as usize) * 32 + (z as usize)) * 32 + (x as usize)
     }
 
+    /// 设置方块并清除它的附加数据
     pub fn set_block(&mut self, x: u32, y: u32, z: u32, id: BlockId) {
+        self.set_block_with_metadata(x, y, z, id, 0);
+    }
+
+    pub fn set_block_with_metadata(&mut self, x: u32, y: u32, z: u32, id: BlockId, metadata: u8) {
         let idx = Self::index(x, y, z);
-        let old_block = self.blocks[idx];
+        let old_block = BlockId::from_u8(self.blocks[idx]);
+        let old_metadata = self.get_metadata(x, y, z);
         self.blocks[idx] = id as u8;
-        
+        self.write_metadata(idx, metadata);
+
+        // 方块被替换时，它的方块实体随之移除，等待处理掉落等后续操作
+        if old_block != id && !self.block_entities.is_empty() {
+            if let Some(entity) = self.block_entities.remove(&UVec3::new(x, y, z)) {
+                let world_pos = self.coord * 32 + IVec3::new(x as i32, y as i32, z as i32);
+                self.removed_block_entities.push((world_pos, entity));
+            }
+        }
+
+        // 流体数量随修改更新，实心方块列表仍由 compute_solid_blocks 重新统计
+        if old_block.is_fluid() != id.is_fluid() {
+            if id.is_fluid() { self.fluid_count += 1 } else { self.fluid_count = self.fluid_count.saturating_sub(1) }
+        }
+
         // 如果方块发生了变化，标记为dirty
-        if old_block != id as u8 {
+        if old_block != id || old_metadata != metadata {
             self.dirty = true;
         }
     }
 
     pub fn get_block(&self, x: u32, y: u32, z: u32) -> BlockId {
-        let idx = Self::index(x, y, z);
-        match self.blocks[idx] { 0 => BlockId::Air, 1 => BlockId::Stone, 2 => BlockId::Dirt, 3 => BlockId::Grass, 4 => BlockId::Bedrock, _ => BlockId::Air }
+        BlockId::from_u8(self.blocks[Self::index(x, y, z)])
+    }
+
+    pub fn get_metadata(&self, x: u32, y: u32, z: u32) -> u8 {
+        if self.metadata.is_empty() {
+            return 0;
+        }
+        read_nibble(&self.metadata, Self::index(x, y, z))
+    }
+
+    fn write_metadata(&mut self, idx: usize, metadata: u8) {
+        if self.metadata.is_empty() {
+            if metadata & 0x0F == 0 {
+                return;
+            }
+            self.metadata = vec![0; Self::COUNT / 2];
+        }
+        write_nibble(&mut self.metadata, idx, metadata);
+    }
+
+    /// 附加数据全部为 0 时释放数组，读档和收到服务器区块后调用
+    pub fn compact_metadata(&mut self) {
+        if self.metadata.iter().all(|&byte| byte == 0) {
+            self.metadata = Vec::new();
+        }
+    }
+
+    pub fn block_entity(&self, local: UVec3) -> Option<&BlockEntity> {
+        self.block_entities.get(&local)
+    }
+
+    /// 设置或移除（`None`）局部坐标处的方块实体，并标记区块为已修改以写入存档
+    pub fn set_block_entity(&mut self, local: UVec3, entity: Option<BlockEntity>) {
+        match entity {
+            Some(entity) => self.block_entities.insert(local, entity),
+            None => self.block_entities.remove(&local),
+        };
+        self.modified = true;
+    }
+
+    pub fn has_removed_block_entities(&self) -> bool {
+        !self.removed_block_entities.is_empty()
+    }
+
+    /// 取出所属方块已被替换的方块实体：(世界坐标, 方块实体)
+    pub fn take_removed_block_entities(&mut self) -> Vec<(IVec3, BlockEntity)> {
+        std::mem::take(&mut self.removed_block_entities)
     }
-}
\ No newline at end of file
+
+    pub fn entities(&self) -> &[EntityRecord] {
+        &self.entities
+    }
+
+    /// 替换区块内的持久实体记录，之前或现在有实体时区块需要重新保存
+    pub fn set_entities(&mut self, entities: Vec<EntityRecord>) {
+        if !entities.is_empty() || !self.entities.is_empty() {
+            self.entities = entities;
+            self.modified = true;
+        }
+    }
+
+    /// 列（x, z）的生物群系，区块没有记录生物群系时返回 None
+    pub fn biome(&self, x: u32, z: u32) -> Option<BiomeType> {
+        let id = *self.biomes.get((z * Self::SIZE.x + x) as usize)?;
+        BiomeType::from_id(id)
+    }
+
+    /// 记录列（x, z）的生物群系，第一次写入时分配整个区块的数组
+    pub fn set_biome(&mut self, x: u32, z: u32, biome: BiomeType) {
+        if self.biomes.is_empty() {
+            self.biomes = vec![BiomeType::Plains.id(); (Self::SIZE.x * Self::SIZE.z) as usize];
+        }
+        self.biomes[(z * Self::SIZE.x + x) as usize] = biome.id();
+    }
+
+    /// 所有列的生物群系编号（先 z 后 x），没有记录时为空，用于网络传输
+    pub fn biome_ids(&self) -> &[u8] {
+        &self.biomes
+    }
+
+    /// 替换所有列的生物群系编号，长度不是空也不是每列一个时返回 false
+    pub fn set_biome_ids(&mut self, ids: Vec<u8>) -> bool {
+        if !ids.is_empty() && ids.len() != (Self::SIZE.x * Self::SIZE.z) as usize {
+            return false;
+        }
+        self.biomes = ids;
+        true
+    }
+
+    pub fn get_light(&self, x: u32, y: u32, z: u32) -> u8 {
+        self.light[Self::index(x, y, z)] & 0x0F
+    }
+
+    pub fn get_sky_light(&self, x: u32, y: u32, z: u32) -> u8 {
+        self.light[Self::index(x, y, z)] >> 4
+    }
+
+    /// 设置方块光照等级，等级变化时标记为 dirty 以重建网格
+    pub fn set_light(&mut self, x: u32, y: u32, z: u32, level: u8) {
+        let light = &mut self.light[Self::index(x, y, z)];
+        let updated = (*light & 0xF0) | (level & 0x0F);
+        if *light != updated {
+            *light = updated;
+            self.dirty = true;
+        }
+    }
+
+    /// 设置天空光照等级，等级变化时标记为 dirty 以重建网格
+    pub fn set_sky_light(&mut self, x: u32, y: u32, z: u32, level: u8) {
+        let light = &mut self.light[Self::index(x, y, z)];
+        let updated = (*light & 0x0F) | ((level & 0x0F) << 4);
+        if *light != updated {
+            *light = updated;
+            self.dirty = true;
+        }
+    }
+
+    /// 所有格子按露天处理，天空光照为 15。远景区块不计算光照，相邻完整区块朝向它的面不会变暗
+    pub fn fill_sky_light(&mut self) {
+        for light in &mut self.light {
+            *light |= 0xF0;
+        }
+    }
+
+    /// 列 (x, z) 中最高的遮挡天空的方块（实心方块或流体）的局部高度
+    pub fn column_top(&self, x: u32, z: u32) -> Option<u32> {
+        (0..Self::SIZE.y).rev().find(|&y| self.get_block(x, y, z).blocks_sky())
+    }
+
+    /// 降采样为半分辨率：每个 2×2×2 单元中至少一半是实心方块时，整个单元填充为其中最多的方块类型，否则为空气。
+    /// 流体按空气处理，远处不显示水面
+    pub fn downsample_lod(&mut self) {
+        for cy in (0..Self::SIZE.y).step_by(2) {
+            for cz in (0..Self::SIZE.z).step_by(2) {
+                for cx in (0..Self::SIZE.x).step_by(2) {
+                    let mut counts = [0u8; BlockId::COUNT];
+                    for (dx, dy, dz) in Self::CELL_OFFSETS {
+                        let block = self.get_block(cx + dx, cy + dy, cz + dz);
+                        if block.is_solid() {
+                            counts[block as usize] += 1;
+                        }
+                    }
+                    let solid: u8 = counts.iter().sum();
+                    let fill = if solid >= 4 {
+                        // 数量相同时取编号较小的方块
+                        let (id, _) = counts.iter().enumerate().skip(1).rev().max_by_key(|(_, n)| **n).unwrap();
+                        id as u8
+                    } else {
+                        BlockId::Air as u8
+                    };
+                    for (dx, dy, dz) in Self::CELL_OFFSETS {
+                        self.blocks[Self::index(cx + dx, cy + dy, cz + dz)] = fill;
+                    }
+                }
+            }
+        }
+        self.metadata = Vec::new();
+        self.dirty = true;
+    }
+
+    const CELL_OFFSETS: [(u32, u32, u32); 8] = [
+        (0, 0, 0), (1, 0, 0), (0, 1, 0), (1, 1, 0),
+        (0, 0, 1), (1, 0, 1), (0, 1, 1), (1, 1, 1),
+    ];
+}
+
+/// 两个 4 位数值共用一个字节，低 4 位为偶数下标
+fn read_nibble(data: &[u8], idx: usize) -> u8 {
+    (data[idx / 2] >> ((idx % 2) * 4)) & 0x0F
+}
+
+fn write_nibble(data: &mut [u8], idx: usize, value: u8) {
+    let shift = (idx % 2) * 4;
+    let byte = &mut data[idx / 2];
+    *byte = (*byte & !(0x0F << shift)) | ((value & 0x0F) << shift);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downsample_fills_cells_with_majority_block() {
+        let mut chunk = Chunk::new(IVec3::ZERO);
+        // 第一个单元：3 块石头 + 2 块泥土，实心方块过半，按数量最多的石头填充
+        chunk.set_block(0, 0, 0, BlockId::Stone);
+        chunk.set_block(1, 0, 0, BlockId::Stone);
+        chunk.set_block(0, 1, 0, BlockId::Stone);
+        chunk.set_block(1, 1, 0, BlockId::Dirt);
+        chunk.set_block(0, 0, 1, BlockId::Dirt);
+        // 第二个单元：只有 3 块实心，变为空气
+        chunk.set_block(2, 0, 0, BlockId::Grass);
+        chunk.set_block(3, 0, 0, BlockId::Grass);
+        chunk.set_block(2, 1, 0, BlockId::Grass);
+
+        chunk.downsample_lod();
+
+        for (dx, dy, dz) in Chunk::CELL_OFFSETS {
+            assert_eq!(chunk.get_block(dx, dy, dz), BlockId::Stone);
+            assert_eq!(chunk.get_block(2 + dx, dy, dz), BlockId::Air);
+        }
+    }
+
+    #[test]
+    fn solid_classification_and_exposed_faces() {
+        let mut chunk = Chunk::new(IVec3::ZERO);
+        chunk.compute_solid_blocks();
+        assert!(chunk.is_fully_air());
+
+        chunk.blocks.fill(BlockId::Stone as u8);
+        chunk.compute_solid_blocks();
+        assert!(chunk.is_fully_solid());
+        assert!(!chunk.face_has_air(IVec3::X));
+
+        // 在 +X 边界挖掉一个方块后，只有 +X 面暴露
+        chunk.set_block(31, 5, 7, BlockId::Air);
+        chunk.compute_solid_blocks();
+        assert!(!chunk.is_fully_solid() && !chunk.is_fully_air());
+        assert!(chunk.face_has_air(IVec3::X));
+        assert!(!chunk.face_has_air(IVec3::NEG_X));
+        assert!(!chunk.face_has_air(IVec3::Y));
+    }
+
+    #[test]
+    fn metadata_is_allocated_only_when_non_zero() {
+        let mut chunk = Chunk::new(IVec3::ZERO);
+        chunk.set_block_with_metadata(1, 2, 3, BlockId::Stone, 0);
+        assert!(chunk.metadata.is_empty());
+
+        chunk.set_block_with_metadata(1, 2, 3, BlockId::Stone, 5);
+        chunk.set_block_with_metadata(2, 2, 3, BlockId::Water, 3);
+        assert_eq!(chunk.metadata.len(), Chunk::COUNT / 2);
+        assert_eq!((chunk.get_block(1, 2, 3), chunk.get_metadata(1, 2, 3)), (BlockId::Stone, 5));
+        assert_eq!(chunk.get_metadata(2, 2, 3), 3);
+
+        chunk.set_block(1, 2, 3, BlockId::Air);
+        chunk.set_block(2, 2, 3, BlockId::Water);
+        chunk.compact_metadata();
+        assert!(chunk.metadata.is_empty());
+        assert_eq!(chunk.get_metadata(2, 2, 3), 0);
+    }
+}
@@ -1,23 +1,74 @@
 use bevy::prelude::*;
-use std::collections::{HashSet, VecDeque};
-use crate::world::chunk::Chunk;
-use crate::world::storage::ChunkStorage;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use crate::world::chunk::{Chunk, LodLevel};
+use crate::world::storage::{ChunkLifecycle, ChunkStorage};
+use crate::world::entity_persistence::{assign_entity_chunks, spawn_saved_entities, PersistentEntities};
+use crate::world::events::{ChunkLoadedEvent, ChunkUnloadedEvent};
 use crate::world::generator::{WorldGenerator, WorldGeneratorConfig};
+use crate::world::pending_blocks::{apply_pending_blocks, load_pending_blocks, PendingBlockStore};
+use crate::world::persistence;
+use crate::world::chunk_stats::{update_chunk_stats, ChunkStats};
 use crate::block_registry::BlockRegistry;
 use crate::controller::FirstPersonController;
 use bevy::tasks::{AsyncComputeTaskPool, Task, TaskPool, TaskPoolBuilder};
 use futures_lite::future;
-use crate::game_state::GameState;
+use crate::game_state::{GameState, WorldManager};
 use crate::ui::GameSettings;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 区块加载中心：加载器围绕带有此组件的实体加载和卸载区块。
+/// 客户端挂在玩家上，服务器模式下挂在世界出生点上
+#[derive(Component, Default)]
+pub struct ChunkLoadAnchor;
 
 /// 区块加载器配置
 #[derive(Resource)]
 pub struct ChunkLoaderConfig {
     pub max_loaded_chunks: usize,    // 最大同时加载区块数量
     pub surface_priority_quota: usize, // 地表优先区块配额
-    pub sphere_loading_radius: f32,   // 球形加载半径
-    pub max_chunks_per_frame: usize, // 每帧最多处理的区块数量
+    pub horizontal_radius: f32,       // 水平加载半径
+    pub vertical_radius_up: f32,      // 向上加载半径
+    pub vertical_radius_down: f32,    // 向下加载半径
+    pub lod_radius: f32,              // 远景半分辨率区块的水平半径，不大于加载半径时关闭 LOD
+    pub unload_margin: f32,          // 卸载滞后距离：超出加载半径这么多区块后才考虑卸载
+    pub unload_grace_period: f32,    // 区块加载或进入加载半径后，这么多秒内不会被卸载
+    pub memory_chunk_limit: Option<usize>, // 估计内存超出软上限时按内存算出的区块数上限，由 `memory::update_memory_limit` 设置
+}
+
+impl ChunkLoaderConfig {
+    /// 区块偏移到玩家的"加载距离"：垂直分量按上/下半径缩放到水平单位，
+    /// 因此加载范围是一个上下不对称的椭球，距离不超过 `horizontal_radius` 即在范围内
+    pub fn load_distance(&self, offset: IVec3) -> f32 {
+        let vertical_radius = if offset.y >= 0 { self.vertical_radius_up } else { self.vertical_radius_down };
+        let dy = offset.y as f32 * self.horizontal_radius / vertical_radius.max(0.5);
+        Vec3::new(offset.x as f32, dy, offset.z as f32).length()
+    }
+
+    /// 实际使用的最大加载区块数：内存超出软上限时取两者中较小的
+    pub fn chunk_limit(&self) -> usize {
+        self.memory_chunk_limit.map_or(self.max_loaded_chunks, |limit| limit.min(self.max_loaded_chunks))
+    }
+
+    pub fn in_load_range(&self, offset: IVec3) -> bool {
+        self.load_distance(offset) <= self.horizontal_radius
+    }
+
+    /// 地表区块的水平加载范围；开启 LOD 时扩展到 LOD 半径
+    pub fn surface_range(&self) -> f32 {
+        (self.horizontal_radius * 1.2).max(self.lod_radius)
+    }
+
+    /// 该偏移处的区块应使用的细节等级：超出完整细节半径的地表区块使用半分辨率
+    pub fn lod_for(&self, offset: IVec3) -> u8 {
+        let horizontal_distance = Vec2::new(offset.x as f32, offset.z as f32).length();
+        if self.lod_radius > self.horizontal_radius && horizontal_distance > self.horizontal_radius {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 impl Default for ChunkLoaderConfig {
@@ -25,17 +76,90 @@ impl Default for ChunkLoaderConfig {
         Self {
             max_loaded_chunks: 1000,     // 默认最大1000个区块
             surface_priority_quota: 600, // 地表优先配额600个
-            sphere_loading_radius: 12.0, // 球形加载半径12个区块
-            max_chunks_per_frame: 3,     // 每帧最多处理3个区块
+            horizontal_radius: 12.0,     // 水平加载半径12个区块
+            vertical_radius_up: 4.0,     // 向上4个区块
+            vertical_radius_down: 6.0,   // 向下6个区块
+            lod_radius: 0.0,             // 默认关闭远景 LOD
+            unload_margin: 2.0,          // 超出加载半径2个区块才卸载
+            unload_grace_period: 10.0,   // 10秒保护期
+            memory_chunk_limit: None,
         }
     }
 }
 
+/// 视线 ±60° 以内的区块视为可见（cos 60°）
+const VIEW_CONE_COS: f32 = 0.5;
+/// 可见区块的优先级加成，远大于加载半径内的距离差，距离只作为同档位内的次级排序依据
+const VIEW_CONE_BONUS: f32 = 100.0;
+/// 身后区块的优先级惩罚
+const BEHIND_PENALTY: f32 = 50.0;
+/// 视线方向偏转超过约30°时重新排序待加载队列
+const RESORT_ANGLE_COS: f32 = 0.866;
+/// 脚下保护区块的优先级，高于任何距离/视野计算出的优先级
+pub const EMERGENCY_PRIORITY: f32 = 2000.0;
+/// 优先级不低于此值的区块视为紧急区块，不受每帧时间预算限制（紧急区块的 urgency 远小于100）
+const EMERGENCY_PRIORITY_FLOOR: f32 = EMERGENCY_PRIORITY - 100.0;
+/// 每帧启动生成任务数量的自适应范围
+const MIN_TASK_STARTS: usize = 2;
+const MAX_TASK_STARTS: usize = 64;
+/// 玩家持续处于深度地下多少秒后进入激进加载/卸载模式
+const DEEP_UNDERGROUND_SECONDS: f32 = 30.0;
+/// 同一区块卸载后在这么多秒内再次加载，记为一次重复加载（抖动）
+const RELOAD_WINDOW_SECONDS: f32 = 30.0;
+/// 区块水平距离超过加载半径的这个倍数后，尚未生成完的结果视为过期
+const STALE_CHUNK_MARGIN: f32 = 1.5;
+/// 垂直方向额外的容差，覆盖地表搜索范围向上的8层
+const STALE_VERTICAL_SLACK: f32 = 8.0;
+
+/// 根据区块相对玩家的方向计算视野加成
+fn view_direction_bonus(offset: IVec3, forward: Vec3) -> f32 {
+    // 相邻区块和脚下的区块始终视为可见，转身时不能让它们排到后面
+    let directly_below = offset.x == 0 && offset.z == 0 && offset.y <= 0;
+    let offset = offset.as_vec3();
+    let len = offset.length();
+    if len < 2.0 || directly_below {
+        return VIEW_CONE_BONUS;
+    }
+    let cos = offset.dot(forward) / len;
+    if cos >= VIEW_CONE_COS {
+        VIEW_CONE_BONUS
+    } else if cos <= -VIEW_CONE_COS {
+        -BEHIND_PENALTY
+    } else {
+        0.0
+    }
+}
+
+/// 区块是否仍在玩家加载范围附近；玩家快速离开后，排队或生成中的远处区块不再需要
+fn is_chunk_still_relevant(chunk_pos: IVec3, player_chunk_pos: IVec3, config: &ChunkLoaderConfig) -> bool {
+    let offset = chunk_pos - player_chunk_pos;
+    let horizontal_distance = Vec2::new(offset.x as f32, offset.z as f32).length();
+    let vertical_radius = if offset.y >= 0 { config.vertical_radius_up } else { config.vertical_radius_down };
+    horizontal_distance <= config.horizontal_radius.max(config.lod_radius) * STALE_CHUNK_MARGIN
+        && (offset.y as f32).abs() <= vertical_radius * STALE_CHUNK_MARGIN + STALE_VERTICAL_SLACK
+}
+
+fn player_chunk_coord(player_pos: Vec3) -> IVec3 {
+    IVec3::new(
+        (player_pos.x / 32.0).floor() as i32,
+        (player_pos.y / 32.0).floor() as i32,
+        (player_pos.z / 32.0).floor() as i32,
+    )
+}
+
+/// 区块加载优先级：距离越近越高，视线方向内的区块大幅优先
+fn chunk_load_priority(chunk_pos: IVec3, player_chunk_pos: IVec3, forward: Vec3) -> f32 {
+    let offset = chunk_pos - player_chunk_pos;
+    1000.0 - offset.as_vec3().length() + view_direction_bonus(offset, forward)
+}
+
 /// 异步区块生成任务
 #[derive(Component)]
 pub struct ChunkGenerationTask {
     pub task: Task<Chunk>,
     pub position: IVec3,
+    pub emergency: bool,  // 紧急区块（脚下保护），完成处理时不受时间预算限制
+    pub lod: u8,          // 生成的细节等级
 }
 
 /// 异步区块卸载任务
@@ -46,11 +170,103 @@ pub struct ChunkUnloadTask {
     pub entity: Entity,
 }
 
+/// 待加载队列中的一项，按优先级排序（优先级相同时按坐标，保证顺序稳定）
+#[derive(Clone, Copy, Debug)]
+struct QueuedChunk {
+    priority: f32,
+    coord: IVec3,
+}
+
+impl PartialEq for QueuedChunk {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueuedChunk {}
+
+impl PartialOrd for QueuedChunk {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedChunk {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .total_cmp(&other.priority)
+            .then_with(|| other.coord.to_array().cmp(&self.coord.to_array()))
+    }
+}
+
 /// 区块加载队列
+///
+/// 待加载区块保存在最大堆中，`queued` 记录每个坐标当前的优先级，用于去重和重新排序：
+/// 重新设置优先级时只向堆中压入新条目，旧条目在到达堆顶时被丢弃。
 #[derive(Resource, Default)]
 pub struct ChunkLoadQueue {
-    pub pending: VecDeque<IVec3>,  // 待加载的区块位置
+    pending: BinaryHeap<QueuedChunk>,  // 待加载的区块（可能含已过期的条目）
+    queued: HashMap<IVec3, f32>,  // 待加载的区块位置 -> 当前优先级
     pub generating: HashSet<IVec3>,  // 正在生成的区块位置
+    pub sort_forward: Vec3,  // 待加载队列上次排序时的视线方向
+    pub sort_origin: IVec3,  // 待加载队列上次排序时玩家所在的区块
+}
+
+impl ChunkLoadQueue {
+    /// 加入待加载队列；已在队列中的区块只更新优先级。返回是否为新加入的区块
+    pub fn push(&mut self, coord: IVec3, priority: f32) -> bool {
+        let is_new = match self.queued.insert(coord, priority) {
+            None => true,
+            Some(old) if old == priority => return false,
+            Some(_) => false,
+        };
+        self.pending.push(QueuedChunk { priority, coord });
+        self.discard_stale();
+        is_new
+    }
+
+    /// 取出优先级最高的区块
+    pub fn pop(&mut self) -> Option<IVec3> {
+        let next = self.pending.pop()?;
+        self.queued.remove(&next.coord);
+        self.discard_stale();
+        Some(next.coord)
+    }
+
+    pub fn contains(&self, coord: &IVec3) -> bool {
+        self.queued.contains_key(coord)
+    }
+
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+
+    /// 当前最高优先级
+    pub fn highest_priority(&self) -> Option<f32> {
+        self.pending.peek().map(|top| top.priority)
+    }
+
+    /// 用新的优先级函数重新计算整个队列（玩家移动或转身后调用）
+    pub fn reprioritize(&mut self, priority: impl Fn(IVec3) -> f32) {
+        for (coord, value) in self.queued.iter_mut() {
+            *value = priority(*coord);
+        }
+        self.pending = self.queued.iter().map(|(&coord, &priority)| QueuedChunk { priority, coord }).collect();
+    }
+
+    /// 弹出堆顶已被更新或移除的旧条目，保证堆顶总是有效的
+    fn discard_stale(&mut self) {
+        while let Some(top) = self.pending.peek() {
+            if self.queued.get(&top.coord) == Some(&top.priority) {
+                break;
+            }
+            self.pending.pop();
+        }
+    }
 }
 
 /// 区块卸载队列
@@ -58,15 +274,174 @@ pub struct ChunkLoadQueue {
 pub struct ChunkUnloadQueue {
     pub pending: VecDeque<(Entity, IVec3)>,  // 待卸载的区块
     pub unloading: HashSet<IVec3>,  // 正在卸载的区块位置
+    pub already_despawned: usize,   // 卸载完成时区块实体已经不存在的次数，正常情况下应为 0
+}
+
+/// 区块加载历史：用于卸载滞后保护和识别重复加载
+#[derive(Resource, Default)]
+pub struct ChunkLoadHistory {
+    last_relevant: HashMap<IVec3, f32>,  // 区块最近一次加载或处于加载半径内的时间
+    unloaded_at: HashMap<IVec3, f32>,    // 最近卸载的区块及卸载时间
+}
+
+impl ChunkLoadHistory {
+    /// 记录区块加载完成；该区块刚被卸载过（重复加载）时返回 true
+    pub fn record_loaded(&mut self, coord: IVec3, now: f32) -> bool {
+        let reloaded = self.unloaded_at.remove(&coord).is_some_and(|unloaded| now - unloaded <= RELOAD_WINDOW_SECONDS);
+        self.last_relevant.insert(coord, now);
+        reloaded
+    }
+
+    pub fn record_unloaded(&mut self, coord: IVec3, now: f32) {
+        self.last_relevant.remove(&coord);
+        self.unloaded_at.insert(coord, now);
+    }
+
+    pub fn mark_relevant(&mut self, coord: IVec3, now: f32) {
+        self.last_relevant.insert(coord, now);
+    }
+
+    /// 区块是否仍在保护期内
+    pub fn is_protected(&self, coord: IVec3, now: f32, grace_period: f32) -> bool {
+        self.last_relevant.get(&coord).is_some_and(|t| now - t < grace_period)
+    }
+
+    /// 清理已超出统计窗口的卸载记录
+    fn prune(&mut self, now: f32) {
+        self.unloaded_at.retain(|_, t| now - *t <= RELOAD_WINDOW_SECONDS);
+    }
 }
 
-/// 自定义区块生成线程池
+/// 区块流水线的每帧时间预算：完成处理和网格构建共用同一个毫秒预算，
+/// 生成任务的启动数量根据上一帧的实际耗时自适应调整
+#[derive(Resource)]
+pub struct ChunkWorkBudget {
+    pub target_ms: f32,      // 目标预算（毫秒），从 GameSettings 同步
+    pub used_ms: f32,        // 本帧已使用
+    pub last_frame_ms: f32,  // 上一帧实际使用
+    pub start_limit: usize,  // 本帧最多启动的生成任务数
+    pub avg_mesh_ms: f32,    // 单个区块网格构建的平均耗时
+    pub peak_mesh_ms: f32,   // 单个区块网格构建的最长耗时
+    pub meshes_built: usize,   // 网格构建总次数（包括重建）
+    pub mesh_rebuilds: usize,  // 其中替换已有网格的次数
+}
+
+impl Default for ChunkWorkBudget {
+    fn default() -> Self {
+        Self {
+            target_ms: 4.0,
+            used_ms: 0.0,
+            last_frame_ms: 0.0,
+            start_limit: 16,
+            avg_mesh_ms: 1.0,
+            peak_mesh_ms: 0.0,
+            meshes_built: 0,
+            mesh_rebuilds: 0,
+        }
+    }
+}
+
+impl ChunkWorkBudget {
+    /// 新一帧开始：记录上一帧耗时，超出预算时减半任务启动数，余量充足时逐步增加
+    pub fn begin_frame(&mut self, target_ms: f32) {
+        self.target_ms = target_ms;
+        self.last_frame_ms = self.used_ms;
+        self.used_ms = 0.0;
+        if self.last_frame_ms > self.target_ms {
+            self.start_limit = (self.start_limit / 2).max(MIN_TASK_STARTS);
+        } else if self.last_frame_ms < self.target_ms * 0.5 {
+            self.start_limit = (self.start_limit + 2).min(MAX_TASK_STARTS);
+        }
+    }
+
+    pub fn spend(&mut self, ms: f32) {
+        self.used_ms += ms;
+    }
+
+    /// 记录一次网格构建耗时并更新平均值，`rebuild` 表示替换了区块已有的网格
+    pub fn record_mesh(&mut self, ms: f32, rebuild: bool) {
+        self.avg_mesh_ms = self.avg_mesh_ms * 0.9 + ms * 0.1;
+        self.peak_mesh_ms = self.peak_mesh_ms.max(ms);
+        self.meshes_built += 1;
+        if rebuild {
+            self.mesh_rebuilds += 1;
+        }
+        self.spend(ms);
+    }
+
+    pub fn remaining_ms(&self) -> f32 {
+        (self.target_ms - self.used_ms).max(0.0)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.used_ms >= self.target_ms
+    }
+
+    /// 按剩余预算估算本帧还能接收多少个完成的区块（每个区块随后都要构建网格）
+    pub fn affordable_chunks(&self) -> usize {
+        (self.remaining_ms() / self.avg_mesh_ms.max(0.01)) as usize
+    }
+
+    /// 上一帧耗时占预算的比例
+    pub fn usage(&self) -> f32 {
+        self.last_frame_ms / self.target_ms.max(0.01)
+    }
+}
+
+/// 需求分析系统的节流状态：上次更新的时间、玩家所在区块和世界坐标
+#[derive(Resource, Default)]
+pub struct ChunkDemandState {
+    pub last_check: Option<(f32, IVec3, Vec3)>,
+}
+
+/// 卸载检测系统的节流状态：上次检测的时间和玩家世界坐标
+#[derive(Resource, Default)]
+pub struct UnloadDetectionState {
+    pub last_check: Option<(f32, Vec3)>,
+}
+
+/// 深度地下计时器：由需求分析系统维护，卸载检测系统共用同一份计时
+#[derive(Resource, Default)]
+pub struct DeepUndergroundTimer {
+    started_at: Option<f32>,
+}
+
+impl DeepUndergroundTimer {
+    /// 玩家周围都是地下区块时开始或继续计时，否则重置
+    pub fn update(&mut self, all_underground: bool, now: f32) {
+        if !all_underground {
+            self.started_at = None;
+        } else if self.started_at.is_none() {
+            self.started_at = Some(now);
+        }
+    }
+
+    pub fn elapsed(&self, now: f32) -> Option<f32> {
+        self.started_at.map(|start| now - start)
+    }
+
+    /// 是否已经持续处于深度地下足够久，需要进入激进模式
+    pub fn is_long_time(&self, now: f32) -> bool {
+        self.elapsed(now).is_some_and(|elapsed| elapsed >= DEEP_UNDERGROUND_SECONDS)
+    }
+}
+
+/// 区块生成线程池的默认线程数，之后由设置中的线程数取代
+const DEFAULT_GENERATION_THREADS: u32 = 32;
+
+/// 自定义区块生成线程池。插件只在没有预先插入时创建默认线程池，测试可以插入较小的线程池
 #[derive(Resource)]
 pub struct ChunkGenerationThreadPool {
     pub pool: Arc<TaskPool>,
     pub thread_count: u32,
 }
 
+impl Default for ChunkGenerationThreadPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_GENERATION_THREADS)
+    }
+}
+
 impl ChunkGenerationThreadPool {
     pub fn new(thread_count: u32) -> Self {
         let thread_count = thread_count.max(1);
@@ -98,6 +473,36 @@ impl ChunkGenerationThreadPool {
     }
 }
 
+/// 生成任务共享的世界生成器和方块注册表快照。每个任务只克隆 Arc，
+/// 不再为每个区块重新创建噪声和复制注册表
+#[derive(Resource, Clone)]
+pub struct GenerationContext {
+    pub generator: Arc<WorldGenerator>,
+    pub registry: Arc<BlockRegistry>,
+}
+
+impl GenerationContext {
+    pub fn new(config: &WorldGeneratorConfig, registry: &BlockRegistry) -> Self {
+        Self {
+            generator: Arc::new(WorldGenerator::new(config.clone())),
+            registry: Arc::new(registry.clone()),
+        }
+    }
+}
+
+/// 生成器配置（例如进入另一个世界换了种子）或方块定义变化时重新创建生成上下文
+pub fn update_generation_context(
+    mut commands: Commands,
+    context: Option<Res<GenerationContext>>,
+    generator_config: Res<WorldGeneratorConfig>,
+    registry: Res<BlockRegistry>,
+) {
+    if context.is_none() || generator_config.is_changed() || registry.is_changed() {
+        debug!("Rebuilding chunk generation context (seed {})", generator_config.seed);
+        commands.insert_resource(GenerationContext::new(&generator_config, &registry));
+    }
+}
+
 /// 线程池管理系统 - 监控设置变化并更新线程池
 pub fn thread_pool_management_system(
     mut thread_pool: ResMut<ChunkGenerationThreadPool>,
@@ -110,29 +515,58 @@ pub fn thread_pool_management_system(
     }
 }
 
+/// 最大加载区块数调低（例如应用了区块预设）且已加载的区块超过新上限时，清除卸载检测的节流状态，
+/// 本帧立即进行一次卸载检测。在 `chunk_demand_system` 用新设置更新配置之前运行
+pub fn unload_on_limit_decrease(
+    game_settings: Option<Res<GameSettings>>,
+    loader_config: Res<ChunkLoaderConfig>,
+    chunk_storage: Res<ChunkStorage>,
+    mut detection_state: ResMut<UnloadDetectionState>,
+) {
+    let Some(settings) = game_settings else { return };
+    let new_limit = settings.max_loaded_chunks as usize;
+    if new_limit < loader_config.max_loaded_chunks && chunk_storage.chunks.len() > new_limit {
+        info!("Chunk limit lowered from {} to {} with {} chunks loaded, checking for unloads now",
+              loader_config.max_loaded_chunks, new_limit, chunk_storage.chunks.len());
+        detection_state.last_check = None;
+    }
+}
+
+/// 每帧开始时重置区块流水线的时间预算
+pub fn chunk_budget_system(
+    mut budget: ResMut<ChunkWorkBudget>,
+    game_settings: Option<Res<GameSettings>>,
+) {
+    let target_ms = game_settings.map_or(budget.target_ms, |settings| settings.chunk_budget_ms);
+    budget.begin_frame(target_ms);
+}
+
 /// 智能区块需求分析系统 - 基于数量限制的智能加载策略
 pub fn chunk_demand_system(
-    player_query: Query<&Transform, With<FirstPersonController>>,
+    player_query: Query<(&Transform, Option<&FirstPersonController>), With<ChunkLoadAnchor>>,
     mut loader_config: ResMut<ChunkLoaderConfig>,
     game_settings: Option<Res<GameSettings>>,
     mut load_queue: ResMut<ChunkLoadQueue>,
-    chunk_query: Query<&Chunk>,
+    mut demand_state: ResMut<ChunkDemandState>,
+    mut deep_timer: ResMut<DeepUndergroundTimer>,
+    chunk_query: Query<(&Chunk, Option<&LodLevel>)>,
     time: Res<Time>,
 ) {
     // 从游戏设置更新配置
     if let Some(settings) = game_settings {
         loader_config.max_loaded_chunks = settings.max_loaded_chunks as usize;
         loader_config.surface_priority_quota = settings.surface_priority_quota as usize;
-        loader_config.sphere_loading_radius = settings.sphere_loading_radius;
+        loader_config.horizontal_radius = settings.horizontal_radius;
+        loader_config.vertical_radius_up = settings.vertical_radius_up;
+        loader_config.vertical_radius_down = settings.vertical_radius_down;
+        loader_config.lod_radius = settings.lod_radius;
+        loader_config.unload_margin = settings.unload_margin;
+        loader_config.unload_grace_period = settings.unload_grace_period;
     }
-    
-    // 添加静态变量来缓存上次检查的时间和位置，以及深度地下检测
-    static LAST_CHECK: Mutex<Option<(f32, IVec3, Vec3)>> = Mutex::new(None);
-    static DEEP_UNDERGROUND_TIMER: Mutex<Option<f32>> = Mutex::new(None); // 深度地下计时器
-    
+
     // 获取玩家位置
-    let player_transform = match player_query.get_single() {
-        Ok(transform) => transform,
+    let (player_transform, controller) = match player_query.get_single() {
+        Ok(player) => player,
         Err(_) => return,
     };
 
@@ -142,6 +576,18 @@ pub fn chunk_demand_system(
         (player_pos.y / 32.0).floor() as i32,
         (player_pos.z / 32.0).floor() as i32,
     );
+    // 没有视线方向的加载中心（服务器出生点）按默认朝向排序
+    let forward = controller.map_or(Vec3::NEG_Z, |controller| controller.look_direction());
+
+    // 玩家进入新区块或视线方向明显变化时重新计算队列优先级，让转身后面前的区块先生成
+    if load_queue.sort_origin != player_chunk_pos || forward.dot(load_queue.sort_forward) < RESORT_ANGLE_COS {
+        if !load_queue.is_empty() {
+            load_queue.reprioritize(|coord| chunk_load_priority(coord, player_chunk_pos, forward));
+            debug!("Player moved or turned, re-prioritized {} pending chunks", load_queue.len());
+        }
+        load_queue.sort_forward = forward;
+        load_queue.sort_origin = player_chunk_pos;
+    }
 
     // 检查是否需要更新，并检测快速移动
     let current_time = time.elapsed_seconds();
@@ -150,7 +596,8 @@ pub fn chunk_demand_system(
     let mut emergency_load = false;
     let mut player_velocity = Vec3::ZERO; // 初始化玩家速度
     
-    if let Ok(mut last_check) = LAST_CHECK.lock() {
+    {
+        let last_check = &mut demand_state.last_check;
         if let Some((last_time, last_chunk_pos, last_world_pos)) = *last_check {
             let time_delta = current_time - last_time;
             let chunk_moved = last_chunk_pos != player_chunk_pos;
@@ -185,14 +632,18 @@ pub fn chunk_demand_system(
         return;
     }
 
-    // 收集当前已加载的区块
+    // 收集当前已加载的区块；进入完整细节范围的 LOD 区块视为"需要升级"而不是已加载，会被重新加入队列
     let mut loaded_chunks = HashSet::new();
-    for chunk in chunk_query.iter() {
-        loaded_chunks.insert(chunk.coord);
+    let mut current_loaded_count = 0;
+    for (chunk, lod) in chunk_query.iter() {
+        current_loaded_count += 1;
+        let lod = lod.map_or(0, |l| l.0);
+        if lod <= loader_config.lod_for(chunk.coord - player_chunk_pos) {
+            loaded_chunks.insert(chunk.coord);
+        }
     }
 
     // 检查是否达到最大区块数量限制（快速移动时大幅放宽限制）
-    let current_loaded_count = loaded_chunks.len();
     
     // 异步检测算法：简化检测逻辑，减少主线程计算
     let is_near_surface_simple = player_chunk_pos.y >= 0;
@@ -213,39 +664,25 @@ pub fn chunk_demand_system(
     let all_chunks_underground = surrounding_chunks.iter().all(|chunk_pos| chunk_pos.y < 0);
     
     // 深度地下计时器管理
-    let mut is_deep_underground_long_time = false;
-    if let Ok(mut timer) = DEEP_UNDERGROUND_TIMER.lock() {
-        if all_chunks_underground {
-            // 开始或继续计时
-            if timer.is_none() {
-                *timer = Some(current_time);
-            } else if let Some(start_time) = *timer {
-                // 检查是否已经持续30秒
-                if current_time - start_time >= 30.0 {
-                    is_deep_underground_long_time = true;
-                }
-            }
-        } else {
-            // 重置计时器
-            *timer = None;
-        }
-    }
+    deep_timer.update(all_chunks_underground, current_time);
+    let is_deep_underground_long_time = deep_timer.is_long_time(current_time);
     
     // 保守的500区块限制：如果两个检测都不为真，则限制为500个区块
     let conservative_limit = 500;
     let use_conservative_mode = !is_near_surface_simple && !emergency_load && !is_fast_moving;
     
+    let max_loaded_chunks = loader_config.chunk_limit();
     let effective_max = if is_deep_underground_long_time {
         // 深度地下激进模式：只保留最少的必要区块
         50 // 激进模式：只保留50个区块
     } else if use_conservative_mode {
-        conservative_limit.min(loader_config.max_loaded_chunks) // 保守模式：最多500个区块
+        conservative_limit.min(max_loaded_chunks) // 保守模式：最多500个区块
     } else if emergency_load {
-        loader_config.max_loaded_chunks + 200 // 紧急情况下允许超出200个区块
+        max_loaded_chunks + 200 // 紧急情况下允许超出200个区块
     } else if is_fast_moving {
-        loader_config.max_loaded_chunks + 100 // 快速移动时允许超出100个区块
+        max_loaded_chunks + 100 // 快速移动时允许超出100个区块
     } else {
-        loader_config.max_loaded_chunks
+        max_loaded_chunks
     };
     
     if current_loaded_count >= effective_max {
@@ -318,9 +755,9 @@ pub fn chunk_demand_system(
      
      if is_near_surface {
          let surface_radius = if is_fast_moving { 
-             (loader_config.sphere_loading_radius * 1.5) as i32 // 快速移动时扩大范围
+             (loader_config.horizontal_radius * 1.5).max(loader_config.lod_radius) as i32 // 快速移动时扩大范围
          } else { 
-             (loader_config.sphere_loading_radius * 1.2) as i32 // 稍微扩大地表搜索范围
+             loader_config.surface_range() as i32 // 稍微扩大地表搜索范围，开启 LOD 时覆盖远景
          };
          
          // 地表区块主要在玩家Y坐标附近的几个层级
@@ -338,13 +775,15 @@ pub fn chunk_demand_system(
                      let horizontal_distance = (dx * dx + dz * dz).sqrt();
                      
                      // 在地表搜索范围内且未加载
-                     if horizontal_distance <= loader_config.sphere_loading_radius * 1.2 
+                     if horizontal_distance <= loader_config.surface_range() 
                         && !loaded_chunks.contains(&chunk_pos) 
-                        && !load_queue.generating.contains(&chunk_pos) {
+                        && !load_queue.generating.contains(&chunk_pos)
+                        && !load_queue.contains(&chunk_pos) {
                          
-                         // 地表区块优先级：距离越近优先级越高，接近玩家Y坐标的优先级更高
+                         // 地表区块优先级：距离越近优先级越高，接近玩家Y坐标的优先级更高，视线方向内的优先
                          let y_distance = (chunk_pos.y - player_chunk_pos.y).abs() as f32;
-                         let priority = 1000.0 - horizontal_distance - y_distance * 0.5;
+                         let priority = 1000.0 - horizontal_distance - y_distance * 0.5
+                             + view_direction_bonus(chunk_pos - player_chunk_pos, forward);
                          surface_candidates.push((chunk_pos, priority));
                      }
                  }
@@ -359,29 +798,23 @@ pub fn chunk_demand_system(
     let mut sphere_candidates = Vec::new();
     
     if is_near_surface_simple {
-        // 地表模式：使用原有的球形加载算法
-        let sphere_radius = if is_fast_moving { 
-            (loader_config.sphere_loading_radius * 1.2) as i32 
-        } else { 
-            loader_config.sphere_loading_radius as i32
-        };
+        // 地表模式：椭球加载，水平半径与上下半径分开配置
+        let horizontal_radius = loader_config.horizontal_radius.ceil() as i32;
+        let radius_up = loader_config.vertical_radius_up.ceil() as i32;
+        let radius_down = loader_config.vertical_radius_down.ceil() as i32;
         
-        for x in (player_chunk_pos.x - sphere_radius)..=(player_chunk_pos.x + sphere_radius) {
-            for z in (player_chunk_pos.z - sphere_radius)..=(player_chunk_pos.z + sphere_radius) {
-                for y in (player_chunk_pos.y - sphere_radius)..=(player_chunk_pos.y + sphere_radius) {
+        for x in (player_chunk_pos.x - horizontal_radius)..=(player_chunk_pos.x + horizontal_radius) {
+            for z in (player_chunk_pos.z - horizontal_radius)..=(player_chunk_pos.z + horizontal_radius) {
+                for y in (player_chunk_pos.y - radius_down)..=(player_chunk_pos.y + radius_up) {
                     let chunk_pos = IVec3::new(x, y, z);
                     
-                    let dx = (chunk_pos.x - player_chunk_pos.x) as f32;
-                    let dy = (chunk_pos.y - player_chunk_pos.y) as f32;
-                    let dz = (chunk_pos.z - player_chunk_pos.z) as f32;
-                    let distance = (dx * dx + dy * dy + dz * dz).sqrt();
-                    
-                    if distance <= loader_config.sphere_loading_radius 
+                    if loader_config.in_load_range(chunk_pos - player_chunk_pos) 
                        && !loaded_chunks.contains(&chunk_pos) 
                        && !load_queue.generating.contains(&chunk_pos)
+                       && !load_queue.contains(&chunk_pos)
                        && !surface_candidates.iter().any(|(pos, _)| *pos == chunk_pos) {
                         
-                        let priority = 1000.0 - distance;
+                        let priority = chunk_load_priority(chunk_pos, player_chunk_pos, forward);
                         sphere_candidates.push((chunk_pos, priority));
                     }
                 }
@@ -409,7 +842,7 @@ pub fn chunk_demand_system(
               ];
               
               for chunk_pos in essential_chunks {
-                  if !loaded_chunks.contains(&chunk_pos) && !load_queue.generating.contains(&chunk_pos) {
+                  if !loaded_chunks.contains(&chunk_pos) && !load_queue.generating.contains(&chunk_pos) && !load_queue.contains(&chunk_pos) {
                       sphere_candidates.push((chunk_pos, 1000.0)); // 最高优先级
                   }
               }
@@ -431,11 +864,12 @@ pub fn chunk_demand_system(
                           // 地底精确视线检测：只加载最近的区块
                           if distance <= underground_radius as f32
                              && !loaded_chunks.contains(&chunk_pos) 
-                             && !load_queue.generating.contains(&chunk_pos) {
+                             && !load_queue.generating.contains(&chunk_pos)
+                             && !load_queue.contains(&chunk_pos) {
                               
                               // 地底优先级：玩家当前Y层最高优先级
                               let y_penalty = if dy.abs() < 0.1 { 0.0 } else { dy.abs() * 3.0 }; // 当前Y层无惩罚
-                              let priority = 1000.0 - distance - y_penalty;
+                              let priority = chunk_load_priority(chunk_pos, player_chunk_pos, forward) - y_penalty;
                               sphere_candidates.push((chunk_pos, priority));
                           }
                       }
@@ -448,7 +882,7 @@ pub fn chunk_demand_system(
     sphere_candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
     
     // 分配加载配额（地底模式优化）
-    let mut chunks_to_add = Vec::new();
+    let mut chunks_to_add: Vec<(IVec3, f32)> = Vec::new();
     let max_per_frame = if emergency_load { 
         if is_near_surface { 64 } else { 32 } // 地底紧急情况减少加载
     } else if is_fast_moving { 
@@ -461,8 +895,9 @@ pub fn chunk_demand_system(
     // 紧急加载优先
     if emergency_load {
         let emergency_to_add = emergency_chunks.len().min(10).min(remaining_quota); // 最多10个紧急区块
-        for i in 0..emergency_to_add {
-            chunks_to_add.push(emergency_chunks[i].0);
+        for &(chunk_pos, urgency) in &emergency_chunks[..emergency_to_add] {
+            // 紧急区块即使已在队列中也要提升到最前
+            chunks_to_add.push((chunk_pos, EMERGENCY_PRIORITY - urgency));
             remaining_quota -= 1;
         }
     }
@@ -471,27 +906,27 @@ pub fn chunk_demand_system(
     let surface_quota = loader_config.surface_priority_quota.min(remaining_quota);
     let surface_to_add = surface_candidates.len().min(surface_quota);
     
-    for i in 0..surface_to_add {
-        if !chunks_to_add.contains(&surface_candidates[i].0) {
-            chunks_to_add.push(surface_candidates[i].0);
+    for &(chunk_pos, priority) in &surface_candidates[..surface_to_add] {
+        if !chunks_to_add.iter().any(|(pos, _)| *pos == chunk_pos) {
+            chunks_to_add.push((chunk_pos, priority));
             remaining_quota -= 1;
         }
     }
     
     // 然后分配剩余配额给球形区块
     let sphere_to_add = sphere_candidates.len().min(remaining_quota);
-    for i in 0..sphere_to_add {
-        if !chunks_to_add.contains(&sphere_candidates[i].0) {
-            chunks_to_add.push(sphere_candidates[i].0);
+    for &(chunk_pos, priority) in &sphere_candidates[..sphere_to_add] {
+        if !chunks_to_add.iter().any(|(pos, _)| *pos == chunk_pos) {
+            chunks_to_add.push((chunk_pos, priority));
         }
     }
     
-    // 记录添加的数量
-    let added_count = chunks_to_add.len();
-    
-    // 添加到加载队列
-    for chunk_pos in chunks_to_add {
-        load_queue.pending.push_back(chunk_pos);
+    // 添加到加载队列（已在队列中的只更新优先级），记录新加入的数量
+    let mut added_count = 0;
+    for (chunk_pos, priority) in chunks_to_add {
+        if load_queue.push(chunk_pos, priority) {
+            added_count += 1;
+        }
     }
     
     // 输出调试信息
@@ -518,13 +953,8 @@ pub fn chunk_demand_system(
          }
          
          // 显示深度地下计时器状态
-         if all_chunks_underground {
-             if let Ok(timer) = DEEP_UNDERGROUND_TIMER.lock() {
-                 if let Some(start_time) = *timer {
-                     let elapsed = current_time - start_time;
-                     info!("Deep underground timer: {:.1}s / 30.0s", elapsed);
-                 }
-             }
+         if let Some(elapsed) = deep_timer.elapsed(current_time) {
+             info!("Deep underground timer: {:.1}s / {:.1}s", elapsed, DEEP_UNDERGROUND_SECONDS);
          }
      }
 }
@@ -532,34 +962,69 @@ pub fn chunk_demand_system(
 /// 异步区块生成系统 - 启动异步生成任务（多线程）
 pub fn chunk_generation_system(
     mut commands: Commands,
+    player_query: Query<&Transform, With<ChunkLoadAnchor>>,
     mut load_queue: ResMut<ChunkLoadQueue>,
     loader_config: Res<ChunkLoaderConfig>,
-    generator_config: Res<WorldGeneratorConfig>,
-    registry: Res<BlockRegistry>,
+    budget: Res<ChunkWorkBudget>,
+    context: Option<Res<GenerationContext>>,
     thread_pool: Res<ChunkGenerationThreadPool>,
+    world_manager: Res<WorldManager>,
+    stats: Res<ChunkStats>,
+    pending_blocks: Res<PendingBlockStore>,
 ) {
+    // 生成上下文由链中前面的 update_generation_context 插入
+    let Some(context) = context else { return };
     let mut chunks_started = 0;
+    let world_dir = world_manager.current_world_dir();
+    let mut skipped = 0;
+    let player_chunk_pos = player_query.get_single().ok().map(|t| player_chunk_coord(t.translation));
 
-    // 保守的任务启动策略，避免启动过多任务导致性能问题
-    // 无论线程数多少，每帧最多启动16个新任务
-    let max_tasks_per_frame = 16;
+    // 每帧启动的任务数由时间预算根据上一帧耗时决定；紧急区块不受限制
+    loop {
+        let emergency = load_queue.highest_priority().is_some_and(|p| p >= EMERGENCY_PRIORITY_FLOOR);
+        if chunks_started >= budget.start_limit && !emergency {
+            break;
+        }
+        if let Some(chunk_pos) = load_queue.pop() {
+            // 排队期间玩家已经远离的区块直接跳过，之后回来时会被需求系统重新加入
+            if let Some(player_chunk_pos) = player_chunk_pos {
+                if !is_chunk_still_relevant(chunk_pos, player_chunk_pos, &loader_config) {
+                    skipped += 1;
+                    continue;
+                }
+            }
 
-    // 每帧最多启动指定数量的生成任务
-    while chunks_started < max_tasks_per_frame {
-        if let Some(chunk_pos) = load_queue.pending.pop_front() {
             // 标记为正在生成
             load_queue.generating.insert(chunk_pos);
+            let lod = player_chunk_pos.map_or(0, |p| loader_config.lod_for(chunk_pos - p));
 
             // 克隆必要的数据用于异步任务
-            let config = generator_config.clone();
-            let registry_clone = registry.clone();
+            let context = context.clone();
+            let world_dir = world_dir.clone();
+            let counters = stats.counters().clone();
+            let pending_blocks = pending_blocks.clone();
 
-            // 使用自定义线程池启动异步生成任务
+            // 使用自定义线程池启动异步生成任务；存档中有玩家修改过的区块时直接读取
             let task = thread_pool.pool.spawn(async move {
-                let generator = WorldGenerator::new(config);
-                let mut chunk = Chunk::new(chunk_pos);
-                generator.generate_chunk(&mut chunk, &registry_clone);
+                let started = Instant::now();
+                let saved = persistence::read_chunk(&world_dir, chunk_pos).unwrap_or_else(|e| {
+                    warn!("{}, regenerating it", e);
+                    counters.record_unreadable();
+                    None
+                });
+                let from_save = saved.is_some();
+                let mut chunk = saved.unwrap_or_else(|| {
+                    let mut chunk = Chunk::new(chunk_pos);
+                    // 结构中落在其他区块的方块记下来，目标区块加载后由 apply_pending_blocks 写入
+                    let outside = context.generator.generate_chunk(&mut chunk, &context.registry);
+                    pending_blocks.lock().record(chunk_pos, outside);
+                    chunk
+                });
+                if lod > 0 {
+                    chunk.downsample_lod();
+                }
                 chunk.compute_solid_blocks();
+                counters.record_generation(started.elapsed(), from_save);
                 chunk
             });
 
@@ -567,6 +1032,8 @@ pub fn chunk_generation_system(
             commands.spawn(ChunkGenerationTask {
                 task,
                 position: chunk_pos,
+                emergency,
+                lod,
             });
 
             chunks_started += 1;
@@ -574,56 +1041,100 @@ pub fn chunk_generation_system(
             break;
         }
     }
+
+    if skipped > 0 {
+        let total = stats.counters().record_discarded(skipped as u64);
+        info!("Skipped {} queued chunks out of range (total stale discarded: {})", skipped, total);
+    }
 }
 
 /// 区块完成处理系统 - 处理完成的异步生成任务（主线程优化）
 pub fn chunk_completion_system(
     mut commands: Commands,
     mut task_query: Query<(Entity, &mut ChunkGenerationTask)>,
+    player_query: Query<&Transform, With<ChunkLoadAnchor>>,
+    loader_config: Res<ChunkLoaderConfig>,
     chunk_storage: Res<ChunkStorage>,
     mut load_queue: ResMut<ChunkLoadQueue>,
+    mut history: ResMut<ChunkLoadHistory>,
+    mut budget: ResMut<ChunkWorkBudget>,
+    mut loaded_events: EventWriter<ChunkLoadedEvent>,
     thread_pool: Res<ChunkGenerationThreadPool>,
+    time: Res<Time>,
+    stats: Res<ChunkStats>,
 ) {
+    let started = Instant::now();
     let mut completed_tasks = Vec::new();
+    let mut discarded = 0;
+    let player_chunk_pos = player_query.get_single().ok().map(|t| player_chunk_coord(t.translation));
     
-    // 保守的任务处理策略，避免主线程卡顿
-    // 无论线程数多少，每帧最多处理8个完成的任务
-    let max_tasks_per_frame = 8;
+    // 按剩余时间预算决定本帧接收多少个完成的区块，至少一个以保证进度；紧急区块不受限制
+    let max_tasks_per_frame = budget.affordable_chunks().max(1);
     let mut processed_count = 0;
     
     for (entity, mut task) in task_query.iter_mut() {
-        if processed_count >= max_tasks_per_frame {
-            break;
+        // 玩家已经远离的区块：销毁任务实体会丢弃 Task，未完成的任务随之取消，已完成的结果不再生成实体
+        if let Some(player_chunk_pos) = player_chunk_pos {
+            if !is_chunk_still_relevant(task.position, player_chunk_pos, &loader_config) {
+                load_queue.generating.remove(&task.position);
+                commands.entity(entity).despawn();
+                discarded += 1;
+                continue;
+            }
+        }
+
+        if processed_count >= max_tasks_per_frame && !task.emergency {
+            continue;
         }
         
         // 使用真正的非阻塞轮询，避免主线程卡顿
         if let Some(chunk) = future::block_on(future::poll_once(&mut task.task)) {
-            completed_tasks.push((entity, task.position, chunk));
+            completed_tasks.push((entity, task.position, task.lod, chunk));
             processed_count += 1;
         }
     }
     
     // 处理完成的任务
-    for (entity, chunk_pos, chunk) in completed_tasks {
+    for (entity, chunk_pos, lod, chunk) in completed_tasks {
+        let saved_entities = chunk.entities().to_vec();
         let chunk_world_pos = Vec3::new(
             chunk_pos.x as f32 * 32.0,
             chunk_pos.y as f32 * 32.0,
             chunk_pos.z as f32 * 32.0,
         );
 
-        // 生成区块实体
-        let chunk_entity = commands
-            .spawn((
-                chunk,
-                SpatialBundle {
-                    transform: Transform::from_translation(chunk_world_pos),
-                    ..default()
-                },
-            ))
-            .id();
+        // LOD 区块升级：替换原实体上的区块数据，网格系统会因 dirty 标志重新构建网格。
+        // 原区块已经在卸载时丢弃结果，卸载完成后需求系统会重新加入队列
+        let existing = chunk_storage.get(&chunk_pos).filter(|e| commands.get_entity(*e).is_some());
+        let chunk_entity = if let Some(existing) = existing {
+            if chunk_storage.state(&chunk_pos) != Some(ChunkLifecycle::Active) {
+                stats.counters().record_discarded(1);
+                load_queue.generating.remove(&chunk_pos);
+                commands.entity(entity).despawn();
+                continue;
+            }
+            commands.entity(existing).insert((chunk, LodLevel(lod)));
+            existing
+        } else {
+            // 生成区块实体，延迟命令应用后由 activate_loaded_chunks 切换为 Active
+            let spawned = commands
+                .spawn((
+                    chunk,
+                    LodLevel(lod),
+                    SpatialBundle {
+                        transform: Transform::from_translation(chunk_world_pos),
+                        ..default()
+                    },
+                ))
+                .id();
+            chunk_storage.insert_loading(chunk_pos, spawned);
+            spawned
+        };
 
-        // 存储到区块存储中
-        chunk_storage.insert(chunk_pos, chunk_entity);
+        // 存档中的实体与区块在同一次延迟命令应用时出现
+        spawn_saved_entities(&mut commands, &saved_entities, lod);
+        stats.counters().record_completed(history.record_loaded(chunk_pos, time.elapsed_seconds()));
+        loaded_events.send(ChunkLoadedEvent { coord: chunk_pos, entity: chunk_entity });
 
         // 从生成中移除
         load_queue.generating.remove(&chunk_pos);
@@ -631,19 +1142,27 @@ pub fn chunk_completion_system(
         // 移除任务实体
         commands.entity(entity).despawn();
     }
+
+    budget.spend(started.elapsed().as_secs_f32() * 1000.0);
+
+    if discarded > 0 {
+        let total = stats.counters().record_discarded(discarded as u64);
+        info!("Discarded {} out-of-range chunk generation tasks (total stale discarded: {})", discarded, total);
+    }
 }
 
 /// 积极区块卸载检测系统 - 基于数量限制的智能卸载策略
 pub fn chunk_unload_detection_system(
-    player_query: Query<&Transform, With<FirstPersonController>>,
+    player_query: Query<&Transform, With<ChunkLoadAnchor>>,
     loader_config: Res<ChunkLoaderConfig>,
     chunk_query: Query<(Entity, &Chunk)>,
+    chunk_storage: Res<ChunkStorage>,
     mut unload_queue: ResMut<ChunkUnloadQueue>,
+    mut detection_state: ResMut<UnloadDetectionState>,
+    mut history: ResMut<ChunkLoadHistory>,
+    deep_timer: Res<DeepUndergroundTimer>,
     time: Res<Time>,
 ) {
-    // 添加静态变量来缓存上次检查的时间和位置
-    static LAST_CHECK: Mutex<Option<(f32, Vec3)>> = Mutex::new(None);
-    
     // 获取玩家位置
     let player_transform = match player_query.get_single() {
         Ok(transform) => transform,
@@ -662,7 +1181,8 @@ pub fn chunk_unload_detection_system(
     let mut should_update = false;
     let mut is_fast_moving = false;
     
-    if let Ok(mut last_check) = LAST_CHECK.lock() {
+    {
+        let last_check = &mut detection_state.last_check;
         if let Some((last_time, last_world_pos)) = *last_check {
             let time_delta = current_time - last_time;
             
@@ -693,11 +1213,10 @@ pub fn chunk_unload_detection_system(
     // 收集所有已加载的区块信息
     let mut loaded_chunks = Vec::new();
     for (entity, chunk) in chunk_query.iter() {
-        // 计算区块到玩家的距离
+        // 计算区块到玩家的加载距离（与需求系统相同的椭球形状）
         let dx = (chunk.coord.x - player_chunk_pos.x) as f32;
-        let dy = (chunk.coord.y - player_chunk_pos.y) as f32;
         let dz = (chunk.coord.z - player_chunk_pos.z) as f32;
-        let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+        let distance = loader_config.load_distance(chunk.coord - player_chunk_pos);
         
         // 计算水平距离（用于地表优先级判断）
         let horizontal_distance = (dx * dx + dz * dz).sqrt();
@@ -706,40 +1225,30 @@ pub fn chunk_unload_detection_system(
         let is_surface = chunk.coord.y >= player_chunk_pos.y - 2 && 
                         chunk.coord.y <= player_chunk_pos.y + 8;
         
+        // 仍在加载范围内的区块刷新保护时间（与需求系统的地表/椭球加载范围一致）
+        let in_load_range = if is_surface {
+            horizontal_distance <= loader_config.surface_range()
+        } else {
+            distance <= loader_config.horizontal_radius
+        };
+        if in_load_range {
+            history.mark_relevant(chunk.coord, current_time);
+        }
+        
         loaded_chunks.push((entity, chunk.coord, distance, horizontal_distance, is_surface));
     }
+    history.prune(current_time);
 
     let current_loaded_count = loaded_chunks.len();
     
     // 获取玩家是否在地底的信息（调整检测条件）
     let is_underground = player_chunk_pos.y < 0;
     
-    // 检查是否处于深度地下激进模式
-    static DEEP_UNDERGROUND_TIMER: Mutex<Option<f32>> = Mutex::new(None);
-    
-    let surrounding_chunks = vec![
-        IVec3::new(player_chunk_pos.x + 1, player_chunk_pos.y, player_chunk_pos.z),
-        IVec3::new(player_chunk_pos.x - 1, player_chunk_pos.y, player_chunk_pos.z),
-        IVec3::new(player_chunk_pos.x, player_chunk_pos.y, player_chunk_pos.z + 1),
-        IVec3::new(player_chunk_pos.x, player_chunk_pos.y, player_chunk_pos.z - 1),
-        IVec3::new(player_chunk_pos.x + 1, player_chunk_pos.y, player_chunk_pos.z + 1),
-        IVec3::new(player_chunk_pos.x + 1, player_chunk_pos.y, player_chunk_pos.z - 1),
-        IVec3::new(player_chunk_pos.x - 1, player_chunk_pos.y, player_chunk_pos.z + 1),
-        IVec3::new(player_chunk_pos.x - 1, player_chunk_pos.y, player_chunk_pos.z - 1),
-    ];
-    
-    let all_chunks_underground = surrounding_chunks.iter().all(|chunk_pos| chunk_pos.y < 0);
-    
-    let mut is_deep_underground_long_time = false;
-    if let Ok(timer) = DEEP_UNDERGROUND_TIMER.lock() {
-        if let Some(start_time) = *timer {
-            if current_time - start_time >= 30.0 {
-                is_deep_underground_long_time = true;
-            }
-        }
-    }
+    // 检查是否处于深度地下激进模式（计时由需求分析系统维护）
+    let is_deep_underground_long_time = deep_timer.is_long_time(current_time);
     
-    // 智能卸载策略：根据移动状态和地底状态调整卸载阈值
+    // 智能卸载策略：根据移动状态和地底状态调整卸载阈值；估计内存超出软上限时按内存限制计算
+    let max_loaded_chunks = loader_config.chunk_limit();
     let unload_threshold = if is_deep_underground_long_time {
         // 深度地下激进模式：立即开始激进卸载
         60 // 只保留60个区块
@@ -747,17 +1256,17 @@ pub fn chunk_unload_detection_system(
         // 地底模式更保守，因为加载的区块更少
         if is_fast_moving {
             // 地底快速移动时几乎不卸载
-            loader_config.max_loaded_chunks + 200 // 允许超出200个区块才开始卸载
+            max_loaded_chunks + 200 // 允许超出200个区块才开始卸载
         } else {
             // 地底正常移动时也很保守
-            loader_config.max_loaded_chunks + 100 // 允许超出100个区块才开始卸载
+            max_loaded_chunks + 100 // 允许超出100个区块才开始卸载
         }
     } else if is_fast_moving {
         // 地表快速移动时极其保守
-        loader_config.max_loaded_chunks + 150 // 允许超出150个区块才开始卸载
+        max_loaded_chunks + 150 // 允许超出150个区块才开始卸载
     } else {
         // 地表正常移动时预防性卸载
-        loader_config.max_loaded_chunks * 9 / 10
+        max_loaded_chunks * 9 / 10
     };
      
      let should_unload = current_loaded_count >= unload_threshold;
@@ -770,9 +1279,9 @@ pub fn chunk_unload_detection_system(
     let target_unload_count = if is_fast_moving {
         // 快速移动时只卸载极少量区块
         (current_loaded_count / 200).max(1) // 每次只卸载0.5%或至少1个
-    } else if current_loaded_count >= loader_config.max_loaded_chunks {
+    } else if current_loaded_count >= max_loaded_chunks {
         // 超过限制，卸载到90%
-        current_loaded_count - (loader_config.max_loaded_chunks * 9 / 10)
+        current_loaded_count - (max_loaded_chunks * 9 / 10)
     } else {
         // 预防性卸载，卸载少量区块
         (current_loaded_count / 20).max(1) // 卸载5%或至少1个
@@ -794,7 +1303,7 @@ pub fn chunk_unload_detection_system(
                 // 同类型区块按距离排序
                 if is_surface_a && is_surface_b {
                     // 地表区块：超出地表优先范围的优先卸载
-                    let surface_range = loader_config.sphere_loading_radius * 1.2;
+                    let surface_range = loader_config.surface_range();
                     let out_of_surface_a = h_dist_a > surface_range;
                     let out_of_surface_b = h_dist_b > surface_range;
                     
@@ -813,7 +1322,7 @@ pub fn chunk_unload_detection_system(
 
     // 添加到卸载队列
     let mut unloaded_count = 0;
-    for (entity, coord, distance, _, _is_surface) in loaded_chunks.iter() {
+    for (entity, coord, distance, horizontal_distance, is_surface) in loaded_chunks.iter() {
         if unloaded_count >= target_unload_count {
             break;
         }
@@ -846,6 +1355,16 @@ pub fn chunk_unload_detection_system(
                 continue;
             }
             
+            // 滞后保护：只卸载超出加载范围 + 滞后距离的区块，且不在保护期内
+            let beyond_margin = if *is_surface {
+                *horizontal_distance > loader_config.surface_range() + loader_config.unload_margin
+            } else {
+                *distance > loader_config.horizontal_radius + loader_config.unload_margin
+            };
+            if !beyond_margin || history.is_protected(*coord, current_time, loader_config.unload_grace_period) {
+                continue;
+            }
+            
             // 快速移动时大幅扩大保护范围
             let protection_radius = if is_fast_moving { 6 } else { 2 }; // 快速移动时扩大保护范围
             let dx = (coord.x - player_chunk_pos.x).abs();
@@ -856,7 +1375,8 @@ pub fn chunk_unload_detection_system(
             }
         }
         
-        if !unload_queue.pending.iter().any(|(e, _)| *e == *entity) {
+        // 只有没有被后台任务引用的 Active 区块能加入队列，已在等待或正在卸载的区块不会被销毁两次
+        if chunk_storage.begin_unload(coord, *entity) {
             unload_queue.pending.push_back((*entity, *coord));
             unloaded_count += 1;
         }
@@ -874,6 +1394,10 @@ pub fn chunk_unload_system(
     mut commands: Commands,
     mut unload_queue: ResMut<ChunkUnloadQueue>,
     thread_pool: Res<ChunkGenerationThreadPool>,
+    mut chunk_query: Query<&mut Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+    world_manager: Res<WorldManager>,
+    persistent_entities: PersistentEntities,
 ) {
     let mut chunks_started = 0;
     let max_unload_tasks_per_frame = 5; // 每帧最多启动5个卸载任务
@@ -881,13 +1405,26 @@ pub fn chunk_unload_system(
     // 启动异步卸载任务
     while chunks_started < max_unload_tasks_per_frame {
         if let Some((entity, chunk_pos)) = unload_queue.pending.pop_front() {
-            // 标记为正在卸载
+            // 标记为正在卸载，之后的修改不再保存；区块已不在等待卸载状态时跳过
+            if !chunk_storage.start_unloading(&chunk_pos, entity) {
+                continue;
+            }
             unload_queue.unloading.insert(chunk_pos);
 
-            // 创建异步卸载任务（在后台线程中执行清理工作）
+            // 区块内的持久实体写入区块后随区块销毁
+            if let Ok(mut chunk) = chunk_query.get_mut(entity) {
+                persistent_entities.store_unloading(entity, &mut chunk, &mut commands);
+            }
+
+            // 创建异步卸载任务，玩家修改过的区块在后台线程中写入存档
+            let modified = chunk_query.get(entity).ok().filter(|chunk| chunk.modified).cloned();
+            let world_dir = world_manager.current_world_dir();
             let task = thread_pool.pool.spawn(async move {
-                // 在这里可以执行一些清理工作，比如保存区块数据等
-                // 使用异步延时而不是阻塞延时
+                if let Some(chunk) = modified {
+                    if let Err(e) = persistence::save_chunk(&world_dir, &chunk) {
+                        error!("{}", e);
+                    }
+                }
                 futures_lite::future::yield_now().await;
             });
 
@@ -912,11 +1449,17 @@ pub fn chunk_unload_completion_system(
     chunk_query: Query<Entity, With<Chunk>>, // 添加区块查询以验证实体存在
     chunk_storage: Res<ChunkStorage>,
     mut unload_queue: ResMut<ChunkUnloadQueue>,
+    mut history: ResMut<ChunkLoadHistory>,
+    mut unloaded_events: EventWriter<ChunkUnloadedEvent>,
+    time: Res<Time>,
 ) {
     let mut completed_tasks = Vec::new();
     
     for (task_entity, mut unload_task) in task_query.iter_mut() {
-        // 检查任务是否完成
+        // 仍被后台任务引用的区块等引用释放后再销毁；任务完成后不能再轮询，所以先检查引用
+        if chunk_storage.is_retained(&unload_task.position) {
+            continue;
+        }
         if let Some(_) = future::block_on(future::poll_once(&mut unload_task.task)) {
             completed_tasks.push((task_entity, unload_task.entity, unload_task.position));
         }
@@ -934,10 +1477,13 @@ pub fn chunk_unload_completion_system(
         } else {
             // 实体已经不存在，只需要清理相关数据
             warn!("Chunk entity {:?} at {:?} was already despawned", chunk_entity, chunk_pos);
+            unload_queue.already_despawned += 1;
         }
         
         // 从存储中移除
-        chunk_storage.remove(&chunk_pos);
+        chunk_storage.remove_unloaded(&chunk_pos, chunk_entity);
+        history.record_unloaded(chunk_pos, time.elapsed_seconds());
+        unloaded_events.send(ChunkUnloadedEvent { coord: chunk_pos });
         
         // 从卸载中移除
         unload_queue.unloading.remove(&chunk_pos);
@@ -949,23 +1495,282 @@ pub fn chunk_unload_completion_system(
     }
 }
 
+/// 本帧生成的区块实体已经存在，切换为 Active，之后的网格构建和碰撞可以使用
+fn activate_loaded_chunks(chunk_storage: Res<ChunkStorage>, added: Query<(Entity, &Chunk), Added<Chunk>>) {
+    for (entity, chunk) in added.iter() {
+        chunk_storage.activate(&chunk.coord, entity);
+    }
+}
+
+/// 区块加载流水线的系统集合。集合末尾会应用所有延迟命令，
+/// 排在该集合之后的系统能看到本帧新生成/销毁的区块实体
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChunkLoaderSet;
+
 /// 区块加载器插件
 pub struct ChunkLoaderPlugin;
 
 impl Plugin for ChunkLoaderPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ChunkLoaderConfig::default())
-           .insert_resource(ChunkLoadQueue::default())
-           .insert_resource(ChunkUnloadQueue::default())
-           .insert_resource(ChunkGenerationThreadPool::new(32)) // 默认32个线程
+        // 配置和线程池使用 init_resource，已经插入的（例如测试中较小的加载范围）不会被覆盖
+        app.init_resource::<ChunkLoaderConfig>()
+           .init_resource::<ChunkLoadQueue>()
+           .init_resource::<ChunkUnloadQueue>()
+           .init_resource::<ChunkDemandState>()
+           .init_resource::<UnloadDetectionState>()
+           .init_resource::<DeepUndergroundTimer>()
+           .init_resource::<ChunkLoadHistory>()
+           .init_resource::<ChunkWorkBudget>()
+           .init_resource::<ChunkGenerationThreadPool>()
+           .init_resource::<ChunkStats>()
+           .init_resource::<PendingBlockStore>()
+           .add_systems(OnEnter(GameState::Loading), load_pending_blocks)
            .add_systems(Update, (
+               chunk_budget_system,
                thread_pool_management_system,
+               unload_on_limit_decrease,
                chunk_demand_system,
+               update_generation_context,
+               apply_deferred,
                chunk_generation_system,
                chunk_completion_system,
                chunk_unload_detection_system,
+               assign_entity_chunks,
                chunk_unload_system,
                chunk_unload_completion_system,
-           ).chain().run_if(in_state(GameState::InGame))); // 使用 chain() 确保系统按顺序执行
+               apply_deferred,
+               activate_loaded_chunks,
+               apply_pending_blocks,
+           ).chain().in_set(ChunkLoaderSet).run_if(in_state(GameState::InGame).or_else(in_state(GameState::Loading)))) // 使用 chain() 确保系统按顺序执行
+           .add_systems(Update, update_chunk_stats.after(ChunkLoaderSet).run_if(in_state(GameState::InGame).or_else(in_state(GameState::Loading))))
+           .add_systems(OnExit(GameState::InGame), reset_chunk_loader_state);
+    }
+}
+
+/// 离开游戏状态时清空节流缓存和深度地下计时，回到游戏后重新开始计算
+fn reset_chunk_loader_state(
+    mut demand_state: ResMut<ChunkDemandState>,
+    mut detection_state: ResMut<UnloadDetectionState>,
+    mut deep_timer: ResMut<DeepUndergroundTimer>,
+) {
+    *demand_state = ChunkDemandState::default();
+    *detection_state = UnloadDetectionState::default();
+    *deep_timer = DeepUndergroundTimer::default();
+}
+
+/// 区块加载/卸载完整流程的无窗口测试：用 `MinimalPlugins` 和 `ChunkLoaderPlugin` 搭建应用，
+/// 由测试直接移动玩家，逐帧运行并检查加载器的不变量
+#[cfg(test)]
+mod lifecycle_tests;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// 只包含需求分析系统及其资源的最小世界，玩家位置和时间由测试直接控制
+    fn demand_world(player_pos: Vec3) -> (World, Schedule, Entity) {
+        let mut world = World::new();
+        world.insert_resource(ChunkLoaderConfig::default());
+        world.init_resource::<ChunkLoadQueue>();
+        world.init_resource::<ChunkDemandState>();
+        world.init_resource::<DeepUndergroundTimer>();
+        world.init_resource::<Time>();
+        let player = world
+            .spawn((Transform::from_translation(player_pos), FirstPersonController::default(), ChunkLoadAnchor))
+            .id();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(chunk_demand_system);
+        (world, schedule, player)
+    }
+
+    fn advance(world: &mut World, seconds: f32) {
+        world.resource_mut::<Time>().advance_by(Duration::from_secs_f32(seconds));
+    }
+
+    fn move_player(world: &mut World, player: Entity, pos: Vec3) {
+        world.get_mut::<Transform>(player).unwrap().translation = pos;
+    }
+
+    #[test]
+    fn load_queue_deduplicates_coordinates() {
+        let mut queue = ChunkLoadQueue::default();
+        assert!(queue.push(IVec3::new(1, 0, 0), 10.0));
+        assert!(!queue.push(IVec3::new(1, 0, 0), 10.0));
+        assert!(!queue.push(IVec3::new(1, 0, 0), 20.0));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.highest_priority(), Some(20.0));
+        assert_eq!(queue.pop(), Some(IVec3::new(1, 0, 0)));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn load_queue_pops_highest_priority_first() {
+        let mut queue = ChunkLoadQueue::default();
+        queue.push(IVec3::new(0, 0, 1), 5.0);
+        queue.push(IVec3::new(0, 0, 2), 50.0);
+        queue.push(IVec3::new(0, 0, 3), 20.0);
+        // 降低已在队列中区块的优先级
+        queue.push(IVec3::new(0, 0, 2), 1.0);
+        let order: Vec<IVec3> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(order, vec![IVec3::new(0, 0, 3), IVec3::new(0, 0, 1), IVec3::new(0, 0, 2)]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn load_queue_reprioritize_reorders_pending() {
+        let mut queue = ChunkLoadQueue::default();
+        for x in 0..5 {
+            queue.push(IVec3::new(x, 0, 0), x as f32);
+        }
+        queue.reprioritize(|coord| -coord.x as f32);
+        assert_eq!(queue.len(), 5);
+        assert_eq!(queue.highest_priority(), Some(0.0));
+        let order: Vec<i32> = std::iter::from_fn(|| queue.pop()).map(|c| c.x).collect();
+        assert_eq!(order, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn chunks_in_view_outrank_closer_chunks_behind() {
+        let player = IVec3::ZERO;
+        let ahead = chunk_load_priority(IVec3::new(0, 0, -8), player, Vec3::NEG_Z);
+        let behind = chunk_load_priority(IVec3::new(0, 0, 3), player, Vec3::NEG_Z);
+        let below = chunk_load_priority(IVec3::new(0, -3, 0), player, Vec3::NEG_Z);
+        assert!(ahead > behind);
+        assert!(below > ahead);
+    }
+
+    #[test]
+    fn demand_system_queues_nearby_chunks_on_first_run() {
+        let (mut world, mut schedule, _) = demand_world(Vec3::new(16.0, 40.0, 16.0));
+        schedule.run(&mut world);
+
+        let radius = world.resource::<ChunkLoaderConfig>().horizontal_radius;
+        let mut queue = world.resource_mut::<ChunkLoadQueue>();
+        // 地表正常移动时每次最多加入16个区块
+        assert_eq!(queue.len(), 16);
+        let player_chunk = IVec3::new(0, 1, 0);
+        while let Some(coord) = queue.pop() {
+            let offset = (coord - player_chunk).as_vec3();
+            assert!(Vec2::new(offset.x, offset.z).length() <= radius * 1.2, "{:?} is too far", coord);
+        }
+    }
+
+    #[test]
+    fn demand_system_throttles_until_interval_or_chunk_change() {
+        let (mut world, mut schedule, player) = demand_world(Vec3::new(31.5, 40.0, 16.0));
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<ChunkLoadQueue>().len(), 16);
+
+        // 同一区块内、间隔不足0.5秒：不更新
+        advance(&mut world, 0.1);
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<ChunkLoadQueue>().len(), 16);
+
+        // 慢速跨入相邻区块：立即更新
+        advance(&mut world, 0.1);
+        move_player(&mut world, player, Vec3::new(32.5, 40.0, 16.0));
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<ChunkLoadQueue>().len(), 32);
+        assert_eq!(world.resource::<ChunkDemandState>().last_check.map(|c| c.1), Some(IVec3::new(1, 1, 0)));
+
+        // 原地等待超过间隔：再次更新
+        advance(&mut world, 0.6);
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<ChunkLoadQueue>().len(), 48);
+    }
+
+    #[test]
+    fn deep_underground_timer_runs_while_underground_and_resets_at_surface() {
+        let (mut world, mut schedule, player) = demand_world(Vec3::new(16.0, -3200.0, 16.0));
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<DeepUndergroundTimer>().elapsed(0.0), Some(0.0));
+
+        advance(&mut world, DEEP_UNDERGROUND_SECONDS + 1.0);
+        schedule.run(&mut world);
+        let now = world.resource::<Time>().elapsed_seconds();
+        assert!(world.resource::<DeepUndergroundTimer>().is_long_time(now));
+        // 激进模式只加载玩家所在区块及其周围的必要区块
+        assert!(world.resource::<ChunkLoadQueue>().contains(&IVec3::new(0, -100, 0)));
+
+        advance(&mut world, 1.0);
+        move_player(&mut world, player, Vec3::new(16.0, 40.0, 16.0));
+        schedule.run(&mut world);
+        let now = world.resource::<Time>().elapsed_seconds();
+        assert_eq!(world.resource::<DeepUndergroundTimer>().elapsed(now), None);
+    }
+
+    #[test]
+    fn load_history_counts_reloads_within_window() {
+        let mut history = ChunkLoadHistory::default();
+        let coord = IVec3::new(12, 0, 0);
+        history.record_loaded(coord, 0.0);
+        assert!(history.is_protected(coord, 5.0, 10.0));
+        assert!(!history.is_protected(coord, 15.0, 10.0));
+
+        history.record_unloaded(coord, 20.0);
+        assert!(history.record_loaded(coord, 25.0));
+
+        // 超出统计窗口后再次加载不算抖动
+        history.record_unloaded(coord, 30.0);
+        assert!(!history.record_loaded(coord, 30.0 + RELOAD_WINDOW_SECONDS + 1.0));
+    }
+
+    #[test]
+    fn work_budget_adapts_task_starts_to_last_frame() {
+        let mut budget = ChunkWorkBudget::default();
+        budget.spend(8.0);
+        budget.begin_frame(4.0);
+        assert_eq!(budget.start_limit, 8);
+        assert_eq!(budget.usage(), 2.0);
+
+        budget.avg_mesh_ms = 0.5;
+        budget.spend(1.0);
+        assert_eq!(budget.affordable_chunks(), 6);
+        budget.spend(4.0);
+        assert!(budget.is_exhausted());
+        assert_eq!(budget.affordable_chunks(), 0);
+
+        budget.begin_frame(4.0);
+        budget.begin_frame(4.0);
+        assert_eq!(budget.start_limit, 6);
+    }
+
+    #[test]
+    fn load_range_is_an_asymmetric_ellipsoid() {
+        let config = ChunkLoaderConfig::default();
+        assert!(config.in_load_range(IVec3::new(12, 0, 0)));
+        assert!(!config.in_load_range(IVec3::new(13, 0, 0)));
+        assert!(config.in_load_range(IVec3::new(0, 4, 0)));
+        assert!(!config.in_load_range(IVec3::new(0, 5, 0)));
+        assert!(config.in_load_range(IVec3::new(0, -6, 0)));
+        assert!(!config.in_load_range(IVec3::new(0, -7, 0)));
+        assert!(!config.in_load_range(IVec3::new(9, 3, 0)));
+    }
+
+    #[test]
+    fn lod_applies_only_beyond_full_detail_radius() {
+        let mut config = ChunkLoaderConfig::default();
+        assert_eq!(config.lod_for(IVec3::new(20, 0, 0)), 0);
+        config.lod_radius = 24.0;
+        assert_eq!(config.lod_for(IVec3::new(12, 0, 0)), 0);
+        assert_eq!(config.lod_for(IVec3::new(13, 0, 0)), 1);
+        assert_eq!(config.surface_range(), 24.0);
+    }
+
+    #[test]
+    fn generation_context_is_shared_until_config_changes() {
+        let mut app = App::new();
+        app.insert_resource(WorldGeneratorConfig::default())
+           .insert_resource(BlockRegistry::default())
+           .add_systems(Update, update_generation_context);
+        app.update();
+        let first = app.world.resource::<GenerationContext>().generator.clone();
+        app.update();
+        assert!(Arc::ptr_eq(&first, &app.world.resource::<GenerationContext>().generator));
+
+        app.world.resource_mut::<WorldGeneratorConfig>().seed = 7;
+        app.update();
+        assert!(!Arc::ptr_eq(&first, &app.world.resource::<GenerationContext>().generator));
+    }
+}
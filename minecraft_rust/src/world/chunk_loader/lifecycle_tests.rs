@@ -0,0 +1,276 @@
+use super::*;
+use crate::game_state::WorldState;
+use crate::world::events::{ChunkUnloadedEvent, ChunksModified};
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+
+/// 每次 `update` 推进的虚拟时间，保护期和检测间隔都按它计算，与测试机器的速度无关
+const FRAME: Duration = Duration::from_millis(50);
+/// 每帧之间等待后台生成线程的时间
+const FRAME_SLEEP: Duration = Duration::from_millis(2);
+const MAX_FRAMES: usize = 3000;
+/// 玩家所在的区块：在地形最高处之上，大部分区块是空气，生成很快
+const START: IVec3 = IVec3::new(0, 6, 0);
+
+/// 由区块事件重建的已加载区块集合；同一区块重复加载或卸载未加载的区块都记为违规
+#[derive(Resource, Default)]
+struct LifecycleLog {
+    live: HashSet<IVec3>,
+    violations: Vec<String>,
+}
+
+fn record_chunk_events(
+    mut loaded: EventReader<ChunkLoadedEvent>,
+    mut unloaded: EventReader<ChunkUnloadedEvent>,
+    mut log: ResMut<LifecycleLog>,
+) {
+    for event in loaded.read() {
+        if !log.live.insert(event.coord) {
+            log.violations.push(format!("{:?} loaded twice", event.coord));
+        }
+    }
+    for event in unloaded.read() {
+        if !log.live.remove(&event.coord) {
+            log.violations.push(format!("{:?} unloaded while not loaded", event.coord));
+        }
+    }
+}
+
+/// 测试世界：只有区块加载器（不含光照等后续处理），较小的加载范围、4 个生成线程、空的方块注册表，存档目录指向临时目录
+fn lifecycle_app(name: &str, player_pos: Vec3) -> (App, Entity) {
+    let saves = std::env::temp_dir().join(format!("chunk_lifecycle_{}_{}", name, std::process::id()));
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+       .add_state::<GameState>()
+       .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+       .insert_resource(BlockRegistry::default())
+       .init_resource::<WorldState>()
+       .insert_resource(WorldManager { saves_directory: saves, current_world: Some("test".to_string()), ..default() })
+       .insert_resource(ChunkLoaderConfig {
+           horizontal_radius: 2.0,
+           vertical_radius_up: 1.0,
+           vertical_radius_down: 1.0,
+           unload_margin: 1.0,
+           unload_grace_period: 1.0,
+           ..default()
+       })
+       .insert_resource(ChunkGenerationThreadPool::new(4))
+       .insert_resource(ChunkStorage::new())
+       .insert_resource(WorldGeneratorConfig::default())
+       .add_event::<ChunkLoadedEvent>()
+       .add_event::<ChunkUnloadedEvent>()
+       .add_event::<ChunksModified>()
+       .add_plugins(ChunkLoaderPlugin)
+       .init_resource::<LifecycleLog>()
+       .add_systems(Update, record_chunk_events.after(ChunkLoaderSet));
+    app.finish();
+    app.cleanup();
+    let player = app
+        .world
+        .spawn((Transform::from_translation(player_pos), FirstPersonController::default(), ChunkLoadAnchor))
+        .id();
+    (app, player)
+}
+
+/// 每帧都必须成立的不变量：每个坐标只有一个区块实体，存储中的条目都指向该坐标的存活区块
+fn check_invariants(app: &mut App) {
+    let world = &mut app.world;
+    let mut coords = HashSet::new();
+    for (entity, chunk) in world.query::<(Entity, &Chunk)>().iter(world) {
+        assert!(coords.insert(chunk.coord), "two chunk entities at {:?}", chunk.coord);
+        assert_eq!(world.resource::<ChunkStorage>().get(&chunk.coord), Some(entity), "storage lost {:?}", chunk.coord);
+    }
+    for entry in world.resource::<ChunkStorage>().chunks.iter() {
+        let chunk = world.get::<Chunk>(entry.entity);
+        assert_eq!(chunk.map(|chunk| chunk.coord), Some(*entry.key()), "stale storage entry {:?}", entry.key());
+        // 每帧结束时本帧生成的区块都已切换为 Active
+        assert_ne!(entry.state, ChunkLifecycle::Loading, "{:?} stayed in Loading", entry.key());
+    }
+    assert_eq!(world.resource::<ChunkUnloadQueue>().already_despawned, 0, "a chunk entity was despawned twice");
+    let log = world.resource::<LifecycleLog>();
+    assert!(log.violations.is_empty(), "{:?}", log.violations);
+}
+
+/// 逐帧运行直到 `done` 成立，超过帧数上限时失败
+fn run_until(app: &mut App, what: &str, mut done: impl FnMut(&mut App) -> bool) {
+    for _ in 0..MAX_FRAMES {
+        app.update();
+        check_invariants(app);
+        if done(app) {
+            return;
+        }
+        std::thread::sleep(FRAME_SLEEP);
+    }
+    panic!("{} did not happen within {} frames", what, MAX_FRAMES);
+}
+
+fn loaded_coords(app: &mut App) -> HashSet<IVec3> {
+    let world = &mut app.world;
+    world.query::<&Chunk>().iter(world).map(|chunk| chunk.coord).collect()
+}
+
+/// 加载半径内的所有区块坐标
+fn chunks_in_range(app: &App, center: IVec3) -> Vec<IVec3> {
+    let config = app.world.resource::<ChunkLoaderConfig>();
+    let radius = config.horizontal_radius.ceil() as i32;
+    let mut coords = Vec::new();
+    for x in -radius..=radius {
+        for y in -radius..=radius {
+            for z in -radius..=radius {
+                let offset = IVec3::new(x, y, z);
+                if config.in_load_range(offset) {
+                    coords.push(center + offset);
+                }
+            }
+        }
+    }
+    coords
+}
+
+/// 所有区块任务都已完成，加载队列为空
+fn pipeline_idle(app: &mut App) -> bool {
+    let world = &mut app.world;
+    let tasks = world.query::<&ChunkGenerationTask>().iter(world).count() + world.query::<&ChunkUnloadTask>().iter(world).count();
+    let queue = world.resource::<ChunkLoadQueue>();
+    let unload_queue = world.resource::<ChunkUnloadQueue>();
+    tasks == 0 && queue.is_empty() && queue.generating.is_empty() && unload_queue.pending.is_empty() && unload_queue.unloading.is_empty()
+}
+
+fn cleanup(app: &App) {
+    let _ = std::fs::remove_dir_all(&app.world.resource::<WorldManager>().saves_directory);
+}
+
+#[test]
+fn chunks_around_player_load_and_pipeline_drains() {
+    let (mut app, _) = lifecycle_app("load", START.as_vec3() * 32.0 + Vec3::splat(16.0));
+    let center = START;
+    let expected = chunks_in_range(&app, center);
+    run_until(&mut app, "loading the chunks in range", |app| {
+        let loaded = loaded_coords(app);
+        expected.iter().all(|coord| loaded.contains(coord)) && pipeline_idle(app)
+    });
+
+    // 玩家不动时已加载的区块不会被卸载，之后补充加载的区块（地表范围）完成后流水线再次排空
+    let settled = loaded_coords(&mut app);
+    run_until(&mut app, "draining the pipeline again", |app| {
+        app.world.resource::<Time>().elapsed_seconds() > 10.0 && pipeline_idle(app)
+    });
+    assert!(settled.is_subset(&loaded_coords(&mut app)));
+    cleanup(&app);
+}
+
+#[test]
+fn chunks_far_behind_player_unload() {
+    let (mut app, player) = lifecycle_app("unload", START.as_vec3() * 32.0 + Vec3::splat(16.0));
+    let expected = chunks_in_range(&app, START);
+    run_until(&mut app, "loading the chunks around the start", |app| {
+        let loaded = loaded_coords(app);
+        expected.iter().all(|coord| loaded.contains(coord)) && pipeline_idle(app)
+    });
+
+    // 卸载只在已加载数量接近上限时发生：把上限设为略高于一处的区块数，
+    // 换到新位置后新旧区块之和超过上限，旧区块必须全部让出位置
+    let old_chunks = loaded_coords(&mut app);
+    app.world.resource_mut::<ChunkLoaderConfig>().max_loaded_chunks = old_chunks.len() * 21 / 20;
+
+    let destination = START + IVec3::new(10, 0, 0);
+    app.world.get_mut::<Transform>(player).unwrap().translation = destination.as_vec3() * 32.0 + Vec3::splat(16.0);
+    let expected = chunks_in_range(&app, destination);
+    run_until(&mut app, "unloading the chunks behind the player", |app| {
+        let loaded = loaded_coords(app);
+        old_chunks.iter().all(|coord| !loaded.contains(coord))
+            && expected.iter().all(|coord| loaded.contains(coord))
+            && pipeline_idle(app)
+    });
+    assert_eq!(app.world.resource::<ChunkStats>().counters().snapshot().reloaded, 0);
+    cleanup(&app);
+}
+
+#[test]
+fn rapid_reloads_of_the_same_region_keep_the_lifecycle_consistent() {
+    let (mut app, player) = lifecycle_app("stress", START.as_vec3() * 32.0 + Vec3::splat(16.0));
+    let home = chunks_in_range(&app, START);
+    run_until(&mut app, "loading the chunks around the start", |app| {
+        let loaded = loaded_coords(app);
+        home.iter().all(|coord| loaded.contains(coord)) && pipeline_idle(app)
+    });
+    let limit = loaded_coords(&mut app).len() * 21 / 20;
+    app.world.resource_mut::<ChunkLoaderConfig>().max_loaded_chunks = limit;
+
+    // 被后台任务引用的区块在引用期间一直保留，其余的区块在两处之间来回卸载和加载
+    let retained = START + IVec3::new(-2, 0, 0);
+    assert!(app.world.resource::<ChunkStorage>().retain(&retained));
+    let away = START + IVec3::new(10, 0, 0);
+    let away_chunks = chunks_in_range(&app, away);
+    for round in 0..3 {
+        for (target, expected) in [(away, &away_chunks), (START, &home)] {
+            app.world.get_mut::<Transform>(player).unwrap().translation = target.as_vec3() * 32.0 + Vec3::splat(16.0);
+            // 不等待流水线排空就返回，上一处的卸载任务和这一处的生成任务交错进行
+            run_until(&mut app, &format!("reloading {:?} in round {}", target, round), |app| {
+                let loaded = loaded_coords(app);
+                expected.iter().all(|coord| loaded.contains(coord))
+            });
+            assert!(loaded_coords(&mut app).contains(&retained), "retained chunk was unloaded");
+        }
+    }
+    assert_eq!(app.world.resource::<ChunkStorage>().state(&retained), Some(ChunkLifecycle::Active));
+    assert!(app.world.resource::<ChunkStats>().counters().snapshot().reloaded > 0);
+    app.world.resource::<ChunkStorage>().release(&retained);
+    run_until(&mut app, "draining the pipeline", pipeline_idle);
+    cleanup(&app);
+}
+
+#[test]
+fn dropped_items_are_saved_with_their_chunk_and_restored() {
+    use crate::inventory::{ItemStack, ItemType};
+    use crate::world::chunk::BlockId;
+    use crate::world::falling_block::{spawn_dropped_item, DroppedItem};
+    use bevy::ecs::system::RunSystemOnce;
+
+    let (mut app, player) = lifecycle_app("entities", START.as_vec3() * 32.0 + Vec3::splat(16.0));
+    let home = chunks_in_range(&app, START);
+    run_until(&mut app, "loading the chunks around the start", |app| {
+        let loaded = loaded_coords(app);
+        home.iter().all(|coord| loaded.contains(coord)) && pipeline_idle(app)
+    });
+    app.world.resource_mut::<ChunkLoaderConfig>().max_loaded_chunks = loaded_coords(&mut app).len() * 21 / 20;
+
+    // 一个在玩家所在的区块中间，一个在相邻区块的边界上，一个在加载范围外未加载的区块中（随相邻的已加载区块保存）
+    let outside = START + IVec3::X * 3;
+    assert!(!loaded_coords(&mut app).contains(&outside));
+    let drops = [
+        (ItemStack::new(ItemType::Block(BlockId::Dirt), 5), START.as_vec3() * 32.0 + Vec3::new(10.5, 3.0, 20.5)),
+        (ItemStack::new(ItemType::Block(BlockId::Stone), 1), START.as_vec3() * 32.0 + Vec3::new(32.25, 1.0, 0.5)),
+        (ItemStack::new(ItemType::Block(BlockId::Sand), 2), outside.as_vec3() * 32.0 + Vec3::new(0.5, 1.0, 0.5)),
+    ];
+    app.world.run_system_once(move |mut commands: Commands| {
+        for (stack, position) in drops {
+            spawn_dropped_item(&mut commands, stack, position);
+        }
+    });
+    let dropped = |app: &mut App| {
+        let world = &mut app.world;
+        let mut items: Vec<(ItemStack, Vec3)> = world.query::<(&DroppedItem, &Transform)>().iter(world).map(|(item, transform)| (item.stack, transform.translation)).collect();
+        items.sort_by(|a, b| a.1.x.total_cmp(&b.1.x));
+        items
+    };
+    assert_eq!(dropped(&mut app), drops);
+
+    // 离开后区块都被卸载，掉落物随区块销毁
+    let away = START + IVec3::new(10, 0, 0);
+    app.world.get_mut::<Transform>(player).unwrap().translation = away.as_vec3() * 32.0 + Vec3::splat(16.0);
+    run_until(&mut app, "unloading the chunks with the items", |app| {
+        let loaded = loaded_coords(app);
+        home.iter().all(|coord| !loaded.contains(coord)) && pipeline_idle(app)
+    });
+    assert!(dropped(&mut app).is_empty());
+
+    // 回来后从存档中恢复
+    app.world.get_mut::<Transform>(player).unwrap().translation = START.as_vec3() * 32.0 + Vec3::splat(16.0);
+    run_until(&mut app, "reloading the chunks with the items", |app| {
+        let loaded = loaded_coords(app);
+        home.iter().all(|coord| loaded.contains(coord)) && pipeline_idle(app)
+    });
+    assert_eq!(dropped(&mut app), drops);
+    cleanup(&app);
+}
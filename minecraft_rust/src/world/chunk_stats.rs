@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use crate::world::chunk::Chunk;
+use crate::world::chunk_loader::ChunkLoadQueue;
+use crate::world::events::ChunksModified;
+
+/// 每秒生成数的历史保留的秒数
+pub const RATE_HISTORY_SECONDS: usize = 60;
+/// 滑动平均中最新样本的权重
+const AVERAGE_WEIGHT: f32 = 0.1;
+
+/// 区块流水线的累计计数，生成线程和主线程都直接累加
+#[derive(Default)]
+pub struct ChunkCounters {
+    generated: AtomicU64,
+    loaded_from_save: AtomicU64,
+    generation_micros: AtomicU64,
+    peak_generation_micros: AtomicU64,
+    completed: AtomicU64,
+    discarded: AtomicU64,
+    reloaded: AtomicU64,
+    unreadable: AtomicU64,
+}
+
+/// 某一时刻的计数
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ChunkCounterSnapshot {
+    /// 生成任务完成的次数（包括从存档读取）
+    pub generated: u64,
+    pub loaded_from_save: u64,
+    /// 生成任务的累计耗时（毫秒）和单次最长耗时
+    pub generation_ms: f64,
+    pub peak_generation_ms: f64,
+    /// 生成区块实体（或替换 LOD 数据）的次数
+    pub completed: u64,
+    /// 因玩家离开而丢弃的区块：跳过的排队项和取消或丢弃的生成任务
+    pub discarded: u64,
+    /// 卸载后短时间内又被加载的次数
+    pub reloaded: u64,
+    /// 存档文件损坏、改为重新生成的区块
+    pub unreadable: u64,
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros().min(u64::MAX as u128) as u64
+}
+
+impl ChunkCounters {
+    /// 生成线程完成一个区块，`from_save` 表示从存档读取而不是重新生成
+    pub fn record_generation(&self, duration: Duration, from_save: bool) {
+        let micros = micros(duration);
+        self.generated.fetch_add(1, Ordering::Relaxed);
+        if from_save {
+            self.loaded_from_save.fetch_add(1, Ordering::Relaxed);
+        }
+        self.generation_micros.fetch_add(micros, Ordering::Relaxed);
+        self.peak_generation_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    pub fn record_completed(&self, reloaded: bool) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        if reloaded {
+            self.reloaded.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 存档中的区块文件无法读取，改为重新生成
+    pub fn record_unreadable(&self) {
+        self.unreadable.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录丢弃的区块数，返回累计值
+    pub fn record_discarded(&self, count: u64) -> u64 {
+        self.discarded.fetch_add(count, Ordering::Relaxed) + count
+    }
+
+    pub fn snapshot(&self) -> ChunkCounterSnapshot {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let ms = |counter: &AtomicU64| counter.load(Ordering::Relaxed) as f64 / 1000.0;
+        ChunkCounterSnapshot {
+            generated: load(&self.generated),
+            loaded_from_save: load(&self.loaded_from_save),
+            generation_ms: ms(&self.generation_micros),
+            peak_generation_ms: ms(&self.peak_generation_micros),
+            completed: load(&self.completed),
+            discarded: load(&self.discarded),
+            reloaded: load(&self.reloaded),
+            unreadable: load(&self.unreadable),
+        }
+    }
+}
+
+/// 区块流水线统计，调试界面和基准测试都从这里读取。
+/// 计数器由加载和生成系统累加，队列长度、滑动平均和峰值每帧由 `update_chunk_stats` 更新；
+/// 网格构建在主线程进行，次数和耗时记在 `ChunkWorkBudget` 中
+#[derive(Resource, Default)]
+pub struct ChunkStats {
+    counters: Arc<ChunkCounters>,
+    pub current: ChunkCounterSnapshot,
+    pub queued: usize,
+    pub generating: usize,
+    /// 等待构建网格的区块
+    pub awaiting_mesh: usize,
+    pub peak_queued: usize,
+    pub peak_generating: usize,
+    pub peak_awaiting_mesh: usize,
+    /// 方块修改的批数和每批修改的区块数之和
+    pub edit_batches: u64,
+    pub edited_chunks: u64,
+    /// 生成单个区块耗时的滑动平均（毫秒）
+    pub avg_generation_ms: f32,
+    /// 最近每秒完成生成的区块数，最新的在最后
+    pub generated_per_second: VecDeque<u32>,
+    second_started: Option<f32>,
+    generated_at_second_start: u64,
+}
+
+impl ChunkStats {
+    /// 计数器，克隆后传给生成线程
+    pub fn counters(&self) -> &Arc<ChunkCounters> {
+        &self.counters
+    }
+
+    /// 用新的计数更新滑动平均，耗时按两次更新之间完成的数量取平均
+    fn update_averages(&mut self, snapshot: ChunkCounterSnapshot) {
+        let previous = std::mem::replace(&mut self.current, snapshot);
+        let generated = snapshot.generated - previous.generated;
+        if generated > 0 {
+            let ms = ((snapshot.generation_ms - previous.generation_ms) / generated as f64) as f32;
+            self.avg_generation_ms = blend(self.avg_generation_ms, ms, previous.generated == 0);
+        }
+    }
+
+    /// 每满一秒记录这一秒完成生成的区块数，第一次调用只记下起点
+    fn update_rate(&mut self, now: f32) {
+        let generated = self.current.generated;
+        let Some(started) = self.second_started else {
+            self.second_started = Some(now);
+            self.generated_at_second_start = generated;
+            return;
+        };
+        if now - started < 1.0 {
+            return;
+        }
+        self.generated_per_second.push_back((generated - self.generated_at_second_start) as u32);
+        if self.generated_per_second.len() > RATE_HISTORY_SECONDS {
+            self.generated_per_second.pop_front();
+        }
+        self.generated_at_second_start = generated;
+        self.second_started = Some(now);
+    }
+}
+
+/// 第一个样本直接作为平均值
+fn blend(average: f32, sample: f32, first: bool) -> f32 {
+    if first {
+        sample
+    } else {
+        average * (1.0 - AVERAGE_WEIGHT) + sample * AVERAGE_WEIGHT
+    }
+}
+
+/// 在区块流水线之后运行，更新队列长度、峰值、滑动平均、每秒生成数和方块修改数
+pub fn update_chunk_stats(
+    mut stats: ResMut<ChunkStats>,
+    load_queue: Res<ChunkLoadQueue>,
+    chunks: Query<&Chunk>,
+    mut modified: EventReader<ChunksModified>,
+    time: Res<Time>,
+) {
+    let snapshot = stats.counters.snapshot();
+    stats.update_averages(snapshot);
+    stats.queued = load_queue.len();
+    stats.generating = load_queue.generating.len();
+    stats.awaiting_mesh = chunks.iter().filter(|chunk| chunk.dirty).count();
+    stats.peak_queued = stats.peak_queued.max(stats.queued);
+    stats.peak_generating = stats.peak_generating.max(stats.generating);
+    stats.peak_awaiting_mesh = stats.peak_awaiting_mesh.max(stats.awaiting_mesh);
+    for event in modified.read() {
+        stats.edit_batches += 1;
+        stats.edited_chunks += event.coords.len() as u64;
+    }
+    stats.update_rate(time.elapsed_seconds());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_use_the_durations_since_the_last_update() {
+        let mut stats = ChunkStats::default();
+        let counters = stats.counters().clone();
+        stats.update_rate(0.5);
+        counters.record_generation(Duration::from_millis(10), false);
+        counters.record_generation(Duration::from_millis(30), true);
+        stats.update_averages(counters.snapshot());
+        assert_eq!(stats.avg_generation_ms, 20.0);
+        assert_eq!(stats.current.loaded_from_save, 1);
+        assert_eq!(stats.current.peak_generation_ms, 30.0);
+
+        counters.record_generation(Duration::from_millis(120), false);
+        stats.update_averages(counters.snapshot());
+        assert!((stats.avg_generation_ms - 30.0).abs() < 1e-4);
+        assert_eq!(counters.record_discarded(3), 3);
+        assert_eq!(counters.record_discarded(2), 5);
+
+        stats.update_rate(1.0);
+        stats.update_rate(1.6);
+        assert_eq!(stats.generated_per_second, [3]);
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::inventory::ItemStack;
+use crate::world::access::world_pos_to_chunk_coord;
+use crate::world::chunk::{Chunk, LodLevel};
+use crate::world::falling_block::{respawn_dropped_item, DroppedItem};
+use crate::world::storage::{ChunkLifecycle, ChunkStorage};
+
+/// 随区块保存的实体。区块卸载时写入区块存档并随区块销毁，区块再次加载时重新生成。
+/// `chunk` 是保存它的区块，由 `assign_entity_chunks` 维护：通常是实体所在的区块，
+/// 所在区块没有加载时（例如掉落物生成在已卸载的区块中）是相邻的已加载区块
+#[derive(Component)]
+pub struct PersistentEntity {
+    chunk: IVec3,
+}
+
+impl PersistentEntity {
+    pub fn at(position: Vec3) -> Self {
+        Self { chunk: world_pos_to_chunk_coord(position.floor().as_ivec3()) }
+    }
+}
+
+/// 各类持久实体自己的数据，枚举的变体就是实体类型
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum EntityData {
+    DroppedItem { stack: ItemStack, age: f32 },
+}
+
+/// 区块存档中的一个实体
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EntityRecord {
+    pub position: Vec3,
+    pub data: EntityData,
+}
+
+impl EntityRecord {
+    pub fn spawn(&self, commands: &mut Commands) {
+        match &self.data {
+            EntityData::DroppedItem { stack, age } => respawn_dropped_item(commands, *stack, *age, self.position),
+        }
+    }
+}
+
+/// 按区块整理存活的持久实体，写入区块的实体记录
+#[derive(SystemParam)]
+pub struct PersistentEntities<'w, 's> {
+    entities: Query<'w, 's, (Entity, &'static PersistentEntity, &'static Transform, Option<&'static DroppedItem>)>,
+    lods: Query<'w, 's, &'static LodLevel>,
+    chunk_storage: Res<'w, ChunkStorage>,
+}
+
+impl PersistentEntities<'_, '_> {
+    /// 所有持久实体的 (实体, 保存它的区块, 记录)
+    fn records(&self) -> impl Iterator<Item = (Entity, IVec3, EntityRecord)> + '_ {
+        self.entities.iter().filter_map(|(entity, persistent, transform, dropped)| {
+            let position = transform.translation;
+            let item = dropped?;
+            let data = EntityData::DroppedItem { stack: item.stack, age: item.age() };
+            Some((entity, persistent.chunk, EntityRecord { position, data }))
+        })
+    }
+
+    /// LOD 区块的方块是降采样后的，不能写回存档，也不生成其中的实体
+    fn full_detail(&self, chunk_entity: Entity) -> bool {
+        self.lods.get(chunk_entity).map_or(true, |lod| lod.0 == 0)
+    }
+
+    /// 区块开始卸载时把其中的实体写入区块并销毁
+    pub fn store_unloading(&self, chunk_entity: Entity, chunk: &mut Chunk, commands: &mut Commands) {
+        if !self.full_detail(chunk_entity) {
+            return;
+        }
+        let mut records = Vec::new();
+        for (entity, coord, record) in self.records() {
+            if coord == chunk.coord {
+                commands.entity(entity).despawn_recursive();
+                records.push(record);
+            }
+        }
+        if !records.is_empty() {
+            debug!("Saving {} entities with chunk {:?}", records.len(), chunk.coord);
+        }
+        chunk.set_entities(records);
+    }
+
+    /// 保存世界前把所有实体的当前位置和状态写入保存它的区块，实体不销毁。
+    /// 正在卸载的区块在卸载开始时已经写入过，其中的实体已经销毁
+    pub fn store_all(&self, chunks: &mut Query<&mut Chunk>) {
+        let mut by_chunk: HashMap<IVec3, Vec<EntityRecord>> = HashMap::new();
+        for (_, coord, record) in self.records() {
+            by_chunk.entry(coord).or_default().push(record);
+        }
+        for mut chunk in chunks.iter_mut() {
+            let records = by_chunk.remove(&chunk.coord).unwrap_or_default();
+            if records.is_empty() && chunk.entities().is_empty() {
+                continue;
+            }
+            let Some(chunk_entity) = self.chunk_storage.get(&chunk.coord) else { continue };
+            if self.chunk_storage.state(&chunk.coord) == Some(ChunkLifecycle::Unloading) || !self.full_detail(chunk_entity) {
+                continue;
+            }
+            chunk.set_entities(records);
+        }
+    }
+}
+
+/// 让每个持久实体都有一个会保存它的区块：所在区块可以保存实体（已加载、未开始卸载且是完整细节）时就是所在区块；
+/// 否则保留之前的区块，之前的区块也不能保存时改为最近的相邻区块。
+/// 这样实体不会留在没有区块保存它的地方，在卸载之前总会随某个区块写入存档
+pub fn assign_entity_chunks(
+    mut entities: Query<(&Transform, &mut PersistentEntity)>,
+    chunk_storage: Res<ChunkStorage>,
+    lods: Query<&LodLevel>,
+) {
+    let can_store = |coord: IVec3| {
+        chunk_storage.get(&coord).is_some_and(|entity| {
+            matches!(chunk_storage.state(&coord), Some(ChunkLifecycle::Active | ChunkLifecycle::PendingUnload))
+                && lods.get(entity).map_or(true, |lod| lod.0 == 0)
+        })
+    };
+    for (transform, mut persistent) in entities.iter_mut() {
+        let current = world_pos_to_chunk_coord(transform.translation.floor().as_ivec3());
+        if can_store(current) {
+            if persistent.chunk != current {
+                persistent.chunk = current;
+            }
+            continue;
+        }
+        if can_store(persistent.chunk) {
+            continue;
+        }
+        let neighbour = (-1..=1)
+            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z))))
+            .filter(|offset| *offset != IVec3::ZERO)
+            .map(|offset| current + offset)
+            .filter(|coord| can_store(*coord))
+            .min_by_key(|coord| (*coord - current).length_squared());
+        if let Some(coord) = neighbour {
+            persistent.chunk = coord;
+        }
+    }
+}
+
+/// 区块加载完成时生成存档中记录的实体，只生成完整细节的区块中的实体
+pub fn spawn_saved_entities(commands: &mut Commands, records: &[EntityRecord], lod: u8) {
+    if lod > 0 {
+        return;
+    }
+    for record in records {
+        record.spawn(commands);
+    }
+}
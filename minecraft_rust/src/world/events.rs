@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use crate::world::block_entity::BlockEntity;
+use crate::world::chunk::Chunk;
+use crate::world::storage::ChunkStorage;
+
+/// 区块已加载：由 `chunk_completion_system` 发送。
+/// 排在 `ChunkLoaderSet` 之后运行的读取者可以保证实体已经存在并且已写入 `ChunkStorage`。
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ChunkLoadedEvent {
+    pub coord: IVec3,
+    pub entity: Entity,
+}
+
+/// 区块已卸载：由 `chunk_unload_completion_system` 发送。
+/// 排在 `ChunkLoaderSet` 之后运行的读取者可以保证实体已被销毁并且已从 `ChunkStorage` 移除。
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ChunkUnloadedEvent {
+    pub coord: IVec3,
+}
+
+/// 世界坐标处的方块已被修改（玩家编辑、流体流动或方块下落）。流体系统据此安排周围流体的更新，
+/// 下落方块系统据此检查失去支撑的沙子和沙砾，光照系统据此重新扩散方块光照，联机服务器据此向玩家广播方块变化
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BlockChangedEvent {
+    pub pos: IVec3,
+}
+
+/// 一次方块修改涉及的区块（不含只重建网格的相邻区块），由 `WorldAccess::apply` 每批发送一次
+#[derive(Event, Debug, Clone)]
+pub struct ChunksModified {
+    pub coords: Vec<IVec3>,
+}
+
+/// 方块实体的所属方块已被替换（破坏或被其他方块覆盖），方块实体已从区块中移除。
+/// 掉落箱子里的物品、关闭正在查看这个箱子的界面都由此触发
+#[derive(Event, Debug, Clone)]
+pub struct BlockEntityRemovedEvent {
+    pub pos: IVec3,
+    pub entity: BlockEntity,
+}
+
+const FACE_NEIGHBORS: [IVec3; 6] = [
+    IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z,
+];
+
+/// 区块加载或卸载后，相邻区块边界上的可见面会变化，需要重新构建网格
+pub fn mark_neighbors_dirty_on_chunk_events(
+    mut loaded: EventReader<ChunkLoadedEvent>,
+    mut unloaded: EventReader<ChunkUnloadedEvent>,
+    chunk_storage: Res<ChunkStorage>,
+    mut chunk_query: Query<&mut Chunk>,
+) {
+    let coords = loaded.read().map(|e| e.coord).chain(unloaded.read().map(|e| e.coord));
+    for coord in coords {
+        for offset in FACE_NEIGHBORS {
+            if let Some(neighbor) = chunk_storage.get(&(coord + offset)) {
+                if let Ok(mut chunk) = chunk_query.get_mut(neighbor) {
+                    chunk.dirty = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::chunk_loader::*;
+    use crate::world::chunk_stats::ChunkStats;
+    use bevy::tasks::TaskPool;
+
+    /// 记录收到的区块事件数量
+    #[derive(Resource, Default)]
+    struct ChunkEventLog {
+        loaded: usize,
+        unloaded: usize,
+    }
+
+    struct ChunkEventRecorderPlugin;
+
+    impl Plugin for ChunkEventRecorderPlugin {
+        fn build(&self, app: &mut App) {
+            app.init_resource::<ChunkEventLog>()
+               .add_systems(Update, record_chunk_events.after(ChunkLoaderSet));
+        }
+    }
+
+    fn record_chunk_events(
+        mut log: ResMut<ChunkEventLog>,
+        mut loaded: EventReader<ChunkLoadedEvent>,
+        mut unloaded: EventReader<ChunkUnloadedEvent>,
+        chunk_query: Query<&Chunk>,
+    ) {
+        for event in loaded.read() {
+            // 事件到达时实体必须已经存在
+            assert_eq!(chunk_query.get(event.entity).map(|c| c.coord), Ok(event.coord));
+            log.loaded += 1;
+        }
+        log.unloaded += unloaded.read().count();
+    }
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_event::<ChunkLoadedEvent>()
+           .add_event::<ChunkUnloadedEvent>()
+           .insert_resource(ChunkStorage::new())
+           .init_resource::<Time>()
+           .insert_resource(ChunkLoaderConfig::default())
+           .init_resource::<ChunkLoadQueue>()
+           .init_resource::<ChunkUnloadQueue>()
+           .init_resource::<ChunkLoadHistory>()
+           .init_resource::<ChunkWorkBudget>()
+           .init_resource::<ChunkStats>()
+           .insert_resource(ChunkGenerationThreadPool::new(1))
+           .add_systems(Update, (chunk_completion_system, chunk_unload_completion_system, apply_deferred)
+               .chain()
+               .in_set(ChunkLoaderSet))
+           .add_plugins(ChunkEventRecorderPlugin);
+        app
+    }
+
+    /// 运行若干帧直到没有剩余的后台任务
+    fn run_until_idle(app: &mut App) {
+        for _ in 0..100 {
+            app.update();
+            let world = &mut app.world;
+            let pending = world.query::<&ChunkGenerationTask>().iter(world).count()
+                + world.query::<&ChunkUnloadTask>().iter(world).count();
+            if pending == 0 {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        panic!("chunk tasks did not finish");
+    }
+
+    #[test]
+    fn chunk_events_match_storage_changes() {
+        let mut app = test_app();
+        let pool = TaskPool::new();
+        for x in 0..4 {
+            let position = IVec3::new(x, 0, 0);
+            let task = pool.spawn(async move { Chunk::new(position) });
+            app.world.spawn(ChunkGenerationTask { task, position, emergency: true, lod: 0 });
+        }
+        run_until_idle(&mut app);
+
+        let stored = app.world.resource::<ChunkStorage>().chunks.len();
+        assert_eq!(stored, 4);
+        assert_eq!(app.world.resource::<ChunkEventLog>().loaded, stored);
+
+        for x in 0..2 {
+            let position = IVec3::new(x, 0, 0);
+            let entity = app.world.resource::<ChunkStorage>().get(&position).unwrap();
+            let task = pool.spawn(async {});
+            app.world.spawn(ChunkUnloadTask { task, position, entity });
+        }
+        run_until_idle(&mut app);
+
+        assert_eq!(app.world.resource::<ChunkStorage>().chunks.len(), 2);
+        assert_eq!(app.world.resource::<ChunkEventLog>().unloaded, 2);
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+use bevy::prelude::*;
+use crate::audio::{SoundEvent, SoundKind};
+use crate::controller::box_collides;
+use crate::game_state::GameState;
+use crate::inventory::{ItemStack, ItemType};
+use crate::world::access::WorldAccess;
+use crate::world::chunk::{BlockId, Chunk, LodLevel};
+use crate::world::chunk_loader::ChunkLoaderSet;
+use crate::world::entity_persistence::PersistentEntity;
+use crate::world::events::{BlockChangedEvent, ChunkLoadedEvent};
+use crate::world::storage::ChunkStorage;
+use crate::world::BlockUpdateSet;
+
+/// 下落加速度和最大下落速度（格/秒），与原版下落方块接近
+const GRAVITY: f32 = 16.0;
+const TERMINAL_VELOCITY: f32 = 40.0;
+/// 下落超过这么久（通常是落进了未加载的区块）就变成掉落物
+const MAX_FALL_SECONDS: f32 = 30.0;
+/// 掉落物无人拾取时保留的时间
+const DROPPED_ITEM_SECONDS: f32 = 300.0;
+/// 碰撞箱比方块略小，贴着墙下落时不会卡住
+const HALF_EXTENT: f32 = 0.49;
+/// 每次碰撞检测最多移动的距离（格）。速度快或帧时间长时一帧分成几步检测，不会穿过一格厚的地面
+const MAX_STEP_DISTANCE: f32 = 0.5;
+
+/// 正在下落的沙子或沙砾，位置为方块中心。落地后重新写回区块
+#[derive(Component)]
+pub struct FallingBlock {
+    pub block: BlockId,
+    velocity: f32,
+    age: f32,
+}
+
+/// 地上的掉落物，玩家靠近时拾取
+#[derive(Component)]
+pub struct DroppedItem {
+    pub stack: ItemStack,
+    age: f32,
+}
+
+/// 等待检查是否悬空的方块位置，由方块变化和区块加载收集
+#[derive(Resource, Default)]
+struct GravityChecks(Vec<IVec3>);
+
+pub struct FallingBlockPlugin;
+
+impl Plugin for FallingBlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GravityChecks>()
+           .add_systems(Update, collect_gravity_checks
+               .after(ChunkLoaderSet)
+               .in_set(BlockUpdateSet)
+               .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Loading))))
+           .add_systems(Update, (
+               start_falling_blocks,
+               update_falling_blocks,
+               expire_dropped_items,
+           ).chain().after(collect_gravity_checks).in_set(BlockUpdateSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 方块变化时检查它本身（刚放下的沙子）和上方的方块（失去支撑）；
+/// 新加载的区块检查其中所有会下落的方块，以及上方区块底层的方块
+fn collect_gravity_checks(
+    mut block_changes: EventReader<BlockChangedEvent>,
+    mut loaded: EventReader<ChunkLoadedEvent>,
+    chunks: Query<(&Chunk, Option<&LodLevel>)>,
+    chunk_storage: Res<ChunkStorage>,
+    mut checks: ResMut<GravityChecks>,
+) {
+    for event in block_changes.read() {
+        checks.0.extend([event.pos, event.pos + IVec3::Y]);
+    }
+
+    for event in loaded.read() {
+        let full_chunk = |coord: IVec3| {
+            chunk_storage
+                .get(&coord)
+                .and_then(|entity| chunks.get(entity).ok())
+                .filter(|(_, lod)| lod.map_or(true, |lod| lod.0 == 0))
+                .map(|(chunk, _)| chunk)
+        };
+        if let Some(chunk) = full_chunk(event.coord) {
+            for (i, &block) in chunk.blocks.iter().enumerate() {
+                if BlockId::from_u8(block).has_gravity() {
+                    // 下标顺序为 (y*32 + z)*32 + x
+                    let local = IVec3::new((i % 32) as i32, (i / 1024) as i32, (i / 32 % 32) as i32);
+                    checks.0.push(chunk.coord * 32 + local);
+                }
+            }
+        }
+        if let Some(above) = full_chunk(event.coord + IVec3::Y) {
+            for x in 0..32 {
+                for z in 0..32 {
+                    if above.get_block(x, 0, z).has_gravity() {
+                        checks.0.push(above.coord * 32 + IVec3::new(x as i32, 0, z as i32));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 下方是空气或流体的沙子和沙砾从区块中移除，变成下落方块实体。
+/// 移除后发送方块变化事件，上面叠着的方块会在下一帧跟着下落
+fn start_falling_blocks(
+    mut commands: Commands,
+    mut checks: ResMut<GravityChecks>,
+    mut world: WorldAccess,
+    mut block_changes: EventWriter<BlockChangedEvent>,
+) {
+    if checks.0.is_empty() {
+        return;
+    }
+    let positions: HashSet<IVec3> = checks.0.drain(..).collect();
+    for pos in positions {
+        let Some(block) = world.block(pos).filter(|block| block.has_gravity()) else { continue };
+        // 下方区块未加载时保持不动，加载后会再检查一次
+        if !world.block(pos - IVec3::Y).is_some_and(|below| !below.is_solid()) {
+            continue;
+        }
+        world.set_block(pos, BlockId::Air);
+        block_changes.send(BlockChangedEvent { pos });
+        commands.spawn((
+            FallingBlock { block, velocity: 0.0, age: 0.0 },
+            SpatialBundle::from_transform(Transform::from_translation(pos.as_vec3() + Vec3::splat(0.5))),
+        ));
+    }
+}
+
+/// 下落方块按重力加速，用玩家的碰撞检测判断落地，每步最多移动 `MAX_STEP_DISTANCE`。
+/// 落地的格子可以放下（空气或流体）时写回区块，否则（例如落在火把上）变成掉落物
+fn update_falling_blocks(
+    mut commands: Commands,
+    mut falling: Query<(Entity, &mut FallingBlock, &mut Transform)>,
+    mut world: WorldAccess,
+    mut block_changes: EventWriter<BlockChangedEvent>,
+    mut sounds: Option<ResMut<Events<SoundEvent>>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    let half = Vec3::new(HALF_EXTENT, 0.5, HALF_EXTENT);
+    for (entity, mut falling_block, mut transform) in falling.iter_mut() {
+        falling_block.age += dt;
+        falling_block.velocity = (falling_block.velocity + GRAVITY * dt).min(TERMINAL_VELOCITY);
+        let distance = falling_block.velocity * dt;
+        let steps = (distance / MAX_STEP_DISTANCE).ceil().max(1.0) as u32;
+        let mut landed = None;
+        for _ in 0..steps {
+            let proposed = transform.translation - Vec3::Y * (distance / steps as f32);
+            if box_collides(proposed - half, proposed + half, &world.view()) {
+                landed = Some(proposed);
+                break;
+            }
+            transform.translation = proposed;
+        }
+        let Some(proposed) = landed else {
+            if falling_block.age > MAX_FALL_SECONDS {
+                commands.entity(entity).despawn_recursive();
+                spawn_dropped_item(&mut commands, ItemStack::new(ItemType::Block(falling_block.block), 1), transform.translation);
+            }
+            continue;
+        };
+
+        // 底面刚好穿进下方方块，落地的格子是底面向上取整的那一格
+        let landing = IVec3::new(
+            transform.translation.x.floor() as i32,
+            (proposed.y - 0.5).ceil() as i32,
+            transform.translation.z.floor() as i32,
+        );
+        commands.entity(entity).despawn_recursive();
+        let occupant = world.block(landing);
+        if occupant.is_some_and(|block| block == BlockId::Air || block.is_fluid()) {
+            world.set_block(landing, falling_block.block);
+            block_changes.send(BlockChangedEvent { pos: landing });
+            if let Some(sounds) = sounds.as_mut() {
+                sounds.send(SoundEvent::block(SoundKind::Place, falling_block.block));
+            }
+        } else {
+            spawn_dropped_item(&mut commands, ItemStack::new(ItemType::Block(falling_block.block), 1), landing.as_vec3() + Vec3::splat(0.5));
+        }
+    }
+}
+
+impl DroppedItem {
+    /// 已经在地上存在的时间（秒），超过 `DROPPED_ITEM_SECONDS` 后消失
+    pub fn age(&self) -> f32 {
+        self.age
+    }
+}
+
+/// 在指定位置生成掉落物，例如关闭物品栏时放不回去的物品
+pub fn spawn_dropped_item(commands: &mut Commands, stack: ItemStack, position: Vec3) {
+    respawn_dropped_item(commands, stack, 0.0, position);
+}
+
+/// 生成已经存在了 `age` 秒的掉落物，区块重新加载时从存档中恢复，消失的计时接着之前的继续
+pub fn respawn_dropped_item(commands: &mut Commands, stack: ItemStack, age: f32, position: Vec3) {
+    commands.spawn((
+        DroppedItem { stack, age },
+        PersistentEntity::at(position),
+        SpatialBundle::from_transform(Transform::from_translation(position)),
+    ));
+}
+
+fn expire_dropped_items(mut commands: Commands, mut items: Query<(Entity, &mut DroppedItem)>, time: Res<Time>) {
+    for (entity, mut item) in items.iter_mut() {
+        item.age += time.delta_seconds();
+        if item.age > DROPPED_ITEM_SECONDS {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::test_support::flat_chunk_app;
+    use std::time::Duration;
+    use bevy::time::TimeUpdateStrategy;
+
+    #[test]
+    fn stacked_sand_cascades_onto_the_ground() {
+        let (mut app, entity) = flat_chunk_app(0, BlockId::Stone);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(20)))
+           .init_resource::<GravityChecks>()
+           .add_event::<ChunkLoadedEvent>()
+           .add_systems(Update, (collect_gravity_checks, start_falling_blocks, update_falling_blocks).chain());
+
+        // 石头地面上方悬空叠着三块沙子，最下面一块下方有一格水
+        let mut chunk = app.world.get_mut::<Chunk>(entity).unwrap();
+        chunk.set_block(5, 1, 5, BlockId::Water);
+        for y in 10..13 {
+            chunk.set_block(5, y, 5, BlockId::Sand);
+        }
+        chunk.compute_solid_blocks();
+        app.world.send_event(ChunkLoadedEvent { coord: IVec3::ZERO, entity });
+
+        for _ in 0..200 {
+            app.update();
+        }
+        let chunk = app.world.get::<Chunk>(entity).unwrap();
+        let column: Vec<BlockId> = (1..13).map(|y| chunk.get_block(5, y, 5)).collect();
+        assert_eq!(&column[..3], &[BlockId::Sand; 3]);
+        assert!(column[3..].iter().all(|block| *block == BlockId::Air));
+        assert_eq!(app.world.query::<&FallingBlock>().iter(&app.world).count(), 0);
+    }
+
+    #[test]
+    fn fast_falling_block_lands_on_a_thin_floor_during_long_frames() {
+        // 一格厚的石板悬在 y = 10，下方是空气；以最大速度下落时一帧移动 10 格
+        let (mut app, entity) = flat_chunk_app(10, BlockId::Stone);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(250)))
+           .add_systems(Update, update_falling_blocks);
+        app.world.spawn((
+            FallingBlock { block: BlockId::Gravel, velocity: TERMINAL_VELOCITY, age: 0.0 },
+            SpatialBundle::from_transform(Transform::from_xyz(5.5, 30.5, 5.5)),
+        ));
+
+        for _ in 0..5 {
+            app.update();
+        }
+        let chunk = app.world.get::<Chunk>(entity).unwrap();
+        assert_eq!(chunk.get_block(5, 11, 5), BlockId::Gravel);
+        assert_eq!(app.world.query::<&FallingBlock>().iter(&app.world).count(), 0);
+    }
+}
@@ -0,0 +1,352 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use bevy::prelude::*;
+use crate::game_state::GameState;
+use crate::world::access::{world_pos_to_chunk_coord, WorldAccess};
+use crate::world::chunk::{BlockId, Chunk, LodLevel};
+use crate::world::chunk_loader::ChunkLoaderSet;
+use crate::world::events::{BlockChangedEvent, ChunkLoadedEvent};
+use crate::world::storage::ChunkStorage;
+use crate::world::BlockUpdateSet;
+
+/// 流体附加数据：低 3 位为流动等级（0 为源头，越大离源头越远），`FALLING` 位表示从上方落下的流体
+pub const FALLING: u8 = 8;
+const LEVEL_MASK: u8 = 7;
+const MAX_LEVEL: u8 = 7;
+/// 固定更新频率，与原版的游戏刻相同
+const TICKS_PER_SECOND: f64 = 20.0;
+/// 每个流体刻最多处理的更新数，大片流体同时流动时剩余的更新顺延到之后的刻
+const UPDATES_PER_TICK: usize = 256;
+
+const HORIZONTAL: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+const NEIGHBORS: [IVec3; 6] = [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z];
+
+/// 流体两次更新之间间隔的刻数：岩浆比水流得慢
+fn tick_delay(fluid: BlockId) -> u64 {
+    if fluid == BlockId::Lava { 30 } else { 5 }
+}
+
+/// 每流过一格等级增加的量：水最远流 7 格，岩浆 3 格
+fn level_step(fluid: BlockId) -> u8 {
+    if fluid == BlockId::Lava { 2 } else { 1 }
+}
+
+/// 流体表面在方块内的高度（0.0 ~ 1.0）：下落的流体和上方也是同种流体时充满整格，否则随等级降低
+pub fn surface_height(metadata: u8, same_fluid_above: bool) -> f32 {
+    if metadata & FALLING != 0 || same_fluid_above {
+        1.0
+    } else {
+        (8 - (metadata & LEVEL_MASK)) as f32 / 9.0
+    }
+}
+
+/// 向水平方向扩散时视为的等级：源头和下落的流体都相当于 0 级
+fn spread_level(metadata: u8) -> u8 {
+    if metadata & FALLING != 0 { 0 } else { metadata & LEVEL_MASK }
+}
+
+/// 等待处理的流体更新，按到期的刻排序；同一位置同时只安排一次
+#[derive(Resource, Default)]
+pub struct FluidUpdates {
+    tick: u64,
+    queue: BinaryHeap<Reverse<(u64, i32, i32, i32)>>,
+    scheduled: HashSet<IVec3>,
+    /// 方块变化的位置，下一个流体刻安排它和周围的流体更新
+    changed: Vec<IVec3>,
+}
+
+impl FluidUpdates {
+    pub fn schedule(&mut self, pos: IVec3, delay: u64) {
+        if self.scheduled.insert(pos) {
+            self.queue.push(Reverse((self.tick + delay, pos.x, pos.y, pos.z)));
+        }
+    }
+
+    /// 取出一个已到期的更新
+    fn pop_due(&mut self) -> Option<IVec3> {
+        let Reverse((due, x, y, z)) = *self.queue.peek()?;
+        if due > self.tick {
+            return None;
+        }
+        self.queue.pop();
+        let pos = IVec3::new(x, y, z);
+        self.scheduled.remove(&pos);
+        Some(pos)
+    }
+}
+
+pub struct FluidPlugin;
+
+impl Plugin for FluidPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FluidUpdates>()
+           .insert_resource(Time::<Fixed>::from_hz(TICKS_PER_SECOND))
+           .add_systems(Update, collect_fluid_updates
+               .after(ChunkLoaderSet)
+               .in_set(BlockUpdateSet)
+               .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Loading))))
+           .add_systems(FixedUpdate, update_fluids.in_set(BlockUpdateSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 收集方块变化和新加载区块中可能流动的流体。事件只保留两帧，固定刻不一定每帧都运行，
+/// 所以在 Update 中读取，交给下一个流体刻处理
+fn collect_fluid_updates(
+    mut block_changes: EventReader<BlockChangedEvent>,
+    mut loaded: EventReader<ChunkLoadedEvent>,
+    chunks: Query<(&Chunk, Option<&LodLevel>)>,
+    chunk_storage: Res<ChunkStorage>,
+    mut updates: ResMut<FluidUpdates>,
+) {
+    updates.changed.extend(block_changes.read().map(|event| event.pos));
+
+    for event in loaded.read() {
+        let full_chunk = |coord: IVec3| {
+            chunk_storage
+                .get(&coord)
+                .and_then(|entity| chunks.get(entity).ok())
+                .filter(|(chunk, lod)| chunk.has_fluid() && lod.map_or(true, |lod| lod.0 == 0))
+                .map(|(chunk, _)| chunk)
+        };
+        if let Some(chunk) = full_chunk(event.coord) {
+            seed_chunk(chunk, &mut updates);
+        }
+        // 相邻区块贴着新区块的那一层流体之前流不过来，现在可以继续流动
+        for offset in NEIGHBORS {
+            if let Some(neighbor) = full_chunk(event.coord + offset) {
+                seed_boundary(neighbor, -offset, &mut updates);
+            }
+        }
+    }
+}
+
+/// 安排区块中可能流动的流体：正在流动的、旁边或下方是空气的，以及区块边界上的
+fn seed_chunk(chunk: &Chunk, updates: &mut FluidUpdates) {
+    let origin = chunk.coord * 32;
+    for y in 0..32 {
+        for z in 0..32 {
+            for x in 0..32 {
+                let block = chunk.get_block(x as u32, y as u32, z as u32);
+                if !block.is_fluid() {
+                    continue;
+                }
+                let local = IVec3::new(x, y, z);
+                let active = chunk.get_metadata(x as u32, y as u32, z as u32) != 0
+                    || HORIZONTAL.iter().chain(&[IVec3::NEG_Y]).any(|offset| {
+                        let n = local + *offset;
+                        n.cmplt(IVec3::ZERO).any()
+                            || n.cmpge(IVec3::splat(32)).any()
+                            || chunk.get_block(n.x as u32, n.y as u32, n.z as u32) == BlockId::Air
+                    });
+                if active {
+                    updates.schedule(origin + local, tick_delay(block));
+                }
+            }
+        }
+    }
+}
+
+/// 安排区块 `side` 方向边界层上的所有流体
+fn seed_boundary(chunk: &Chunk, side: IVec3, updates: &mut FluidUpdates) {
+    let edge = |v: i32| if v > 0 { Some(31) } else if v < 0 { Some(0) } else { None };
+    for a in 0..32 {
+        for b in 0..32 {
+            let local = match (edge(side.x), edge(side.y), edge(side.z)) {
+                (Some(x), _, _) => IVec3::new(x, a, b),
+                (_, Some(y), _) => IVec3::new(a, y, b),
+                (_, _, Some(z)) => IVec3::new(a, b, z),
+                _ => return,
+            };
+            let block = chunk.get_block(local.x as u32, local.y as u32, local.z as u32);
+            if block.is_fluid() {
+                updates.schedule(chunk.coord * 32 + local, tick_delay(block));
+            }
+        }
+    }
+}
+
+/// 流体刻：先安排上一刻方块变化周围的流体，再在预算内处理到期的更新
+fn update_fluids(
+    mut updates: ResMut<FluidUpdates>,
+    mut access: WorldAccess,
+    lods: Query<&LodLevel>,
+    mut block_changes: EventWriter<BlockChangedEvent>,
+) {
+    let is_lod = |entity: Entity| lods.get(entity).is_ok_and(|lod| lod.0 > 0);
+    let mut world = FluidWorld { access: &mut access, is_lod: &is_lod, changed: Vec::new() };
+    updates.tick += 1;
+
+    for pos in std::mem::take(&mut updates.changed) {
+        for target in std::iter::once(pos).chain(NEIGHBORS.iter().map(|offset| pos + *offset)) {
+            if let Some((block, _)) = world.get(target).filter(|(block, _)| block.is_fluid()) {
+                updates.schedule(target, tick_delay(block));
+            }
+        }
+    }
+
+    for _ in 0..UPDATES_PER_TICK {
+        let Some(pos) = updates.pop_due() else { break };
+        update_fluid(&mut world, pos);
+    }
+
+    // 每个变化都会在下一刻安排周围流体的更新，联机服务器也据此广播
+    for pos in world.changed {
+        block_changes.send(BlockChangedEvent { pos });
+    }
+}
+
+/// 流体更新读写的世界视图。只访问已加载的完整分辨率区块，远景 LOD 区块按未加载处理
+struct FluidWorld<'a, 'w, 's> {
+    access: &'a mut WorldAccess<'w, 's>,
+    is_lod: &'a dyn Fn(Entity) -> bool,
+    changed: Vec<IVec3>,
+}
+
+impl FluidWorld<'_, '_, '_> {
+    fn get(&self, pos: IVec3) -> Option<(BlockId, u8)> {
+        let entity = self.access.chunk_storage.get(&world_pos_to_chunk_coord(pos))?;
+        if (self.is_lod)(entity) {
+            return None;
+        }
+        self.access.get_with_metadata(pos)
+    }
+
+    /// 每次更新立即写入，之后的读取要看到这次修改
+    fn set(&mut self, pos: IVec3, block: BlockId, metadata: u8) {
+        if self.access.set_block_with_metadata(pos, block, metadata).is_some() {
+            self.changed.push(pos);
+        }
+    }
+}
+
+fn update_fluid(world: &mut FluidWorld, pos: IVec3) {
+    let Some((fluid, metadata)) = world.get(pos) else { return };
+    if !fluid.is_fluid() {
+        return;
+    }
+
+    // 岩浆碰到水：源头变成黑曜石，流动的岩浆变成石头
+    if fluid == BlockId::Lava
+        && HORIZONTAL.iter().chain(&[IVec3::Y]).any(|offset| world.get(pos + *offset).is_some_and(|(block, _)| block == BlockId::Water))
+    {
+        world.set(pos, if metadata == 0 { BlockId::Obsidian } else { BlockId::Stone }, 0);
+        return;
+    }
+
+    // 非源头的流体按周围重新计算等级，失去来源时逐格消退
+    if metadata != 0 {
+        match flowing_state(world, pos, fluid) {
+            Some(state) if state == metadata => {}
+            Some(state) => return world.set(pos, fluid, state),
+            None => return world.set(pos, BlockId::Air, 0),
+        }
+    }
+
+    // 优先向下流，下方不能流入时才向四周扩散
+    match world.get(pos - IVec3::Y) {
+        None => return,
+        Some((BlockId::Air, _)) => return world.set(pos - IVec3::Y, fluid, FALLING),
+        Some((below, below_metadata)) if below == fluid => {
+            if below_metadata != 0 && below_metadata != FALLING {
+                world.set(pos - IVec3::Y, fluid, FALLING);
+            }
+            return;
+        }
+        Some((BlockId::Water, _)) => {
+            // 岩浆流到水面上，水变成石头
+            return world.set(pos - IVec3::Y, BlockId::Stone, 0);
+        }
+        Some((below, _)) if below.is_fluid() => return,
+        Some(_) => {}
+    }
+
+    let next = spread_level(metadata) + level_step(fluid);
+    if next > MAX_LEVEL {
+        return;
+    }
+    for offset in HORIZONTAL {
+        let target = pos + offset;
+        match world.get(target) {
+            Some((BlockId::Air, _)) => world.set(target, fluid, next),
+            Some((block, level)) if block == fluid && level != 0 && level & FALLING == 0 && level > next => {
+                world.set(target, fluid, next)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 非源头流体应有的状态：上方有同种流体时为下落，否则比最近的水平来源高一级；没有来源时返回 None。
+/// 水的两侧至少有两个源头且下方是实心方块或水源时形成新的水源
+fn flowing_state(world: &FluidWorld, pos: IVec3, fluid: BlockId) -> Option<u8> {
+    if world.get(pos + IVec3::Y).is_some_and(|(block, _)| block == fluid) {
+        return Some(FALLING);
+    }
+    let mut sources = 0;
+    let mut nearest: Option<u8> = None;
+    for offset in HORIZONTAL {
+        let Some((block, metadata)) = world.get(pos + offset) else { continue };
+        if block != fluid {
+            continue;
+        }
+        if metadata == 0 {
+            sources += 1;
+        }
+        let level = spread_level(metadata);
+        nearest = Some(nearest.map_or(level, |n| n.min(level)));
+    }
+    if fluid == BlockId::Water && sources >= 2 {
+        let below = world.get(pos - IVec3::Y);
+        if below.is_some_and(|(block, metadata)| block.is_solid() || (block == BlockId::Water && metadata == 0)) {
+            return Some(0);
+        }
+    }
+    nearest.map(|level| level + level_step(fluid)).filter(|level| *level <= MAX_LEVEL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::test_support::flat_chunk_app;
+
+    /// 单个区块的流体世界：石头地面上按 `setup` 放置流体，运行 `ticks` 个流体刻
+    fn run_flow(setup: impl FnOnce(&mut Chunk), ticks: usize) -> Chunk {
+        let (mut app, entity) = flat_chunk_app(0, BlockId::Stone);
+        app.init_resource::<FluidUpdates>()
+           .add_systems(Update, (collect_fluid_updates_for_test, update_fluids).chain());
+        let mut chunk = app.world.get_mut::<Chunk>(entity).unwrap();
+        setup(&mut chunk);
+        chunk.compute_solid_blocks();
+        app.world.resource_scope(|world, mut updates: Mut<FluidUpdates>| {
+            seed_chunk(world.get::<Chunk>(entity).unwrap(), &mut updates);
+        });
+        for _ in 0..ticks {
+            app.update();
+        }
+        app.world.get::<Chunk>(entity).unwrap().clone()
+    }
+
+    fn collect_fluid_updates_for_test(mut events: EventReader<BlockChangedEvent>, mut updates: ResMut<FluidUpdates>) {
+        updates.changed.extend(events.read().map(|event| event.pos));
+    }
+
+    #[test]
+    fn water_spreads_with_decreasing_level() {
+        let chunk = run_flow(|chunk| chunk.set_block(16, 1, 16, BlockId::Water), 200);
+        assert_eq!((chunk.get_block(16, 1, 16), chunk.get_metadata(16, 1, 16)), (BlockId::Water, 0));
+        assert_eq!((chunk.get_block(19, 1, 16), chunk.get_metadata(19, 1, 16)), (BlockId::Water, 3));
+        assert_eq!((chunk.get_block(16, 1, 9), chunk.get_metadata(16, 1, 9)), (BlockId::Water, 7));
+        assert_eq!(chunk.get_block(16, 1, 8), BlockId::Air);
+        assert_eq!(chunk.get_block(16, 2, 16), BlockId::Air);
+    }
+
+    #[test]
+    fn lava_source_touching_water_becomes_obsidian() {
+        let chunk = run_flow(|chunk| {
+            chunk.set_block(16, 1, 16, BlockId::Lava);
+            chunk.set_block(18, 1, 16, BlockId::Water);
+        }, 60);
+        assert_eq!(chunk.get_block(16, 1, 16), BlockId::Obsidian);
+        assert!(chunk.get_solid_blocks().len() > 32 * 32);
+    }
+}
@@ -1,8 +1,18 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
 use bevy::prelude::*;
 use noise::{NoiseFn, Perlin, Seedable};
+use serde::Deserialize;
+use crate::random::random_unit;
+use crate::world::access::{split_world_pos, world_pos_to_chunk_coord};
 use crate::world::chunk::{Chunk, BlockId};
 use crate::block_registry::BlockRegistry;
 
+/// 每个区块列中出现遗迹的概率
+const RUIN_CHANCE: f32 = 0.08;
+/// 温度和湿度噪声的缩放，生物群系大约几百格宽
+const CLIMATE_SCALE: f64 = 0.002;
+
 /// 世界生成器配置
 #[derive(Resource, Clone)]
 pub struct WorldGeneratorConfig {
@@ -36,6 +46,8 @@ pub struct WorldGenerator {
     terrain_noise: Perlin,
     cave_noise: Perlin,
     ore_noise: Perlin,
+    temperature_noise: Perlin,
+    humidity_noise: Perlin,
     config: WorldGeneratorConfig,
 }
 
@@ -54,12 +66,27 @@ impl WorldGenerator {
             terrain_noise,
             cave_noise,
             ore_noise,
+            temperature_noise: Perlin::new(config.seed + 3),
+            humidity_noise: Perlin::new(config.seed + 4),
             config,
         }
     }
 
-    /// 生成区块地形
-    pub fn generate_chunk(&self, chunk: &mut Chunk, registry: &BlockRegistry) {
+    /// 指定位置的温度和湿度（0.0 ~ 1.0），随位置平滑变化
+    pub fn climate(&self, x: i32, z: i32) -> (f64, f64) {
+        let sample = |noise: &Perlin| (noise.get([x as f64 * CLIMATE_SCALE, z as f64 * CLIMATE_SCALE]) + 1.0) * 0.5;
+        (sample(&self.temperature_noise), sample(&self.humidity_noise))
+    }
+
+    /// 指定方块列所在的生物群系
+    pub fn biome_at(&self, x: i32, z: i32) -> BiomeType {
+        let (temperature, humidity) = self.climate(x, z);
+        BiomeType::from_climate(temperature, humidity)
+    }
+
+    /// 生成区块地形和原点在这个区块中的结构。结构中落在其他区块的方块按世界坐标返回，
+    /// 由 `PendingBlocks` 保存到目标区块加载时再写入
+    pub fn generate_chunk(&self, chunk: &mut Chunk, registry: &BlockRegistry) -> Vec<(IVec3, BlockId)> {
         let chunk_world_x = chunk.coord.x * 32;
         let chunk_world_z = chunk.coord.z * 32;
         let chunk_world_y = chunk.coord.y * 32;
@@ -68,6 +95,7 @@ impl WorldGenerator {
             for z in 0..32 {
                 let world_x = chunk_world_x + x as i32;
                 let world_z = chunk_world_z + z as i32;
+                chunk.set_biome(x as u32, z as u32, self.biome_at(world_x, world_z));
                 
                 // 生成地形高度
                 let height = self.generate_height(world_x, world_z);
@@ -85,6 +113,42 @@ impl WorldGenerator {
                 }
             }
         }
+
+        let mut outside = Vec::new();
+        if let Some(placement) = self.structure_originating_in(chunk.coord) {
+            for (pos, block) in placement.blocks() {
+                let (coord, local) = split_world_pos(pos);
+                if coord == chunk.coord {
+                    chunk.set_block(local.x, local.y, local.z, block);
+                } else {
+                    outside.push((pos, block));
+                }
+            }
+        }
+        outside
+    }
+
+    /// 原点在这一区块列中的结构。只由种子和区块列坐标决定，与区块的生成顺序无关
+    pub fn structure_in_column(&self, column: IVec2) -> Option<StructurePlacement> {
+        let index = ((column.x as u32 as u64) << 32 | column.y as u32 as u64).wrapping_mul(3);
+        let roll = |n: u64| random_unit(self.config.seed as u64, index.wrapping_add(n));
+        if roll(0) >= RUIN_CHANCE {
+            return None;
+        }
+        let x = column.x * 32 + (roll(1) * 32.0) as i32;
+        let z = column.y * 32 + (roll(2) * 32.0) as i32;
+        // 地基放在地表那一层，海平面以下不生成
+        let surface = self.get_surface_height(x, z);
+        if surface <= self.config.sea_level {
+            return None;
+        }
+        Some(StructurePlacement { structure: small_ruin(), origin: IVec3::new(x, surface, z) })
+    }
+
+    /// 原点（地基的角落）位于这个区块中的结构，区块列中的其他区块返回 None
+    pub fn structure_originating_in(&self, coord: IVec3) -> Option<StructurePlacement> {
+        self.structure_in_column(coord.xz())
+            .filter(|placement| world_pos_to_chunk_coord(placement.origin) == coord)
     }
 
     /// 获取指定位置的地面高度（公共方法）
@@ -193,8 +257,72 @@ impl WorldGenerator {
     }
 }
 
+/// 结构：相对原点的方块列表。空气也会写入，用来清空结构内部的地形
+#[derive(Debug, Clone, PartialEq)]
+pub struct Structure {
+    pub name: String,
+    pub blocks: Vec<(IVec3, BlockId)>,
+}
+
+/// 结构模板文件（JSON）。`layers` 从下到上排列，每层的行沿 z 方向、字符沿 x 方向，
+/// 字符按 `palette` 换成方块，调色板中没有的字符（如空格）保留原来的地形
+#[derive(Deserialize)]
+struct StructureTemplate {
+    name: String,
+    palette: HashMap<char, BlockId>,
+    layers: Vec<Vec<String>>,
+}
+
+impl Structure {
+    pub fn from_template(json: &str) -> Result<Self, String> {
+        let template: StructureTemplate = serde_json::from_str(json).map_err(|e| format!("Failed to parse structure template: {}", e))?;
+        let mut blocks = Vec::new();
+        for (y, layer) in template.layers.iter().enumerate() {
+            for (z, row) in layer.iter().enumerate() {
+                for (x, symbol) in row.chars().enumerate() {
+                    if let Some(&block) = template.palette.get(&symbol) {
+                        blocks.push((IVec3::new(x as i32, y as i32, z as i32), block));
+                    }
+                }
+            }
+        }
+        Ok(Self { name: template.name, blocks })
+    }
+}
+
+/// 内置的遗迹模板，第一次使用时解析
+fn small_ruin() -> &'static Structure {
+    static RUIN: OnceLock<Structure> = OnceLock::new();
+    RUIN.get_or_init(|| {
+        Structure::from_template(include_str!("../../structures/small_ruin.json")).expect("built-in structure template is valid")
+    })
+}
+
+/// 内置的橡树模板，树苗长大时使用。树干底部在模板的 (2, 0, 2)
+pub fn oak_tree() -> &'static Structure {
+    static TREE: OnceLock<Structure> = OnceLock::new();
+    TREE.get_or_init(|| {
+        Structure::from_template(include_str!("../../structures/oak_tree.json")).expect("built-in structure template is valid")
+    })
+}
+
+/// 一次结构放置：结构和原点的世界坐标
+#[derive(Clone, Copy, Debug)]
+pub struct StructurePlacement {
+    pub structure: &'static Structure,
+    pub origin: IVec3,
+}
+
+impl StructurePlacement {
+    /// 结构中每个方块的世界坐标
+    pub fn blocks(&self) -> impl Iterator<Item = (IVec3, BlockId)> + '_ {
+        self.structure.blocks.iter().map(|&(offset, block)| (self.origin + offset, block))
+    }
+}
+
 /// 生物群系类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BiomeType {
     Plains,
     Forest,
@@ -204,6 +332,17 @@ pub enum BiomeType {
 }
 
 impl BiomeType {
+    const ALL: [BiomeType; 5] = [BiomeType::Plains, BiomeType::Forest, BiomeType::Desert, BiomeType::Mountains, BiomeType::Ocean];
+
+    /// 区块存档中记录的编号，与 `from_id` 对应
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+
     /// 根据温度和湿度确定生物群系
     pub fn from_climate(temperature: f64, humidity: f64) -> Self {
         match (temperature, humidity) {
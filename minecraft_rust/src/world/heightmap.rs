@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+use crate::world::chunk::Chunk;
+
+/// 没有记录到遮挡方块的列
+pub const NO_HEIGHT: i32 = i32::MIN;
+
+/// 每个方块列中最高的遮挡天空的方块（见 `BlockId::blocks_sky`）的世界高度，按区块列 (x, z) 分块存储。
+/// 只统计已加载的区块：区块加载时抬高，放置和破坏方块时增量更新。
+/// 天空光照以它为准，高于它的格子直接被天空照亮；小地图也用它取每列的顶部方块
+#[derive(Resource, Default)]
+pub struct Heightmap {
+    columns: HashMap<IVec2, Vec<i32>>,
+}
+
+impl Heightmap {
+    /// 世界坐标 (x, z) 列的高度；这一列还没有加载过任何区块时返回 None
+    pub fn height(&self, x: i32, z: i32) -> Option<i32> {
+        let column = self.columns.get(&IVec2::new(x.div_euclid(32), z.div_euclid(32)))?;
+        Some(column[z.rem_euclid(32) as usize * 32 + x.rem_euclid(32) as usize])
+    }
+
+    /// 格子是否直接被天空照亮；未知的列按露天处理
+    pub fn is_exposed(&self, pos: IVec3) -> bool {
+        self.height(pos.x, pos.z).map_or(true, |height| pos.y > height)
+    }
+
+    pub fn set_height(&mut self, x: i32, z: i32, height: i32) {
+        let column = self.columns
+            .entry(IVec2::new(x.div_euclid(32), z.div_euclid(32)))
+            .or_insert_with(|| vec![NO_HEIGHT; 32 * 32]);
+        column[z.rem_euclid(32) as usize * 32 + x.rem_euclid(32) as usize] = height;
+    }
+
+    /// 用新加载的区块抬高各列的高度，返回高度变化的列：(世界 x, 世界 z, 原高度)
+    pub fn record_chunk(&mut self, chunk: &Chunk) -> Vec<(i32, i32, i32)> {
+        let origin = chunk.coord * 32;
+        let mut raised = Vec::new();
+        for z in 0..32 {
+            for x in 0..32 {
+                let (wx, wz) = (origin.x + x as i32, origin.z + z as i32);
+                let old = self.height(wx, wz).unwrap_or(NO_HEIGHT);
+                match chunk.column_top(x, z).map(|y| origin.y + y as i32) {
+                    Some(top) if top > old => {
+                        self.set_height(wx, wz, top);
+                        raised.push((wx, wz, old));
+                    }
+                    // 第一次记录这一列时即使没有遮挡方块也要建立条目，之后按已知的列处理
+                    _ if old == NO_HEIGHT => self.set_height(wx, wz, NO_HEIGHT),
+                    _ => {}
+                }
+            }
+        }
+        raised
+    }
+
+    /// 区块列 (x, z) 的所有区块都已卸载时丢弃这一列的数据
+    pub fn forget_column(&mut self, column: IVec2) {
+        self.columns.remove(&column);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::chunk::BlockId;
+
+    #[test]
+    fn chunks_only_raise_known_columns() {
+        let mut heightmap = Heightmap::default();
+        assert!(heightmap.is_exposed(IVec3::new(5, -100, 5)));
+
+        let mut low = Chunk::new(IVec3::new(0, -1, 0));
+        low.set_block(5, 10, 5, BlockId::Stone);
+        assert_eq!(heightmap.record_chunk(&low), vec![(5, 5, NO_HEIGHT)]);
+        assert_eq!(heightmap.height(5, 5), Some(-22));
+        assert_eq!(heightmap.height(6, 5), Some(NO_HEIGHT));
+
+        // 上方的区块有水面，抬高这一列；更低的方块不改变高度
+        let mut high = Chunk::new(IVec3::ZERO);
+        high.set_block(5, 3, 5, BlockId::Water);
+        high.set_block(6, 0, 5, BlockId::Torch);
+        assert_eq!(heightmap.record_chunk(&high), vec![(5, 5, -22)]);
+        assert!(heightmap.record_chunk(&low).is_empty());
+        assert!(heightmap.is_exposed(IVec3::new(5, 4, 5)));
+        assert!(!heightmap.is_exposed(IVec3::new(5, 3, 5)));
+        assert!(heightmap.is_exposed(IVec3::new(6, 0, 5)));
+    }
+}
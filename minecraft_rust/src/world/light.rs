@@ -0,0 +1,466 @@
+use std::collections::VecDeque;
+use bevy::prelude::*;
+use crate::block_registry::BlockRegistry;
+use crate::game_state::GameState;
+use crate::world::access::split_world_pos;
+use crate::world::chunk::{BlockId, Chunk, LodLevel};
+use crate::world::chunk_loader::ChunkLoaderSet;
+use crate::world::events::{BlockChangedEvent, ChunkUnloadedEvent};
+use crate::world::heightmap::{Heightmap, NO_HEIGHT};
+use crate::world::storage::ChunkStorage;
+use crate::world::BlockUpdateSet;
+
+const NEIGHBORS: [IVec3; 6] = [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z];
+const HORIZONTAL: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+/// 直接被天空照亮的格子的天空光照
+const SKY_LEVEL: u8 = 15;
+
+/// 光照计算：新区块整体补算，方块变化时只重新扩散受影响的范围。网格构建在它之后运行
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LightSet;
+
+/// 两种光照分别扩散，网格取两者中较亮的一个
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Channel {
+    /// 发光方块（火把、岩浆）发出的光
+    Block,
+    /// 从天空照下来的光，高于高度图的格子为 15
+    Sky,
+}
+
+pub struct LightPlugin;
+
+impl Plugin for LightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Heightmap>()
+           .add_systems(Update, (light_new_chunks, update_block_light, forget_unloaded_columns)
+               .chain()
+               .in_set(LightSet)
+               .after(ChunkLoaderSet)
+               .after(BlockUpdateSet)
+               .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Loading))));
+    }
+}
+
+/// 为还没有计算光照的完整分辨率区块补算光照。先用新区块抬高高度图，被新区块遮住的已有格子失去天空直射；
+/// 然后从区块内的发光方块、露天的格子和相邻区块边界上的光照开始扩散。远景 LOD 区块不计算光照，整体按露天处理
+fn light_new_chunks(
+    mut chunks: Query<&mut Chunk>,
+    lods: Query<&LodLevel>,
+    chunk_storage: Res<ChunkStorage>,
+    registry: Res<BlockRegistry>,
+    mut heightmap: ResMut<Heightmap>,
+) {
+    let is_lod = |entity: Entity| lods.get(entity).is_ok_and(|lod| lod.0 > 0);
+    let unlit: Vec<(Entity, IVec3)> = chunks
+        .iter()
+        .filter(|chunk| !chunk.lit)
+        .filter_map(|chunk| Some((chunk_storage.get(&chunk.coord)?, chunk.coord)))
+        .collect();
+    if unlit.is_empty() {
+        return;
+    }
+
+    let mut raised = Vec::new();
+    for &(entity, coord) in &unlit {
+        let Ok(mut chunk) = chunks.get_mut(entity) else { continue };
+        chunk.lit = true;
+        if is_lod(entity) {
+            chunk.fill_sky_light();
+        } else {
+            raised.extend(heightmap.record_chunk(&chunk).into_iter().map(|(x, z, old)| (x, z, old, coord.y * 32 - 1)));
+        }
+    }
+
+    let mut world = LightWorld {
+        chunks: &mut chunks,
+        is_lod: &is_lod,
+        chunk_storage: &chunk_storage,
+        heightmap: &mut heightmap,
+        emission: registry.light_emission(),
+    };
+    let mut queues = [VecDeque::new(), VecDeque::new()];
+    // 新区块自己的格子还没有光照，只需处理它下方原本露天的格子
+    for (x, z, old, below_chunk) in raised {
+        world.relight_column(x, z, below_chunk, old, &mut queues[1]);
+    }
+    for (entity, coord) in unlit {
+        if !is_lod(entity) {
+            world.seed_chunk(coord, &mut queues);
+        }
+    }
+    let [block_queue, sky_queue] = queues;
+    world.propagate(Channel::Block, block_queue);
+    world.propagate(Channel::Sky, sky_queue);
+}
+
+/// 方块变化后的增量更新：先更新这一列的高度图，重新计算露天状态改变的格子，
+/// 再按原来的光照等级向外清除可能来自这里的光，从光源和清除范围边缘重新扩散
+fn update_block_light(
+    mut block_changes: EventReader<BlockChangedEvent>,
+    mut chunks: Query<&mut Chunk>,
+    lods: Query<&LodLevel>,
+    chunk_storage: Res<ChunkStorage>,
+    registry: Res<BlockRegistry>,
+    mut heightmap: ResMut<Heightmap>,
+) {
+    let is_lod = |entity: Entity| lods.get(entity).is_ok_and(|lod| lod.0 > 0);
+    let mut world = LightWorld {
+        chunks: &mut chunks,
+        is_lod: &is_lod,
+        chunk_storage: &chunk_storage,
+        heightmap: &mut heightmap,
+        emission: registry.light_emission(),
+    };
+    let mut block_queue = VecDeque::new();
+    let mut sky_queue = VecDeque::new();
+    for event in block_changes.read() {
+        world.update_height(event.pos, &mut sky_queue);
+        world.relight(Channel::Block, event.pos, &mut block_queue);
+        world.relight(Channel::Sky, event.pos, &mut sky_queue);
+    }
+    world.propagate(Channel::Block, block_queue);
+    world.propagate(Channel::Sky, sky_queue);
+}
+
+/// 区块列的所有区块都卸载后丢弃它的高度图，长时间探索时高度图不会无限增长
+fn forget_unloaded_columns(
+    mut unloaded: EventReader<ChunkUnloadedEvent>,
+    chunk_storage: Res<ChunkStorage>,
+    mut heightmap: ResMut<Heightmap>,
+) {
+    for event in unloaded.read() {
+        let column = IVec2::new(event.coord.x, event.coord.z);
+        if !chunk_storage.chunks.iter().any(|entry| IVec2::new(entry.key().x, entry.key().z) == column) {
+            heightmap.forget_column(column);
+        }
+    }
+}
+
+/// 光照读写的世界视图。只访问已加载且已计算过光照的完整分辨率区块
+struct LightWorld<'a, 'w, 's, 'c> {
+    chunks: &'a mut Query<'w, 's, &'c mut Chunk>,
+    is_lod: &'a dyn Fn(Entity) -> bool,
+    chunk_storage: &'a ChunkStorage,
+    heightmap: &'a mut Heightmap,
+    emission: [u8; BlockId::COUNT],
+}
+
+impl LightWorld<'_, '_, '_, '_> {
+    fn locate(&self, pos: IVec3) -> Option<(Entity, UVec3)> {
+        let (coord, local) = split_world_pos(pos);
+        let entity = self.chunk_storage.get(&coord).filter(|entity| !(self.is_lod)(*entity))?;
+        Some((entity, local))
+    }
+
+    /// 位置处的方块和光照等级；区块未加载或尚未计算光照时返回 None
+    fn get(&self, channel: Channel, pos: IVec3) -> Option<(BlockId, u8)> {
+        let (entity, local) = self.locate(pos)?;
+        let chunk = self.chunks.get(entity).ok().filter(|chunk| chunk.lit)?;
+        let light = match channel {
+            Channel::Block => chunk.get_light(local.x, local.y, local.z),
+            Channel::Sky => chunk.get_sky_light(local.x, local.y, local.z),
+        };
+        Some((chunk.get_block(local.x, local.y, local.z), light))
+    }
+
+    /// 写入光照等级。边界上的光照变化时相邻区块也要重建网格，因为它的面取这一格的光照
+    fn set(&mut self, channel: Channel, pos: IVec3, level: u8) {
+        let Some((entity, local)) = self.locate(pos) else { return };
+        let Ok(mut chunk) = self.chunks.get_mut(entity) else { return };
+        match channel {
+            Channel::Block if chunk.get_light(local.x, local.y, local.z) != level => chunk.set_light(local.x, local.y, local.z, level),
+            Channel::Sky if chunk.get_sky_light(local.x, local.y, local.z) != level => chunk.set_sky_light(local.x, local.y, local.z, level),
+            _ => return,
+        }
+        let coord = chunk.coord;
+        for offset in NEIGHBORS {
+            let n = local.as_ivec3() + offset;
+            if n.cmplt(IVec3::ZERO).any() || n.cmpge(IVec3::splat(32)).any() {
+                if let Some(mut neighbor) = self.chunk_storage.get(&(coord + offset)).and_then(|e| self.chunks.get_mut(e).ok()) {
+                    neighbor.dirty = true;
+                }
+            }
+        }
+    }
+
+    /// 光源在这一格产生的光照：发光方块的发光等级，或露天格子的天空光照
+    fn source(&self, channel: Channel, pos: IVec3, block: BlockId) -> u8 {
+        match channel {
+            Channel::Block => self.emission[block as usize],
+            Channel::Sky if !block.blocks_sky() && self.heightmap.is_exposed(pos) => SKY_LEVEL,
+            Channel::Sky => 0,
+        }
+    }
+
+    /// 区块内的光源，以及相邻区块贴着这个区块的那一层有光照的格子。
+    /// 露天的格子直接设为 15，只有旁边或下方不露天的格子需要继续向外扩散
+    fn seed_chunk(&mut self, coord: IVec3, queues: &mut [VecDeque<IVec3>; 2]) {
+        let Some(entity) = self.chunk_storage.get(&coord) else { return };
+        let origin = coord * 32;
+        // 本区块和四周一圈的列高度，逐格判断露天时不必查高度图
+        let mut heights = [[NO_HEIGHT; 34]; 34];
+        for (dz, row) in heights.iter_mut().enumerate() {
+            for (dx, height) in row.iter_mut().enumerate() {
+                *height = self.heightmap.height(origin.x + dx as i32 - 1, origin.z + dz as i32 - 1).unwrap_or(NO_HEIGHT);
+            }
+        }
+        let exposed = |x: i32, y: i32, z: i32| origin.y + y > heights[(z + 1) as usize][(x + 1) as usize];
+
+        let Ok(mut chunk) = self.chunks.get_mut(entity) else { return };
+        let mut any_light = false;
+        for y in 0..32 {
+            for z in 0..32 {
+                for x in 0..32 {
+                    let block = chunk.get_block(x as u32, y as u32, z as u32);
+                    let pos = origin + IVec3::new(x, y, z);
+                    let emitted = self.emission[block as usize];
+                    if emitted > 0 {
+                        chunk.set_light(x as u32, y as u32, z as u32, emitted);
+                        queues[0].push_back(pos);
+                        any_light = true;
+                    }
+                    if !block.blocks_sky() && exposed(x, y, z) {
+                        chunk.set_sky_light(x as u32, y as u32, z as u32, SKY_LEVEL);
+                        any_light = true;
+                        if !exposed(x, y - 1, z) || HORIZONTAL.iter().any(|o| !exposed(x + o.x, y, z + o.z)) {
+                            queues[1].push_back(pos);
+                        }
+                    }
+                }
+            }
+        }
+        drop(chunk);
+        // 相邻区块的面取这个区块边界格子的光照
+        if any_light {
+            for offset in NEIGHBORS {
+                if let Some(mut neighbor) = self.chunk_storage.get(&(coord + offset)).and_then(|e| self.chunks.get_mut(e).ok()) {
+                    neighbor.dirty = true;
+                }
+            }
+        }
+
+        for offset in NEIGHBORS {
+            let Some(neighbor) = self.locate((coord + offset) * 32)
+                .and_then(|(entity, _)| self.chunks.get(entity).ok())
+                .filter(|neighbor| neighbor.lit)
+            else {
+                continue;
+            };
+            // 相邻区块贴着本区块的那一层，局部坐标为 0 或 31
+            let edge = |v: i32| if v > 0 { 0 } else { 31 };
+            for a in 0..32 {
+                for b in 0..32 {
+                    let local = match offset.to_array() {
+                        [x, 0, 0] => UVec3::new(edge(x), a, b),
+                        [0, y, 0] => UVec3::new(a, edge(y), b),
+                        [_, _, z] => UVec3::new(a, b, edge(z)),
+                    };
+                    let pos = (coord + offset) * 32 + local.as_ivec3();
+                    if neighbor.get_light(local.x, local.y, local.z) > 1 {
+                        queues[0].push_back(pos);
+                    }
+                    if neighbor.get_sky_light(local.x, local.y, local.z) > 1 {
+                        queues[1].push_back(pos);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 方块变化后更新这一列的高度：放在最高处的遮挡方块抬高高度，挖掉最高的遮挡方块时向下找到下一个。
+    /// 露天状态改变的格子重新计算天空光照
+    fn update_height(&mut self, pos: IVec3, sky_queue: &mut VecDeque<IVec3>) {
+        let Some((block, _)) = self.get(Channel::Sky, pos) else { return };
+        let Some(old) = self.heightmap.height(pos.x, pos.z) else { return };
+        let new = if block.blocks_sky() && pos.y > old {
+            pos.y
+        } else if !block.blocks_sky() && pos.y == old {
+            // 向下穿过已加载的区块；到达未加载的区块时以它的上边界为准
+            let mut y = pos.y - 1;
+            loop {
+                match self.get(Channel::Sky, IVec3::new(pos.x, y, pos.z)) {
+                    Some((below, _)) if below.blocks_sky() => break y,
+                    Some(_) => y -= 1,
+                    None => break NO_HEIGHT,
+                }
+            }
+        } else {
+            return;
+        };
+        self.heightmap.set_height(pos.x, pos.z, new);
+        self.relight_column(pos.x, pos.z, new.max(old), new.min(old), sky_queue);
+    }
+
+    /// 高度变化后，列中 `low` 以上到 `high` 的格子露天状态改变，逐格重新计算天空光照。
+    /// 从高处向下，遇到未加载的区块为止；`low` 为 NO_HEIGHT 时一直延伸到已加载区块的底部
+    fn relight_column(&mut self, x: i32, z: i32, high: i32, low: i32, sky_queue: &mut VecDeque<IVec3>) {
+        let mut y = high;
+        while y > low {
+            let pos = IVec3::new(x, y, z);
+            if self.get(Channel::Sky, pos).is_none() {
+                break;
+            }
+            self.relight(Channel::Sky, pos, sky_queue);
+            y -= 1;
+        }
+    }
+
+    /// 重新计算一格的光照：清除原来的光照（以及由它照亮的周围格子），
+    /// 然后把光源和清除范围边缘仍有光照的格子放入扩散队列
+    fn relight(&mut self, channel: Channel, pos: IVec3, queue: &mut VecDeque<IVec3>) {
+        let Some((block, old_light)) = self.get(channel, pos) else { return };
+
+        let mut removal = VecDeque::new();
+        if old_light > 0 {
+            self.set(channel, pos, 0);
+            removal.push_back((pos, old_light));
+        }
+        while let Some((p, level)) = removal.pop_front() {
+            for offset in NEIGHBORS {
+                let n = p + offset;
+                let Some((neighbor_block, light)) = self.get(channel, n) else { continue };
+                if light != 0 && light < level {
+                    self.set(channel, n, 0);
+                    removal.push_back((n, light));
+                    // 被清除的光源重新发光
+                    let source = self.source(channel, n, neighbor_block);
+                    if source > 0 {
+                        self.set(channel, n, source);
+                        queue.push_back(n);
+                    }
+                } else if light >= level {
+                    queue.push_back(n);
+                }
+            }
+        }
+
+        let source = self.source(channel, pos, block);
+        if source > 0 {
+            self.set(channel, pos, source);
+            queue.push_back(pos);
+        }
+        // 挖掉方块后，周围的光可以照进这一格
+        if !block.is_opaque() {
+            for offset in NEIGHBORS {
+                if self.get(channel, pos + offset).is_some_and(|(_, light)| light > 1) {
+                    queue.push_back(pos + offset);
+                }
+            }
+        }
+    }
+
+    /// 广度优先扩散：每经过一格光照减一，不透光的方块阻挡光照
+    fn propagate(&mut self, channel: Channel, mut queue: VecDeque<IVec3>) {
+        while let Some(pos) = queue.pop_front() {
+            let Some((_, level)) = self.get(channel, pos) else { continue };
+            if level <= 1 {
+                continue;
+            }
+            for offset in NEIGHBORS {
+                let n = pos + offset;
+                if let Some((block, light)) = self.get(channel, n) {
+                    if !block.is_opaque() && light + 1 < level {
+                        self.set(channel, n, level - 1);
+                        queue.push_back(n);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_registry::ScriptBlockDefinition;
+
+    /// 两个相邻区块：x = 0..32 为实心的地面区块（顶层 y = 31），x = 32..64 为空气
+    fn test_app() -> App {
+        let mut registry = BlockRegistry::new();
+        registry.id_to_blockid.insert("torch".to_string(), BlockId::Torch);
+        registry.definitions.insert("torch".to_string(), ScriptBlockDefinition { light_level: 14, ..default() });
+
+        let mut app = App::new();
+        app.insert_resource(ChunkStorage::new())
+           .insert_resource(registry)
+           .init_resource::<Heightmap>()
+           .add_event::<BlockChangedEvent>()
+           .add_event::<ChunkUnloadedEvent>()
+           .add_systems(Update, (light_new_chunks, update_block_light).chain());
+        for coord in [IVec3::ZERO, IVec3::X] {
+            let mut chunk = Chunk::new(coord);
+            if coord == IVec3::ZERO {
+                chunk.blocks.fill(BlockId::Stone as u8);
+            }
+            chunk.compute_solid_blocks();
+            let entity = app.world.spawn(chunk).id();
+            app.world.resource::<ChunkStorage>().insert(coord, entity);
+        }
+        app.update();
+        app
+    }
+
+    fn light_at(app: &App, pos: IVec3) -> (u8, u8) {
+        let (coord, local) = split_world_pos(pos);
+        let entity = app.world.resource::<ChunkStorage>().get(&coord).unwrap();
+        let chunk = app.world.get::<Chunk>(entity).unwrap();
+        (chunk.get_light(local.x, local.y, local.z), chunk.get_sky_light(local.x, local.y, local.z))
+    }
+
+    fn set_block(app: &mut App, pos: IVec3, block: BlockId) {
+        let (coord, local) = split_world_pos(pos);
+        let entity = app.world.resource::<ChunkStorage>().get(&coord).unwrap();
+        app.world.get_mut::<Chunk>(entity).unwrap().set_block(local.x, local.y, local.z, block);
+        app.world.send_event(BlockChangedEvent { pos });
+        app.update();
+    }
+
+    #[test]
+    fn torch_light_crosses_chunks_and_is_removed_incrementally() {
+        let mut app = test_app();
+        // 在地面区块里挖一条贯穿到空气区块的隧道，隧道口露天的光照进隧道
+        for x in 20..32 {
+            set_block(&mut app, IVec3::new(x, 10, 10), BlockId::Air);
+        }
+        assert_eq!(light_at(&app, IVec3::new(32, 10, 10)), (0, 15));
+        assert_eq!(light_at(&app, IVec3::new(29, 10, 10)), (0, 12));
+        assert_eq!(light_at(&app, IVec3::new(20, 10, 10)), (0, 3));
+
+        let torch = IVec3::new(22, 10, 10);
+        set_block(&mut app, torch, BlockId::Torch);
+        assert_eq!(light_at(&app, torch), (14, 5));
+        assert_eq!(light_at(&app, torch + IVec3::new(3, 0, 0)), (11, 8));
+        assert_eq!(light_at(&app, torch - IVec3::X), (13, 4));
+
+        // 封住隧道口后天空光照消失，火把移走后完全黑暗
+        set_block(&mut app, IVec3::new(31, 10, 10), BlockId::Stone);
+        assert_eq!(light_at(&app, torch + IVec3::new(3, 0, 0)).1, 0);
+        set_block(&mut app, torch, BlockId::Air);
+        assert_eq!(light_at(&app, torch), (0, 0));
+        assert_eq!(light_at(&app, IVec3::new(30, 10, 10)), (0, 0));
+    }
+
+    #[test]
+    fn skylight_follows_the_heightmap() {
+        let mut app = test_app();
+        assert_eq!(light_at(&app, IVec3::new(40, 0, 5)), (0, 15));
+        assert_eq!(light_at(&app, IVec3::new(5, 31, 5)), (0, 0));
+
+        // 空气区块里搭一个屋顶，屋顶下的格子不再露天，光照从屋檐外扩散进来
+        for x in 36..45 {
+            for z in 0..9 {
+                set_block(&mut app, IVec3::new(x, 20, z), BlockId::Stone);
+            }
+        }
+        assert_eq!(app.world.resource::<Heightmap>().height(40, 4), Some(20));
+        assert_eq!(light_at(&app, IVec3::new(40, 19, 4)), (0, 10));
+        assert_eq!(light_at(&app, IVec3::new(40, 5, 8)), (0, 14));
+
+        // 挖掉屋顶的一块，下面这一列重新露天
+        set_block(&mut app, IVec3::new(40, 20, 4), BlockId::Air);
+        assert_eq!(app.world.resource::<Heightmap>().height(40, 4), Some(NO_HEIGHT));
+        assert_eq!(light_at(&app, IVec3::new(40, 19, 4)), (0, 15));
+        assert_eq!(light_at(&app, IVec3::new(41, 19, 4)), (0, 14));
+    }
+}
@@ -1,14 +1,29 @@
 use bevy::prelude::*;
-use self::chunk::{Chunk, BlockId};
+use self::chunk::BlockId;
 use self::storage::ChunkStorage;
 use self::generator::{WorldGenerator, WorldGeneratorConfig};
-use crate::block_registry::BlockRegistry;
-use crate::game_state::GameState;
+use self::events::{BlockChangedEvent, ChunkLoadedEvent, ChunkUnloadedEvent, ChunksModified};
+use crate::game_state::{GameState, WorldState};
 
 pub mod chunk;
 pub mod storage;
+pub mod access;
 pub mod generator;
 pub mod chunk_loader;
+pub mod chunk_stats;
+pub mod events;
+pub mod persistence;
+pub mod pending_blocks;
+pub mod fluid;
+pub mod falling_block;
+pub mod light;
+pub mod heightmap;
+pub mod block_entity;
+pub mod entity_persistence;
+pub mod block_update;
+pub mod sapling;
+#[cfg(test)]
+pub mod test_support;
 
 pub struct WorldPlugin;
 
@@ -16,8 +31,20 @@ impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(ChunkStorage::new())
            .insert_resource(WorldGeneratorConfig::default())
+           .add_event::<ChunkLoadedEvent>()
+           .add_event::<ChunkUnloadedEvent>()
+           .add_event::<BlockChangedEvent>()
+           .add_event::<ChunksModified>()
            .add_plugins(chunk_loader::ChunkLoaderPlugin)
-           .add_systems(OnEnter(GameState::InGame), setup_world);
+           .add_plugins(fluid::FluidPlugin)
+           .add_plugins(falling_block::FallingBlockPlugin)
+           .add_plugins(light::LightPlugin)
+           .add_plugins(block_entity::BlockEntityPlugin)
+           .add_plugins(block_update::BlockUpdatePlugin)
+           .add_plugins(sapling::SaplingPlugin)
+           .add_systems(Update, events::mark_neighbors_dirty_on_chunk_events
+               .after(chunk_loader::ChunkLoaderSet)
+               .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Loading))));
     }
 }
 
@@ -32,52 +59,44 @@ impl Default for WorldConfig {
     }
 }
 
-#[derive(Resource, Default)]
-pub struct WorldState {
-    pub chunks_loaded: usize,
-}
+/// 方块更新系统（流体流动、方块下落、相邻方块变化、随机刻）的集合，由 `BlockChangedEvent` 和 `BlockUpdateQueue` 驱动。
+/// 联机客户端不运行这些系统，结果由服务器同步
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockUpdateSet;
 
-fn setup_world(
-    mut commands: Commands, 
-    chunk_storage: Res<ChunkStorage>,
-    registry: Res<BlockRegistry>,
-    generator_config: Res<WorldGeneratorConfig>
-) {
-    // 创建世界生成器
-    let generator = WorldGenerator::new(generator_config.clone());
-    
-    // Generate a 5x5 area of chunks for better terrain visibility
-    for x in -2..=2 {
-        for z in -2..=2 {
-            for y in 0..=2 { // 生成多层区块以展示地形高度变化
-                let chunk_pos = IVec3::new(x, y, z);
-                let chunk_world_pos = Vec3::new(
-                    x as f32 * 32.0,
-                    y as f32 * 32.0,
-                    z as f32 * 32.0,
-                );
+/// 玩家出生位置（世界坐标），进入加载状态时计算，出生区域预加载和玩家生成都以它为准
+#[derive(Resource, Clone, Copy)]
+pub struct SpawnPoint(pub Vec3);
 
-                // Create chunk entity
-                let mut chunk = Chunk::new(chunk_pos);
-                
-                // Generate terrain for this chunk using the new generator
-                generator.generate_chunk(&mut chunk, &registry);
-                chunk.compute_solid_blocks();
-                
-                // Spawn chunk entity
-                let chunk_entity = commands
-                    .spawn((
-                        chunk,
-                        SpatialBundle {
-                            transform: Transform::from_translation(chunk_world_pos),
-                            ..default()
-                        },
-                    ))
-                    .id();
+/// 世界出生点上方 3 格的位置。新世界第一次进入时计算出生点并写入世界状态，之后不再重新计算
+pub fn world_spawn_position(world_state: &mut WorldState, generator_config: &WorldGeneratorConfig) -> Vec3 {
+    let world_spawn = *world_state.spawn_point.get_or_insert_with(|| {
+        let generator = WorldGenerator::new(generator_config.clone());
+        let (spawn_x, surface_height, spawn_z) = find_safe_spawn_point(&generator);
+        IVec3::new(spawn_x, surface_height, spawn_z)
+    });
+    world_spawn.as_vec3() + Vec3::Y * 3.0
+}
 
-                // Store chunk in storage
-                chunk_storage.insert(chunk_pos, chunk_entity);
+/// 在原点附近寻找一个不在水下的地表位置，返回 (x, 地表高度, z)
+pub fn find_safe_spawn_point(generator: &WorldGenerator) -> (i32, i32, i32) {
+    // 尝试在原点附近找到一个安全的出生点
+    for radius in 0..10 {
+        for x in -radius..=radius {
+            for z in -radius..=radius {
+                // 只检查圆形边界上的点
+                if x*x + z*z == radius*radius || radius == 0 {
+                    let surface_height = generator.get_surface_height(x, z);
+                    
+                    // 确保出生点不在水下（假设海平面是64）
+                    if surface_height >= 64 {
+                        return (x, surface_height, z);
+                    }
+                }
             }
         }
     }
-}
\ No newline at end of file
+    
+    // 如果找不到合适的点，就使用原点
+    (0, generator.get_surface_height(0, 0), 0)
+}
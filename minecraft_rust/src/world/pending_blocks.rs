@@ -0,0 +1,173 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game_state::WorldManager;
+use crate::world::access::{world_pos_to_chunk_coord, BlockEditBatch, WorldAccess};
+use crate::world::chunk::{BlockId, LodLevel};
+
+/// 等待写入的结构方块，与区块目录放在同一个存档目录中
+const PENDING_BLOCKS_FILE: &str = "pending_blocks.bin";
+
+/// 结构中落在其他区块的方块，按目标区块坐标保存，目标区块加载（生成或从存档读取）后写入并移除。
+/// 写入经过 `WorldAccess`，区块因此标记为已修改并随区块存档保存，之后重新加载不会再写一次
+#[derive(Default, Serialize, Deserialize)]
+pub struct PendingBlocks {
+    chunks: HashMap<IVec3, Vec<(IVec3, BlockId)>>,
+    /// 已经记录过溢出方块的结构原点区块。没有修改过的区块卸载后会重新生成，
+    /// 不能再记录一次，否则玩家在目标区块中拆掉的结构方块会重新出现
+    placed: HashSet<IVec3>,
+}
+
+impl PendingBlocks {
+    /// 记录 `origin` 区块生成的结构中落在其他区块的方块，每个区块只记录一次，返回是否记录
+    pub fn record(&mut self, origin: IVec3, blocks: Vec<(IVec3, BlockId)>) -> bool {
+        if blocks.is_empty() || !self.placed.insert(origin) {
+            return false;
+        }
+        for (pos, block) in blocks {
+            self.chunks.entry(world_pos_to_chunk_coord(pos)).or_default().push((pos, block));
+        }
+        true
+    }
+
+    /// 取出等待写入这个区块的方块（世界坐标）
+    pub fn take(&mut self, coord: IVec3) -> Option<Vec<(IVec3, BlockId)>> {
+        self.chunks.remove(&coord)
+    }
+
+    /// 有等待写入的方块的区块
+    pub fn coords(&self) -> impl Iterator<Item = IVec3> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn path(world_dir: &Path) -> PathBuf {
+        world_dir.join(PENDING_BLOCKS_FILE)
+    }
+
+    /// 读取存档中的等待写入方块，新世界或文件损坏时从空开始
+    pub fn load(world_dir: &Path) -> Self {
+        let path = Self::path(world_dir);
+        match fs::read(&path) {
+            Ok(bytes) => bincode::deserialize(&bytes).unwrap_or_else(|e| {
+                warn!("Failed to parse pending structure blocks {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        bincode::serialize(self).map_err(|e| format!("Failed to serialize pending structure blocks: {}", e))
+    }
+}
+
+/// 生成线程和主线程共享的 `PendingBlocks`：生成线程记录溢出的方块，主线程写入已加载的区块
+#[derive(Resource, Clone, Default)]
+pub struct PendingBlockStore(Arc<Mutex<PendingBlocks>>);
+
+impl PendingBlockStore {
+    pub fn lock(&self) -> MutexGuard<'_, PendingBlocks> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 进入加载状态时读取当前世界的等待写入方块
+pub fn load_pending_blocks(world_manager: Res<WorldManager>, store: Res<PendingBlockStore>) {
+    *store.lock() = PendingBlocks::load(&world_manager.current_world_dir());
+}
+
+/// 把等待写入的方块写入已激活的完整区块，所有区块合成一批修改。
+/// 远景 LOD 区块是降采样的数据，等升级为完整区块后再写入
+pub fn apply_pending_blocks(store: Res<PendingBlockStore>, mut world: WorldAccess, lods: Query<&LodLevel>) {
+    let mut pending = store.lock();
+    if pending.is_empty() {
+        return;
+    }
+    let ready: Vec<IVec3> = pending
+        .coords()
+        .filter(|coord| {
+            world.chunk_storage.get_active(coord).is_some_and(|entity| !lods.get(entity).is_ok_and(|lod| lod.0 > 0))
+        })
+        .collect();
+    let mut batch = BlockEditBatch::new();
+    for coord in ready {
+        for (pos, block) in pending.take(coord).unwrap_or_default() {
+            batch.set(pos, block);
+        }
+    }
+    drop(pending);
+    if !batch.is_empty() {
+        debug!("Placing {} pending structure blocks", batch.len());
+        world.apply(batch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_registry::BlockRegistry;
+    use crate::world::access::split_world_pos;
+    use crate::world::chunk::Chunk;
+    use crate::world::generator::{WorldGenerator, WorldGeneratorConfig};
+
+    /// 按给定顺序生成区块，每生成一个就把等待的方块写入所有已生成的区块（相当于已加载）
+    fn generate_in_order(generator: &WorldGenerator, order: &[IVec3]) -> HashMap<IVec3, Chunk> {
+        let registry = BlockRegistry::default();
+        let mut pending = PendingBlocks::default();
+        let mut chunks = HashMap::new();
+        for &coord in order {
+            let mut chunk = Chunk::new(coord);
+            let outside = generator.generate_chunk(&mut chunk, &registry);
+            pending.record(coord, outside);
+            chunks.insert(coord, chunk);
+            for (coord, chunk) in chunks.iter_mut() {
+                for (pos, block) in pending.take(*coord).unwrap_or_default() {
+                    let (_, local) = split_world_pos(pos);
+                    chunk.set_block(local.x, local.y, local.z, block);
+                }
+            }
+        }
+        chunks
+    }
+
+    #[test]
+    fn structures_are_seamless_regardless_of_generation_order() {
+        let generator = WorldGenerator::new(WorldGeneratorConfig::default());
+        // 找一个跨越区块边界的遗迹
+        let placement = (-200..200)
+            .flat_map(|x| (-4..4).map(move |z| IVec2::new(x, z)))
+            .filter_map(|column| generator.structure_in_column(column))
+            .find(|placement| {
+                let origin = world_pos_to_chunk_coord(placement.origin);
+                placement.blocks().any(|(pos, _)| world_pos_to_chunk_coord(pos) != origin)
+            })
+            .expect("a ruin crossing a chunk boundary");
+        let mut order: Vec<IVec3> = placement.blocks().map(|(pos, _)| world_pos_to_chunk_coord(pos)).collect();
+        order.sort_by_key(|coord| coord.to_array());
+        order.dedup();
+        assert!(order.len() > 1);
+
+        let forward = generate_in_order(&generator, &order);
+        order.reverse();
+        let reverse = generate_in_order(&generator, &order);
+        for (pos, block) in placement.blocks() {
+            let (coord, local) = split_world_pos(pos);
+            assert_eq!(forward[&coord].get_block(local.x, local.y, local.z), block);
+            assert_eq!(reverse[&coord].get_block(local.x, local.y, local.z), block);
+        }
+
+        // 原点区块重新生成（没有修改过的区块卸载后再加载）不会再次记录
+        let mut pending = PendingBlocks::default();
+        let origin = world_pos_to_chunk_coord(placement.origin);
+        let outside = generator.generate_chunk(&mut Chunk::new(origin), &BlockRegistry::default());
+        assert!(pending.record(origin, outside.clone()));
+        assert!(!pending.record(origin, outside));
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::world::chunk::{BlockEntityChunk, Chunk, EntityChunk, LegacyChunk, MetadataChunk};
+
+/// 存档目录下保存区块文件的子目录
+const CHUNKS_DIR: &str = "chunks";
+/// 区块文件的格式版本：1 没有附加数据（`LegacyChunk`），2 没有方块实体（`MetadataChunk`），
+/// 3 的附加数据总是完整的数组，4 没有持久实体（`BlockEntityChunk`，附加数据全为 0 时为空数组），
+/// 5 没有生物群系（`EntityChunk`），6 为当前格式
+pub const CHUNK_FORMAT_VERSION: u32 = 6;
+
+/// 完整附加数据数组的长度，每个方块 4 位
+const FULL_METADATA_LEN: usize = Chunk::COUNT / 2;
+
+pub fn chunk_path(world_dir: &Path, coord: IVec3) -> PathBuf {
+    world_dir.join(CHUNKS_DIR).join(format!("{}_{}_{}.bin", coord.x, coord.y, coord.z))
+}
+
+/// 读取已保存的区块；文件不存在或损坏时返回 None，由生成器重新生成
+pub fn load_chunk(world_dir: &Path, coord: IVec3) -> Option<Chunk> {
+    read_chunk(world_dir, coord).unwrap_or_else(|e| {
+        warn!("{}", e);
+        None
+    })
+}
+
+/// 读取已保存的区块，文件不存在时返回 None，文件损坏或与坐标不符时返回错误
+pub fn read_chunk(world_dir: &Path, coord: IVec3) -> Result<Option<Chunk>, String> {
+    let path = chunk_path(world_dir, coord);
+    let Ok(bytes) = fs::read(&path) else { return Ok(None) };
+    match deserialize_chunk(&bytes) {
+        Ok(mut chunk) if chunk.coord == coord && chunk.blocks.len() == Chunk::COUNT && matches!(chunk.metadata.len(), 0 | FULL_METADATA_LEN) => {
+            chunk.compact_metadata();
+            chunk.compute_solid_blocks();
+            chunk.dirty = true;
+            Ok(Some(chunk))
+        }
+        Ok(_) => Err(format!("Saved chunk {:?} has mismatched data", path)),
+        Err(e) => Err(format!("Failed to read saved chunk {:?}: {}", path, e)),
+    }
+}
+
+/// 旧存档依次按没有生物群系、没有持久实体、没有方块实体、没有附加数据的格式读取，缺少的部分为空
+fn deserialize_chunk(bytes: &[u8]) -> bincode::Result<Chunk> {
+    bincode::deserialize::<Chunk>(bytes)
+        .or_else(|e| bincode::deserialize::<EntityChunk>(bytes).map(Chunk::from).map_err(|_| e))
+        .or_else(|e| bincode::deserialize::<BlockEntityChunk>(bytes).map(Chunk::from).map_err(|_| e))
+        .or_else(|e| bincode::deserialize::<MetadataChunk>(bytes).map(Chunk::from).map_err(|_| e))
+        .or_else(|e| bincode::deserialize::<LegacyChunk>(bytes).map(Chunk::from).map_err(|_| e))
+}
+
+/// 把存档中旧格式的区块文件改写为当前格式，返回改写的文件数。无法识别的文件保留原样，读取时由生成器重新生成
+pub fn upgrade_chunk_files(world_dir: &Path) -> Result<usize, String> {
+    let dir = world_dir.join(CHUNKS_DIR);
+    let Ok(entries) = fs::read_dir(&dir) else { return Ok(0) };
+    let mut upgraded = 0;
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().is_none_or(|ext| ext != "bin") {
+            continue;
+        }
+        let bytes = fs::read(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        if bincode::deserialize::<Chunk>(&bytes).is_ok() {
+            continue;
+        }
+        match deserialize_chunk(&bytes) {
+            Ok(chunk) => {
+                let bytes = bincode::serialize(&chunk).map_err(|e| format!("Failed to serialize chunk {:?}: {}", chunk.coord, e))?;
+                write_atomically(&path, &bytes)?;
+                upgraded += 1;
+            }
+            Err(e) => warn!("Leaving unreadable chunk file {:?} as is: {}", path, e),
+        }
+    }
+    Ok(upgraded)
+}
+
+/// 写入区块文件。先写临时文件再重命名，写到一半退出时不会留下截断的存档
+pub fn save_chunk(world_dir: &Path, chunk: &Chunk) -> Result<(), String> {
+    let path = chunk_path(world_dir, chunk.coord);
+    let bytes = bincode::serialize(chunk).map_err(|e| format!("Failed to serialize chunk {:?}: {}", chunk.coord, e))?;
+    write_atomically(&path, &bytes)
+}
+
+pub fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes).map_err(|e| format!("Failed to write {:?}: {}", tmp, e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::chunk::BlockId;
+    use crate::world::generator::BiomeType;
+
+    #[test]
+    fn saved_chunk_round_trips() {
+        let dir = std::env::temp_dir().join(format!("chunk_persistence_{}", std::process::id()));
+        let mut chunk = Chunk::new(IVec3::new(-2, 1, 5));
+        chunk.set_block(3, 4, 5, BlockId::Stone);
+        chunk.set_biome(3, 5, BiomeType::Desert);
+        chunk.modified = true;
+        save_chunk(&dir, &chunk).unwrap();
+
+        let loaded = load_chunk(&dir, chunk.coord).unwrap();
+        assert_eq!(loaded.get_block(3, 4, 5), BlockId::Stone);
+        assert_eq!((loaded.biome(3, 5), loaded.biome(5, 3)), (Some(BiomeType::Desert), Some(BiomeType::Plains)));
+        assert_eq!(loaded.get_solid_blocks().len(), 1);
+        assert!(!loaded.modified);
+        assert!(load_chunk(&dir, IVec3::ZERO).is_none());
+
+        // 损坏的文件与不存在的文件区分开，读取失败时由生成器重新生成
+        write_atomically(&chunk_path(&dir, IVec3::ZERO), b"not a chunk").unwrap();
+        assert!(read_chunk(&dir, IVec3::ZERO).is_err());
+        assert!(matches!(read_chunk(&dir, IVec3::ONE), Ok(None)));
+        assert!(load_chunk(&dir, IVec3::ZERO).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
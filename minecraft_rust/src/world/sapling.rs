@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use crate::game_state::GameState;
+use crate::localization::LocalizationManager;
+use crate::message_log::MessageLog;
+use crate::world::block_update::{AddBlockUpdateHandler, BlockUpdateContext, BlockUpdates};
+use crate::world::chunk::BlockId;
+use crate::world::generator::{oak_tree, StructurePlacement};
+use crate::world::BlockUpdateSet;
+
+/// 树苗生长需要的最低天空光照
+const MIN_GROWTH_SKY_LIGHT: u8 = 9;
+/// 随机刻抽到有光照的树苗时长成树的几率
+const GROWTH_CHANCE: f32 = 1.0 / 7.0;
+/// 树干底部在橡树模板中的位置
+const TRUNK_OFFSET: IVec3 = IVec3::new(2, 0, 2);
+
+/// 让 `pos` 处的树苗立即长成树（调试命令 /grow），不需要光照和几率，空间不够时仍然不长
+#[derive(Event)]
+pub struct GrowSaplingEvent {
+    pub pos: IVec3,
+}
+
+/// 树苗：种在草方块或泥土上，下方的方块被移走时掉落；有天空光照时在随机刻中按几率
+/// 用结构放置换成一棵橡树，树干和树冠的位置被挡住时不生长
+pub struct SaplingPlugin;
+
+impl Plugin for SaplingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GrowSaplingEvent>()
+           .add_block_update_handler(BlockId::Sapling, break_unplanted_sapling)
+           .add_random_tick_handler(BlockId::Sapling, grow_on_random_tick)
+           .add_systems(Update, force_sapling_growth
+               .in_set(BlockUpdateSet)
+               .run_if(in_state(GameState::InGame))
+               .run_if(on_event::<GrowSaplingEvent>()));
+    }
+}
+
+/// 可以种树苗的方块
+pub fn can_plant_on(block: BlockId) -> bool {
+    matches!(block, BlockId::Grass | BlockId::Dirt)
+}
+
+fn break_unplanted_sapling(context: &mut BlockUpdateContext, pos: IVec3) {
+    if context.world.block(pos - IVec3::Y).is_some_and(|soil| !can_plant_on(soil)) {
+        context.break_block(pos, BlockId::Sapling);
+    }
+}
+
+fn grow_on_random_tick(context: &mut BlockUpdateContext, pos: IVec3) {
+    let lit = context.world.sky_light(pos).is_some_and(|light| light >= MIN_GROWTH_SKY_LIGHT);
+    if lit && context.random() < GROWTH_CHANCE {
+        grow_tree(context, pos);
+    }
+}
+
+/// 把 `pos` 处的树苗换成一棵橡树，返回是否长成。树的方块只能覆盖空气和树叶，
+/// 有其他方块或者所在区块未加载时不生长
+fn grow_tree(context: &mut BlockUpdateContext, pos: IVec3) -> bool {
+    let placement = StructurePlacement { structure: oak_tree(), origin: pos - TRUNK_OFFSET };
+    let obstructed = placement.blocks().any(|(target, _)| match context.world.block(target) {
+        Some(BlockId::Air | BlockId::Leaves) => false,
+        Some(BlockId::Sapling) => target != pos,
+        _ => true,
+    });
+    if obstructed {
+        return false;
+    }
+    for (target, block) in placement.blocks() {
+        context.set_block(target, block);
+    }
+    true
+}
+
+fn force_sapling_growth(
+    mut events: EventReader<GrowSaplingEvent>,
+    mut updates: BlockUpdates,
+    mut message_log: Option<ResMut<MessageLog>>,
+    localization: Option<Res<LocalizationManager>>,
+) {
+    for event in events.read() {
+        let key = updates.run(|context| match context.world.block(event.pos) {
+            Some(BlockId::Sapling) if grow_tree(context, event.pos) => "game.command.grow.grown",
+            Some(BlockId::Sapling) => "game.command.grow.obstructed",
+            _ => "game.command.grow.not_sapling",
+        });
+        info!("Forced sapling growth at {:?}: {}", event.pos, key);
+        if let (Some(message_log), Some(localization)) = (message_log.as_mut(), localization.as_ref()) {
+            message_log.info(localization.get(key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::inventory::ItemType;
+    use crate::world::access::WorldAccess;
+    use crate::world::block_update::BlockUpdatePlugin;
+    use crate::world::chunk::Chunk;
+    use crate::world::falling_block::DroppedItem;
+    use crate::world::test_support::flat_chunk_app;
+
+    #[test]
+    fn sapling_grows_only_with_room_and_drops_without_soil() {
+        let (mut app, entity) = flat_chunk_app(4, BlockId::Dirt);
+        app.add_state::<GameState>()
+           .add_plugins((BlockUpdatePlugin, SaplingPlugin));
+        app.world.insert_resource(State::new(GameState::InGame));
+        let mut chunk = app.world.get_mut::<Chunk>(entity).unwrap();
+        chunk.set_block(8, 5, 8, BlockId::Sapling);
+        chunk.set_block(20, 5, 20, BlockId::Sapling);
+        // 第二棵树苗的树冠位置有一块石头
+        chunk.set_block(21, 8, 20, BlockId::Stone);
+        chunk.compute_solid_blocks();
+
+        app.world.send_event(GrowSaplingEvent { pos: IVec3::new(8, 5, 8) });
+        app.world.send_event(GrowSaplingEvent { pos: IVec3::new(20, 5, 20) });
+        app.update();
+        let chunk = app.world.get::<Chunk>(entity).unwrap();
+        for y in 5..11 {
+            assert_eq!(chunk.get_block(8, y, 8), BlockId::Log, "trunk at y = {}", y);
+        }
+        assert_eq!(chunk.get_block(6, 8, 6), BlockId::Leaves);
+        assert_eq!(chunk.get_block(8, 11, 8), BlockId::Leaves);
+        assert_eq!(chunk.get_block(20, 5, 20), BlockId::Sapling);
+        assert_eq!(chunk.get_block(20, 6, 20), BlockId::Air);
+
+        app.world.run_system_once(|mut world: WorldAccess| {
+            world.set_block(IVec3::new(20, 4, 20), BlockId::Air);
+        });
+        app.update();
+        assert_eq!(app.world.get::<Chunk>(entity).unwrap().get_block(20, 5, 20), BlockId::Air);
+        let drops: Vec<ItemType> = app.world.query::<&DroppedItem>().iter(&app.world).map(|item| item.stack.item_type).collect();
+        assert_eq!(drops, vec![ItemType::Block(BlockId::Sapling)]);
+    }
+}
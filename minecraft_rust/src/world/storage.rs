@@ -1,9 +1,31 @@
 use bevy::prelude::*;
 use dashmap::DashMap;
 
+/// 区块的生命周期：Loading → Active → PendingUnload → Unloading，之后从存储中移除。
+/// 只有 Active 的区块参与网格构建和碰撞，也只有它们能被后台任务引用
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkLifecycle {
+    /// 实体已通过命令生成，延迟命令应用后由加载器切换为 Active
+    Loading,
+    Active,
+    /// 已加入卸载队列，等待卸载任务启动
+    PendingUnload,
+    /// 卸载任务正在保存区块数据，完成且没有引用后销毁实体
+    Unloading,
+}
+
+/// 存储中的一个区块
+#[derive(Clone, Copy, Debug)]
+pub struct ChunkSlot {
+    pub entity: Entity,
+    pub state: ChunkLifecycle,
+    /// 正在使用该区块的后台任务数，不为 0 时区块不会开始卸载，也不会被销毁
+    refs: u32,
+}
+
 #[derive(Resource, Default)]
 pub struct ChunkStorage {
-    pub chunks: DashMap<IVec3, Entity>,
+    pub chunks: DashMap<IVec3, ChunkSlot>,
 }
 
 impl ChunkStorage {
@@ -13,15 +35,87 @@ impl ChunkStorage {
         }
     }
 
-    pub fn insert(&self, coord: IVec3, entity: Entity) { 
-        self.chunks.insert(coord, entity); 
+    /// 记录已经存在的区块实体，直接为 Active。测试中用来搭建世界，游戏中的区块都经过 Loading
+    #[cfg(test)]
+    pub fn insert(&self, coord: IVec3, entity: Entity) {
+        self.chunks.insert(coord, ChunkSlot { entity, state: ChunkLifecycle::Active, refs: 0 });
+    }
+
+    /// 记录刚通过命令生成的区块实体，状态为 Loading
+    pub fn insert_loading(&self, coord: IVec3, entity: Entity) {
+        self.chunks.insert(coord, ChunkSlot { entity, state: ChunkLifecycle::Loading, refs: 0 });
+    }
+
+    /// 坐标处的区块实体，不论处于哪个状态
+    pub fn get(&self, coord: &IVec3) -> Option<Entity> {
+        self.chunks.get(coord).map(|slot| slot.entity)
+    }
+
+    /// 坐标处 Active 的区块实体，网格构建和碰撞只使用这些区块
+    pub fn get_active(&self, coord: &IVec3) -> Option<Entity> {
+        self.chunks.get(coord).filter(|slot| slot.state == ChunkLifecycle::Active).map(|slot| slot.entity)
+    }
+
+    pub fn state(&self, coord: &IVec3) -> Option<ChunkLifecycle> {
+        self.chunks.get(coord).map(|slot| slot.state)
+    }
+
+    /// 坐标处仍是 `entity` 且处于 `from` 状态时切换到 `to`，返回是否切换
+    fn transition(&self, coord: &IVec3, entity: Entity, from: ChunkLifecycle, to: ChunkLifecycle) -> bool {
+        let Some(mut slot) = self.chunks.get_mut(coord) else { return false };
+        if slot.entity != entity || slot.state != from {
+            return false;
+        }
+        slot.state = to;
+        true
+    }
+
+    /// 实体生成后切换为 Active
+    pub fn activate(&self, coord: &IVec3, entity: Entity) -> bool {
+        self.transition(coord, entity, ChunkLifecycle::Loading, ChunkLifecycle::Active)
+    }
+
+    /// 把没有被引用的 Active 区块标记为等待卸载，返回是否可以加入卸载队列
+    pub fn begin_unload(&self, coord: &IVec3, entity: Entity) -> bool {
+        if self.chunks.get(coord).is_none_or(|slot| slot.refs > 0) {
+            return false;
+        }
+        self.transition(coord, entity, ChunkLifecycle::Active, ChunkLifecycle::PendingUnload)
+    }
+
+    /// 卸载任务启动时调用，之后区块数据不再保存
+    pub fn start_unloading(&self, coord: &IVec3, entity: Entity) -> bool {
+        self.transition(coord, entity, ChunkLifecycle::PendingUnload, ChunkLifecycle::Unloading)
+    }
+
+    /// 后台任务开始使用 Active 区块时调用，返回 false 表示区块未加载或正在卸载，不能使用
+    pub fn retain(&self, coord: &IVec3) -> bool {
+        let Some(mut slot) = self.chunks.get_mut(coord) else { return false };
+        if slot.state != ChunkLifecycle::Active {
+            return false;
+        }
+        slot.refs += 1;
+        true
+    }
+
+    /// 与 `retain` 成对调用
+    pub fn release(&self, coord: &IVec3) {
+        if let Some(mut slot) = self.chunks.get_mut(coord) {
+            slot.refs = slot.refs.saturating_sub(1);
+        }
     }
-    
-    pub fn get(&self, coord: &IVec3) -> Option<Entity> { 
-        self.chunks.get(coord).map(|e| *e.value()) 
+
+    /// 区块是否仍被后台任务引用
+    pub fn is_retained(&self, coord: &IVec3) -> bool {
+        self.chunks.get(coord).is_some_and(|slot| slot.refs > 0)
     }
 
     pub fn remove(&self, coord: &IVec3) -> Option<Entity> {
-        self.chunks.remove(coord).map(|(_, entity)| entity)
+        self.chunks.remove(coord).map(|(_, slot)| slot.entity)
+    }
+
+    /// 卸载完成后移除，坐标处已经是另一个实体时保留
+    pub fn remove_unloaded(&self, coord: &IVec3, entity: Entity) -> bool {
+        self.chunks.remove_if(coord, |_, slot| slot.entity == entity).is_some()
     }
-}
\ No newline at end of file
+}
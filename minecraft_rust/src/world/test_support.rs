@@ -0,0 +1,26 @@
+//! 无窗口测试共用的世界搭建工具
+
+use bevy::prelude::*;
+use super::chunk::{BlockId, Chunk};
+use super::events::{BlockChangedEvent, ChunksModified};
+use super::storage::ChunkStorage;
+
+/// 只有原点一个区块的测试应用：`MinimalPlugins`、`ChunkStorage` 和方块变化事件，
+/// 区块在 `floor_y` 层铺满 `block`。返回应用和区块实体，测试可通过 `get_mut::<Chunk>` 继续摆放方块
+pub fn flat_chunk_app(floor_y: u32, block: BlockId) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+       .insert_resource(ChunkStorage::new())
+       .add_event::<BlockChangedEvent>()
+       .add_event::<ChunksModified>();
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    for x in 0..32 {
+        for z in 0..32 {
+            chunk.set_block(x, floor_y, z, block);
+        }
+    }
+    chunk.compute_solid_blocks();
+    let entity = app.world.spawn(chunk).id();
+    app.world.resource::<ChunkStorage>().insert(IVec3::ZERO, entity);
+    (app, entity)
+}
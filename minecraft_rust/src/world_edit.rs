@@ -0,0 +1,423 @@
+use std::collections::{BTreeMap, VecDeque};
+use bevy::prelude::*;
+use crate::controller::{FirstPersonController, LookTarget};
+use crate::game_state::GameState;
+use crate::inventory::ItemType;
+use crate::localization::LocalizationManager;
+use crate::message_log::MessageLog;
+use crate::world::access::{world_pos_to_chunk_coord, BlockEditBatch, WorldAccess, WorldView};
+use crate::world::chunk::BlockId;
+use crate::world::storage::ChunkLifecycle;
+use crate::world::BlockUpdateSet;
+
+/// 每帧最多修改的方块数；超过它的操作按区块分多帧执行，并在消息记录中显示进度
+const BLOCKS_PER_FRAME: usize = 32 * 32 * 32 * 2;
+/// 一次操作（填充、复制、粘贴）最多涉及的方块数
+const MAX_BLOCKS: usize = 4 * 1024 * 1024;
+/// 分帧执行时每完成这个百分比报告一次进度
+const PROGRESS_STEP: usize = 10;
+const POS1_COLOR: Color = Color::rgb(1.0, 0.4, 0.3);
+const POS2_COLOR: Color = Color::rgb(0.3, 0.6, 1.0);
+
+/// 一个方块和它的附加数据，写在世界坐标 `pos`（剪贴板中为相对玩家的偏移）处
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct BlockEntry {
+    pos: IVec3,
+    block: BlockId,
+    metadata: u8,
+}
+
+/// 世界编辑命令，由命令行发出
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum WorldEditCommand {
+    /// 把准星指向的方块设为选区的第一个或第二个角
+    Pos1,
+    Pos2,
+    /// 把选区填成同一种方块
+    Set(BlockId),
+    /// 把选区复制到剪贴板，位置相对玩家所在的方块；较大的选区分多帧复制
+    Copy,
+    /// 以玩家所在的方块为基准粘贴剪贴板
+    Paste,
+    /// 撤销上一次填充或粘贴
+    Undo,
+}
+
+/// 一个区块中要做的事
+enum ChunkTask {
+    /// 写入这些方块
+    Write(Vec<BlockEntry>),
+    /// 把区块中 `min..=max` 范围内的方块读入剪贴板
+    Copy { min: IVec3, max: IVec3 },
+}
+
+impl ChunkTask {
+    fn len(&self) -> usize {
+        match self {
+            ChunkTask::Write(entries) => entries.len(),
+            ChunkTask::Copy { min, max } => volume(*min, *max).unwrap_or(0),
+        }
+    }
+}
+
+/// 操作完成后的结果
+enum JobOutput {
+    /// 修改前的方块，完成后作为撤销记录
+    Undo(Vec<BlockEntry>),
+    /// 撤销本身不记录
+    None,
+    /// 复制到的方块，位置相对 `anchor`
+    Clipboard { anchor: IVec3, entries: Vec<BlockEntry> },
+}
+
+/// 正在执行的操作（填充、粘贴、撤销或复制）：按区块分组，每帧处理若干个区块
+struct EditJob {
+    chunks: VecDeque<(IVec3, ChunkTask)>,
+    output: JobOutput,
+    total: usize,
+    done: usize,
+    /// 执行期间所在区块被卸载而没有处理的方块数，完成时报告
+    skipped: usize,
+    reported: usize,
+}
+
+impl EditJob {
+    fn new(chunks: VecDeque<(IVec3, ChunkTask)>, output: JobOutput) -> Self {
+        let total = chunks.iter().map(|(_, task)| task.len()).sum();
+        Self { chunks, output, total, done: 0, skipped: 0, reported: 0 }
+    }
+
+    /// 写入方块，`record_undo` 时记录修改前的方块
+    fn write(entries: Vec<BlockEntry>, record_undo: bool) -> Self {
+        let capacity = entries.len();
+        let mut chunks: BTreeMap<(i32, i32, i32), Vec<BlockEntry>> = BTreeMap::new();
+        for entry in entries {
+            let coord = world_pos_to_chunk_coord(entry.pos);
+            chunks.entry(coord.into()).or_default().push(entry);
+        }
+        let chunks = chunks.into_iter().map(|(coord, entries)| (coord.into(), ChunkTask::Write(entries))).collect();
+        Self::new(chunks, if record_undo { JobOutput::Undo(Vec::with_capacity(capacity)) } else { JobOutput::None })
+    }
+
+    /// 把 `min..=max` 中的方块复制到剪贴板，位置相对 `anchor`
+    fn copy(min: IVec3, max: IVec3, anchor: IVec3) -> Self {
+        let (min_chunk, max_chunk) = (world_pos_to_chunk_coord(min), world_pos_to_chunk_coord(max));
+        let chunks = positions(min_chunk, max_chunk)
+            .map(|coord| {
+                let origin = coord * 32;
+                (coord, ChunkTask::Copy { min: min.max(origin), max: max.min(origin + IVec3::splat(31)) })
+            })
+            .collect();
+        Self::new(chunks, JobOutput::Clipboard { anchor, entries: Vec::new() })
+    }
+
+    /// 处理区块直到本帧处理了至少 `budget` 个方块或全部完成，返回是否完成
+    fn run(&mut self, world: &mut WorldAccess, budget: usize) -> bool {
+        let mut processed = 0;
+        while processed < budget {
+            let Some((coord, task)) = self.chunks.pop_front() else { break };
+            let count = task.len();
+            processed += count;
+            self.done += count;
+            if !chunk_loaded(&world.view(), coord) {
+                self.skipped += count;
+                continue;
+            }
+            match (task, &mut self.output) {
+                (ChunkTask::Write(entries), output) => {
+                    let mut batch = BlockEditBatch::new();
+                    for entry in &entries {
+                        if let JobOutput::Undo(inverse) = output {
+                            if let Some((block, metadata)) = world.get_with_metadata(entry.pos) {
+                                inverse.push(BlockEntry { pos: entry.pos, block, metadata });
+                            }
+                        }
+                        batch.set_with_metadata(entry.pos, entry.block, entry.metadata);
+                    }
+                    world.apply(batch);
+                }
+                (ChunkTask::Copy { min, max }, JobOutput::Clipboard { anchor, entries }) => {
+                    let view = world.view();
+                    entries.extend(positions(min, max).filter_map(|pos| {
+                        view.get_with_metadata(pos).map(|(block, metadata)| BlockEntry { pos: pos - *anchor, block, metadata })
+                    }));
+                }
+                (ChunkTask::Copy { .. }, _) => {}
+            }
+        }
+        self.chunks.is_empty()
+    }
+}
+
+/// 世界编辑的状态：选区、剪贴板、撤销记录和正在执行的修改
+#[derive(Resource, Default)]
+pub struct WorldEdit {
+    pos1: Option<IVec3>,
+    pos2: Option<IVec3>,
+    clipboard: Vec<BlockEntry>,
+    undo: Option<Vec<BlockEntry>>,
+    job: Option<EditJob>,
+}
+
+impl WorldEdit {
+    /// 选区的最小角和最大角（包含两端）
+    fn selection(&self) -> Option<(IVec3, IVec3)> {
+        let (a, b) = (self.pos1?, self.pos2?);
+        Some((a.min(b), a.max(b)))
+    }
+}
+
+/// 命令行中 `/set` 的方块 id：物品 id（例如 "grass_block"）或 "air"
+pub fn block_from_id(id: &str) -> Option<BlockId> {
+    if id == "air" {
+        return Some(BlockId::Air);
+    }
+    match ItemType::from_id(id)? {
+        ItemType::Block(block) => Some(block),
+        _ => None,
+    }
+}
+
+/// 供建造使用的世界编辑命令：/pos1 /pos2 选区，/set 填充，/copy /paste 复制粘贴，/undo 撤销。
+/// 所有修改都通过 `BlockEditBatch` 按区块写入，较大的操作分多帧完成
+pub struct WorldEditPlugin;
+
+impl Plugin for WorldEditPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WorldEditCommand>()
+           .init_resource::<WorldEdit>()
+           .add_systems(Update, (
+               handle_world_edit_commands.run_if(on_event::<WorldEditCommand>()),
+               run_edit_job.after(handle_world_edit_commands).run_if(|edit: Res<WorldEdit>| edit.job.is_some()),
+           ).in_set(BlockUpdateSet).run_if(in_state(GameState::InGame)))
+           .add_systems(Update, draw_selection.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// 区域内的方块数，超过 `MAX_BLOCKS` 时返回 None
+fn volume(min: IVec3, max: IVec3) -> Option<usize> {
+    let size = (max - min + IVec3::ONE).as_uvec3();
+    let volume = size.x as u64 * size.y as u64 * size.z as u64;
+    (volume <= MAX_BLOCKS as u64).then_some(volume as usize)
+}
+
+/// 区块是否已加载且没有开始卸载，只有这样的区块能够修改和读取
+fn chunk_loaded(world: &WorldView, coord: IVec3) -> bool {
+    world.chunk(coord).is_some() && world.chunk_storage.state(&coord) != Some(ChunkLifecycle::Unloading)
+}
+
+/// 区域涉及的区块是否都已加载
+fn region_loaded(world: &WorldView, min: IVec3, max: IVec3) -> bool {
+    positions(world_pos_to_chunk_coord(min), world_pos_to_chunk_coord(max)).all(|coord| chunk_loaded(world, coord))
+}
+
+fn positions(min: IVec3, max: IVec3) -> impl Iterator<Item = IVec3> {
+    (min.y..=max.y).flat_map(move |y| (min.z..=max.z).flat_map(move |z| (min.x..=max.x).map(move |x| IVec3::new(x, y, z))))
+}
+
+fn handle_world_edit_commands(
+    mut commands: EventReader<WorldEditCommand>,
+    mut edit: ResMut<WorldEdit>,
+    look_target: Res<LookTarget>,
+    player: Query<&Transform, With<FirstPersonController>>,
+    world: WorldAccess,
+    mut message_log: ResMut<MessageLog>,
+    localization: Res<LocalizationManager>,
+) {
+    let anchor = player.get_single().map_or(IVec3::ZERO, |transform| transform.translation.floor().as_ivec3());
+    let view = world.view();
+    for &command in commands.read() {
+        if edit.job.is_some() && !matches!(command, WorldEditCommand::Pos1 | WorldEditCommand::Pos2) {
+            message_log.error(localization.get("game.worldedit.busy"));
+            continue;
+        }
+        let result = match command {
+            WorldEditCommand::Pos1 | WorldEditCommand::Pos2 => match look_target.hit {
+                Some((pos, _)) => {
+                    let key = if command == WorldEditCommand::Pos1 {
+                        edit.pos1 = Some(pos);
+                        "game.worldedit.pos1"
+                    } else {
+                        edit.pos2 = Some(pos);
+                        "game.worldedit.pos2"
+                    };
+                    message_log.info(format!("{}: {} {} {}", localization.get(key), pos.x, pos.y, pos.z));
+                    Ok(())
+                }
+                None => Err("game.worldedit.no_target"),
+            },
+            WorldEditCommand::Set(block) => checked_selection(&edit, &view).map(|(min, max)| {
+                let entries = positions(min, max).map(|pos| BlockEntry { pos, block, metadata: 0 }).collect();
+                start_job(&mut edit, EditJob::write(entries, true), &mut message_log, &localization);
+            }),
+            WorldEditCommand::Copy => checked_selection(&edit, &view).map(|(min, max)| {
+                start_job(&mut edit, EditJob::copy(min, max, anchor), &mut message_log, &localization);
+            }),
+            WorldEditCommand::Paste => {
+                let entries: Vec<BlockEntry> = edit.clipboard.iter().map(|entry| BlockEntry { pos: anchor + entry.pos, ..*entry }).collect();
+                let min = entries.iter().map(|entry| entry.pos).reduce(IVec3::min);
+                let max = entries.iter().map(|entry| entry.pos).reduce(IVec3::max);
+                match min.zip(max) {
+                    None => Err("game.worldedit.empty_clipboard"),
+                    Some((min, max)) if !region_loaded(&view, min, max) => Err("game.worldedit.not_loaded"),
+                    Some(_) => {
+                        start_job(&mut edit, EditJob::write(entries, true), &mut message_log, &localization);
+                        Ok(())
+                    }
+                }
+            }
+            WorldEditCommand::Undo => match edit.undo.take() {
+                Some(entries) => {
+                    start_job(&mut edit, EditJob::write(entries, false), &mut message_log, &localization);
+                    Ok(())
+                }
+                None => Err("game.worldedit.nothing_to_undo"),
+            },
+        };
+        if let Err(key) = result {
+            message_log.error(localization.get(key));
+        }
+    }
+}
+
+/// 完整、不超过大小上限且全部已加载的选区
+fn checked_selection(edit: &WorldEdit, world: &WorldView) -> Result<(IVec3, IVec3), &'static str> {
+    let (min, max) = edit.selection().ok_or("game.worldedit.no_selection")?;
+    volume(min, max).ok_or("game.worldedit.too_large")?;
+    if !region_loaded(world, min, max) {
+        return Err("game.worldedit.not_loaded");
+    }
+    Ok((min, max))
+}
+
+fn start_job(edit: &mut WorldEdit, job: EditJob, message_log: &mut MessageLog, localization: &LocalizationManager) {
+    if job.total > BLOCKS_PER_FRAME {
+        info!("Starting world edit of {} blocks", job.total);
+        message_log.info(format!("{}: {}", localization.get("game.worldedit.started"), job.total));
+    }
+    edit.job = Some(job);
+}
+
+/// 执行正在进行的操作，每帧最多 `BLOCKS_PER_FRAME` 个方块，完成后保存撤销记录或剪贴板。
+/// 执行期间被卸载的区块中的方块无法处理，完成时报告跳过的数量
+fn run_edit_job(
+    mut edit: ResMut<WorldEdit>,
+    mut world: WorldAccess,
+    mut message_log: ResMut<MessageLog>,
+    localization: Res<LocalizationManager>,
+) {
+    let Some(job) = edit.job.as_mut() else { return };
+    let finished = job.run(&mut world, BLOCKS_PER_FRAME);
+    let percent = job.done * 100 / job.total.max(1);
+    if !finished && percent >= job.reported + PROGRESS_STEP {
+        job.reported = percent - percent % PROGRESS_STEP;
+        message_log.info(format!("{}: {}%", localization.get("game.worldedit.progress"), job.reported));
+    }
+    if !finished {
+        return;
+    }
+    let Some(job) = edit.job.take() else { return };
+    let processed = job.done - job.skipped;
+    info!("World edit finished, {} blocks processed, {} skipped", processed, job.skipped);
+    if job.skipped > 0 {
+        message_log.error(format!("{}: {}", localization.get("game.worldedit.skipped"), job.skipped));
+    }
+    match job.output {
+        JobOutput::Undo(inverse) => {
+            edit.undo = Some(inverse);
+            message_log.info(format!("{}: {}", localization.get("game.worldedit.done"), processed));
+        }
+        JobOutput::None => message_log.info(format!("{}: {}", localization.get("game.worldedit.undone"), processed)),
+        JobOutput::Clipboard { entries, .. } => {
+            message_log.info(format!("{}: {}", localization.get("game.worldedit.copied"), entries.len()));
+            edit.clipboard = entries;
+        }
+    }
+}
+
+/// 选区的线框，以及两个角所在的方块
+fn draw_selection(mut gizmos: Gizmos, edit: Res<WorldEdit>) {
+    let block_box = |min: Vec3, max: Vec3| Transform::from_translation((min + max) * 0.5).with_scale(max - min);
+    if let Some((min, max)) = edit.selection() {
+        gizmos.cuboid(block_box(min.as_vec3(), max.as_vec3() + Vec3::ONE), Color::YELLOW);
+    }
+    for (corner, color) in [(edit.pos1, POS1_COLOR), (edit.pos2, POS2_COLOR)] {
+        if let Some(corner) = corner {
+            let corner = corner.as_vec3();
+            gizmos.cuboid(block_box(corner - Vec3::splat(0.01), corner + Vec3::splat(1.01)), color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::SystemState;
+    use crate::world::chunk::Chunk;
+    use crate::world::events::ChunksModified;
+    use crate::world::storage::ChunkStorage;
+
+    #[test]
+    fn fill_runs_chunk_by_chunk_and_undo_restores_blocks_and_metadata() {
+        let mut world = World::new();
+        world.init_resource::<Events<ChunksModified>>();
+        let storage = ChunkStorage::new();
+        for x in 0..2 {
+            let coord = IVec3::new(x, 0, 0);
+            storage.insert(coord, world.spawn(Chunk::new(coord)).id());
+        }
+        world.insert_resource(storage);
+        let mut state = SystemState::<WorldAccess>::new(&mut world);
+        state.get_mut(&mut world).set_block_with_metadata(IVec3::new(31, 1, 1), BlockId::Water, 3);
+
+        // 跨越区块边界的填充：每次至少处理一个区块
+        let (min, max) = (IVec3::new(30, 0, 0), IVec3::new(33, 2, 2));
+        assert_eq!(volume(min, max), Some(36));
+        assert_eq!(volume(IVec3::ZERO, IVec3::splat(1024)), None);
+        let mut job = EditJob::write(positions(min, max).map(|pos| BlockEntry { pos, block: BlockId::Stone, metadata: 0 }).collect(), true);
+        assert_eq!(job.chunks.len(), 2);
+        assert!(!job.run(&mut state.get_mut(&mut world), 1));
+        assert_eq!(job.done, 18);
+        assert!(job.run(&mut state.get_mut(&mut world), 1));
+        let access = state.get_mut(&mut world);
+        assert!(positions(min, max).all(|pos| access.block(pos) == Some(BlockId::Stone)));
+        assert!(!region_loaded(&access.view(), min, IVec3::new(64, 0, 0)));
+
+        let JobOutput::Undo(inverse) = job.output else { panic!("fill did not record undo") };
+        let mut undo = EditJob::write(inverse, false);
+        assert!(undo.run(&mut state.get_mut(&mut world), BLOCKS_PER_FRAME));
+        let access = state.get_mut(&mut world);
+        assert_eq!(access.get_with_metadata(IVec3::new(31, 1, 1)), Some((BlockId::Water, 3)));
+        assert_eq!(access.block(IVec3::new(33, 2, 2)), Some(BlockId::Air));
+        assert!(matches!(undo.output, JobOutput::None));
+    }
+
+    #[test]
+    fn copy_runs_chunk_by_chunk_and_skips_chunks_unloaded_meanwhile() {
+        let mut world = World::new();
+        world.init_resource::<Events<ChunksModified>>();
+        let storage = ChunkStorage::new();
+        for x in 0..3 {
+            let coord = IVec3::new(x, 0, 0);
+            storage.insert(coord, world.spawn(Chunk::new(coord)).id());
+        }
+        world.insert_resource(storage);
+        let mut state = SystemState::<WorldAccess>::new(&mut world);
+        state.get_mut(&mut world).set_block_with_metadata(IVec3::new(33, 0, 0), BlockId::Water, 3);
+
+        // 选区跨越三个区块，每次处理一个区块；第三个区块在复制过程中被卸载
+        let anchor = IVec3::new(32, 0, 0);
+        let mut job = EditJob::copy(IVec3::new(31, 0, 0), IVec3::new(64, 1, 1), anchor);
+        assert_eq!((job.chunks.len(), job.total), (3, 34 * 4));
+        assert!(!job.run(&mut state.get_mut(&mut world), 1));
+        world.resource::<ChunkStorage>().remove(&IVec3::new(2, 0, 0));
+        assert!(!job.run(&mut state.get_mut(&mut world), 1));
+        assert!(job.run(&mut state.get_mut(&mut world), 1));
+        assert_eq!(job.skipped, 4);
+
+        let JobOutput::Clipboard { entries, .. } = job.output else { panic!("copy did not fill the clipboard") };
+        assert_eq!(entries.len(), 33 * 4);
+        assert!(entries.contains(&BlockEntry { pos: IVec3::new(1, 0, 0), block: BlockId::Water, metadata: 3 }));
+        assert!(entries.iter().all(|entry| entry.pos.x >= -1 && entry.pos.x < 32));
+    }
+}
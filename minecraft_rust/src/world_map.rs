@@ -0,0 +1,546 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::tasks::IoTaskPool;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+use crate::controller::FirstPersonController;
+use crate::game_state::{GameState, WorldManager};
+use crate::hud::hud_visible;
+use crate::localization::LocalizationManager;
+use crate::world::access::WorldView;
+use crate::world::chunk::{BlockId, Chunk};
+use crate::world::chunk_loader::ChunkLoaderSet;
+use crate::world::events::{BlockChangedEvent, ChunkLoadedEvent};
+use crate::world::heightmap::{Heightmap, NO_HEIGHT};
+use crate::world::light::LightSet;
+use crate::world::storage::ChunkStorage;
+use crate::world::generator::WorldGeneratorConfig;
+
+/// 每个地图块对应一个区块列（32×32 个方块列）
+const TILE_SIZE: usize = 32;
+/// 小地图边长（像素），一个像素对应一个方块列
+const MINIMAP_SIZE: u32 = 128;
+/// 探索数据的自动保存间隔（秒）
+const SAVE_INTERVAL_SECS: f32 = 60.0;
+const UNEXPLORED: i32 = i32::MIN;
+
+/// 一个区块列的地图数据：每列最高非空气方块的高度和类型
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MapTile {
+    heights: Vec<i32>,
+    blocks: Vec<u8>,
+    #[serde(skip)]
+    pixels: Vec<u8>,  // RGBA，仅在该地图块变化时重新光栅化
+    #[serde(skip)]
+    version: u32,
+}
+
+impl MapTile {
+    fn new() -> Self {
+        Self {
+            heights: vec![UNEXPLORED; TILE_SIZE * TILE_SIZE],
+            blocks: vec![BlockId::Air as u8; TILE_SIZE * TILE_SIZE],
+            pixels: vec![0; TILE_SIZE * TILE_SIZE * 4],
+            version: 0,
+        }
+    }
+
+    fn rasterize(&mut self) {
+        self.pixels.resize(TILE_SIZE * TILE_SIZE * 4, 0);
+        for (i, (&height, &block)) in self.heights.iter().zip(&self.blocks).enumerate() {
+            let color = if height == UNEXPLORED { [0, 0, 0, 0] } else { column_color(block, height) };
+            self.pixels[i * 4..i * 4 + 4].copy_from_slice(&color);
+        }
+        self.version += 1;
+    }
+}
+
+/// 方块类型对应的地图颜色，越高的地方越亮
+fn column_color(block: u8, height: i32) -> [u8; 4] {
+    let base: [f32; 3] = match block {
+        1 => [125.0, 125.0, 125.0],  // 石头
+        2 => [134.0, 96.0, 67.0],    // 泥土
+        3 => [95.0, 159.0, 53.0],    // 草方块
+        4 => [50.0, 50.0, 50.0],     // 基岩
+        5 => [64.0, 100.0, 220.0],   // 水
+        6 => [220.0, 100.0, 20.0],   // 岩浆
+        7 => [30.0, 20.0, 45.0],     // 黑曜石
+        8 => [219.0, 207.0, 163.0],  // 沙子
+        9 => [136.0, 126.0, 126.0],  // 沙砾
+        10 => [255.0, 200.0, 80.0],  // 火把
+        11 => [150.0, 110.0, 60.0],  // 工作台
+        12 => [160.0, 115.0, 50.0],  // 箱子
+        13 => [200.0, 60.0, 40.0],   // TNT
+        14 => [170.0, 30.0, 30.0],   // 床
+        15 => [105.0, 80.0, 50.0],   // 原木
+        16 => [60.0, 120.0, 40.0],   // 树叶
+        17 => [80.0, 140.0, 50.0],   // 树苗
+        _ => [0.0, 0.0, 0.0],
+    };
+    let shade = 0.7 + 0.3 * (height as f32 / 128.0).clamp(0.0, 1.0);
+    [(base[0] * shade) as u8, (base[1] * shade) as u8, (base[2] * shade) as u8, 255]
+}
+
+/// 已探索区域的地图：按区块列 (x, z) 存储的顶部方块采样
+#[derive(Resource, Default)]
+pub struct WorldMap {
+    tiles: HashMap<IVec2, MapTile>,
+    changed: HashSet<IVec2>,  // 等待重新光栅化的地图块
+    generation: u64,  // 每次有地图块重新光栅化时递增
+    unsaved: bool,
+}
+
+impl WorldMap {
+    /// 用新加载的区块更新顶部方块采样：只有比已记录高度更高的方块会覆盖原有数据
+    pub fn record_chunk(&mut self, chunk: &Chunk) {
+        let key = IVec2::new(chunk.coord.x, chunk.coord.z);
+        let tile = self.tiles.entry(key).or_insert_with(MapTile::new);
+        let mut changed = false;
+        for z in 0..TILE_SIZE as u32 {
+            for x in 0..TILE_SIZE as u32 {
+                let Some(y) = (0..Chunk::SIZE.y).rev().find(|&y| chunk.get_block(x, y, z) != BlockId::Air) else { continue };
+                let world_y = chunk.coord.y * 32 + y as i32;
+                let i = z as usize * TILE_SIZE + x as usize;
+                if world_y >= tile.heights[i] {
+                    let block = chunk.get_block(x, y, z) as u8;
+                    changed |= tile.heights[i] != world_y || tile.blocks[i] != block;
+                    tile.heights[i] = world_y;
+                    tile.blocks[i] = block;
+                }
+            }
+        }
+        if changed {
+            self.changed.insert(key);
+            self.unsaved = true;
+        }
+    }
+
+    /// 用方块变化后的列高度覆盖一列的采样，挖掉顶部方块时地图可以变低
+    pub fn record_column(&mut self, x: i32, z: i32, height: i32, block: BlockId) {
+        let key = IVec2::new(x.div_euclid(32), z.div_euclid(32));
+        let tile = self.tiles.entry(key).or_insert_with(MapTile::new);
+        let i = z.rem_euclid(32) as usize * TILE_SIZE + x.rem_euclid(32) as usize;
+        if tile.heights[i] != height || tile.blocks[i] != block as u8 {
+            tile.heights[i] = height;
+            tile.blocks[i] = block as u8;
+            self.changed.insert(key);
+            self.unsaved = true;
+        }
+    }
+
+    /// 只重新光栅化发生变化的地图块
+    pub fn rasterize_changed(&mut self) {
+        if self.changed.is_empty() {
+            return;
+        }
+        for key in self.changed.drain() {
+            if let Some(tile) = self.tiles.get_mut(&key) {
+                tile.rasterize();
+            }
+        }
+        self.generation += 1;
+    }
+
+    /// 世界坐标 (x, z) 处方块列的颜色
+    fn pixel(&self, x: i32, z: i32) -> Option<&[u8]> {
+        let tile = self.tiles.get(&IVec2::new(x.div_euclid(32), z.div_euclid(32)))?;
+        let i = (z.rem_euclid(32) as usize * TILE_SIZE + x.rem_euclid(32) as usize) * 4;
+        tile.pixels.get(i..i + 4)
+    }
+}
+
+/// 存档中的地图数据；种子不一致时丢弃
+#[derive(Serialize, Deserialize)]
+struct SavedWorldMap {
+    seed: u32,
+    tiles: Vec<(IVec2, MapTile)>,
+}
+
+#[derive(Serialize)]
+struct SavedWorldMapRef<'a> {
+    seed: u32,
+    tiles: Vec<(&'a IVec2, &'a MapTile)>,
+}
+
+fn map_file(world_manager: &WorldManager) -> PathBuf {
+    world_manager.current_world_dir().join("map.bin")
+}
+
+/// 小地图纹理
+#[derive(Resource)]
+struct MinimapImage(Handle<Image>);
+
+#[derive(Component)]
+struct MinimapRoot;
+
+/// 全屏地图的显示状态
+#[derive(Resource)]
+struct MapView {
+    open: bool,
+    center: Vec2,  // 视图中心的世界坐标 (x, z)
+    zoom: f32,     // 每个方块列的像素数
+    textures: HashMap<IVec2, (u32, egui::TextureHandle)>,  // 地图块纹理及其对应的版本
+}
+
+impl Default for MapView {
+    fn default() -> Self {
+        Self { open: false, center: Vec2::ZERO, zoom: 2.0, textures: HashMap::new() }
+    }
+}
+
+pub struct WorldMapPlugin;
+
+impl Plugin for WorldMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldMap>()
+           .init_resource::<MapView>()
+           .add_systems(Startup, create_minimap_image)
+           .add_systems(OnEnter(GameState::Loading), load_world_map)
+           .add_systems(Update, (record_loaded_chunks, record_changed_columns, rasterize_world_map)
+               .chain()
+               .after(ChunkLoaderSet)
+               .after(LightSet)
+               .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Loading))))
+           .add_systems(OnExit(GameState::Loading), setup_minimap)
+           .add_systems(Update, (
+               update_minimap_image.after(rasterize_world_map),
+               toggle_map_view,
+               map_view_ui.after(rasterize_world_map).after(toggle_map_view).run_if(hud_visible),
+               autosave_world_map,
+           ).run_if(in_state(GameState::InGame)))
+           .add_systems(OnExit(GameState::InGame), save_world_map);
+    }
+}
+
+fn create_minimap_image(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = Image::new_fill(
+        Extent3d { width: MINIMAP_SIZE, height: MINIMAP_SIZE, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    commands.insert_resource(MinimapImage(images.add(image)));
+}
+
+fn load_world_map(
+    world_manager: Res<WorldManager>,
+    generator_config: Res<WorldGeneratorConfig>,
+    mut map: ResMut<WorldMap>,
+) {
+    let path = map_file(&world_manager);
+    let Ok(bytes) = std::fs::read(&path) else { return };
+    match bincode::deserialize::<SavedWorldMap>(&bytes) {
+        Ok(saved) if saved.seed == generator_config.seed => {
+            *map = WorldMap::default();
+            for (key, tile) in saved.tiles {
+                map.tiles.insert(key, tile);
+                map.changed.insert(key);
+            }
+            info!("Loaded {} explored map tiles from {:?}", map.tiles.len(), path);
+        }
+        Ok(_) => info!("Ignoring map data in {:?} saved for a different seed", path),
+        Err(e) => warn!("Failed to read map data {:?}: {}", path, e),
+    }
+}
+
+fn record_loaded_chunks(
+    mut loaded: EventReader<ChunkLoadedEvent>,
+    chunk_query: Query<&Chunk>,
+    mut map: ResMut<WorldMap>,
+) {
+    for event in loaded.read() {
+        if let Ok(chunk) = chunk_query.get(event.entity) {
+            map.record_chunk(chunk);
+        }
+    }
+}
+
+/// 方块变化的列从高度图取新的顶部方块（光照系统已在本帧更新了高度图）
+fn record_changed_columns(
+    mut block_changes: EventReader<BlockChangedEvent>,
+    heightmap: Res<Heightmap>,
+    chunk_query: Query<&Chunk>,
+    chunk_storage: Res<ChunkStorage>,
+    mut map: ResMut<WorldMap>,
+) {
+    let world = WorldView::new(&chunk_storage, &chunk_query);
+    for event in block_changes.read() {
+        let Some(height) = heightmap.height(event.pos.x, event.pos.z).filter(|&height| height != NO_HEIGHT) else { continue };
+        let Some(block) = world.block(IVec3::new(event.pos.x, height, event.pos.z)) else { continue };
+        map.record_column(event.pos.x, event.pos.z, height, block);
+    }
+}
+
+fn rasterize_world_map(mut map: ResMut<WorldMap>) {
+    map.rasterize_changed();
+}
+
+fn setup_minimap(
+    mut commands: Commands,
+    minimap: Res<MinimapImage>,
+    existing: Query<(), With<MinimapRoot>>,
+    localization: Res<LocalizationManager>,
+) {
+    if !existing.is_empty() {
+        return; // 从暂停返回时已经存在
+    }
+    let size = MINIMAP_SIZE as f32;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    width: Val::Px(size),
+                    height: Val::Px(size),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                border_color: Color::rgba(1.0, 1.0, 1.0, 0.6).into(),
+                ..default()
+            },
+            MinimapRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(ImageBundle {
+                style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), ..default() },
+                image: UiImage::new(minimap.0.clone()),
+                ..default()
+            });
+            // 玩家位置标记（小地图始终以玩家为中心）
+            parent.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(size / 2.0 - 3.0),
+                    top: Val::Px(size / 2.0 - 3.0),
+                    width: Val::Px(4.0),
+                    height: Val::Px(4.0),
+                    ..default()
+                },
+                background_color: Color::RED.into(),
+                ..default()
+            });
+            // 北方（-Z）位于小地图上方
+            parent.spawn(
+                TextBundle::from_section(
+                    localization.get("game.map.north"),
+                    TextStyle { font: default(), font_size: 14.0, color: Color::WHITE },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(size / 2.0 - 6.0),
+                    top: Val::Px(0.0),
+                    ..default()
+                }),
+            );
+        });
+}
+
+/// 玩家移动到新的方块列或地图有变化时，从已光栅化的地图块拼出以玩家为中心的小地图
+fn update_minimap_image(
+    player_query: Query<&Transform, With<FirstPersonController>>,
+    map: Res<WorldMap>,
+    minimap: Res<MinimapImage>,
+    mut images: ResMut<Assets<Image>>,
+    mut last: Local<Option<(IVec2, u64)>>,
+) {
+    let Ok(transform) = player_query.get_single() else { return };
+    let center = IVec2::new(transform.translation.x.floor() as i32, transform.translation.z.floor() as i32);
+    if *last == Some((center, map.generation)) {
+        return;
+    }
+    *last = Some((center, map.generation));
+
+    let Some(image) = images.get_mut(&minimap.0) else { return };
+    let half = MINIMAP_SIZE as i32 / 2;
+    for row in 0..MINIMAP_SIZE as i32 {
+        for col in 0..MINIMAP_SIZE as i32 {
+            let i = (row * MINIMAP_SIZE as i32 + col) as usize * 4;
+            let pixel = map.pixel(center.x - half + col, center.y - half + row).unwrap_or(&[0, 0, 0, 0]);
+            image.data[i..i + 4].copy_from_slice(pixel);
+        }
+    }
+}
+
+fn toggle_map_view(
+    keyboard: Res<Input<KeyCode>>,
+    player_query: Query<&Transform, With<FirstPersonController>>,
+    mut view: ResMut<MapView>,
+) {
+    if keyboard.just_pressed(KeyCode::M) {
+        view.open = !view.open;
+        if let (true, Ok(transform)) = (view.open, player_query.get_single()) {
+            view.center = Vec2::new(transform.translation.x, transform.translation.z);
+        }
+    }
+}
+
+/// 全屏地图：拖动或方向键平移，滚轮缩放
+fn map_view_ui(
+    mut contexts: EguiContexts,
+    mut view: ResMut<MapView>,
+    map: Res<WorldMap>,
+    keyboard: Res<Input<KeyCode>>,
+    player_query: Query<&Transform, With<FirstPersonController>>,
+    localization: Res<LocalizationManager>,
+    time: Res<Time>,
+) {
+    if !view.open {
+        return;
+    }
+    let ctx = contexts.ctx_mut();
+
+    // 方向键平移（每秒移动 400 像素）
+    let pan_speed = 400.0 / view.zoom * time.delta_seconds();
+    if keyboard.pressed(KeyCode::Left) { view.center.x -= pan_speed; }
+    if keyboard.pressed(KeyCode::Right) { view.center.x += pan_speed; }
+    if keyboard.pressed(KeyCode::Up) { view.center.y -= pan_speed; }
+    if keyboard.pressed(KeyCode::Down) { view.center.y += pan_speed; }
+
+    // 更新发生变化的地图块纹理
+    let view = &mut *view;
+    for (key, tile) in &map.tiles {
+        let stale = view.textures.get(key).map_or(true, |(version, _)| *version != tile.version);
+        if stale && !tile.pixels.is_empty() {
+            let image = egui::ColorImage::from_rgba_unmultiplied([TILE_SIZE, TILE_SIZE], &tile.pixels);
+            match view.textures.get_mut(key) {
+                Some((version, texture)) => {
+                    texture.set(image, egui::TextureOptions::NEAREST);
+                    *version = tile.version;
+                }
+                None => {
+                    let texture = ctx.load_texture(format!("map_tile_{}_{}", key.x, key.y), image, egui::TextureOptions::NEAREST);
+                    view.textures.insert(*key, (tile.version, texture));
+                }
+            }
+        }
+    }
+
+    egui::CentralPanel::default()
+        .frame(egui::Frame::none().fill(egui::Color32::from_black_alpha(220)))
+        .show(ctx, |ui| {
+            let rect = ui.max_rect();
+            let response = ui.interact(rect, ui.id().with("map_view"), egui::Sense::drag());
+            let drag = response.drag_delta();
+            view.center -= Vec2::new(drag.x, drag.y) / view.zoom;
+            let scroll = ui.input(|i| i.scroll_delta.y);
+            if scroll != 0.0 {
+                view.zoom = (view.zoom * (1.0 + scroll * 0.002)).clamp(0.25, 16.0);
+            }
+
+            let screen_center = rect.center();
+            let to_screen = |world: Vec2| {
+                let offset = (world - view.center) * view.zoom;
+                screen_center + egui::vec2(offset.x, offset.y)
+            };
+            let painter = ui.painter_at(rect);
+            let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+            for (key, (_, texture)) in &view.textures {
+                let min = to_screen(key.as_vec2() * TILE_SIZE as f32);
+                let tile_rect = egui::Rect::from_min_size(min, egui::Vec2::splat(TILE_SIZE as f32 * view.zoom));
+                if rect.intersects(tile_rect) {
+                    painter.image(texture.id(), tile_rect, uv, egui::Color32::WHITE);
+                }
+            }
+
+            if let Ok(transform) = player_query.get_single() {
+                let player = to_screen(Vec2::new(transform.translation.x, transform.translation.z));
+                painter.circle_filled(player, 4.0, egui::Color32::RED);
+            }
+
+            painter.text(
+                rect.center_top() + egui::vec2(0.0, 12.0),
+                egui::Align2::CENTER_TOP,
+                localization.get("game.map.north"),
+                egui::FontId::proportional(20.0),
+                egui::Color32::WHITE,
+            );
+            painter.text(
+                rect.left_bottom() + egui::vec2(12.0, -12.0),
+                egui::Align2::LEFT_BOTTOM,
+                localization.get("game.map.hint"),
+                egui::FontId::proportional(14.0),
+                egui::Color32::LIGHT_GRAY,
+            );
+        });
+}
+
+fn autosave_world_map(
+    time: Res<Time>,
+    mut since_save: Local<f32>,
+    world_manager: Res<WorldManager>,
+    generator_config: Res<WorldGeneratorConfig>,
+    map: ResMut<WorldMap>,
+) {
+    *since_save += time.delta_seconds();
+    if *since_save >= SAVE_INTERVAL_SECS {
+        *since_save = 0.0;
+        save_world_map(world_manager, generator_config, map);
+    }
+}
+
+/// 在后台线程写入探索数据；离开游戏状态（暂停或退出）时也会保存
+fn save_world_map(
+    world_manager: Res<WorldManager>,
+    generator_config: Res<WorldGeneratorConfig>,
+    mut map: ResMut<WorldMap>,
+) {
+    if !map.unsaved {
+        return;
+    }
+    let saved = SavedWorldMapRef { seed: generator_config.seed, tiles: map.tiles.iter().collect() };
+    let bytes = match bincode::serialize(&saved) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Failed to serialize map data: {}", e);
+            return;
+        }
+    };
+    map.unsaved = false;
+    let path = map_file(&world_manager);
+    IoTaskPool::get()
+        .spawn(async move {
+            let result = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, bytes));
+            if let Err(e) = result {
+                error!("Failed to write map data {:?}: {}", path, e);
+            }
+        })
+        .detach();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_keeps_highest_block_and_rasterizes_changed_tiles() {
+        let mut map = WorldMap::default();
+        let mut low = Chunk::new(IVec3::new(1, 0, 2));
+        low.set_block(3, 10, 4, BlockId::Stone);
+        let mut high = Chunk::new(IVec3::new(1, 1, 2));
+        high.set_block(3, 5, 4, BlockId::Grass);
+
+        map.record_chunk(&high);
+        map.record_chunk(&low);
+        map.rasterize_changed();
+        assert!(map.changed.is_empty());
+
+        let (x, z) = (32 + 3, 64 + 4);
+        assert_eq!(map.pixel(x, z), Some(&column_color(BlockId::Grass as u8, 37)[..]));
+        assert_eq!(map.pixel(x + 1, z), Some(&[0, 0, 0, 0][..]));
+        assert_eq!(map.pixel(0, 0), None);
+
+        // 较低的区块重新加载不会产生变化
+        let generation = map.generation;
+        map.record_chunk(&low);
+        map.rasterize_changed();
+        assert_eq!(map.generation, generation);
+    }
+}